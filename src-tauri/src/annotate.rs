@@ -0,0 +1,295 @@
+//! 图片批注：箭头 / 文字 / 高亮的矢量叠加层，存 annotations 表，
+//! 原图永远不动；需要把反馈发出去时用导出命令把批注压平到副本上。
+//!
+//! 坐标统一用百分比（0-100，同人脸框的约定），与显示分辨率无关。
+//! 文字渲染走 ab_glyph + 系统字体（按平台常见路径探测），机器上
+//! 一款字体都找不到时文字批注画成占位框，其余形状不受影响。
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::db::{self, AppDbPool};
+
+/// 单条批注。kind："arrow" | "text" | "highlight"
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Annotation {
+    /// 箭头：从 (x1,y1) 指向 (x2,y2)
+    #[serde(rename_all = "camelCase")]
+    Arrow { x1: f32, y1: f32, x2: f32, y2: f32, color: String },
+    /// 文字：锚点在左上角，size 为相对图高的百分比
+    #[serde(rename_all = "camelCase")]
+    Text { x: f32, y: f32, text: String, size: f32, color: String },
+    /// 高亮：半透明填充的矩形区域
+    #[serde(rename_all = "camelCase")]
+    Highlight { x: f32, y: f32, w: f32, h: f32, color: String },
+}
+
+/// "#RRGGBB" → RGB，解析失败回落红色
+fn parse_color(hex: &str) -> [u8; 3] {
+    let s = hex.trim_start_matches('#');
+    if s.len() == 6 {
+        if let Ok(v) = u32::from_str_radix(s, 16) {
+            return [(v >> 16) as u8, (v >> 8) as u8, v as u8];
+        }
+    }
+    [0xEF, 0x44, 0x44]
+}
+
+/// 点到线段的距离（粗线段光栅化用）
+fn segment_distance(px: f32, py: f32, x1: f32, y1: f32, x2: f32, y2: f32) -> f32 {
+    let (dx, dy) = (x2 - x1, y2 - y1);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq > 0.0 {
+        (((px - x1) * dx + (py - y1) * dy) / len_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (cx, cy) = (x1 + t * dx, y1 + t * dy);
+    ((px - cx).powi(2) + (py - cy).powi(2)).sqrt()
+}
+
+fn draw_thick_line(img: &mut image::RgbaImage, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: [u8; 3]) {
+    let half = thickness / 2.0;
+    let min_x = (x1.min(x2) - half).floor().max(0.0) as u32;
+    let max_x = (x1.max(x2) + half).ceil().min(img.width() as f32 - 1.0) as u32;
+    let min_y = (y1.min(y2) - half).floor().max(0.0) as u32;
+    let max_y = (y1.max(y2) + half).ceil().min(img.height() as f32 - 1.0) as u32;
+    for py in min_y..=max_y {
+        for px in min_x..=max_x {
+            if segment_distance(px as f32 + 0.5, py as f32 + 0.5, x1, y1, x2, y2) <= half {
+                img.put_pixel(px, py, image::Rgba([color[0], color[1], color[2], 0xFF]));
+            }
+        }
+    }
+}
+
+fn draw_arrow(img: &mut image::RgbaImage, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: [u8; 3]) {
+    draw_thick_line(img, x1, y1, x2, y2, thickness, color);
+    // 箭头两翼：终点回指 30°，长度为线宽的 4 倍
+    let angle = (y2 - y1).atan2(x2 - x1);
+    let head = thickness * 4.0;
+    for side in [-1.0f32, 1.0] {
+        let a = angle + std::f32::consts::PI - side * 0.5;
+        draw_thick_line(img, x2, y2, x2 + head * a.cos(), y2 + head * a.sin(), thickness, color);
+    }
+}
+
+fn draw_highlight(img: &mut image::RgbaImage, x: u32, y: u32, w: u32, h: u32, color: [u8; 3]) {
+    // 35% 透明度叠在原像素上
+    for py in y..(y + h).min(img.height()) {
+        for px in x..(x + w).min(img.width()) {
+            let p = img.get_pixel_mut(px, py);
+            for k in 0..3 {
+                p[k] = ((color[k] as u32 * 90 + p[k] as u32 * 165) / 255) as u8;
+            }
+        }
+    }
+}
+
+/// 按平台常见路径找一款可用的系统字体
+fn load_system_font() -> Option<ab_glyph::FontVec> {
+    let candidates: &[&str] = if cfg!(windows) {
+        &[
+            "C:/Windows/Fonts/msyh.ttc",
+            "C:/Windows/Fonts/arial.ttf",
+            "C:/Windows/Fonts/segoeui.ttf",
+        ]
+    } else if cfg!(target_os = "macos") {
+        &[
+            "/System/Library/Fonts/PingFang.ttc",
+            "/System/Library/Fonts/Helvetica.ttc",
+            "/Library/Fonts/Arial.ttf",
+        ]
+    } else {
+        &[
+            "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+            "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+            "/usr/share/fonts/truetype/noto/NotoSansCJK-Regular.ttc",
+            "/usr/share/fonts/TTF/DejaVuSans.ttf",
+        ]
+    };
+    for path in candidates {
+        if let Ok(bytes) = std::fs::read(path) {
+            if let Ok(font) = ab_glyph::FontVec::try_from_vec(bytes) {
+                return Some(font);
+            }
+        }
+    }
+    None
+}
+
+fn draw_text(img: &mut image::RgbaImage, x: f32, y: f32, text: &str, px_size: f32, color: [u8; 3]) {
+    use ab_glyph::{Font, ScaleFont};
+    let Some(font) = load_system_font() else {
+        // 没有字体时画个占位框，至少标出位置
+        let w = px_size * text.chars().count() as f32 * 0.6;
+        draw_thick_line(img, x, y, x + w, y, 2.0, color);
+        return;
+    };
+    let scaled = font.as_scaled(ab_glyph::PxScale::from(px_size));
+    let mut pen_x = x;
+    let baseline = y + scaled.ascent();
+    for ch in text.chars() {
+        let glyph_id = scaled.glyph_id(ch);
+        let glyph = glyph_id.with_scale_and_position(px_size, ab_glyph::point(pen_x, baseline));
+        pen_x += scaled.h_advance(glyph_id);
+        if let Some(outlined) = font.outline_glyph(glyph) {
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                let (px, py) = (bounds.min.x as i32 + gx as i32, bounds.min.y as i32 + gy as i32);
+                if px >= 0 && py >= 0 && (px as u32) < img.width() && (py as u32) < img.height() {
+                    let p = img.get_pixel_mut(px as u32, py as u32);
+                    let a = (coverage * 255.0) as u32;
+                    for k in 0..3 {
+                        p[k] = ((color[k] as u32 * a + p[k] as u32 * (255 - a)) / 255) as u8;
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// 把批注画到图上（坐标从百分比换算为像素）
+fn render_annotations(img: &mut image::RgbaImage, annotations: &[Annotation]) {
+    let (w, h) = (img.width() as f32, img.height() as f32);
+    // 线宽随图片尺寸走，1080p 下约 4px
+    let thickness = (w.min(h) / 270.0).max(2.0);
+    for a in annotations {
+        match a {
+            Annotation::Arrow { x1, y1, x2, y2, color } => {
+                draw_arrow(
+                    img,
+                    x1 / 100.0 * w,
+                    y1 / 100.0 * h,
+                    x2 / 100.0 * w,
+                    y2 / 100.0 * h,
+                    thickness,
+                    parse_color(color),
+                );
+            }
+            Annotation::Text { x, y, text, size, color } => {
+                draw_text(
+                    img,
+                    x / 100.0 * w,
+                    y / 100.0 * h,
+                    text,
+                    (size / 100.0 * h).max(8.0),
+                    parse_color(color),
+                );
+            }
+            Annotation::Highlight { x, y, w: rw, h: rh, color } => {
+                draw_highlight(
+                    img,
+                    (x / 100.0 * w).max(0.0) as u32,
+                    (y / 100.0 * h).max(0.0) as u32,
+                    (rw / 100.0 * w).max(1.0) as u32,
+                    (rh / 100.0 * h).max(1.0) as u32,
+                    parse_color(color),
+                );
+            }
+        }
+    }
+}
+
+/// 保存一张图的批注（整份覆盖；空数组等价于删除）
+#[tauri::command]
+pub fn save_annotations(
+    file_id: String,
+    annotations: Vec<Annotation>,
+    pool: tauri::State<AppDbPool>,
+) -> Result<(), String> {
+    let conn = pool.get_connection();
+    let entry = db::file_index::get_entry_by_id(&conn, &file_id)
+        .map_err(|e| e.to_string())?
+        .ok_or("文件不在索引中")?;
+    if annotations.is_empty() {
+        db::annotations::delete_annotations(&conn, &file_id).map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+    let json = serde_json::to_string(&annotations).map_err(|e| e.to_string())?;
+    db::annotations::set_annotations(&conn, &file_id, &entry.path, &json).map_err(|e| e.to_string())
+}
+
+/// 读取一张图的批注（没有时返回空数组）
+#[tauri::command]
+pub fn load_annotations(
+    file_id: String,
+    pool: tauri::State<AppDbPool>,
+) -> Result<Vec<Annotation>, String> {
+    let conn = pool.get_connection();
+    match db::annotations::get_annotations(&conn, &file_id).map_err(|e| e.to_string())? {
+        Some(json) => serde_json::from_str(&json).map_err(|e| format!("批注数据损坏: {}", e)),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// 把批注压平到图片副本并返回副本路径。dest_path 缺省时
+/// 写到原图旁边的 "{名字}_annotated.png"
+#[tauri::command]
+pub async fn export_annotated_copy(
+    file_id: String,
+    dest_path: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    let pool = app.state::<AppDbPool>().inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let (path, annotations): (String, Vec<Annotation>) = {
+            let conn = pool.get_connection();
+            let entry = db::file_index::get_entry_by_id(&conn, &file_id)
+                .map_err(|e| e.to_string())?
+                .ok_or("文件不在索引中")?;
+            let json = db::annotations::get_annotations(&conn, &file_id)
+                .map_err(|e| e.to_string())?
+                .ok_or("该图片没有批注")?;
+            (entry.path, serde_json::from_str(&json).map_err(|e| format!("批注数据损坏: {}", e))?)
+        };
+
+        let mut img = image::open(&path)
+            .map_err(|e| format!("解码图片失败: {}", e))?
+            .to_rgba8();
+        render_annotations(&mut img, &annotations);
+
+        let out = match dest_path {
+            Some(p) => std::path::PathBuf::from(p),
+            None => {
+                let src = Path::new(&path);
+                let stem = src.file_stem().and_then(|s| s.to_str()).unwrap_or("image");
+                src.with_file_name(format!("{}_annotated.png", stem))
+            }
+        };
+        img.save(&out).map_err(|e| format!("保存副本失败: {}", e))?;
+        Ok(out.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("导出任务失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color_and_fallback() {
+        assert_eq!(parse_color("#3B82F6"), [0x3B, 0x82, 0xF6]);
+        assert_eq!(parse_color("ff0000"), [0xFF, 0x00, 0x00]);
+        assert_eq!(parse_color("not-a-color"), [0xEF, 0x44, 0x44]);
+    }
+
+    #[test]
+    fn test_render_arrow_and_highlight() {
+        let mut img = image::RgbaImage::from_pixel(100, 100, image::Rgba([0, 0, 0, 0xFF]));
+        let annotations = vec![
+            Annotation::Arrow { x1: 10.0, y1: 50.0, x2: 90.0, y2: 50.0, color: "#FF0000".into() },
+            Annotation::Highlight { x: 0.0, y: 0.0, w: 20.0, h: 20.0, color: "#00FF00".into() },
+        ];
+        render_annotations(&mut img, &annotations);
+        // 箭杆中点是纯红
+        assert_eq!(img.get_pixel(50, 50).0[..3], [0xFF, 0, 0]);
+        // 高亮区域混入了绿色但不是纯绿
+        let p = img.get_pixel(5, 5);
+        assert!(p[1] > 0 && p[1] < 0xFF);
+    }
+}
@@ -0,0 +1,473 @@
+//! 本地 HTTP API：给外部工具（浏览器扩展、脚本）提供查询入口。
+//!
+//! 服务只绑定 127.0.0.1，所有请求需要携带令牌
+//! （`Authorization: Bearer <token>` 或查询参数 `token=`），
+//! 令牌在首次启动时生成并持久化在应用数据目录的 api_token.txt。
+//! 端点：
+//! - `GET /api/ping`                    连通性检查
+//! - `GET /api/search?q=<表达式>`       过滤查询 DSL（见 [`crate::filter_query`]），返回路径列表
+//! - `GET /api/metadata?id=|path=`      单个文件的元数据
+//! - `GET /api/file?path=`              原图字节（仅限已入索引的文件）
+//! - `GET /api/thumbnail?path=&size=`   即时生成的 JPEG 缩略图（仅限已入索引的文件）
+//!
+//! HTTP 解析为手写的最小实现（HTTP/1.1、每请求一连接），避免引入服务端框架。
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use tauri::Manager;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::db::AppDbPool;
+
+/// 请求体上限，防止恶意大包占满内存
+const MAX_BODY_SIZE: usize = 64 * 1024 * 1024;
+
+struct ApiServer {
+    port: u16,
+    token: String,
+    handle: tauri::async_runtime::JoinHandle<()>,
+}
+
+static API_SERVER: Lazy<Mutex<Option<ApiServer>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiServerStatus {
+    pub running: bool,
+    pub port: Option<u16>,
+    pub token: Option<String>,
+}
+
+/// 已解析的请求：方法、路径、查询参数、头（键小写）、请求体
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: HashMap<String, String>,
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+/// 待写出的响应
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn json(value: serde_json::Value) -> Self {
+        Response {
+            status: 200,
+            content_type: "application/json",
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    pub fn error(status: u16, message: &str) -> Self {
+        Response {
+            status,
+            content_type: "application/json",
+            body: serde_json::json!({ "error": message }).to_string().into_bytes(),
+        }
+    }
+}
+
+/// 解析查询串：`a=1&b=%20` → 键值对（百分号解码）
+pub fn parse_query(raw: &str) -> HashMap<String, String> {
+    let mut out = HashMap::new();
+    for pair in raw.split('&') {
+        let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+        if k.is_empty() {
+            continue;
+        }
+        let k = urlencoding::decode(k).map(|c| c.into_owned()).unwrap_or_else(|_| k.to_string());
+        let v = urlencoding::decode(v).map(|c| c.into_owned()).unwrap_or_else(|_| v.to_string());
+        out.insert(k, v);
+    }
+    out
+}
+
+/// 从连接上读出一个完整请求（头 + 按 Content-Length 的体）
+async fn read_request(stream: &mut BufReader<TcpStream>) -> Result<Request, String> {
+    let mut request_line = String::new();
+    stream
+        .read_line(&mut request_line)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().ok_or("空请求")?.to_string();
+    let target = parts.next().ok_or("缺少请求路径")?;
+    let (path, query_raw) = target.split_once('?').unwrap_or((target, ""));
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        stream.read_line(&mut line).await.map_err(|e| e.to_string())?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let content_length: usize = headers
+        .get("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    if content_length > MAX_BODY_SIZE {
+        return Err("请求体过大".to_string());
+    }
+    let mut body = vec![0u8; content_length];
+    if content_length > 0 {
+        stream.read_exact(&mut body).await.map_err(|e| e.to_string())?;
+    }
+
+    Ok(Request {
+        method,
+        path: path.to_string(),
+        query: parse_query(query_raw),
+        headers,
+        body,
+    })
+}
+
+async fn write_response(stream: &mut BufReader<TcpStream>, resp: Response) {
+    let reason = match resp.status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        resp.status,
+        reason,
+        resp.content_type,
+        resp.body.len()
+    );
+    let stream = stream.get_mut();
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(&resp.body).await;
+    let _ = stream.flush().await;
+}
+
+/// 校验令牌：Bearer 头或 token 查询参数
+fn is_authorized(req: &Request, token: &str) -> bool {
+    if let Some(auth) = req.headers.get("authorization") {
+        if let Some(bearer) = auth.strip_prefix("Bearer ") {
+            return bearer.trim() == token;
+        }
+    }
+    req.query.get("token").map(|t| t == token).unwrap_or(false)
+}
+
+/// 路径是否在索引里（API 只允许访问库内文件）
+fn is_indexed(pool: &AppDbPool, path: &str) -> bool {
+    let conn = pool.get_connection();
+    crate::db::file_index::get_entry_by_id(&conn, &crate::db::generate_id(path))
+        .ok()
+        .flatten()
+        .is_some()
+}
+
+async fn handle_search(req: &Request, app: &tauri::AppHandle) -> Response {
+    let Some(q) = req.query.get("q") else {
+        return Response::error(400, "缺少查询参数 q");
+    };
+    let (mut where_clause, mut params) = match crate::filter_query::compile(q) {
+        Ok(r) => r,
+        Err(e) => return Response::error(400, &e),
+    };
+    if let Some(dir) = req.query.get("scope") {
+        where_clause.push_str(" AND fi.path LIKE ?");
+        params.push(rusqlite::types::Value::Text(format!(
+            "{}%",
+            crate::db::normalize_path(dir)
+        )));
+    }
+    let limit: usize = req
+        .query
+        .get("limit")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(200)
+        .clamp(1, 10000);
+
+    let pool = app.state::<AppDbPool>().inner().clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let conn = pool.get_connection();
+        let sql = format!(
+            "SELECT fi.path FROM file_index fi
+             LEFT JOIN file_metadata fm ON fm.file_id = fi.file_id
+             WHERE {}
+             ORDER BY fi.modified_at DESC
+             LIMIT {}",
+            where_clause, limit
+        );
+        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(params), |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<String>, _>>().map_err(|e| e.to_string())
+    })
+    .await;
+
+    match result {
+        Ok(Ok(paths)) => Response::json(serde_json::json!({ "files": paths })),
+        Ok(Err(e)) => Response::error(400, &e),
+        Err(e) => Response::error(500, &e.to_string()),
+    }
+}
+
+async fn handle_metadata(req: &Request, app: &tauri::AppHandle) -> Response {
+    let file_id = match (req.query.get("id"), req.query.get("path")) {
+        (Some(id), _) => id.clone(),
+        (None, Some(path)) => crate::db::generate_id(path),
+        (None, None) => return Response::error(400, "缺少查询参数 id 或 path"),
+    };
+    let pool = app.state::<AppDbPool>().inner().clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let conn = pool.get_connection();
+        crate::db::file_metadata::get_metadata_by_id(&conn, &file_id).map_err(|e| e.to_string())
+    })
+    .await;
+    match result {
+        Ok(Ok(Some(meta))) => Response::json(serde_json::to_value(meta).unwrap_or_default()),
+        Ok(Ok(None)) => Response::error(404, "文件没有元数据记录"),
+        Ok(Err(e)) => Response::error(500, &e),
+        Err(e) => Response::error(500, &e.to_string()),
+    }
+}
+
+async fn handle_file(req: &Request, app: &tauri::AppHandle) -> Response {
+    let Some(path) = req.query.get("path") else {
+        return Response::error(400, "缺少查询参数 path");
+    };
+    let normalized = crate::db::normalize_path(path);
+    let pool = app.state::<AppDbPool>();
+    if !is_indexed(&pool, &normalized) {
+        return Response::error(404, "文件不在库索引中");
+    }
+    match tokio::fs::read(&normalized).await {
+        Ok(bytes) => Response {
+            status: 200,
+            content_type: "application/octet-stream",
+            body: bytes,
+        },
+        Err(_) => Response::error(404, "读取文件失败"),
+    }
+}
+
+async fn handle_thumbnail(req: &Request, app: &tauri::AppHandle) -> Response {
+    let Some(path) = req.query.get("path") else {
+        return Response::error(400, "缺少查询参数 path");
+    };
+    let normalized = crate::db::normalize_path(path);
+    let pool = app.state::<AppDbPool>();
+    if !is_indexed(&pool, &normalized) {
+        return Response::error(404, "文件不在库索引中");
+    }
+    let size: u32 = req
+        .query
+        .get("size")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(256)
+        .clamp(32, 1024);
+
+    let result = tokio::task::spawn_blocking(move || -> Result<Vec<u8>, String> {
+        let img = image::open(&normalized).map_err(|e| format!("解码失败: {}", e))?;
+        let thumb = img.thumbnail(size, size);
+        let mut out = std::io::Cursor::new(Vec::new());
+        thumb
+            .to_rgb8()
+            .write_to(&mut out, image::ImageOutputFormat::Jpeg(85))
+            .map_err(|e| format!("编码失败: {}", e))?;
+        Ok(out.into_inner())
+    })
+    .await;
+
+    match result {
+        Ok(Ok(bytes)) => Response {
+            status: 200,
+            content_type: "image/jpeg",
+            body: bytes,
+        },
+        Ok(Err(e)) => Response::error(500, &e),
+        Err(e) => Response::error(500, &e.to_string()),
+    }
+}
+
+/// 按路径分发请求
+async fn route(req: Request, app: tauri::AppHandle) -> Response {
+    match (req.method.as_str(), req.path.as_str()) {
+        ("GET", "/api/ping") => Response::json(serde_json::json!({ "ok": true })),
+        ("GET", "/api/search") => handle_search(&req, &app).await,
+        ("GET", "/api/metadata") => handle_metadata(&req, &app).await,
+        ("GET", "/api/file") => handle_file(&req, &app).await,
+        ("GET", "/api/thumbnail") => handle_thumbnail(&req, &app).await,
+        ("POST", "/api/ingest") => crate::ingest::handle_ingest(&req, &app).await,
+        ("GET", _) | ("POST", _) => Response::error(404, "未知的端点"),
+        _ => Response::error(405, "不支持的方法"),
+    }
+}
+
+async fn handle_connection(stream: TcpStream, token: String, app: tauri::AppHandle) {
+    let mut stream = BufReader::new(stream);
+    let req = match read_request(&mut stream).await {
+        Ok(req) => req,
+        Err(e) => {
+            write_response(&mut stream, Response::error(400, &e)).await;
+            return;
+        }
+    };
+    if !is_authorized(&req, &token) {
+        write_response(&mut stream, Response::error(401, "令牌无效")).await;
+        return;
+    }
+    let resp = route(req, app).await;
+    write_response(&mut stream, resp).await;
+}
+
+/// 读取（或首次生成）API 令牌。
+/// 没有随机数依赖，用时间戳 + 进程 ID 的两轮 MD5 拼出 32 位十六进制
+fn load_or_create_token(app: &tauri::AppHandle) -> Result<String, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join("api_token.txt");
+    if let Ok(existing) = std::fs::read_to_string(&path) {
+        let existing = existing.trim().to_string();
+        if !existing.is_empty() {
+            return Ok(existing);
+        }
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let seed = format!("{}:{}:aurora-api", now, std::process::id());
+    let token = format!(
+        "{:x}{:x}",
+        md5::compute(seed.as_bytes()),
+        md5::compute(format!("{}:2", seed).as_bytes())
+    );
+    let token = token[..32].to_string();
+    std::fs::write(&path, &token).map_err(|e| e.to_string())?;
+    Ok(token)
+}
+
+/// 启动本地 API 服务（已在运行时返回当前状态）。默认端口 21520
+#[tauri::command]
+pub async fn start_api_server(
+    port: Option<u16>,
+    app: tauri::AppHandle,
+) -> Result<ApiServerStatus, String> {
+    {
+        let guard = API_SERVER.lock().unwrap();
+        if let Some(server) = guard.as_ref() {
+            return Ok(ApiServerStatus {
+                running: true,
+                port: Some(server.port),
+                token: Some(server.token.clone()),
+            });
+        }
+    }
+
+    let port = port.unwrap_or(21520);
+    let token = load_or_create_token(&app)?;
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .await
+        .map_err(|e| format!("绑定端口 {} 失败: {}", port, e))?;
+
+    let accept_token = token.clone();
+    let accept_app = app.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        loop {
+            let Ok((stream, _)) = listener.accept().await else {
+                break;
+            };
+            let token = accept_token.clone();
+            let app = accept_app.clone();
+            tauri::async_runtime::spawn(async move {
+                handle_connection(stream, token, app).await;
+            });
+        }
+    });
+
+    let mut guard = API_SERVER.lock().unwrap();
+    *guard = Some(ApiServer {
+        port,
+        token: token.clone(),
+        handle,
+    });
+    Ok(ApiServerStatus {
+        running: true,
+        port: Some(port),
+        token: Some(token),
+    })
+}
+
+/// 停止本地 API 服务（未启动时静默）
+#[tauri::command]
+pub async fn stop_api_server() -> Result<(), String> {
+    let server = API_SERVER.lock().unwrap().take();
+    if let Some(server) = server {
+        server.handle.abort();
+    }
+    Ok(())
+}
+
+/// 查询本地 API 服务状态（含令牌，供前端展示给外部工具配置）
+#[tauri::command]
+pub async fn get_api_server_status() -> Result<ApiServerStatus, String> {
+    let guard = API_SERVER.lock().unwrap();
+    Ok(match guard.as_ref() {
+        Some(server) => ApiServerStatus {
+            running: true,
+            port: Some(server.port),
+            token: Some(server.token.clone()),
+        },
+        None => ApiServerStatus {
+            running: false,
+            port: None,
+            token: None,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query_decodes_percent_encoding() {
+        let q = parse_query("q=format%3Apng%20sunset&limit=50&empty");
+        assert_eq!(q.get("q").map(String::as_str), Some("format:png sunset"));
+        assert_eq!(q.get("limit").map(String::as_str), Some("50"));
+        assert!(!q.contains_key("empty") || q.get("empty").map(String::as_str) == Some(""));
+    }
+
+    #[test]
+    fn test_is_authorized_accepts_bearer_and_query_token() {
+        let mut req = Request {
+            method: "GET".to_string(),
+            path: "/api/ping".to_string(),
+            query: HashMap::new(),
+            headers: HashMap::new(),
+            body: Vec::new(),
+        };
+        assert!(!is_authorized(&req, "secret"));
+        req.headers
+            .insert("authorization".to_string(), "Bearer secret".to_string());
+        assert!(is_authorized(&req, "secret"));
+        req.headers.clear();
+        req.query.insert("token".to_string(), "secret".to_string());
+        assert!(is_authorized(&req, "secret"));
+        assert!(!is_authorized(&req, "other"));
+    }
+}
@@ -0,0 +1,145 @@
+//! 日志配置：运行期可调的按模块日志级别、日志文件按大小轮转，
+//! 以及给应用内调试面板用的 [`get_recent_logs`]。
+//!
+//! tauri-plugin-log 的级别在构建 Dispatch 时就定死了，这里把底层级别放到
+//! Trace，再挂一个查表的 filter：默认级别 + 按模块前缀的覆盖表都存在
+//! 运行期可写的静态表里，[`set_log_level`] 改表即刻生效，无需重启。
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use log::LevelFilter;
+use once_cell::sync::Lazy;
+use tauri::Manager;
+
+/// 日志文件名（app_log_dir 下的 aurora.log）
+pub const LOG_FILE_NAME: &str = "aurora";
+/// 单个日志文件超过该大小后轮转
+pub const MAX_LOG_FILE_SIZE: u128 = 5 * 1024 * 1024;
+
+/// 未命中模块覆盖表时的默认级别
+static DEFAULT_LEVEL: Lazy<RwLock<LevelFilter>> = Lazy::new(|| RwLock::new(LevelFilter::Info));
+/// 按模块前缀的级别覆盖（键形如 "aurora_gallery::clip"、"reqwest"）
+static MODULE_LEVELS: Lazy<RwLock<HashMap<String, LevelFilter>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    match level.to_ascii_lowercase().as_str() {
+        "trace" => Ok(LevelFilter::Trace),
+        "debug" => Ok(LevelFilter::Debug),
+        "info" => Ok(LevelFilter::Info),
+        "warn" => Ok(LevelFilter::Warn),
+        "error" => Ok(LevelFilter::Error),
+        "off" => Ok(LevelFilter::Off),
+        other => Err(format!("未知日志级别: {}", other)),
+    }
+}
+
+/// target 对应的生效级别：取覆盖表中能按模块边界匹配的最长前缀，
+/// 没有命中则用默认级别
+fn effective_level(target: &str) -> LevelFilter {
+    let overrides = MODULE_LEVELS.read().unwrap();
+    let mut best: Option<(usize, LevelFilter)> = None;
+    for (prefix, level) in overrides.iter() {
+        let matched = target == prefix
+            || (target.len() > prefix.len()
+                && target.starts_with(prefix.as_str())
+                && target[prefix.len()..].starts_with("::"));
+        if matched && best.is_none_or(|(len, _)| prefix.len() > len) {
+            best = Some((prefix.len(), *level));
+        }
+    }
+    match best {
+        Some((_, level)) => level,
+        None => *DEFAULT_LEVEL.read().unwrap(),
+    }
+}
+
+/// 挂到日志插件上的运行期过滤器
+pub fn should_log(metadata: &log::Metadata) -> bool {
+    metadata.level() <= effective_level(metadata.target())
+}
+
+/// 设置日志级别，立即生效。module 传 "*"（或空串）调整默认级别，
+/// 否则按模块前缀覆盖；level 为 trace/debug/info/warn/error/off
+#[tauri::command]
+pub fn set_log_level(module: String, level: String) -> Result<(), String> {
+    let level = parse_level(&level)?;
+    let module = module.trim();
+    if module.is_empty() || module == "*" {
+        *DEFAULT_LEVEL.write().unwrap() = level;
+    } else {
+        MODULE_LEVELS
+            .write()
+            .unwrap()
+            .insert(module.to_string(), level);
+    }
+    log::info!("日志级别已调整: {} -> {}", module, level);
+    Ok(())
+}
+
+/// 当前生效的级别配置（"*" 为默认级别，其余为模块覆盖）
+#[tauri::command]
+pub fn get_log_levels() -> HashMap<String, String> {
+    let mut levels: HashMap<String, String> = MODULE_LEVELS
+        .read()
+        .unwrap()
+        .iter()
+        .map(|(module, level)| (module.clone(), level.to_string().to_lowercase()))
+        .collect();
+    levels.insert(
+        "*".to_string(),
+        DEFAULT_LEVEL.read().unwrap().to_string().to_lowercase(),
+    );
+    levels
+}
+
+/// 读取当前日志文件的最后 lines 行（默认 200，上限 5000），给调试面板用
+#[tauri::command]
+pub fn get_recent_logs(lines: Option<usize>, app: tauri::AppHandle) -> Result<Vec<String>, String> {
+    let lines = lines.unwrap_or(200).clamp(1, 5000);
+    let log_file = app
+        .path()
+        .app_log_dir()
+        .map_err(|e| format!("无法获取日志目录: {}", e))?
+        .join(format!("{}.log", LOG_FILE_NAME));
+    if !log_file.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&log_file).map_err(|e| format!("读取日志失败: {}", e))?;
+    let all: Vec<&str> = content.lines().collect();
+    let start = all.len().saturating_sub(lines);
+    Ok(all[start..].iter().map(|s| s.to_string()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(parse_level("INFO").unwrap(), LevelFilter::Info);
+        assert_eq!(parse_level("off").unwrap(), LevelFilter::Off);
+        assert!(parse_level("verbose").is_err());
+    }
+
+    #[test]
+    fn test_effective_level_prefix_match() {
+        MODULE_LEVELS
+            .write()
+            .unwrap()
+            .insert("aurora_gallery::clip".to_string(), LevelFilter::Debug);
+        // 模块本身及其子模块命中覆盖
+        assert_eq!(effective_level("aurora_gallery::clip"), LevelFilter::Debug);
+        assert_eq!(
+            effective_level("aurora_gallery::clip::embedding"),
+            LevelFilter::Debug
+        );
+        // 前缀相似但不在模块边界上的不命中
+        assert_eq!(
+            effective_level("aurora_gallery::clipboard"),
+            *DEFAULT_LEVEL.read().unwrap()
+        );
+        MODULE_LEVELS.write().unwrap().clear();
+    }
+}
@@ -0,0 +1,504 @@
+//! 音频素材的基础支持：mp3 / wav / flac 的时长元数据与波形缩略图。
+//!
+//! 时长直接解析文件头（WAV 的 fmt/data 块、FLAC 的 STREAMINFO、
+//! MP3 的帧头 + Xing 帧数），不解码音频数据，扫描级别的开销。
+//! 波形图 WAV 直接读 PCM 采样；mp3 / flac 没有纯 Rust 解码依赖，
+//! 系统装了 ffmpeg 时借它转 PCM，没装则只有时长、没有波形。
+//! 波形 PNG 落在缩略图缓存目录的 waveforms/ 子目录，缓存键带
+//! 文件大小和修改时间，源文件改动后自动失效。
+
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/// 支持的音频扩展名
+const SUPPORTED_AUDIO_EXTENSIONS: &[&str] = &["mp3", "wav", "flac"];
+
+/// 波形图尺寸与颜色（主题蓝，同托盘进度条）
+const WAVE_WIDTH: u32 = 512;
+const WAVE_HEIGHT: u32 = 128;
+const WAVE_COLOR: [u8; 4] = [0x3B, 0x82, 0xF6, 0xFF];
+
+pub fn is_supported_audio(extension: &str) -> bool {
+    SUPPORTED_AUDIO_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+}
+
+/// 从文件头解析出的音频属性
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioInfo {
+    /// 容器格式："wav" | "flac" | "mp3"
+    pub format: String,
+    pub duration_seconds: f64,
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// PCM 位深（mp3 无此概念时为 None）
+    pub bits_per_sample: Option<u16>,
+}
+
+/// WAV fmt 块与 data 块的位置信息（波形读取时复用）
+struct WavLayout {
+    audio_format: u16,
+    channels: u16,
+    sample_rate: u32,
+    byte_rate: u32,
+    bits_per_sample: u16,
+    data_offset: u64,
+    data_len: u64,
+}
+
+fn parse_wav_layout(file: &mut std::fs::File) -> Result<WavLayout, String> {
+    let mut header = [0u8; 12];
+    file.seek(SeekFrom::Start(0)).map_err(|e| e.to_string())?;
+    file.read_exact(&mut header).map_err(|_| "文件太小，不是有效的 WAV".to_string())?;
+    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return Err("不是有效的 WAV 文件".to_string());
+    }
+
+    let mut fmt: Option<(u16, u16, u32, u32, u16)> = None;
+    let mut data: Option<(u64, u64)> = None;
+    let mut pos = 12u64;
+    // 顺序走 chunk，拿到 fmt 和 data 即可
+    loop {
+        let mut chunk_head = [0u8; 8];
+        if file.seek(SeekFrom::Start(pos)).is_err() || file.read_exact(&mut chunk_head).is_err() {
+            break;
+        }
+        let id = &chunk_head[0..4];
+        let size = u32::from_le_bytes([chunk_head[4], chunk_head[5], chunk_head[6], chunk_head[7]]) as u64;
+        if id == b"fmt " && size >= 16 {
+            let mut body = [0u8; 16];
+            file.read_exact(&mut body).map_err(|e| e.to_string())?;
+            fmt = Some((
+                u16::from_le_bytes([body[0], body[1]]),
+                u16::from_le_bytes([body[2], body[3]]),
+                u32::from_le_bytes([body[4], body[5], body[6], body[7]]),
+                u32::from_le_bytes([body[8], body[9], body[10], body[11]]),
+                u16::from_le_bytes([body[14], body[15]]),
+            ));
+        } else if id == b"data" {
+            data = Some((pos + 8, size));
+        }
+        if fmt.is_some() && data.is_some() {
+            break;
+        }
+        // chunk 按 2 字节对齐
+        pos += 8 + size + (size & 1);
+    }
+
+    let (audio_format, channels, sample_rate, byte_rate, bits_per_sample) =
+        fmt.ok_or("WAV 缺少 fmt 块")?;
+    let (data_offset, data_len) = data.ok_or("WAV 缺少 data 块")?;
+    if channels == 0 || sample_rate == 0 || byte_rate == 0 {
+        return Err("WAV fmt 块数据非法".to_string());
+    }
+    Ok(WavLayout {
+        audio_format,
+        channels,
+        sample_rate,
+        byte_rate,
+        bits_per_sample,
+        data_offset,
+        data_len,
+    })
+}
+
+fn parse_wav_info(file: &mut std::fs::File) -> Result<AudioInfo, String> {
+    let layout = parse_wav_layout(file)?;
+    Ok(AudioInfo {
+        format: "wav".to_string(),
+        duration_seconds: layout.data_len as f64 / layout.byte_rate as f64,
+        sample_rate: layout.sample_rate,
+        channels: layout.channels,
+        bits_per_sample: Some(layout.bits_per_sample),
+    })
+}
+
+/// FLAC 的 STREAMINFO（首个元数据块）带精确的总采样数
+fn parse_flac_info(buf: &[u8]) -> Result<AudioInfo, String> {
+    if buf.len() < 4 + 4 + 18 || &buf[0..4] != b"fLaC" {
+        return Err("不是有效的 FLAC 文件".to_string());
+    }
+    // 块头：1 字节 last/type + 3 字节长度；STREAMINFO 必须是第一个块
+    if buf[4] & 0x7F != 0 {
+        return Err("FLAC 首个元数据块不是 STREAMINFO".to_string());
+    }
+    let b = &buf[8..]; // STREAMINFO 块体
+    let sample_rate = ((b[10] as u32) << 12) | ((b[11] as u32) << 4) | ((b[12] as u32) >> 4);
+    let channels = (((b[12] >> 1) & 0x07) + 1) as u16;
+    let bits_per_sample = ((((b[12] & 0x01) << 4) | (b[13] >> 4)) + 1) as u16;
+    let total_samples = (((b[13] & 0x0F) as u64) << 32)
+        | ((b[14] as u64) << 24)
+        | ((b[15] as u64) << 16)
+        | ((b[16] as u64) << 8)
+        | (b[17] as u64);
+    if sample_rate == 0 {
+        return Err("FLAC STREAMINFO 采样率为 0".to_string());
+    }
+    Ok(AudioInfo {
+        format: "flac".to_string(),
+        duration_seconds: total_samples as f64 / sample_rate as f64,
+        sample_rate,
+        channels,
+        bits_per_sample: Some(bits_per_sample),
+    })
+}
+
+/// MPEG Layer III 码率表（kbps），索引 0 与 15 保留
+const MP3_BITRATES_V1: [u32; 16] = [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 0];
+const MP3_BITRATES_V2: [u32; 16] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160, 0];
+
+/// MP3 时长：优先 Xing/Info 帧里的总帧数（VBR 精确），
+/// 否则按首帧码率做 CBR 估算
+fn parse_mp3_info(buf: &[u8], file_len: u64) -> Result<AudioInfo, String> {
+    // 跳过 ID3v2 标签（长度是 syncsafe 整数）
+    let mut pos = 0usize;
+    if buf.len() >= 10 && &buf[0..3] == b"ID3" {
+        let size = ((buf[6] as usize) << 21)
+            | ((buf[7] as usize) << 14)
+            | ((buf[8] as usize) << 7)
+            | (buf[9] as usize);
+        pos = 10 + size;
+    }
+
+    // 找帧同步字
+    while pos + 4 <= buf.len() {
+        if buf[pos] == 0xFF && buf[pos + 1] & 0xE0 == 0xE0 {
+            break;
+        }
+        pos += 1;
+    }
+    if pos + 4 > buf.len() {
+        return Err("未找到 MP3 帧头".to_string());
+    }
+
+    let h1 = buf[pos + 1];
+    let h2 = buf[pos + 2];
+    let version = (h1 >> 3) & 0x03; // 3=MPEG1, 2=MPEG2, 0=MPEG2.5
+    let layer = (h1 >> 1) & 0x03; // 1=Layer III
+    if layer != 1 || version == 1 {
+        return Err("不是 MPEG Layer III 音频".to_string());
+    }
+    let is_v1 = version == 3;
+    let bitrate_kbps = if is_v1 {
+        MP3_BITRATES_V1[(h2 >> 4) as usize]
+    } else {
+        MP3_BITRATES_V2[(h2 >> 4) as usize]
+    };
+    let sample_rate = match ((h2 >> 2) & 0x03, version) {
+        (0, 3) => 44100,
+        (1, 3) => 48000,
+        (2, 3) => 32000,
+        (0, 2) => 22050,
+        (1, 2) => 24000,
+        (2, 2) => 16000,
+        (0, 0) => 11025,
+        (1, 0) => 12000,
+        (2, 0) => 8000,
+        _ => return Err("MP3 采样率字段非法".to_string()),
+    };
+    let channel_mode = (buf[pos + 3] >> 6) & 0x03;
+    let channels: u16 = if channel_mode == 3 { 1 } else { 2 };
+    let samples_per_frame: u32 = if is_v1 { 1152 } else { 576 };
+
+    // Xing/Info 头在首帧侧信息之后
+    let side_info = match (is_v1, channels) {
+        (true, 1) => 17,
+        (true, _) => 32,
+        (false, 1) => 9,
+        (false, _) => 17,
+    };
+    let xing_pos = pos + 4 + side_info;
+    if xing_pos + 12 <= buf.len()
+        && (&buf[xing_pos..xing_pos + 4] == b"Xing" || &buf[xing_pos..xing_pos + 4] == b"Info")
+        && buf[xing_pos + 7] & 0x01 != 0
+    {
+        let frames = u32::from_be_bytes([
+            buf[xing_pos + 8],
+            buf[xing_pos + 9],
+            buf[xing_pos + 10],
+            buf[xing_pos + 11],
+        ]);
+        return Ok(AudioInfo {
+            format: "mp3".to_string(),
+            duration_seconds: frames as f64 * samples_per_frame as f64 / sample_rate as f64,
+            sample_rate,
+            channels,
+            bits_per_sample: None,
+        });
+    }
+
+    if bitrate_kbps == 0 {
+        return Err("MP3 码率字段非法".to_string());
+    }
+    let audio_bytes = file_len.saturating_sub(pos as u64);
+    Ok(AudioInfo {
+        format: "mp3".to_string(),
+        duration_seconds: audio_bytes as f64 * 8.0 / (bitrate_kbps as f64 * 1000.0),
+        sample_rate,
+        channels,
+        bits_per_sample: None,
+    })
+}
+
+fn read_audio_info(path: &str) -> Result<AudioInfo, String> {
+    let ext = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    if !is_supported_audio(&ext) {
+        return Err(format!("不支持的音频格式: {}", ext));
+    }
+    let mut file = std::fs::File::open(path).map_err(|e| format!("打开文件失败: {}", e))?;
+    let file_len = file.metadata().map_err(|e| e.to_string())?.len();
+    match ext.as_str() {
+        "wav" => parse_wav_info(&mut file),
+        _ => {
+            // FLAC / MP3 只需要文件开头（ID3 标签可能较大，多读一点）
+            let mut buf = Vec::with_capacity(64 * 1024);
+            (&mut file)
+                .take(1024 * 1024)
+                .read_to_end(&mut buf)
+                .map_err(|e| e.to_string())?;
+            if ext == "flac" {
+                parse_flac_info(&buf)
+            } else {
+                parse_mp3_info(&buf, file_len)
+            }
+        }
+    }
+}
+
+/// 逐块读 WAV 的 data 段，取每个时间桶的峰值（0.0 - 1.0）。
+/// 支持 PCM 8/16/24/32 位整型与 32 位浮点
+fn read_wav_peaks(path: &str, buckets: usize) -> Result<Vec<f32>, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("打开文件失败: {}", e))?;
+    let layout = parse_wav_layout(&mut file)?;
+    let bytes_per_sample = (layout.bits_per_sample / 8) as u64;
+    if bytes_per_sample == 0 {
+        return Err("WAV 位深非法".to_string());
+    }
+    let supported = matches!(
+        (layout.audio_format, layout.bits_per_sample),
+        (1, 8) | (1, 16) | (1, 24) | (1, 32) | (3, 32)
+    );
+    if !supported {
+        return Err(format!(
+            "不支持的 WAV 编码（format {} / {} bit）",
+            layout.audio_format, layout.bits_per_sample
+        ));
+    }
+
+    let total_samples = layout.data_len / bytes_per_sample;
+    if total_samples == 0 {
+        return Ok(vec![0.0; buckets]);
+    }
+    let samples_per_bucket = (total_samples / buckets as u64).max(1);
+
+    file.seek(SeekFrom::Start(layout.data_offset)).map_err(|e| e.to_string())?;
+    let mut reader = std::io::BufReader::new(file);
+    let mut peaks = vec![0.0f32; buckets];
+    let mut sample_index = 0u64;
+    let mut chunk = vec![0u8; 64 * 1024 * bytes_per_sample as usize];
+    let mut remaining = layout.data_len;
+    while remaining > 0 {
+        let want = chunk.len().min(remaining as usize);
+        let n = reader.read(&mut chunk[..want]).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        remaining -= n as u64;
+        for raw in chunk[..n].chunks_exact(bytes_per_sample as usize) {
+            let value = match (layout.audio_format, layout.bits_per_sample) {
+                (1, 8) => (raw[0] as f32 - 128.0) / 128.0,
+                (1, 16) => i16::from_le_bytes([raw[0], raw[1]]) as f32 / 32768.0,
+                (1, 24) => {
+                    let v = i32::from_le_bytes([0, raw[0], raw[1], raw[2]]) >> 8;
+                    v as f32 / 8_388_608.0
+                }
+                (1, 32) => i32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as f32 / 2_147_483_648.0,
+                _ => f32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]),
+            };
+            let bucket = ((sample_index / samples_per_bucket) as usize).min(buckets - 1);
+            peaks[bucket] = peaks[bucket].max(value.abs().min(1.0));
+            sample_index += 1;
+        }
+    }
+    Ok(peaks)
+}
+
+/// mp3 / flac 借系统的 ffmpeg 转成 16 位单声道 PCM 再取峰值
+fn read_peaks_via_ffmpeg(path: &str, buckets: usize) -> Result<Vec<f32>, String> {
+    let output = std::process::Command::new("ffmpeg")
+        .args(["-v", "error", "-i", path, "-map", "0:a:0", "-ac", "1", "-f", "s16le", "-"])
+        .output()
+        .map_err(|_| "生成 mp3/flac 波形需要系统安装 ffmpeg".to_string())?;
+    if !output.status.success() {
+        return Err(format!(
+            "ffmpeg 解码失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let samples: Vec<i16> = output
+        .stdout
+        .chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]))
+        .collect();
+    if samples.is_empty() {
+        return Ok(vec![0.0; buckets]);
+    }
+    let samples_per_bucket = (samples.len() / buckets).max(1);
+    let mut peaks = vec![0.0f32; buckets];
+    for (i, s) in samples.iter().enumerate() {
+        let bucket = (i / samples_per_bucket).min(buckets - 1);
+        peaks[bucket] = peaks[bucket].max((*s as f32 / 32768.0).abs());
+    }
+    Ok(peaks)
+}
+
+/// 峰值画成对称波形图：透明底、主题蓝，柱状逐列绘制
+fn render_waveform(peaks: &[f32]) -> image::RgbaImage {
+    let mut img = image::RgbaImage::new(WAVE_WIDTH, WAVE_HEIGHT);
+    let mid = WAVE_HEIGHT as f32 / 2.0;
+    for (x, peak) in peaks.iter().enumerate().take(WAVE_WIDTH as usize) {
+        // 静音也画 1 像素中线，波形轮廓保持连续
+        let half = (peak * (mid - 1.0)).max(1.0);
+        let y0 = (mid - half).max(0.0) as u32;
+        let y1 = ((mid + half) as u32).min(WAVE_HEIGHT - 1);
+        for y in y0..=y1 {
+            img.put_pixel(x as u32, y, image::Rgba(WAVE_COLOR));
+        }
+    }
+    img
+}
+
+/// 缓存键：路径 + 大小 + 修改时间的 md5（与缩略图缓存同一套思路）
+fn cache_key(path: &str) -> Result<String, String> {
+    let metadata = std::fs::metadata(path).map_err(|e| format!("读取文件信息失败: {}", e))?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Ok(format!(
+        "{:x}",
+        md5::compute(format!("{}-{}-{}", path, metadata.len(), modified).as_bytes())
+    ))
+}
+
+/// 解析音频时长等属性（不解码音频数据）
+#[tauri::command]
+pub async fn get_audio_info(file_path: String) -> Result<AudioInfo, String> {
+    tokio::task::spawn_blocking(move || read_audio_info(&file_path))
+        .await
+        .map_err(|e| format!("解析任务失败: {}", e))?
+}
+
+/// 生成（或命中缓存的）波形缩略图，返回 PNG 路径
+#[tauri::command]
+pub async fn generate_waveform_thumbnail(
+    file_path: String,
+    cache_root: String,
+) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || {
+        let ext = Path::new(&file_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        if !is_supported_audio(&ext) {
+            return Err(format!("不支持的音频格式: {}", ext));
+        }
+
+        let dir = PathBuf::from(&cache_root).join("waveforms");
+        std::fs::create_dir_all(&dir).map_err(|e| format!("创建缓存目录失败: {}", e))?;
+        let out = dir.join(format!("{}.png", cache_key(&file_path)?));
+        if out.exists() {
+            return Ok(out.to_string_lossy().to_string());
+        }
+
+        let peaks = if ext == "wav" {
+            read_wav_peaks(&file_path, WAVE_WIDTH as usize)?
+        } else {
+            read_peaks_via_ffmpeg(&file_path, WAVE_WIDTH as usize)?
+        };
+        render_waveform(&peaks)
+            .save(&out)
+            .map_err(|e| format!("保存波形图失败: {}", e))?;
+        Ok(out.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("波形任务失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// 拼一个 1 秒 8000Hz 单声道 16 位 PCM 的方波 WAV
+    fn make_test_wav(path: &Path) {
+        let sample_rate = 8000u32;
+        let samples: Vec<i16> = (0..sample_rate)
+            .map(|i| if (i / 100) % 2 == 0 { 16384 } else { -16384 })
+            .collect();
+        let data_len = samples.len() * 2;
+        let mut f = std::fs::File::create(path).unwrap();
+        f.write_all(b"RIFF").unwrap();
+        f.write_all(&((36 + data_len) as u32).to_le_bytes()).unwrap();
+        f.write_all(b"WAVEfmt ").unwrap();
+        f.write_all(&16u32.to_le_bytes()).unwrap();
+        f.write_all(&1u16.to_le_bytes()).unwrap(); // PCM
+        f.write_all(&1u16.to_le_bytes()).unwrap(); // mono
+        f.write_all(&sample_rate.to_le_bytes()).unwrap();
+        f.write_all(&(sample_rate * 2).to_le_bytes()).unwrap(); // byte rate
+        f.write_all(&2u16.to_le_bytes()).unwrap(); // block align
+        f.write_all(&16u16.to_le_bytes()).unwrap();
+        f.write_all(b"data").unwrap();
+        f.write_all(&(data_len as u32).to_le_bytes()).unwrap();
+        for s in samples {
+            f.write_all(&s.to_le_bytes()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_wav_info_and_peaks() {
+        let path = std::env::temp_dir().join("aurora_test_audio.wav");
+        make_test_wav(&path);
+        let info = read_audio_info(path.to_str().unwrap()).unwrap();
+        assert_eq!(info.format, "wav");
+        assert_eq!(info.sample_rate, 8000);
+        assert_eq!(info.channels, 1);
+        assert!((info.duration_seconds - 1.0).abs() < 0.01);
+
+        let peaks = read_wav_peaks(path.to_str().unwrap(), 64).unwrap();
+        assert_eq!(peaks.len(), 64);
+        // 方波峰值应接近 0.5
+        assert!(peaks.iter().all(|p| (*p - 0.5).abs() < 0.01));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_flac_streaminfo_parse() {
+        // 手拼 STREAMINFO：44100Hz / 双声道 / 16bit / 88200 个采样（2 秒）
+        let mut buf = b"fLaC".to_vec();
+        buf.extend_from_slice(&[0x80, 0, 0, 34]); // last-block + type 0 + 长度
+        let mut block = vec![0u8; 34];
+        block[10] = 0x0A; // 44100 = 0x0AC44，高 8 位
+        block[11] = 0xC4;
+        block[12] = 0x42; // 低 4 位 + 声道 (2-1)=1 + bps 高位
+        block[13] = 0xF0; // bps 低 4 位 (16-1=15) + 总采样高 4 位 0
+        block[15] = 0x01; // 88200 = 0x015888
+        block[16] = 0x58;
+        block[17] = 0x88;
+        buf.extend_from_slice(&block);
+        let info = parse_flac_info(&buf).unwrap();
+        assert_eq!(info.sample_rate, 44100);
+        assert_eq!(info.channels, 2);
+        assert_eq!(info.bits_per_sample, Some(16));
+        assert!((info.duration_seconds - 2.0).abs() < 0.001);
+    }
+}
@@ -0,0 +1,403 @@
+//! 自动化管线：把后端操作（搜索 → 打标 → 移动 → 导出）串成声明式脚本一次执行。
+//!
+//! 脚本是一个 JSON 对象：`{ "steps": [ {"op": "...", ...}, ... ] }`，
+//! 管线维护一个"工作集"（文件路径列表），search 产生工作集，
+//! 后续步骤依次作用在工作集上。支持的步骤：
+//! - `{"op":"search","query":"<过滤 DSL>","scope":"<可选目录>"}`
+//! - `{"op":"limit","count":100}`
+//! - `{"op":"tag","add":["标签",...]}`（并集合并，不覆盖已有标签）
+//! - `{"op":"rate","value":5}`
+//! - `{"op":"category","value":"分类名"}`
+//! - `{"op":"move","dest":"目标文件夹"}`（走 move_file，索引/元数据/颜色随之迁移）
+//! - `{"op":"export","target":"导出目标名"}`（见 [`crate::export_backend`]）
+//! - `{"op":"delete","toTrash":true}`（只支持回收站删除，不提供永久删除）
+//!
+//! 整个管线共用一条进度流 "automation-progress"，同一时间只允许一个管线运行。
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager};
+
+use crate::db::{self, AppDbPool};
+
+static AUTOMATION_RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Deserialize)]
+struct Script {
+    steps: Vec<Step>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum Step {
+    Search {
+        query: String,
+        #[serde(default)]
+        scope: Option<String>,
+    },
+    Limit {
+        count: usize,
+    },
+    Tag {
+        add: Vec<String>,
+    },
+    Rate {
+        value: i64,
+    },
+    Category {
+        value: String,
+    },
+    Move {
+        dest: String,
+    },
+    Export {
+        target: String,
+    },
+    Delete {
+        #[serde(default, rename = "toTrash")]
+        to_trash: Option<bool>,
+    },
+}
+
+impl Step {
+    fn name(&self) -> &'static str {
+        match self {
+            Step::Search { .. } => "search",
+            Step::Limit { .. } => "limit",
+            Step::Tag { .. } => "tag",
+            Step::Rate { .. } => "rate",
+            Step::Category { .. } => "category",
+            Step::Move { .. } => "move",
+            Step::Export { .. } => "export",
+            Step::Delete { .. } => "delete",
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct AutomationProgress {
+    step: usize,
+    total_steps: usize,
+    op: String,
+    processed: usize,
+    total: usize,
+}
+
+/// 每一步的执行结果
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StepReport {
+    pub op: String,
+    /// 该步实际作用到的文件数
+    pub affected: usize,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationReport {
+    pub steps: Vec<StepReport>,
+    /// 管线结束时工作集里剩余的文件
+    pub remaining: Vec<String>,
+}
+
+fn emit_progress(
+    app: &tauri::AppHandle,
+    step: usize,
+    total_steps: usize,
+    op: &str,
+    processed: usize,
+    total: usize,
+) {
+    let _ = app.emit(
+        "automation-progress",
+        AutomationProgress {
+            step,
+            total_steps,
+            op: op.to_string(),
+            processed,
+            total,
+        },
+    );
+}
+
+/// 执行 search 步骤：编译过滤 DSL 并查询，结果成为新的工作集
+async fn run_search(
+    query: &str,
+    scope: Option<&str>,
+    app: &tauri::AppHandle,
+) -> Result<Vec<String>, String> {
+    let (mut where_clause, mut params) = crate::filter_query::compile(query)?;
+    if let Some(dir) = scope {
+        where_clause.push_str(" AND fi.path LIKE ?");
+        params.push(rusqlite::types::Value::Text(format!(
+            "{}%",
+            db::normalize_path(dir)
+        )));
+    }
+    let pool = app.state::<AppDbPool>().inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get_connection();
+        let sql = format!(
+            "SELECT fi.path FROM file_index fi
+             LEFT JOIN file_metadata fm ON fm.file_id = fi.file_id
+             WHERE {}
+             ORDER BY fi.modified_at DESC",
+            where_clause
+        );
+        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(params), |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<String>, _>>().map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("搜索任务失败: {}", e))?
+}
+
+/// 对工作集里的每个文件修改元数据（打标 / 评分 / 分类共用）
+fn apply_metadata<F>(pool: &AppDbPool, files: &[String], mut update: F) -> usize
+where
+    F: FnMut(&mut db::file_metadata::FileMetadata),
+{
+    let conn = pool.get_connection();
+    let now = chrono::Utc::now().timestamp();
+    let mut affected = 0;
+    for path in files {
+        let file_id = db::generate_id(path);
+        let mut meta = db::file_metadata::get_metadata_by_id(&conn, &file_id)
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| db::file_metadata::FileMetadata {
+                file_id: file_id.clone(),
+                path: path.clone(),
+                tags: None,
+                description: None,
+                source_url: None,
+                source_title: None,
+                source_author: None,
+                ai_data: None,
+                category: None,
+                rating: None,
+                notes: None,
+                sensitive: None,
+                updated_at: None,
+            });
+        update(&mut meta);
+        meta.updated_at = Some(now);
+        if db::file_metadata::upsert_file_metadata(&conn, &meta).is_ok() {
+            affected += 1;
+        }
+    }
+    affected
+}
+
+/// 把新标签并入已有标签（大小写不敏感去重，保持原顺序）
+fn merge_tags(existing: &Option<serde_json::Value>, add: &[String]) -> serde_json::Value {
+    let mut tags: Vec<String> = existing
+        .as_ref()
+        .and_then(|v| v.as_array())
+        .map(|a| a.iter().filter_map(|t| t.as_str().map(String::from)).collect())
+        .unwrap_or_default();
+    for tag in add {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.clone());
+        }
+    }
+    serde_json::json!(tags)
+}
+
+/// 解析并执行自动化脚本，返回每步的执行报告
+#[tauri::command]
+pub async fn run_automation(
+    script: serde_json::Value,
+    app: tauri::AppHandle,
+) -> Result<AutomationReport, String> {
+    let script: Script =
+        serde_json::from_value(script).map_err(|e| format!("解析脚本失败: {}", e))?;
+    if script.steps.is_empty() {
+        return Err("脚本不包含任何步骤".to_string());
+    }
+
+    if AUTOMATION_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("自动化管线已在运行中".to_string());
+    }
+
+    let result = run_steps(script, &app).await;
+    AUTOMATION_RUNNING.store(false, Ordering::SeqCst);
+    result
+}
+
+async fn run_steps(script: Script, app: &tauri::AppHandle) -> Result<AutomationReport, String> {
+    let total_steps = script.steps.len();
+    let mut working_set: Vec<String> = Vec::new();
+    let mut reports = Vec::new();
+
+    for (i, step) in script.steps.iter().enumerate() {
+        let op = step.name();
+        emit_progress(app, i + 1, total_steps, op, 0, working_set.len());
+
+        let affected = match step {
+            Step::Search { query, scope } => {
+                working_set = run_search(query, scope.as_deref(), app).await?;
+                working_set.len()
+            }
+            Step::Limit { count } => {
+                working_set.truncate(*count);
+                working_set.len()
+            }
+            Step::Tag { add } => {
+                let pool = app.state::<AppDbPool>();
+                apply_metadata(&pool, &working_set, |meta| {
+                    meta.tags = Some(merge_tags(&meta.tags, add));
+                })
+            }
+            Step::Rate { value } => {
+                let value = (*value).clamp(0, 5);
+                let pool = app.state::<AppDbPool>();
+                apply_metadata(&pool, &working_set, |meta| {
+                    meta.rating = Some(value);
+                })
+            }
+            Step::Category { value } => {
+                let pool = app.state::<AppDbPool>();
+                apply_metadata(&pool, &working_set, |meta| {
+                    meta.category = Some(value.clone());
+                })
+            }
+            Step::Move { dest } => {
+                let dest_dir = db::normalize_path(dest);
+                std::fs::create_dir_all(&dest_dir)
+                    .map_err(|e| format!("创建目标文件夹失败: {}", e))?;
+                let mut moved = 0;
+                let mut new_set = Vec::with_capacity(working_set.len());
+                for (n, path) in working_set.iter().enumerate() {
+                    let Some(name) = Path::new(path).file_name().and_then(|f| f.to_str()) else {
+                        continue;
+                    };
+                    let mut target = format!("{}/{}", dest_dir, name);
+                    if Path::new(&target).exists() {
+                        target = crate::generate_unique_file_path(&target);
+                    }
+                    match crate::move_file(path.clone(), target.clone(), app.clone()).await {
+                        Ok(()) => {
+                            moved += 1;
+                            new_set.push(target);
+                        }
+                        Err(_) => new_set.push(path.clone()),
+                    }
+                    if (n + 1).is_multiple_of(20) {
+                        emit_progress(app, i + 1, total_steps, op, n + 1, working_set.len());
+                    }
+                }
+                working_set = new_set;
+                moved
+            }
+            Step::Export { target } => {
+                // 以各文件所在目录的公共前缀为基准目录，保持远端相对结构
+                let base = common_parent(&working_set);
+                let report = crate::export_backend::export_to_target(
+                    target.clone(),
+                    base,
+                    working_set.clone(),
+                    app.clone(),
+                )
+                .await?;
+                report.uploaded
+            }
+            Step::Delete { to_trash } => {
+                if !to_trash.unwrap_or(true) {
+                    return Err("自动化管线只支持回收站删除（toTrash: true）".to_string());
+                }
+                let mut deleted = 0;
+                for (n, path) in working_set.iter().enumerate() {
+                    if crate::delete_file_to_trash(path.clone(), app.clone()).await.is_ok() {
+                        deleted += 1;
+                    }
+                    if (n + 1).is_multiple_of(20) {
+                        emit_progress(app, i + 1, total_steps, op, n + 1, working_set.len());
+                    }
+                }
+                working_set.clear();
+                deleted
+            }
+        };
+
+        emit_progress(app, i + 1, total_steps, op, affected, working_set.len());
+        reports.push(StepReport {
+            op: op.to_string(),
+            affected,
+        });
+    }
+
+    Ok(AutomationReport {
+        steps: reports,
+        remaining: working_set,
+    })
+}
+
+/// 求一组路径的公共父目录（用于导出时的相对路径基准）
+fn common_parent(paths: &[String]) -> String {
+    let mut iter = paths.iter();
+    let Some(first) = iter.next() else {
+        return String::new();
+    };
+    let mut prefix: Vec<&str> = match Path::new(first).parent() {
+        Some(p) => p.to_str().unwrap_or("").split('/').collect(),
+        None => return String::new(),
+    };
+    for path in iter {
+        let parent: Vec<&str> = match Path::new(path).parent() {
+            Some(p) => p.to_str().unwrap_or("").split('/').collect(),
+            None => return String::new(),
+        };
+        let common = prefix
+            .iter()
+            .zip(parent.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        prefix.truncate(common);
+    }
+    prefix.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_tags_case_insensitive_union() {
+        let existing = Some(serde_json::json!(["Art", "sketch"]));
+        let merged = merge_tags(&existing, &["art".to_string(), "portfolio".to_string()]);
+        assert_eq!(merged, serde_json::json!(["Art", "sketch", "portfolio"]));
+    }
+
+    #[test]
+    fn test_common_parent() {
+        let paths = vec![
+            "D:/lib/a/x.png".to_string(),
+            "D:/lib/b/y.png".to_string(),
+        ];
+        assert_eq!(common_parent(&paths), "D:/lib");
+        assert_eq!(common_parent(&[]), "");
+    }
+
+    #[test]
+    fn test_script_parses_tagged_steps() {
+        let script: Script = serde_json::from_value(serde_json::json!({
+            "steps": [
+                {"op": "search", "query": "rating:>=4"},
+                {"op": "tag", "add": ["portfolio"]},
+                {"op": "move", "dest": "D:/sorted"}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(script.steps.len(), 3);
+        assert_eq!(script.steps[1].name(), "tag");
+    }
+}
@@ -0,0 +1,199 @@
+//! 开机自启与后台启动：`set_launch_at_login` 把应用挂进系统的登录启动
+//! （Windows 注册表 Run 键 / macOS LaunchAgent / Linux xdg autostart），
+//! minimized 为 true 时带上 `--background` 参数——main 里检测到该参数
+//! 就只启动托盘、监视器和后台工作线程，不显示主窗口。
+
+use serde::Serialize;
+
+/// Windows Run 键 / LaunchAgent / .desktop 共用的条目名
+const ENTRY_NAME: &str = "AuroraGallery";
+
+/// 当前进程是否以后台模式启动（带 --background 参数）
+pub fn is_background_launch() -> bool {
+    std::env::args().any(|a| a == "--background")
+}
+
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchAtLoginStatus {
+    pub enabled: bool,
+    pub minimized: bool,
+}
+
+fn exe_path() -> Result<String, String> {
+    std::env::current_exe()
+        .map_err(|e| format!("获取程序路径失败: {}", e))
+        .map(|p| p.to_string_lossy().to_string())
+}
+
+#[cfg(windows)]
+const RUN_KEY: &str = "HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\Run";
+
+#[cfg(windows)]
+fn apply_launch_at_login(enabled: bool, minimized: bool) -> Result<(), String> {
+    if !enabled {
+        // 条目不存在时 reg delete 返回非零，忽略
+        let _ = std::process::Command::new("reg")
+            .args(["delete", RUN_KEY, "/v", ENTRY_NAME, "/f"])
+            .status();
+        return Ok(());
+    }
+    let exe = exe_path()?.replace('/', "\\");
+    let command = if minimized {
+        format!("\"{}\" --background", exe)
+    } else {
+        format!("\"{}\"", exe)
+    };
+    let status = std::process::Command::new("reg")
+        .args([
+            "add", RUN_KEY, "/v", ENTRY_NAME, "/t", "REG_SZ", "/d", &command, "/f",
+        ])
+        .status()
+        .map_err(|e| format!("写注册表失败: {}", e))?;
+    if !status.success() {
+        return Err("写注册表失败".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn query_launch_at_login() -> LaunchAtLoginStatus {
+    let output = std::process::Command::new("reg")
+        .args(["query", RUN_KEY, "/v", ENTRY_NAME])
+        .output();
+    let Ok(output) = output else {
+        return LaunchAtLoginStatus::default();
+    };
+    if !output.status.success() {
+        return LaunchAtLoginStatus::default();
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    LaunchAtLoginStatus {
+        enabled: text.contains(ENTRY_NAME),
+        minimized: text.contains("--background"),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn agent_plist_path() -> Result<std::path::PathBuf, String> {
+    let home = std::env::var("HOME").map_err(|_| "无法确定用户目录".to_string())?;
+    Ok(std::path::PathBuf::from(home)
+        .join("Library/LaunchAgents")
+        .join(format!("com.misakimiku.{}.plist", ENTRY_NAME.to_lowercase())))
+}
+
+#[cfg(target_os = "macos")]
+fn apply_launch_at_login(enabled: bool, minimized: bool) -> Result<(), String> {
+    let plist = agent_plist_path()?;
+    if !enabled {
+        if plist.exists() {
+            std::fs::remove_file(&plist).map_err(|e| format!("删除 LaunchAgent 失败: {}", e))?;
+        }
+        return Ok(());
+    }
+    let exe = exe_path()?;
+    let args = if minimized {
+        format!("<string>{}</string>\n\t\t<string>--background</string>", exe)
+    } else {
+        format!("<string>{}</string>", exe)
+    };
+    let content = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">\n<dict>\n\
+         \t<key>Label</key>\n\t<string>com.misakimiku.{}</string>\n\
+         \t<key>ProgramArguments</key>\n\t<array>\n\t\t{}\n\t</array>\n\
+         \t<key>RunAtLoad</key>\n\t<true/>\n\
+         </dict>\n</plist>\n",
+        ENTRY_NAME.to_lowercase(),
+        args
+    );
+    if let Some(parent) = plist.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&plist, content).map_err(|e| format!("写 LaunchAgent 失败: {}", e))
+}
+
+#[cfg(target_os = "macos")]
+fn query_launch_at_login() -> LaunchAtLoginStatus {
+    let Ok(plist) = agent_plist_path() else {
+        return LaunchAtLoginStatus::default();
+    };
+    match std::fs::read_to_string(&plist) {
+        Ok(text) => LaunchAtLoginStatus {
+            enabled: true,
+            minimized: text.contains("--background"),
+        },
+        Err(_) => LaunchAtLoginStatus::default(),
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn autostart_desktop_path() -> Result<std::path::PathBuf, String> {
+    let config = std::env::var("XDG_CONFIG_HOME")
+        .map(std::path::PathBuf::from)
+        .or_else(|_| {
+            std::env::var("HOME").map(|h| std::path::PathBuf::from(h).join(".config"))
+        })
+        .map_err(|_| "无法确定配置目录".to_string())?;
+    Ok(config.join("autostart").join("aurora-gallery.desktop"))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn apply_launch_at_login(enabled: bool, minimized: bool) -> Result<(), String> {
+    let desktop = autostart_desktop_path()?;
+    if !enabled {
+        if desktop.exists() {
+            std::fs::remove_file(&desktop).map_err(|e| format!("删除自启条目失败: {}", e))?;
+        }
+        return Ok(());
+    }
+    let exe = exe_path()?;
+    let exec = if minimized {
+        format!("\"{}\" --background", exe)
+    } else {
+        format!("\"{}\"", exe)
+    };
+    let content = format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Aurora Gallery\n\
+         Exec={}\n\
+         X-GNOME-Autostart-enabled=true\n",
+        exec
+    );
+    if let Some(parent) = desktop.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&desktop, content).map_err(|e| format!("写自启条目失败: {}", e))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn query_launch_at_login() -> LaunchAtLoginStatus {
+    let Ok(desktop) = autostart_desktop_path() else {
+        return LaunchAtLoginStatus::default();
+    };
+    match std::fs::read_to_string(&desktop) {
+        Ok(text) => LaunchAtLoginStatus {
+            enabled: true,
+            minimized: text.contains("--background"),
+        },
+        Err(_) => LaunchAtLoginStatus::default(),
+    }
+}
+
+/// 设置开机自启。minimized 为 true 时登录后只在后台运行（托盘可见）
+#[tauri::command]
+pub async fn set_launch_at_login(enabled: bool, minimized: bool) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || apply_launch_at_login(enabled, minimized))
+        .await
+        .map_err(|e| format!("设置自启任务失败: {}", e))?
+}
+
+/// 查询开机自启状态
+#[tauri::command]
+pub async fn get_launch_at_login() -> Result<LaunchAtLoginStatus, String> {
+    tokio::task::spawn_blocking(query_launch_at_login)
+        .await
+        .map_err(|e| format!("查询自启任务失败: {}", e))
+}
@@ -0,0 +1,266 @@
+//! 性能基准测试：在用户机器上实测扫描吞吐、缩略图生成速率、SQLite 写入
+//! 吞吐和 CLIP 编码速率，返回结构化结果方便与基线对比定位"卡顿"反馈。
+//!
+//! 扫描 / 缩略图 / CLIP 用库里已索引的真实文件做样本；SQLite 写入用临时
+//! 数据库合成负载，不碰用户数据。
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::db::{self, AppDbPool};
+
+/// 同一时间只允许一轮基准测试
+static BENCHMARK_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 单项基准结果
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkResult {
+    /// "scan" | "thumbnail" | "sqlite" | "clip"
+    pub kind: String,
+    /// 处理的条目数
+    pub items: usize,
+    pub elapsed_ms: u128,
+    /// 条目 / 秒
+    pub rate_per_sec: f64,
+    pub detail: String,
+}
+
+fn make_result(kind: &str, items: usize, elapsed_ms: u128, detail: String) -> BenchmarkResult {
+    let rate = if elapsed_ms == 0 {
+        0.0
+    } else {
+        items as f64 * 1000.0 / elapsed_ms as f64
+    };
+    BenchmarkResult {
+        kind: kind.to_string(),
+        items,
+        elapsed_ms,
+        rate_per_sec: (rate * 10.0).round() / 10.0,
+        detail,
+    }
+}
+
+/// 从索引里取磁盘上仍存在的图片做样本，最多 limit 个
+fn sample_images(pool: &AppDbPool, limit: usize) -> Result<Vec<String>, String> {
+    let conn = pool.get_connection();
+    Ok(db::file_index::get_all_image_files(&conn)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|e| e.path)
+        .filter(|p| Path::new(p).is_file())
+        .take(limit)
+        .collect())
+}
+
+/// 扫描吞吐：遍历库根目录并 stat 每个条目，和索引扫描的磁盘访问模式一致
+fn bench_scan(root: &str) -> Result<BenchmarkResult, String> {
+    if !Path::new(root).is_dir() {
+        return Err(format!("库根目录不存在: {}", root));
+    }
+    let start = Instant::now();
+    let mut items = 0usize;
+    for entry in jwalk::WalkDir::new(root)
+        .process_read_dir(|_, _, _, dir_entry_results| {
+            dir_entry_results.retain(|result| {
+                result
+                    .as_ref()
+                    .map(|entry| {
+                        let name = entry.file_name().to_str().unwrap_or("");
+                        name != ".Aurora_Cache" && !name.starts_with('.')
+                    })
+                    .unwrap_or(true)
+            });
+        })
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let _ = entry.metadata();
+        items += 1;
+    }
+    Ok(make_result(
+        "scan",
+        items,
+        start.elapsed().as_millis(),
+        format!("遍历 {}", root),
+    ))
+}
+
+/// 缩略图生成速率：解码 + 缩放到 256px（内存中完成，不写缓存）
+fn bench_thumbnail(paths: &[String]) -> BenchmarkResult {
+    let start = Instant::now();
+    let mut ok = 0usize;
+    let mut failed = 0usize;
+    for path in paths {
+        match image::open(path) {
+            Ok(img) => {
+                let _ = img.thumbnail(256, 256);
+                ok += 1;
+            }
+            Err(_) => failed += 1,
+        }
+    }
+    make_result(
+        "thumbnail",
+        ok,
+        start.elapsed().as_millis(),
+        if failed > 0 {
+            format!("{} 张解码失败", failed)
+        } else {
+            "解码 + 缩放到 256px".to_string()
+        },
+    )
+}
+
+/// SQLite 写入吞吐：临时库里事务内插入合成行，模式与 file_index 相近
+fn bench_sqlite() -> Result<BenchmarkResult, String> {
+    const ROWS: usize = 5000;
+    let db_path = std::env::temp_dir().join(format!("aurora_bench_{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&db_path);
+    let result = (|| {
+        let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             CREATE TABLE bench (id TEXT PRIMARY KEY, path TEXT, size INTEGER, modified_at INTEGER);",
+        )
+        .map_err(|e| e.to_string())?;
+        let start = Instant::now();
+        conn.execute("BEGIN", []).map_err(|e| e.to_string())?;
+        {
+            let mut stmt = conn
+                .prepare("INSERT INTO bench (id, path, size, modified_at) VALUES (?1, ?2, ?3, ?4)")
+                .map_err(|e| e.to_string())?;
+            for i in 0..ROWS {
+                stmt.execute(rusqlite::params![
+                    format!("id{:06}", i),
+                    format!("C:/bench/sample/{:06}.png", i),
+                    (i * 1024) as i64,
+                    1700000000i64 + i as i64,
+                ])
+                .map_err(|e| e.to_string())?;
+            }
+        }
+        conn.execute("COMMIT", []).map_err(|e| e.to_string())?;
+        Ok(make_result(
+            "sqlite",
+            ROWS,
+            start.elapsed().as_millis(),
+            "临时库事务内插入".to_string(),
+        ))
+    })();
+    let _ = std::fs::remove_file(&db_path);
+    result
+}
+
+/// CLIP 编码速率：对样本图片逐张编码（需要模型已初始化，未加载时自动加载）
+async fn bench_clip(paths: Vec<String>) -> Result<BenchmarkResult, String> {
+    let manager = crate::clip::get_clip_manager()
+        .await
+        .ok_or("CLIP 尚未初始化")?;
+    {
+        let mut guard = manager.write().await;
+        if !guard.is_model_loaded() {
+            guard
+                .load_model()
+                .await
+                .map_err(|e| format!("Failed to load model: {}", e))?;
+        }
+    }
+    let mut guard = manager.write().await;
+    let model = guard.model_mut().ok_or("CLIP 模型不可用")?;
+    let start = Instant::now();
+    let mut ok = 0usize;
+    let mut failed = 0usize;
+    for path in &paths {
+        match model.encode_image(path) {
+            Ok(_) => ok += 1,
+            Err(_) => failed += 1,
+        }
+    }
+    Ok(make_result(
+        "clip",
+        ok,
+        start.elapsed().as_millis(),
+        if failed > 0 {
+            format!("{} 张编码失败", failed)
+        } else {
+            "逐张编码".to_string()
+        },
+    ))
+}
+
+/// 运行基准测试。kind 为 "scan" / "thumbnail" / "sqlite" / "clip" / "all"；
+/// 返回各项的结构化结果，供支持侧与基线对比
+#[tauri::command]
+pub async fn run_benchmark(kind: String, app: tauri::AppHandle) -> Result<Vec<BenchmarkResult>, String> {
+    if BENCHMARK_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("基准测试正在进行中".to_string());
+    }
+    let result = run_benchmark_inner(&kind, &app).await;
+    BENCHMARK_RUNNING.store(false, Ordering::SeqCst);
+    result
+}
+
+async fn run_benchmark_inner(kind: &str, app: &tauri::AppHandle) -> Result<Vec<BenchmarkResult>, String> {
+    let pool = app.state::<AppDbPool>().inner().clone();
+    let mut results = Vec::new();
+
+    if matches!(kind, "scan" | "all") {
+        let root = {
+            let conn = pool.get_connection();
+            db::get_library_setting(&conn, "library_root")
+                .ok()
+                .flatten()
+                .ok_or("尚未记录库根目录，无法测扫描吞吐")?
+        };
+        results.push(
+            tokio::task::spawn_blocking(move || bench_scan(&root))
+                .await
+                .map_err(|e| format!("扫描基准任务失败: {}", e))??,
+        );
+    }
+    if matches!(kind, "thumbnail" | "all") {
+        let samples = sample_images(&pool, 50)?;
+        if samples.is_empty() {
+            return Err("索引中没有可用的样本图片".to_string());
+        }
+        results.push(
+            tokio::task::spawn_blocking(move || bench_thumbnail(&samples))
+                .await
+                .map_err(|e| format!("缩略图基准任务失败: {}", e))?,
+        );
+    }
+    if matches!(kind, "sqlite" | "all") {
+        results.push(
+            tokio::task::spawn_blocking(bench_sqlite)
+                .await
+                .map_err(|e| format!("SQLite 基准任务失败: {}", e))??,
+        );
+    }
+    if matches!(kind, "clip" | "all") {
+        let samples = sample_images(&pool, 16)?;
+        if samples.is_empty() {
+            return Err("索引中没有可用的样本图片".to_string());
+        }
+        // "all" 时 CLIP 不可用只记一条失败项，不拖垮整轮
+        match bench_clip(samples).await {
+            Ok(result) => results.push(result),
+            Err(e) if kind == "all" => {
+                results.push(make_result("clip", 0, 0, format!("跳过: {}", e)))
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    if results.is_empty() {
+        return Err(format!("未知基准类型: {}", kind));
+    }
+    Ok(results)
+}
@@ -4,7 +4,7 @@ use std::path::PathBuf;
 
 fn main() {
     let appdata = env::var("APPDATA").unwrap_or_else(|_| "".to_string());
-    let mut db_path = if !appdata.is_empty() {
+    let db_path = if !appdata.is_empty() {
         let mut p = PathBuf::from(appdata);
         p.push("com.aurora.gallery");
         p.push("metadata.db");
@@ -34,7 +34,7 @@ fn main() {
         let by: Option<f64> = row.get(5).ok();
         let bw: Option<f64> = row.get(6).ok();
         let bh: Option<f64> = row.get(7).ok();
-        let updated: Option<i64> = row.get(8).ok();
+        let _updated: Option<i64> = row.get(8).ok();
         println!("- {} | {} | cover={} | count={} | box={:?}", id, name, cover.unwrap_or_default(), cnt.unwrap_or(0), (bx,by,bw,bh));
     }
 }
\ No newline at end of file
@@ -0,0 +1,406 @@
+//! 自动图片分类：启发式规则 + CLIP 零样本分类。
+//!
+//! 结果写入 file_metadata.category，只填充尚未分类的文件——
+//! 用户手动设置的分类视为最终结果，后台任务不会覆盖。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use rusqlite::OptionalExtension;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::clip;
+use crate::db::{self, AppDbPool};
+
+/// 零样本分类的类别及对应的文本提示
+const CATEGORY_PROMPTS: &[(&str, &str)] = &[
+    ("screenshot", "a screenshot of a computer or phone user interface"),
+    ("photo", "a photograph taken with a camera"),
+    ("artwork", "a digital illustration, painting or anime artwork"),
+    ("document", "a scanned document or a page of printed text"),
+    ("meme", "a funny meme image with overlaid caption text"),
+];
+
+/// 常见屏幕分辨率，PNG 且尺寸完全吻合时直接判定为截图
+const SCREEN_SIZES: &[(u32, u32)] = &[
+    (1280, 720),
+    (1366, 768),
+    (1440, 900),
+    (1920, 1080),
+    (2560, 1440),
+    (2560, 1600),
+    (3840, 2160),
+    (1170, 2532),
+    (1179, 2556),
+    (1290, 2796),
+    (1080, 2340),
+    (1440, 3200),
+];
+
+/// 单飞标志，避免并发跑两个分类任务
+static CLASSIFY_RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ClassifyProgress {
+    processed: usize,
+    total: usize,
+    /// 实际写入分类的文件数
+    classified: usize,
+}
+
+/// 基于文件名和尺寸的启发式判定，命中时跳过 CLIP 推理
+fn heuristic_category(path: &str, width: Option<u32>, height: Option<u32>) -> Option<&'static str> {
+    let name = path.rsplit('/').next().unwrap_or(path).to_lowercase();
+    if name.contains("screenshot") || name.contains("screen shot")
+        || name.contains("截图") || name.contains("截屏")
+    {
+        return Some("screenshot");
+    }
+    // 截图基本都是 PNG 且尺寸与屏幕分辨率完全一致
+    if name.ends_with(".png") {
+        if let (Some(w), Some(h)) = (width, height) {
+            if SCREEN_SIZES.contains(&(w, h)) || SCREEN_SIZES.contains(&(h, w)) {
+                return Some("screenshot");
+            }
+        }
+    }
+    None
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+/// CLIP 零样本分类：相似度乘温度后做 softmax，返回 (胜出标签下标, 置信度)
+fn classify_embedding<T>(
+    embedding: &[f32],
+    label_embeddings: &[(T, Vec<f32>)],
+) -> (usize, f32) {
+    let logits: Vec<f32> = label_embeddings
+        .iter()
+        .map(|(_, text_emb)| cosine_similarity(embedding, text_emb) * 100.0)
+        .collect();
+
+    let max_logit = logits.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+    let exps: Vec<f32> = logits.iter().map(|l| (l - max_logit).exp()).collect();
+    let sum: f32 = exps.iter().sum();
+
+    let (best_idx, best_exp) = exps
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .unwrap_or((0, &exps[0]));
+
+    (best_idx, best_exp / sum)
+}
+
+/// 后台自动分类任务。threshold 为置信度阈值（默认 0.5），低于阈值的文件保持未分类。
+/// 返回实际写入分类的文件数。
+#[tauri::command]
+pub async fn run_auto_classification(
+    threshold: Option<f32>,
+    app: AppHandle,
+) -> Result<usize, String> {
+    if CLASSIFY_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("分类任务已在运行".to_string());
+    }
+
+    let result = run_classification_inner(threshold.unwrap_or(0.5), &app).await;
+    CLASSIFY_RUNNING.store(false, Ordering::SeqCst);
+    result
+}
+
+async fn run_classification_inner(threshold: f32, app: &AppHandle) -> Result<usize, String> {
+    let manager = clip::get_clip_manager()
+        .await
+        .ok_or("CLIP manager not initialized")?;
+
+    // 确保模型已加载，并一次性编码所有类别提示
+    {
+        let guard = manager.read().await;
+        if !guard.is_model_loaded() {
+            drop(guard);
+            let mut guard = manager.write().await;
+            if !guard.is_model_loaded() {
+                guard.load_model().await.map_err(|e| format!("Failed to load model: {}", e))?;
+            }
+        }
+    }
+
+    let (label_embeddings, embeddings) = {
+        let mut guard = manager.write().await;
+        let model = guard.model_mut().ok_or("CLIP model not available")?;
+
+        let mut label_embeddings: Vec<(&'static str, Vec<f32>)> = Vec::new();
+        for (label, prompt) in CATEGORY_PROMPTS {
+            label_embeddings.push((label, model.encode_text(prompt)?));
+        }
+
+        let store = guard.embedding_store().ok_or("Embedding store not available")?;
+        let embeddings = store.get_all_embeddings()?;
+        (label_embeddings, embeddings)
+    };
+
+    let pool = app.state::<AppDbPool>().inner().clone();
+    let app = app.clone();
+    let total = embeddings.len();
+
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get_connection();
+        let mut classified = 0usize;
+
+        for (i, emb) in embeddings.iter().enumerate() {
+            // 已有分类（用户手动或上次任务写入）的文件不再碰
+            let existing: Option<Option<String>> = conn
+                .query_row(
+                    "SELECT category FROM file_metadata WHERE file_id = ?1",
+                    rusqlite::params![emb.file_id],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?;
+            if matches!(existing, Some(Some(ref c)) if !c.is_empty()) {
+                continue;
+            }
+
+            let entry = db::file_index::get_entry_by_id(&conn, &emb.file_id)
+                .map_err(|e| e.to_string())?;
+
+            let category = entry
+                .as_ref()
+                .and_then(|e| heuristic_category(&e.path, e.width, e.height))
+                .or_else(|| {
+                    let (idx, confidence) = classify_embedding(&emb.embedding, &label_embeddings);
+                    if confidence >= threshold { Some(label_embeddings[idx].0) } else { None }
+                });
+
+            if let Some(category) = category {
+                db::file_metadata::set_category(&conn, &emb.file_id, Some(category))
+                    .map_err(|e| e.to_string())?;
+                classified += 1;
+            }
+
+            if (i + 1).is_multiple_of(50) || i + 1 == total {
+                let _ = app.emit("classify-progress", ClassifyProgress {
+                    processed: i + 1,
+                    total,
+                    classified,
+                });
+            }
+        }
+
+        Ok(classified)
+    })
+    .await
+    .map_err(|e| format!("Classification task failed: {}", e))?
+}
+
+/// 查询分类任务是否在跑，供前端刷新按钮状态
+#[tauri::command]
+pub fn is_classification_running() -> bool {
+    CLASSIFY_RUNNING.load(Ordering::SeqCst)
+}
+
+/// NSFW 零样本判定用的文本提示
+const NSFW_PROMPTS: &[(&str, &str)] = &[
+    ("sfw", "a normal safe for work photo, illustration or screenshot"),
+    ("nsfw", "explicit adult content with nudity or sexual activity"),
+];
+
+/// NSFW 扫描单飞标志
+static NSFW_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 后台 NSFW 扫描：对所有已有 CLIP 嵌入的文件做零样本判定，
+/// 把结果写入 file_metadata.sensitive。已有标记（手动或上次扫描）的文件不再碰，
+/// 手动修改即为最终结果。threshold 为判定敏感所需的置信度（默认 0.7）。
+/// 返回新标记为敏感的文件数。
+#[tauri::command]
+pub async fn run_nsfw_scan(threshold: Option<f32>, app: AppHandle) -> Result<usize, String> {
+    if NSFW_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("NSFW 扫描已在运行".to_string());
+    }
+
+    let result = run_nsfw_scan_inner(threshold.unwrap_or(0.7), &app).await;
+    NSFW_RUNNING.store(false, Ordering::SeqCst);
+    result
+}
+
+async fn run_nsfw_scan_inner(threshold: f32, app: &AppHandle) -> Result<usize, String> {
+    let manager = clip::get_clip_manager()
+        .await
+        .ok_or("CLIP manager not initialized")?;
+
+    {
+        let guard = manager.read().await;
+        if !guard.is_model_loaded() {
+            drop(guard);
+            let mut guard = manager.write().await;
+            if !guard.is_model_loaded() {
+                guard.load_model().await.map_err(|e| format!("Failed to load model: {}", e))?;
+            }
+        }
+    }
+
+    let (label_embeddings, embeddings) = {
+        let mut guard = manager.write().await;
+        let model = guard.model_mut().ok_or("CLIP model not available")?;
+
+        let mut label_embeddings: Vec<(&'static str, Vec<f32>)> = Vec::new();
+        for (label, prompt) in NSFW_PROMPTS {
+            label_embeddings.push((label, model.encode_text(prompt)?));
+        }
+
+        let store = guard.embedding_store().ok_or("Embedding store not available")?;
+        let embeddings = store.get_all_embeddings()?;
+        (label_embeddings, embeddings)
+    };
+
+    let pool = app.state::<AppDbPool>().inner().clone();
+    let app = app.clone();
+    let total = embeddings.len();
+
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get_connection();
+        let mut flagged = 0usize;
+
+        for (i, emb) in embeddings.iter().enumerate() {
+            // 已有标记的文件（手动设置或上次扫描结果）跳过
+            let existing: Option<Option<bool>> = conn
+                .query_row(
+                    "SELECT sensitive FROM file_metadata WHERE file_id = ?1",
+                    rusqlite::params![emb.file_id],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?;
+            if matches!(existing, Some(Some(_))) {
+                continue;
+            }
+
+            let (idx, confidence) = classify_embedding(&emb.embedding, &label_embeddings);
+            let sensitive = label_embeddings[idx].0 == "nsfw" && confidence >= threshold;
+            db::file_metadata::set_sensitive(&conn, &emb.file_id, sensitive)
+                .map_err(|e| e.to_string())?;
+            if sensitive {
+                flagged += 1;
+            }
+
+            if (i + 1).is_multiple_of(50) || i + 1 == total {
+                let _ = app.emit("nsfw-scan-progress", ClassifyProgress {
+                    processed: i + 1,
+                    total,
+                    classified: flagged,
+                });
+            }
+        }
+
+        Ok(flagged)
+    })
+    .await
+    .map_err(|e| format!("NSFW scan task failed: {}", e))?
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ZeroShotResult {
+    pub file_id: String,
+    /// 胜出标签；文件没有嵌入或置信度不够时为 None
+    pub label: Option<String>,
+    pub confidence: f32,
+    /// 是否已把标签写进 imported_tags
+    pub applied: bool,
+}
+
+/// 用户自定义标签集的批量零样本打标：把每张图的嵌入和各标签文本比分，
+/// 返回胜出标签与置信度。apply_tags 为 true 时把胜出标签并进
+/// imported_tags（category 字段留给固定类别的自动分类任务，互不干扰）。
+/// 只用已有的图片嵌入，不做图片推理，未入嵌入库的文件返回 None
+#[tauri::command]
+pub async fn clip_zero_shot_classify(
+    file_ids: Vec<String>,
+    labels: Vec<String>,
+    apply_tags: Option<bool>,
+    threshold: Option<f32>,
+    app: AppHandle,
+) -> Result<Vec<ZeroShotResult>, String> {
+    if labels.is_empty() {
+        return Err("标签列表不能为空".to_string());
+    }
+    let threshold = threshold.unwrap_or(0.0);
+    let apply = apply_tags.unwrap_or(false);
+
+    let manager = clip::get_clip_manager()
+        .await
+        .ok_or("CLIP manager not initialized")?;
+    {
+        let guard = manager.read().await;
+        if !guard.is_model_loaded() {
+            drop(guard);
+            let mut guard = manager.write().await;
+            if !guard.is_model_loaded() {
+                guard.load_model().await.map_err(|e| format!("Failed to load model: {}", e))?;
+            }
+        }
+    }
+
+    let (label_embeddings, store) = {
+        let mut guard = manager.write().await;
+        let model = guard.model_mut().ok_or("CLIP model not available")?;
+        let mut label_embeddings: Vec<(String, Vec<f32>)> = Vec::with_capacity(labels.len());
+        for label in &labels {
+            // 标签套进提示模板，零样本效果比裸词稳定
+            label_embeddings.push((
+                label.clone(),
+                model.encode_text(&format!("a photo of {}", label))?,
+            ));
+        }
+        let store = guard.embedding_store().ok_or("Embedding store not available")?.clone();
+        (label_embeddings, store)
+    };
+
+    let pool = app.state::<AppDbPool>().inner().clone();
+    tokio::task::spawn_blocking(move || -> Result<Vec<ZeroShotResult>, String> {
+        let conn = pool.get_connection();
+        let mut results = Vec::with_capacity(file_ids.len());
+        for file_id in file_ids {
+            let Some(emb) = store.get_embedding(&file_id)? else {
+                results.push(ZeroShotResult { file_id, label: None, confidence: 0.0, applied: false });
+                continue;
+            };
+            let (idx, confidence) = classify_embedding(&emb.embedding, &label_embeddings);
+            if confidence < threshold {
+                results.push(ZeroShotResult { file_id, label: None, confidence, applied: false });
+                continue;
+            }
+            let label = label_embeddings[idx].0.clone();
+            let mut applied = false;
+            if apply {
+                if let Ok(Some(entry)) = db::file_index::get_entry_by_id(&conn, &file_id) {
+                    applied = db::file_metadata::merge_imported_tags(
+                        &conn,
+                        &file_id,
+                        &entry.path,
+                        std::slice::from_ref(&label),
+                    )
+                    .is_ok();
+                }
+            }
+            results.push(ZeroShotResult { file_id, label: Some(label), confidence, applied });
+        }
+        Ok(results)
+    })
+    .await
+    .map_err(|e| format!("零样本打标任务失败: {}", e))?
+}
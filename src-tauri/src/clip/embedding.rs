@@ -1,6 +1,6 @@
 //! CLIP 向量嵌入存储和管理
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 use rusqlite::{Connection, params};
 use serde::{Serialize, Deserialize};
@@ -26,7 +26,7 @@ pub struct EmbeddingStore {
 
 impl EmbeddingStore {
     /// 创建新的嵌入存储
-    pub fn new(cache_dir: &PathBuf) -> Result<Self, String> {
+    pub fn new(cache_dir: &Path) -> Result<Self, String> {
         let db_path = cache_dir.join("embeddings.db");
         
         // 确保数据库表存在
@@ -58,9 +58,24 @@ impl EmbeddingStore {
             [],
         ).map_err(|e| format!("Failed to create index: {}", e))?;
 
+        // “更多相似图片”的预计算结果（neighbors 为 JSON 数组，见 crate::related）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS related_images (
+                file_id TEXT PRIMARY KEY,
+                neighbors TEXT NOT NULL,
+                computed_at INTEGER NOT NULL
+            )",
+            [],
+        ).map_err(|e| format!("Failed to create related_images table: {}", e))?;
+
         Ok(())
     }
 
+    /// 嵌入数据库文件路径（健康检查用）
+    pub fn db_path(&self) -> &Path {
+        &self.db_path
+    }
+
     /// 获取数据库连接
     fn get_connection(&self) -> Result<Connection, String> {
         Connection::open(&self.db_path)
@@ -114,6 +129,95 @@ impl EmbeddingStore {
         Ok(())
     }
 
+    /// 批量改写嵌入的 file_id（文件夹合并、ID 命名空间迁移后键整体变化）。
+    /// 单个事务完成，避免逐条查询 + 逐条开连接
+    pub fn migrate_embeddings_batch(&self, pairs: &[(String, String)]) -> Result<(), String> {
+        if pairs.is_empty() {
+            return Ok(());
+        }
+        let mut conn = self.get_connection()?;
+        let tx = conn.transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        {
+            let mut stmt = tx.prepare(
+                "UPDATE OR REPLACE image_embeddings SET file_id = ?1 WHERE file_id = ?2"
+            ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
+            for (old_id, new_id) in pairs {
+                stmt.execute(params![new_id, old_id])
+                    .map_err(|e| format!("Failed to migrate embedding: {}", e))?;
+            }
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+        Ok(())
+    }
+
+    /// 只取所有 file_id（不读嵌入 BLOB，导入去重时用）
+    pub fn get_all_file_ids(&self) -> Result<Vec<String>, String> {
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare("SELECT file_id FROM image_embeddings")
+            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        let rows = stmt.query_map([], |row| row.get(0))
+            .map_err(|e| format!("Failed to query file ids: {}", e))?;
+        Ok(rows.flatten().collect())
+    }
+
+    /// 读取某张图预计算的最近邻 JSON（没算过时为 None）
+    pub fn get_related(&self, file_id: &str) -> Result<Option<(String, i64)>, String> {
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT neighbors, computed_at FROM related_images WHERE file_id = ?1"
+        ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        stmt.query_row(params![file_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map(Some)
+            .or_else(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => Ok(None),
+                other => Err(format!("Failed to query related: {}", other)),
+            })
+    }
+
+    /// 已预计算最近邻的 (file_id, computed_at) 列表，预计算任务用来跳过未变化的图
+    pub fn get_related_timestamps(&self) -> Result<Vec<(String, i64)>, String> {
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare("SELECT file_id, computed_at FROM related_images")
+            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| format!("Failed to query related timestamps: {}", e))?;
+        Ok(rows.flatten().collect())
+    }
+
+    /// 批量写入预计算的最近邻（file_id, neighbors JSON, computed_at）
+    pub fn save_related_batch(&self, rows: &[(String, String, i64)]) -> Result<(), String> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let mut conn = self.get_connection()?;
+        let tx = conn.transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO related_images (file_id, neighbors, computed_at)
+                 VALUES (?1, ?2, ?3)"
+            ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
+            for (file_id, neighbors, computed_at) in rows {
+                stmt.execute(params![file_id, neighbors, computed_at])
+                    .map_err(|e| format!("Failed to save related: {}", e))?;
+            }
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+        Ok(())
+    }
+
+    /// 删除已无对应嵌入的预计算行（文件被删除后清理）
+    pub fn prune_related(&self) -> Result<usize, String> {
+        let conn = self.get_connection()?;
+        conn.execute(
+            "DELETE FROM related_images WHERE file_id NOT IN (SELECT file_id FROM image_embeddings)",
+            [],
+        ).map_err(|e| format!("Failed to prune related: {}", e))
+    }
+
     /// 获取单个嵌入
     pub fn get_embedding(&self, file_id: &str) -> Result<Option<ImageEmbedding>, String> {
         let conn = self.get_connection()?;
@@ -1,10 +1,10 @@
 //! CLIP 模型加载和推理
 //! 支持 ONNX 格式的 CLIP 模型，使用 ONNX Runtime 进行 GPU 加速推理
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use once_cell::sync::OnceCell;
 use ort::session::Session;
-use ort::value::{Tensor, Value};
+use ort::value::Tensor;
 use ort::ep::ExecutionProvider;
 
 use super::ClipConfig;
@@ -214,7 +214,7 @@ impl ClipModel {
 
     /// 确保模型文件存在，如果不存在则下载
     async fn ensure_model_file(url: &str, cache_dir: &PathBuf) -> Result<PathBuf, String> {
-        let file_name = url.split('/').last().ok_or("Invalid URL")?;
+        let file_name = url.split('/').next_back().ok_or("Invalid URL")?;
         let file_path = cache_dir.join(file_name);
 
         if file_path.exists() {
@@ -247,16 +247,16 @@ impl ClipModel {
     }
     
     /// 检查模型文件是否存在于本地
-    pub fn check_local_model_files(cache_dir: &PathBuf, model_name: &str) -> Result<bool, String> {
+    pub fn check_local_model_files(cache_dir: &Path, model_name: &str) -> Result<bool, String> {
         let model_info = match model_name {
             "ViT-B-32" => ModelInfo::vit_b_32(),
             "ViT-L-14" => ModelInfo::vit_l_14(),
             _ => return Err(format!("Unknown model: {}", model_name)),
         };
         
-        let image_file = model_info.image_model_url.split('/').last().unwrap_or("vision_model.onnx");
-        let text_file = model_info.text_model_url.split('/').last().unwrap_or("text_model.onnx");
-        let tokenizer_file = model_info.tokenizer_url.split('/').last().unwrap_or("tokenizer.json");
+        let image_file = model_info.image_model_url.split('/').next_back().unwrap_or("vision_model.onnx");
+        let text_file = model_info.text_model_url.split('/').next_back().unwrap_or("text_model.onnx");
+        let tokenizer_file = model_info.tokenizer_url.split('/').next_back().unwrap_or("tokenizer.json");
         
         let image_path = cache_dir.join(image_file);
         let text_path = cache_dir.join(text_file);
@@ -295,7 +295,42 @@ impl ClipModel {
             .map_err(|e| format!("Failed to extract embedding: {}", e))?;
 
         // 转换为 Vec<f32> 并归一化
-        let mut vec: Vec<f32> = embedding_data.iter().copied().collect();
+        let mut vec: Vec<f32> = embedding_data.to_vec();
+        normalize_vector(&mut vec);
+
+        Ok(vec)
+    }
+
+    /// 编码内存中的图像数据 - 用于不在图库中的图片（如拖入的外部文件）
+    pub fn encode_image_bytes(&mut self, image_data: &[u8]) -> Result<Vec<f32>, String> {
+        if image_data.is_empty() {
+            return Err("Empty image data provided".to_string());
+        }
+
+        // 获取会话 - 需要可变引用
+        let session = self.vision_session.as_mut()
+            .ok_or("Vision model not loaded")?;
+
+        // 从字节解码并预处理为 NCHW 格式张量
+        let tensor_data = self.image_preprocessor.preprocess_bytes(image_data)
+            .map_err(|e| format!("Failed to preprocess image data: {}", e))?;
+
+        // 创建输入 Tensor - 使用 (shape, data) 元组格式
+        let input_shape: Vec<i64> = vec![1, 3, self.model_info.image_size as i64, self.model_info.image_size as i64];
+        let input_tensor = Tensor::from_array((input_shape, tensor_data.into_boxed_slice()))
+            .map_err(|e| format!("Failed to create input tensor: {}", e))?;
+
+        // 执行推理 - session.run 需要可变引用
+        let outputs = session.run(vec![("pixel_values", input_tensor)])
+            .map_err(|e| format!("Failed to run inference: {}", e))?;
+
+        // 提取嵌入向量 - try_extract_tensor 返回 (Shape, &[f32])
+        let (_shape, embedding_data): (&ort::tensor::Shape, &[f32]) = outputs["image_embeds"]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| format!("Failed to extract embedding: {}", e))?;
+
+        // 转换为 Vec<f32> 并归一化
+        let mut vec: Vec<f32> = embedding_data.to_vec();
         normalize_vector(&mut vec);
 
         Ok(vec)
@@ -318,12 +353,12 @@ impl ClipModel {
 
         // 创建输入 Tensors - 使用 (shape, data) 元组格式
         let input_ids_shape: Vec<i64> = vec![1, input_ids.len() as i64];
-        let input_ids_data: Vec<i64> = input_ids.into_iter().map(|x| x as i64).collect();
+        let input_ids_data: Vec<i64> = input_ids.into_iter().collect();
         let input_ids_tensor = Tensor::from_array((input_ids_shape, input_ids_data.into_boxed_slice()))
             .map_err(|e| format!("Failed to create input_ids tensor: {}", e))?;
 
         let attention_mask_shape: Vec<i64> = vec![1, attention_mask.len() as i64];
-        let attention_mask_data: Vec<i64> = attention_mask.into_iter().map(|x| x as i64).collect();
+        let attention_mask_data: Vec<i64> = attention_mask.into_iter().collect();
         let attention_mask_tensor = Tensor::from_array((attention_mask_shape, attention_mask_data.into_boxed_slice()))
             .map_err(|e| format!("Failed to create attention_mask tensor: {}", e))?;
 
@@ -341,7 +376,7 @@ impl ClipModel {
             .map_err(|e| format!("Failed to extract embedding: {}", e))?;
 
         // 转换为 Vec<f32> 并归一化
-        let mut vec: Vec<f32> = embedding_data.iter().copied().collect();
+        let mut vec: Vec<f32> = embedding_data.to_vec();
         normalize_vector(&mut vec);
 
         Ok(vec)
@@ -423,7 +458,7 @@ impl ClipModel {
         let mut results = Vec::with_capacity(actual_batch_size);
         
         // 将扁平化的张量转换为每行一个向量的格式
-        let flat_embeddings: Vec<f32> = embeddings_data.iter().copied().collect();
+        let flat_embeddings: Vec<f32> = embeddings_data.to_vec();
         for i in 0..actual_batch_size {
             let start = i * embedding_dim;
             let end = start + embedding_dim;
@@ -26,7 +26,21 @@ impl ImagePreprocessor {
         // 使用 image 库加载图像
         let img = image::open(image_path)
             .map_err(|e| format!("Failed to open image {}: {}", image_path, e))?;
-        
+
+        self.preprocess_dynamic_image(img)
+    }
+
+    /// 预处理内存中的图像数据（例如拖入搜索框的外部图片）
+    /// 与 `preprocess` 相同，只是从字节解码而不是从文件读取
+    pub fn preprocess_bytes(&self, image_data: &[u8]) -> Result<Vec<f32>, String> {
+        let img = image::load_from_memory(image_data)
+            .map_err(|e| format!("Failed to decode image data: {}", e))?;
+
+        self.preprocess_dynamic_image(img)
+    }
+
+    /// 对已解码的图像执行缩放和归一化
+    fn preprocess_dynamic_image(&self, img: image::DynamicImage) -> Result<Vec<f32>, String> {
         // 如果图像尺寸过大，先进行快速下采样以提高性能
         let (width, height) = (img.width(), img.height());
         let max_dimension = 1024u32; // 最大维度限制
@@ -77,7 +91,7 @@ impl ImagePreprocessor {
         // 提取像素并归一化
         // CLIP 使用 ImageNet 归一化: mean=[0.48145466, 0.4578275, 0.40821073], std=[0.26862954, 0.26130258, 0.27577711]
         let mean = [0.48145466f32, 0.4578275f32, 0.40821073f32];
-        let std = [0.26862954f32, 0.26130258f32, 0.27577711f32];
+        let std = [0.26862954f32, 0.261_302_6_f32, 0.275_777_1_f32];
         
         let mut tensor = vec![0.0f32; 3 * self.target_size * self.target_size];
         
@@ -87,10 +101,10 @@ impl ImagePreprocessor {
             
             // NCHW 格式: [batch=0, channel, y, x]
             // R channel
-            tensor[0 * self.target_size * self.target_size + y * self.target_size + x] = 
+            tensor[y * self.target_size + x] = 
                 (pixel[0] as f32 / 255.0 - mean[0]) / std[0];
             // G channel
-            tensor[1 * self.target_size * self.target_size + y * self.target_size + x] = 
+            tensor[self.target_size * self.target_size + y * self.target_size + x] = 
                 (pixel[1] as f32 / 255.0 - mean[1]) / std[1];
             // B channel
             tensor[2 * self.target_size * self.target_size + y * self.target_size + x] = 
@@ -298,6 +298,73 @@ impl VectorIndex {
     }
 }
 
+/// 可组合的重排序阶段：在一路主搜索结果上按权重叠加若干额外信号。
+///
+/// 主路分数在结果集内做 min-max 归一化（不同来源的分数量纲不一致，
+/// 余弦相似度和调色板距离不能直接相加）；各信号的分数由调用方保证
+/// 已在 0-1 区间，结果集里查不到的条目按 0 分计。最终分数是加权平均，
+/// 权重全为 0 时原样返回。
+pub struct Reranker {
+    /// 主路分数的权重
+    base_weight: f32,
+    /// (权重, file_id -> 0-1 分数) 的信号列表
+    signals: Vec<(f32, std::collections::HashMap<String, f32>)>,
+}
+
+impl Reranker {
+    /// 创建重排序器，`base_weight` 是主路分数的权重
+    pub fn new(base_weight: f32) -> Self {
+        Self {
+            base_weight,
+            signals: Vec::new(),
+        }
+    }
+
+    /// 追加一路信号（builder 风格，可链式叠加多路）
+    pub fn with_signal(
+        mut self,
+        weight: f32,
+        scores: std::collections::HashMap<String, f32>,
+    ) -> Self {
+        self.signals.push((weight, scores));
+        self
+    }
+
+    /// min-max 归一化到 0-1；所有分数相同时全部记 1
+    fn normalize(values: &[f32]) -> Vec<f32> {
+        let min = values.iter().cloned().fold(f32::INFINITY, f32::min);
+        let max = values.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        if max - min <= f32::EPSILON {
+            return vec![1.0; values.len()];
+        }
+        values.iter().map(|v| (v - min) / (max - min)).collect()
+    }
+
+    /// 重新打分并按新分数降序截取前 top_k 条，排名同步更新
+    pub fn apply(&self, mut results: Vec<SearchResult>, top_k: usize) -> Vec<SearchResult> {
+        let total: f32 = self.base_weight + self.signals.iter().map(|(w, _)| w).sum::<f32>();
+        if !results.is_empty() && total > 0.0 {
+            let base_scores: Vec<f32> = results.iter().map(|r| r.score).collect();
+            let base_norm = Self::normalize(&base_scores);
+
+            for (i, result) in results.iter_mut().enumerate() {
+                let mut score = self.base_weight * base_norm[i];
+                for (weight, scores) in &self.signals {
+                    score += weight * scores.get(&result.file_id).copied().unwrap_or(0.0);
+                }
+                result.score = score / total;
+            }
+        }
+
+        results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+        results.truncate(top_k);
+        for (i, result) in results.iter_mut().enumerate() {
+            result.rank = i + 1;
+        }
+        results
+    }
+}
+
 /// 混合搜索（结合多种搜索方式）
 pub struct HybridSearcher {
     clip_searcher: SimilaritySearcher,
@@ -384,13 +451,42 @@ mod tests {
         let mut index = VectorIndex::new(3);
         
         index.add("file1".to_string(), vec![1.0, 0.0, 0.0]);
-        add.add("file2".to_string(), vec![0.0, 1.0, 0.0]);
-        add.add("file3".to_string(), vec![0.0, 0.0, 1.0]);
+        index.add("file2".to_string(), vec![0.0, 1.0, 0.0]);
+        index.add("file3".to_string(), vec![0.0, 0.0, 1.0]);
 
         let results = index.search(&[1.0, 0.0, 0.0], 2);
-        
+
         assert_eq!(results.len(), 2);
         assert_eq!(results[0].file_id, "file1");
         assert!(results[0].score > 0.99);
     }
+
+    #[test]
+    fn test_reranker_signal_promotes_result() {
+        let results = vec![
+            SearchResult { file_id: "a".to_string(), score: 0.9, rank: 1 },
+            SearchResult { file_id: "b".to_string(), score: 0.8, rank: 2 },
+            SearchResult { file_id: "c".to_string(), score: 0.1, rank: 3 },
+        ];
+
+        // 信号强烈偏向 b，权重足以反超主路分数差距
+        let mut scores = std::collections::HashMap::new();
+        scores.insert("b".to_string(), 1.0);
+
+        let reranked = Reranker::new(0.5).with_signal(0.5, scores).apply(results, 3);
+        assert_eq!(reranked[0].file_id, "b");
+        assert_eq!(reranked[0].rank, 1);
+        assert_eq!(reranked[1].file_id, "a");
+    }
+
+    #[test]
+    fn test_reranker_zero_weights_keeps_order() {
+        let results = vec![
+            SearchResult { file_id: "a".to_string(), score: 0.9, rank: 1 },
+            SearchResult { file_id: "b".to_string(), score: 0.8, rank: 2 },
+        ];
+        let reranked = Reranker::new(0.0).apply(results, 2);
+        assert_eq!(reranked[0].file_id, "a");
+        assert!((reranked[0].score - 0.9).abs() < 1e-6);
+    }
 }
@@ -4,7 +4,6 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use rusqlite::{Connection, params};
 use std::fs;
 use std::time::{SystemTime, Duration};
-use serde_json;
 use palette::{FromColor, Srgb, Lab};
 
 use crate::color_extractor::ColorResult;
@@ -13,6 +12,21 @@ use crate::color_extractor::ColorResult;
 pub struct CachedImage {
     pub file_path: String,
     pub labs: Vec<Lab>,
+    /// 与 labs 对齐的面积占比（0-100）。旧数据没有占比时全为 0
+    pub weights: Vec<f32>,
+}
+
+// 辅助函数：把颜色结果转换为对齐的 Lab 与占比权重
+fn labs_and_weights(colors: &[ColorResult]) -> (Vec<Lab>, Vec<f32>) {
+    let mut labs = Vec::with_capacity(colors.len());
+    let mut weights = Vec::with_capacity(colors.len());
+    for c in colors {
+        if let Some(lab) = hex_to_lab(&c.hex) {
+            labs.push(lab);
+            weights.push(c.percentage);
+        }
+    }
+    (labs, weights)
 }
 
 // Helper for cache conversion
@@ -316,10 +330,10 @@ impl ColorDbPool {
                 if let Err(e) = pool.refresh_cache_in_batches(500) {
                     eprintln!("[ColorDB] background preheat failed: {}", e);
                     // allow retry on next ensure call
-                    let _ = pool.cache_inited.store(false, Ordering::SeqCst);
+                    pool.cache_inited.store(false, Ordering::SeqCst);
                 } else {
                     // Only set cache_inited to true after successful loading
-                    let _ = pool.cache_inited.store(true, Ordering::SeqCst);
+                    pool.cache_inited.store(true, Ordering::SeqCst);
                 }
                 // Reset initializing flag
                 INITIALIZING.store(false, Ordering::SeqCst);
@@ -330,7 +344,7 @@ impl ColorDbPool {
             let res = self.refresh_cache();
             if res.is_ok() {
                 // Only set cache_inited to true after successful loading
-                let _ = self.cache_inited.store(true, Ordering::SeqCst);
+                self.cache_inited.store(true, Ordering::SeqCst);
             }
             // Reset initializing flag
             INITIALIZING.store(false, Ordering::SeqCst);
@@ -350,8 +364,18 @@ impl ColorDbPool {
 
     // Load DB rows in batches and append to cache to avoid big IO/CPU spike
     pub fn refresh_cache_in_batches(&self, batch_size: usize) -> Result<()> {
+        // 低内存模式下限制缓存条目数，超大图库只缓存前 10 万条
+        let max_cached: usize = if crate::is_low_memory() { 100_000 } else { usize::MAX };
         let mut offset: i64 = 0;
         loop {
+            {
+                let cache = self.cache.read().map_err(|e| e.to_string())?;
+                if cache.len() >= max_cached {
+                    eprintln!("Color cache capped at {} entries (low-memory mode)", cache.len());
+                    break;
+                }
+            }
+
             let batch = self.load_from_db_internal_batch(offset, batch_size as i64)?;
             if batch.is_empty() {
                 break;
@@ -359,7 +383,7 @@ impl ColorDbPool {
 
             {
                 let mut cache = self.cache.write().map_err(|e| e.to_string())?;
-                cache.extend(batch.into_iter());
+                cache.extend(batch);
             }
 
             // Small pause to reduce IO burst on startup
@@ -385,18 +409,15 @@ impl ColorDbPool {
          }).map_err(|e| e.to_string())?;
 
          let mut results = Vec::new();
-         for row in rows {
-             if let Ok((file_path, colors_json)) = row {
-                 if let Ok(colors) = serde_json::from_str::<Vec<ColorResult>>(&colors_json) {
-                     let labs = colors.into_iter()
-                         .filter_map(|c| hex_to_lab(&c.hex))
-                         .collect();
-
-                     results.push(CachedImage {
-                         file_path,
-                         labs,
-                     });
-                 }
+         for (file_path, colors_json) in rows.flatten() {
+             if let Ok(colors) = serde_json::from_str::<Vec<ColorResult>>(&colors_json) {
+                 let (labs, weights) = labs_and_weights(&colors);
+
+                 results.push(CachedImage {
+                     file_path,
+                     labs,
+                     weights,
+                 });
              }
          }
          Ok(results)
@@ -558,11 +579,8 @@ impl ColorDbPool {
                 // 但为了 UI 即时反馈，我们还是查询一下新数据吧。
                 
                 // 为了避免巨大的查询，我们只查询目标路径下的数据
-                let _dest_pattern = if dest_normalized.ends_with('/') {
-                    format!("{}%", dest_normalized) 
-                } else {
-                    format!("{}%", dest_normalized) // 这里简化处理，可能是文件也可能是目录
-                };
+                // 这里简化处理，可能是文件也可能是目录
+                let _dest_pattern = format!("{}%", dest_normalized);
 
                 // 注意：这里需要一个新的连接，因为之前的 tx 已经 commit 并且 conn 被借用了（虽然已经释放）。
                 // 但 self.conn 是 Mutex，我们需要小心死锁。self.get_connection() 会返回新的 Connection (如果池化) 或者是锁。
@@ -585,10 +603,8 @@ impl ColorDbPool {
                     Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
                 }).map_err(|e| e.to_string())?;
                 
-                for row in rows {
-                    if let Ok((path, colors_json)) = row {
-                        self.update_cache_item(&mut cache, &path, &colors_json);
-                    }
+                for (path, colors_json) in rows.flatten() {
+                    self.update_cache_item(&mut cache, &path, &colors_json);
                 }
             }
         }
@@ -598,16 +614,16 @@ impl ColorDbPool {
     // 辅助函数：更新缓存项
     fn update_cache_item(&self, cache: &mut Vec<CachedImage>, path: &str, colors_json: &str) {
         if let Ok(color_results) = serde_json::from_str::<Vec<ColorResult>>(colors_json) {
-            let labs: Vec<Lab> = color_results.iter()
-                .filter_map(|c| hex_to_lab(&c.hex))
-                .collect();
+            let (labs, weights) = labs_and_weights(&color_results);
             
             if let Some(pos) = cache.iter().position(|x| x.file_path == path) {
                 cache[pos].labs = labs;
+                cache[pos].weights = weights;
             } else {
                 cache.push(CachedImage {
                     file_path: path.to_string(),
                     labs,
+                    weights,
                 });
             }
         }
@@ -627,18 +643,15 @@ impl ColorDbPool {
          }).map_err(|e| e.to_string())?;
  
          let mut results = Vec::new();
-         for row in rows {
-             if let Ok((file_path, colors_json)) = row {
-                 if let Ok(colors) = serde_json::from_str::<Vec<ColorResult>>(&colors_json) {
-                     let labs = colors.into_iter()
-                         .filter_map(|c| hex_to_lab(&c.hex))
-                         .collect();
-
-                     results.push(CachedImage {
-                         file_path,
-                         labs,
-                     });
-                 }
+         for (file_path, colors_json) in rows.flatten() {
+             if let Ok(colors) = serde_json::from_str::<Vec<ColorResult>>(&colors_json) {
+                 let (labs, weights) = labs_and_weights(&colors);
+
+                 results.push(CachedImage {
+                     file_path,
+                     labs,
+                     weights,
+                 });
              }
          }
          Ok(results)
@@ -690,18 +703,18 @@ impl ColorDbPool {
         tx.commit().map_err(|e| format!("Failed to commit transaction: {}", e))?;
         
         // Update Cache
-        let labs: Vec<Lab> = colors.iter()
-            .filter_map(|c| hex_to_lab(&c.hex))
-            .collect();
+        let (labs, weights) = labs_and_weights(colors);
         
         let mut cache = self.cache.write().map_err(|e| e.to_string())?;
         
         if let Some(pos) = cache.iter().position(|x| x.file_path == normalized_path) {
             cache[pos].labs = labs;
+            cache[pos].weights = weights;
         } else {
             cache.push(CachedImage {
                 file_path: normalized_path.clone(),
                 labs,
+                weights,
             });
         }
         Ok(())
@@ -788,16 +801,16 @@ impl ColorDbPool {
                 let mut cache = self.cache.write().map_err(|e| e.to_string())?;
                 for (file_path, colors) in color_data {
                      let normalized_path = file_path.replace("\\", "/");
-                     let labs: Vec<Lab> = colors.iter()
-                         .filter_map(|c| hex_to_lab(&c.hex))
-                         .collect();
+                     let (labs, weights) = labs_and_weights(colors);
                      
                      if let Some(pos) = cache.iter().position(|x| x.file_path == normalized_path) {
                          cache[pos].labs = labs;
+                         cache[pos].weights = weights;
                      } else {
                          cache.push(CachedImage {
                              file_path: normalized_path,
                              labs,
+                             weights,
                          });
                      }
                 }
@@ -827,7 +840,7 @@ impl ColorDbPool {
         eprintln!("Resolved DB path: {}", db_path.display());
         eprintln!("Resolved WAL path: {}", wal_path.display());
         
-        let db_size = fs::metadata(&db_path)
+        let db_size = fs::metadata(db_path)
             .map(|m| {
                 let size = m.len();
                 eprintln!("DB file metadata found, size: {} bytes", size);
@@ -921,7 +934,13 @@ pub fn init_db(conn: &mut Connection) -> Result<()> {
         "CREATE INDEX IF NOT EXISTS idx_file_path ON dominant_colors(file_path)",
         [],
     ).map_err(|e| e.to_string())?;
-    
+
+    // move_colors 的 LIKE 前缀改写要有 NOCASE 索引才能避开全表扫描
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_file_path_nocase ON dominant_colors(file_path COLLATE NOCASE)",
+        [],
+    ).map_err(|e| e.to_string())?;
+
     conn.execute(
         "CREATE INDEX IF NOT EXISTS idx_status ON dominant_colors(status)",
         [],
@@ -947,7 +966,12 @@ pub fn init_db(conn: &mut Connection) -> Result<()> {
         "CREATE INDEX IF NOT EXISTS idx_color_indices_file_path ON image_color_indices(file_path)",
         [],
     ).map_err(|e| e.to_string())?;
-    
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_color_indices_file_path_nocase ON image_color_indices(file_path COLLATE NOCASE)",
+        [],
+    ).map_err(|e| e.to_string())?;
+
     conn.execute(
         "CREATE INDEX IF NOT EXISTS idx_color_indices_lab ON image_color_indices(l, a, b)",
         [],
@@ -1164,10 +1188,8 @@ pub fn get_error_files(
     }).map_err(|e| e.to_string())?;
     
     let mut files = Vec::new();
-    for row in rows {
-        if let Ok((path, timestamp)) = row {
-            files.push((path, timestamp));
-        }
+    for (path, timestamp) in rows.flatten() {
+        files.push((path, timestamp));
     }
     
     Ok(files)
@@ -11,6 +11,9 @@ pub struct ColorResult {
     pub lab_a: f32,       // LAB a
     pub lab_b: f32,       // LAB b
     pub is_dark: bool,    // 是否为深色
+    /// 该颜色占图片有效像素的百分比（0-100）。旧数据没有此字段，反序列化时默认为 0
+    #[serde(default)]
+    pub percentage: f32,
 }
 
 
@@ -154,6 +157,7 @@ pub fn get_dominant_colors(img: &DynamicImage, count: usize) -> Vec<ColorResult>
                 lab_a: lab.a,
                 lab_b: lab.b,
                 is_dark,
+                percentage: 0.0,
             }, *original_index));
             
             added_rgb_set.insert(new_rgb);
@@ -187,6 +191,7 @@ pub fn get_dominant_colors(img: &DynamicImage, count: usize) -> Vec<ColorResult>
                     lab_a: lab.a,
                     lab_b: lab.b,
                     is_dark,
+                    percentage: 0.0,
                 }, *original_index));
                 
                 added_rgb_set.insert(new_rgb);
@@ -240,13 +245,214 @@ pub fn get_dominant_colors(img: &DynamicImage, count: usize) -> Vec<ColorResult>
         pixel_counts[best_idx] += 1;
     }
     
-    // 将计数附加到结果上
-    let mut final_result: Vec<_> = temp_result.into_iter().enumerate().map(|(i, (c, _))| {
+    // 将计数附加到结果上，并换算为占比
+    let total_count: usize = pixel_counts.iter().sum();
+    let mut final_result: Vec<_> = temp_result.into_iter().enumerate().map(|(i, (mut c, _))| {
+        if total_count > 0 {
+            c.percentage = pixel_counts[i] as f32 * 100.0 / total_count as f32;
+        }
         (c, pixel_counts[i])
     }).collect();
     
     // 按计数降序排序
-    final_result.sort_by(|a, b| b.1.cmp(&a.1));
+    final_result.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
     
     final_result.into_iter().map(|(c, _)| c).collect()
 }
+
+/// 主色调提取设置：算法与颜色数量
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractionSettings {
+    /// "histogram"（color_thief 直方图，快）或 "kmeans"（Lab 空间 k-means，更贴近实际色块）
+    pub algorithm: String,
+    /// 提取的颜色数量
+    pub color_count: usize,
+}
+
+impl Default for ExtractionSettings {
+    fn default() -> Self {
+        Self {
+            algorithm: "histogram".to_string(),
+            color_count: 8,
+        }
+    }
+}
+
+static EXTRACTION_SETTINGS: once_cell::sync::Lazy<std::sync::RwLock<ExtractionSettings>> =
+    once_cell::sync::Lazy::new(|| std::sync::RwLock::new(ExtractionSettings::default()));
+
+/// 修改主色调提取设置，返回生效后的完整设置
+#[tauri::command]
+pub fn set_color_extraction_settings(
+    algorithm: Option<String>,
+    color_count: Option<usize>,
+) -> Result<ExtractionSettings, String> {
+    let mut settings = EXTRACTION_SETTINGS.write().unwrap();
+    if let Some(alg) = algorithm {
+        match alg.as_str() {
+            "histogram" | "kmeans" => settings.algorithm = alg,
+            other => return Err(format!("未知的提取算法: {}", other)),
+        }
+    }
+    if let Some(n) = color_count {
+        settings.color_count = n.clamp(1, 16);
+    }
+    Ok(settings.clone())
+}
+
+/// 查询当前主色调提取设置
+#[tauri::command]
+pub fn get_color_extraction_settings() -> ExtractionSettings {
+    EXTRACTION_SETTINGS.read().unwrap().clone()
+}
+
+/// 按当前设置提取主色调（后台 worker 与重提取任务的统一入口）
+pub fn get_dominant_colors_auto(img: &DynamicImage) -> Vec<ColorResult> {
+    let settings = EXTRACTION_SETTINGS.read().unwrap().clone();
+    match settings.algorithm.as_str() {
+        "kmeans" => get_dominant_colors_kmeans(img, settings.color_count),
+        _ => get_dominant_colors(img, settings.color_count),
+    }
+}
+
+/// Lab 空间 k-means 提取：比直方图慢，但聚类中心更贴近实际色块。
+/// 采用确定性的最远点初始化，避免引入随机数依赖且结果可复现
+pub fn get_dominant_colors_kmeans(img: &DynamicImage, count: usize) -> Vec<ColorResult> {
+    let rgba_img = img.to_rgba8();
+
+    // 与直方图路径相同的像素过滤规则（透明 / 接近纯白），并做步长采样
+    let step = 4usize;
+    let mut samples: Vec<Lab> = Vec::new();
+    for (i, p) in rgba_img.pixels().enumerate() {
+        if !i.is_multiple_of(step) {
+            continue;
+        }
+        let [r, g, b, a] = p.0;
+        if a < 125 {
+            continue;
+        }
+        if r > 250 && g > 250 && b > 250 {
+            continue;
+        }
+        let srgb = Srgb::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+        samples.push(Lab::from_color(srgb));
+    }
+
+    if samples.is_empty() {
+        return Vec::new();
+    }
+
+    let k = count.clamp(1, samples.len());
+
+    fn dist_sq(a: &Lab, b: &Lab) -> f32 {
+        let dl = a.l - b.l;
+        let da = a.a - b.a;
+        let db = a.b - b.b;
+        dl * dl + da * da + db * db
+    }
+
+    // 最远点初始化：第一个中心取全部样本的均值附近，之后每次取离现有中心最远的样本
+    let mean = Lab::new(
+        samples.iter().map(|s| s.l).sum::<f32>() / samples.len() as f32,
+        samples.iter().map(|s| s.a).sum::<f32>() / samples.len() as f32,
+        samples.iter().map(|s| s.b).sum::<f32>() / samples.len() as f32,
+    );
+    let first = samples
+        .iter()
+        .min_by(|a, b| {
+            dist_sq(a, &mean)
+                .partial_cmp(&dist_sq(b, &mean))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .copied()
+        .unwrap_or(mean);
+    let mut centers = vec![first];
+    while centers.len() < k {
+        let farthest = samples
+            .iter()
+            .max_by(|a, b| {
+                let da = centers.iter().map(|c| dist_sq(a, c)).fold(f32::INFINITY, f32::min);
+                let db = centers.iter().map(|c| dist_sq(b, c)).fold(f32::INFINITY, f32::min);
+                da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .copied();
+        match farthest {
+            Some(f) => centers.push(f),
+            None => break,
+        }
+    }
+
+    // Lloyd 迭代，固定最多 12 轮，收敛后提前退出
+    let mut assignments = vec![0usize; samples.len()];
+    for _ in 0..12 {
+        let mut changed = false;
+        for (i, s) in samples.iter().enumerate() {
+            let mut best = 0usize;
+            let mut best_d = f32::INFINITY;
+            for (ci, c) in centers.iter().enumerate() {
+                let d = dist_sq(s, c);
+                if d < best_d {
+                    best_d = d;
+                    best = ci;
+                }
+            }
+            if assignments[i] != best {
+                assignments[i] = best;
+                changed = true;
+            }
+        }
+
+        let mut sums = vec![(0.0f32, 0.0f32, 0.0f32, 0usize); centers.len()];
+        for (i, s) in samples.iter().enumerate() {
+            let entry = &mut sums[assignments[i]];
+            entry.0 += s.l;
+            entry.1 += s.a;
+            entry.2 += s.b;
+            entry.3 += 1;
+        }
+        for (ci, (l, a, b, n)) in sums.into_iter().enumerate() {
+            if n > 0 {
+                centers[ci] = Lab::new(l / n as f32, a / n as f32, b / n as f32);
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    // 统计各簇占比并生成结果，按占比降序
+    let mut cluster_counts = vec![0usize; centers.len()];
+    for &a in &assignments {
+        cluster_counts[a] += 1;
+    }
+    let total = samples.len();
+
+    let mut clusters: Vec<(Lab, usize)> = centers
+        .into_iter()
+        .zip(cluster_counts)
+        .filter(|&(_, n)| n > 0)
+        .collect();
+    clusters.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+
+    clusters
+        .into_iter()
+        .map(|(lab, n)| {
+            let srgb = Srgb::from_color(lab);
+            let r = (srgb.red.clamp(0.0, 1.0) * 255.0).round() as u8;
+            let g = (srgb.green.clamp(0.0, 1.0) * 255.0).round() as u8;
+            let b = (srgb.blue.clamp(0.0, 1.0) * 255.0).round() as u8;
+            let luminance = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+            ColorResult {
+                hex: format!("#{:02x}{:02x}{:02x}", r, g, b),
+                rgb: [r, g, b],
+                lab_l: lab.l,
+                lab_a: lab.a,
+                lab_b: lab.b,
+                is_dark: luminance < 128.0,
+                percentage: n as f32 * 100.0 / total as f32,
+            }
+        })
+        .collect()
+}
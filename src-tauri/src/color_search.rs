@@ -3,9 +3,76 @@ use std::sync::Arc;
 use rayon::prelude::*;
 use palette::{FromColor, Srgb, Lab};
 use palette::color_difference::Ciede2000;
-use tauri;
 use crate::color_db;
 
+/// 相似度预设的生效参数，随搜索响应一并返回以便复现结果
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarityParams {
+    pub preset: String,
+    /// 单色搜索的最低分
+    pub single_color_threshold: f32,
+    /// 2-4 色搜索的最低分
+    pub multi_color_threshold: f32,
+    /// 氛围搜索（5 色以上）的最低分
+    pub atmosphere_threshold: f32,
+    /// CLIP 文本/以图搜图在未显式指定时的最低相似度
+    pub clip_min_score: f32,
+}
+
+/// 当前相似度预设，默认 normal（阈值与历史硬编码值一致）
+static SIMILARITY_PRESET: once_cell::sync::Lazy<std::sync::RwLock<String>> =
+    once_cell::sync::Lazy::new(|| std::sync::RwLock::new("normal".to_string()));
+
+fn similarity_params_for(preset: &str) -> SimilarityParams {
+    match preset {
+        "strict" => SimilarityParams {
+            preset: "strict".to_string(),
+            single_color_threshold: 85.0,
+            multi_color_threshold: 92.0,
+            atmosphere_threshold: 90.0,
+            clip_min_score: 0.25,
+        },
+        "loose" => SimilarityParams {
+            preset: "loose".to_string(),
+            single_color_threshold: 65.0,
+            multi_color_threshold: 80.0,
+            atmosphere_threshold: 75.0,
+            clip_min_score: 0.05,
+        },
+        _ => SimilarityParams {
+            preset: "normal".to_string(),
+            single_color_threshold: 75.0,
+            multi_color_threshold: 88.0,
+            atmosphere_threshold: 85.0,
+            clip_min_score: 0.15,
+        },
+    }
+}
+
+/// 取当前预设的生效参数（供各搜索命令使用）
+pub fn current_similarity_params() -> SimilarityParams {
+    similarity_params_for(&SIMILARITY_PRESET.read().unwrap().clone())
+}
+
+/// 切换相似度预设（strict / normal / loose），返回生效参数
+#[tauri::command]
+pub fn set_similarity_preset(preset: String) -> Result<SimilarityParams, String> {
+    match preset.as_str() {
+        "strict" | "normal" | "loose" => {
+            *SIMILARITY_PRESET.write().unwrap() = preset.clone();
+            Ok(similarity_params_for(&preset))
+        }
+        other => Err(format!("未知的相似度预设: {}", other)),
+    }
+}
+
+/// 查询当前相似度预设的生效参数
+#[tauri::command]
+pub fn get_similarity_params() -> SimilarityParams {
+    current_similarity_params()
+}
+
 // Helper: Hex string to Lab color
 pub fn hex_to_lab(hex: &str) -> Option<Lab> {
     let hex = hex.trim_start_matches('#');
@@ -20,11 +87,351 @@ pub fn hex_to_lab(hex: &str) -> Option<Lab> {
     Some(Lab::from_color(srgb))
 }
 
+/// 调色板第 1..n 位颜色的默认面积权重（归一化前）。
+/// 主色调提取按占比排序，排名越靠前代表的面积越大
+fn rank_weights(n: usize) -> Vec<f32> {
+    const RAW: [f32; 8] = [0.35, 0.25, 0.15, 0.10, 0.06, 0.04, 0.03, 0.02];
+    let mut weights: Vec<f32> = (0..n).map(|i| RAW.get(i).copied().unwrap_or(0.01)).collect();
+    let sum: f32 = weights.iter().sum();
+    if sum > 0.0 {
+        for w in weights.iter_mut() {
+            *w /= sum;
+        }
+    }
+    weights
+}
+
+/// 候选调色板的权重：优先使用数据库里的实际面积占比，
+/// 旧数据没有占比（全 0）时退回排名权重
+fn candidate_weights_or_rank(weights: &[f32], n: usize) -> Vec<f32> {
+    let sum: f32 = weights.iter().take(n).sum();
+    if weights.len() >= n && sum > 0.0 {
+        weights.iter().take(n).map(|w| w / sum).collect()
+    } else {
+        rank_weights(n)
+    }
+}
+
+/// 以 CIEDE2000 为地面距离的近似 Earth Mover's Distance：
+/// 按距离从小到大贪心搬运两个调色板的"面积质量"，返回加权平均搬运距离。
+/// 调色板最多 8 色，贪心解与精确解的差距可以忽略
+fn palette_emd(target_labs: &[Lab], target_weights: &[f32], candidate_labs: &[Lab], candidate_weights: &[f32]) -> f32 {
+    if target_labs.is_empty() || candidate_labs.is_empty() {
+        return f32::INFINITY;
+    }
+
+    let mut pairs: Vec<(f32, usize, usize)> = Vec::with_capacity(target_labs.len() * candidate_labs.len());
+    for (i, t) in target_labs.iter().enumerate() {
+        for (j, c) in candidate_labs.iter().enumerate() {
+            pairs.push((c.difference(*t), i, j));
+        }
+    }
+    pairs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut remaining_t = target_weights.to_vec();
+    let mut remaining_c = candidate_weights.to_vec();
+    let mut cost = 0.0f32;
+    let mut moved = 0.0f32;
+
+    for (dist, i, j) in pairs {
+        let flow = remaining_t[i].min(remaining_c[j]);
+        if flow > 0.0 {
+            cost += dist * flow;
+            moved += flow;
+            remaining_t[i] -= flow;
+            remaining_c[j] -= flow;
+            if moved >= 0.999 {
+                break;
+            }
+        }
+    }
+
+    if moved > 0.0 { cost / moved } else { f32::INFINITY }
+}
+
+/// 对单张图片的调色板打分；达到对应模式的阈值时返回分数。
+/// 单色 / 2-4 色 / 氛围（5 色以上）三种模式使用不同的阈值
+fn score_palette_match(
+    target_labs: &[Lab],
+    candidate_labs: &[Lab],
+    candidate_weights: &[f32],
+    is_single_color: bool,
+    is_atmosphere_search: bool,
+    params: &SimilarityParams,
+) -> Option<f32> {
+    if candidate_labs.is_empty() { return None; }
+
+    let score: f32;
+    let threshold: f32;
+
+    if is_single_color {
+        // (omitted inner helpers retained)
+        let target = &target_labs[0];
+
+        // 辅助函数：计算颜色的"色彩程度"（基于Lab空间）
+        fn calc_colorfulness(lab_a: f32, lab_b: f32) -> f32 {
+            (lab_a * lab_a + lab_b * lab_b).sqrt() / 127.0
+        }
+        
+        // 检查目标颜色是否有明显色彩（非灰色）
+        let target_colorfulness = calc_colorfulness(target.a, target.b);
+        let target_is_colorful = target_colorfulness > 0.05; // 目标颜色有色彩
+        
+        // 检查候选图片是否为纯黑白/灰度
+        let candidate_max_colorfulness = candidate_labs.iter()
+            .take(5)
+            .map(|lab| calc_colorfulness(lab.a, lab.b))
+            .fold(0.0f32, f32::max);
+        let candidate_is_grayscale = candidate_max_colorfulness < 0.03;
+        
+        // 如果搜索的是彩色，但候选图是纯灰度，直接排除
+        if target_is_colorful && candidate_is_grayscale {
+            return None;
+        }
+        
+        // 位置权重：第1位=1.0, 第2位=0.7, 第3位=0.5, 第4位=0.35, 之后更低
+        // 这确保占比大的颜色能贡献高分，但也允许第2-3位的颜色有一定权重
+        let position_weights = [1.0f32, 0.7, 0.5, 0.35, 0.25, 0.18, 0.12, 0.08];
+        
+        let mut best_weighted_score = 0.0f32;
+        
+        for (idx, candidate) in candidate_labs.iter().enumerate() {
+            let dist = candidate.difference(*target); // CIEDE2000
+            
+            // 相似度分数：距离越小，分数越高
+            // DeltaE < 10 认为是相似颜色，< 5 非常相似
+            let similarity = if dist < 5.0 {
+                100.0
+            } else if dist < 10.0 {
+                100.0 - (dist - 5.0) * 4.0 // 5-10 -> 100-80
+            } else if dist < 20.0 {
+                80.0 - (dist - 10.0) * 3.0 // 10-20 -> 80-50
+            } else if dist < 30.0 {
+                50.0 - (dist - 20.0) * 2.0 // 20-30 -> 50-30
+            } else {
+                (30.0 - (dist - 30.0).min(30.0)).max(0.0) // 30+ -> 30-0
+            };
+            
+            // 位置权重
+            let pos_weight = if idx < position_weights.len() {
+                position_weights[idx]
+            } else {
+                0.05 // 第8个以后的颜色权重很低
+            };
+            
+            // 加权分数 = 相似度 * 位置权重
+            let weighted_score = similarity * pos_weight;
+            
+            if weighted_score > best_weighted_score {
+                best_weighted_score = weighted_score;
+            }
+        }
+        
+        score = best_weighted_score;
+        // 阈值由相似度预设决定（normal 为 75.0），以减少不相关的结果数量
+        // 这确保只有主色非常接近或前几位颜色有极高相似度的图片才会被召回
+        threshold = params.single_color_threshold;
+    } else if is_atmosphere_search {
+        // ========== 氛围搜索（5色以上）：整体调色板结构匹配 ==========
+        // 核心思想：找与参考图片整体色调相似的图片
+        // 要求双向匹配：目标颜色能在候选中找到 + 候选主色也要在目标中有对应
+        // 同时避免将黑白漫画与彩色图片匹配（通过彩度检测）
+        
+        // 辅助函数：计算颜色的"色彩程度"（基于Lab空间）
+        // 在Lab空间中，a和b值决定了色彩，值越大表示色彩越饱和
+        fn calc_colorfulness(lab_a: f32, lab_b: f32) -> f32 {
+            // 计算a、b值的欧氏距离，表示离灰色轴（a=0, b=0）有多远
+            (lab_a * lab_a + lab_b * lab_b).sqrt() / 127.0 // 除以Lab色彩空间的最大值作为归一化
+        }
+        
+        // 计算目标调色板的整体色彩程度
+        let target_colorfulness: Vec<f32> = target_labs.iter()
+            .take(5)
+            .map(|lab| calc_colorfulness(lab.a, lab.b))
+            .collect();
+        
+        let target_avg_colorfulness = if !target_colorfulness.is_empty() {
+            target_colorfulness.iter().sum::<f32>() / target_colorfulness.len() as f32
+        } else {
+            0.0
+        };
+        
+        // 计算候选调色板的整体色彩程度
+        let candidate_colorfulness: Vec<f32> = candidate_labs.iter()
+            .take(5)
+            .map(|lab| calc_colorfulness(lab.a, lab.b))
+            .collect();
+        
+        let candidate_avg_colorfulness = if !candidate_colorfulness.is_empty() {
+            candidate_colorfulness.iter().sum::<f32>() / candidate_colorfulness.len() as f32
+        } else {
+            0.0
+        };
+        
+        // 策略1+2：带面积权重的 Earth Mover’s Distance
+        // 把两个调色板视为按占比加权的颜色分布，计算整体搬运代价。
+        // 天然是双向匹配：目标或候选任何一侧的主色没有对应都会抬高距离
+        let t_weights = rank_weights(target_labs.len().min(8));
+        let c_weights = candidate_weights_or_rank(candidate_weights, candidate_labs.len().min(8));
+        let emd = palette_emd(
+            &target_labs[..target_labs.len().min(8)],
+            &t_weights,
+            &candidate_labs[..candidate_labs.len().min(8)],
+            &c_weights,
+        );
+
+        // 策略3：色彩程度不匹配惩罚 - 防止将黑白漫画与彩色图片匹配
+        // 改进版：区分纯黑白、低饱和度彩色、高饱和度彩色三种情况
+        let mut colorfulness_mismatch_penalty = 0.0f32;
+         
+        // 辅助函数：判断是否为"纯黑白/灰度"图片
+        // 纯黑白图片的特征：所有颜色的 colorfulness 都非常低（< 0.03）
+        fn is_pure_grayscale(colorfulness_values: &[f32]) -> bool {
+            if colorfulness_values.is_empty() { return true; }
+            let max_cf = colorfulness_values.iter().cloned().fold(0.0f32, f32::max);
+            // 如果最大 colorfulness 都 < 0.03，认为是纯灰度
+            max_cf < 0.03
+        }
+        
+        // 辅助函数：判断颜色是否有明确的色相方向（而不是散乱或接近灰色轴）
+        // 通过检查 a、b 值是否有一致的倾向
+        fn has_color_tendency(labs: &[Lab]) -> bool {
+            if labs.len() < 2 { return false; }
+            
+            // 统计有意义的色彩值（colorfulness > 0.02 的颜色）
+            let meaningful_colors: Vec<(f32, f32)> = labs.iter()
+                .take(5)
+                .filter(|lab| {
+                    let cf = (lab.a * lab.a + lab.b * lab.b).sqrt() / 127.0;
+                    cf > 0.02  // 只考虑有一点色彩的颜色
+                })
+                .map(|lab| (lab.a, lab.b))
+                .collect();
+            
+            // 如果没有足够的有意义颜色，没有色彩倾向
+            if meaningful_colors.len() < 2 { return false; }
+            
+            // 计算平均 a、b 值
+            let avg_a = meaningful_colors.iter().map(|(a, _)| *a).sum::<f32>() / meaningful_colors.len() as f32;
+            let avg_b = meaningful_colors.iter().map(|(_, b)| *b).sum::<f32>() / meaningful_colors.len() as f32;
+            
+            // 如果平均值离原点有一定距离，说明有色彩倾向
+            let avg_chroma = (avg_a * avg_a + avg_b * avg_b).sqrt();
+            avg_chroma > 3.0  // Lab 空间中，a/b 差异 > 3 就有可感知的颜色倾向
+        }
+        
+        let target_is_pure_grayscale = is_pure_grayscale(&target_colorfulness);
+        let candidate_is_pure_grayscale = is_pure_grayscale(&candidate_colorfulness);
+        let target_has_color = has_color_tendency(target_labs);
+        let candidate_has_color = has_color_tendency(candidate_labs);
+        
+        // 核心逻辑：目标有颜色倾向，但候选是纯灰度 → 重罚
+        if target_has_color && candidate_is_pure_grayscale {
+            // 目标是低饱和度彩色（如暖色调），候选是纯黑白 → 绝对排除
+            colorfulness_mismatch_penalty = 50.0;
+        } else if target_has_color && !candidate_has_color {
+            // 目标有色彩倾向，候选没有明确色彩倾向 → 强惩罚
+            colorfulness_mismatch_penalty = 40.0;
+        } else if !target_is_pure_grayscale && candidate_is_pure_grayscale {
+            // 目标有一点色彩（可能是轻微偏色的图），候选是纯黑白 → 强惩罚
+            colorfulness_mismatch_penalty = 35.0;
+        } else if target_is_pure_grayscale && !candidate_is_pure_grayscale {
+            // 目标是纯黑白，候选有颜色 → 中等惩罚
+            colorfulness_mismatch_penalty = 25.0;
+        } else {
+            // 两者都有颜色或都是灰度，检查 colorfulness 差异
+            let colorfulness_diff = (target_avg_colorfulness - candidate_avg_colorfulness).abs();
+            
+            if target_avg_colorfulness > 0.2 && candidate_avg_colorfulness < 0.05 {
+                // 高饱和目标 vs 极低饱和候选
+                colorfulness_mismatch_penalty = colorfulness_diff * 40.0;
+            } else if colorfulness_diff > 0.1 {
+                // 一般的色彩程度差异惩罚
+                colorfulness_mismatch_penalty = (colorfulness_diff - 0.1) * 15.0;
+            }
+        }
+        
+        // 最终分数：EMD 每 1 个 DeltaE 扣 3 分，整体调色板偏移 5 即降到阈值附近
+        let raw_score = 100.0 - emd * 3.0 - colorfulness_mismatch_penalty;
+        score = raw_score.max(0.0);
+        
+        // 氛围搜索阈值（normal 为 85 分）
+        // 这确保只有真正氛围相似的图片才能通过
+        threshold = params.atmosphere_threshold;
+        
+    } else {
+        // ========== 中等数量颜色搜索（2-4色）==========
+        // 用户挑选的颜色视为等权重，候选侧按占比加权，
+        // 用 EMD 同时约束"目标颜色要找得到"和"候选主色不能跑偏"
+        let t_weights = vec![1.0 / target_labs.len() as f32; target_labs.len()];
+        let c_weights = candidate_weights_or_rank(candidate_weights, candidate_labs.len().min(8));
+        let emd = palette_emd(
+            target_labs,
+            &t_weights,
+            &candidate_labs[..candidate_labs.len().min(8)],
+            &c_weights,
+        );
+
+        // EMD 每 1 个 DeltaE 扣 1.5 分，normal 阈值 88 约等于容忍 8 个 DeltaE
+        score = (100.0 - emd * 1.5).max(0.0);
+        threshold = params.multi_color_threshold;
+    }
+
+    if score >= threshold {
+        Some(score)
+    } else {
+        None
+    }
+}
+
+/// EMD 距离映射到 0 分的上界：CIEDE2000 加权平均距离到这个值
+/// 以上视为与目标调色板完全不像
+const RERANK_EMD_CEILING: f32 = 45.0;
+
+/// 一组图片与目标调色板的相似度（0-1），给搜索结果重排序用。
+///
+/// 与 [`score_palette_match`] 不同，这里不做阈值裁剪也不分模式——
+/// 排序和加权交给重排序阶段，这里只负责把 EMD 距离线性映射到
+/// 0-1 分。没有提取过主色的图片不会出现在返回的表里
+pub fn palette_similarity_scores(
+    pool: &Arc<color_db::ColorDbPool>,
+    paths: &[String],
+    target_palette: &[String],
+) -> std::collections::HashMap<String, f32> {
+    let target_labs: Vec<Lab> = target_palette.iter().filter_map(|h| hex_to_lab(h)).collect();
+    let mut scores = std::collections::HashMap::new();
+    if target_labs.is_empty() {
+        return scores;
+    }
+    let target_weights = rank_weights(target_labs.len());
+
+    let mut conn = pool.get_connection();
+    for path in paths {
+        let Ok(Some(colors)) = color_db::get_colors_by_file_path(&mut conn, path) else {
+            continue;
+        };
+        let candidate_labs: Vec<Lab> = colors.iter().filter_map(|c| hex_to_lab(&c.hex)).collect();
+        if candidate_labs.is_empty() {
+            continue;
+        }
+        let raw_weights: Vec<f32> = colors.iter().map(|c| c.percentage).collect();
+        let candidate_weights = candidate_weights_or_rank(&raw_weights, candidate_labs.len());
+
+        let emd = palette_emd(&target_labs, &target_weights, &candidate_labs, &candidate_weights);
+        scores.insert(path.clone(), (1.0 - emd / RERANK_EMD_CEILING).clamp(0.0, 1.0));
+    }
+    scores
+}
+
 #[tauri::command]
 pub async fn search_by_palette(
     pool_state: tauri::State<'_, Arc<color_db::ColorDbPool>>,
-    target_palette: Vec<String>
+    target_palette: Vec<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
 ) -> Result<Vec<String>, String> {
+    let page_offset = offset.unwrap_or(0);
+    let page_limit = limit.unwrap_or(usize::MAX);
     eprintln!("[search_by_palette] Called with {} colors: {:?}", target_palette.len(), target_palette);
     
     // Parse target palette to Lab once
@@ -39,6 +446,7 @@ pub async fn search_by_palette(
 
     let is_single_color = target_labs.len() == 1;
     let is_atmosphere_search = target_labs.len() >= 5;
+    let params = current_similarity_params();
 
     let pool = pool_state.inner().clone();
 
@@ -54,7 +462,7 @@ pub async fn search_by_palette(
             let delta = 20.0f32;
             if let Ok(mut stmt) = conn.prepare("SELECT DISTINCT file_path FROM image_color_indices WHERE l BETWEEN ? AND ? AND a BETWEEN ? AND ? AND b BETWEEN ? AND ? LIMIT 10000") {
                 if let Ok(rows) = stmt.query_map(rusqlite::params![target.l - delta, target.l + delta, target.a - delta, target.a + delta, target.b - delta, target.b + delta], |r| r.get::<_, String>(0)) {
-                    for r in rows { if let Ok(p) = r { candidate_set.insert(p); } }
+                    for p in rows.flatten() { candidate_set.insert(p); }
                 }
             }
         }
@@ -80,21 +488,28 @@ pub async fn search_by_palette(
                         let w = if idx < position_weights.len() { position_weights[idx] } else { 0.05 };
                         best = best.max(sim * w);
                     }
-                    if best >= 60.0 { scored.push((path.clone(), best)); }
+                    // 快速路径比主路径放宽 15 分（normal 下即历史的 60 分）
+                    if best >= params.single_color_threshold - 15.0 { scored.push((path.clone(), best)); }
                 } else {
                     let mut total = 0.0f32; let mut cnt = 0u32;
                     for t in target_labs.iter().take(5) { let md = candidate_labs.iter().map(|c| c.difference(*t)).fold(f32::INFINITY, |a, b| a.min(b)); total += md; cnt += 1; }
                     if cnt == 0 { continue; }
                     let avg = total / cnt as f32;
                     let score = if avg < 5.0 { 100.0 } else if avg < 10.0 { 90.0 } else if avg < 20.0 { 70.0 } else if avg < 30.0 { 50.0 } else { 20.0 };
-                    if (is_atmosphere_search && score >= 85.0) || (!is_atmosphere_search && score >= 70.0) { scored.push((path.clone(), score)); }
+                    // 氛围搜索沿用主路径阈值；2-4 色放宽 18 分（normal 下即历史的 85/70 分）
+                    if (is_atmosphere_search && score >= params.atmosphere_threshold) || (!is_atmosphere_search && score >= params.multi_color_threshold - 18.0) { scored.push((path.clone(), score)); }
                 }
             }
         }
 
         scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
         scored.truncate(50000);
-        let final_results = scored.into_iter().map(|(p, _)| p).collect::<Vec<String>>();
+        let final_results = scored
+            .into_iter()
+            .skip(page_offset)
+            .take(page_limit)
+            .map(|(p, _)| p)
+            .collect::<Vec<String>>();
         eprintln!("[search_by_palette] Returning {} results (DB fast-path truncated)", final_results.len());
         return Ok(final_results);
     }
@@ -108,306 +523,8 @@ pub async fn search_by_palette(
              let mut results: Vec<(String, f32)> = all_colors.par_iter()
                 .filter_map(|image_data| {
                      // Use PRECOMPUTED Labs! No hex_to_lab parsing here anymore.
-                     let candidate_labs = &image_data.labs;
-                     
-                     if candidate_labs.is_empty() { return None; }
-                     
-                     let score: f32;
-                     let threshold: f32;
-
-                     if is_single_color {
-                         // (omitted inner helpers retained)
-                         let target = &target_labs[0];
-
-                         // 辅助函数：计算颜色的"色彩程度"（基于Lab空间）
-                         fn calc_colorfulness(lab_a: f32, lab_b: f32) -> f32 {
-                             (lab_a * lab_a + lab_b * lab_b).sqrt() / 127.0
-                         }
-                         
-                         // 检查目标颜色是否有明显色彩（非灰色）
-                         let target_colorfulness = calc_colorfulness(target.a, target.b);
-                         let target_is_colorful = target_colorfulness > 0.05; // 目标颜色有色彩
-                         
-                         // 检查候选图片是否为纯黑白/灰度
-                         let candidate_max_colorfulness = candidate_labs.iter()
-                             .take(5)
-                             .map(|lab| calc_colorfulness(lab.a, lab.b))
-                             .fold(0.0f32, f32::max);
-                         let candidate_is_grayscale = candidate_max_colorfulness < 0.03;
-                         
-                         // 如果搜索的是彩色，但候选图是纯灰度，直接排除
-                         if target_is_colorful && candidate_is_grayscale {
-                             return None;
-                         }
-                         
-                         // 位置权重：第1位=1.0, 第2位=0.7, 第3位=0.5, 第4位=0.35, 之后更低
-                         // 这确保占比大的颜色能贡献高分，但也允许第2-3位的颜色有一定权重
-                         let position_weights = [1.0f32, 0.7, 0.5, 0.35, 0.25, 0.18, 0.12, 0.08];
-                         
-                         let mut best_weighted_score = 0.0f32;
-                         
-                         for (idx, candidate) in candidate_labs.iter().enumerate() {
-                             let dist = candidate.difference(*target); // CIEDE2000
-                             
-                             // 相似度分数：距离越小，分数越高
-                             // DeltaE < 10 认为是相似颜色，< 5 非常相似
-                             let similarity = if dist < 5.0 {
-                                 100.0
-                             } else if dist < 10.0 {
-                                 100.0 - (dist - 5.0) * 4.0 // 5-10 -> 100-80
-                             } else if dist < 20.0 {
-                                 80.0 - (dist - 10.0) * 3.0 // 10-20 -> 80-50
-                             } else if dist < 30.0 {
-                                 50.0 - (dist - 20.0) * 2.0 // 20-30 -> 50-30
-                             } else {
-                                 (30.0 - (dist - 30.0).min(30.0)).max(0.0) // 30+ -> 30-0
-                             };
-                             
-                             // 位置权重
-                             let pos_weight = if idx < position_weights.len() {
-                                 position_weights[idx]
-                             } else {
-                                 0.05 // 第8个以后的颜色权重很低
-                             };
-                             
-                             // 加权分数 = 相似度 * 位置权重
-                             let weighted_score = similarity * pos_weight;
-                             
-                             if weighted_score > best_weighted_score {
-                                 best_weighted_score = weighted_score;
-                             }
-                         }
-                         
-                         score = best_weighted_score;
-                         // 阈值：提高到 75.0 以减少不相关的结果数量
-                         // 这确保只有主色非常接近或前几位颜色有极高相似度的图片才会被召回
-                         threshold = 75.0;
-                     } else if is_atmosphere_search {
-                         // ========== 氛围搜索（5色以上）：整体调色板结构匹配 ==========
-                         // 核心思想：找与参考图片整体色调相似的图片
-                         // 要求双向匹配：目标颜色能在候选中找到 + 候选主色也要在目标中有对应
-                         // 同时避免将黑白漫画与彩色图片匹配（通过彩度检测）
-                         
-                         // 辅助函数：计算颜色的"色彩程度"（基于Lab空间）
-                         // 在Lab空间中，a和b值决定了色彩，值越大表示色彩越饱和
-                         fn calc_colorfulness(lab_a: f32, lab_b: f32) -> f32 {
-                             // 计算a、b值的欧氏距离，表示离灰色轴（a=0, b=0）有多远
-                             (lab_a * lab_a + lab_b * lab_b).sqrt() / 127.0 // 除以Lab色彩空间的最大值作为归一化
-                         }
-                         
-                         // 计算目标调色板的整体色彩程度
-                         let target_colorfulness: Vec<f32> = target_labs.iter()
-                             .take(5)
-                             .map(|lab| calc_colorfulness(lab.a, lab.b))
-                             .collect();
-                         
-                         let target_avg_colorfulness = if !target_colorfulness.is_empty() {
-                             target_colorfulness.iter().sum::<f32>() / target_colorfulness.len() as f32
-                         } else {
-                             0.0
-                         };
-                         
-                         // 计算候选调色板的整体色彩程度
-                         let candidate_colorfulness: Vec<f32> = candidate_labs.iter()
-                             .take(5)
-                             .map(|lab| calc_colorfulness(lab.a, lab.b))
-                             .collect();
-                         
-                         let candidate_avg_colorfulness = if !candidate_colorfulness.is_empty() {
-                             candidate_colorfulness.iter().sum::<f32>() / candidate_colorfulness.len() as f32
-                         } else {
-                             0.0
-                         };
-                         
-                         // 策略1：计算加权最小距离（考虑位置）
-                         // 目标调色板中的前几个颜色更重要
-                         let target_weights = [1.0f32, 0.85, 0.7, 0.55, 0.4];
-                         
-                         let mut weighted_total_dist = 0.0f32;
-                         let mut total_weight = 0.0f32;
-                         
-                         for (t_idx, t) in target_labs.iter().enumerate() {
-                             let t_weight = if t_idx < target_weights.len() {
-                                 target_weights[t_idx]
-                             } else {
-                                 0.05
-                             };
-                             
-                             // 找候选颜色中最佳匹配，同时考虑候选位置
-                             let mut best_match_dist = f32::INFINITY;
-                             let mut best_match_pos = candidate_labs.len();
-                             
-                             for (c_idx, c) in candidate_labs.iter().enumerate() {
-                                 let dist = c.difference(*t);
-                                 if dist < best_match_dist {
-                                     best_match_dist = dist;
-                                     best_match_pos = c_idx;
-                                 }
-                             }
-                             
-                             // 位置惩罚：如果目标的主色（前3位）只能在候选的后面找到匹配，大幅增加惩罚
-                             let position_penalty = if t_idx < 3 {
-                                 if best_match_pos > 4 {
-                                     best_match_dist * 0.8 // 主色匹配在后面，增加80%惩罚
-                                 } else if best_match_pos > 2 {
-                                     best_match_dist * 0.4 // 主色匹配在中间，增加40%惩罚
-                                 } else {
-                                     0.0 // 主色匹配在前面，无惩罚
-                                 }
-                             } else {
-                                 0.0
-                             };
-                             
-                             let adjusted_dist = best_match_dist + position_penalty;
-                             weighted_total_dist += adjusted_dist * t_weight;
-                             total_weight += t_weight;
-                         }
-                         
-                         let avg_weighted_dist = weighted_total_dist / total_weight;
-                          
-                         // 策略2：严格的双向匹配 - 候选图片的主色也必须在目标调色板中找到对应
-                         // 这是关键：防止完全不同氛围的图片被匹配进来
-                         let mut reverse_mismatch_penalty = 0.0f32;
-                          
-                         // 检查候选图片的前5个主色
-                         for (c_idx, c) in candidate_labs.iter().take(5).enumerate() {
-                             let min_dist_to_target = target_labs.iter()
-                                 .map(|t| c.difference(*t))
-                                 .fold(f32::INFINITY, |a, b| a.min(b));
-                             
-                             // 更严格的不匹配阈值：DeltaE > 12 就开始惩罚
-                             // 第1个主色最重要，第2、3个次之
-                             if min_dist_to_target > 12.0 {
-                                 let penalty_weight = match c_idx {
-                                     0 => 10.0,  // 第1个主色不匹配，重罚
-                                     1 => 7.5,   // 第2个主色
-                                     2 => 5.5,   // 第3个主色
-                                     3 => 4.0,   // 第4个主色
-                                     _ => 2.5,   // 第5个主色
-                                 };
-                                 
-                                 // 惩罚力度：差异越大，惩罚越重
-                                 let excess_dist = min_dist_to_target - 12.0;
-                                 reverse_mismatch_penalty += excess_dist * penalty_weight * 0.18;
-                             }
-                         }
-                         
-                         // 策略3：色彩程度不匹配惩罚 - 防止将黑白漫画与彩色图片匹配
-                         // 改进版：区分纯黑白、低饱和度彩色、高饱和度彩色三种情况
-                         let mut colorfulness_mismatch_penalty = 0.0f32;
-                          
-                         // 辅助函数：判断是否为"纯黑白/灰度"图片
-                         // 纯黑白图片的特征：所有颜色的 colorfulness 都非常低（< 0.03）
-                         fn is_pure_grayscale(colorfulness_values: &[f32]) -> bool {
-                             if colorfulness_values.is_empty() { return true; }
-                             let max_cf = colorfulness_values.iter().cloned().fold(0.0f32, f32::max);
-                             // 如果最大 colorfulness 都 < 0.03，认为是纯灰度
-                             max_cf < 0.03
-                         }
-                         
-                         // 辅助函数：判断颜色是否有明确的色相方向（而不是散乱或接近灰色轴）
-                         // 通过检查 a、b 值是否有一致的倾向
-                         fn has_color_tendency(labs: &[Lab]) -> bool {
-                             if labs.len() < 2 { return false; }
-                             
-                             // 统计有意义的色彩值（colorfulness > 0.02 的颜色）
-                             let meaningful_colors: Vec<(f32, f32)> = labs.iter()
-                                 .take(5)
-                                 .filter(|lab| {
-                                     let cf = (lab.a * lab.a + lab.b * lab.b).sqrt() / 127.0;
-                                     cf > 0.02  // 只考虑有一点色彩的颜色
-                                 })
-                                 .map(|lab| (lab.a, lab.b))
-                                 .collect();
-                             
-                             // 如果没有足够的有意义颜色，没有色彩倾向
-                             if meaningful_colors.len() < 2 { return false; }
-                             
-                             // 计算平均 a、b 值
-                             let avg_a = meaningful_colors.iter().map(|(a, _)| *a).sum::<f32>() / meaningful_colors.len() as f32;
-                             let avg_b = meaningful_colors.iter().map(|(_, b)| *b).sum::<f32>() / meaningful_colors.len() as f32;
-                             
-                             // 如果平均值离原点有一定距离，说明有色彩倾向
-                             let avg_chroma = (avg_a * avg_a + avg_b * avg_b).sqrt();
-                             avg_chroma > 3.0  // Lab 空间中，a/b 差异 > 3 就有可感知的颜色倾向
-                         }
-                         
-                         let target_is_pure_grayscale = is_pure_grayscale(&target_colorfulness);
-                         let candidate_is_pure_grayscale = is_pure_grayscale(&candidate_colorfulness);
-                         let target_has_color = has_color_tendency(&target_labs);
-                         let candidate_has_color = has_color_tendency(&candidate_labs);
-                         
-                         // 核心逻辑：目标有颜色倾向，但候选是纯灰度 → 重罚
-                         if target_has_color && candidate_is_pure_grayscale {
-                             // 目标是低饱和度彩色（如暖色调），候选是纯黑白 → 绝对排除
-                             colorfulness_mismatch_penalty = 50.0;
-                         } else if target_has_color && !candidate_has_color {
-                             // 目标有色彩倾向，候选没有明确色彩倾向 → 强惩罚
-                             colorfulness_mismatch_penalty = 40.0;
-                         } else if !target_is_pure_grayscale && candidate_is_pure_grayscale {
-                             // 目标有一点色彩（可能是轻微偏色的图），候选是纯黑白 → 强惩罚
-                             colorfulness_mismatch_penalty = 35.0;
-                         } else if target_is_pure_grayscale && !candidate_is_pure_grayscale {
-                             // 目标是纯黑白，候选有颜色 → 中等惩罚
-                             colorfulness_mismatch_penalty = 25.0;
-                         } else {
-                             // 两者都有颜色或都是灰度，检查 colorfulness 差异
-                             let colorfulness_diff = (target_avg_colorfulness - candidate_avg_colorfulness).abs();
-                             
-                             if target_avg_colorfulness > 0.2 && candidate_avg_colorfulness < 0.05 {
-                                 // 高饱和目标 vs 极低饱和候选
-                                 colorfulness_mismatch_penalty = colorfulness_diff * 40.0;
-                             } else if colorfulness_diff > 0.1 {
-                                 // 一般的色彩程度差异惩罚
-                                 colorfulness_mismatch_penalty = (colorfulness_diff - 0.1) * 15.0;
-                             }
-                         }
-                         
-                         // 最终分数
-                         let raw_score = 100.0 - avg_weighted_dist - reverse_mismatch_penalty - colorfulness_mismatch_penalty;
-                         score = raw_score.max(0.0);
-                         
-                         // 氛围搜索阈值提高到85分
-                         // 这确保只有真正氛围相似的图片才能通过
-                         threshold = 85.0;
-                         
-                     } else {
-                         // ========== 中等数量颜色搜索（2-4色）==========
-                         // 混合策略：要求每个目标颜色都能找到匹配，但也考虑位置
-                         
-                         let mut total_min_dist = 0.0f32;
-                         let mut position_bonus = 0.0f32;
-                         
-                         for t in &target_labs {
-                             let mut min_dist = f32::INFINITY;
-                             let mut best_pos = candidate_labs.len();
-                             
-                             for (idx, c) in candidate_labs.iter().enumerate() {
-                                 let dist = c.difference(*t);
-                                 if dist < min_dist {
-                                     min_dist = dist;
-                                     best_pos = idx;
-                                 }
-                             }
-                             
-                             total_min_dist += min_dist;
-                             
-                             // 如果匹配颜色在前4位，给予位置奖励
-                             if best_pos < 4 && min_dist < 15.0 {
-                                 position_bonus += (4.0 - best_pos as f32) * 2.0;
-                             }
-                         }
-                         
-                         let avg_dist = total_min_dist / target_labs.len() as f32;
-                         score = 100.0 - avg_dist + position_bonus / target_labs.len() as f32;
-                         threshold = 88.0;
-                     }
-                     
-                     if score >= threshold {
-                         Some((image_data.file_path.clone(), score))
-                     } else {
-                         None
-                     }
+                     score_palette_match(&target_labs, &image_data.labs, &image_data.weights, is_single_color, is_atmosphere_search, &params)
+                         .map(|score| (image_data.file_path.clone(), score))
                 })
                 .collect();
 
@@ -425,10 +542,15 @@ pub async fn search_by_palette(
     
     // 限制在 50000 条以内，以兼顾性能和用户的分页需求
     results.truncate(50000);
-    
-    let final_results: Vec<String> = results.iter().map(|(path, _)| path.clone()).collect();
+
+    let final_results: Vec<String> = results
+        .iter()
+        .skip(page_offset)
+        .take(page_limit)
+        .map(|(path, _)| path.clone())
+        .collect();
     eprintln!("[search_by_palette] Returning {} results (paged support)", final_results.len());
-    
+
     Ok(final_results)
 }
 
@@ -437,5 +559,89 @@ pub async fn search_by_color(
      pool_state: tauri::State<'_, Arc<color_db::ColorDbPool>>,
      color: String
 ) -> Result<Vec<String>, String> {
-    search_by_palette(pool_state, vec![color]).await
+    search_by_palette(pool_state, vec![color], None, None).await
+}
+
+/// 流式调色板搜索：把缓存分块打分，每块一出结果立刻通过
+/// palette-search-chunk 事件推给前端，大图库上首批匹配一秒内可见；
+/// 全部扫完后发 palette-search-done，返回匹配总数。
+/// 缓存未预热时返回 cache_not_ready，前端可回退到一次性的 search_by_palette
+#[tauri::command]
+pub async fn search_by_palette_stream(
+    app: tauri::AppHandle,
+    pool_state: tauri::State<'_, Arc<color_db::ColorDbPool>>,
+    target_palette: Vec<String>,
+    chunk_size: Option<usize>,
+) -> Result<usize, String> {
+    use tauri::Emitter;
+
+    let target_labs: Vec<Lab> = target_palette.iter()
+        .filter_map(|h| hex_to_lab(h))
+        .collect();
+    if target_labs.is_empty() {
+        return Ok(0);
+    }
+
+    let is_single_color = target_labs.len() == 1;
+    let is_atmosphere_search = target_labs.len() >= 5;
+    let params = current_similarity_params();
+    let chunk = chunk_size.unwrap_or(5000).max(100);
+
+    let pool = pool_state.inner().clone();
+    if !pool.is_cache_initialized() {
+        let _ = pool.ensure_cache_initialized_async();
+        return Err("cache_not_ready".to_string());
+    }
+
+    tokio::task::spawn_blocking(move || {
+        pool.access_cache(|all_colors| {
+            let total = all_colors.len();
+            let mut matched_total = 0usize;
+
+            for (chunk_idx, chunk_slice) in all_colors.chunks(chunk).enumerate() {
+                let mut scored: Vec<(String, f32)> = chunk_slice.par_iter()
+                    .filter_map(|image_data| {
+                        score_palette_match(&target_labs, &image_data.labs, &image_data.weights, is_single_color, is_atmosphere_search, &params)
+                            .map(|score| (image_data.file_path.clone(), score))
+                    })
+                    .collect();
+                scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+                matched_total += scored.len();
+
+                let scanned = (chunk_idx * chunk + chunk_slice.len()).min(total);
+                let _ = app.emit("palette-search-chunk", serde_json::json!({
+                    "chunkIndex": chunk_idx,
+                    "scanned": scanned,
+                    "total": total,
+                    "paths": scored.iter().map(|(p, _)| p.as_str()).collect::<Vec<_>>(),
+                }));
+            }
+
+            let _ = app.emit("palette-search-done", serde_json::json!({
+                "matched": matched_total,
+                "total": total,
+                "params": &params,
+            }));
+            matched_total
+        })
+    })
+    .await
+    .map_err(|e| format!("Search task failed: {}", e))?
+    .map_err(|e| format!("Cache access failed: {}", e))
+}
+
+/// 与 search_by_palette 相同，但响应附带生效的相似度参数，便于复现搜索结果
+#[tauri::command]
+pub async fn search_by_palette_detailed(
+    pool_state: tauri::State<'_, Arc<color_db::ColorDbPool>>,
+    target_palette: Vec<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<serde_json::Value, String> {
+    let params = current_similarity_params();
+    let paths = search_by_palette(pool_state, target_palette, offset, limit).await?;
+    Ok(serde_json::json!({
+        "paths": paths,
+        "params": params,
+    }))
 }
@@ -8,8 +8,8 @@ use std::num::NonZeroU32;
 use std::fs::{self, File};
 use std::io::{BufReader, Read};
 use std::sync::Mutex;
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use tauri::{AppHandle, Emitter};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use tauri::{AppHandle, Emitter, Manager};
 use serde::Serialize;
 use crossbeam_channel::{unbounded, Sender, Receiver};
 use tokio::task;
@@ -27,6 +27,11 @@ static IS_SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
 // 全局批次ID计数器
 static BATCH_ID_COUNTER: AtomicU64 = AtomicU64::new(0);
 
+// 启动以来的累计处理统计（供后台状态面板计算吞吐量）
+static PROCESSED_SINCE_START: AtomicU64 = AtomicU64::new(0);
+static FIRST_PROCESSED_AT_MS: AtomicU64 = AtomicU64::new(0);
+static LAST_WORKER_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
 // 进度报告结构体
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -87,6 +92,36 @@ pub fn is_shutting_down() -> bool {
     IS_SHUTTING_DOWN.load(Ordering::SeqCst)
 }
 
+// 记录一次处理结果，供 get_background_status 计算吞吐量
+fn record_result(error: Option<&str>) {
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let _ = FIRST_PROCESSED_AT_MS.compare_exchange(0, now_ms, Ordering::SeqCst, Ordering::SeqCst);
+    PROCESSED_SINCE_START.fetch_add(1, Ordering::SeqCst);
+    if let Some(msg) = error {
+        *LAST_WORKER_ERROR.lock().unwrap() = Some(msg.to_string());
+    }
+}
+
+// 汇报启动以来的累计统计：(已处理数, 每秒文件数, 最近一次错误)
+pub fn worker_stats() -> (u64, f64, Option<String>) {
+    let processed = PROCESSED_SINCE_START.load(Ordering::SeqCst);
+    let first = FIRST_PROCESSED_AT_MS.load(Ordering::SeqCst);
+    let rate = if processed > 0 && first > 0 {
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(first);
+        let elapsed_secs = (now_ms.saturating_sub(first) as f64 / 1000.0).max(0.001);
+        processed as f64 / elapsed_secs
+    } else {
+        0.0
+    };
+    (processed, rate, LAST_WORKER_ERROR.lock().unwrap().clone())
+}
+
 // 生成新的批次ID
 fn generate_batch_id() -> u64 {
     BATCH_ID_COUNTER.fetch_add(1, Ordering::SeqCst)
@@ -112,7 +147,7 @@ pub async fn color_extraction_worker(
     let (result_sender, result_receiver): (Sender<ProcessingResult>, Receiver<ProcessingResult>) = unbounded();
 
     // 创建批次信息通道（无界）
-    let (batch_info_sender, batch_info_receiver): (Sender<(u64, usize)>, Receiver<(u64, usize)>) = unbounded();
+    let (batch_info_sender, batch_info_receiver) = unbounded::<(u64, usize)>();
     
     // 使用互斥锁跟踪当前处理的文件，用于进度报告
     let current_file = Arc::new(Mutex::new(String::new()));
@@ -129,7 +164,12 @@ pub async fn color_extraction_worker(
     // - 上限设置为逻辑核心数，充分利用 CPU 性能
     let logical_cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
     let env_workers = std::env::var("AURORA_COLOR_WORKERS").ok().and_then(|s| s.parse::<usize>().ok());
-    let default_workers = logical_cores.max(4);
+    // 低内存模式下最多 2 个 worker，限制并发解码的内存峰值
+    let default_workers = if crate::is_low_memory() {
+        (logical_cores / 4).clamp(1, 2)
+    } else {
+        logical_cores.max(4)
+    };
     let num_workers = env_workers.unwrap_or(default_workers).min(logical_cores);
     // 在基准模式下输出配置（方便快速验证）
     if std::env::var("AURORA_BENCH").as_deref().ok() == Some("1") {
@@ -380,7 +420,7 @@ fn consumer_loop(
         match task_receiver.recv_timeout(Duration::from_millis(50)) {
             Ok((batch_id, file_path)) => {
                 // 更新当前处理的文件
-                let _ = *current_file.lock().unwrap() = file_path.clone();
+                *current_file.lock().unwrap() = file_path.clone();
 
                 // 识别重载格式并进入信号量保护
                 let is_heavy = file_path.to_lowercase().ends_with(".jxl") || file_path.to_lowercase().ends_with(".avif");
@@ -407,7 +447,7 @@ fn consumer_loop(
                 // 处理图片（解码 + 提取）并记录耗时
                 let processing_result: ProcessingResult = match img_res {
                     Ok(img) => {
-                        let colors = color_extractor::get_dominant_colors(&img, 8);
+                        let colors = color_extractor::get_dominant_colors_auto(&img);
                         let t_after_extract = std::time::Instant::now();
 
                         if bench {
@@ -523,9 +563,13 @@ async fn result_processor(
         match result_receiver.try_recv() {
             Ok(result) => {
                 let (batch_id, file_path, colors_opt, is_error) = match result {
-                    Ok((bid, path, colors)) => (bid, path, Some(colors), false),
+                    Ok((bid, path, colors)) => {
+                        record_result(None);
+                        (bid, path, Some(colors), false)
+                    }
                     Err((bid, err_msg)) => {
                         eprintln!("Error processing file: {}", err_msg);
+                        record_result(Some(&err_msg));
                         (bid, String::new(), None, true)
                     }
                 };
@@ -581,7 +625,7 @@ async fn result_processor(
                     }
                 }
                 
-                if (total_success_count + total_error_count) % 50 == 0 {
+                if (total_success_count + total_error_count).is_multiple_of(50) {
                     eprintln!("Total processed: {} (Success: {}, Errors: {})", 
                              total_success_count + total_error_count, total_success_count, total_error_count);
                 }
@@ -619,7 +663,7 @@ async fn result_processor(
                 // 通道暂时为空
                 let elapsed_time = last_save_time.elapsed();
                 if (!result_buffer.is_empty() && elapsed_time >= auto_save_interval) || is_paused() || is_shutting_down() {
-                    let batch_data: Vec<_> = result_buffer.drain(0..).collect();
+                    let batch_data: Vec<_> = std::mem::take(&mut result_buffer);
                     if is_shutting_down() || is_paused() {
                         save_batch_results(pool.clone(), batch_data).await;
                     } else {
@@ -651,7 +695,7 @@ async fn result_processor(
         
         // 2. 批量保存逻辑
         if result_buffer.len() >= batch_save_threshold {
-            let batch_data: Vec<_> = result_buffer.drain(0..).collect();
+            let batch_data: Vec<_> = std::mem::take(&mut result_buffer);
             tokio::task::spawn(save_batch_results(pool.clone(), batch_data));
             last_save_time = tokio::time::Instant::now();
         }
@@ -685,13 +729,13 @@ async fn result_processor(
                     }
                 }
                 if result_buffer.len() >= batch_save_threshold {
-                    let batch_data: Vec<_> = result_buffer.drain(0..).collect();
+                    let batch_data: Vec<_> = std::mem::take(&mut result_buffer);
                     save_batch_results(pool.clone(), batch_data).await;
                 }
             }
             
             if !result_buffer.is_empty() {
-                let batch_data: Vec<_> = result_buffer.drain(0..).collect();
+                let batch_data: Vec<_> = std::mem::take(&mut result_buffer);
                 save_batch_results(pool.clone(), batch_data).await;
             }
             
@@ -714,7 +758,7 @@ async fn result_processor(
     
     // 保存剩余的结果
     if !result_buffer.is_empty() {
-        let batch_data: Vec<_> = result_buffer.drain(0..).collect();
+        let batch_data: Vec<_> = std::mem::take(&mut result_buffer);
         save_batch_results(pool.clone(), batch_data).await;
     }
 }
@@ -924,7 +968,7 @@ async fn process_single_file(pool: Arc<ColorDbPool>, file_path: String) -> Resul
     let img = load_and_resize_image_optimized(&file_path, None)?;
     
     // 3. 提取主色调
-    let colors = color_extractor::get_dominant_colors(&img, 8);
+    let colors = color_extractor::get_dominant_colors_auto(&img);
     
     if colors.is_empty() {
         // 更新状态为错误
@@ -950,3 +994,185 @@ async fn process_single_file(pool: Arc<ColorDbPool>, file_path: String) -> Resul
     
     Ok(())
 }
+
+// 主动构建颜色索引的运行标志（避免重复启动）
+static BUILD_INDEX_RUNNING: AtomicBool = AtomicBool::new(false);
+
+// 主动构建颜色索引的进度
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColorIndexProgress {
+    pub processed: usize,
+    pub total: usize,
+    pub indexed: usize,
+    pub files_per_sec: f64,
+    pub eta_seconds: u64,
+}
+
+// 主动全速构建指定范围的颜色索引
+// 与后台的被动 color_worker 不同：不受暂停状态影响，占满 CPU 尽快完成，并上报吞吐量与预计剩余时间
+#[tauri::command]
+pub async fn build_color_index(scope: Option<String>, app: AppHandle) -> Result<usize, String> {
+    run_color_index_job(scope, true, "color-index-progress", app).await
+}
+
+// 用当前提取设置对范围内所有图片重新提取主色调
+// 不跳过已提取的文件，用于在切换提取算法 / 颜色数量后升级旧数据
+#[tauri::command]
+pub async fn reextract_colors(scope: Option<String>, app: AppHandle) -> Result<usize, String> {
+    run_color_index_job(scope, false, "color-reextract-progress", app).await
+}
+
+// build_color_index 与 reextract_colors 的共用实现
+// skip_existing 决定是否跳过 dominant_colors 中已是 'extracted' 状态的文件
+async fn run_color_index_job(
+    scope: Option<String>,
+    skip_existing: bool,
+    event: &'static str,
+    app: AppHandle,
+) -> Result<usize, String> {
+    if BUILD_INDEX_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("颜色索引构建已在运行".to_string());
+    }
+
+    let db_pool = app.state::<crate::db::AppDbPool>().inner().clone();
+    let color_pool = app.state::<Arc<ColorDbPool>>().inner().clone();
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        // 收集范围内的图片路径，跳过已完成提取的文件
+        let paths: Vec<String> = {
+            let conn = db_pool.get_connection();
+            let entries = match &scope {
+                Some(dir) => crate::db::file_index::get_entries_under_path(&conn, dir),
+                None => crate::db::file_index::get_all_image_files(&conn),
+            }
+            .map_err(|e| e.to_string())?;
+            entries
+                .into_iter()
+                .filter(|e| e.file_type == "Image")
+                .map(|e| e.path)
+                .collect()
+        };
+
+        let pending: Vec<String> = if skip_existing {
+            let extracted: std::collections::HashSet<String> = {
+                let conn = color_pool.get_connection();
+                let mut stmt = conn
+                    .prepare("SELECT file_path FROM dominant_colors WHERE status = 'extracted'")
+                    .map_err(|e| e.to_string())?;
+                let set = stmt
+                    .query_map([], |row| row.get(0))
+                    .map_err(|e| e.to_string())?
+                    .filter_map(|r| r.ok())
+                    .collect();
+                set
+            };
+            paths
+                .into_iter()
+                .filter(|p| !extracted.contains(p))
+                .collect()
+        } else {
+            paths
+        };
+
+        let total = pending.len();
+        if total == 0 {
+            return Ok(0usize);
+        }
+
+        let start = std::time::Instant::now();
+        let processed = AtomicUsize::new(0);
+        let indexed = AtomicUsize::new(0);
+        let buffer: Mutex<Vec<(String, Vec<color_extractor::ColorResult>)>> =
+            Mutex::new(Vec::new());
+
+        let flush = |batch: Vec<(String, Vec<color_extractor::ColorResult>)>| {
+            if batch.is_empty() {
+                return;
+            }
+            let refs: Vec<(&str, &[color_extractor::ColorResult])> = batch
+                .iter()
+                .map(|(path, colors)| (path.as_str(), colors.as_slice()))
+                .collect();
+            if let Err(e) = color_pool.batch_save_colors(&refs) {
+                eprintln!("Failed to batch save colors: {}", e);
+            } else {
+                indexed.fetch_add(batch.len(), Ordering::SeqCst);
+            }
+        };
+
+        // 正常全速占满核心；低内存模式下收紧到 1-2 个线程
+        let logical_cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+        let threads = if crate::is_low_memory() {
+            (logical_cores / 4).clamp(1, 2)
+        } else {
+            logical_cores
+        };
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .map_err(|e| format!("Failed to build thread pool: {}", e))?;
+
+        use rayon::prelude::*;
+        thread_pool.install(|| pending.par_iter().for_each(|path| {
+            if let Ok(img) = load_and_resize_image_optimized(path, None) {
+                let colors = color_extractor::get_dominant_colors_auto(&img);
+                if !colors.is_empty() {
+                    let full = {
+                        let mut guard = buffer.lock().unwrap();
+                        guard.push((path.clone(), colors));
+                        if guard.len() >= 64 {
+                            Some(std::mem::take(&mut *guard))
+                        } else {
+                            None
+                        }
+                    };
+                    if let Some(batch) = full {
+                        flush(batch);
+                    }
+                }
+            }
+
+            let done = processed.fetch_add(1, Ordering::SeqCst) + 1;
+            if done.is_multiple_of(50) || done == total {
+                let elapsed = start.elapsed().as_secs_f64().max(0.001);
+                let rate = done as f64 / elapsed;
+                let eta = if rate > 0.0 {
+                    ((total - done) as f64 / rate).ceil() as u64
+                } else {
+                    0
+                };
+                let _ = app.emit(
+                    event,
+                    ColorIndexProgress {
+                        processed: done,
+                        total,
+                        indexed: indexed.load(Ordering::SeqCst),
+                        files_per_sec: rate,
+                        eta_seconds: eta,
+                    },
+                );
+            }
+        }));
+
+        // 保存尾批
+        let remainder = std::mem::take(&mut *buffer.lock().unwrap());
+        flush(remainder);
+
+        Ok(indexed.load(Ordering::SeqCst))
+    })
+    .await
+    .map_err(|e| format!("颜色索引构建任务失败: {}", e));
+
+    BUILD_INDEX_RUNNING.store(false, Ordering::SeqCst);
+    result?
+}
+
+// 查询主动颜色索引构建是否正在运行
+#[tauri::command]
+pub fn is_color_index_building() -> bool {
+    BUILD_INDEX_RUNNING.load(Ordering::SeqCst)
+}
@@ -0,0 +1,200 @@
+//! 图片对比：给两张近似的导出图算结构差异，帮用户挑一张留下。
+//!
+//! [`compare_images`] 返回三样东西：
+//! 1. SSIM（灰度 8×8 窗口的结构相似度，1.0 为完全一致）；
+//! 2. 像素差异热力图（红色越亮差异越大），存成临时 PNG 返回路径；
+//! 3. EXIF 差异列表（只列两边取值不同的标签）。
+//!
+//! 两图尺寸不一致时按较小的一边缩到同尺寸再比。
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use image::GrayImage;
+use serde::Serialize;
+
+/// 对比结果
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareResult {
+    /// 结构相似度，0.0 - 1.0
+    pub ssim: f64,
+    /// 取值不同的像素占比，0.0 - 1.0
+    pub diff_pixel_ratio: f64,
+    /// 差异热力图 PNG 的临时文件路径
+    pub heatmap_path: String,
+    /// EXIF 差异（标签名 → 两边的取值）
+    pub exif_diffs: Vec<ExifDiff>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExifDiff {
+    pub tag: String,
+    pub value_a: Option<String>,
+    pub value_b: Option<String>,
+}
+
+/// 灰度图上按 8×8 窗口计算 SSIM 后取均值（标准常数 K1=0.01 K2=0.03）
+fn ssim(a: &GrayImage, b: &GrayImage) -> f64 {
+    const WINDOW: u32 = 8;
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+    let (width, height) = a.dimensions();
+    let mut total = 0.0;
+    let mut windows = 0usize;
+
+    let mut y = 0;
+    while y + WINDOW <= height {
+        let mut x = 0;
+        while x + WINDOW <= width {
+            let (mut sum_a, mut sum_b) = (0.0, 0.0);
+            let (mut sum_a2, mut sum_b2, mut sum_ab) = (0.0, 0.0, 0.0);
+            for dy in 0..WINDOW {
+                for dx in 0..WINDOW {
+                    let pa = a.get_pixel(x + dx, y + dy)[0] as f64;
+                    let pb = b.get_pixel(x + dx, y + dy)[0] as f64;
+                    sum_a += pa;
+                    sum_b += pb;
+                    sum_a2 += pa * pa;
+                    sum_b2 += pb * pb;
+                    sum_ab += pa * pb;
+                }
+            }
+            let n = (WINDOW * WINDOW) as f64;
+            let mu_a = sum_a / n;
+            let mu_b = sum_b / n;
+            let var_a = sum_a2 / n - mu_a * mu_a;
+            let var_b = sum_b2 / n - mu_b * mu_b;
+            let cov = sum_ab / n - mu_a * mu_b;
+            total += ((2.0 * mu_a * mu_b + C1) * (2.0 * cov + C2))
+                / ((mu_a * mu_a + mu_b * mu_b + C1) * (var_a + var_b + C2));
+            windows += 1;
+            x += WINDOW;
+        }
+        y += WINDOW;
+    }
+    if windows == 0 {
+        1.0
+    } else {
+        (total / windows as f64).clamp(0.0, 1.0)
+    }
+}
+
+/// 读 EXIF 成"标签名 → 显示值"表（没有 EXIF 的格式返回空表）
+fn read_exif_map(path: &str) -> BTreeMap<String, String> {
+    let mut map = BTreeMap::new();
+    let Ok(file) = std::fs::File::open(path) else {
+        return map;
+    };
+    let mut reader = std::io::BufReader::new(file);
+    let Ok(exif) = exif::Reader::new().read_from_container(&mut reader) else {
+        return map;
+    };
+    for field in exif.fields() {
+        if field.ifd_num == exif::In::PRIMARY {
+            map.insert(
+                field.tag.to_string(),
+                field.display_value().with_unit(&exif).to_string(),
+            );
+        }
+    }
+    map
+}
+
+fn exif_diffs(path_a: &str, path_b: &str) -> Vec<ExifDiff> {
+    let map_a = read_exif_map(path_a);
+    let map_b = read_exif_map(path_b);
+    let mut tags: Vec<&String> = map_a.keys().chain(map_b.keys()).collect();
+    tags.sort();
+    tags.dedup();
+    tags.into_iter()
+        .filter(|tag| map_a.get(*tag) != map_b.get(*tag))
+        .map(|tag| ExifDiff {
+            tag: tag.clone(),
+            value_a: map_a.get(tag).cloned(),
+            value_b: map_b.get(tag).cloned(),
+        })
+        .collect()
+}
+
+/// 对比两张图片，返回 SSIM、差异热力图与 EXIF 差异
+#[tauri::command]
+pub async fn compare_images(file_a: String, file_b: String) -> Result<CompareResult, String> {
+    if !Path::new(&file_a).is_file() {
+        return Err(format!("文件不存在: {}", file_a));
+    }
+    if !Path::new(&file_b).is_file() {
+        return Err(format!("文件不存在: {}", file_b));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let img_a = image::open(&file_a).map_err(|e| format!("解码失败: {}", e))?;
+        let img_b = image::open(&file_b).map_err(|e| format!("解码失败: {}", e))?;
+
+        // 统一到两图中较小的尺寸（并封顶 2048，超大图比对没必要全分辨率）
+        let width = img_a.width().min(img_b.width()).clamp(1, 2048);
+        let height = img_a.height().min(img_b.height()).clamp(1, 2048);
+        let rgb_a = img_a
+            .resize_exact(width, height, image::imageops::FilterType::Triangle)
+            .to_rgb8();
+        let rgb_b = img_b
+            .resize_exact(width, height, image::imageops::FilterType::Triangle)
+            .to_rgb8();
+
+        // 热力图：每像素通道差的最大值映射到红色亮度
+        let mut heatmap = image::RgbImage::new(width, height);
+        let mut diff_pixels = 0usize;
+        for y in 0..height {
+            for x in 0..width {
+                let pa = rgb_a.get_pixel(x, y);
+                let pb = rgb_b.get_pixel(x, y);
+                let delta = (0..3)
+                    .map(|c| pa[c].abs_diff(pb[c]))
+                    .max()
+                    .unwrap_or(0);
+                if delta > 0 {
+                    diff_pixels += 1;
+                }
+                heatmap.put_pixel(x, y, image::Rgb([delta, 0, 0]));
+            }
+        }
+        let heatmap_path = std::env::temp_dir().join(format!(
+            "aurora_diff_{}_{}.png",
+            std::process::id(),
+            chrono::Utc::now().timestamp_millis()
+        ));
+        heatmap
+            .save(&heatmap_path)
+            .map_err(|e| format!("保存热力图失败: {}", e))?;
+
+        let gray_a = image::DynamicImage::ImageRgb8(rgb_a).to_luma8();
+        let gray_b = image::DynamicImage::ImageRgb8(rgb_b).to_luma8();
+
+        Ok(CompareResult {
+            ssim: ssim(&gray_a, &gray_b),
+            diff_pixel_ratio: diff_pixels as f64 / (width as f64 * height as f64),
+            heatmap_path: heatmap_path.to_string_lossy().to_string(),
+            exif_diffs: exif_diffs(&file_a, &file_b),
+        })
+    })
+    .await
+    .map_err(|e| format!("对比任务失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ssim_identical_and_different() {
+        let img = GrayImage::from_fn(32, 32, |x, y| image::Luma([((x + y) % 256) as u8]));
+        assert!((ssim(&img, &img) - 1.0).abs() < 1e-9);
+
+        let inverted = GrayImage::from_fn(32, 32, |x, y| {
+            image::Luma([255 - ((x + y) % 256) as u8])
+        });
+        assert!(ssim(&img, &inverted) < 0.5);
+    }
+}
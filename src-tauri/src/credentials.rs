@@ -0,0 +1,156 @@
+//! 凭据存取：导出目标的密码 / Secret Key 不落在配置文件里。
+//!
+//! Windows 上走系统凭据管理器（Credential Manager，通用凭据），
+//! 其它平台回退为应用数据目录下的 credentials.json（Unix 下权限 0600）。
+//! 凭据按 "AuroraGallery/{名称}" 作为条目名存放。
+
+#[cfg(not(windows))]
+use std::collections::HashMap;
+#[cfg(not(windows))]
+use tauri::Manager;
+
+fn entry_name(name: &str) -> String {
+    format!("AuroraGallery/{}", name)
+}
+
+#[cfg(windows)]
+mod win {
+    use windows_sys::Win32::Foundation::FILETIME;
+    use windows_sys::Win32::Security::Credentials::{
+        CredDeleteW, CredFree, CredReadW, CredWriteW, CREDENTIALW, CRED_PERSIST_LOCAL_MACHINE,
+        CRED_TYPE_GENERIC,
+    };
+
+    fn to_wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    pub fn store(name: &str, secret: &str) -> Result<(), String> {
+        let target = to_wide(name);
+        let blob = secret.as_bytes();
+        let cred = CREDENTIALW {
+            Flags: 0,
+            Type: CRED_TYPE_GENERIC,
+            TargetName: target.as_ptr() as *mut u16,
+            Comment: std::ptr::null_mut(),
+            LastWritten: FILETIME {
+                dwLowDateTime: 0,
+                dwHighDateTime: 0,
+            },
+            CredentialBlobSize: blob.len() as u32,
+            CredentialBlob: blob.as_ptr() as *mut u8,
+            Persist: CRED_PERSIST_LOCAL_MACHINE,
+            AttributeCount: 0,
+            Attributes: std::ptr::null_mut(),
+            TargetAlias: std::ptr::null_mut(),
+            UserName: std::ptr::null_mut(),
+        };
+        let ok = unsafe { CredWriteW(&cred, 0) };
+        if ok == 0 {
+            return Err("写入系统凭据管理器失败".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn get(name: &str) -> Result<Option<String>, String> {
+        let target = to_wide(name);
+        let mut pcred: *mut CREDENTIALW = std::ptr::null_mut();
+        let ok = unsafe { CredReadW(target.as_ptr(), CRED_TYPE_GENERIC, 0, &mut pcred) };
+        if ok == 0 {
+            return Ok(None);
+        }
+        let secret = unsafe {
+            let cred = &*pcred;
+            let blob =
+                std::slice::from_raw_parts(cred.CredentialBlob, cred.CredentialBlobSize as usize);
+            let s = String::from_utf8_lossy(blob).to_string();
+            CredFree(pcred as *mut _);
+            s
+        };
+        Ok(Some(secret))
+    }
+
+    pub fn delete(name: &str) {
+        let target = to_wide(name);
+        unsafe {
+            let _ = CredDeleteW(target.as_ptr(), CRED_TYPE_GENERIC, 0);
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn fallback_path(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join("credentials.json"))
+}
+
+#[cfg(not(windows))]
+fn load_fallback(app: &tauri::AppHandle) -> Result<HashMap<String, String>, String> {
+    let path = fallback_path(app)?;
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+#[cfg(not(windows))]
+fn save_fallback(app: &tauri::AppHandle, map: &HashMap<String, String>) -> Result<(), String> {
+    let path = fallback_path(app)?;
+    let content = serde_json::to_string_pretty(map).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| e.to_string())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+    }
+    Ok(())
+}
+
+/// 保存一条凭据（同名覆盖）
+pub fn store_secret(app: &tauri::AppHandle, name: &str, secret: &str) -> Result<(), String> {
+    let entry = entry_name(name);
+    #[cfg(windows)]
+    {
+        let _ = app;
+        win::store(&entry, secret)
+    }
+    #[cfg(not(windows))]
+    {
+        let mut map = load_fallback(app)?;
+        map.insert(entry, secret.to_string());
+        save_fallback(app, &map)
+    }
+}
+
+/// 读取凭据，不存在时返回 None
+pub fn get_secret(app: &tauri::AppHandle, name: &str) -> Result<Option<String>, String> {
+    let entry = entry_name(name);
+    #[cfg(windows)]
+    {
+        let _ = app;
+        win::get(&entry)
+    }
+    #[cfg(not(windows))]
+    {
+        Ok(load_fallback(app)?.get(&entry).cloned())
+    }
+}
+
+/// 删除凭据（不存在时静默）
+pub fn delete_secret(app: &tauri::AppHandle, name: &str) {
+    let entry = entry_name(name);
+    #[cfg(windows)]
+    {
+        let _ = app;
+        win::delete(&entry);
+    }
+    #[cfg(not(windows))]
+    {
+        if let Ok(mut map) = load_fallback(app) {
+            map.remove(&entry);
+            let _ = save_fallback(app, &map);
+        }
+    }
+}
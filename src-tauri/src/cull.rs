@@ -0,0 +1,202 @@
+//! 选片（culling）会话：摄影师逐张按 keep / reject 走完一个文件夹，
+//! 会话结束时才对落选的统一打标或移走，中途随便反悔。
+//!
+//! 会话是单例的后端内存状态——选片本来就是一次专注走完的流程，
+//! 新会话直接顶掉旧的。决定过程只记内存，不碰数据库；落盘动作
+//! 全部集中在 finish_cull_session，这也是键盘连打能跟上手速的原因。
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::db::{self, AppDbPool};
+
+/// 当前选片会话（None 表示没有进行中的会话）
+static SESSION: Lazy<Mutex<Option<CullSession>>> = Lazy::new(|| Mutex::new(None));
+
+struct CullSession {
+    /// 按路径排序的待选队列
+    queue: Vec<CullItem>,
+    /// file_id → 是否保留
+    decisions: HashMap<String, bool>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CullItem {
+    pub file_id: String,
+    pub path: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CullProgress {
+    pub decided: usize,
+    pub total: usize,
+    pub rejected: usize,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CullSummary {
+    pub kept: usize,
+    pub rejected: usize,
+    /// 实际执行了落盘动作的文件数（move 失败的不计）
+    pub applied: usize,
+}
+
+/// 开始一个选片会话，返回按路径排序的队列（顶掉未完成的旧会话）。
+/// folder_id 也可以是搜索结果的虚拟文件夹 id，直接对命中列表选片
+#[tauri::command]
+pub fn start_cull_session(
+    folder_id: String,
+    pool: tauri::State<AppDbPool>,
+) -> Result<Vec<CullItem>, String> {
+    let conn = pool.get_connection();
+    let mut queue: Vec<CullItem> = if crate::virtual_folder::is_virtual_id(&folder_id) {
+        let ids = crate::virtual_folder::resolve_ids(&folder_id)
+            .ok_or("虚拟文件夹不存在或已失效")?;
+        let mut items = Vec::with_capacity(ids.len());
+        for id in &ids {
+            if let Some(e) = db::file_index::get_entry_by_id(&conn, id).map_err(|e| e.to_string())? {
+                if e.file_type == "Image" {
+                    items.push(CullItem { file_id: e.file_id, path: e.path, name: e.name });
+                }
+            }
+        }
+        items
+    } else {
+        let folder = db::file_index::get_entry_by_id(&conn, &folder_id)
+            .map_err(|e| e.to_string())?
+            .ok_or("文件夹不在索引中")?;
+        if folder.file_type != "Folder" {
+            return Err("选片会话只能从文件夹开始".to_string());
+        }
+        db::file_index::get_entries_under_path(&conn, &folder.path)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|e| e.file_type == "Image")
+            .map(|e| CullItem { file_id: e.file_id, path: e.path, name: e.name })
+            .collect()
+    };
+    queue.sort_by(|a, b| a.path.cmp(&b.path));
+    if queue.is_empty() {
+        return Err("该文件夹下没有可选片的图片".to_string());
+    }
+
+    *SESSION.lock().unwrap() = Some(CullSession { queue: queue.clone(), decisions: HashMap::new() });
+    Ok(queue)
+}
+
+/// 记录一个决定。decision："keep" | "reject"，重复决定覆盖旧值
+#[tauri::command]
+pub fn cull_decide(file_id: String, decision: String) -> Result<CullProgress, String> {
+    let keep = match decision.as_str() {
+        "keep" => true,
+        "reject" => false,
+        other => return Err(format!("无效的决定: {}（可选 keep / reject）", other)),
+    };
+    let mut guard = SESSION.lock().unwrap();
+    let session = guard.as_mut().ok_or("没有进行中的选片会话")?;
+    if !session.queue.iter().any(|item| item.file_id == file_id) {
+        return Err("该文件不在本次选片队列中".to_string());
+    }
+    session.decisions.insert(file_id, keep);
+    Ok(CullProgress {
+        decided: session.decisions.len(),
+        total: session.queue.len(),
+        rejected: session.decisions.values().filter(|keep| !**keep).count(),
+    })
+}
+
+/// 结束会话并批量处理落选文件。action："tag"（打 rejected 标签）|
+/// "move"（移到 dest_dir 并移出索引）| "none"（只结束，不动文件）
+#[tauri::command]
+pub async fn finish_cull_session(
+    action: String,
+    dest_dir: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<CullSummary, String> {
+    let session = SESSION.lock().unwrap().take().ok_or("没有进行中的选片会话")?;
+    let rejected: Vec<CullItem> = session
+        .queue
+        .iter()
+        .filter(|item| session.decisions.get(&item.file_id) == Some(&false))
+        .cloned()
+        .collect();
+    let kept = session.decisions.values().filter(|keep| **keep).count();
+
+    let mut summary = CullSummary { kept, rejected: rejected.len(), applied: 0 };
+    match action.as_str() {
+        "none" => return Ok(summary),
+        "tag" | "move" => {}
+        other => return Err(format!("无效的动作: {}（可选 tag / move / none）", other)),
+    }
+    if rejected.is_empty() {
+        return Ok(summary);
+    }
+
+    let pool = app.state::<AppDbPool>().inner().clone();
+    let move_paths = tokio::task::spawn_blocking(move || -> Result<(CullSummary, Vec<String>), String> {
+        let mut conn = pool.get_connection();
+        let mut moved_old_paths = Vec::new();
+        if action == "tag" {
+            for item in &rejected {
+                db::file_metadata::merge_imported_tags(&conn, &item.file_id, &item.path, &["rejected".to_string()])
+                    .map_err(|e| e.to_string())?;
+                let _ = db::activity_log::record(&conn, "tag", &item.path, Some("cull:rejected"), "cull");
+                summary.applied += 1;
+            }
+        } else {
+            let dest = dest_dir.ok_or("move 动作需要 dest_dir")?;
+            let dest_root = std::path::Path::new(&dest);
+            std::fs::create_dir_all(dest_root).map_err(|e| format!("创建目标目录失败: {}", e))?;
+            let mut removed_ids = Vec::new();
+            for item in &rejected {
+                let target = crate::generate_unique_file_path(
+                    &dest_root.join(&item.name).to_string_lossy(),
+                );
+                let moved = std::fs::rename(&item.path, &target).or_else(|_| {
+                    std::fs::copy(&item.path, &target)
+                        .and_then(|_| std::fs::remove_file(&item.path))
+                        .map(|_| ())
+                });
+                match moved {
+                    Ok(()) => {
+                        removed_ids.push(item.file_id.clone());
+                        moved_old_paths.push(item.path.clone());
+                        let _ = db::activity_log::record(
+                            &conn,
+                            "move",
+                            &item.path,
+                            Some(&format!("cull:moved-to {}", target)),
+                            "cull",
+                        );
+                        summary.applied += 1;
+                    }
+                    Err(e) => log::warn!("选片移动 {} 失败: {}", item.path, e),
+                }
+            }
+            db::file_index::delete_entries_by_ids(&mut conn, &removed_ids).map_err(|e| e.to_string())?;
+        }
+        Ok((summary, moved_old_paths))
+    })
+    .await
+    .map_err(|e| format!("选片收尾任务失败: {}", e))??;
+
+    let (summary, moved_old_paths) = move_paths;
+    for path in &moved_old_paths {
+        crate::invalidate_file_caches(&app, path).await;
+    }
+    Ok(summary)
+}
+
+/// 放弃当前选片会话（不做任何落盘动作）
+#[tauri::command]
+pub fn cancel_cull_session() -> bool {
+    SESSION.lock().unwrap().take().is_some()
+}
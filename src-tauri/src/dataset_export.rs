@@ -0,0 +1,331 @@
+//! 训练集导出：把库里的图片连同描述 / 标签导出成常见的
+//! (image, caption) 训练格式，给 AI 绘画用户喂 LoRA / 微调用。
+//!
+//! 支持两种布局：
+//! - kohya：`{repeats}_{concept}/` 目录下图片与同名 .txt 字幕成对；
+//! - jsonl：`images/` 目录 + `metadata.jsonl`（每行 file_name + text，
+//!   HuggingFace datasets 风格）。
+//!
+//! 字幕优先取手写描述，其次 AI 描述，再拼上手动与 AI 标签。
+//! 图片按 kohya 的分辨率桶处理：等效面积 resolution²、宽高取 64 的
+//! 倍数、按原图宽高比选桶，先缩放覆盖再居中裁剪。
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::db::AppDbPool;
+
+/// 导出任务的单飞标志
+static DATASET_EXPORT_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 桶边长的对齐粒度（kohya 约定 64）
+const BUCKET_STEP: u32 = 64;
+
+/// 导出选项
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DatasetExportOptions {
+    /// 库内目录路径，限定导出范围；空串 = 整个库
+    #[serde(default)]
+    pub scope: String,
+    /// "kohya" | "jsonl"
+    pub format: String,
+    pub dest_dir: String,
+    /// 分辨率桶的基准边长（默认 1024，即面积约 1024²）
+    #[serde(default)]
+    pub resolution: Option<u32>,
+    /// kohya 目录名里的概念词（默认 "dataset"）
+    #[serde(default)]
+    pub concept: Option<String>,
+    /// kohya 目录名里的重复次数（默认 1）
+    #[serde(default)]
+    pub repeats: Option<u32>,
+    /// 只导出有字幕内容的图（默认 true；关掉则无字幕的图配空字幕）
+    #[serde(default)]
+    pub require_caption: Option<bool>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DatasetExportReport {
+    pub exported: usize,
+    pub skipped_no_caption: usize,
+    pub failed: Vec<String>,
+    pub dest_dir: String,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct DatasetExportProgress {
+    processed: usize,
+    total: usize,
+    current: String,
+}
+
+/// 按原图宽高比选分辨率桶：面积约 base²，两边都是 64 的倍数
+fn bucket_dims(width: u32, height: u32, base: u32) -> (u32, u32) {
+    let area = base as f64 * base as f64;
+    let ar = width.max(1) as f64 / height.max(1) as f64;
+    let bw = (area * ar).sqrt();
+    let bw = ((bw / BUCKET_STEP as f64).round() as u32 * BUCKET_STEP).max(BUCKET_STEP);
+    let bh = area / bw as f64;
+    let bh = ((bh / BUCKET_STEP as f64).round() as u32 * BUCKET_STEP).max(BUCKET_STEP);
+    (bw, bh)
+}
+
+/// 缩放覆盖桶尺寸后居中裁剪（kohya 的 bucket 处理方式）
+fn fit_to_bucket(img: &image::DynamicImage, bw: u32, bh: u32) -> image::DynamicImage {
+    let scale = (bw as f64 / img.width() as f64).max(bh as f64 / img.height() as f64);
+    let rw = ((img.width() as f64 * scale).ceil() as u32).max(bw);
+    let rh = ((img.height() as f64 * scale).ceil() as u32).max(bh);
+    let resized = img.resize_exact(rw, rh, image::imageops::FilterType::Lanczos3);
+    resized.crop_imm((rw - bw) / 2, (rh - bh) / 2, bw, bh)
+}
+
+/// 组装字幕：描述（手写优先，其次 AI）在前，标签（手动 + AI 去重）逗号接续
+fn build_caption(
+    description: Option<&str>,
+    tags: Option<&serde_json::Value>,
+    ai_data: Option<&serde_json::Value>,
+) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let desc = description
+        .filter(|d| !d.trim().is_empty())
+        .map(|d| d.trim().to_string())
+        .or_else(|| {
+            ai_data
+                .and_then(|a| a.get("description"))
+                .and_then(|d| d.as_str())
+                .filter(|d| !d.trim().is_empty())
+                .map(|d| d.trim().to_string())
+        });
+    if let Some(desc) = desc {
+        parts.push(desc);
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    let mut push_tags = |value: Option<&serde_json::Value>| {
+        if let Some(arr) = value.and_then(|v| v.as_array()) {
+            for tag in arr.iter().filter_map(|t| t.as_str()) {
+                let tag = tag.trim();
+                if !tag.is_empty() && seen.insert(tag.to_lowercase()) {
+                    parts.push(tag.to_string());
+                }
+            }
+        }
+    };
+    push_tags(tags);
+    push_tags(ai_data.and_then(|a| a.get("tags")));
+
+    parts.join(", ")
+}
+
+/// JSONL 行的转义由 serde 负责，这里只定义形状
+#[derive(Serialize)]
+struct JsonlRecord<'a> {
+    file_name: &'a str,
+    text: &'a str,
+}
+
+/// 一行导出素材：路径、手写描述、标签 JSON、ai_data JSON
+type CaptionRow = (
+    String,
+    Option<String>,
+    Option<serde_json::Value>,
+    Option<serde_json::Value>,
+);
+
+/// 导出训练集。scope 为库内目录（空 = 整库），format 为 "kohya" 或 "jsonl"
+#[tauri::command]
+pub async fn export_training_dataset(
+    options: DatasetExportOptions,
+    pool: tauri::State<'_, AppDbPool>,
+    app: tauri::AppHandle,
+) -> Result<DatasetExportReport, String> {
+    match options.format.as_str() {
+        "kohya" | "jsonl" => {}
+        other => return Err(format!("不支持的格式: {}（可选 kohya / jsonl）", other)),
+    }
+    let pool = pool.inner().clone();
+
+    if DATASET_EXPORT_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("已有训练集导出在进行中".to_string());
+    }
+    let result =
+        tokio::task::spawn_blocking(move || export_inner(&pool, &options, &app)).await;
+    DATASET_EXPORT_RUNNING.store(false, Ordering::SeqCst);
+    result.map_err(|e| format!("训练集导出任务失败: {}", e))?
+}
+
+fn export_inner(
+    pool: &AppDbPool,
+    options: &DatasetExportOptions,
+    app: &tauri::AppHandle,
+) -> Result<DatasetExportReport, String> {
+    let resolution = options.resolution.unwrap_or(1024).clamp(256, 4096);
+    let require_caption = options.require_caption.unwrap_or(true);
+
+    // 范围内的图片连同字幕素材一次查出
+    let rows: Vec<CaptionRow> = {
+        let conn = pool.get_connection();
+        let scope = crate::db::normalize_path(&options.scope);
+        let mut stmt = conn
+            .prepare(
+                "SELECT fi.path, fm.description, fm.tags, fm.ai_data
+                 FROM file_index fi
+                 LEFT JOIN file_metadata fm ON fm.file_id = fi.file_id
+                 WHERE fi.file_type = 'Image' AND fi.path LIKE ?1 || '%'
+                 ORDER BY fi.path",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([scope], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+    if rows.is_empty() {
+        return Err("范围内没有图片".to_string());
+    }
+
+    let image_dir = match options.format.as_str() {
+        "kohya" => {
+            let concept = options
+                .concept
+                .as_deref()
+                .filter(|c| !c.trim().is_empty())
+                .unwrap_or("dataset");
+            PathBuf::from(&options.dest_dir).join(format!(
+                "{}_{}",
+                options.repeats.unwrap_or(1).max(1),
+                concept.trim()
+            ))
+        }
+        _ => PathBuf::from(&options.dest_dir).join("images"),
+    };
+    std::fs::create_dir_all(&image_dir).map_err(|e| format!("创建目标目录失败: {}", e))?;
+
+    let mut jsonl: Option<std::io::BufWriter<std::fs::File>> = if options.format == "jsonl" {
+        let file = std::fs::File::create(PathBuf::from(&options.dest_dir).join("metadata.jsonl"))
+            .map_err(|e| format!("创建 metadata.jsonl 失败: {}", e))?;
+        Some(std::io::BufWriter::new(file))
+    } else {
+        None
+    };
+
+    let total = rows.len();
+    let mut exported = 0usize;
+    let mut skipped_no_caption = 0usize;
+    let mut failed = Vec::new();
+
+    for (processed, (path, description, tags, ai_data)) in rows.into_iter().enumerate() {
+        let caption = build_caption(description.as_deref(), tags.as_ref(), ai_data.as_ref());
+        if caption.is_empty() && require_caption {
+            skipped_no_caption += 1;
+            continue;
+        }
+        if !Path::new(&path).is_file() {
+            failed.push(format!("{}（文件不存在）", path));
+            continue;
+        }
+        let img = match image::open(&path) {
+            Ok(img) => img,
+            Err(e) => {
+                failed.push(format!("{}（解码失败: {}）", path, e));
+                continue;
+            }
+        };
+        let (bw, bh) = bucket_dims(img.width(), img.height(), resolution);
+        let bucketed = fit_to_bucket(&img, bw, bh);
+
+        // 统一存 PNG，文件名加序号避免不同目录的同名图互相覆盖
+        let stem = Path::new(&path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("image");
+        let file_name = format!("{:05}_{}.png", exported, stem);
+        let out = image_dir.join(&file_name);
+        if let Err(e) = bucketed.save(&out) {
+            failed.push(format!("{}（保存失败: {}）", out.display(), e));
+            continue;
+        }
+
+        if let Some(writer) = &mut jsonl {
+            let rel = format!("images/{}", file_name);
+            let record = JsonlRecord {
+                file_name: &rel,
+                text: &caption,
+            };
+            let line = serde_json::to_string(&record).map_err(|e| e.to_string())?;
+            writeln!(writer, "{}", line).map_err(|e| format!("写 metadata.jsonl 失败: {}", e))?;
+        } else {
+            let txt = image_dir.join(format!("{:05}_{}.txt", exported, stem));
+            std::fs::write(&txt, &caption).map_err(|e| format!("写字幕失败: {}", e))?;
+        }
+        exported += 1;
+
+        if processed.is_multiple_of(10) {
+            let _ = app.emit(
+                "dataset-export-progress",
+                DatasetExportProgress {
+                    processed,
+                    total,
+                    current: path.clone(),
+                },
+            );
+        }
+    }
+
+    if let Some(mut writer) = jsonl {
+        writer
+            .flush()
+            .map_err(|e| format!("写 metadata.jsonl 失败: {}", e))?;
+    }
+
+    Ok(DatasetExportReport {
+        exported,
+        skipped_no_caption,
+        failed,
+        dest_dir: options.dest_dir.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_dims_aspect_and_alignment() {
+        // 方图：恰好 base × base
+        assert_eq!(bucket_dims(2000, 2000, 1024), (1024, 1024));
+        // 横图：宽 > 高，两边都是 64 的倍数，面积接近 1024²
+        let (bw, bh) = bucket_dims(3000, 2000, 1024);
+        assert!(bw > bh);
+        assert!(bw.is_multiple_of(64) && bh.is_multiple_of(64));
+        let area = bw as f64 * bh as f64;
+        assert!((area / (1024.0 * 1024.0) - 1.0).abs() < 0.15);
+    }
+
+    #[test]
+    fn test_build_caption_priority_and_dedup() {
+        let tags = serde_json::json!(["风景", "山"]);
+        let ai = serde_json::json!({
+            "description": "AI 描述",
+            "tags": ["山", "雪"]
+        });
+        // 手写描述优先，标签跨来源去重
+        let caption = build_caption(Some("手写描述"), Some(&tags), Some(&ai));
+        assert_eq!(caption, "手写描述, 风景, 山, 雪");
+        // 没有手写描述时落到 AI 描述
+        let caption = build_caption(None, None, Some(&ai));
+        assert_eq!(caption, "AI 描述, 山, 雪");
+    }
+}
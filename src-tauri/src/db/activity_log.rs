@@ -0,0 +1,95 @@
+use rusqlite::{params, Connection, Result};
+use serde::{Deserialize, Serialize};
+
+/// 一条库变更记录，用于大规模导入/同步之后审计发生了什么
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityEntry {
+    pub id: i64,
+    /// 动作类型："add" | "delete" | "move" | "rename" | "metadata" | "scan" | "cleanup"
+    pub action: String,
+    pub path: String,
+    /// 附加信息（移动的目标路径、扫描新增数量等）
+    pub detail: Option<String>,
+    /// 来源："user"（用户操作）或 "background"（后台任务）
+    pub origin: String,
+    pub created_at: i64,
+}
+
+pub fn create_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS activity_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            action TEXT NOT NULL,
+            path TEXT NOT NULL,
+            detail TEXT,
+            origin TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_activity_log_created ON activity_log(created_at)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// 追加一条变更记录
+pub fn record(
+    conn: &Connection,
+    action: &str,
+    path: &str,
+    detail: Option<&str>,
+    origin: &str,
+) -> Result<()> {
+    let now = chrono::Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO activity_log (action, path, detail, origin, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![action, path, detail, origin, now],
+    )?;
+    Ok(())
+}
+
+/// 读取变更记录（since 为 Unix 秒时间戳，None 表示不限），按时间倒序
+pub fn get_feed(
+    conn: &Connection,
+    since: Option<i64>,
+    limit: usize,
+) -> Result<Vec<ActivityEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, action, path, detail, origin, created_at
+         FROM activity_log
+         WHERE created_at >= ?1
+         ORDER BY created_at DESC, id DESC
+         LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![since.unwrap_or(0), limit as i64], |row| {
+        Ok(ActivityEntry {
+            id: row.get(0)?,
+            action: row.get(1)?,
+            path: row.get(2)?,
+            detail: row.get(3)?,
+            origin: row.get(4)?,
+            created_at: row.get(5)?,
+        })
+    })?;
+
+    let mut entries = Vec::new();
+    for row in rows {
+        entries.push(row?);
+    }
+    Ok(entries)
+}
+
+/// 控制表大小：只保留最近的 max_rows 条
+pub fn prune(conn: &Connection, max_rows: usize) -> Result<()> {
+    conn.execute(
+        "DELETE FROM activity_log WHERE id NOT IN (
+            SELECT id FROM activity_log ORDER BY id DESC LIMIT ?1
+         )",
+        params![max_rows as i64],
+    )?;
+    Ok(())
+}
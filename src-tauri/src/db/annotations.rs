@@ -0,0 +1,41 @@
+//! 每张图一份矢量批注（箭头 / 文字 / 高亮），整体存 JSON 数组。
+//! 批注形状的解析与渲染在 `crate::annotate`，这里只管存取
+
+use rusqlite::{params, Connection, OptionalExtension, Result};
+
+pub fn create_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS annotations (
+            file_id TEXT PRIMARY KEY,
+            path TEXT NOT NULL,
+            data TEXT NOT NULL,
+            updated_at INTEGER
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn get_annotations(conn: &Connection, file_id: &str) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT data FROM annotations WHERE file_id = ?1",
+        params![file_id],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+pub fn set_annotations(conn: &Connection, file_id: &str, path: &str, data: &str) -> Result<()> {
+    let now = chrono::Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO annotations (file_id, path, data, updated_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(file_id) DO UPDATE SET data = excluded.data, updated_at = excluded.updated_at",
+        params![file_id, path, data, now],
+    )?;
+    Ok(())
+}
+
+pub fn delete_annotations(conn: &Connection, file_id: &str) -> Result<()> {
+    conn.execute("DELETE FROM annotations WHERE file_id = ?1", params![file_id])?;
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use rusqlite::{params, Connection, Result};
+use serde::{Deserialize, Serialize};
+
+/// 侧边栏分类（带排序、颜色和图标），file_metadata.category 按名称引用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Category {
+    pub id: String,
+    pub name: String,
+    /// 颜色 (#RRGGBB)，前端展示用
+    pub color: Option<String>,
+    /// 图标名，前端展示用
+    pub icon: Option<String>,
+    pub sort_order: i64,
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
+}
+
+/// 分类名及其下的文件数
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryCount {
+    pub category: String,
+    pub count: i64,
+}
+
+pub fn create_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS categories (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE,
+            color TEXT,
+            icon TEXT,
+            sort_order INTEGER DEFAULT 0,
+            created_at INTEGER,
+            updated_at INTEGER
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn get_all_categories(conn: &Connection) -> Result<Vec<Category>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, color, icon, sort_order, created_at, updated_at
+         FROM categories ORDER BY sort_order, name",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(Category {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            color: row.get(2)?,
+            icon: row.get(3)?,
+            sort_order: row.get::<_, Option<i64>>(4)?.unwrap_or(0),
+            created_at: row.get(5)?,
+            updated_at: row.get(6)?,
+        })
+    })?;
+    rows.collect()
+}
+
+pub fn upsert_category(conn: &Connection, category: &Category) -> Result<()> {
+    conn.execute(
+        "INSERT INTO categories (id, name, color, icon, sort_order, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(id) DO UPDATE SET
+            name = excluded.name,
+            color = excluded.color,
+            icon = excluded.icon,
+            sort_order = excluded.sort_order,
+            updated_at = excluded.updated_at",
+        params![
+            category.id,
+            category.name,
+            category.color,
+            category.icon,
+            category.sort_order,
+            category.created_at,
+            category.updated_at
+        ],
+    )?;
+    Ok(())
+}
+
+/// 重命名分类时同步更新引用它的文件元数据
+pub fn rename_category(conn: &Connection, id: &str, new_name: &str) -> Result<()> {
+    use rusqlite::OptionalExtension;
+    let old_name: Option<String> = conn
+        .query_row(
+            "SELECT name FROM categories WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(old_name) = old_name {
+        if old_name != new_name {
+            conn.execute(
+                "UPDATE categories SET name = ?1, updated_at = ?2 WHERE id = ?3",
+                params![new_name, chrono::Utc::now().timestamp(), id],
+            )?;
+            conn.execute(
+                "UPDATE file_metadata SET category = ?1 WHERE category = ?2",
+                params![new_name, old_name],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// 删除分类，引用它的文件元数据 category 置空
+pub fn delete_category(conn: &Connection, id: &str) -> Result<()> {
+    use rusqlite::OptionalExtension;
+    let name: Option<String> = conn
+        .query_row(
+            "SELECT name FROM categories WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    conn.execute("DELETE FROM categories WHERE id = ?1", params![id])?;
+    if let Some(name) = name {
+        conn.execute(
+            "UPDATE file_metadata SET category = NULL WHERE category = ?1",
+            params![name],
+        )?;
+    }
+    Ok(())
+}
+
+/// 按传入顺序重排分类
+pub fn reorder_categories(conn: &Connection, ids: &[String]) -> Result<()> {
+    let now = chrono::Utc::now().timestamp();
+    for (i, id) in ids.iter().enumerate() {
+        conn.execute(
+            "UPDATE categories SET sort_order = ?1, updated_at = ?2 WHERE id = ?3",
+            params![i as i64, now, id],
+        )?;
+    }
+    Ok(())
+}
+
+/// 各分类下的文件数（只统计有分类的文件），供侧边栏展示
+pub fn get_category_counts(conn: &Connection) -> Result<Vec<CategoryCount>> {
+    let mut stmt = conn.prepare(
+        "SELECT category, COUNT(*) FROM file_metadata
+         WHERE category IS NOT NULL AND category != ''
+         GROUP BY category",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(CategoryCount {
+            category: row.get(0)?,
+            count: row.get(1)?,
+        })
+    })?;
+    rows.collect()
+}
@@ -0,0 +1,80 @@
+use rusqlite::{params, Connection, Result};
+use serde::{Deserialize, Serialize};
+
+/// 一条解码失败记录（损坏或不支持的图片）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodeErrorEntry {
+    pub file_id: String,
+    pub path: String,
+    pub error: String,
+    /// 累计失败次数
+    pub attempts: i64,
+    pub updated_at: i64,
+}
+
+pub fn create_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS decode_errors (
+            file_id TEXT PRIMARY KEY,
+            path TEXT NOT NULL,
+            error TEXT,
+            attempts INTEGER DEFAULT 1,
+            updated_at INTEGER
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// 记录一次解码失败（同一文件重复失败时累加次数并更新错误信息）
+pub fn record_decode_error(conn: &Connection, path: &str, error: &str) -> Result<()> {
+    let normalized = super::normalize_path(path);
+    let file_id = super::generate_id(&normalized);
+    let now = chrono::Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO decode_errors (file_id, path, error, attempts, updated_at)
+         VALUES (?1, ?2, ?3, 1, ?4)
+         ON CONFLICT(file_id) DO UPDATE SET
+            error = excluded.error,
+            attempts = attempts + 1,
+            updated_at = excluded.updated_at",
+        params![file_id, normalized, error, now],
+    )?;
+    Ok(())
+}
+
+/// 解码成功后清除失败记录（文件可能被替换修复）
+pub fn clear_decode_error(conn: &Connection, path: &str) -> Result<()> {
+    let file_id = super::generate_id(path);
+    conn.execute("DELETE FROM decode_errors WHERE file_id = ?1", params![file_id])?;
+    Ok(())
+}
+
+/// 删除某个路径（含目录前缀）下的失败记录，文件被删除/移动时调用
+pub fn delete_errors_by_path(conn: &Connection, path: &str) -> Result<()> {
+    let normalized = super::normalize_path(path);
+    let dir_pattern = format!("{}/%", normalized);
+    conn.execute(
+        "DELETE FROM decode_errors WHERE path = ?1 OR path LIKE ?2",
+        params![normalized, dir_pattern],
+    )?;
+    Ok(())
+}
+
+/// 返回所有记录在案的损坏文件（按最近失败时间倒序）
+pub fn get_corrupt_files(conn: &Connection) -> Result<Vec<DecodeErrorEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT file_id, path, error, attempts, updated_at
+         FROM decode_errors ORDER BY updated_at DESC",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(DecodeErrorEntry {
+            file_id: row.get(0)?,
+            path: row.get(1)?,
+            error: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+            attempts: row.get(3)?,
+            updated_at: row.get::<_, Option<i64>>(4)?.unwrap_or(0),
+        })
+    })?;
+    rows.collect()
+}
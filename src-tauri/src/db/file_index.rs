@@ -16,6 +16,14 @@ pub struct FileIndexEntry {
     pub width: Option<u32>,
     pub height: Option<u32>,
     pub format: Option<String>,
+    /// 位深（每通道比特数），扫描时从文件头读取
+    pub bit_depth: Option<u32>,
+    /// 色彩空间 / 配置文件（如 sRGB、CMYK、Gray、ICC）
+    pub color_space: Option<String>,
+    /// 是否带透明通道
+    pub has_alpha: Option<bool>,
+    /// 是否为动图（GIF/APNG/动态 WebP）
+    pub is_animated: Option<bool>,
 }
 
 pub fn create_table(conn: &Connection) -> Result<()> {
@@ -31,7 +39,11 @@ pub fn create_table(conn: &Connection) -> Result<()> {
             modified_at INTEGER DEFAULT 0,
             width INTEGER,
             height INTEGER,
-            format TEXT
+            format TEXT,
+            bit_depth INTEGER,
+            color_space TEXT,
+            has_alpha INTEGER,
+            is_animated INTEGER
         )",
         [],
     )?;
@@ -40,27 +52,58 @@ pub fn create_table(conn: &Connection) -> Result<()> {
         "CREATE INDEX IF NOT EXISTS idx_file_index_path ON file_index(path)",
         [],
     )?;
-    
+
+    // LIKE 默认大小写不敏感，前缀匹配（整树移动/删除）要有 NOCASE
+    // collation 的索引才能走索引，否则是全表扫描。
+    // Windows 的文件系统本身大小写不敏感，同一路径换个大小写再入索引
+    // 会生成不同的 file_id 产生重复行，所以在 Windows 上这个索引还要
+    // 加 UNIQUE（建索引前先清掉历史版本留下的大小写重复行，保留最后
+    // 写入的一行）；其余平台路径大小写敏感，保持普通索引
+    if cfg!(windows) {
+        let _ = conn.execute(
+            "DELETE FROM file_index WHERE rowid NOT IN (
+                SELECT MAX(rowid) FROM file_index GROUP BY path COLLATE NOCASE)",
+            [],
+        );
+        // 旧版本建过的非唯一 NOCASE 索引被唯一索引取代
+        let _ = conn.execute("DROP INDEX IF EXISTS idx_file_index_path_nocase", []);
+        conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_file_index_path_unique_nocase ON file_index(path COLLATE NOCASE)",
+            [],
+        )?;
+    } else {
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_file_index_path_nocase ON file_index(path COLLATE NOCASE)",
+            [],
+        )?;
+    }
+
     conn.execute(
         "CREATE INDEX IF NOT EXISTS idx_file_index_parent ON file_index(parent_id)",
         [],
     )?;
 
+    // Migration: 文件夹的缓存递归大小（NULL 表示未计算/已失效）
+    let _ = conn.execute("ALTER TABLE file_index ADD COLUMN recursive_size INTEGER", []);
+
+    // Migration: 文件头信息列（位深/色彩空间/透明/动图）
+    let _ = conn.execute("ALTER TABLE file_index ADD COLUMN bit_depth INTEGER", []);
+    let _ = conn.execute("ALTER TABLE file_index ADD COLUMN color_space TEXT", []);
+    let _ = conn.execute("ALTER TABLE file_index ADD COLUMN has_alpha INTEGER", []);
+    let _ = conn.execute("ALTER TABLE file_index ADD COLUMN is_animated INTEGER", []);
+
     Ok(())
 }
 
 pub fn batch_upsert(conn: &mut Connection, entries: &[FileIndexEntry]) -> Result<()> {
     let tx = conn.transaction()?;
-    
+
     {
-        let mut stmt = tx.prepare(
-            "INSERT INTO file_index (
-                file_id, parent_id, path, name, file_type, size, 
-                created_at, modified_at, width, height, format
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
-            ON CONFLICT(file_id) DO UPDATE SET
-                parent_id = excluded.parent_id,
-                path = excluded.path,
+        // 除主键外 path 上还有唯一约束（Windows 上额外有 NOCASE 唯一索引，
+        // 同一路径换大小写会生成不同 file_id，撞的就是它）。按撞到的索引
+        // 分别处理：path 冲突时用新行整体替换旧行（含 file_id），避免一条
+        // 冲突让整批写入回滚
+        const UPDATE_COLS: &str = "parent_id = excluded.parent_id,
                 name = excluded.name,
                 file_type = excluded.file_type,
                 size = excluded.size,
@@ -68,8 +111,27 @@ pub fn batch_upsert(conn: &mut Connection, entries: &[FileIndexEntry]) -> Result
                 modified_at = excluded.modified_at,
                 width = excluded.width,
                 height = excluded.height,
-                format = excluded.format"
-        )?;
+                format = excluded.format,
+                bit_depth = excluded.bit_depth,
+                color_space = excluded.color_space,
+                has_alpha = excluded.has_alpha,
+                is_animated = excluded.is_animated";
+        let mut sql = format!(
+            "INSERT INTO file_index (
+                file_id, parent_id, path, name, file_type, size,
+                created_at, modified_at, width, height, format,
+                bit_depth, color_space, has_alpha, is_animated
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+            ON CONFLICT(file_id) DO UPDATE SET path = excluded.path, {UPDATE_COLS}
+            ON CONFLICT(path) DO UPDATE SET file_id = excluded.file_id, {UPDATE_COLS}"
+        );
+        if cfg!(windows) {
+            sql.push_str(&format!(
+                " ON CONFLICT(path COLLATE NOCASE) DO UPDATE SET
+                    file_id = excluded.file_id, path = excluded.path, {UPDATE_COLS}"
+            ));
+        }
+        let mut stmt = tx.prepare(&sql)?;
 
         for entry in entries {
             stmt.execute(params![
@@ -83,18 +145,30 @@ pub fn batch_upsert(conn: &mut Connection, entries: &[FileIndexEntry]) -> Result
                 entry.modified_at,
                 entry.width,
                 entry.height,
-                entry.format
+                entry.format,
+                entry.bit_depth,
+                entry.color_space,
+                entry.has_alpha,
+                entry.is_animated
             ])?;
         }
+
+        // 写入了新的文件大小，缓存的递归大小全部失效（读取时惰性重算）
+        if !entries.is_empty() {
+            tx.execute(
+                "UPDATE file_index SET recursive_size = NULL WHERE recursive_size IS NOT NULL",
+                [],
+            )?;
+        }
     }
-    
+
     tx.commit()?;
     Ok(())
 }
 
 pub fn get_entries_under_path(conn: &Connection, root_path: &str) -> Result<Vec<FileIndexEntry>> {
     let pattern = format!("{}%", root_path);
-    let mut stmt = conn.prepare("SELECT file_id, parent_id, path, name, file_type, size, created_at, modified_at, width, height, format FROM file_index WHERE path LIKE ?1")?;
+    let mut stmt = conn.prepare("SELECT file_id, parent_id, path, name, file_type, size, created_at, modified_at, width, height, format, bit_depth, color_space, has_alpha, is_animated FROM file_index WHERE path LIKE ?1")?;
     let rows = stmt.query_map(params![pattern], |row| {
         Ok(FileIndexEntry {
             file_id: row.get(0)?,
@@ -108,6 +182,10 @@ pub fn get_entries_under_path(conn: &Connection, root_path: &str) -> Result<Vec<
             width: row.get(8)?,
             height: row.get(9)?,
             format: row.get(10)?,
+            bit_depth: row.get(11)?,
+            color_space: row.get(12)?,
+            has_alpha: row.get(13)?,
+            is_animated: row.get(14)?,
         })
     })?;
 
@@ -118,8 +196,37 @@ pub fn get_entries_under_path(conn: &Connection, root_path: &str) -> Result<Vec<
     Ok(entries)
 }
 
+/// 按 file_id 获取单个索引条目
+pub fn get_entry_by_id(conn: &Connection, file_id: &str) -> Result<Option<FileIndexEntry>> {
+    use rusqlite::OptionalExtension;
+    conn.query_row(
+        "SELECT file_id, parent_id, path, name, file_type, size, created_at, modified_at, width, height, format, bit_depth, color_space, has_alpha, is_animated FROM file_index WHERE file_id = ?1",
+        params![file_id],
+        |row| {
+            Ok(FileIndexEntry {
+                file_id: row.get(0)?,
+                parent_id: row.get(1)?,
+                path: row.get(2)?,
+                name: row.get(3)?,
+                file_type: row.get(4)?,
+                size: row.get(5)?,
+                created_at: row.get(6)?,
+                modified_at: row.get(7)?,
+                width: row.get(8)?,
+                height: row.get(9)?,
+                format: row.get(10)?,
+                bit_depth: row.get(11)?,
+                color_space: row.get(12)?,
+                has_alpha: row.get(13)?,
+                is_animated: row.get(14)?,
+            })
+        },
+    )
+    .optional()
+}
+
 pub fn get_all_entries(conn: &Connection) -> Result<Vec<FileIndexEntry>> {
-    let mut stmt = conn.prepare("SELECT file_id, parent_id, path, name, file_type, size, created_at, modified_at, width, height, format FROM file_index")?;
+    let mut stmt = conn.prepare("SELECT file_id, parent_id, path, name, file_type, size, created_at, modified_at, width, height, format, bit_depth, color_space, has_alpha, is_animated FROM file_index")?;
     let rows = stmt.query_map([], |row| {
         Ok(FileIndexEntry {
             file_id: row.get(0)?,
@@ -133,6 +240,10 @@ pub fn get_all_entries(conn: &Connection) -> Result<Vec<FileIndexEntry>> {
             width: row.get(8)?,
             height: row.get(9)?,
             format: row.get(10)?,
+            bit_depth: row.get(11)?,
+            color_space: row.get(12)?,
+            has_alpha: row.get(13)?,
+            is_animated: row.get(14)?,
         })
     })?;
 
@@ -147,7 +258,7 @@ pub fn get_all_entries(conn: &Connection) -> Result<Vec<FileIndexEntry>> {
 /// 用于 CLIP 嵌入向量生成
 pub fn get_all_image_files(conn: &Connection) -> Result<Vec<FileIndexEntry>> {
     let mut stmt = conn.prepare(
-        "SELECT file_id, parent_id, path, name, file_type, size, created_at, modified_at, width, height, format 
+        "SELECT file_id, parent_id, path, name, file_type, size, created_at, modified_at, width, height, format, bit_depth, color_space, has_alpha, is_animated 
          FROM file_index 
          WHERE file_type = 'Image'"
     )?;
@@ -164,6 +275,10 @@ pub fn get_all_image_files(conn: &Connection) -> Result<Vec<FileIndexEntry>> {
             width: row.get(8)?,
             height: row.get(9)?,
             format: row.get(10)?,
+            bit_depth: row.get(11)?,
+            color_space: row.get(12)?,
+            has_alpha: row.get(13)?,
+            is_animated: row.get(14)?,
         })
     })?;
 
@@ -193,6 +308,10 @@ pub fn get_minimal_entries_under_path(conn: &Connection, root_path: &str) -> Res
             width: None,
             height: None,
             format: None,
+            bit_depth: None,
+            color_space: None,
+            has_alpha: None,
+            is_animated: None,
         })
     })?;
 
@@ -203,68 +322,6 @@ pub fn get_minimal_entries_under_path(conn: &Connection, root_path: &str) -> Res
     Ok(entries)
 }
 
-
-#[cfg(test)]
-mod bench_tests {
-    use super::*;
-    use rusqlite::Connection;
-    use std::time::Instant;
-    use std::fs;
-    use std::env;
-
-    #[test]
-    fn bench_entries_fetch() {
-        // 可通过环境变量 AURORA_BENCH_COUNT 调整样本大小（默认 68k）
-        let n: usize = env::var("AURORA_BENCH_COUNT").ok().and_then(|s| s.parse().ok()).unwrap_or(68000);
-        let tmpdir = env::temp_dir().join(format!("aurora_bench_{}", std::process::id()));
-        let _ = fs::remove_dir_all(&tmpdir);
-        fs::create_dir_all(&tmpdir).unwrap();
-        let db_path = tmpdir.join("bench.db");
-
-        let mut conn = Connection::open(db_path).expect("open db");
-        create_table(&conn).expect("create table");
-
-        // 生成伪索引数据
-        let mut entries = Vec::with_capacity(n);
-        for i in 0..n {
-            let path = format!("/bench/root/dir{}/file{}.jpg", i / 100, i);
-            entries.push(FileIndexEntry {
-                file_id: format!("id{}", i),
-                parent_id: None,
-                path: path.clone(),
-                name: format!("file{}.jpg", i),
-                file_type: "Image".into(),
-                size: 1024,
-                created_at: 0,
-                modified_at: i as i64,
-                width: Some(800),
-                height: Some(600),
-                format: Some("jpg".into()),
-            });
-        }
-
-        // 批量写入（衡量写入成本不在此次基准主要关注点，但仍需要）
-        batch_upsert(&mut conn, &entries).expect("batch upsert");
-
-        // 测量当前（重字段）查询
-        let t0 = Instant::now();
-        let all = get_entries_under_path(&conn, "/bench/root").expect("get_entries_under_path");
-        let dur_all = t0.elapsed();
-
-        // 测量轻量查询
-        let t1 = Instant::now();
-        let minimal = get_minimal_entries_under_path(&conn, "/bench/root").expect("get_minimal_entries_under_path");
-        let dur_min = t1.elapsed();
-
-        println!("bench: inserted={}, get_entries_under_path -> {:?} (count={}), get_minimal -> {:?} (count={})", n, dur_all, all.len(), dur_min, minimal.len());
-
-        assert_eq!(all.len(), minimal.len(), "row counts must match");
-
-        // 清理
-        let _ = fs::remove_dir_all(&tmpdir);
-    }
-}
-
 pub fn delete_entries_by_ids(conn: &mut Connection, ids: &[String]) -> Result<()> {
     if ids.is_empty() {
         return Ok(());
@@ -278,6 +335,11 @@ pub fn delete_entries_by_ids(conn: &mut Connection, ids: &[String]) -> Result<()
              let mut stmt = tx.prepare(&sql)?;
              stmt.execute(rusqlite::params_from_iter(chunk))?;
          }
+         // 无法便捷定位受影响的祖先目录，整体失效缓存大小
+         tx.execute(
+             "UPDATE file_index SET recursive_size = NULL WHERE recursive_size IS NOT NULL",
+             [],
+         )?;
     }
     tx.commit()?;
     Ok(())
@@ -286,13 +348,16 @@ pub fn delete_entries_by_ids(conn: &mut Connection, ids: &[String]) -> Result<()
 pub fn delete_entries_by_path(conn: &Connection, path: &str) -> Result<()> {
     // 规范化路径
     let normalized_path = path.replace("\\", "/");
-    
+
     // 删除记录
     conn.execute(
         "DELETE FROM file_index WHERE path = ? OR path LIKE ?",
         params![normalized_path, format!("{}/%", normalized_path.trim_end_matches('/'))],
     )?;
-    
+
+    // 祖先目录的缓存递归大小已过期
+    invalidate_size_cache_for(conn, &normalized_path)?;
+
     Ok(())
 }
 
@@ -325,9 +390,16 @@ pub fn delete_orphaned_entries(conn: &mut Connection, root_path: &str, existing_
             let mut stmt = tx.prepare(&sql)?;
             stmt.execute(rusqlite::params_from_iter(chunk))?;
         }
+
+        if count > 0 {
+            tx.execute(
+                "UPDATE file_index SET recursive_size = NULL WHERE recursive_size IS NOT NULL",
+                [],
+            )?;
+        }
         count
     };
-    
+
     tx.commit()?;
     Ok(deleted_count)
 }
@@ -344,11 +416,12 @@ pub fn migrate_index_dir(conn: &Connection, old_path: &str, new_path: &str) -> R
         .to_string();
 
     // 0. 清理目标路径及其子项的残留项（防止 UNIQUE 约束冲突）
-    // 使用 lower() 确保大小写不敏感匹配，防止 abc -> ABC 这种重命名失败
+    // 大小写不敏感匹配（防止 abc -> ABC 这种重命名失败），但不要套
+    // lower()：= COLLATE NOCASE 和裸 LIKE 语义相同且能用 NOCASE 索引
     let new_dir_prefix_clean = if new_normalized.ends_with('/') { new_normalized.clone() } else { format!("{}/", new_normalized) };
     let new_dir_pattern = format!("{}%", new_dir_prefix_clean);
     conn.execute(
-        "DELETE FROM file_index WHERE lower(path) = lower(?1) OR lower(path) LIKE lower(?2)",
+        "DELETE FROM file_index WHERE path = ?1 COLLATE NOCASE OR path LIKE ?2",
         params![new_normalized, new_dir_pattern],
     )?;
 
@@ -374,6 +447,312 @@ pub fn migrate_index_dir(conn: &Connection, old_path: &str, new_path: &str) -> R
         "UPDATE file_index SET path = ?1 || SUBSTR(path, ?2) WHERE path LIKE ?3",
         params![new_dir_prefix, skip_len, dir_pattern],
     )?;
-    
+
+    // 新旧位置的祖先目录缓存大小都已过期
+    invalidate_size_cache_for(conn, &old_normalized)?;
+    invalidate_size_cache_for(conn, &new_normalized)?;
+
+    Ok(())
+}
+
+/// 使某个路径的所有祖先文件夹的缓存递归大小失效（文件增删改/移动后调用）
+pub fn invalidate_size_cache_for(conn: &Connection, path: &str) -> Result<()> {
+    let normalized = super::normalize_path(path);
+    conn.execute(
+        "UPDATE file_index SET recursive_size = NULL
+         WHERE file_type = 'Folder' AND (?1 LIKE path || '/%' OR path = ?1)",
+        params![normalized],
+    )?;
+    Ok(())
+}
+
+/// 使所有文件夹的缓存递归大小失效（批量写入后无法定位受影响的祖先时使用）
+pub fn invalidate_all_size_caches(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "UPDATE file_index SET recursive_size = NULL WHERE recursive_size IS NOT NULL",
+        [],
+    )?;
+    Ok(())
+}
+
+/// 获取文件夹的递归大小（字节）。
+/// 优先返回缓存值；缓存失效时按路径前缀汇总一次并写回缓存，后续调用即时返回
+pub fn get_recursive_size(conn: &Connection, folder_id: &str) -> Result<u64> {
+    let (path, cached): (String, Option<i64>) = conn.query_row(
+        "SELECT path, recursive_size FROM file_index WHERE file_id = ?1 AND file_type = 'Folder'",
+        params![folder_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+
+    if let Some(size) = cached {
+        return Ok(size as u64);
+    }
+
+    let dir_pattern = format!("{}/%", path);
+    let total: i64 = conn.query_row(
+        "SELECT COALESCE(SUM(size), 0) FROM file_index
+         WHERE file_type != 'Folder' AND path LIKE ?1",
+        params![dir_pattern],
+        |row| row.get(0),
+    )?;
+
+    conn.execute(
+        "UPDATE file_index SET recursive_size = ?1 WHERE file_id = ?2",
+        params![total, folder_id],
+    )?;
+
+    Ok(total as u64)
+}
+
+/// 树图节点：文件为自身大小，文件夹为递归大小
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreemapNode {
+    pub file_id: String,
+    pub name: String,
+    pub path: String,
+    pub file_type: String,
+    pub size: u64,
+    /// 深度耗尽或节点为文件时为空
+    pub children: Vec<TreemapNode>,
+}
+
+/// 按格式的占用统计
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatUsage {
+    pub format: String,
+    pub count: i64,
+    pub bytes: i64,
+}
+
+/// 磁盘占用分析数据：层级树 + 最大文件 + 格式分布
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageTreemap {
+    pub root: TreemapNode,
+    pub largest_files: Vec<TreemapNode>,
+    pub format_distribution: Vec<FormatUsage>,
+}
+
+/// 递归构建树图节点，子项按大小降序排列
+fn build_treemap_node(
+    conn: &Connection,
+    file_id: &str,
+    name: &str,
+    path: &str,
+    file_type: &str,
+    size: u64,
+    depth: u32,
+) -> Result<TreemapNode> {
+    let mut children = Vec::new();
+    if file_type == "Folder" && depth > 0 {
+        let mut stmt = conn.prepare(
+            "SELECT file_id, name, path, file_type, size FROM file_index WHERE parent_id = ?1",
+        )?;
+        let rows: Vec<(String, String, String, String, u64)> = stmt
+            .query_map(params![file_id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        for (cid, cname, cpath, ctype, csize) in rows {
+            let csize = if ctype == "Folder" {
+                get_recursive_size(conn, &cid).unwrap_or(0)
+            } else {
+                csize
+            };
+            children.push(build_treemap_node(conn, &cid, &cname, &cpath, &ctype, csize, depth - 1)?);
+        }
+        children.sort_by_key(|c| std::cmp::Reverse(c.size));
+    }
+
+    Ok(TreemapNode {
+        file_id: file_id.to_string(),
+        name: name.to_string(),
+        path: path.to_string(),
+        file_type: file_type.to_string(),
+        size,
+        children,
+    })
+}
+
+/// 生成磁盘占用分析数据：以 root_id 为根、展开 depth 层的大小树，
+/// 以及范围内前 20 大文件和按格式的占用分布
+pub fn get_storage_treemap(conn: &Connection, root_id: &str, depth: u32) -> Result<StorageTreemap> {
+    let (name, path, file_type): (String, String, String) = conn.query_row(
+        "SELECT name, path, file_type FROM file_index WHERE file_id = ?1",
+        params![root_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+
+    let root_size = if file_type == "Folder" {
+        get_recursive_size(conn, root_id).unwrap_or(0)
+    } else {
+        conn.query_row(
+            "SELECT size FROM file_index WHERE file_id = ?1",
+            params![root_id],
+            |row| row.get(0),
+        )?
+    };
+
+    let root = build_treemap_node(conn, root_id, &name, &path, &file_type, root_size, depth)?;
+
+    let dir_pattern = format!("{}/%", path);
+
+    let mut stmt = conn.prepare(
+        "SELECT file_id, name, path, file_type, size FROM file_index
+         WHERE file_type != 'Folder' AND path LIKE ?1
+         ORDER BY size DESC LIMIT 20",
+    )?;
+    let largest_files: Vec<TreemapNode> = stmt
+        .query_map(params![dir_pattern], |row| {
+            Ok(TreemapNode {
+                file_id: row.get(0)?,
+                name: row.get(1)?,
+                path: row.get(2)?,
+                file_type: row.get(3)?,
+                size: row.get(4)?,
+                children: Vec::new(),
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut stmt = conn.prepare(
+        "SELECT COALESCE(LOWER(format), 'unknown') AS fmt, COUNT(*), COALESCE(SUM(size), 0)
+         FROM file_index
+         WHERE file_type != 'Folder' AND path LIKE ?1
+         GROUP BY fmt ORDER BY 3 DESC",
+    )?;
+    let format_distribution: Vec<FormatUsage> = stmt
+        .query_map(params![dir_pattern], |row| {
+            Ok(FormatUsage {
+                format: row.get(0)?,
+                count: row.get(1)?,
+                bytes: row.get(2)?,
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(StorageTreemap { root, largest_files, format_distribution })
+}
+
+/// 尺寸补全队列表：记录扫描后仍缺少宽高的文件路径。
+/// 持久化到数据库，应用中途退出后下次启动可以继续补全，
+/// 避免 file_index 里永远留着宽高为 0 的条目。
+pub fn create_backfill_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS dimension_backfill_queue (
+            path TEXT PRIMARY KEY,
+            enqueued_at INTEGER
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// 把待补全的路径加入队列（已存在的忽略）
+pub fn enqueue_backfill(conn: &mut Connection, paths: &[String]) -> Result<()> {
+    let tx = conn.transaction()?;
+    {
+        let now = chrono::Utc::now().timestamp();
+        let mut stmt = tx.prepare(
+            "INSERT OR IGNORE INTO dimension_backfill_queue (path, enqueued_at) VALUES (?1, ?2)",
+        )?;
+        for path in paths {
+            stmt.execute(params![path, now])?;
+        }
+    }
+    tx.commit()?;
     Ok(())
 }
+
+/// 补全完成（或文件已不存在）后从队列移除
+pub fn dequeue_backfill(conn: &mut Connection, paths: &[String]) -> Result<()> {
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare("DELETE FROM dimension_backfill_queue WHERE path = ?1")?;
+        for path in paths {
+            stmt.execute(params![path])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// 读取当前排队中的全部路径（按入队时间）
+pub fn get_backfill_paths(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt =
+        conn.prepare("SELECT path FROM dimension_backfill_queue ORDER BY enqueued_at ASC")?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    rows.collect()
+}
+
+
+#[cfg(test)]
+mod bench_tests {
+    use super::*;
+    use rusqlite::Connection;
+    use std::time::Instant;
+    use std::fs;
+    use std::env;
+
+    #[test]
+    fn bench_entries_fetch() {
+        // 可通过环境变量 AURORA_BENCH_COUNT 调整样本大小（默认 68k）
+        let n: usize = env::var("AURORA_BENCH_COUNT").ok().and_then(|s| s.parse().ok()).unwrap_or(68000);
+        let tmpdir = env::temp_dir().join(format!("aurora_bench_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&tmpdir);
+        fs::create_dir_all(&tmpdir).unwrap();
+        let db_path = tmpdir.join("bench.db");
+
+        let mut conn = Connection::open(db_path).expect("open db");
+        create_table(&conn).expect("create table");
+
+        // 生成伪索引数据
+        let mut entries = Vec::with_capacity(n);
+        for i in 0..n {
+            let path = format!("/bench/root/dir{}/file{}.jpg", i / 100, i);
+            entries.push(FileIndexEntry {
+                file_id: format!("id{}", i),
+                parent_id: None,
+                path: path.clone(),
+                name: format!("file{}.jpg", i),
+                file_type: "Image".into(),
+                size: 1024,
+                created_at: 0,
+                modified_at: i as i64,
+                width: Some(800),
+                height: Some(600),
+                format: Some("jpg".into()),
+                bit_depth: None,
+                color_space: None,
+                has_alpha: None,
+                is_animated: None,
+            });
+        }
+
+        // 批量写入（衡量写入成本不在此次基准主要关注点，但仍需要）
+        batch_upsert(&mut conn, &entries).expect("batch upsert");
+
+        // 测量当前（重字段）查询
+        let t0 = Instant::now();
+        let all = get_entries_under_path(&conn, "/bench/root").expect("get_entries_under_path");
+        let dur_all = t0.elapsed();
+
+        // 测量轻量查询
+        let t1 = Instant::now();
+        let minimal = get_minimal_entries_under_path(&conn, "/bench/root").expect("get_minimal_entries_under_path");
+        let dur_min = t1.elapsed();
+
+        println!("bench: inserted={}, get_entries_under_path -> {:?} (count={}), get_minimal -> {:?} (count={})", n, dur_all, all.len(), dur_min, minimal.len());
+
+        assert_eq!(all.len(), minimal.len(), "row counts must match");
+
+        // 清理
+        let _ = fs::remove_dir_all(&tmpdir);
+    }
+}
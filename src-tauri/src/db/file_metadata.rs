@@ -10,22 +10,36 @@ pub struct FileMetadata {
     pub tags: Option<serde_json::Value>,
     pub description: Option<String>,
     pub source_url: Option<String>,
+    /// 从来源站点抓取的作品标题（oEmbed）
+    pub source_title: Option<String>,
+    /// 从来源站点抓取的作者名（oEmbed）
+    pub source_author: Option<String>,
     pub ai_data: Option<serde_json::Value>,
     pub category: Option<String>,
+    pub rating: Option<i64>,
+    /// 富文本笔记 (Markdown)，通过 notes_fts 全文索引
+    pub notes: Option<String>,
+    /// 敏感内容标记（NSFW 检测或手动设置），缩略图端点据此返回模糊变体
+    pub sensitive: Option<bool>,
     pub updated_at: Option<i64>,
 }
 
 pub fn upsert_file_metadata(conn: &Connection, metadata: &FileMetadata) -> Result<()> {
     conn.execute(
-        "INSERT INTO file_metadata (file_id, path, tags, description, source_url, ai_data, category, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+        "INSERT INTO file_metadata (file_id, path, tags, description, source_url, source_title, source_author, ai_data, category, rating, notes, sensitive, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
          ON CONFLICT(file_id) DO UPDATE SET
             path = excluded.path,
             tags = excluded.tags,
             description = excluded.description,
             source_url = excluded.source_url,
+            source_title = excluded.source_title,
+            source_author = excluded.source_author,
             ai_data = excluded.ai_data,
             category = excluded.category,
+            rating = excluded.rating,
+            notes = excluded.notes,
+            sensitive = excluded.sensitive,
             updated_at = excluded.updated_at",
         params![
             metadata.file_id,
@@ -33,17 +47,595 @@ pub fn upsert_file_metadata(conn: &Connection, metadata: &FileMetadata) -> Resul
             metadata.tags,
             metadata.description,
             metadata.source_url,
+            metadata.source_title,
+            metadata.source_author,
             metadata.ai_data,
             metadata.category,
+            metadata.rating,
+            metadata.notes,
+            metadata.sensitive,
             metadata.updated_at
         ],
     )?;
+    sync_notes_fts(conn, &metadata.file_id, metadata.notes.as_deref())?;
     Ok(())
 }
 
+/// 笔记全文索引表 (FTS5)，file_id 仅作关联不参与分词
+pub fn create_notes_fts(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS notes_fts USING fts5(file_id UNINDEXED, notes)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// 同步单个文件的笔记到 FTS 索引 (先删后插，空笔记只删)
+fn sync_notes_fts(conn: &Connection, file_id: &str, notes: Option<&str>) -> Result<()> {
+    conn.execute("DELETE FROM notes_fts WHERE file_id = ?1", params![file_id])?;
+    if let Some(notes) = notes {
+        if !notes.is_empty() {
+            conn.execute(
+                "INSERT INTO notes_fts (file_id, notes) VALUES (?1, ?2)",
+                params![file_id, notes],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// 设置笔记。没有元数据记录的文件会基于 file_index 中的路径新建记录。
+pub fn set_note(conn: &Connection, file_id: &str, notes: Option<&str>) -> Result<()> {
+    use rusqlite::OptionalExtension;
+
+    let now = chrono::Utc::now().timestamp();
+    let updated = conn.execute(
+        "UPDATE file_metadata SET notes = ?1, updated_at = ?2 WHERE file_id = ?3",
+        params![notes, now, file_id],
+    )?;
+    if updated == 0 {
+        let path: Option<String> = conn
+            .query_row(
+                "SELECT path FROM file_index WHERE file_id = ?1",
+                params![file_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(path) = path {
+            conn.execute(
+                "INSERT INTO file_metadata (file_id, path, notes, updated_at) VALUES (?1, ?2, ?3, ?4)",
+                params![file_id, path, notes, now],
+            )?;
+        }
+    }
+    sync_notes_fts(conn, file_id, notes)?;
+    Ok(())
+}
+
+/// 把文件内嵌的关键字（IPTC/XMP）合并进 tags，并在 imported_tags 中记录来源。
+/// imported_tags 只由扫描写入，作为这批标签的出处标记（provenance），
+/// 不参与 upsert_file_metadata，避免前端普通保存把它清掉。
+/// 没有元数据记录的文件会基于 file_index 中的路径新建记录。返回是否有新增标签
+pub fn merge_imported_tags(
+    conn: &Connection,
+    file_id: &str,
+    path: &str,
+    keywords: &[String],
+) -> Result<bool> {
+    use rusqlite::OptionalExtension;
+
+    if keywords.is_empty() {
+        return Ok(false);
+    }
+
+    let existing: Option<Option<String>> = conn
+        .query_row(
+            "SELECT tags FROM file_metadata WHERE file_id = ?1",
+            params![file_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    let mut tags: Vec<String> = existing
+        .as_ref()
+        .and_then(|t| t.as_deref())
+        .and_then(|t| serde_json::from_str::<serde_json::Value>(t).ok())
+        .and_then(|v| v.as_array().cloned())
+        .map(|a| a.iter().filter_map(|t| t.as_str().map(String::from)).collect())
+        .unwrap_or_default();
+
+    // 大小写不敏感去重合并
+    let lowered: std::collections::HashSet<String> =
+        tags.iter().map(|t| t.to_lowercase()).collect();
+    let mut added = false;
+    for kw in keywords {
+        if !lowered.contains(&kw.to_lowercase()) {
+            tags.push(kw.clone());
+            added = true;
+        }
+    }
+    if !added {
+        return Ok(false);
+    }
+
+    let tags_json = serde_json::to_string(&tags).unwrap_or_else(|_| "[]".to_string());
+    let imported_json = serde_json::to_string(keywords).unwrap_or_else(|_| "[]".to_string());
+    let now = chrono::Utc::now().timestamp();
+
+    let updated = conn.execute(
+        "UPDATE file_metadata SET tags = ?1, imported_tags = ?2, updated_at = ?3 WHERE file_id = ?4",
+        params![tags_json, imported_json, now, file_id],
+    )?;
+    if updated == 0 {
+        conn.execute(
+            "INSERT INTO file_metadata (file_id, path, tags, imported_tags, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![file_id, path, tags_json, imported_json, now],
+        )?;
+    }
+    Ok(true)
+}
+
+/// 设置分类。没有元数据记录的文件会基于 file_index 中的路径新建记录。
+pub fn set_category(conn: &Connection, file_id: &str, category: Option<&str>) -> Result<()> {
+    use rusqlite::OptionalExtension;
+
+    let now = chrono::Utc::now().timestamp();
+    let updated = conn.execute(
+        "UPDATE file_metadata SET category = ?1, updated_at = ?2 WHERE file_id = ?3",
+        params![category, now, file_id],
+    )?;
+    if updated == 0 {
+        let path: Option<String> = conn
+            .query_row(
+                "SELECT path FROM file_index WHERE file_id = ?1",
+                params![file_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(path) = path {
+            conn.execute(
+                "INSERT INTO file_metadata (file_id, path, category, updated_at) VALUES (?1, ?2, ?3, ?4)",
+                params![file_id, path, category, now],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// 设置敏感标记。没有元数据记录的文件会基于 file_index 中的路径新建记录。
+pub fn set_sensitive(conn: &Connection, file_id: &str, sensitive: bool) -> Result<()> {
+    use rusqlite::OptionalExtension;
+
+    let now = chrono::Utc::now().timestamp();
+    let updated = conn.execute(
+        "UPDATE file_metadata SET sensitive = ?1, updated_at = ?2 WHERE file_id = ?3",
+        params![sensitive, now, file_id],
+    )?;
+    if updated == 0 {
+        let path: Option<String> = conn
+            .query_row(
+                "SELECT path FROM file_index WHERE file_id = ?1",
+                params![file_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(path) = path {
+            conn.execute(
+                "INSERT INTO file_metadata (file_id, path, sensitive, updated_at) VALUES (?1, ?2, ?3, ?4)",
+                params![file_id, path, sensitive, now],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// 描述为空时填入文本（伴生 .txt caption 导入用），已有描述的不覆盖。
+/// 返回是否实际写入
+pub fn fill_description_if_empty(
+    conn: &Connection,
+    file_id: &str,
+    path: &str,
+    description: &str,
+) -> Result<bool> {
+    let now = chrono::Utc::now().timestamp();
+    let updated = conn.execute(
+        "UPDATE file_metadata SET description = ?1, updated_at = ?2
+         WHERE file_id = ?3 AND (description IS NULL OR description = '')",
+        params![description, now, file_id],
+    )?;
+    if updated > 0 {
+        return Ok(true);
+    }
+    let exists: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM file_metadata WHERE file_id = ?1",
+        params![file_id],
+        |row| row.get(0),
+    )?;
+    if exists == 0 {
+        conn.execute(
+            "INSERT INTO file_metadata (file_id, path, description, updated_at) VALUES (?1, ?2, ?3, ?4)",
+            params![file_id, path, description, now],
+        )?;
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+/// 写入从来源站点抓取的标题/作者信息
+pub fn set_source_info(
+    conn: &Connection,
+    file_id: &str,
+    title: Option<&str>,
+    author: Option<&str>,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE file_metadata SET source_title = ?1, source_author = ?2, updated_at = ?3 WHERE file_id = ?4",
+        params![title, author, chrono::Utc::now().timestamp(), file_id],
+    )?;
+    Ok(())
+}
+
+/// 按路径查询文件是否被标记为敏感内容（供缩略图端点使用）
+pub fn is_sensitive(conn: &Connection, path: &str) -> Result<bool> {
+    use rusqlite::OptionalExtension;
+    let file_id = super::generate_id(path);
+    let sensitive: Option<Option<bool>> = conn
+        .query_row(
+            "SELECT sensitive FROM file_metadata WHERE file_id = ?1",
+            params![file_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(sensitive.flatten().unwrap_or(false))
+}
+
+pub fn get_note(conn: &Connection, file_id: &str) -> Result<Option<String>> {
+    use rusqlite::OptionalExtension;
+    let notes: Option<Option<String>> = conn
+        .query_row(
+            "SELECT notes FROM file_metadata WHERE file_id = ?1",
+            params![file_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(notes.flatten())
+}
+
+/// 笔记全文搜索命中项，snippet 中命中词用 <b></b> 包裹
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteSearchResult {
+    pub file_id: String,
+    pub path: String,
+    pub snippet: String,
+}
+
+/// 全文搜索笔记，按 FTS5 相关度排序
+pub fn search_notes(conn: &Connection, query: &str, limit: i64) -> Result<Vec<NoteSearchResult>> {
+    let mut stmt = conn.prepare(
+        "SELECT f.file_id, m.path, snippet(notes_fts, 1, '<b>', '</b>', '…', 12)
+         FROM notes_fts f
+         JOIN file_metadata m ON m.file_id = f.file_id
+         WHERE notes_fts MATCH ?1
+         ORDER BY rank
+         LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![query, limit], |row| {
+        Ok(NoteSearchResult {
+            file_id: row.get(0)?,
+            path: row.get(1)?,
+            snippet: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// AI 提示词全文索引表 (FTS5)，正面/负面提示词分列存放
+pub fn create_prompt_fts(conn: &Connection) -> Result<()> {
+    // 旧版本只有 prompt 一列；缺 negative_prompt 时重建并从 ai_data 回填
+    let has_negative = conn
+        .prepare("SELECT negative_prompt FROM prompt_fts LIMIT 0")
+        .is_ok();
+    if !has_negative {
+        conn.execute("DROP TABLE IF EXISTS prompt_fts", [])?;
+    }
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS prompt_fts USING fts5(file_id UNINDEXED, prompt, negative_prompt)",
+        [],
+    )?;
+    if !has_negative {
+        conn.execute(
+            "INSERT INTO prompt_fts (file_id, prompt, negative_prompt)
+             SELECT file_id,
+                    COALESCE(json_extract(ai_data, '$.prompt'), ''),
+                    COALESCE(json_extract(ai_data, '$.negativePrompt'), '')
+             FROM file_metadata
+             WHERE json_extract(ai_data, '$.prompt') IS NOT NULL",
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+/// 同步单个文件的提示词到 FTS 索引 (先删后插，空提示只删)
+fn sync_prompt_fts(
+    conn: &Connection,
+    file_id: &str,
+    prompt: Option<&str>,
+    negative_prompt: Option<&str>,
+) -> Result<()> {
+    conn.execute("DELETE FROM prompt_fts WHERE file_id = ?1", params![file_id])?;
+    if let Some(prompt) = prompt {
+        if !prompt.is_empty() {
+            conn.execute(
+                "INSERT INTO prompt_fts (file_id, prompt, negative_prompt) VALUES (?1, ?2, ?3)",
+                params![file_id, prompt, negative_prompt.unwrap_or("")],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// 写入 AI 生成元数据并同步提示词索引。
+/// 没有元数据记录的文件会基于 file_index 中的路径新建记录。
+pub fn set_ai_data(conn: &Connection, file_id: &str, ai_data: &serde_json::Value) -> Result<()> {
+    use rusqlite::OptionalExtension;
+
+    let now = chrono::Utc::now().timestamp();
+    let updated = conn.execute(
+        "UPDATE file_metadata SET ai_data = ?1, updated_at = ?2 WHERE file_id = ?3",
+        params![ai_data, now, file_id],
+    )?;
+    if updated == 0 {
+        let path: Option<String> = conn
+            .query_row(
+                "SELECT path FROM file_index WHERE file_id = ?1",
+                params![file_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(path) = path {
+            conn.execute(
+                "INSERT INTO file_metadata (file_id, path, ai_data, updated_at) VALUES (?1, ?2, ?3, ?4)",
+                params![file_id, path, ai_data, now],
+            )?;
+        }
+    }
+    sync_prompt_fts(
+        conn,
+        file_id,
+        ai_data.get("prompt").and_then(|p| p.as_str()),
+        ai_data.get("negativePrompt").and_then(|p| p.as_str()),
+    )?;
+    Ok(())
+}
+
+/// 按生成模型名查询文件（ai_data.model 精确匹配）
+pub fn get_files_by_sd_model(conn: &Connection, model: &str) -> Result<Vec<FileMetadata>> {
+    let mut stmt = conn.prepare(
+        "SELECT file_id, path, tags, description, source_url, source_title, source_author, ai_data, category, rating, notes, sensitive, updated_at
+         FROM file_metadata WHERE json_extract(ai_data, '$.model') = ?1",
+    )?;
+    let rows = stmt.query_map(params![model], |row| {
+        Ok(FileMetadata {
+            file_id: row.get(0)?,
+            path: row.get(1)?,
+            tags: row.get(2)?,
+            description: row.get(3)?,
+            source_url: row.get(4)?,
+            source_title: row.get(5)?,
+            source_author: row.get(6)?,
+            ai_data: row.get(7)?,
+            category: row.get(8)?,
+            rating: row.get(9)?,
+            notes: row.get(10)?,
+            sensitive: row.get(11)?,
+            updated_at: row.get(12)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// 全文搜索 AI 提示词，按 FTS5 相关度排序
+pub fn search_prompts(conn: &Connection, query: &str, limit: i64) -> Result<Vec<NoteSearchResult>> {
+    let mut stmt = conn.prepare(
+        "SELECT f.file_id, m.path, snippet(prompt_fts, 1, '<b>', '</b>', '…', 12)
+         FROM prompt_fts f
+         JOIN file_metadata m ON m.file_id = f.file_id
+         WHERE prompt_fts MATCH ?1
+         ORDER BY rank
+         LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![query, limit], |row| {
+        Ok(NoteSearchResult {
+            file_id: row.get(0)?,
+            path: row.get(1)?,
+            snippet: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// 按生成方式搜索的命中项，附带主要生成参数方便前端直接展示
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptSearchResult {
+    pub file_id: String,
+    pub path: String,
+    /// 命中片段（正面或负面提示词，命中词用 <b></b> 包裹）
+    pub snippet: String,
+    pub sampler: Option<String>,
+    pub steps: Option<i64>,
+    pub cfg_scale: Option<f64>,
+    pub model: Option<String>,
+}
+
+/// 全文搜索生成提示词（含负面提示词），可叠加采样器/步数/CFG 过滤
+pub fn search_by_prompt_text(
+    conn: &Connection,
+    query: &str,
+    sampler: Option<&str>,
+    steps: Option<i64>,
+    cfg_scale: Option<f64>,
+    limit: i64,
+) -> Result<Vec<PromptSearchResult>> {
+    let mut stmt = conn.prepare(
+        "SELECT f.file_id, m.path, snippet(prompt_fts, -1, '<b>', '</b>', '…', 12),
+                json_extract(m.ai_data, '$.sampler'),
+                json_extract(m.ai_data, '$.steps'),
+                json_extract(m.ai_data, '$.cfgScale'),
+                json_extract(m.ai_data, '$.model')
+         FROM prompt_fts f
+         JOIN file_metadata m ON m.file_id = f.file_id
+         WHERE prompt_fts MATCH ?1
+           AND (?2 IS NULL OR json_extract(m.ai_data, '$.sampler') = ?2 COLLATE NOCASE)
+           AND (?3 IS NULL OR json_extract(m.ai_data, '$.steps') = ?3)
+           AND (?4 IS NULL OR json_extract(m.ai_data, '$.cfgScale') = ?4)
+         ORDER BY rank
+         LIMIT ?5",
+    )?;
+    let rows = stmt.query_map(params![query, sampler, steps, cfg_scale, limit], |row| {
+        Ok(PromptSearchResult {
+            file_id: row.get(0)?,
+            path: row.get(1)?,
+            snippet: row.get(2)?,
+            sampler: row.get(3)?,
+            steps: row.get(4)?,
+            cfg_scale: row.get(5)?,
+            model: row.get(6)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// 批量编辑补丁：None 表示该字段保持不变
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataPatch {
+    /// 要追加的标签（去重）
+    pub add_tags: Option<Vec<String>>,
+    /// 要移除的标签
+    pub remove_tags: Option<Vec<String>>,
+    /// 覆盖分类
+    pub category: Option<String>,
+    /// 描述模板，支持 {filename} 和 {date} 占位符
+    pub description_template: Option<String>,
+    /// 覆盖评分
+    pub rating: Option<i64>,
+}
+
+/// 渲染描述模板：{filename} 替换为文件名，{date} 替换为今天的日期
+fn render_description_template(template: &str, path: &str) -> String {
+    let filename = path.rsplit('/').next().unwrap_or(path);
+    let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+    template
+        .replace("{filename}", filename)
+        .replace("{date}", &date)
+}
+
+/// 在单个事务中对一批文件应用补丁。
+/// 没有元数据记录的文件会基于 file_index 中的路径新建记录；
+/// file_index 中也不存在的 ID 会被跳过。
+/// 每处理一部分文件调用一次 on_progress(已处理数, 总数)。
+pub fn bulk_update_metadata<F: FnMut(usize, usize)>(
+    conn: &mut Connection,
+    file_ids: &[String],
+    patch: &MetadataPatch,
+    mut on_progress: F,
+) -> Result<usize> {
+    use rusqlite::OptionalExtension;
+
+    let tx = conn.transaction()?;
+    let total = file_ids.len();
+    let mut updated = 0usize;
+
+    for (i, file_id) in file_ids.iter().enumerate() {
+        // 已有记录则在其基础上修改，否则从 file_index 取路径新建
+        let mut meta = match get_metadata_by_id(&tx, file_id)? {
+            Some(m) => m,
+            None => {
+                let path: Option<String> = tx
+                    .query_row(
+                        "SELECT path FROM file_index WHERE file_id = ?1",
+                        params![file_id],
+                        |row| row.get(0),
+                    )
+                    .optional()?;
+                match path {
+                    Some(p) => FileMetadata {
+                        file_id: file_id.clone(),
+                        path: p,
+                        tags: None,
+                        description: None,
+                        source_url: None,
+                        source_title: None,
+                        source_author: None,
+                        ai_data: None,
+                        category: None,
+                        rating: None,
+                        notes: None,
+                        sensitive: None,
+                        updated_at: None,
+                    },
+                    // 索引里也没有这个文件，跳过
+                    None => continue,
+                }
+            }
+        };
+
+        // 标签增删（保持原有顺序，新标签追加到末尾）
+        if patch.add_tags.is_some() || patch.remove_tags.is_some() {
+            let mut tags: Vec<String> = meta
+                .tags
+                .as_ref()
+                .and_then(|v| v.as_array())
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|t| t.as_str().map(|s| s.to_string()))
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            if let Some(remove) = &patch.remove_tags {
+                tags.retain(|t| !remove.contains(t));
+            }
+            if let Some(add) = &patch.add_tags {
+                for tag in add {
+                    if !tags.contains(tag) {
+                        tags.push(tag.clone());
+                    }
+                }
+            }
+            meta.tags = Some(serde_json::Value::from(tags));
+        }
+
+        if let Some(category) = &patch.category {
+            meta.category = Some(category.clone());
+        }
+        if let Some(template) = &patch.description_template {
+            meta.description = Some(render_description_template(template, &meta.path));
+        }
+        if let Some(rating) = patch.rating {
+            meta.rating = Some(rating);
+        }
+        meta.updated_at = Some(chrono::Utc::now().timestamp());
+
+        upsert_file_metadata(&tx, &meta)?;
+        updated += 1;
+
+        // 进度回调：每 200 个文件或最后一个
+        if (i + 1) % 200 == 0 || i + 1 == total {
+            on_progress(i + 1, total);
+        }
+    }
+
+    tx.commit()?;
+    Ok(updated)
+}
+
 pub fn get_metadata_by_id(conn: &Connection, file_id: &str) -> Result<Option<FileMetadata>> {
     let mut stmt = conn.prepare(
-        "SELECT file_id, path, tags, description, source_url, ai_data, category, updated_at FROM file_metadata WHERE file_id = ?1"
+        "SELECT file_id, path, tags, description, source_url, source_title, source_author, ai_data, category, rating, notes, sensitive, updated_at FROM file_metadata WHERE file_id = ?1"
     )?;
     
     let mut rows = stmt.query_map(params![file_id], |row| {
@@ -53,9 +645,14 @@ pub fn get_metadata_by_id(conn: &Connection, file_id: &str) -> Result<Option<Fil
             tags: row.get(2)?,
             description: row.get(3)?,
             source_url: row.get(4)?,
-            ai_data: row.get(5)?,
-            category: row.get(6)?,
-            updated_at: row.get(7)?,
+            source_title: row.get(5)?,
+            source_author: row.get(6)?,
+            ai_data: row.get(7)?,
+            category: row.get(8)?,
+            rating: row.get(9)?,
+            notes: row.get(10)?,
+            sensitive: row.get(11)?,
+            updated_at: row.get(12)?,
         })
     })?;
 
@@ -68,7 +665,7 @@ pub fn get_metadata_by_id(conn: &Connection, file_id: &str) -> Result<Option<Fil
 
 pub fn get_all_metadata(conn: &Connection) -> Result<Vec<FileMetadata>> {
     let mut stmt = conn.prepare(
-        "SELECT file_id, path, tags, description, source_url, ai_data, category, updated_at FROM file_metadata"
+        "SELECT file_id, path, tags, description, source_url, source_title, source_author, ai_data, category, rating, notes, sensitive, updated_at FROM file_metadata"
     )?;
     
     let metadata_iter = stmt.query_map([], |row| {
@@ -78,9 +675,14 @@ pub fn get_all_metadata(conn: &Connection) -> Result<Vec<FileMetadata>> {
             tags: row.get(2)?,
             description: row.get(3)?,
             source_url: row.get(4)?,
-            ai_data: row.get(5)?,
-            category: row.get(6)?,
-            updated_at: row.get(7)?,
+            source_title: row.get(5)?,
+            source_author: row.get(6)?,
+            ai_data: row.get(7)?,
+            category: row.get(8)?,
+            rating: row.get(9)?,
+            notes: row.get(10)?,
+            sensitive: row.get(11)?,
+            updated_at: row.get(12)?,
         })
     })?;
 
@@ -94,7 +696,7 @@ pub fn get_all_metadata(conn: &Connection) -> Result<Vec<FileMetadata>> {
 pub fn get_metadata_under_path(conn: &Connection, root_path: &str) -> Result<Vec<FileMetadata>> {
     let pattern = format!("{}%", root_path.replace("\\", "/"));
     let mut stmt = conn.prepare(
-        "SELECT file_id, path, tags, description, source_url, ai_data, category, updated_at FROM file_metadata WHERE path LIKE ?1"
+        "SELECT file_id, path, tags, description, source_url, source_title, source_author, ai_data, category, rating, notes, sensitive, updated_at FROM file_metadata WHERE path LIKE ?1"
     )?;
     
     let metadata_iter = stmt.query_map(params![pattern], |row| {
@@ -104,9 +706,14 @@ pub fn get_metadata_under_path(conn: &Connection, root_path: &str) -> Result<Vec
             tags: row.get(2)?,
             description: row.get(3)?,
             source_url: row.get(4)?,
-            ai_data: row.get(5)?,
-            category: row.get(6)?,
-            updated_at: row.get(7)?,
+            source_title: row.get(5)?,
+            source_author: row.get(6)?,
+            ai_data: row.get(7)?,
+            category: row.get(8)?,
+            rating: row.get(9)?,
+            notes: row.get(10)?,
+            sensitive: row.get(11)?,
+            updated_at: row.get(12)?,
         })
     })?;
 
@@ -119,7 +726,16 @@ pub fn get_metadata_under_path(conn: &Connection, root_path: &str) -> Result<Vec
 
 pub fn delete_metadata_by_path(conn: &Connection, path: &str) -> Result<()> {
     let normalized_path = path.replace("\\", "/");
-    
+
+    // 先清理笔记索引（需要在元数据删除前按路径找到 file_id）
+    let dir_pattern_fts = format!("{}/%", normalized_path.trim_end_matches('/'));
+    conn.execute(
+        "DELETE FROM notes_fts WHERE file_id IN (
+            SELECT file_id FROM file_metadata WHERE path = ?1 OR path LIKE ?2
+        )",
+        params![normalized_path, dir_pattern_fts],
+    )?;
+
     // 删除单个文件元数据
     conn.execute(
         "DELETE FROM file_metadata WHERE path = ?",
@@ -147,6 +763,11 @@ pub fn migrate_metadata(conn: &Connection, old_id: &str, new_id: &str, new_path:
         "UPDATE file_metadata SET file_id = ?1, path = ?2 WHERE file_id = ?3",
         params![new_id, normalized_path, old_id],
     )?;
+    // 笔记索引跟随 file_id 迁移
+    conn.execute(
+        "UPDATE notes_fts SET file_id = ?1 WHERE file_id = ?2",
+        params![new_id, old_id],
+    )?;
     Ok(())
 }
 
@@ -164,11 +785,11 @@ pub fn migrate_metadata_dir(conn: &Connection, old_path: &str, new_path: &str) -
     let old_normalized = super::normalize_path(old_path);
     let new_normalized = super::normalize_path(new_path);
     
-    // 0. 清理目标路径残留 (大小写不敏感)
+    // 0. 清理目标路径残留 (大小写不敏感，走 NOCASE 索引而不是 lower() 全表扫描)
     let new_dir_prefix_clean = if new_normalized.ends_with('/') { new_normalized.clone() } else { format!("{}/", new_normalized) };
     let new_dir_pattern = format!("{}%", new_dir_prefix_clean);
     conn.execute(
-        "DELETE FROM file_metadata WHERE lower(path) = lower(?1) OR lower(path) LIKE lower(?2)",
+        "DELETE FROM file_metadata WHERE path = ?1 COLLATE NOCASE OR path LIKE ?2",
         params![new_normalized, new_dir_pattern],
     )?;
 
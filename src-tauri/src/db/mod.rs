@@ -1,4 +1,4 @@
-use rusqlite::{Connection, Result};
+use rusqlite::{Connection, OptionalExtension, Result};
 use std::fs;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
@@ -7,6 +7,12 @@ pub mod persons;
 pub mod file_metadata;
 pub mod file_index;
 pub mod topics;
+pub mod writer;
+pub mod decode_errors;
+pub mod categories;
+pub mod activity_log;
+pub mod watch_rules;
+pub mod annotations;
 
 #[derive(Clone)]
 pub struct AppDbPool {
@@ -20,16 +26,22 @@ impl AppDbPool {
             fs::create_dir_all(parent).map_err(|e| e.to_string())?;
         }
 
-        let conn = Connection::open(path).map_err(|e| e.to_string())?;
+        let mut conn = Connection::open(path).map_err(|e| e.to_string())?;
 
         // Performance settings
         let _ = conn.execute("PRAGMA journal_mode=WAL", []);
         let _ = conn.execute("PRAGMA synchronous=NORMAL", []);
         let _ = conn.execute("PRAGMA foreign_keys=ON", []);
 
+        // 注册自然排序 collation，SQL 端可用 ORDER BY ... COLLATE NATURAL
+        let _ = conn.create_collation("NATURAL", crate::natural_sort::natural_cmp);
+
         // Initialize tables for the database
         init_db(&conn).map_err(|e| e.to_string())?;
 
+        // 恢复该库的 ID 命名空间并改写旧格式的键
+        init_id_namespace(&mut conn).map_err(|e| e.to_string())?;
+
         Ok(Self {
             conn: Arc::new(Mutex::new(conn)),
         })
@@ -45,16 +57,22 @@ impl AppDbPool {
             fs::create_dir_all(parent).map_err(|e| e.to_string())?;
         }
 
-        let conn = Connection::open(path).map_err(|e| e.to_string())?;
+        let mut conn = Connection::open(path).map_err(|e| e.to_string())?;
 
         // Performance settings
         let _ = conn.execute("PRAGMA journal_mode=WAL", []);
         let _ = conn.execute("PRAGMA synchronous=NORMAL", []);
         let _ = conn.execute("PRAGMA foreign_keys=ON", []);
 
+        // 注册自然排序 collation（新连接同样需要）
+        let _ = conn.create_collation("NATURAL", crate::natural_sort::natural_cmp);
+
         // Initialize tables for the new database
         init_db(&conn).map_err(|e| e.to_string())?;
 
+        // 切到哪个库就用哪个库的 ID 命名空间
+        init_id_namespace(&mut conn).map_err(|e| e.to_string())?;
+
         let mut conn_guard = self.conn.lock().unwrap();
         *conn_guard = conn;
         Ok(())
@@ -84,17 +102,172 @@ pub fn normalize_path(path: &str) -> String {
     normalized
 }
 
+// --- 每库 ID 命名空间 ---
+// file_id 是路径 MD5 的前 9 位，单库内足够，但 CLIP 嵌入库这类存储是
+// 多个库共享的，不同库根下的路径可能截断出相同的哈希。给每个库配一个
+// 短前缀（优先从 library_root 推导，同一个库的各 profile 拿到相同前缀），
+// ID 形如 "{ns}-{hash}"。打开库时发现旧格式 / 旧前缀的键会按 path 列
+// 整体重算改写；嵌入库没有路径列，改写产生的新旧 ID 对先攒在这里，
+// 由 main 侧取走后异步套用（见 take_pending_id_migrations）。
+
+static ID_NAMESPACE: once_cell::sync::Lazy<std::sync::RwLock<String>> =
+    once_cell::sync::Lazy::new(|| std::sync::RwLock::new(String::new()));
+
+static PENDING_ID_MIGRATIONS: once_cell::sync::Lazy<Mutex<Vec<(String, String)>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(Vec::new()));
+
+/// 当前库的 ID 命名空间前缀（空串表示未启用前缀）
+pub fn current_id_namespace() -> String {
+    ID_NAMESPACE.read().unwrap().clone()
+}
+
+/// 取走迁移攒下的旧 ID → 新 ID 改写对，调用方负责套用到嵌入库
+pub fn take_pending_id_migrations() -> Vec<(String, String)> {
+    std::mem::take(&mut *PENDING_ID_MIGRATIONS.lock().unwrap())
+}
+
+/// 命名空间只允许小写字母和数字、最长 8 位（要拼进 ID 和 SQL LIKE）；
+/// 空串合法，表示不加前缀
+pub fn is_valid_id_namespace(ns: &str) -> bool {
+    ns.len() <= 8 && ns.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+}
+
 pub fn generate_id(path: &str) -> String {
     let normalized = normalize_path(path);
     let hash = md5::compute(normalized.as_bytes());
     let hash_str = format!("{:x}", hash);
     // 确保至少有9个字符，避免切片越界
-    if hash_str.len() >= 9 {
+    let hash_part = if hash_str.len() >= 9 {
         hash_str[..9].to_string()
     } else {
         // 如果hash字符串不足9字符，用前导零填充（理论上MD5不会出现这种情况）
         format!("{:0>9}", hash_str)
+    };
+    let ns = ID_NAMESPACE.read().unwrap();
+    if ns.is_empty() {
+        hash_part
+    } else {
+        format!("{}-{}", *ns, hash_part)
+    }
+}
+
+/// 打开库时恢复（没有就分配）该库的命名空间，并把旧格式的键改写掉
+fn init_id_namespace(conn: &mut Connection) -> Result<()> {
+    let ns = match get_library_setting(conn, "id_namespace")? {
+        Some(ns) if is_valid_id_namespace(&ns) => ns,
+        _ => {
+            // 优先从库根推导，保证同一个库的各 profile 数据库前缀一致；
+            // 还没配置库根的新库用时间戳+进程号随机一个
+            let seed = match get_library_setting(conn, "library_root")? {
+                Some(root) => normalize_path(&root),
+                None => format!("{}-{}", chrono::Utc::now().timestamp_micros(), std::process::id()),
+            };
+            let digest = format!("{:x}", md5::compute(seed.as_bytes()));
+            let ns = digest[..4].to_string();
+            set_library_setting(conn, "id_namespace", &ns)?;
+            ns
+        }
+    };
+    *ID_NAMESPACE.write().unwrap() = ns;
+    rewrite_ids(conn)?;
+    Ok(())
+}
+
+/// 修改当前库的命名空间并整体改写既有键，返回改写的键数
+pub fn apply_id_namespace(conn: &mut Connection, ns: &str) -> std::result::Result<usize, String> {
+    if !is_valid_id_namespace(ns) {
+        return Err("命名空间只允许小写字母和数字，最长 8 位".to_string());
+    }
+    set_library_setting(conn, "id_namespace", ns).map_err(|e| e.to_string())?;
+    *ID_NAMESPACE.write().unwrap() = ns.to_string();
+    rewrite_ids(conn).map_err(|e| e.to_string())
+}
+
+/// 把不符合当前命名空间的 file_id 键按 path 列重算改写（含 file_index
+/// 的 parent_id 与封面 / 全文索引等引用）。幂等：干净的库只付一次带
+/// 条件的查询成本，重复执行不产生写入
+fn rewrite_ids(conn: &mut Connection) -> Result<usize> {
+    let ns = current_id_namespace();
+    // 旧格式键的筛选条件：有前缀时找前缀不对的，无前缀时找带前缀的
+    // （ns 已通过 is_valid_id_namespace 校验，拼 SQL 安全）
+    let stale = if ns.is_empty() {
+        "file_id LIKE '%-%'".to_string()
+    } else {
+        format!("file_id NOT LIKE '{}-%'", ns)
+    };
+    let tx = conn.transaction()?;
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    {
+        let rows: Vec<(String, String)> = tx
+            .prepare(&format!("SELECT file_id, path FROM file_index WHERE {}", stale))?
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?
+            .flatten()
+            .collect();
+        let mut update = tx.prepare(
+            "UPDATE OR REPLACE file_index SET file_id = ?1, parent_id = ?2 WHERE file_id = ?3",
+        )?;
+        for (old_id, path) in rows {
+            let new_id = generate_id(&path);
+            let parent_id = Path::new(&path)
+                .parent()
+                .and_then(|p| p.to_str())
+                .map(generate_id);
+            update.execute(rusqlite::params![new_id, parent_id, old_id])?;
+            pairs.push((old_id, new_id));
+        }
+        for table in ["file_metadata", "annotations", "decode_errors"] {
+            let rows: Vec<(String, String)> = tx
+                .prepare(&format!("SELECT file_id, path FROM {} WHERE {}", table, stale))?
+                .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?
+                .flatten()
+                .collect();
+            let mut update = tx.prepare(&format!(
+                "UPDATE OR REPLACE {} SET file_id = ?1 WHERE file_id = ?2",
+                table
+            ))?;
+            for (old_id, path) in rows {
+                let new_id = generate_id(&path);
+                update.execute(rusqlite::params![new_id, old_id])?;
+                pairs.push((old_id, new_id));
+            }
+        }
+        if !pairs.is_empty() {
+            // 引用 file_id 但没有 path 列的地方按映射改写
+            let map: std::collections::HashMap<&str, &str> = pairs
+                .iter()
+                .map(|(o, n)| (o.as_str(), n.as_str()))
+                .collect();
+            for (table, column) in [
+                ("persons", "cover_file_id"),
+                ("topics", "cover_file_id"),
+                ("topics", "background_file_id"),
+                ("notes_fts", "file_id"),
+                ("prompt_fts", "file_id"),
+            ] {
+                let rows: Vec<(i64, Option<String>)> = tx
+                    .prepare(&format!("SELECT rowid, {} FROM {}", column, table))?
+                    .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?
+                    .flatten()
+                    .collect();
+                let mut update = tx.prepare(&format!(
+                    "UPDATE {} SET {} = ?1 WHERE rowid = ?2",
+                    table, column
+                ))?;
+                for (rowid, id) in rows {
+                    if let Some(new_id) = id.as_deref().and_then(|i| map.get(i)) {
+                        update.execute(rusqlite::params![new_id, rowid])?;
+                    }
+                }
+            }
+        }
+    }
+    tx.commit()?;
+    let rewritten = pairs.len();
+    if rewritten > 0 {
+        log::info!("ID 命名空间迁移：改写了 {} 个键", rewritten);
+        PENDING_ID_MIGRATIONS.lock().unwrap().extend(pairs);
     }
+    Ok(rewritten)
 }
 
 pub fn init_db(conn: &Connection) -> Result<()> {
@@ -133,17 +306,92 @@ pub fn init_db(conn: &Connection) -> Result<()> {
     // Migration: Add category column if it doesn't exist
     let _ = conn.execute("ALTER TABLE file_metadata ADD COLUMN category TEXT", []);
 
+    // Migration: Add rating column if it doesn't exist
+    let _ = conn.execute("ALTER TABLE file_metadata ADD COLUMN rating INTEGER", []);
+
+    // Migration: Add notes column if it doesn't exist
+    let _ = conn.execute("ALTER TABLE file_metadata ADD COLUMN notes TEXT", []);
+
+    // Migration: Add sensitive column if it doesn't exist
+    let _ = conn.execute("ALTER TABLE file_metadata ADD COLUMN sensitive INTEGER", []);
+
+    // Migration: Add source title/author columns if they don't exist
+    let _ = conn.execute("ALTER TABLE file_metadata ADD COLUMN source_title TEXT", []);
+    let _ = conn.execute("ALTER TABLE file_metadata ADD COLUMN source_author TEXT", []);
+
+    // Migration: Add imported_tags column if it doesn't exist
+    let _ = conn.execute("ALTER TABLE file_metadata ADD COLUMN imported_tags TEXT", []);
+
     // Create indexes for file_metadata
     conn.execute(
         "CREATE INDEX IF NOT EXISTS idx_file_metadata_path ON file_metadata(path)",
         [],
     )?;
 
+    // 整树移动时的 LIKE 前缀匹配需要 NOCASE collation 的索引（见 file_index）
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_file_metadata_path_nocase ON file_metadata(path COLLATE NOCASE)",
+        [],
+    )?;
+
+    // Create notes full-text index
+    file_metadata::create_notes_fts(conn)?;
+
+    // Create AI prompt full-text index
+    file_metadata::create_prompt_fts(conn)?;
+
     // Create file_index table
     file_index::create_table(conn)?;
 
+    // Create dimension backfill queue table
+    file_index::create_backfill_table(conn)?;
+
+    // Create decode errors table
+    decode_errors::create_table(conn)?;
+
     // Create topics table
     topics::create_table(conn)?;
 
+    // Create categories table
+    categories::create_table(conn)?;
+
+    // Create activity log table
+    activity_log::create_table(conn)?;
+
+    // Create watch-folder rules table
+    watch_rules::create_table(conn)?;
+
+    // Create per-image annotations table
+    annotations::create_table(conn)?;
+
+    // Create per-library settings table
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS library_settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// 读取当前库的设置项（库级设置随 metadata.db 一起存放，切换库后各自独立）
+pub fn get_library_setting(conn: &Connection, key: &str) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT value FROM library_settings WHERE key = ?",
+        [key],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// 写入当前库的设置项
+pub fn set_library_setting(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO library_settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        rusqlite::params![key, value],
+    )?;
     Ok(())
 }
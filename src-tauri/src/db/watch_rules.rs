@@ -0,0 +1,99 @@
+use rusqlite::{params, Connection, Result};
+use serde::{Deserialize, Serialize};
+
+/// 一条监视文件夹规则："Downloads 里出现 *.png 时移动到收件箱并打 unsorted 标签"。
+/// action："move" | "copy"；pattern 为文件名通配（* 和 ?，大小写不敏感）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchRule {
+    pub id: String,
+    pub name: String,
+    /// 被监视的目录
+    pub watch_dir: String,
+    /// 文件名通配模式，如 "*.png"
+    pub pattern: String,
+    pub action: String,
+    /// 命中后移动/复制到的目录（库内路径）
+    pub dest_dir: String,
+    /// 命中后附加的标签
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub enabled: bool,
+    pub updated_at: Option<i64>,
+}
+
+pub fn create_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS watch_rules (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            watch_dir TEXT NOT NULL,
+            pattern TEXT NOT NULL,
+            action TEXT NOT NULL,
+            dest_dir TEXT NOT NULL,
+            tags TEXT,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            updated_at INTEGER
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn get_all_rules(conn: &Connection) -> Result<Vec<WatchRule>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, watch_dir, pattern, action, dest_dir, tags, enabled, updated_at
+         FROM watch_rules ORDER BY name",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        let tags: Option<String> = row.get(6)?;
+        Ok(WatchRule {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            watch_dir: row.get(2)?,
+            pattern: row.get(3)?,
+            action: row.get(4)?,
+            dest_dir: row.get(5)?,
+            tags: tags
+                .and_then(|t| serde_json::from_str(&t).ok())
+                .unwrap_or_default(),
+            enabled: row.get::<_, i64>(7)? != 0,
+            updated_at: row.get(8)?,
+        })
+    })?;
+    rows.collect()
+}
+
+pub fn upsert_rule(conn: &Connection, rule: &WatchRule) -> Result<()> {
+    let tags = serde_json::to_string(&rule.tags).unwrap_or_else(|_| "[]".to_string());
+    conn.execute(
+        "INSERT INTO watch_rules (id, name, watch_dir, pattern, action, dest_dir, tags, enabled, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+         ON CONFLICT(id) DO UPDATE SET
+            name = excluded.name,
+            watch_dir = excluded.watch_dir,
+            pattern = excluded.pattern,
+            action = excluded.action,
+            dest_dir = excluded.dest_dir,
+            tags = excluded.tags,
+            enabled = excluded.enabled,
+            updated_at = excluded.updated_at",
+        params![
+            rule.id,
+            rule.name,
+            rule.watch_dir,
+            rule.pattern,
+            rule.action,
+            rule.dest_dir,
+            tags,
+            rule.enabled as i64,
+            rule.updated_at,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn delete_rule(conn: &Connection, rule_id: &str) -> Result<()> {
+    conn.execute("DELETE FROM watch_rules WHERE id = ?1", params![rule_id])?;
+    Ok(())
+}
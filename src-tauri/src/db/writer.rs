@@ -0,0 +1,106 @@
+//! 应用数据库单写入者队列
+//!
+//! SQLite 同一时刻只允许一个写入者，UI 高频保存（标签、评分等）与后台批量任务
+//! 并发写入时容易出现 "database is locked"。这里把所有写操作序列化到一个专用
+//! 线程：写请求通过 channel 提交，工作线程按批次在单个事务中执行，并通过
+//! oneshot 通道把结果异步返回给调用方。
+
+use std::thread;
+
+use crossbeam_channel::{unbounded, Sender};
+use rusqlite::Connection;
+
+use super::AppDbPool;
+
+/// 单个批次最多合并的写请求数，避免单个事务持续过久
+const MAX_BATCH_SIZE: usize = 64;
+
+/// 在工作线程事务中执行的写闭包
+type WriteFn = Box<dyn FnOnce(&Connection) -> Result<(), String> + Send>;
+
+/// 一条写请求：闭包在工作线程的事务中执行，结果通过 oneshot 返回
+struct WriteJob {
+    f: WriteFn,
+    done: tokio::sync::oneshot::Sender<Result<(), String>>,
+}
+
+/// 应用数据库的写入队列句柄（可克隆，保存在 Tauri 状态中）
+#[derive(Clone)]
+pub struct DbWriter {
+    sender: Sender<WriteJob>,
+}
+
+impl DbWriter {
+    /// 启动写入工作线程并返回队列句柄
+    pub fn start(pool: AppDbPool) -> Self {
+        let (sender, receiver) = unbounded::<WriteJob>();
+
+        thread::spawn(move || {
+            // 阻塞等待第一条请求；channel 关闭（所有句柄被丢弃）时退出
+            while let Ok(first) = receiver.recv() {
+                let mut batch = vec![first];
+                // 将排队中的请求合并到同一批次，减少事务开销
+                while batch.len() < MAX_BATCH_SIZE {
+                    match receiver.try_recv() {
+                        Ok(job) => batch.push(job),
+                        Err(_) => break,
+                    }
+                }
+
+                Self::run_batch(&pool, batch);
+            }
+        });
+
+        Self { sender }
+    }
+
+    /// 在单个事务中执行一批写请求，并逐个回发结果
+    fn run_batch(pool: &AppDbPool, batch: Vec<WriteJob>) {
+        let mut conn = pool.get_connection();
+
+        let tx = match conn.transaction() {
+            Ok(tx) => tx,
+            Err(e) => {
+                // 无法开启事务时让所有请求都收到同一个错误
+                let msg = format!("Failed to begin write transaction: {}", e);
+                for job in batch {
+                    let _ = job.done.send(Err(msg.clone()));
+                }
+                return;
+            }
+        };
+
+        let mut results = Vec::with_capacity(batch.len());
+        let mut completions = Vec::with_capacity(batch.len());
+        for job in batch {
+            results.push((job.f)(&tx));
+            completions.push(job.done);
+        }
+
+        let commit_result = tx.commit().map_err(|e| format!("Failed to commit write batch: {}", e));
+
+        for (result, done) in results.into_iter().zip(completions) {
+            // 闭包自身失败时返回其错误；否则结果取决于提交是否成功
+            let final_result = match (&commit_result, result) {
+                (_, Err(e)) => Err(e),
+                (Ok(()), Ok(())) => Ok(()),
+                (Err(e), Ok(())) => Err(e.clone()),
+            };
+            let _ = done.send(final_result);
+        }
+    }
+
+    /// 提交一个写操作并异步等待其完成（包含所在批次的事务提交）
+    pub async fn submit<F>(&self, f: F) -> Result<(), String>
+    where
+        F: FnOnce(&Connection) -> Result<(), String> + Send + 'static,
+    {
+        let (done, rx) = tokio::sync::oneshot::channel();
+        self.sender
+            .send(WriteJob { f: Box::new(f), done })
+            .map_err(|_| "Database writer is not running".to_string())?;
+
+        rx.await
+            .map_err(|_| "Database writer dropped the request".to_string())?
+    }
+}
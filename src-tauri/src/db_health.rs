@@ -0,0 +1,240 @@
+//! 数据库健康检查：启动后在后台对三个库（metadata / colors / embeddings）
+//! 跑 `PRAGMA integrity_check`，发现损坏时尝试自动修复。
+//!
+//! 修复用 `VACUUM INTO` 把还能读出的数据抢救到新文件，再把损坏文件改名
+//! 留档（.corrupt-<时间戳>）、新文件顶上原名，连接池切换两次完成换血。
+//! 结果通过 "db-health-result" 事件推给前端，之后也可用 [`get_db_health`]
+//! 随时查询最近一次的结论。
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+use rusqlite::Connection;
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+
+use crate::color_db::ColorDbPool;
+use crate::db::AppDbPool;
+
+/// 单个数据库的检查结论
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DbHealth {
+    /// "metadata" | "colors" | "embeddings"
+    pub name: String,
+    pub ok: bool,
+    /// integrity_check 的第一行输出（正常为 "ok"）
+    pub message: String,
+    /// 本次检查中是否执行了自动修复
+    pub repaired: bool,
+}
+
+static LAST_HEALTH: Lazy<Mutex<Vec<DbHealth>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// 当前连接打开的主数据库文件路径
+fn main_db_file(conn: &Connection) -> Option<PathBuf> {
+    conn.query_row(
+        "SELECT file FROM pragma_database_list WHERE name = 'main'",
+        [],
+        |row| row.get::<_, String>(0),
+    )
+    .ok()
+    .filter(|f| !f.is_empty())
+    .map(PathBuf::from)
+}
+
+/// integrity_check 的第一行（"ok" 表示健康）
+fn integrity_first_line(conn: &Connection) -> String {
+    conn.query_row("PRAGMA integrity_check(1)", [], |row| row.get::<_, String>(0))
+        .unwrap_or_else(|e| format!("integrity_check 执行失败: {}", e))
+}
+
+/// 把 conn 当前打开的库抢救到 `<原名>.recovered`，返回新文件路径
+fn vacuum_into_recovered(conn: &Connection, db_path: &Path) -> Result<PathBuf, String> {
+    let recovered = PathBuf::from(format!("{}.recovered", db_path.display()));
+    let _ = std::fs::remove_file(&recovered);
+    let escaped = recovered.to_string_lossy().replace('\\', "/").replace('\'', "''");
+    conn.execute(&format!("VACUUM INTO '{}'", escaped), [])
+        .map_err(|e| format!("VACUUM INTO 失败: {}", e))?;
+    Ok(recovered)
+}
+
+/// 文件换血：损坏文件改名留档，抢救文件顶上原名
+fn swap_in_recovered(db_path: &Path, recovered: &Path) -> Result<(), String> {
+    let corrupt = format!(
+        "{}.corrupt-{}",
+        db_path.display(),
+        chrono::Utc::now().timestamp()
+    );
+    std::fs::rename(db_path, &corrupt).map_err(|e| format!("留档损坏文件失败: {}", e))?;
+    std::fs::rename(recovered, db_path).map_err(|e| format!("替换数据库文件失败: {}", e))?;
+    Ok(())
+}
+
+/// 检查 metadata 库，损坏时修复（切换连接池绕开文件占用）
+fn check_metadata(pool: &AppDbPool) -> DbHealth {
+    let (message, db_path) = {
+        let conn = pool.get_connection();
+        (integrity_first_line(&conn), main_db_file(&conn))
+    };
+    if message == "ok" {
+        return DbHealth { name: "metadata".to_string(), ok: true, message, repaired: false };
+    }
+    let Some(db_path) = db_path else {
+        return DbHealth { name: "metadata".to_string(), ok: false, message, repaired: false };
+    };
+
+    let repair = (|| -> Result<(), String> {
+        let recovered = {
+            let conn = pool.get_connection();
+            vacuum_into_recovered(&conn, &db_path)?
+        };
+        // 切到抢救文件，释放原文件句柄后换血，再切回原名
+        pool.switch(&recovered)?;
+        swap_in_recovered(&db_path, &recovered)?;
+        pool.switch(&db_path)
+    })();
+
+    match repair {
+        Ok(()) => DbHealth {
+            name: "metadata".to_string(),
+            ok: true,
+            message: format!("已从损坏中修复（{}）", message),
+            repaired: true,
+        },
+        Err(e) => DbHealth {
+            name: "metadata".to_string(),
+            ok: false,
+            message: format!("{}；修复失败: {}", message, e),
+            repaired: false,
+        },
+    }
+}
+
+/// 检查 colors 库，流程同 metadata
+fn check_colors(pool: &ColorDbPool) -> DbHealth {
+    let (message, db_path) = {
+        let conn = pool.get_connection();
+        (integrity_first_line(&conn), main_db_file(&conn))
+    };
+    if message == "ok" {
+        return DbHealth { name: "colors".to_string(), ok: true, message, repaired: false };
+    }
+    let Some(db_path) = db_path else {
+        return DbHealth { name: "colors".to_string(), ok: false, message, repaired: false };
+    };
+
+    let repair = (|| -> Result<(), String> {
+        let recovered = {
+            let conn = pool.get_connection();
+            vacuum_into_recovered(&conn, &db_path)?
+        };
+        pool.switch(&recovered)?;
+        swap_in_recovered(&db_path, &recovered)?;
+        pool.switch(&db_path)
+    })();
+
+    match repair {
+        Ok(()) => DbHealth {
+            name: "colors".to_string(),
+            ok: true,
+            message: format!("已从损坏中修复（{}）", message),
+            repaired: true,
+        },
+        Err(e) => DbHealth {
+            name: "colors".to_string(),
+            ok: false,
+            message: format!("{}；修复失败: {}", message, e),
+            repaired: false,
+        },
+    }
+}
+
+/// 检查 embeddings 库。该库按路径临时开连接，无需切换连接池，
+/// 换血前只要把我们自己的连接先关掉
+fn check_embeddings(db_path: &Path) -> DbHealth {
+    if !db_path.exists() {
+        return DbHealth {
+            name: "embeddings".to_string(),
+            ok: true,
+            message: "ok（尚未创建）".to_string(),
+            repaired: false,
+        };
+    }
+    let conn = match Connection::open(db_path) {
+        Ok(conn) => conn,
+        Err(e) => {
+            return DbHealth {
+                name: "embeddings".to_string(),
+                ok: false,
+                message: format!("打开失败: {}", e),
+                repaired: false,
+            }
+        }
+    };
+    let message = integrity_first_line(&conn);
+    if message == "ok" {
+        return DbHealth { name: "embeddings".to_string(), ok: true, message, repaired: false };
+    }
+
+    let repair = (|| -> Result<(), String> {
+        let recovered = vacuum_into_recovered(&conn, db_path)?;
+        drop(conn);
+        swap_in_recovered(db_path, &recovered)
+    })();
+
+    match repair {
+        Ok(()) => DbHealth {
+            name: "embeddings".to_string(),
+            ok: true,
+            message: format!("已从损坏中修复（{}）", message),
+            repaired: true,
+        },
+        Err(e) => DbHealth {
+            name: "embeddings".to_string(),
+            ok: false,
+            message: format!("{}；修复失败: {}", message, e),
+            repaired: false,
+        },
+    }
+}
+
+/// 跑一轮完整检查：三个库依次检查（必要时修复），存档并广播结果
+pub async fn run_health_check(app: tauri::AppHandle) -> Vec<DbHealth> {
+    let pool = app.state::<AppDbPool>().inner().clone();
+    let color_db = app.state::<Arc<ColorDbPool>>().inner().clone();
+    let embeddings_path = match crate::clip::get_clip_manager().await {
+        Some(manager) => {
+            let guard = manager.read().await;
+            guard.embedding_store().map(|s| s.db_path().to_path_buf())
+        }
+        None => None,
+    };
+
+    let results = tokio::task::spawn_blocking(move || {
+        let mut results = vec![check_metadata(&pool), check_colors(&color_db)];
+        if let Some(path) = embeddings_path {
+            results.push(check_embeddings(&path));
+        }
+        results
+    })
+    .await
+    .unwrap_or_default();
+
+    *LAST_HEALTH.lock().unwrap() = results.clone();
+    let _ = app.emit("db-health-result", results.clone());
+    results
+}
+
+/// 最近一次健康检查的结果（启动后的后台检查或手动触发）
+#[tauri::command]
+pub fn get_db_health() -> Vec<DbHealth> {
+    LAST_HEALTH.lock().unwrap().clone()
+}
+
+/// 手动触发一轮健康检查
+#[tauri::command]
+pub async fn run_db_health_check(app: tauri::AppHandle) -> Result<Vec<DbHealth>, String> {
+    Ok(run_health_check(app).await)
+}
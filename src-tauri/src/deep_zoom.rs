@@ -0,0 +1,296 @@
+//! 超大图的深度缩放（DZI 风格瓦片金字塔）：超过 50MP 的全景 / 拼接图
+//! 直接解码进 webview 会占掉数百 MB 内存，改为按需生成一次瓦片金字塔，
+//! 经 deepzoom:// 协议按层级取瓦片，查看器就能流畅平移缩放。
+//!
+//! 金字塔落在缩略图缓存目录的 deepzoom/ 子目录下，缓存键带文件大小
+//! 和修改时间，原图改动后自动失效。层级编号沿用 DZI 约定：
+//! maxLevel = ceil(log2(max(w,h)))，每低一级边长减半。
+
+use std::borrow::Cow;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tauri::Emitter;
+
+/// 瓦片边长（像素）
+const TILE_SIZE: u32 = 256;
+/// 低于这个像素数的图不值得建金字塔，直接整图解码即可
+const MIN_PIXELS: u64 = 50_000_000;
+/// 瓦片 JPEG 质量
+const TILE_QUALITY: u8 = 80;
+
+/// 金字塔生成的单飞标志（同一时间只建一座）
+static BUILD_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 最近一次 prepare 用的缓存根目录，协议处理器从这里找瓦片
+static CACHE_ROOT: Lazy<RwLock<Option<PathBuf>>> = Lazy::new(|| RwLock::new(None));
+
+/// 金字塔描述（info.json 的内容，也是 prepare 命令的返回值）
+#[derive(Serialize, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeepZoomInfo {
+    /// 缓存键，取瓦片时带上
+    pub key: String,
+    /// 原图尺寸
+    pub width: u32,
+    pub height: u32,
+    pub tile_size: u32,
+    /// 最高层级（原始分辨率所在层）
+    pub max_level: u32,
+}
+
+/// 缓存键：路径 + 大小 + 修改时间的 md5（与缩略图缓存同一套思路）
+fn cache_key(path: &str) -> Result<String, String> {
+    let metadata = std::fs::metadata(path).map_err(|e| format!("读取文件信息失败: {}", e))?;
+    let modified = metadata
+        .modified()
+        .map(|t| {
+            t.duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+        })
+        .unwrap_or(0);
+    Ok(format!(
+        "{:x}",
+        md5::compute(format!("{}-{}-{}", path, metadata.len(), modified).as_bytes())
+    ))
+}
+
+/// 某个缓存键对应的金字塔目录
+fn pyramid_dir(cache_root: &Path, key: &str) -> PathBuf {
+    cache_root.join("deepzoom").join(key)
+}
+
+fn tile_path(dir: &Path, level: u32, x: u32, y: u32) -> PathBuf {
+    dir.join(format!("{}_{}_{}.jpg", level, x, y))
+}
+
+/// 把一层图像切成瓦片写盘
+fn write_level_tiles(dir: &Path, level: u32, img: &image::RgbImage) -> Result<(), String> {
+    let cols = img.width().div_ceil(TILE_SIZE);
+    let rows = img.height().div_ceil(TILE_SIZE);
+    for y in 0..rows {
+        for x in 0..cols {
+            let tw = TILE_SIZE.min(img.width() - x * TILE_SIZE);
+            let th = TILE_SIZE.min(img.height() - y * TILE_SIZE);
+            let tile = image::imageops::crop_imm(img, x * TILE_SIZE, y * TILE_SIZE, tw, th)
+                .to_image();
+            let mut buf = Vec::new();
+            let mut encoder =
+                image::codecs::jpeg::JpegEncoder::new_with_quality(&mut buf, TILE_QUALITY);
+            encoder
+                .encode_image(&image::DynamicImage::ImageRgb8(tile))
+                .map_err(|e| format!("编码瓦片失败: {}", e))?;
+            std::fs::write(tile_path(dir, level, x, y), buf)
+                .map_err(|e| format!("写瓦片失败: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// 生成完整金字塔（阻塞，调用方放 spawn_blocking 里）
+fn build_pyramid(
+    file_path: &str,
+    cache_root: &Path,
+    key: &str,
+    app: &tauri::AppHandle,
+) -> Result<DeepZoomInfo, String> {
+    let img = image::open(file_path).map_err(|e| format!("解码失败: {}", e))?;
+    let (width, height) = (img.width(), img.height());
+    // DZI 约定：maxLevel = ceil(log2(最长边))
+    let max_dim = width.max(height).max(1);
+    let max_level = if max_dim.is_power_of_two() {
+        31 - max_dim.leading_zeros()
+    } else {
+        32 - max_dim.leading_zeros()
+    };
+
+    let dir = pyramid_dir(cache_root, key);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建金字塔目录失败: {}", e))?;
+
+    // 从原始分辨率逐级减半切片；低层级图像越来越小，大头在最高两级
+    let mut current = img.to_rgb8();
+    let mut level = max_level;
+    loop {
+        write_level_tiles(&dir, level, &current)?;
+        let _ = app.emit(
+            "deepzoom-progress",
+            serde_json::json!({ "key": key, "level": level, "maxLevel": max_level }),
+        );
+        if level == 0 || (current.width() <= 1 && current.height() <= 1) {
+            break;
+        }
+        current = image::imageops::resize(
+            &current,
+            current.width().div_ceil(2).max(1),
+            current.height().div_ceil(2).max(1),
+            image::imageops::FilterType::Triangle,
+        );
+        level -= 1;
+    }
+
+    let info = DeepZoomInfo {
+        key: key.to_string(),
+        width,
+        height,
+        tile_size: TILE_SIZE,
+        max_level,
+    };
+    // info.json 最后写，作为"金字塔完整"的标记
+    let json = serde_json::to_string(&info).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join("info.json"), json).map_err(|e| format!("写描述文件失败: {}", e))?;
+    Ok(info)
+}
+
+/// 为超大图准备瓦片金字塔（已有缓存时直接返回描述）。
+/// 小于 50MP 的图会被拒绝——前端直接整图加载即可
+#[tauri::command]
+pub async fn prepare_deep_zoom(
+    file_path: String,
+    cache_root: String,
+    app: tauri::AppHandle,
+) -> Result<DeepZoomInfo, String> {
+    if !Path::new(&file_path).is_file() {
+        return Err(format!("文件不存在: {}", file_path));
+    }
+    // 先用文件头探尺寸（不解码整图），过滤掉不需要金字塔的图
+    let mut header = Vec::new();
+    std::fs::File::open(&file_path)
+        .and_then(|mut f| f.by_ref().take(256 * 1024).read_to_end(&mut header))
+        .map_err(|e| format!("读取文件失败: {}", e))?;
+    let info = imageinfo::ImageInfo::from_raw_data(&header)
+        .map_err(|e| format!("无法识别图片尺寸: {:?}", e))?;
+    let pixels = info.size.width.max(0) as u64 * info.size.height.max(0) as u64;
+    if pixels < MIN_PIXELS {
+        return Err(format!(
+            "图片 {}MP，未达到深度缩放阈值（50MP），请直接加载",
+            pixels / 1_000_000
+        ));
+    }
+
+    let root = PathBuf::from(&cache_root);
+    let key = cache_key(&file_path)?;
+    *CACHE_ROOT.write().unwrap() = Some(root.clone());
+
+    // 已有完整金字塔直接复用
+    let info_file = pyramid_dir(&root, &key).join("info.json");
+    if let Ok(text) = std::fs::read_to_string(&info_file) {
+        if let Ok(info) = serde_json::from_str::<DeepZoomInfo>(&text) {
+            return Ok(info);
+        }
+    }
+
+    if BUILD_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("已有金字塔正在生成，请稍后重试".to_string());
+    }
+    let result = tokio::task::spawn_blocking(move || {
+        let r = build_pyramid(&file_path, &root, &key, &app);
+        BUILD_RUNNING.store(false, Ordering::SeqCst);
+        r
+    })
+    .await;
+    match result {
+        Ok(r) => r,
+        Err(e) => {
+            BUILD_RUNNING.store(false, Ordering::SeqCst);
+            Err(format!("金字塔生成任务失败: {}", e))
+        }
+    }
+}
+
+fn http_response(
+    status: u16,
+    content_type: &str,
+    body: Vec<u8>,
+) -> tauri::http::Response<Cow<'static, [u8]>> {
+    tauri::http::Response::builder()
+        .status(status)
+        .header("Content-Type", content_type)
+        .body(Cow::Owned(body))
+        .unwrap_or_else(|_| tauri::http::Response::new(Cow::Borrowed(&[][..])))
+}
+
+/// deepzoom:// 协议处理器：
+/// `/info?key=` 返回金字塔描述，`/tile?key=&level=&x=&y=` 返回 JPEG 瓦片。
+/// key 只认十六进制串，杜绝路径穿越
+pub fn handle_protocol<R: tauri::Runtime>(
+    _ctx: tauri::UriSchemeContext<'_, R>,
+    request: tauri::http::Request<Vec<u8>>,
+) -> tauri::http::Response<Cow<'static, [u8]>> {
+    let uri = request.uri();
+    let query = crate::api_server::parse_query(uri.query().unwrap_or(""));
+    let Some(key) = query.get("key") else {
+        return http_response(400, "text/plain; charset=utf-8", b"missing key".to_vec());
+    };
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_hexdigit()) {
+        return http_response(400, "text/plain; charset=utf-8", b"bad key".to_vec());
+    }
+    let Some(root) = CACHE_ROOT.read().unwrap().clone() else {
+        return http_response(404, "text/plain; charset=utf-8", b"not prepared".to_vec());
+    };
+    let dir = pyramid_dir(&root, key);
+
+    match uri.path() {
+        "/info" => match std::fs::read(dir.join("info.json")) {
+            Ok(bytes) => http_response(200, "application/json", bytes),
+            Err(_) => http_response(404, "text/plain; charset=utf-8", b"not found".to_vec()),
+        },
+        "/tile" => {
+            let parse = |name: &str| query.get(name).and_then(|v| v.parse::<u32>().ok());
+            let (Some(level), Some(x), Some(y)) = (parse("level"), parse("x"), parse("y")) else {
+                return http_response(
+                    400,
+                    "text/plain; charset=utf-8",
+                    b"missing level/x/y".to_vec(),
+                );
+            };
+            match std::fs::read(tile_path(&dir, level, x, y)) {
+                Ok(bytes) => http_response(200, "image/jpeg", bytes),
+                Err(_) => http_response(404, "text/plain; charset=utf-8", b"no tile".to_vec()),
+            }
+        }
+        _ => http_response(404, "text/plain; charset=utf-8", b"not found".to_vec()),
+    }
+}
+
+/// 清空深度缩放瓦片缓存，返回释放的字节数
+#[tauri::command]
+pub async fn clear_deep_zoom_cache(cache_root: String) -> Result<u64, String> {
+    tokio::task::spawn_blocking(move || {
+        let dir = PathBuf::from(cache_root).join("deepzoom");
+        if !dir.is_dir() {
+            return Ok(0);
+        }
+        let mut freed = 0u64;
+        for entry in walkdir_sizes(&dir) {
+            freed += entry;
+        }
+        std::fs::remove_dir_all(&dir).map_err(|e| format!("删除缓存失败: {}", e))?;
+        Ok(freed)
+    })
+    .await
+    .map_err(|e| format!("清理任务失败: {}", e))?
+}
+
+fn walkdir_sizes(dir: &Path) -> Vec<u64> {
+    let mut sizes = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return sizes;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            sizes.extend(walkdir_sizes(&path));
+        } else if let Ok(meta) = entry.metadata() {
+            sizes.push(meta.len());
+        }
+    }
+    sizes
+}
@@ -0,0 +1,139 @@
+//! 原生拖出：把选中文件作为真实文件路径（Windows 的 CF_HDROP /
+//! macOS 的 NSFilePromise）拖进资源管理器、Photoshop 等外部程序，
+//! 补上拖拽集成里"只有预览图"的那一半。
+//!
+//! 可选在拖出前临场转换格式（png / jpeg / webp）：转换结果写进
+//! 系统临时目录的 aurora_drag_out/ 下，拖出的就是转换后的副本，
+//! 原图不动。拖拽必须在主线程发起，与 tauri-plugin-drag 同一套路。
+
+use std::path::PathBuf;
+use std::sync::mpsc::channel;
+
+use tauri::{Emitter, Manager};
+
+use crate::db::{self, AppDbPool};
+
+/// 拖出前的临场转换，None 表示直接拖原文件
+fn convert_for_drag(path: &str, format: &str, out_dir: &std::path::Path) -> Result<PathBuf, String> {
+    let src = std::path::Path::new(path);
+    let stem = src.file_stem().and_then(|s| s.to_str()).unwrap_or("export");
+    let img = image::open(src).map_err(|e| format!("解码 {} 失败: {}", path, e))?;
+
+    let (ext, fmt) = match format {
+        "png" => ("png", image::ImageFormat::Png),
+        "jpeg" | "jpg" => ("jpg", image::ImageFormat::Jpeg),
+        "webp" => ("webp", image::ImageFormat::WebP),
+        other => return Err(format!("不支持的转换格式: {}", other)),
+    };
+    let mut out = out_dir.join(format!("{}.{}", stem, ext));
+    // 多选里可能有同名文件，加序号避让
+    let mut n = 1;
+    while out.exists() {
+        out = out_dir.join(format!("{}_{}.{}", stem, n, ext));
+        n += 1;
+    }
+    // JPEG 不带 alpha，先落到白底
+    let img = if fmt == image::ImageFormat::Jpeg && img.color().has_alpha() {
+        let rgba = img.to_rgba8();
+        let mut rgb = image::RgbImage::from_pixel(rgba.width(), rgba.height(), image::Rgb([255, 255, 255]));
+        for (x, y, p) in rgba.enumerate_pixels() {
+            let a = p[3] as u32;
+            let bg = rgb.get_pixel_mut(x, y);
+            for k in 0..3 {
+                bg[k] = ((p[k] as u32 * a + bg[k] as u32 * (255 - a)) / 255) as u8;
+            }
+        }
+        image::DynamicImage::ImageRgb8(rgb)
+    } else {
+        img
+    };
+    img.save_with_format(&out, fmt).map_err(|e| format!("写出转换文件失败: {}", e))?;
+    Ok(out)
+}
+
+/// 拖拽角标：第一个文件的缩略 PNG，解码失败时退化为半透明灰块
+fn drag_badge(first_path: &str) -> Vec<u8> {
+    let img = image::open(first_path)
+        .map(|i| i.thumbnail(128, 128))
+        .unwrap_or_else(|_| {
+            image::DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(
+                64,
+                64,
+                image::Rgba([0x80, 0x80, 0x80, 0xA0]),
+            ))
+        });
+    let mut png = Vec::new();
+    let _ = img.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png);
+    png
+}
+
+/// 发起携带真实文件路径的 OS 拖拽。export_format 非空时先转换
+/// 再拖转换后的副本。结束后发 "drag-out-finished" 事件（带结果）
+#[tauri::command]
+pub async fn start_file_drag(
+    file_ids: Vec<String>,
+    export_format: Option<String>,
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+) -> Result<(), String> {
+    let pool = app.state::<AppDbPool>().inner().clone();
+
+    // 路径解析与临场转换都是阻塞工作，先在线程池里备齐
+    let paths: Vec<PathBuf> = tokio::task::spawn_blocking(move || {
+        let conn = pool.get_connection();
+        let mut resolved = Vec::with_capacity(file_ids.len());
+        for id in &file_ids {
+            if let Some(entry) = db::file_index::get_entry_by_id(&conn, id).map_err(|e| e.to_string())? {
+                resolved.push(entry.path);
+            }
+        }
+        if resolved.is_empty() {
+            return Err("选中的文件都不在索引中".to_string());
+        }
+        match export_format.as_deref().filter(|f| !f.is_empty()) {
+            None => Ok(resolved.into_iter().map(PathBuf::from).collect()),
+            Some(format) => {
+                let out_dir = std::env::temp_dir().join("aurora_drag_out");
+                std::fs::create_dir_all(&out_dir).map_err(|e| format!("创建临时目录失败: {}", e))?;
+                resolved
+                    .iter()
+                    .map(|p| convert_for_drag(p, format, &out_dir))
+                    .collect()
+            }
+        }
+    })
+    .await
+    .map_err(|e| format!("拖出准备任务失败: {}", e))??;
+
+    let badge = drag_badge(&paths[0].to_string_lossy());
+    let app_for_event = app.clone();
+    let (tx, rx) = channel();
+    app.run_on_main_thread(move || {
+        #[cfg(target_os = "linux")]
+        let raw_window = window.gtk_window();
+        #[cfg(not(target_os = "linux"))]
+        let raw_window = tauri::Result::Ok(window.clone());
+
+        let result = match raw_window {
+            Ok(w) => drag::start_drag(
+                &w,
+                drag::DragItem::Files(paths),
+                drag::Image::Raw(badge),
+                move |result, _cursor_pos| {
+                    let outcome = match result {
+                        drag::DragResult::Dropped => "dropped",
+                        drag::DragResult::Cancel => "cancelled",
+                    };
+                    let _ = app_for_event.emit("drag-out-finished", outcome);
+                },
+                drag::Options::default(),
+            )
+            .map_err(|e| format!("发起拖拽失败: {}", e)),
+            Err(e) => Err(format!("获取窗口句柄失败: {}", e)),
+        };
+        let _ = tx.send(result);
+    })
+    .map_err(|e| format!("切换到主线程失败: {}", e))?;
+
+    rx.recv().map_err(|e| format!("拖拽结果接收失败: {}", e))?
+}
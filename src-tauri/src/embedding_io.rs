@@ -0,0 +1,328 @@
+//! 嵌入库的导出 / 导入：在算力强的机器上批量算好 CLIP 嵌入再搬过来，
+//! 或者把嵌入丢进外部分析 notebook。支持两种格式：
+//!
+//! - "npz"：NumPy 压缩包，`np.load` 直接读。包含 embeddings.npy
+//!   （N×D float32）和 ids.json（与行号对齐的 file_id / 模型版本 /
+//!   时间戳数组）；
+//! - "jsonl"：一行一条完整记录，流式处理和跨语言解析都方便。
+//!
+//! parquet 需要拖一整套列存依赖，体量与收益不成比例，不提供。
+//! npy 的写读和 ZIP 的解包都是手写的最小实现（写包复用
+//! [`crate::zip_package::ZipWriter`]），只保证吃自己导出的文件和
+//! NumPy 默认参数保存的文件。
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::clip::{self, embedding::ImageEmbedding};
+
+/// npz 里与 embeddings.npy 行号对齐的一条 id 记录
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct IdRecord {
+    file_id: String,
+    model_version: String,
+    created_at: i64,
+}
+
+// --- npy 最小实现（v1.0，little-endian float32，C order） ---
+
+fn write_npy_f32(rows: usize, cols: usize, data: &[f32]) -> Vec<u8> {
+    let dict = format!(
+        "{{'descr': '<f4', 'fortran_order': False, 'shape': ({}, {}), }}",
+        rows, cols
+    );
+    // 头部（magic+版本+长度+字典）总长补齐到 64 的倍数，以 \n 结尾
+    let base = 10 + dict.len() + 1;
+    let padding = if base.is_multiple_of(64) { 0 } else { 64 - base % 64 };
+    let header_len = (dict.len() + padding + 1) as u16;
+
+    let mut out = Vec::with_capacity(10 + header_len as usize + data.len() * 4);
+    out.extend_from_slice(b"\x93NUMPY\x01\x00");
+    out.extend_from_slice(&header_len.to_le_bytes());
+    out.extend_from_slice(dict.as_bytes());
+    out.extend(std::iter::repeat_n(b' ', padding));
+    out.push(b'\n');
+    for v in data {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    out
+}
+
+fn parse_npy_f32(bytes: &[u8]) -> Result<(usize, usize, Vec<f32>), String> {
+    if bytes.len() < 10 || &bytes[..6] != b"\x93NUMPY" {
+        return Err("不是有效的 npy 文件".to_string());
+    }
+    let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+    let header = std::str::from_utf8(&bytes[10..10 + header_len])
+        .map_err(|_| "npy 头部不是 UTF-8".to_string())?;
+    if !header.contains("'<f4'") {
+        return Err("只支持 float32（<f4）的 npy".to_string());
+    }
+    if header.contains("'fortran_order': True") {
+        return Err("不支持 Fortran 序的 npy".to_string());
+    }
+    let shape_part = header
+        .split("'shape':")
+        .nth(1)
+        .and_then(|s| s.split('(').nth(1))
+        .and_then(|s| s.split(')').next())
+        .ok_or("npy 头部缺少 shape")?;
+    let dims: Vec<usize> = shape_part
+        .split(',')
+        .filter_map(|d| d.trim().parse().ok())
+        .collect();
+    if dims.len() != 2 {
+        return Err("只支持二维的 npy".to_string());
+    }
+    let (rows, cols) = (dims[0], dims[1]);
+    let data_bytes = &bytes[10 + header_len..];
+    if data_bytes.len() < rows * cols * 4 {
+        return Err("npy 数据长度与 shape 不符".to_string());
+    }
+    let data = data_bytes[..rows * cols * 4]
+        .chunks_exact(4)
+        .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect();
+    Ok((rows, cols, data))
+}
+
+// --- ZIP 最小解包（只认自己的 ZipWriter 和 NumPy 写出的条目） ---
+
+fn read_zip_entries(bytes: &[u8]) -> Result<HashMap<String, Vec<u8>>, String> {
+    // 从尾部找 EOCD（0x06054b50），定位中央目录
+    let eocd_pos = bytes
+        .windows(4)
+        .rposition(|w| w == [0x50, 0x4b, 0x05, 0x06])
+        .ok_or("ZIP 缺少结束记录")?;
+    if bytes.len() < eocd_pos + 22 {
+        return Err("ZIP 结束记录不完整".to_string());
+    }
+    let count = u16::from_le_bytes([bytes[eocd_pos + 10], bytes[eocd_pos + 11]]) as usize;
+    let cd_offset = u32::from_le_bytes([
+        bytes[eocd_pos + 16],
+        bytes[eocd_pos + 17],
+        bytes[eocd_pos + 18],
+        bytes[eocd_pos + 19],
+    ]) as usize;
+
+    let mut entries = HashMap::new();
+    let mut pos = cd_offset;
+    for _ in 0..count {
+        if bytes.len() < pos + 46 || bytes[pos..pos + 4] != [0x50, 0x4b, 0x01, 0x02] {
+            return Err("ZIP 中央目录损坏".to_string());
+        }
+        let method = u16::from_le_bytes([bytes[pos + 10], bytes[pos + 11]]);
+        let compressed = u32::from_le_bytes([
+            bytes[pos + 20], bytes[pos + 21], bytes[pos + 22], bytes[pos + 23],
+        ]) as usize;
+        let name_len = u16::from_le_bytes([bytes[pos + 28], bytes[pos + 29]]) as usize;
+        let extra_len = u16::from_le_bytes([bytes[pos + 30], bytes[pos + 31]]) as usize;
+        let comment_len = u16::from_le_bytes([bytes[pos + 32], bytes[pos + 33]]) as usize;
+        let local_offset = u32::from_le_bytes([
+            bytes[pos + 42], bytes[pos + 43], bytes[pos + 44], bytes[pos + 45],
+        ]) as usize;
+        let name = String::from_utf8_lossy(&bytes[pos + 46..pos + 46 + name_len]).to_string();
+
+        // 数据偏移要从本地头重新算（本地头的 extra 可能和中央目录不同）
+        if bytes.len() < local_offset + 30 || bytes[local_offset..local_offset + 4] != [0x50, 0x4b, 0x03, 0x04] {
+            return Err(format!("ZIP 条目 {} 的本地头损坏", name));
+        }
+        let l_name = u16::from_le_bytes([bytes[local_offset + 26], bytes[local_offset + 27]]) as usize;
+        let l_extra = u16::from_le_bytes([bytes[local_offset + 28], bytes[local_offset + 29]]) as usize;
+        let data_start = local_offset + 30 + l_name + l_extra;
+        let raw = bytes
+            .get(data_start..data_start + compressed)
+            .ok_or_else(|| format!("ZIP 条目 {} 数据越界", name))?;
+
+        let data = match method {
+            0 => raw.to_vec(),
+            8 => {
+                let mut out = Vec::new();
+                flate2::read::DeflateDecoder::new(raw)
+                    .read_to_end(&mut out)
+                    .map_err(|e| format!("解压 ZIP 条目 {} 失败: {}", name, e))?;
+                out
+            }
+            other => return Err(format!("不支持的 ZIP 压缩方式: {}", other)),
+        };
+        entries.insert(name, data);
+        pos += 46 + name_len + extra_len + comment_len;
+    }
+    Ok(entries)
+}
+
+async fn get_store() -> Result<clip::embedding::EmbeddingStore, String> {
+    let manager = clip::get_clip_manager()
+        .await
+        .ok_or("CLIP manager not initialized")?;
+    let guard = manager.read().await;
+    Ok(guard
+        .embedding_store()
+        .ok_or("Embedding store not available")?
+        .clone())
+}
+
+/// 导出全部嵌入。dest 可以是目录（自动起名）或完整文件路径；
+/// format："npz"（默认）| "jsonl"。返回导出的记录数
+#[tauri::command]
+pub async fn clip_export_embeddings(
+    dest: String,
+    format: Option<String>,
+) -> Result<usize, String> {
+    let format = format.unwrap_or_else(|| "npz".to_string());
+    if format != "npz" && format != "jsonl" {
+        return Err(format!("不支持的导出格式: {}（可选 npz / jsonl）", format));
+    }
+    let store = get_store().await?;
+
+    tokio::task::spawn_blocking(move || -> Result<usize, String> {
+        let embeddings = store.get_all_embeddings()?;
+        if embeddings.is_empty() {
+            return Err("嵌入库为空，没有可导出的内容".to_string());
+        }
+
+        let dest_path = if Path::new(&dest).is_dir() {
+            let name = format!(
+                "aurora_embeddings_{}.{}",
+                chrono::Local::now().format("%Y%m%d_%H%M%S"),
+                format
+            );
+            Path::new(&dest).join(name)
+        } else {
+            Path::new(&dest).to_path_buf()
+        };
+
+        let count = embeddings.len();
+        if format == "jsonl" {
+            let mut out = String::new();
+            for emb in &embeddings {
+                out.push_str(&serde_json::to_string(emb).map_err(|e| e.to_string())?);
+                out.push('\n');
+            }
+            std::fs::write(&dest_path, out).map_err(|e| format!("写入导出文件失败: {}", e))?;
+            return Ok(count);
+        }
+
+        // npz：所有行的维度必须一致（混用过不同模型时导 jsonl）
+        let dim = embeddings[0].embedding.len();
+        if embeddings.iter().any(|e| e.embedding.len() != dim) {
+            return Err("嵌入维度不一致（存在多个模型的结果），请改用 jsonl 格式".to_string());
+        }
+        let mut data = Vec::with_capacity(count * dim);
+        let mut ids = Vec::with_capacity(count);
+        for emb in &embeddings {
+            data.extend_from_slice(&emb.embedding);
+            ids.push(IdRecord {
+                file_id: emb.file_id.clone(),
+                model_version: emb.model_version.clone(),
+                created_at: emb.created_at,
+            });
+        }
+        let file = std::fs::File::create(&dest_path)
+            .map_err(|e| format!("创建导出文件失败: {}", e))?;
+        let mut zip = crate::zip_package::ZipWriter::new(file);
+        zip.add_file("embeddings.npy", &write_npy_f32(count, dim, &data), None)?;
+        zip.add_file(
+            "ids.json",
+            serde_json::to_string(&ids).map_err(|e| e.to_string())?.as_bytes(),
+            None,
+        )?;
+        zip.finish()?;
+        Ok(count)
+    })
+    .await
+    .map_err(|e| format!("导出任务失败: {}", e))?
+}
+
+/// 导入嵌入（按扩展名识别 npz / jsonl）。默认跳过库里已有的 file_id，
+/// overwrite 为 true 时覆盖。返回实际写入的记录数
+#[tauri::command]
+pub async fn clip_import_embeddings(
+    src: String,
+    overwrite: Option<bool>,
+) -> Result<usize, String> {
+    let overwrite = overwrite.unwrap_or(false);
+    let store = get_store().await?;
+
+    tokio::task::spawn_blocking(move || -> Result<usize, String> {
+        let bytes = std::fs::read(&src).map_err(|e| format!("读取导入文件失败: {}", e))?;
+        let mut records: Vec<ImageEmbedding> = if src.to_lowercase().ends_with(".jsonl") {
+            let text = String::from_utf8(bytes).map_err(|_| "jsonl 文件不是 UTF-8".to_string())?;
+            let mut records = Vec::new();
+            for (i, line) in text.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                records.push(
+                    serde_json::from_str(line)
+                        .map_err(|e| format!("第 {} 行解析失败: {}", i + 1, e))?,
+                );
+            }
+            records
+        } else {
+            let entries = read_zip_entries(&bytes)?;
+            let npy = entries.get("embeddings.npy").ok_or("npz 里缺少 embeddings.npy")?;
+            let ids_json = entries.get("ids.json").ok_or("npz 里缺少 ids.json")?;
+            let (rows, cols, data) = parse_npy_f32(npy)?;
+            let ids: Vec<IdRecord> = serde_json::from_slice(ids_json)
+                .map_err(|e| format!("解析 ids.json 失败: {}", e))?;
+            if ids.len() != rows {
+                return Err("ids.json 与 embeddings.npy 行数不一致".to_string());
+            }
+            ids.into_iter()
+                .enumerate()
+                .map(|(i, rec)| ImageEmbedding {
+                    file_id: rec.file_id,
+                    embedding: data[i * cols..(i + 1) * cols].to_vec(),
+                    model_version: rec.model_version,
+                    created_at: rec.created_at,
+                })
+                .collect()
+        };
+
+        if !overwrite {
+            let existing: std::collections::HashSet<String> =
+                store.get_all_file_ids()?.into_iter().collect();
+            records.retain(|r| !existing.contains(&r.file_id));
+        }
+        let count = records.len();
+        store.save_embeddings_batch(&records)?;
+        Ok(count)
+    })
+    .await
+    .map_err(|e| format!("导入任务失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_npy_roundtrip() {
+        let data = vec![0.5f32, -1.25, 3.0, 0.0, 42.0, -0.001];
+        let bytes = write_npy_f32(2, 3, &data);
+        // 头部总长为 64 的倍数
+        assert!((10 + u16::from_le_bytes([bytes[8], bytes[9]]) as usize).is_multiple_of(64));
+        let (rows, cols, parsed) = parse_npy_f32(&bytes).unwrap();
+        assert_eq!((rows, cols), (2, 3));
+        assert_eq!(parsed, data);
+    }
+
+    #[test]
+    fn test_zip_roundtrip() {
+        let mut buf = std::io::Cursor::new(Vec::new());
+        let mut zip = crate::zip_package::ZipWriter::new(&mut buf);
+        zip.add_file("a.txt", b"hello world", None).unwrap();
+        zip.add_file("dir/b.bin", &[0u8; 1000], None).unwrap();
+        zip.finish().unwrap();
+
+        let entries = read_zip_entries(&buf.into_inner()).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries["a.txt"], b"hello world");
+        assert_eq!(entries["dir/b.bin"], vec![0u8; 1000]);
+    }
+}
@@ -0,0 +1,346 @@
+//! 远端导出后端：把选中的文件推送到 WebDAV 或 S3 兼容存储（NAS、对象存储桶）。
+//!
+//! 目标配置（地址、用户名、桶名等）存在应用数据目录的 export_targets.json，
+//! 密码 / Secret Key 通过 [`crate::credentials`] 存入系统凭据管理器，不落盘。
+//! WebDAV 走 Basic 认证的 PUT（父集合按需 MKCOL）；
+//! S3 兼容端走 SigV4 签名的 PUT，路径式寻址（`{endpoint}/{bucket}/{key}`），
+//! 兼容 MinIO / R2 等自建服务。
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use base64::{engine::general_purpose, Engine as _};
+use tauri::{Emitter, Manager};
+
+static EXPORT_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 一个远端导出目标。kind："webdav" | "s3"；
+/// WebDAV 用 url + username，S3 用 endpoint + bucket + region + username（作为 Access Key）。
+/// prefix 为远端路径前缀，所有上传都放在它下面
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportTarget {
+    pub name: String,
+    pub kind: String,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub bucket: Option<String>,
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct UploadProgress {
+    processed: usize,
+    total: usize,
+    current: String,
+}
+
+/// 上传结果：失败的文件带上原因，方便重试
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportReport {
+    pub uploaded: usize,
+    pub failed: Vec<String>,
+}
+
+fn targets_path(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join("export_targets.json"))
+}
+
+fn load_targets(app: &tauri::AppHandle) -> Result<Vec<ExportTarget>, String> {
+    let path = targets_path(app)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| format!("解析导出目标配置失败: {}", e))
+}
+
+fn save_targets(app: &tauri::AppHandle, targets: &[ExportTarget]) -> Result<(), String> {
+    let path = targets_path(app)?;
+    let content = serde_json::to_string_pretty(targets).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 按 RFC 3986 对 S3 key 逐段编码（'/' 保留）
+fn encode_key(key: &str) -> String {
+    key.split('/')
+        .map(|seg| urlencoding::encode(seg).into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// WebDAV 上传：逐级 MKCOL 父集合（已存在时服务端返回 405，忽略），再 PUT
+async fn upload_webdav(
+    client: &reqwest::Client,
+    target: &ExportTarget,
+    secret: &str,
+    rel: &str,
+    bytes: Vec<u8>,
+) -> Result<(), String> {
+    let base = target
+        .url
+        .as_deref()
+        .ok_or("WebDAV 目标缺少 url")?
+        .trim_end_matches('/')
+        .to_string();
+    let username = target.username.as_deref().unwrap_or("");
+    let auth = format!(
+        "Basic {}",
+        general_purpose::STANDARD.encode(format!("{}:{}", username, secret))
+    );
+
+    let full_rel = match target.prefix.as_deref().filter(|p| !p.is_empty()) {
+        Some(prefix) => format!("{}/{}", prefix.trim_matches('/'), rel),
+        None => rel.to_string(),
+    };
+
+    // 逐级确保父集合存在
+    let segments: Vec<&str> = full_rel.split('/').collect();
+    let mut dir = String::new();
+    for seg in &segments[..segments.len().saturating_sub(1)] {
+        if !dir.is_empty() {
+            dir.push('/');
+        }
+        dir.push_str(seg);
+        let _ = client
+            .request(
+                reqwest::Method::from_bytes(b"MKCOL").unwrap(),
+                format!("{}/{}", base, encode_key(&dir)),
+            )
+            .header("Authorization", &auth)
+            .send()
+            .await;
+    }
+
+    let resp = client
+        .put(format!("{}/{}", base, encode_key(&full_rel)))
+        .header("Authorization", &auth)
+        .body(bytes)
+        .send()
+        .await
+        .map_err(|e| format!("WebDAV 请求失败: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("WebDAV 上传失败: HTTP {}", resp.status()));
+    }
+    Ok(())
+}
+
+/// S3 SigV4 签名链：kSecret → kDate → kRegion → kService → kSigning
+fn sigv4_signing_key(secret: &str, date: &str, region: &str) -> [u8; 32] {
+    let k_date = hmac_sha256::HMAC::mac(date, format!("AWS4{}", secret));
+    let k_region = hmac_sha256::HMAC::mac(region, k_date);
+    let k_service = hmac_sha256::HMAC::mac("s3", k_region);
+    hmac_sha256::HMAC::mac("aws4_request", k_service)
+}
+
+/// S3 兼容上传：SigV4 签名 PUT，签 host / x-amz-content-sha256 / x-amz-date 三个头
+async fn upload_s3(
+    client: &reqwest::Client,
+    target: &ExportTarget,
+    secret: &str,
+    rel: &str,
+    bytes: Vec<u8>,
+) -> Result<(), String> {
+    let endpoint = target
+        .endpoint
+        .as_deref()
+        .ok_or("S3 目标缺少 endpoint")?
+        .trim_end_matches('/');
+    let bucket = target.bucket.as_deref().ok_or("S3 目标缺少 bucket")?;
+    let access_key = target.username.as_deref().ok_or("S3 目标缺少 Access Key")?;
+    let region = target.region.as_deref().unwrap_or("us-east-1");
+
+    let key = match target.prefix.as_deref().filter(|p| !p.is_empty()) {
+        Some(prefix) => format!("{}/{}", prefix.trim_matches('/'), rel),
+        None => rel.to_string(),
+    };
+
+    let host = endpoint
+        .strip_prefix("https://")
+        .or_else(|| endpoint.strip_prefix("http://"))
+        .ok_or("endpoint 必须以 http(s):// 开头")?;
+
+    let now = chrono::Utc::now();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let payload_hash = hex(&hmac_sha256::Hash::hash(&bytes));
+
+    let canonical_uri = format!("/{}/{}", bucket, encode_key(&key));
+    let canonical_headers = format!(
+        "host:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n",
+        host, payload_hash, amz_date
+    );
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "PUT\n{}\n\n{}\n{}\n{}",
+        canonical_uri, canonical_headers, signed_headers, payload_hash
+    );
+
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex(&hmac_sha256::Hash::hash(canonical_request.as_bytes()))
+    );
+    let signature = hex(&hmac_sha256::HMAC::mac(
+        string_to_sign,
+        sigv4_signing_key(secret, &date, region),
+    ));
+
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        access_key, scope, signed_headers, signature
+    );
+
+    let resp = client
+        .put(format!("{}{}", endpoint, canonical_uri))
+        .header("Authorization", authorization)
+        .header("x-amz-content-sha256", payload_hash)
+        .header("x-amz-date", amz_date)
+        .body(bytes)
+        .send()
+        .await
+        .map_err(|e| format!("S3 请求失败: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("S3 上传失败: HTTP {}", resp.status()));
+    }
+    Ok(())
+}
+
+/// 按目标类型分发上传
+async fn upload_file(
+    client: &reqwest::Client,
+    target: &ExportTarget,
+    secret: &str,
+    rel: &str,
+    bytes: Vec<u8>,
+) -> Result<(), String> {
+    match target.kind.as_str() {
+        "webdav" => upload_webdav(client, target, secret, rel, bytes).await,
+        "s3" => upload_s3(client, target, secret, rel, bytes).await,
+        other => Err(format!("未知的导出后端类型: {}", other)),
+    }
+}
+
+/// 保存（或覆盖）一个导出目标，secret 存入系统凭据管理器
+#[tauri::command]
+pub async fn save_export_target(
+    target: ExportTarget,
+    secret: String,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    if !matches!(target.kind.as_str(), "webdav" | "s3") {
+        return Err(format!("未知的导出后端类型: {}", target.kind));
+    }
+    if target.name.trim().is_empty() {
+        return Err("导出目标名称不能为空".to_string());
+    }
+    crate::credentials::store_secret(&app, &target.name, &secret)?;
+
+    let mut targets = load_targets(&app)?;
+    targets.retain(|t| t.name != target.name);
+    targets.push(target);
+    save_targets(&app, &targets)
+}
+
+/// 列出已配置的导出目标（不含凭据）
+#[tauri::command]
+pub async fn list_export_targets(app: tauri::AppHandle) -> Result<Vec<ExportTarget>, String> {
+    load_targets(&app)
+}
+
+/// 删除导出目标及其凭据
+#[tauri::command]
+pub async fn delete_export_target(name: String, app: tauri::AppHandle) -> Result<(), String> {
+    let mut targets = load_targets(&app)?;
+    targets.retain(|t| t.name != name);
+    save_targets(&app, &targets)?;
+    crate::credentials::delete_secret(&app, &name);
+    Ok(())
+}
+
+/// 把一组文件上传到指定目标。
+/// base_dir 为本地基准目录，远端按相对它的路径组织；
+/// 不在 base_dir 下的文件按文件名平铺。失败的文件记入报告，不中断整体
+#[tauri::command]
+pub async fn export_to_target(
+    name: String,
+    base_dir: String,
+    paths: Vec<String>,
+    app: tauri::AppHandle,
+) -> Result<ExportReport, String> {
+    let target = load_targets(&app)?
+        .into_iter()
+        .find(|t| t.name == name)
+        .ok_or_else(|| format!("导出目标不存在: {}", name))?;
+    let secret = crate::credentials::get_secret(&app, &name)?
+        .ok_or_else(|| format!("找不到目标的凭据: {}", name))?;
+
+    if EXPORT_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("导出任务已在运行中".to_string());
+    }
+
+    let base = crate::db::normalize_path(&base_dir);
+    let client = reqwest::Client::new();
+    let total = paths.len();
+    let mut report = ExportReport {
+        uploaded: 0,
+        failed: Vec::new(),
+    };
+
+    for (i, path) in paths.iter().enumerate() {
+        let normalized = crate::db::normalize_path(path);
+        let rel = normalized
+            .strip_prefix(&format!("{}/", base))
+            .map(|r| r.to_string())
+            .unwrap_or_else(|| {
+                Path::new(&normalized)
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or(&normalized)
+                    .to_string()
+            });
+
+        let result = match tokio::fs::read(&normalized).await {
+            Ok(bytes) => upload_file(&client, &target, &secret, &rel, bytes).await,
+            Err(e) => Err(format!("读取文件失败: {}", e)),
+        };
+        match result {
+            Ok(()) => report.uploaded += 1,
+            Err(e) => report.failed.push(format!("{}: {}", normalized, e)),
+        }
+
+        let _ = app.emit(
+            "export-upload-progress",
+            UploadProgress {
+                processed: i + 1,
+                total,
+                current: rel,
+            },
+        );
+    }
+
+    EXPORT_RUNNING.store(false, Ordering::SeqCst);
+    Ok(report)
+}
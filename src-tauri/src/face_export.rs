@@ -0,0 +1,246 @@
+//! 人脸裁剪导出：按人物把所有打过框的脸导出成对齐的正方形小图，
+//! 供选头像或攒 ML 训练集用。
+//!
+//! 人脸框存在 file_metadata.ai_data 的 faces 数组里（personId + 百分比
+//! 坐标框）。导出时以框中心取正方形（带 30% 外扩余量，裁剪更自然），
+//! 越界处贴边收缩，统一缩放到指定分辨率后存 PNG。
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::db::AppDbPool;
+
+/// 导出任务的单飞标志
+static FACE_EXPORT_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 正方形框在人脸框基础上的外扩比例
+const CROP_MARGIN: f64 = 0.3;
+
+/// ai_data.faces 的一项（只取导出需要的字段）
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AiFace {
+    #[serde(default)]
+    id: String,
+    person_id: String,
+    #[serde(rename = "box")]
+    face_box: FaceBoxPercent,
+}
+
+/// 百分比坐标框（0-100，相对原图宽高）
+#[derive(Deserialize)]
+struct FaceBoxPercent {
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FaceCropReport {
+    pub exported: usize,
+    pub failed: Vec<String>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct FaceCropProgress {
+    processed: usize,
+    total: usize,
+    current: String,
+}
+
+/// 把百分比人脸框换算成贴合图内的正方形像素区域 (x, y, side)
+fn square_crop_rect(
+    box_: &FaceBoxPercent,
+    img_w: u32,
+    img_h: u32,
+) -> Option<(u32, u32, u32)> {
+    let w = box_.w / 100.0 * img_w as f64;
+    let h = box_.h / 100.0 * img_h as f64;
+    if w <= 1.0 || h <= 1.0 {
+        return None;
+    }
+    let cx = box_.x / 100.0 * img_w as f64 + w / 2.0;
+    let cy = box_.y / 100.0 * img_h as f64 + h / 2.0;
+    // 以较长边为基准外扩，超出图像时收缩到能放下为止
+    let mut side = (w.max(h) * (1.0 + CROP_MARGIN)).round();
+    side = side.min(img_w as f64).min(img_h as f64);
+    if side < 2.0 {
+        return None;
+    }
+    let x = (cx - side / 2.0).clamp(0.0, img_w as f64 - side);
+    let y = (cy - side / 2.0).clamp(0.0, img_h as f64 - side);
+    Some((x as u32, y as u32, side as u32))
+}
+
+/// 取出某个文件里属于指定人物的人脸框
+fn faces_of_person(ai_data: &serde_json::Value, person_id: &str) -> Vec<AiFace> {
+    ai_data
+        .get("faces")
+        .and_then(|f| f.as_array())
+        .map(|faces| {
+            faces
+                .iter()
+                .filter_map(|f| serde_json::from_value::<AiFace>(f.clone()).ok())
+                .filter(|f| f.person_id == person_id)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 导出文件名：原图文件名 + 脸的序号 / id，避免同图多脸互相覆盖
+fn crop_file_name(source: &str, face: &AiFace, index: usize) -> String {
+    let stem = Path::new(source)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("face");
+    if face.id.is_empty() {
+        format!("{}_face{}.png", stem, index)
+    } else {
+        format!("{}_{}.png", stem, face.id)
+    }
+}
+
+/// 按人物导出对齐的正方形人脸裁剪图。
+/// size 为输出边长（像素，64 - 2048），dest_dir 不存在时自动创建
+#[tauri::command]
+pub async fn export_face_crops(
+    person_id: String,
+    size: u32,
+    dest_dir: String,
+    pool: tauri::State<'_, AppDbPool>,
+    app: tauri::AppHandle,
+) -> Result<FaceCropReport, String> {
+    if person_id.trim().is_empty() {
+        return Err("人物 id 不能为空".to_string());
+    }
+    let size = size.clamp(64, 2048);
+    let pool = pool.inner().clone();
+
+    if FACE_EXPORT_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("已有人脸导出在进行中".to_string());
+    }
+
+    let result = tokio::task::spawn_blocking(move || {
+        export_inner(&pool, &person_id, size, &dest_dir, &app)
+    })
+    .await;
+    FACE_EXPORT_RUNNING.store(false, Ordering::SeqCst);
+    result.map_err(|e| format!("人脸导出任务失败: {}", e))?
+}
+
+fn export_inner(
+    pool: &AppDbPool,
+    person_id: &str,
+    size: u32,
+    dest_dir: &str,
+    app: &tauri::AppHandle,
+) -> Result<FaceCropReport, String> {
+    let dest = PathBuf::from(dest_dir);
+    std::fs::create_dir_all(&dest).map_err(|e| format!("创建目标目录失败: {}", e))?;
+
+    // LIKE 先粗筛出可能包含该人物的行，再在 Rust 侧解析 JSON 精确匹配
+    let candidates: Vec<(String, serde_json::Value)> = {
+        let conn = pool.get_connection();
+        let mut stmt = conn
+            .prepare(
+                "SELECT path, ai_data FROM file_metadata
+                 WHERE ai_data IS NOT NULL AND ai_data LIKE '%' || ?1 || '%'",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([person_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, serde_json::Value>(1)?))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+
+    let total = candidates.len();
+    let mut exported = 0usize;
+    let mut failed = Vec::new();
+
+    for (processed, (path, ai_data)) in candidates.into_iter().enumerate() {
+        let faces = faces_of_person(&ai_data, person_id);
+        if faces.is_empty() {
+            continue;
+        }
+        if !Path::new(&path).is_file() {
+            failed.push(format!("{}（文件不存在）", path));
+            continue;
+        }
+        let img = match image::open(&path) {
+            Ok(img) => img,
+            Err(e) => {
+                failed.push(format!("{}（解码失败: {}）", path, e));
+                continue;
+            }
+        };
+        for (index, face) in faces.iter().enumerate() {
+            let Some((x, y, side)) = square_crop_rect(&face.face_box, img.width(), img.height())
+            else {
+                continue;
+            };
+            let crop = img
+                .crop_imm(x, y, side, side)
+                .resize_exact(size, size, image::imageops::FilterType::Lanczos3);
+            let out = dest.join(crop_file_name(&path, face, index));
+            match crop.save(&out) {
+                Ok(()) => exported += 1,
+                Err(e) => failed.push(format!("{}（保存失败: {}）", out.display(), e)),
+            }
+        }
+        if processed.is_multiple_of(5) {
+            let _ = app.emit(
+                "face-export-progress",
+                FaceCropProgress {
+                    processed,
+                    total,
+                    current: path.clone(),
+                },
+            );
+        }
+    }
+
+    if exported == 0 && failed.is_empty() {
+        return Err("该人物没有可导出的人脸框".to_string());
+    }
+    Ok(FaceCropReport { exported, failed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_square_crop_rect_clamps_to_image() {
+        // 靠右下角的脸：外扩后贴边收缩，不越界
+        let box_ = FaceBoxPercent {
+            x: 80.0,
+            y: 80.0,
+            w: 18.0,
+            h: 15.0,
+        };
+        let (x, y, side) = square_crop_rect(&box_, 1000, 800).unwrap();
+        assert!(x + side <= 1000);
+        assert!(y + side <= 800);
+        assert!(side >= 180); // 至少盖住脸框较长边
+
+        // 退化框（零尺寸）直接跳过
+        let degenerate = FaceBoxPercent {
+            x: 10.0,
+            y: 10.0,
+            w: 0.0,
+            h: 0.0,
+        };
+        assert!(square_crop_rect(&degenerate, 1000, 800).is_none());
+    }
+}
@@ -0,0 +1,340 @@
+//! 打码导出：导出 / 分享前把已检测到的人脸自动模糊或像素化，
+//! 供需要保护隐私的公开发布场景用。
+//!
+//! 人脸框与 [`crate::face_export`] 同源，存在 file_metadata.ai_data 的
+//! faces 数组里（百分比坐标框），打码对所有框生效，不区分是否已
+//! 归入人物。没检测过人脸的图会原样复制并在报告里单独计数——
+//! "没有框"不等于"没有脸"，调用方应提示用户先跑一遍人脸检测。
+//! 输出按原扩展名重新编码，动图只保留首帧。
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager};
+
+use crate::db::{self, AppDbPool};
+
+/// 打码导出任务的单飞标志
+static REDACT_EXPORT_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 打码区域在人脸框基础上的外扩比例，把发际线和下巴一起盖住
+const REDACT_MARGIN: f64 = 0.15;
+/// 像素化时人脸短边切成的块数，块数越少马赛克越粗
+const PIXELATE_BLOCKS: u32 = 10;
+/// 模糊半径与人脸短边的比例（短边 1/6，足以认不出五官）
+const BLUR_SIGMA_RATIO: f32 = 1.0 / 6.0;
+
+/// ai_data.faces 的一项（打码只需要框，不关心归属）
+#[derive(Deserialize)]
+struct AiFaceBox {
+    #[serde(rename = "box")]
+    face_box: FaceBoxPercent,
+}
+
+/// 百分比坐标框（0-100，相对原图宽高）
+#[derive(Deserialize)]
+struct FaceBoxPercent {
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactReport {
+    /// 成功导出的文件数（含无框原样复制的）
+    pub exported: usize,
+    /// 打码的人脸总数
+    pub redacted_faces: usize,
+    /// 没有人脸框、被原样复制的文件数
+    pub without_faces: usize,
+    pub failed: Vec<String>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct RedactProgress {
+    processed: usize,
+    total: usize,
+    current: String,
+}
+
+/// 把百分比框换算成贴合图内的像素矩形 (x, y, w, h)，带外扩余量
+fn pixel_rect(box_: &FaceBoxPercent, img_w: u32, img_h: u32) -> Option<(u32, u32, u32, u32)> {
+    let w = box_.w / 100.0 * img_w as f64;
+    let h = box_.h / 100.0 * img_h as f64;
+    if w <= 1.0 || h <= 1.0 {
+        return None;
+    }
+    let x = (box_.x / 100.0 * img_w as f64 - w * REDACT_MARGIN / 2.0).max(0.0);
+    let y = (box_.y / 100.0 * img_h as f64 - h * REDACT_MARGIN / 2.0).max(0.0);
+    let w = (w * (1.0 + REDACT_MARGIN)).min(img_w as f64 - x);
+    let h = (h * (1.0 + REDACT_MARGIN)).min(img_h as f64 - y);
+    if w < 2.0 || h < 2.0 {
+        return None;
+    }
+    Some((x as u32, y as u32, w as u32, h as u32))
+}
+
+/// 取出一个文件的所有人脸框
+fn face_boxes(ai_data: &serde_json::Value) -> Vec<FaceBoxPercent> {
+    ai_data
+        .get("faces")
+        .and_then(|f| f.as_array())
+        .map(|faces| {
+            faces
+                .iter()
+                .filter_map(|f| serde_json::from_value::<AiFaceBox>(f.clone()).ok())
+                .map(|f| f.face_box)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 对一张图的所有人脸框就地打码，返回处理的框数
+fn redact_faces(img: &mut image::DynamicImage, boxes: &[FaceBoxPercent], mode: &str) -> usize {
+    let (img_w, img_h) = (img.width(), img.height());
+    let mut count = 0;
+    for box_ in boxes {
+        let Some((x, y, w, h)) = pixel_rect(box_, img_w, img_h) else {
+            continue;
+        };
+        let region = img.crop_imm(x, y, w, h);
+        let patched = if mode == "pixelate" {
+            // 先缩到固定块数再用最近邻放回去，得到干净的马赛克格
+            let block = (w.min(h) / PIXELATE_BLOCKS).max(1);
+            region
+                .resize_exact((w / block).max(1), (h / block).max(1), image::imageops::FilterType::Triangle)
+                .resize_exact(w, h, image::imageops::FilterType::Nearest)
+        } else {
+            let sigma = (w.min(h) as f32 * BLUR_SIGMA_RATIO).max(4.0);
+            region.blur(sigma)
+        };
+        image::imageops::overlay(img, &patched, x as i64, y as i64);
+        count += 1;
+    }
+    count
+}
+
+/// 目标文件名；同名冲突时加序号，避免不同目录的同名图互相覆盖
+fn unique_dest(dest_dir: &Path, source: &str) -> PathBuf {
+    let name = Path::new(source)
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("redacted");
+    let mut out = dest_dir.join(name);
+    let mut index = 1;
+    while out.exists() {
+        let stem = Path::new(name).file_stem().and_then(|s| s.to_str()).unwrap_or("redacted");
+        let ext = Path::new(name).extension().and_then(|s| s.to_str()).unwrap_or("png");
+        out = dest_dir.join(format!("{}_{}.{}", stem, index, ext));
+        index += 1;
+    }
+    out
+}
+
+/// 解析选中的文件 id 为 (路径, ai_data) 列表，跳过文件夹
+fn resolve_sources(
+    pool: &AppDbPool,
+    file_ids: &[String],
+) -> Result<Vec<(String, Option<serde_json::Value>)>, String> {
+    let conn = pool.get_connection();
+    let mut sources = Vec::with_capacity(file_ids.len());
+    for id in file_ids {
+        let Some(entry) = db::file_index::get_entry_by_id(&conn, id).map_err(|e| e.to_string())?
+        else {
+            continue;
+        };
+        if entry.file_type == "Folder" {
+            continue;
+        }
+        let ai_data: Option<serde_json::Value> = conn
+            .query_row(
+                "SELECT ai_data FROM file_metadata WHERE path = ?1",
+                [entry.path.as_str()],
+                |row| row.get(0),
+            )
+            .ok();
+        sources.push((entry.path, ai_data));
+    }
+    Ok(sources)
+}
+
+fn redact_inner(
+    sources: Vec<(String, Option<serde_json::Value>)>,
+    dest: &Path,
+    mode: &str,
+    app: &tauri::AppHandle,
+) -> Result<RedactReport, String> {
+    std::fs::create_dir_all(dest).map_err(|e| format!("创建目标目录失败: {}", e))?;
+
+    let total = sources.len();
+    let mut report = RedactReport {
+        exported: 0,
+        redacted_faces: 0,
+        without_faces: 0,
+        failed: Vec::new(),
+    };
+
+    for (processed, (path, ai_data)) in sources.into_iter().enumerate() {
+        if !Path::new(&path).is_file() {
+            report.failed.push(format!("{}（文件不存在）", path));
+            continue;
+        }
+        let boxes = ai_data.as_ref().map(face_boxes).unwrap_or_default();
+        let out = unique_dest(dest, &path);
+
+        if boxes.is_empty() {
+            // 没有框就原样复制，但要让调用方知道这部分没打码
+            match std::fs::copy(&path, &out) {
+                Ok(_) => {
+                    report.exported += 1;
+                    report.without_faces += 1;
+                }
+                Err(e) => report.failed.push(format!("{}（复制失败: {}）", path, e)),
+            }
+        } else {
+            let mut img = match image::open(&path) {
+                Ok(img) => img,
+                Err(e) => {
+                    report.failed.push(format!("{}（解码失败: {}）", path, e));
+                    continue;
+                }
+            };
+            report.redacted_faces += redact_faces(&mut img, &boxes, mode);
+            match img.save(&out) {
+                Ok(()) => report.exported += 1,
+                Err(e) => report.failed.push(format!("{}（保存失败: {}）", out.display(), e)),
+            }
+        }
+
+        if processed.is_multiple_of(5) {
+            let _ = app.emit(
+                "redact-export-progress",
+                RedactProgress {
+                    processed,
+                    total,
+                    current: path.clone(),
+                },
+            );
+        }
+    }
+    Ok(report)
+}
+
+/// 把选中文件打码后导出到目录。mode："blur"（默认）或 "pixelate"
+#[tauri::command]
+pub async fn export_redacted(
+    file_ids: Vec<String>,
+    dest_dir: String,
+    mode: Option<String>,
+    pool: tauri::State<'_, AppDbPool>,
+    app: tauri::AppHandle,
+) -> Result<RedactReport, String> {
+    let mode = mode.unwrap_or_else(|| "blur".to_string());
+    if mode != "blur" && mode != "pixelate" {
+        return Err(format!("未知的打码方式: {}", mode));
+    }
+    let pool = pool.inner().clone();
+
+    if REDACT_EXPORT_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("已有打码导出在进行中".to_string());
+    }
+
+    let result = tokio::task::spawn_blocking(move || {
+        let sources = resolve_sources(&pool, &file_ids)?;
+        if sources.is_empty() {
+            return Err("选中的文件都不在索引中".to_string());
+        }
+        redact_inner(sources, Path::new(&dest_dir), &mode, &app)
+    })
+    .await;
+    REDACT_EXPORT_RUNNING.store(false, Ordering::SeqCst);
+    result.map_err(|e| format!("打码导出任务失败: {}", e))?
+}
+
+/// 打码后交给系统分享：先在临时目录生成打码副本，再走
+/// [`crate::share`] 的系统分享通道。临时副本留给系统清理，
+/// 分享面板可能异步读取文件，不能立刻删
+#[tauri::command]
+pub async fn share_redacted(
+    file_ids: Vec<String>,
+    mode: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<RedactReport, String> {
+    let mode = mode.unwrap_or_else(|| "blur".to_string());
+    if mode != "blur" && mode != "pixelate" {
+        return Err(format!("未知的打码方式: {}", mode));
+    }
+    let pool = app.state::<AppDbPool>().inner().clone();
+
+    if REDACT_EXPORT_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("已有打码导出在进行中".to_string());
+    }
+
+    let result = tokio::task::spawn_blocking(move || {
+        let sources = resolve_sources(&pool, &file_ids)?;
+        if sources.is_empty() {
+            return Err("选中的文件都不在索引中".to_string());
+        }
+        let dest = std::env::temp_dir().join(format!(
+            "aurora_redacted_{}",
+            chrono::Local::now().format("%Y%m%d_%H%M%S")
+        ));
+        let report = redact_inner(sources, &dest, &mode, &app)?;
+        if report.exported == 0 {
+            return Err(format!("没有可分享的文件: {:?}", report.failed));
+        }
+        let paths: Vec<String> = std::fs::read_dir(&dest)
+            .map_err(|e| format!("读取临时目录失败: {}", e))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path().to_string_lossy().to_string())
+            .collect();
+        crate::share::share_with_os(&paths)?;
+        Ok(report)
+    })
+    .await;
+    REDACT_EXPORT_RUNNING.store(false, Ordering::SeqCst);
+    result.map_err(|e| format!("打码分享任务失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pixel_rect_clamps_to_image() {
+        // 框贴右下角，外扩后仍不能越界
+        let box_ = FaceBoxPercent { x: 90.0, y: 90.0, w: 10.0, h: 10.0 };
+        let (x, y, w, h) = pixel_rect(&box_, 1000, 1000).unwrap();
+        assert!(x + w <= 1000);
+        assert!(y + h <= 1000);
+        assert!(w >= 100 && h >= 100);
+    }
+
+    #[test]
+    fn test_redact_changes_face_region_only() {
+        let mut img = image::DynamicImage::ImageRgba8(image::RgbaImage::from_fn(
+            100,
+            100,
+            |x, _| {
+                // 横向渐变，保证打码区域内有可检测的变化
+                image::Rgba([(x * 2) as u8, 0, 0, 255])
+            },
+        ));
+        let original = img.clone();
+        let boxes = vec![FaceBoxPercent { x: 10.0, y: 10.0, w: 30.0, h: 30.0 }];
+        assert_eq!(redact_faces(&mut img, &boxes, "pixelate"), 1);
+
+        // 远离人脸框的角落不受影响
+        assert_eq!(img.crop_imm(80, 80, 10, 10).to_rgba8(), original.crop_imm(80, 80, 10, 10).to_rgba8());
+    }
+}
@@ -0,0 +1,94 @@
+//! 文件占用检测
+//!
+//! Windows 上移动/删除失败最常见的原因是文件被其它进程打开。
+//! 这里通过 RestartManager API 查询占用者进程名，附加到错误信息中，
+//! 让前端可以提示用户 "文件正被 Photoshop 占用" 而不是盲目重试。
+//! 其它平台没有等价的系统接口，返回空列表。
+
+/// 查询占用指定文件的进程名列表（去重，按 RestartManager 返回顺序）
+#[cfg(windows)]
+pub fn locking_processes(path: &str) -> Vec<String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::ERROR_MORE_DATA;
+    use windows_sys::Win32::System::RestartManager::{
+        RmEndSession, RmGetList, RmRegisterResources, RmStartSession, CCH_RM_SESSION_KEY,
+        RM_PROCESS_INFO,
+    };
+
+    let mut names: Vec<String> = Vec::new();
+
+    unsafe {
+        let mut session: u32 = 0;
+        let mut session_key = [0u16; CCH_RM_SESSION_KEY as usize + 1];
+        if RmStartSession(&mut session, 0, session_key.as_mut_ptr()) != 0 {
+            return names;
+        }
+
+        // 注册要查询的文件（RestartManager 需要以 NUL 结尾的宽字符路径）
+        let wide_path: Vec<u16> = std::ffi::OsStr::new(path)
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect();
+        let file_ptr = wide_path.as_ptr();
+
+        if RmRegisterResources(session, 1, &file_ptr, 0, std::ptr::null(), 0, std::ptr::null()) == 0 {
+            // 第一次调用获取所需的数组大小，第二次取回进程信息
+            let mut needed: u32 = 0;
+            let mut count: u32 = 0;
+            let mut reboot_reasons: u32 = 0;
+            let rc = RmGetList(
+                session,
+                &mut needed,
+                &mut count,
+                std::ptr::null_mut(),
+                &mut reboot_reasons,
+            );
+
+            if rc == ERROR_MORE_DATA && needed > 0 {
+                let mut infos: Vec<RM_PROCESS_INFO> =
+                    vec![std::mem::zeroed(); needed as usize];
+                count = needed;
+                if RmGetList(
+                    session,
+                    &mut needed,
+                    &mut count,
+                    infos.as_mut_ptr(),
+                    &mut reboot_reasons,
+                ) == 0
+                {
+                    for info in infos.iter().take(count as usize) {
+                        let app_name = &info.strAppName;
+                        let len = app_name
+                            .iter()
+                            .position(|&c| c == 0)
+                            .unwrap_or(app_name.len());
+                        let name = String::from_utf16_lossy(&app_name[..len]);
+                        if !name.is_empty() && !names.contains(&name) {
+                            names.push(name);
+                        }
+                    }
+                }
+            }
+        }
+
+        RmEndSession(session);
+    }
+
+    names
+}
+
+/// 非 Windows 平台：无系统级占用查询接口
+#[cfg(not(windows))]
+pub fn locking_processes(_path: &str) -> Vec<String> {
+    Vec::new()
+}
+
+/// 生成附加在错误信息末尾的占用说明；未检测到占用者时返回空字符串
+pub fn lock_hint(path: &str) -> String {
+    let processes = locking_processes(path);
+    if processes.is_empty() {
+        String::new()
+    } else {
+        format!(" (文件正被以下程序占用: {})", processes.join(", "))
+    }
+}
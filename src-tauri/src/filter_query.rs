@@ -0,0 +1,266 @@
+//! 网格过滤查询 DSL。
+//!
+//! 让高级用户直接在过滤框输入类似
+//! `format:png width:>3000 tag:"concept art" rating:>=4 date:2024` 的表达式，
+//! 编译成 file_index / file_metadata 上的参数化 SQL 执行。
+//! 语法：空格分隔的 `键:值` 条件（AND 关系），值可以带引号，
+//! 数值键支持 `>` `>=` `<` `<=` 前缀；不带键的裸词按文件名子串匹配。
+
+use rusqlite::types::Value;
+use tauri::Manager;
+
+use crate::db::AppDbPool;
+
+/// 把表达式切成词元，双引号内的空格不分词
+fn tokenize(expr: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in expr.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// 拆出数值比较前缀，默认按等于处理
+fn split_op(raw: &str) -> (&'static str, &str) {
+    for (prefix, op) in [(">=", ">="), ("<=", "<="), (">", ">"), ("<", "<"), ("=", "=")] {
+        if let Some(rest) = raw.strip_prefix(prefix) {
+            return (op, rest);
+        }
+    }
+    ("=", raw)
+}
+
+/// 数值条件：`width:>3000`、`size:>=2mb`（size 支持 kb/mb 后缀，换算成字节）
+fn push_numeric(
+    column: &str,
+    raw: &str,
+    conds: &mut Vec<String>,
+    params: &mut Vec<Value>,
+) -> Result<(), String> {
+    let (op, num_raw) = split_op(raw);
+    let lower = num_raw.to_lowercase();
+    let (digits, multiplier) = if let Some(d) = lower.strip_suffix("mb") {
+        (d, 1024 * 1024)
+    } else if let Some(d) = lower.strip_suffix("kb") {
+        (d, 1024)
+    } else {
+        (lower.as_str(), 1)
+    };
+    let n: i64 = digits
+        .trim()
+        .parse()
+        .map_err(|_| format!("无法解析数值: {}", raw))?;
+    conds.push(format!("{} {} ?", column, op));
+    params.push(Value::Integer(n * multiplier));
+    Ok(())
+}
+
+/// 日期条件：`date:2024`、`date:2024-05`、`date:2024-05-17`，匹配文件修改时间落在该区间
+fn push_date(raw: &str, conds: &mut Vec<String>, params: &mut Vec<Value>) -> Result<(), String> {
+    use chrono::NaiveDate;
+
+    let parts: Vec<&str> = raw.split('-').collect();
+    let (start, end) = match parts.as_slice() {
+        [y] => {
+            let y: i32 = y.parse().map_err(|_| format!("无法解析日期: {}", raw))?;
+            (
+                NaiveDate::from_ymd_opt(y, 1, 1),
+                NaiveDate::from_ymd_opt(y + 1, 1, 1),
+            )
+        }
+        [y, m] => {
+            let y: i32 = y.parse().map_err(|_| format!("无法解析日期: {}", raw))?;
+            let m: u32 = m.parse().map_err(|_| format!("无法解析日期: {}", raw))?;
+            let next = if m == 12 {
+                NaiveDate::from_ymd_opt(y + 1, 1, 1)
+            } else {
+                NaiveDate::from_ymd_opt(y, m + 1, 1)
+            };
+            (NaiveDate::from_ymd_opt(y, m, 1), next)
+        }
+        [y, m, d] => {
+            let y: i32 = y.parse().map_err(|_| format!("无法解析日期: {}", raw))?;
+            let m: u32 = m.parse().map_err(|_| format!("无法解析日期: {}", raw))?;
+            let d: u32 = d.parse().map_err(|_| format!("无法解析日期: {}", raw))?;
+            let day = NaiveDate::from_ymd_opt(y, m, d);
+            (day, day.map(|d| d.succ_opt().unwrap_or(d)))
+        }
+        _ => return Err(format!("无法解析日期: {}", raw)),
+    };
+    let (Some(start), Some(end)) = (start, end) else {
+        return Err(format!("无效的日期: {}", raw));
+    };
+    conds.push("fi.modified_at >= ? AND fi.modified_at < ?".to_string());
+    params.push(Value::Integer(
+        start.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp(),
+    ));
+    params.push(Value::Integer(
+        end.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp(),
+    ));
+    Ok(())
+}
+
+/// 布尔值：true/yes/1 为真，false/no/0 为假
+fn parse_bool(raw: &str) -> Result<bool, String> {
+    match raw.to_lowercase().as_str() {
+        "true" | "yes" | "1" => Ok(true),
+        "false" | "no" | "0" => Ok(false),
+        other => Err(format!("无法解析布尔值: {}", other)),
+    }
+}
+
+/// 把表达式编译成 (WHERE 片段, 参数列表)。所有条件按 AND 连接
+pub fn compile(expr: &str) -> Result<(String, Vec<Value>), String> {
+    let mut conds: Vec<String> = vec!["fi.file_type = 'Image'".to_string()];
+    let mut params: Vec<Value> = Vec::new();
+
+    for token in tokenize(expr) {
+        let Some((key, value)) = token.split_once(':') else {
+            // 裸词：文件名子串匹配
+            conds.push("fi.name LIKE ? ESCAPE '\\'".to_string());
+            params.push(Value::Text(format!("%{}%", escape_like(&token))));
+            continue;
+        };
+        if value.is_empty() {
+            return Err(format!("条件缺少值: {}", token));
+        }
+        match key.to_lowercase().as_str() {
+            "format" | "ext" => {
+                conds.push("lower(fi.format) = ?".to_string());
+                params.push(Value::Text(value.to_lowercase()));
+            }
+            "width" => push_numeric("fi.width", value, &mut conds, &mut params)?,
+            "height" => push_numeric("fi.height", value, &mut conds, &mut params)?,
+            "size" => push_numeric("fi.size", value, &mut conds, &mut params)?,
+            "bitdepth" => push_numeric("fi.bit_depth", value, &mut conds, &mut params)?,
+            "rating" => push_numeric("fm.rating", value, &mut conds, &mut params)?,
+            "date" => push_date(value, &mut conds, &mut params)?,
+            "tag" => {
+                // tags 以 JSON 数组文本存储，按完整元素匹配
+                conds.push("fm.tags LIKE ? ESCAPE '\\'".to_string());
+                params.push(Value::Text(format!(
+                    "%\"{}\"%",
+                    escape_like(value).replace('"', "")
+                )));
+            }
+            "category" => {
+                conds.push("fm.category = ?".to_string());
+                params.push(Value::Text(value.to_string()));
+            }
+            "colorspace" => {
+                conds.push("lower(fi.color_space) = ?".to_string());
+                params.push(Value::Text(value.to_lowercase()));
+            }
+            "alpha" => {
+                conds.push(format!(
+                    "fi.has_alpha = {}",
+                    if parse_bool(value)? { 1 } else { 0 }
+                ));
+            }
+            "animated" => {
+                conds.push(format!(
+                    "fi.is_animated = {}",
+                    if parse_bool(value)? { 1 } else { 0 }
+                ));
+            }
+            "path" => {
+                conds.push("fi.path LIKE ? ESCAPE '\\'".to_string());
+                params.push(Value::Text(format!("%{}%", escape_like(value))));
+            }
+            other => return Err(format!("未知的过滤键: {}", other)),
+        }
+    }
+
+    Ok((conds.join(" AND "), params))
+}
+
+/// 转义 LIKE 通配符，用户输入按字面匹配
+fn escape_like(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// 执行过滤表达式，返回匹配文件的路径（按修改时间倒序）。
+/// `scope` 为目录路径时只在该目录下过滤
+#[tauri::command]
+pub async fn query_files(
+    expr: String,
+    scope: Option<String>,
+    limit: Option<usize>,
+    app: tauri::AppHandle,
+) -> Result<Vec<String>, String> {
+    let (mut where_clause, mut params) = compile(&expr)?;
+    if let Some(dir) = &scope {
+        where_clause.push_str(" AND fi.path LIKE ?");
+        params.push(Value::Text(format!("{}%", crate::normalize_path(dir))));
+    }
+    let limit = limit.unwrap_or(2000).clamp(1, 50000);
+
+    let pool = app.state::<AppDbPool>().inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get_connection();
+        let sql = format!(
+            "SELECT fi.path FROM file_index fi
+             LEFT JOIN file_metadata fm ON fm.file_id = fi.file_id
+             WHERE {}
+             ORDER BY fi.modified_at DESC
+             LIMIT {}",
+            where_clause, limit
+        );
+        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(params), |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<String>, _>>().map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("过滤查询任务失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_basic_filters() {
+        let (sql, params) = compile("format:png width:>3000 rating:>=4").unwrap();
+        assert!(sql.contains("lower(fi.format) = ?"));
+        assert!(sql.contains("fi.width > ?"));
+        assert!(sql.contains("fm.rating >= ?"));
+        assert_eq!(params.len(), 3);
+    }
+
+    #[test]
+    fn test_compile_quoted_tag_and_bare_word() {
+        let (sql, params) = compile("tag:\"concept art\" sunset").unwrap();
+        assert!(sql.contains("fm.tags LIKE ?"));
+        assert!(sql.contains("fi.name LIKE ?"));
+        assert!(matches!(&params[0], Value::Text(t) if t == "%\"concept art\"%"));
+        assert!(matches!(&params[1], Value::Text(t) if t == "%sunset%"));
+    }
+
+    #[test]
+    fn test_compile_rejects_unknown_key() {
+        assert!(compile("foo:bar").is_err());
+        assert!(compile("width:abc").is_err());
+    }
+
+    #[test]
+    fn test_compile_size_suffix_and_date() {
+        let (_, params) = compile("size:>=2mb date:2024").unwrap();
+        assert!(matches!(&params[0], Value::Integer(n) if *n == 2 * 1024 * 1024));
+        assert_eq!(params.len(), 3);
+    }
+}
@@ -0,0 +1,359 @@
+//! 文件夹同步：把库内文件夹与外部目录（作品集目录、U 盘、NAS 挂载点）保持一致。
+//!
+//! 三种模式：
+//! - "mirror"：外部目录成为库文件夹的精确镜像，外部多余的文件会被删除
+//! - "one-way"：只把库内新增/变更的文件复制到外部，不删除外部多余文件
+//! - "two-way"：双向合并，缺失的互相补齐；两侧都存在且内容不同时，
+//!   修改时间新的一侧覆盖旧的一侧，修改时间相同则记为冲突并跳过
+//!
+//! 文件是否一致先比大小，大小相同再比 MD5（分块读取，避免大文件占内存）。
+//! dry_run 只生成计划不落盘；执行后前端应对库内文件夹重新扫描以更新索引。
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tauri::Emitter;
+
+static SYNC_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 一条同步动作。kind："copy-to-external" | "copy-to-library" | "delete-external"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncAction {
+    pub kind: String,
+    /// 相对两侧根目录的路径
+    pub rel_path: String,
+}
+
+/// 同步结果（dry_run 时 actions 为计划，计数均为 0）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    pub mode: String,
+    pub dry_run: bool,
+    pub actions: Vec<SyncAction>,
+    /// 两侧都改过且无法自动取舍的文件（相对路径）
+    pub conflicts: Vec<String>,
+    pub copied: usize,
+    pub deleted: usize,
+    pub failed: usize,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SyncProgress {
+    processed: usize,
+    total: usize,
+}
+
+/// 单个文件的快照：大小 + 修改时间，内容比较时再算 MD5
+struct FileState {
+    abs_path: PathBuf,
+    size: u64,
+    modified_at: i64,
+}
+
+/// 递归收集 root 下的所有文件，键为相对路径（正斜杠分隔）。
+/// 跳过 .Aurora_Cache 与隐藏文件
+fn collect_files(root: &Path, prefix: &str, out: &mut BTreeMap<String, FileState>) -> Result<(), String> {
+    let entries = fs::read_dir(root).map_err(|e| format!("读取目录失败 {}: {}", root.display(), e))?;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if name.starts_with('.') || name == ".Aurora_Cache" {
+            continue;
+        }
+        let rel = if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        if path.is_dir() {
+            collect_files(&path, &rel, out)?;
+        } else if let Ok(md) = entry.metadata() {
+            let modified_at = md
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0);
+            out.insert(
+                rel,
+                FileState {
+                    abs_path: path,
+                    size: md.len(),
+                    modified_at,
+                },
+            );
+        }
+    }
+    Ok(())
+}
+
+/// 分块计算文件 MD5
+fn file_md5(path: &Path) -> Result<md5::Digest, String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("打开文件失败 {}: {}", path.display(), e))?;
+    let mut ctx = md5::Context::new();
+    let mut buf = vec![0u8; 256 * 1024];
+    loop {
+        let n = file.read(&mut buf).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        ctx.consume(&buf[..n]);
+    }
+    Ok(ctx.compute())
+}
+
+/// 内容是否一致：大小不同直接判不同，大小相同再比 MD5
+fn same_content(a: &FileState, b: &FileState) -> Result<bool, String> {
+    if a.size != b.size {
+        return Ok(false);
+    }
+    Ok(file_md5(&a.abs_path)? == file_md5(&b.abs_path)?)
+}
+
+/// 把 rel_path 对应的文件从 src_root 复制到 dest_root（自动创建父目录）
+fn copy_rel(src_root: &Path, dest_root: &Path, rel: &str) -> Result<(), String> {
+    let src = src_root.join(rel);
+    let dest = dest_root.join(rel);
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    fs::copy(&src, &dest).map_err(|e| format!("复制失败 {}: {}", rel, e))?;
+    Ok(())
+}
+
+/// 生成同步计划。library / external 为两侧的文件快照
+fn build_plan(
+    mode: &str,
+    library: &BTreeMap<String, FileState>,
+    external: &BTreeMap<String, FileState>,
+) -> Result<(Vec<SyncAction>, Vec<String>), String> {
+    let mut actions = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for (rel, lib_state) in library {
+        match external.get(rel) {
+            None => actions.push(SyncAction {
+                kind: "copy-to-external".to_string(),
+                rel_path: rel.clone(),
+            }),
+            Some(ext_state) => {
+                if same_content(lib_state, ext_state)? {
+                    continue;
+                }
+                match mode {
+                    // 镜像/单向：库内版本为准
+                    "mirror" | "one-way" => actions.push(SyncAction {
+                        kind: "copy-to-external".to_string(),
+                        rel_path: rel.clone(),
+                    }),
+                    // 双向：修改时间新的一侧覆盖旧的一侧，时间相同视为冲突
+                    "two-way" => {
+                        if lib_state.modified_at > ext_state.modified_at {
+                            actions.push(SyncAction {
+                                kind: "copy-to-external".to_string(),
+                                rel_path: rel.clone(),
+                            });
+                        } else if ext_state.modified_at > lib_state.modified_at {
+                            actions.push(SyncAction {
+                                kind: "copy-to-library".to_string(),
+                                rel_path: rel.clone(),
+                            });
+                        } else {
+                            conflicts.push(rel.clone());
+                        }
+                    }
+                    _ => unreachable!(),
+                }
+            }
+        }
+    }
+
+    // 外部独有的文件：镜像模式删除，双向模式回拷到库，单向模式不动
+    for rel in external.keys() {
+        if library.contains_key(rel) {
+            continue;
+        }
+        match mode {
+            "mirror" => actions.push(SyncAction {
+                kind: "delete-external".to_string(),
+                rel_path: rel.clone(),
+            }),
+            "two-way" => actions.push(SyncAction {
+                kind: "copy-to-library".to_string(),
+                rel_path: rel.clone(),
+            }),
+            _ => {}
+        }
+    }
+
+    Ok((actions, conflicts))
+}
+
+/// 执行同步（dry_run 为 true 时只返回计划）。
+/// mode："mirror" | "one-way" | "two-way"
+#[tauri::command]
+pub async fn sync_folder(
+    library_folder: String,
+    external_dir: String,
+    mode: String,
+    dry_run: Option<bool>,
+    app: tauri::AppHandle,
+) -> Result<SyncReport, String> {
+    if !matches!(mode.as_str(), "mirror" | "one-way" | "two-way") {
+        return Err(format!("未知的同步模式: {}", mode));
+    }
+    let dry_run = dry_run.unwrap_or(false);
+
+    if SYNC_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("同步任务已在运行中".to_string());
+    }
+
+    let result = tokio::task::spawn_blocking(move || {
+        let lib_root = PathBuf::from(&library_folder);
+        let ext_root = PathBuf::from(&external_dir);
+        if !lib_root.is_dir() {
+            return Err(format!("库文件夹不存在: {}", library_folder));
+        }
+        // 外部目录不存在时自动创建（首次同步到空 U 盘的常见情况）
+        fs::create_dir_all(&ext_root).map_err(|e| format!("创建外部目录失败: {}", e))?;
+
+        let mut library = BTreeMap::new();
+        let mut external = BTreeMap::new();
+        collect_files(&lib_root, "", &mut library)?;
+        collect_files(&ext_root, "", &mut external)?;
+
+        let (actions, conflicts) = build_plan(&mode, &library, &external)?;
+
+        let mut report = SyncReport {
+            mode: mode.clone(),
+            dry_run,
+            actions: actions.clone(),
+            conflicts,
+            copied: 0,
+            deleted: 0,
+            failed: 0,
+        };
+        if dry_run {
+            return Ok(report);
+        }
+
+        let total = actions.len();
+        for (i, action) in actions.iter().enumerate() {
+            let ok = match action.kind.as_str() {
+                "copy-to-external" => copy_rel(&lib_root, &ext_root, &action.rel_path).is_ok(),
+                "copy-to-library" => copy_rel(&ext_root, &lib_root, &action.rel_path).is_ok(),
+                "delete-external" => fs::remove_file(ext_root.join(&action.rel_path)).is_ok(),
+                _ => false,
+            };
+            if ok {
+                if action.kind == "delete-external" {
+                    report.deleted += 1;
+                } else {
+                    report.copied += 1;
+                }
+            } else {
+                report.failed += 1;
+            }
+
+            if (i + 1).is_multiple_of(50) || i + 1 == total {
+                let _ = app.emit(
+                    "folder-sync-progress",
+                    SyncProgress {
+                        processed: i + 1,
+                        total,
+                    },
+                );
+            }
+        }
+
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("同步任务失败: {}", e));
+
+    SYNC_RUNNING.store(false, Ordering::SeqCst);
+    result?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(dir: &Path, rel: &str, content: &[u8], mtime_hint: i64) -> FileState {
+        let path = dir.join(rel);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&path, content).unwrap();
+        FileState {
+            abs_path: path,
+            size: content.len() as u64,
+            modified_at: mtime_hint,
+        }
+    }
+
+    #[test]
+    fn test_mirror_plan_deletes_external_extras() {
+        let tmp = std::env::temp_dir().join("aurora_sync_test_mirror");
+        let _ = fs::remove_dir_all(&tmp);
+        let mut library = BTreeMap::new();
+        let mut external = BTreeMap::new();
+        library.insert("a.png".to_string(), state(&tmp.join("lib"), "a.png", b"aaa", 10));
+        external.insert("b.png".to_string(), state(&tmp.join("ext"), "b.png", b"bbb", 10));
+
+        let (actions, conflicts) = build_plan("mirror", &library, &external).unwrap();
+        assert!(conflicts.is_empty());
+        assert_eq!(actions.len(), 2);
+        assert!(actions.iter().any(|a| a.kind == "copy-to-external" && a.rel_path == "a.png"));
+        assert!(actions.iter().any(|a| a.kind == "delete-external" && a.rel_path == "b.png"));
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn test_two_way_newer_wins_and_same_mtime_conflicts() {
+        let tmp = std::env::temp_dir().join("aurora_sync_test_twoway");
+        let _ = fs::remove_dir_all(&tmp);
+        let mut library = BTreeMap::new();
+        let mut external = BTreeMap::new();
+        // 外部更新 → 回拷到库
+        library.insert("a.png".to_string(), state(&tmp.join("lib"), "a.png", b"old", 10));
+        external.insert("a.png".to_string(), state(&tmp.join("ext"), "a.png", b"new!", 20));
+        // 内容不同但修改时间相同 → 冲突
+        library.insert("b.png".to_string(), state(&tmp.join("lib"), "b.png", b"xx", 30));
+        external.insert("b.png".to_string(), state(&tmp.join("ext"), "b.png", b"yy", 30));
+
+        let (actions, conflicts) = build_plan("two-way", &library, &external).unwrap();
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].kind, "copy-to-library");
+        assert_eq!(conflicts, vec!["b.png".to_string()]);
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn test_identical_content_produces_no_actions() {
+        let tmp = std::env::temp_dir().join("aurora_sync_test_same");
+        let _ = fs::remove_dir_all(&tmp);
+        let mut library = BTreeMap::new();
+        let mut external = BTreeMap::new();
+        // 修改时间不同但内容一致 → 不需要任何动作
+        library.insert("a.png".to_string(), state(&tmp.join("lib"), "a.png", b"same", 10));
+        external.insert("a.png".to_string(), state(&tmp.join("ext"), "a.png", b"same", 99));
+
+        let (actions, conflicts) = build_plan("one-way", &library, &external).unwrap();
+        assert!(actions.is_empty());
+        assert!(conflicts.is_empty());
+        let _ = fs::remove_dir_all(&tmp);
+    }
+}
@@ -0,0 +1,126 @@
+//! 文件属性辅助：隐藏文件判定与云盘占位文件检测。
+//!
+//! 过去只把点开头的名字当隐藏文件，Windows 上靠 FILE_ATTRIBUTE_HIDDEN /
+//! SYSTEM 标记的隐藏项（桌面.ini、回收站残留等）照扫不误。这里统一判定，
+//! 并提供"显示隐藏文件"开关。
+//!
+//! 另外识别 OneDrive 等云盘的离线占位文件（FILE_ATTRIBUTE_OFFLINE /
+//! RECALL_ON_DATA_ACCESS）：这类文件一读内容就会触发下载，扫描时只
+//! 记录元数据、跳过读文件头，避免把几十 GB 云端数据拉回本地。
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 是否在扫描中包含隐藏文件（设置页开关，默认不包含）
+static SHOW_HIDDEN: AtomicBool = AtomicBool::new(false);
+
+/// Windows 下读文件属性位（失败返回 None）
+#[cfg(windows)]
+fn windows_attributes(path: &Path) -> Option<u32> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetFileAttributesW;
+
+    const INVALID_FILE_ATTRIBUTES: u32 = u32::MAX;
+    let wide: Vec<u16> = path
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let attrs = unsafe { GetFileAttributesW(wide.as_ptr()) };
+    if attrs == INVALID_FILE_ATTRIBUTES {
+        None
+    } else {
+        Some(attrs)
+    }
+}
+
+/// 条目是否是隐藏项：点开头的名字，或 Windows 的 HIDDEN / SYSTEM 属性
+pub fn is_hidden_entry(name: &str, path: &Path) -> bool {
+    if name.starts_with('.') {
+        return true;
+    }
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::Storage::FileSystem::{
+            FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_SYSTEM,
+        };
+        if let Some(attrs) = windows_attributes(path) {
+            return attrs & (FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM) != 0;
+        }
+    }
+    #[cfg(not(windows))]
+    let _ = path;
+    false
+}
+
+/// 扫描时是否应该跳过该条目（结合"显示隐藏文件"开关）
+pub fn should_hide(name: &str, path: &Path) -> bool {
+    !SHOW_HIDDEN.load(Ordering::SeqCst) && is_hidden_entry(name, path)
+}
+
+/// 是否是云盘离线占位文件（OneDrive "仅联机"等）。
+/// 读这类文件的内容会触发云端下载，扫描只应记录元数据
+pub fn is_offline_placeholder(path: &Path) -> bool {
+    #[cfg(windows)]
+    {
+        // OFFLINE(0x1000)、RECALL_ON_OPEN(0x40000)、RECALL_ON_DATA_ACCESS(0x400000)
+        const PLACEHOLDER_MASK: u32 = 0x1000 | 0x40000 | 0x400000;
+        windows_attributes(path)
+            .map(|attrs| attrs & PLACEHOLDER_MASK != 0)
+            .unwrap_or(false)
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = path;
+        false
+    }
+}
+
+/// 文件的可用性标记（FileNode.availability）：
+/// "online-only" 为云盘占位，其余本地文件为 "local"
+pub fn availability_of(path: &Path) -> Option<String> {
+    if is_offline_placeholder(path) {
+        Some("online-only".to_string())
+    } else {
+        Some("local".to_string())
+    }
+}
+
+/// 设置是否在扫描中包含隐藏文件（改完需要重新扫描生效）
+#[tauri::command]
+pub fn set_show_hidden_files(show: bool) {
+    SHOW_HIDDEN.store(show, Ordering::SeqCst);
+}
+
+#[tauri::command]
+pub fn get_show_hidden_files() -> bool {
+    SHOW_HIDDEN.load(Ordering::SeqCst)
+}
+
+/// 显式把云盘占位文件拉回本地（用户在界面上主动请求水合）。
+/// 顺序读完整个文件即可触发 OneDrive / Dropbox 下载；返回完成后的可用性
+#[tauri::command]
+pub async fn materialize_file(
+    file_id: String,
+    pool: tauri::State<'_, crate::db::AppDbPool>,
+) -> Result<String, String> {
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let path = {
+            let conn = pool.get_connection();
+            crate::db::file_index::get_entry_by_id(&conn, &file_id)
+                .map_err(|e| e.to_string())?
+                .ok_or("文件不在索引中")?
+                .path
+        };
+        if is_offline_placeholder(Path::new(&path)) {
+            let mut file =
+                std::fs::File::open(&path).map_err(|e| format!("打开文件失败: {}", e))?;
+            std::io::copy(&mut file, &mut std::io::sink())
+                .map_err(|e| format!("拉取云端内容失败: {}", e))?;
+        }
+        Ok(availability_of(Path::new(&path)).unwrap_or_else(|| "local".to_string()))
+    })
+    .await
+    .map_err(|e| format!("水合任务失败: {}", e))?
+}
@@ -0,0 +1,129 @@
+//! 视觉定位：文字搜索命中一张图后，告诉用户“图里哪一块最像这段文字”。
+//!
+//! 这里的 CLIP 是 ONNX 导出的池化模型，推理只吐全局向量，拿不到
+//! patch 级 token，所以用滑窗近似：把图按 [`GRID`]×[`GRID`] 网格切开，
+//! 以 [`WINDOW`]×[`WINDOW`] 格为窗口、步长一格滑动，每个窗口单独
+//! 编码后与文本向量比余弦相似度，得到一张粗热力网格。窗口数固定
+//! （4×4 网格 + 2×2 窗口 = 9 次编码），单次调用的推理开销可控。
+
+use std::io::Cursor;
+
+use serde::Serialize;
+
+use crate::clip;
+
+/// 网格密度（每边格数）
+const GRID: u32 = 4;
+/// 滑窗边长（格）
+const WINDOW: u32 = 2;
+/// 编码前 tile 先缩到这个边长，省掉大图的重复解码预处理
+const TILE_EDGE: u32 = 256;
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GroundingRegion {
+    /// 区域位置与大小，0-100 的百分比（与人脸框/批注同一套约定）
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+    /// 与查询的余弦相似度
+    pub score: f32,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GroundingResult {
+    /// 全图与查询的相似度，作为各区域分数的基准
+    pub global_score: f32,
+    /// 所有滑窗区域，按相似度降序
+    pub regions: Vec<GroundingRegion>,
+}
+
+/// 给定文字查询和图片，返回最匹配查询的区域热力网格
+#[tauri::command]
+pub async fn ground_text_in_image(
+    file_path: String,
+    query: String,
+) -> Result<GroundingResult, String> {
+    let manager = clip::get_clip_manager()
+        .await
+        .ok_or("CLIP manager not initialized")?;
+
+    // 确保模型已加载（与 clip_search_by_text 相同的升级锁模式）
+    {
+        let guard = manager.read().await;
+        if !guard.is_model_loaded() {
+            drop(guard);
+            let mut guard = manager.write().await;
+            if !guard.is_model_loaded() {
+                guard.load_model().await.map_err(|e| format!("Failed to load model: {}", e))?;
+            }
+        }
+    }
+
+    // 解码与切窗在阻塞线程做，推理前就把 PNG 字节准备好
+    let tiles = tokio::task::spawn_blocking(move || -> Result<Vec<(GroundingRegion, Vec<u8>)>, String> {
+        let img = image::open(&file_path).map_err(|e| format!("打开图片失败: {}", e))?;
+        let (iw, ih) = (img.width(), img.height());
+        if iw < GRID || ih < GRID {
+            return Err("图片太小，无法切分区域".to_string());
+        }
+
+        let mut tiles = Vec::new();
+        let mut push_tile = |px: u32, py: u32, pw: u32, ph: u32| -> Result<(), String> {
+            let tile = img.crop_imm(px, py, pw, ph).thumbnail(TILE_EDGE, TILE_EDGE);
+            let mut buf = Vec::new();
+            tile.write_to(&mut Cursor::new(&mut buf), image::ImageFormat::Png)
+                .map_err(|e| format!("编码区域失败: {}", e))?;
+            tiles.push((
+                GroundingRegion {
+                    x: px as f32 / iw as f32 * 100.0,
+                    y: py as f32 / ih as f32 * 100.0,
+                    w: pw as f32 / iw as f32 * 100.0,
+                    h: ph as f32 / ih as f32 * 100.0,
+                    score: 0.0,
+                },
+                buf,
+            ));
+            Ok(())
+        };
+
+        // 第一个是整图，作为 global_score 的基准
+        push_tile(0, 0, iw, ih)?;
+        for gy in 0..=(GRID - WINDOW) {
+            for gx in 0..=(GRID - WINDOW) {
+                let px = gx * iw / GRID;
+                let py = gy * ih / GRID;
+                let pw = ((gx + WINDOW) * iw / GRID).min(iw) - px;
+                let ph = ((gy + WINDOW) * ih / GRID).min(ih) - py;
+                push_tile(px, py, pw, ph)?;
+            }
+        }
+        Ok(tiles)
+    })
+    .await
+    .map_err(|e| format!("切分区域任务失败: {}", e))??;
+
+    let mut guard = manager.write().await;
+    let model = guard.model_mut().ok_or("CLIP model not available")?;
+    let text_embedding = model.encode_text(&query)?;
+
+    let mut global_score = 0.0f32;
+    let mut regions = Vec::with_capacity(tiles.len().saturating_sub(1));
+    for (i, (mut region, bytes)) in tiles.into_iter().enumerate() {
+        let tile_embedding = model.encode_image_bytes(&bytes)?;
+        let score = clip::model::cosine_similarity(&text_embedding, &tile_embedding);
+        if i == 0 {
+            global_score = score;
+        } else {
+            region.score = score;
+            regions.push(region);
+        }
+    }
+    regions.sort_unstable_by(|a, b| {
+        b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    Ok(GroundingResult { global_score, regions })
+}
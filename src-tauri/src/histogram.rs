@@ -0,0 +1,131 @@
+//! 直方图与图像统计：给查看器的信息面板算 RGB / 亮度直方图、
+//! 高光 / 阴影溢出占比和平均曝光。
+//!
+//! 为了响应速度，统计在缩小到最长边 1024 的解码图上做——
+//! 直方图形态对缩放不敏感，面板展示足够准。
+
+use std::path::Path;
+
+use serde::Serialize;
+
+/// 统计用的缩放上限（最长边）
+const MAX_SAMPLE_DIM: u32 = 1024;
+
+/// 溢出判定阈值：低于 LOW 记为阴影溢出，高于 HIGH 记为高光溢出
+const CLIP_LOW: u8 = 2;
+const CLIP_HIGH: u8 = 253;
+
+/// 直方图与统计结果
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageHistogram {
+    /// 各通道 256 档直方图
+    pub red: Vec<u32>,
+    pub green: Vec<u32>,
+    pub blue: Vec<u32>,
+    /// 亮度（Rec.709 加权）直方图
+    pub luminance: Vec<u32>,
+    /// 阴影溢出像素占比，0.0 - 1.0
+    pub shadow_clip_ratio: f64,
+    /// 高光溢出像素占比，0.0 - 1.0
+    pub highlight_clip_ratio: f64,
+    /// 平均亮度，0.0 - 255.0
+    pub mean_luminance: f64,
+    /// 平均曝光（以中灰 118 为 0 的 EV 偏移）
+    pub exposure_ev: f64,
+    /// 参与统计的像素数（缩放后）
+    pub sample_pixels: u32,
+}
+
+/// 在已解码的 RGB 图上算直方图与统计
+fn compute(rgb: &image::RgbImage) -> ImageHistogram {
+    let mut red = vec![0u32; 256];
+    let mut green = vec![0u32; 256];
+    let mut blue = vec![0u32; 256];
+    let mut luminance = vec![0u32; 256];
+    let mut shadow_clipped = 0u64;
+    let mut highlight_clipped = 0u64;
+    let mut luma_sum = 0.0f64;
+
+    for pixel in rgb.pixels() {
+        let [r, g, b] = pixel.0;
+        red[r as usize] += 1;
+        green[g as usize] += 1;
+        blue[b as usize] += 1;
+        // Rec.709 亮度加权
+        let luma = 0.2126 * r as f64 + 0.7152 * g as f64 + 0.0722 * b as f64;
+        luminance[(luma.round() as usize).min(255)] += 1;
+        luma_sum += luma;
+        if r <= CLIP_LOW && g <= CLIP_LOW && b <= CLIP_LOW {
+            shadow_clipped += 1;
+        }
+        if r >= CLIP_HIGH && g >= CLIP_HIGH && b >= CLIP_HIGH {
+            highlight_clipped += 1;
+        }
+    }
+
+    let total = (rgb.width() as u64 * rgb.height() as u64).max(1);
+    let mean = luma_sum / total as f64;
+    // 以摄影中灰（sRGB 约 118）为基准换算 EV 偏移
+    let exposure_ev = if mean > 0.0 {
+        (mean / 118.0).log2()
+    } else {
+        f64::NEG_INFINITY.max(-10.0)
+    };
+
+    ImageHistogram {
+        red,
+        green,
+        blue,
+        luminance,
+        shadow_clip_ratio: shadow_clipped as f64 / total as f64,
+        highlight_clip_ratio: highlight_clipped as f64 / total as f64,
+        mean_luminance: mean,
+        exposure_ev,
+        sample_pixels: rgb.width() * rgb.height(),
+    }
+}
+
+/// 计算图片的直方图与曝光统计（在缩小图上采样）
+#[tauri::command]
+pub async fn get_image_histogram(file_path: String) -> Result<ImageHistogram, String> {
+    if !Path::new(&file_path).is_file() {
+        return Err(format!("文件不存在: {}", file_path));
+    }
+    tokio::task::spawn_blocking(move || {
+        let img = image::open(&file_path).map_err(|e| format!("解码失败: {}", e))?;
+        let rgb = if img.width().max(img.height()) > MAX_SAMPLE_DIM {
+            img.thumbnail(MAX_SAMPLE_DIM, MAX_SAMPLE_DIM).to_rgb8()
+        } else {
+            img.to_rgb8()
+        };
+        Ok(compute(&rgb))
+    })
+    .await
+    .map_err(|e| format!("统计任务失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_uniform_gray() {
+        let rgb = image::RgbImage::from_pixel(16, 16, image::Rgb([118, 118, 118]));
+        let hist = compute(&rgb);
+        assert_eq!(hist.red[118], 256);
+        assert_eq!(hist.sample_pixels, 256);
+        assert!(hist.exposure_ev.abs() < 0.05);
+        assert_eq!(hist.shadow_clip_ratio, 0.0);
+        assert_eq!(hist.highlight_clip_ratio, 0.0);
+    }
+
+    #[test]
+    fn test_histogram_clipping() {
+        let mut rgb = image::RgbImage::from_pixel(2, 2, image::Rgb([0, 0, 0]));
+        rgb.put_pixel(0, 0, image::Rgb([255, 255, 255]));
+        let hist = compute(&rgb);
+        assert!((hist.shadow_clip_ratio - 0.75).abs() < 1e-9);
+        assert!((hist.highlight_clip_ratio - 0.25).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,134 @@
+//! 后端字符串的本地化层：托盘菜单、系统通知标题这类"不经前端渲染、
+//! 直接进系统 UI"的文字按设置的语言取值；错误消息通过错误码 +
+//! 本地化文案的形式返回，前端可以只认码做自己的展示。
+//!
+//! 语言存应用数据目录的 locale.txt（启动早于数据库可用，不进库设置）。
+//! 目前支持 "zh-CN" 与 "en"，缺词条时回落中文——与历史行为一致。
+
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tauri::Manager;
+
+/// 当前语言（"zh-CN" | "en"）
+static LOCALE: Lazy<RwLock<String>> = Lazy::new(|| RwLock::new("zh-CN".to_string()));
+
+/// 词条表：key → (中文, 英文)。托盘 / 通知 / 通用错误都从这里取
+const STRINGS: &[(&str, &str, &str)] = &[
+    ("tray.show", "显示窗口", "Show Window"),
+    ("tray.quit", "退出", "Quit"),
+    ("tray.app-name", "Aurora Gallery", "Aurora Gallery"),
+    ("error.file-not-found", "文件不存在", "File not found"),
+    ("error.not-indexed", "文件不在索引中", "File is not in the library index"),
+    ("error.task-running", "已有同类任务在运行", "A task of this kind is already running"),
+    ("error.db", "数据库操作失败", "Database operation failed"),
+    ("error.decode", "图片解码失败", "Failed to decode image"),
+    ("error.io", "文件读写失败", "File I/O failed"),
+    ("error.unsupported", "不支持的操作或格式", "Unsupported operation or format"),
+];
+
+/// 取词条（当前语言缺失时回落中文；未知 key 原样返回）
+pub fn t(key: &str) -> &'static str {
+    let en = LOCALE.read().unwrap().as_str() == "en";
+    for (k, zh, en_text) in STRINGS {
+        if *k == key {
+            return if en { en_text } else { zh };
+        }
+    }
+    // 词条表里没有的 key 属于编码错误，开发期直接暴露出来
+    "??"
+}
+
+/// 带错误码的本地化错误串，格式 "[code] 本地化消息: detail"。
+/// 前端用 `[` 开头判断可解析出码
+pub fn err(code: &str, detail: &str) -> String {
+    let message = t(&format!("error.{}", code));
+    if detail.is_empty() {
+        format!("[{}] {}", code, message)
+    } else {
+        format!("[{}] {}: {}", code, message, detail)
+    }
+}
+
+fn locale_path(app: &tauri::AppHandle) -> Option<std::path::PathBuf> {
+    let dir = app.path().app_data_dir().ok()?;
+    let _ = std::fs::create_dir_all(&dir);
+    Some(dir.join("locale.txt"))
+}
+
+/// 启动时从磁盘恢复语言设置（setup 里调用）
+pub fn load_locale(app: &tauri::AppHandle) {
+    if let Some(path) = locale_path(app) {
+        if let Ok(saved) = std::fs::read_to_string(&path) {
+            let saved = saved.trim();
+            if matches!(saved, "zh-CN" | "en") {
+                *LOCALE.write().unwrap() = saved.to_string();
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LocaleStrings {
+    pub locale: String,
+    /// 全量词条，前端需要展示后端错误码对应文案时查这里
+    pub strings: std::collections::HashMap<String, String>,
+}
+
+/// 设置后端语言并持久化；托盘菜单文字立即切换
+#[tauri::command]
+pub fn set_locale(locale: String, app: tauri::AppHandle) -> Result<(), String> {
+    if !matches!(locale.as_str(), "zh-CN" | "en") {
+        return Err(format!("不支持的语言: {}（可选 zh-CN / en）", locale));
+    }
+    *LOCALE.write().unwrap() = locale.clone();
+    if let Some(path) = locale_path(&app) {
+        let _ = std::fs::write(&path, &locale);
+    }
+
+    // 重建托盘菜单，让"显示窗口 / 退出"换语言
+    if let Some(tray) = app.state::<Option<tauri::tray::TrayIcon>>().inner() {
+        let show = tauri::menu::MenuItem::with_id(&app, "show", t("tray.show"), true, None::<&str>)
+            .map_err(|e| e.to_string())?;
+        let quit = tauri::menu::MenuItem::with_id(&app, "quit", t("tray.quit"), true, None::<&str>)
+            .map_err(|e| e.to_string())?;
+        let menu = tauri::menu::Menu::with_items(&app, &[&show, &quit]).map_err(|e| e.to_string())?;
+        tray.set_menu(Some(menu)).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// 查询当前语言与全量词条
+#[tauri::command]
+pub fn get_locale_strings() -> LocaleStrings {
+    let locale = LOCALE.read().unwrap().clone();
+    let en = locale == "en";
+    LocaleStrings {
+        locale,
+        strings: STRINGS
+            .iter()
+            .map(|(k, zh, en_text)| {
+                (k.to_string(), if en { en_text.to_string() } else { zh.to_string() })
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_and_error_format() {
+        *LOCALE.write().unwrap() = "zh-CN".to_string();
+        assert_eq!(t("tray.quit"), "退出");
+        assert_eq!(err("file-not-found", "/a/b.png"), "[file-not-found] 文件不存在: /a/b.png");
+
+        *LOCALE.write().unwrap() = "en".to_string();
+        assert_eq!(t("tray.quit"), "Quit");
+        assert_eq!(err("db", ""), "[db] Database operation failed");
+        *LOCALE.write().unwrap() = "zh-CN".to_string();
+    }
+}
@@ -0,0 +1,283 @@
+//! 网页采集入库：本地 API 的 `/api/ingest` 端点。
+//!
+//! 浏览器扩展把图片（URL 或 base64 字节）连同页面信息（标题、来源地址、标签）
+//! POST 过来，保存到当前库配置的收件箱文件夹并完整入索引：
+//! file_index 条目 + 尺寸头信息、file_metadata（标签 / 来源 / 描述）、变更日志，
+//! 之后发 "ingest-complete" 事件让前端刷新。
+//! 收件箱路径存在库设置 `ingest_inbox`（随 metadata.db 走，各库独立）。
+
+use std::path::Path;
+
+use base64::{engine::general_purpose, Engine as _};
+use serde::Deserialize;
+use tauri::{Emitter, Manager};
+
+use crate::api_server::{Request, Response};
+use crate::db::{self, AppDbPool};
+
+/// 采集请求体：url 与 bytesBase64 二选一，页面信息可选
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IngestPayload {
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    bytes_base64: Option<String>,
+    #[serde(default)]
+    filename: Option<String>,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    source_url: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+/// 设置当前库的采集收件箱文件夹
+#[tauri::command]
+pub fn set_ingest_inbox(path: String, pool: tauri::State<AppDbPool>) -> Result<(), String> {
+    let normalized = db::normalize_path(&path);
+    if !Path::new(&normalized).is_dir() {
+        return Err(format!("收件箱文件夹不存在: {}", normalized));
+    }
+    let conn = pool.get_connection();
+    db::set_library_setting(&conn, "ingest_inbox", &normalized).map_err(|e| e.to_string())
+}
+
+/// 查询当前库的采集收件箱文件夹
+#[tauri::command]
+pub fn get_ingest_inbox(pool: tauri::State<AppDbPool>) -> Result<Option<String>, String> {
+    let conn = pool.get_connection();
+    db::get_library_setting(&conn, "ingest_inbox").map_err(|e| e.to_string())
+}
+
+/// 去掉文件名里的路径分隔符和控制字符，防止目录穿越
+fn sanitize_filename(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// 从字节内容猜扩展名，猜不出时回退 jpg
+fn extension_from_bytes(bytes: &[u8]) -> &'static str {
+    match image::guess_format(bytes) {
+        Ok(image::ImageFormat::Png) => "png",
+        Ok(image::ImageFormat::Gif) => "gif",
+        Ok(image::ImageFormat::WebP) => "webp",
+        Ok(image::ImageFormat::Bmp) => "bmp",
+        Ok(image::ImageFormat::Tiff) => "tiff",
+        _ => "jpg",
+    }
+}
+
+/// 决定保存文件名：优先请求给的 filename，其次 URL 末段，最后按内容猜格式生成
+fn pick_filename(payload: &IngestPayload, bytes: &[u8]) -> String {
+    if let Some(name) = payload.filename.as_deref().filter(|n| !n.trim().is_empty()) {
+        let name = sanitize_filename(name.trim());
+        if Path::new(&name).extension().is_some() {
+            return name;
+        }
+        return format!("{}.{}", name, extension_from_bytes(bytes));
+    }
+    if let Some(url) = payload.url.as_deref() {
+        let last = url
+            .split('?')
+            .next()
+            .unwrap_or("")
+            .rsplit('/')
+            .next()
+            .unwrap_or("");
+        let last = urlencoding::decode(last)
+            .map(|c| c.into_owned())
+            .unwrap_or_else(|_| last.to_string());
+        let last = sanitize_filename(&last);
+        if !last.is_empty()
+            && Path::new(&last)
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| crate::is_supported_image(&e.to_lowercase()))
+                .unwrap_or(false)
+        {
+            return last;
+        }
+    }
+    format!(
+        "clip_{}.{}",
+        chrono::Local::now().format("%Y%m%d_%H%M%S"),
+        extension_from_bytes(bytes)
+    )
+}
+
+/// 取得图片字节：优先 base64，否则下载 URL
+async fn fetch_bytes(payload: &IngestPayload) -> Result<Vec<u8>, String> {
+    if let Some(b64) = payload.bytes_base64.as_deref() {
+        return general_purpose::STANDARD
+            .decode(b64)
+            .map_err(|e| format!("base64 解码失败: {}", e));
+    }
+    let Some(url) = payload.url.as_deref() else {
+        return Err("需要 url 或 bytesBase64".to_string());
+    };
+    let resp = reqwest::get(url)
+        .await
+        .map_err(|e| format!("下载失败: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("下载失败: HTTP {}", resp.status()));
+    }
+    resp.bytes()
+        .await
+        .map(|b| b.to_vec())
+        .map_err(|e| format!("读取响应失败: {}", e))
+}
+
+/// 保存到收件箱并完整入索引，返回保存后的路径
+async fn ingest(payload: IngestPayload, app: &tauri::AppHandle) -> Result<String, String> {
+    let inbox = {
+        let pool = app.state::<AppDbPool>();
+        let conn = pool.get_connection();
+        db::get_library_setting(&conn, "ingest_inbox")
+            .map_err(|e| e.to_string())?
+            .ok_or("尚未配置采集收件箱文件夹")?
+    };
+    if !Path::new(&inbox).is_dir() {
+        return Err(format!("收件箱文件夹不存在: {}", inbox));
+    }
+
+    let bytes = fetch_bytes(&payload).await?;
+    let filename = pick_filename(&payload, &bytes);
+    let ext = Path::new(&filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    if !crate::is_supported_image(&ext) {
+        return Err(format!("不支持的图片格式: {}", ext));
+    }
+
+    let dest = crate::generate_unique_file_path(&format!("{}/{}", inbox, filename));
+    std::fs::write(&dest, &bytes).map_err(|e| format!("写入文件失败: {}", e))?;
+
+    // 入索引（含头信息），写元数据，记变更日志
+    let normalized = db::normalize_path(&dest);
+    let file_id = db::generate_id(&normalized);
+    {
+        let pool = app.state::<AppDbPool>();
+        let mut conn = pool.get_connection();
+        let md = std::fs::metadata(&dest).ok();
+        let header = crate::probe_image_header(&normalized);
+        let entry = db::file_index::FileIndexEntry {
+            file_id: file_id.clone(),
+            parent_id: Some(db::generate_id(&inbox)),
+            path: normalized.clone(),
+            name: Path::new(&normalized)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("")
+                .to_string(),
+            file_type: "Image".to_string(),
+            size: md.as_ref().map(|m| m.len()).unwrap_or(0),
+            width: None,
+            height: None,
+            format: Some(ext),
+            bit_depth: header.bit_depth,
+            color_space: header.color_space,
+            has_alpha: header.has_alpha,
+            is_animated: header.is_animated,
+            created_at: chrono::Utc::now().timestamp(),
+            modified_at: md
+                .as_ref()
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0),
+        };
+        let _ = db::file_index::batch_upsert(&mut conn, &[entry]);
+
+        let has_meta = !payload.tags.is_empty()
+            || payload.source_url.is_some()
+            || payload.title.is_some()
+            || payload.description.is_some();
+        if has_meta {
+            let metadata = db::file_metadata::FileMetadata {
+                file_id: file_id.clone(),
+                path: normalized.clone(),
+                tags: if payload.tags.is_empty() {
+                    None
+                } else {
+                    Some(serde_json::json!(payload.tags))
+                },
+                description: payload.description.clone(),
+                source_url: payload.source_url.clone(),
+                source_title: payload.title.clone(),
+                source_author: None,
+                ai_data: None,
+                category: None,
+                rating: None,
+                notes: None,
+                sensitive: None,
+                updated_at: Some(chrono::Utc::now().timestamp()),
+            };
+            let _ = db::file_metadata::upsert_file_metadata(&conn, &metadata);
+        }
+
+        let _ = db::activity_log::record(
+            &conn,
+            "add",
+            &normalized,
+            payload.source_url.as_deref(),
+            "background",
+        );
+    }
+
+    let _ = app.emit("ingest-complete", normalized.clone());
+    Ok(normalized)
+}
+
+/// `/api/ingest` 的 HTTP 入口（鉴权已在服务端统一处理）
+pub async fn handle_ingest(req: &Request, app: &tauri::AppHandle) -> Response {
+    let payload: IngestPayload = match serde_json::from_slice(&req.body) {
+        Ok(p) => p,
+        Err(e) => return Response::error(400, &format!("解析请求体失败: {}", e)),
+    };
+    match ingest(payload, app).await {
+        Ok(path) => Response::json(serde_json::json!({
+            "path": path.clone(),
+            "fileId": db::generate_id(&path),
+        })),
+        Err(e) => Response::error(400, &e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_filename_strips_separators() {
+        assert_eq!(sanitize_filename("a/b\\c:d.png"), "a_b_c_d.png");
+        assert_eq!(sanitize_filename("normal.webp"), "normal.webp");
+    }
+
+    #[test]
+    fn test_pick_filename_prefers_explicit_then_url() {
+        let png_magic = b"\x89PNG\r\n\x1a\n";
+        let mut payload = IngestPayload {
+            url: Some("https://example.com/images/art%20work.png?v=2".to_string()),
+            bytes_base64: None,
+            filename: None,
+            title: None,
+            source_url: None,
+            tags: Vec::new(),
+            description: None,
+        };
+        assert_eq!(pick_filename(&payload, png_magic), "art work.png");
+        payload.filename = Some("custom".to_string());
+        assert_eq!(pick_filename(&payload, png_magic), "custom.png");
+    }
+}
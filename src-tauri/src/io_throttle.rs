@@ -0,0 +1,159 @@
+//! 扫描 / 缩略图任务的 I/O 限速与低优先级。
+//!
+//! 两层手段配合：
+//! 1. 令牌桶限速（字节 / 秒与操作 / 秒双配额），后台批量读盘的任务在
+//!    每次磁盘访问前扣配额，超了就睡到配额恢复，把吞吐压在设定值以内；
+//! 2. 平台低优先级 I/O 提示（Windows 后台模式线程、Linux idle 调度类、
+//!    macOS IOPOL_THROTTLE），即使没开限速，系统也会让前台 I/O 优先。
+//!
+//! 默认不限速（0 表示不限），由设置页通过 [`set_io_throttle_settings`] 下发。
+
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+/// 限速设置（0 表示该项不限；默认全部关闭）
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct IoThrottleSettings {
+    pub enabled: bool,
+    /// 后台任务读盘吞吐上限，字节 / 秒
+    pub max_bytes_per_sec: u64,
+    /// 后台任务磁盘操作上限，次 / 秒
+    pub max_ops_per_sec: u32,
+}
+
+static SETTINGS: Lazy<RwLock<IoThrottleSettings>> =
+    Lazy::new(|| RwLock::new(IoThrottleSettings::default()));
+
+/// 令牌桶状态：允许欠账（令牌为负），欠多少按速率折算成睡眠时间
+struct Bucket {
+    bytes: f64,
+    ops: f64,
+    last_refill: Instant,
+}
+
+static BUCKET: Lazy<Mutex<Bucket>> = Lazy::new(|| {
+    Mutex::new(Bucket {
+        bytes: 0.0,
+        ops: 0.0,
+        last_refill: Instant::now(),
+    })
+});
+
+/// 单次扣配额最多睡这么久，避免配置过低时把任务卡死
+const MAX_SLEEP: Duration = Duration::from_secs(2);
+
+/// 记一次磁盘操作并扣 bytes 字节的配额，超出时阻塞到配额恢复。
+/// 未开启限速时为空操作，开销只有一次读锁
+pub fn throttle(bytes: u64) {
+    let (bytes_rate, ops_rate) = {
+        let settings = SETTINGS.read().unwrap();
+        if !settings.enabled {
+            return;
+        }
+        (
+            settings.max_bytes_per_sec as f64,
+            settings.max_ops_per_sec as f64,
+        )
+    };
+
+    let mut wait = Duration::ZERO;
+    {
+        let mut bucket = BUCKET.lock().unwrap();
+        let elapsed = bucket.last_refill.elapsed().as_secs_f64();
+        bucket.last_refill = Instant::now();
+        // 补充令牌，突发余量最多攒 1 秒
+        if bytes_rate > 0.0 {
+            bucket.bytes = (bucket.bytes + elapsed * bytes_rate).min(bytes_rate);
+            bucket.bytes -= bytes as f64;
+            if bucket.bytes < 0.0 {
+                wait = wait.max(Duration::from_secs_f64(-bucket.bytes / bytes_rate));
+            }
+        }
+        if ops_rate > 0.0 {
+            bucket.ops = (bucket.ops + elapsed * ops_rate).min(ops_rate);
+            bucket.ops -= 1.0;
+            if bucket.ops < 0.0 {
+                wait = wait.max(Duration::from_secs_f64(-bucket.ops / ops_rate));
+            }
+        }
+    }
+    if !wait.is_zero() {
+        std::thread::sleep(wait.min(MAX_SLEEP));
+    }
+}
+
+/// 只记一次操作不记字节（stat / readdir 等元数据访问）
+pub fn throttle_op() {
+    throttle(0);
+}
+
+/// 把当前线程的 I/O 降为后台优先级，系统层面给前台让路。
+/// 各平台尽力而为，失败静默（没有权限或内核太旧不影响功能）
+pub fn lower_io_priority() {
+    #[cfg(windows)]
+    unsafe {
+        use windows_sys::Win32::System::Threading::{
+            GetCurrentThread, SetThreadPriority, THREAD_MODE_BACKGROUND_BEGIN,
+        };
+        SetThreadPriority(GetCurrentThread(), THREAD_MODE_BACKGROUND_BEGIN as i32);
+    }
+    #[cfg(target_os = "linux")]
+    unsafe {
+        // ioprio_set(IOPRIO_WHO_PROCESS, 0, IOPRIO_CLASS_IDLE)：0 表示当前线程
+        const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+        const IOPRIO_CLASS_IDLE: libc::c_int = 3;
+        const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+        libc::syscall(
+            libc::SYS_ioprio_set,
+            IOPRIO_WHO_PROCESS,
+            0,
+            IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT,
+        );
+    }
+    #[cfg(target_os = "macos")]
+    unsafe {
+        // setiopolicy_np(IOPOL_TYPE_DISK, IOPOL_SCOPE_THREAD, IOPOL_THROTTLE)
+        const IOPOL_TYPE_DISK: libc::c_int = 0;
+        const IOPOL_SCOPE_THREAD: libc::c_int = 1;
+        const IOPOL_THROTTLE: libc::c_int = 3;
+        libc::setiopolicy_np(IOPOL_TYPE_DISK, IOPOL_SCOPE_THREAD, IOPOL_THROTTLE);
+    }
+}
+
+/// 下发限速设置，立即对后续磁盘访问生效
+#[tauri::command]
+pub fn set_io_throttle_settings(settings: IoThrottleSettings) -> Result<(), String> {
+    if settings.enabled && settings.max_bytes_per_sec == 0 && settings.max_ops_per_sec == 0 {
+        return Err("开启限速时至少要设置一项配额".to_string());
+    }
+    // 开着限速把字节配额设到 1MB/s 以下基本等于卡死扫描，视为配置错误
+    if settings.max_bytes_per_sec > 0 && settings.max_bytes_per_sec < 1024 * 1024 {
+        return Err("字节配额不能低于 1MB/s".to_string());
+    }
+    *SETTINGS.write().unwrap() = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_io_throttle_settings() -> IoThrottleSettings {
+    SETTINGS.read().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_disabled_is_noop() {
+        // 默认关闭，任意扣配额都不应阻塞
+        let start = Instant::now();
+        for _ in 0..1000 {
+            throttle(10 * 1024 * 1024);
+        }
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,177 @@
+//! 库占用锁：防止两个 Aurora 实例（或经网络共享打开的同一库）同时
+//! 读写一套 SQLite 数据库造成损坏。
+//!
+//! 锁是库目录下的 library.lock 文件，内容为持有者信息（进程 ID、主机名、
+//! 心跳时间）。持有方每 10 秒刷新一次心跳；抢锁时如果文件里的心跳超过
+//! 60 秒没更新就视为陈旧锁（持有进程崩溃 / 断电），允许接管。
+//! 抢锁失败的实例把库置为只读：前端据 [`is_library_read_only`] 禁用写入口，
+//! 切换到被占用的库则直接报错。
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+/// 心跳刷新间隔
+const HEARTBEAT_INTERVAL_SECS: u64 = 10;
+/// 心跳超过这个时长没更新视为陈旧锁，可以接管
+const STALE_AFTER_SECS: i64 = 60;
+
+/// 锁文件内容
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LockInfo {
+    pub pid: u32,
+    pub hostname: String,
+    pub started_at: i64,
+    pub heartbeat_at: i64,
+}
+
+struct HeldLock {
+    path: PathBuf,
+    stop: Arc<AtomicBool>,
+}
+
+static HELD_LOCK: Lazy<Mutex<Option<HeldLock>>> = Lazy::new(|| Mutex::new(None));
+/// 抢锁失败后置位，本实例对当前库只读
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+fn hostname() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+fn lock_path(base_dir: &Path) -> PathBuf {
+    base_dir.join("library.lock")
+}
+
+fn write_lock_file(path: &Path, started_at: i64) -> Result<(), String> {
+    let info = LockInfo {
+        pid: std::process::id(),
+        hostname: hostname(),
+        started_at,
+        heartbeat_at: chrono::Utc::now().timestamp(),
+    };
+    let content = serde_json::to_string(&info).map_err(|e| e.to_string())?;
+    std::fs::write(path, content).map_err(|e| e.to_string())
+}
+
+fn read_lock_file(path: &Path) -> Option<LockInfo> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 尝试为 base_dir 下的库抢锁。
+/// 成功后启动心跳线程并释放之前持有的锁（切库场景）；
+/// 失败时返回持有者信息组成的错误串，调用方决定报错还是转只读
+pub fn acquire(base_dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(base_dir).map_err(|e| e.to_string())?;
+    let path = lock_path(base_dir);
+
+    if let Some(existing) = read_lock_file(&path) {
+        let ours = existing.pid == std::process::id() && existing.hostname == hostname();
+        let stale =
+            chrono::Utc::now().timestamp() - existing.heartbeat_at > STALE_AFTER_SECS;
+        if !ours && !stale {
+            return Err(format!(
+                "库正在被另一个实例使用（{} 上的进程 {}）",
+                existing.hostname, existing.pid
+            ));
+        }
+    }
+
+    let started_at = chrono::Utc::now().timestamp();
+    write_lock_file(&path, started_at)?;
+
+    // 先释放旧锁（切换库时），再登记新锁并启动心跳
+    release();
+    let stop = Arc::new(AtomicBool::new(false));
+    {
+        let path = path.clone();
+        let stop = stop.clone();
+        std::thread::spawn(move || {
+            while !stop.load(Ordering::SeqCst) {
+                std::thread::sleep(std::time::Duration::from_secs(HEARTBEAT_INTERVAL_SECS));
+                if stop.load(Ordering::SeqCst) {
+                    break;
+                }
+                let _ = write_lock_file(&path, started_at);
+            }
+        });
+    }
+    *HELD_LOCK.lock().unwrap() = Some(HeldLock { path, stop });
+    READ_ONLY.store(false, Ordering::SeqCst);
+    Ok(())
+}
+
+/// 释放当前持有的锁（停心跳、删锁文件）。未持有时静默
+pub fn release() {
+    if let Some(held) = HELD_LOCK.lock().unwrap().take() {
+        held.stop.store(true, Ordering::SeqCst);
+        let _ = std::fs::remove_file(&held.path);
+    }
+}
+
+/// 标记本实例对当前库只读（启动时抢锁失败的降级路径）
+pub fn mark_read_only() {
+    READ_ONLY.store(true, Ordering::SeqCst);
+}
+
+/// 查询本实例是否以只读方式打开了当前库
+#[tauri::command]
+pub fn is_library_read_only() -> bool {
+    READ_ONLY.load(Ordering::SeqCst)
+}
+
+/// 查询当前库的锁持有者（没有锁文件时返回 None）
+#[tauri::command]
+pub fn get_library_lock_info(base_dir: String) -> Option<LockInfo> {
+    read_lock_file(&lock_path(Path::new(&base_dir)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 锁状态是进程级单例，两段场景放同一个测试里顺序跑，避免并行互相干扰
+    #[test]
+    fn test_lock_lifecycle() {
+        let dir = std::env::temp_dir().join("aurora_lock_test_rt");
+        let _ = std::fs::remove_dir_all(&dir);
+        acquire(&dir).unwrap();
+        let info = read_lock_file(&lock_path(&dir)).unwrap();
+        assert_eq!(info.pid, std::process::id());
+        release();
+        assert!(!lock_path(&dir).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let dir = std::env::temp_dir().join("aurora_lock_test_stale");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = lock_path(&dir);
+
+        // 别的主机上的新鲜锁 → 拒绝
+        let now = chrono::Utc::now().timestamp();
+        let foreign = LockInfo {
+            pid: 99999,
+            hostname: "other-host".to_string(),
+            started_at: now,
+            heartbeat_at: now,
+        };
+        std::fs::write(&path, serde_json::to_string(&foreign).unwrap()).unwrap();
+        assert!(acquire(&dir).is_err());
+
+        // 心跳过期的锁 → 接管
+        let stale = LockInfo {
+            heartbeat_at: now - STALE_AFTER_SECS - 5,
+            ..foreign
+        };
+        std::fs::write(&path, serde_json::to_string(&stale).unwrap()).unwrap();
+        assert!(acquire(&dir).is_ok());
+        release();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,203 @@
+//! 文件清单导出：把一组文件（通常来自多选或工作台）导出成
+//! CSV / JSON / M3U 风格的列表，给电子表格或其他工具接手。
+//!
+//! 列可选：path / name / size / tags / rating / colors / description。
+//! colors 取色彩库里已提取的主色 hex 值；CSV 里多值列用分号分隔，
+//! JSON 里保持数组。M3U 只按播放列表惯例输出路径，忽略列选择。
+
+use std::io::Write;
+use std::sync::Arc;
+
+use tauri::Manager;
+
+use crate::color_db::ColorDbPool;
+use crate::db::{self, AppDbPool};
+
+/// 可选列（也是 CSV 表头的顺序基准）
+const KNOWN_FIELDS: &[&str] = &["path", "name", "size", "tags", "rating", "colors", "description"];
+
+/// 一行导出数据，列值与 fields 一一对应
+struct ExportRow {
+    values: Vec<serde_json::Value>,
+}
+
+/// CSV 转义：含逗号 / 引号 / 换行的值加引号，内部引号翻倍
+fn csv_escape(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 单元格的 CSV 文本表示：数组用分号拼接，空值为空串
+fn csv_cell(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => csv_escape(s),
+        serde_json::Value::Array(items) => csv_escape(
+            &items
+                .iter()
+                .map(|v| v.as_str().map(|s| s.to_string()).unwrap_or_else(|| v.to_string()))
+                .collect::<Vec<_>>()
+                .join(";"),
+        ),
+        other => other.to_string(),
+    }
+}
+
+fn collect_rows(
+    pool: &AppDbPool,
+    color_pool: &ColorDbPool,
+    file_ids: &[String],
+    fields: &[String],
+) -> Result<Vec<ExportRow>, String> {
+    let conn = pool.get_connection();
+    let color_conn = color_pool.get_connection();
+    let mut rows = Vec::with_capacity(file_ids.len());
+
+    for id in file_ids {
+        let Some(entry) = db::file_index::get_entry_by_id(&conn, id).map_err(|e| e.to_string())?
+        else {
+            continue; // 已不在索引中的静默跳过
+        };
+        let meta = db::file_metadata::get_metadata_by_id(&conn, id).map_err(|e| e.to_string())?;
+
+        let mut values = Vec::with_capacity(fields.len());
+        for field in fields {
+            let value = match field.as_str() {
+                "path" => serde_json::Value::String(entry.path.clone()),
+                "name" => serde_json::Value::String(entry.name.clone()),
+                "size" => serde_json::Value::Number(entry.size.into()),
+                "tags" => meta
+                    .as_ref()
+                    .and_then(|m| m.tags.clone())
+                    .unwrap_or(serde_json::Value::Array(Vec::new())),
+                "rating" => meta
+                    .as_ref()
+                    .and_then(|m| m.rating)
+                    .map(|r| serde_json::Value::Number(r.into()))
+                    .unwrap_or(serde_json::Value::Null),
+                "description" => meta
+                    .as_ref()
+                    .and_then(|m| m.description.clone())
+                    .map(serde_json::Value::String)
+                    .unwrap_or(serde_json::Value::Null),
+                "colors" => {
+                    let json: Option<String> = color_conn
+                        .query_row(
+                            "SELECT colors FROM dominant_colors WHERE file_path = ?1 AND status = 'extracted'",
+                            [&entry.path],
+                            |row| row.get(0),
+                        )
+                        .ok();
+                    let hexes: Vec<serde_json::Value> = json
+                        .and_then(|j| {
+                            serde_json::from_str::<Vec<crate::color_extractor::ColorResult>>(&j).ok()
+                        })
+                        .map(|colors| {
+                            colors
+                                .into_iter()
+                                .map(|c| serde_json::Value::String(c.hex))
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    serde_json::Value::Array(hexes)
+                }
+                other => return Err(format!("未知的导出列: {}", other)),
+            };
+            values.push(value);
+        }
+        rows.push(ExportRow { values });
+    }
+    Ok(rows)
+}
+
+/// 导出文件清单。format："csv" | "json" | "m3u"；fields 为空时
+/// 导出全部已知列。返回实际写出的行数
+#[tauri::command]
+pub async fn export_file_list(
+    file_ids: Vec<String>,
+    format: String,
+    fields: Vec<String>,
+    dest_path: String,
+    app: tauri::AppHandle,
+) -> Result<usize, String> {
+    if !matches!(format.as_str(), "csv" | "json" | "m3u") {
+        return Err(format!("不支持的导出格式: {}（可选 csv / json / m3u）", format));
+    }
+    let fields = if fields.is_empty() {
+        KNOWN_FIELDS.iter().map(|f| f.to_string()).collect()
+    } else {
+        fields
+    };
+    let pool = app.state::<AppDbPool>().inner().clone();
+    let color_pool = app.state::<Arc<ColorDbPool>>().inner().clone();
+
+    tokio::task::spawn_blocking(move || {
+        // M3U 只需要路径列
+        let effective_fields = if format == "m3u" {
+            vec!["path".to_string()]
+        } else {
+            fields.clone()
+        };
+        let rows = collect_rows(&pool, &color_pool, &file_ids, &effective_fields)?;
+
+        let mut out = std::fs::File::create(&dest_path).map_err(|e| format!("创建导出文件失败: {}", e))?;
+        match format.as_str() {
+            "csv" => {
+                let mut text = fields.join(",");
+                text.push('\n');
+                for row in &rows {
+                    let line: Vec<String> = row.values.iter().map(csv_cell).collect();
+                    text.push_str(&line.join(","));
+                    text.push('\n');
+                }
+                out.write_all(text.as_bytes()).map_err(|e| e.to_string())?;
+            }
+            "json" => {
+                let objects: Vec<serde_json::Value> = rows
+                    .iter()
+                    .map(|row| {
+                        let map: serde_json::Map<String, serde_json::Value> = fields
+                            .iter()
+                            .cloned()
+                            .zip(row.values.iter().cloned())
+                            .collect();
+                        serde_json::Value::Object(map)
+                    })
+                    .collect();
+                let json = serde_json::to_string_pretty(&objects).map_err(|e| e.to_string())?;
+                out.write_all(json.as_bytes()).map_err(|e| e.to_string())?;
+            }
+            _ => {
+                let mut text = "#EXTM3U\n".to_string();
+                for row in &rows {
+                    if let Some(path) = row.values.first().and_then(|v| v.as_str()) {
+                        text.push_str(path);
+                        text.push('\n');
+                    }
+                }
+                out.write_all(text.as_bytes()).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(rows.len())
+    })
+    .await
+    .map_err(|e| format!("导出任务失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_cell_escaping() {
+        assert_eq!(csv_cell(&serde_json::json!("plain")), "plain");
+        assert_eq!(csv_cell(&serde_json::json!("a,b")), "\"a,b\"");
+        assert_eq!(csv_cell(&serde_json::json!("say \"hi\"")), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_cell(&serde_json::json!(["x", "y"])), "x;y");
+        assert_eq!(csv_cell(&serde_json::Value::Null), "");
+        assert_eq!(csv_cell(&serde_json::json!(42)), "42");
+    }
+}
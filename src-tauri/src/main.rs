@@ -1,5 +1,7 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
+// 各模块保留了不少供后续功能使用的辅助 API，统一不对未使用项告警
+#![allow(dead_code)]
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -11,7 +13,6 @@ use tauri::Manager;
 use tauri::Emitter;
 use tauri::tray::{TrayIconBuilder, TrayIconEvent};
 use tauri::menu::{Menu, MenuItem};
-use serde_json;
 use rusqlite::params; // params! macro required for some ad-hoc SQL in this file
 
 
@@ -34,11 +35,160 @@ mod update_downloader;
 // 导入 CLIP 模块
 mod clip;
 
-use crate::thumbnail::{get_thumbnail, get_thumbnails_batch, save_remote_thumbnail, generate_drag_preview};
-use crate::color_search::{search_by_palette, search_by_color};
+// 操作日志（撤销/重做）
+mod undo;
+
+// 文件占用检测（Windows RestartManager）
+mod file_lock;
+
+// 自然排序（数字按数值、中文可按拼音）
+mod natural_sort;
+
+// 自动图片分类（启发式 + CLIP 零样本）
+mod classifier;
+
+// AI 生成图片元数据解析（SD WebUI / NovelAI）
+mod sd_metadata;
+
+// 元数据写回（XMP 嵌入 JPEG/PNG/TIFF）
+mod metadata_writeback;
+
+// 网格过滤查询 DSL
+mod filter_query;
+
+// 文件夹同步（镜像/单向/双向）
+mod folder_sync;
+
+// 远端导出（WebDAV / S3 兼容存储）与系统凭据存取
+mod credentials;
+mod export_backend;
+
+// 本地 HTTP API（供浏览器扩展 / 脚本集成）
+mod api_server;
+
+// 网页采集入库（/api/ingest 端点 + 收件箱配置）
+mod ingest;
+
+// 自动化管线（声明式脚本串联后端操作）
+mod automation;
+
+// 插件系统（动态库：图像处理器 / 元数据提供者 / 导出目标）
+mod plugins;
+
+// 失联文件重连（前缀映射 + 文件名/大小匹配建议）
+mod relink;
+
+// 库占用锁（锁文件 + 心跳，防止多实例并发写坏数据库）
+mod library_lock;
+
+// 数据库健康检查与自动修复
+mod db_health;
+
+// 日志配置（运行期级别调整、按大小轮转、调试面板取日志）
+mod app_log;
+
+// 性能基准测试（扫描 / 缩略图 / SQLite / CLIP）
+mod benchmark;
+
+// 卷类型检测（SSD / HDD，决定扫描并行度）
+mod volume_info;
+
+// 后台任务 I/O 限速与低优先级
+mod io_throttle;
+
+// 隐藏文件判定与云盘占位文件检测
+mod fs_attrs;
+
+// "打开方式"与外部编辑器集成
+mod open_with;
+
+// 壁纸设置与轮换调度
+mod wallpaper;
+
+// 快速预览浮窗（quickpreview:// 协议 + 无边框置顶窗）
+mod quick_preview;
+
+// 图片对比（SSIM / 差异热力图 / EXIF 差异）
+mod compare;
+
+// 直方图与曝光统计（查看器信息面板）
+mod histogram;
+
+// 超大图深度缩放（deepzoom:// 协议 + 瓦片金字塔）
+mod deep_zoom;
+
+// 人脸裁剪导出（头像 / 训练集）
+mod face_export;
+
+// 训练集导出（kohya / jsonl 字幕格式）
+mod dataset_export;
+
+// 监视文件夹自动入库规则
+mod watch_folders;
+
+// 开机自启与 --background 后台启动
+mod autostart;
+
+// 优雅退出协调器（任务收尾 + 断点记录）
+mod shutdown;
+
+// 托盘进度 / 未读角标
+mod tray_status;
+
+// 长任务的系统通知（toast）
+mod notifications;
+
+// 后端字符串本地化（托盘菜单 / 错误码）
+mod i18n;
+
+// 音频素材的时长解析与波形缩略图
+mod audio;
+
+// 3D 模型素材的解析与转台静帧渲染
+mod model;
+
+// 工作台：跨搜索的临时选集
+mod workbench;
+
+// 文件清单导出（CSV / JSON / M3U）
+mod list_export;
+
+// 携带真实文件路径的原生拖出
+mod drag_out;
+
+// 系统分享（共享面板 / 邮件附件）
+mod share;
+
+// 选集 ZIP 打包
+mod zip_package;
+
+// 键盘驱动的选片（culling）会话
+mod cull;
+
+// 图片批注（矢量叠加层与压平导出）
+mod annotate;
+
+// 被覆盖文件的版本历史
+mod versions;
+
+// 搜索结果的虚拟文件夹
+mod virtual_folder;
+
+// “更多相似图片”的最近邻预计算
+mod related;
+
+// 搜索命中区域的视觉定位
+mod grounding;
+
+// 嵌入库的导出 / 导入
+mod embedding_io;
+
+// 导出 / 分享前的人脸打码
+mod face_redact;
+
+use crate::thumbnail::{get_thumbnail, get_thumbnails_batch, save_remote_thumbnail, generate_drag_preview, get_thumbnail_settings, set_thumbnail_settings, regenerate_thumbnails, pregenerate_thumbnails};
+use crate::color_search::{search_by_palette, search_by_palette_stream, search_by_palette_detailed, search_by_color, set_similarity_preset, get_similarity_params};
 
-use image;
-use jxl_oxide;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 // 全局共享的重载格式（JXL/AVIF）解码任务计数，限制并发以保护 CPU
@@ -52,7 +202,7 @@ pub fn is_jxl(buffer: &[u8]) -> bool {
         return true;
     }
     // Container: 00 00 00 0C 4A 58 4C 20 0D 0A 87 0A
-    if buffer.len() >= 12 && (&buffer[0..12] == &[0, 0, 0, 0x0C, 0x4A, 0x58, 0x4C, 0x20, 0x0D, 0x0A, 0x87, 0x0A] || &buffer[0..12] == b"\x00\x00\x00\x0cJXL \x0d\x0a\x87\x0a") {
+    if buffer.len() >= 12 && (buffer[0..12] == [0, 0, 0, 0x0C, 0x4A, 0x58, 0x4C, 0x20, 0x0D, 0x0A, 0x87, 0x0A] || &buffer[0..12] == b"\x00\x00\x00\x0cJXL \x0d\x0a\x87\x0a") {
         return true;
     }
     false
@@ -94,14 +244,25 @@ pub fn get_image_dimensions(path: &str) -> (u32, u32) {
         }
     }
 
+    // EXR 不在 imageinfo 的支持列表里，直接走 image crate 读头
+    if buf.starts_with(&[0x76, 0x2F, 0x31, 0x01]) || path.to_lowercase().ends_with(".exr") {
+        if let Ok(dim) = image::image_dimensions(path) {
+            return dim;
+        }
+    }
+
     // Try imageinfo for everything else
     // 使用 catch_unwind 捕获可能的 panic，防止扫描线程崩溃
     let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-        imageinfo::ImageInfo::from_file(&mut file)
+        imageinfo::ImageInfo::from_file(&file)
     }));
     
     match result {
         Ok(Ok(info)) => (info.size.width as u32, info.size.height as u32),
+        // TGA 没有魔数，imageinfo 的启发式识别偶尔失败，用 image crate 按扩展名兜底
+        Ok(Err(_)) if path.to_lowercase().ends_with(".tga") => {
+            image::image_dimensions(path).unwrap_or((0, 0))
+        }
         Ok(Err(_)) => (0, 0),
         Err(_) => {
             eprintln!("[Warning] imageinfo panicked while processing: {}", path);
@@ -110,87 +271,285 @@ pub fn get_image_dimensions(path: &str) -> (u32, u32) {
     }
 }
 
-use std::sync::Mutex;
-use std::time::{Instant, Duration};
+/// 扫描时从文件头廉价读取的图像属性（不解码像素）
+#[derive(Debug, Clone, Default)]
+pub struct ImageHeaderInfo {
+    pub bit_depth: Option<u32>,
+    pub color_space: Option<String>,
+    pub has_alpha: Option<bool>,
+    pub is_animated: Option<bool>,
+}
 
-// 全局 HDD 检测结果缓存
-static HDD_CACHE: Mutex<Option<HashMap<String, (bool, Instant)>>> = Mutex::new(None);
-const CACHE_TTL: Duration = Duration::from_secs(300); // 缓存有效期 5 分钟
+/// 只读文件开头（最多 512KB）解析位深/色彩空间/透明/动图标志。
+/// 支持 PNG/JPEG/GIF/WebP/BMP/TIFF，识别不了的格式返回全 None
+pub fn probe_image_header(path: &str) -> ImageHeaderInfo {
+    use std::io::Read;
 
-/// 检测路径是否可能位于HDD（机械硬盘）上
-/// 通过测量小文件的随机读取延迟来判断
-/// 结果会被缓存，避免重复检测
-fn is_likely_hdd(path: &str) -> bool {
-    // 规范化路径作为缓存键
-    let cache_key = normalize_path(path);
-    
-    // 首先检查缓存
-    {
-        let mut cache_guard = HDD_CACHE.lock().unwrap();
-        if cache_guard.is_none() {
-            *cache_guard = Some(HashMap::new());
+    let mut info = ImageHeaderInfo::default();
+    let Ok(file) = std::fs::File::open(path) else {
+        return info;
+    };
+    let mut buf = Vec::with_capacity(64 * 1024);
+    if file.take(512 * 1024).read_to_end(&mut buf).is_err() || buf.len() < 16 {
+        return info;
+    }
+
+    if buf.starts_with(&[0x89, b'P', b'N', b'G']) {
+        if buf.len() >= 26 {
+            info.bit_depth = Some(buf[24] as u32);
+            let color_type = buf[25];
+            info.has_alpha = Some(color_type == 4 || color_type == 6);
         }
-        
-        if let Some(cache) = cache_guard.as_ref() {
-            if let Some((result, timestamp)) = cache.get(&cache_key) {
-                if timestamp.elapsed() < CACHE_TTL {
-                    // 缓存命中且未过期
-                    return *result;
+        // acTL 在 IDAT 之前出现即为 APNG
+        info.is_animated = Some(buf.windows(4).any(|w| w == b"acTL"));
+        if buf.windows(4).any(|w| w == b"iCCP") {
+            info.color_space = Some("ICC".to_string());
+        } else if buf.windows(4).any(|w| w == b"sRGB") {
+            info.color_space = Some("sRGB".to_string());
+        }
+    } else if buf.starts_with(&[0xFF, 0xD8]) {
+        info.has_alpha = Some(false);
+        info.is_animated = Some(false);
+        let mut pos = 2usize;
+        let mut has_icc = false;
+        while pos + 4 <= buf.len() {
+            if buf[pos] != 0xFF {
+                break;
+            }
+            let marker = buf[pos + 1];
+            if marker == 0xDA {
+                break;
+            }
+            let seg_len = u16::from_be_bytes([buf[pos + 2], buf[pos + 3]]) as usize;
+            if seg_len < 2 {
+                break;
+            }
+            let seg_end = pos + 2 + seg_len;
+            // SOF0-SOF15（跳过 DHT/DAC 等同组非 SOF 标记）
+            if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+                if pos + 9 < buf.len() {
+                    info.bit_depth = Some(buf[pos + 4] as u32);
+                    let components = buf[pos + 9];
+                    info.color_space = Some(match components {
+                        1 => "Gray".to_string(),
+                        4 => "CMYK".to_string(),
+                        _ => "YCbCr".to_string(),
+                    });
                 }
+                break;
             }
+            if marker == 0xE2 && buf[pos + 4..].starts_with(b"ICC_PROFILE") {
+                has_icc = true;
+            }
+            pos = seg_end;
         }
-    }
-    
-    // 缓存未命中，执行检测
-    let result = detect_hdd_internal(path);
-    
-    // 更新缓存
-    {
-        let mut cache_guard = HDD_CACHE.lock().unwrap();
-        if let Some(ref mut cache) = cache_guard.as_mut() {
-            cache.insert(cache_key, (result, Instant::now()));
+        if has_icc {
+            info.color_space = Some("ICC".to_string());
+        }
+    } else if buf.starts_with(b"GIF8") {
+        if buf.len() > 10 {
+            info.bit_depth = Some(((buf[10] & 0x07) + 1) as u32);
+        }
+        info.color_space = Some("Indexed".to_string());
+        info.is_animated = Some(buf.windows(11).any(|w| w == b"NETSCAPE2.0"));
+        // 图形控制扩展的透明标志位
+        info.has_alpha = Some(
+            buf.windows(4)
+                .any(|w| w[0] == 0x21 && w[1] == 0xF9 && w[2] == 0x04 && w[3] & 0x01 != 0),
+        );
+    } else if buf.starts_with(b"RIFF") && buf.len() > 20 && &buf[8..12] == b"WEBP" {
+        info.bit_depth = Some(8);
+        if &buf[12..16] == b"VP8X" {
+            let flags = buf[20];
+            info.has_alpha = Some(flags & 0x10 != 0);
+            info.is_animated = Some(flags & 0x02 != 0);
+            info.color_space = Some(if flags & 0x20 != 0 { "ICC" } else { "sRGB" }.to_string());
+        } else {
+            info.has_alpha = Some(&buf[12..16] == b"VP8L" && buf.len() > 24 && buf[24] & 0x10 != 0);
+            info.is_animated = Some(false);
+            info.color_space = Some("sRGB".to_string());
         }
+    } else if buf.starts_with(b"BM") {
+        if buf.len() > 29 {
+            let bpp = u16::from_le_bytes([buf[28], buf[29]]) as u32;
+            info.bit_depth = Some(if bpp >= 24 { 8 } else { bpp });
+            info.has_alpha = Some(bpp == 32);
+        }
+        info.is_animated = Some(false);
+    } else if buf.starts_with(b"II*\0") || buf.starts_with(b"MM\0*") {
+        probe_tiff_header(path, &mut info);
     }
-    
-    result
+
+    info
 }
 
-/// 内部 HDD 检测逻辑
-fn detect_hdd_internal(path: &str) -> bool {
-    let test_path = Path::new(path);
-    let mut read_times = Vec::new();
+/// 解析 TIFF IFD0 的位深/色彩空间/透明标记。
+/// IFD 可能位于文件任意位置，这里按偏移随机读取而不是读整个文件
+fn probe_tiff_header(path: &str, info: &mut ImageHeaderInfo) {
+    use std::io::{Read, Seek, SeekFrom};
 
-    if let Ok(entries) = fs::read_dir(test_path) {
-        let test_files: Vec<_> = entries
-            .filter_map(|e| e.ok())
-            .filter(|e| {
-                if let Ok(meta) = e.metadata() {
-                    meta.is_file() && meta.len() < 1024 * 1024
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return;
+    };
+    let mut header = [0u8; 8];
+    if file.read_exact(&mut header).is_err() {
+        return;
+    }
+    let little_endian = &header[..2] == b"II";
+    let read_u16 = |b: &[u8]| -> u16 {
+        let arr = [b[0], b[1]];
+        if little_endian { u16::from_le_bytes(arr) } else { u16::from_be_bytes(arr) }
+    };
+    let read_u32 = |b: &[u8]| -> u32 {
+        let arr = [b[0], b[1], b[2], b[3]];
+        if little_endian { u32::from_le_bytes(arr) } else { u32::from_be_bytes(arr) }
+    };
+    if read_u16(&header[2..4]) != 42 {
+        return;
+    }
+
+    let ifd_offset = read_u32(&header[4..8]) as u64;
+    if file.seek(SeekFrom::Start(ifd_offset)).is_err() {
+        return;
+    }
+    let mut count_buf = [0u8; 2];
+    if file.read_exact(&mut count_buf).is_err() {
+        return;
+    }
+    let count = read_u16(&count_buf) as usize;
+    if count == 0 || count > 4096 {
+        return;
+    }
+    let mut entries = vec![0u8; count * 12];
+    if file.read_exact(&mut entries).is_err() {
+        return;
+    }
+
+    info.is_animated = Some(false);
+    for entry in entries.chunks_exact(12) {
+        let tag = read_u16(&entry[..2]);
+        let value_count = read_u32(&entry[4..8]);
+        match tag {
+            // BitsPerSample：多通道时各通道位深相同，读内联值或偏移处的第一个
+            258 => {
+                if value_count == 1 {
+                    info.bit_depth = Some(read_u16(&entry[8..10]) as u32);
                 } else {
-                    false
+                    let offset = read_u32(&entry[8..12]) as u64;
+                    let mut v = [0u8; 2];
+                    if file.seek(SeekFrom::Start(offset)).is_ok() && file.read_exact(&mut v).is_ok() {
+                        info.bit_depth = Some(read_u16(&v) as u32);
+                    }
                 }
-            })
-            .take(5)
-            .collect();
-
-        for entry in test_files {
-            let path = entry.path();
-            let start = Instant::now();
-            let _ = fs::metadata(&path);
-            let elapsed = start.elapsed();
-            read_times.push(elapsed.as_millis() as f64);
+            }
+            // PhotometricInterpretation
+            262 => {
+                let v = read_u16(&entry[8..10]);
+                info.color_space = Some(
+                    match v {
+                        0 | 1 => "Gray",
+                        3 => "Indexed",
+                        5 => "CMYK",
+                        6 => "YCbCr",
+                        8..=10 => "Lab",
+                        _ => "RGB",
+                    }
+                    .to_string(),
+                );
+            }
+            // ExtraSamples 存在即视为带透明通道
+            338 => {
+                info.has_alpha = Some(true);
+            }
+            _ => {}
         }
     }
+    if info.has_alpha.is_none() {
+        info.has_alpha = Some(false);
+    }
+}
+
+use std::sync::Mutex;
+
+/// 检测路径是否位于机械硬盘上。
+/// 实际判断交给 volume_info 模块问操作系统（寻道惩罚 / rotational 标志），
+/// 查不出来按 SSD 处理，不再用读延迟猜
+fn is_likely_hdd(path: &str) -> bool {
+    volume_info::is_hdd(path)
+}
+
+// --- 扫描跳过报告 ---
+// 扫描器过去对读不了的目录一律静默丢弃，用户只会发现"少了东西"。
+// 这里把每个跳过的路径连同原因收集起来，扫描结束后随 "scan-report"
+// 事件发给前端并持久化，可用 get_last_scan_report 随时取回。
+
+/// 一个被跳过的路径及原因
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ScanSkip {
+    path: String,
+    /// "无权限" | "路径过长" | "目录循环" | 其他 I/O 错误文本
+    reason: String,
+}
+
+/// 一次扫描的跳过汇总
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ScanReport {
+    root: String,
+    finished_at: i64,
+    skipped: Vec<ScanSkip>,
+}
+
+/// 把 jwalk 错误归类成用户能看懂的原因
+fn classify_walk_error(err: &jwalk::Error) -> ScanSkip {
+    let path = err
+        .path()
+        .map(|p| normalize_path(&p.to_string_lossy()))
+        .unwrap_or_default();
+    let reason = if let Some(ancestor) = err.loop_ancestor() {
+        format!("目录循环（符号链接指回 {}）", normalize_path(&ancestor.to_string_lossy()))
+    } else if let Some(io) = err.io_error() {
+        classify_io_error(io)
+    } else {
+        err.to_string()
+    };
+    ScanSkip { path, reason }
+}
 
-    if read_times.len() >= 3 {
-        let avg_time: f64 = read_times.iter().sum::<f64>() / read_times.len() as f64;
-        log::info!("[HDD Detection] Average read time: {:.2}ms (threshold: 10ms)", avg_time);
-        avg_time > 10.0
+fn classify_io_error(io: &std::io::Error) -> String {
+    // ENAMETOOLONG：Linux 36 / Windows ERROR_FILENAME_EXCED_RANGE 206
+    if io.kind() == std::io::ErrorKind::PermissionDenied {
+        "无权限".to_string()
+    } else if matches!(io.raw_os_error(), Some(36) | Some(206)) {
+        "路径过长".to_string()
     } else {
-        false
+        io.to_string()
     }
 }
 
+fn record_scan_skip(log: &Arc<Mutex<Vec<ScanSkip>>>, skip: ScanSkip) {
+    let mut guard = log.lock().unwrap();
+    // 防御性封顶：一整块坏盘可能产生海量错误
+    if guard.len() < 10000 {
+        guard.push(skip);
+    }
+}
+
+fn scan_report_path(app: &tauri::AppHandle) -> std::path::PathBuf {
+    app.path()
+        .app_data_dir()
+        .unwrap_or_else(|_| std::path::PathBuf::from("."))
+        .join("scan_report.json")
+}
+
+/// 最近一次扫描的跳过报告（没有扫描过时返回 None）
+#[tauri::command]
+fn get_last_scan_report(app: tauri::AppHandle) -> Option<ScanReport> {
+    let content = fs::read_to_string(scan_report_path(&app)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
 // --- Window State Management ---
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -200,40 +559,99 @@ struct SavedWindowState {
     x: f64,
     y: f64,
     maximized: bool,
+    /// 上次所在显示器的名称，恢复时优先校验它是否还连着
+    #[serde(default)]
+    monitor: Option<String>,
 }
 
 impl Default for SavedWindowState {
     fn default() -> Self {
-        Self { width: 1280.0, height: 800.0, x: 100.0, y: 100.0, maximized: false }
+        Self { width: 1280.0, height: 800.0, x: 100.0, y: 100.0, maximized: false, monitor: None }
     }
 }
 
+/// 保存的窗口区域是否仍落在某个已连接显示器上。
+/// 要求至少露出 100px 宽的标题栏区域，不然拔掉外接屏后窗口会开在屏幕外
+fn window_bounds_visible(monitors: &[(f64, f64, f64, f64)], x: f64, y: f64, width: f64) -> bool {
+    const MIN_VISIBLE_WIDTH: f64 = 100.0;
+    const TITLE_GRAB_BAND: f64 = 40.0;
+    monitors.iter().any(|&(mx, my, mw, mh)| {
+        let overlap = (x + width).min(mx + mw) - x.max(mx);
+        overlap >= MIN_VISIBLE_WIDTH && y >= my - 1.0 && y <= my + mh - TITLE_GRAB_BAND
+    })
+}
+
 fn get_window_state_path(app_handle: &tauri::AppHandle) -> std::path::PathBuf {
     app_handle.path().app_data_dir().unwrap_or_else(|_| std::path::PathBuf::from(".")).join("window_state.json")
 }
 
+// --- 多用户配置（profile） ---
+// 家庭共用一台机器时，每个人的收藏 / 评分 / 分类各自独立。
+// 实现上每个 profile 是一份独立的 metadata 数据库：默认 profile 用
+// <base>/metadata.db，其余放在 <base>/profiles/<名称>.db（base 为 .aurora
+// 目录或应用数据目录）。图片文件夹与颜色库（客观的逐图数据）所有 profile
+// 共享；首次切到新 profile 时把 file_index 复制过去，省掉一次全量扫描。
+// 当前 profile 名记录在 <base>/current_profile.txt，启动时恢复
+
+static CURRENT_PROFILE: once_cell::sync::Lazy<std::sync::RwLock<String>> =
+    once_cell::sync::Lazy::new(|| std::sync::RwLock::new("default".to_string()));
+
+/// profile 名只允许字母数字、下划线和连字符（要当文件名用）
+fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// 某个 profile 的 metadata 数据库路径
+fn profile_db_path(base_dir: &Path, name: &str) -> std::path::PathBuf {
+    if name == "default" {
+        base_dir.join("metadata.db")
+    } else {
+        base_dir.join("profiles").join(format!("{}.db", name))
+    }
+}
+
+/// 读取 base 目录里记录的当前 profile（缺失或非法时回退 default）
+fn read_saved_profile(base_dir: &Path) -> String {
+    let name = fs::read_to_string(base_dir.join("current_profile.txt"))
+        .map(|s| s.trim().to_string())
+        .unwrap_or_default();
+    if is_valid_profile_name(&name) {
+        name
+    } else {
+        "default".to_string()
+    }
+}
+
 fn get_initial_db_paths(app_handle: &tauri::AppHandle) -> (std::path::PathBuf, std::path::PathBuf) {
     let app_data_dir = app_handle.path().app_data_dir()
         .expect("Failed to get app data directory");
-    
+
     let config_path = app_data_dir.join("user_data.json");
-    
+
     if config_path.exists() {
         if let Ok(json_str) = fs::read_to_string(config_path) {
             if let Ok(data) = serde_json::from_str::<serde_json::Value>(&json_str) {
                 if let Some(root_paths) = data.get("rootPaths").and_then(|v| v.as_array()) {
-                    if let Some(first_root) = root_paths.get(0).and_then(|v| v.as_str()) {
+                    if let Some(first_root) = root_paths.first().and_then(|v| v.as_str()) {
                         let root = Path::new(first_root);
                         let aurora_dir = root.join(".aurora");
-                        return (aurora_dir.join("colors.db"), aurora_dir.join("metadata.db"));
+                        let profile = read_saved_profile(&aurora_dir);
+                        let metadata_path = profile_db_path(&aurora_dir, &profile);
+                        *CURRENT_PROFILE.write().unwrap() = profile;
+                        return (aurora_dir.join("colors.db"), metadata_path);
                     }
                 }
             }
         }
     }
-    
+
     // Default fallback
-    (app_data_dir.join("colors.db"), app_data_dir.join("metadata.db"))
+    let profile = read_saved_profile(&app_data_dir);
+    let metadata_path = profile_db_path(&app_data_dir, &profile);
+    *CURRENT_PROFILE.write().unwrap() = profile;
+    (app_data_dir.join("colors.db"), metadata_path)
 }
 
 fn save_window_state(app_handle: &tauri::AppHandle) {
@@ -247,7 +665,7 @@ fn save_window_state(app_handle: &tauri::AppHandle) {
         fs::read_to_string(&path)
             .ok()
             .and_then(|s| serde_json::from_str::<SavedWindowState>(&s).ok())
-            .unwrap_or(SavedWindowState::default())
+            .unwrap_or_default()
     } else {
         SavedWindowState::default()
     };
@@ -265,6 +683,11 @@ fn save_window_state(app_handle: &tauri::AppHandle) {
                 state.y = l_pos.y;
                 state.width = l_size.width;
                 state.height = l_size.height;
+                state.monitor = window
+                    .current_monitor()
+                    .ok()
+                    .flatten()
+                    .and_then(|m| m.name().cloned());
             }
         }
     }
@@ -284,6 +707,8 @@ use db::AppDbPool;
 pub enum FileType {
     Image,
     Folder,
+    /// 3D 模型素材（obj / gltf / glb / fbx）
+    Model,
     Unknown,
 }
 
@@ -296,6 +721,18 @@ pub struct ImageMeta {
     pub created: String,
     pub modified: String,
     pub format: String,
+    /// 位深（每通道比特数），来自文件头
+    #[serde(default)]
+    pub bit_depth: Option<u32>,
+    /// 色彩空间 / 配置文件（如 sRGB、CMYK、Gray、ICC）
+    #[serde(default)]
+    pub color_space: Option<String>,
+    /// 是否带透明通道
+    #[serde(default)]
+    pub has_alpha: Option<bool>,
+    /// 是否为动图（GIF/APNG/动态 WebP）
+    #[serde(default)]
+    pub is_animated: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -317,23 +754,81 @@ pub struct FileNode {
     pub source_url: Option<String>,
     pub category: Option<String>,
     pub ai_data: Option<serde_json::Value>,
+    /// 文件可用性："local" 本地可读，"online-only" 云盘占位（未知时为 None）
+    #[serde(default)]
+    pub availability: Option<String>,
 }
 
 // Supported image extensions
 const SUPPORTED_EXTENSIONS: &[&str] = &[
     "jpg", "jpeg", "png", "gif", "webp", "bmp", "tiff", "ico", "svg", "avif", "jxl",
+    // 游戏美术素材格式：DDS（含 DXT1/3/5 压缩）、TGA、EXR（HDR，缩略图经色调映射）
+    "dds", "tga", "exr",
 ];
 
 // Use shared generate_id and normalize_path
 use db::{generate_id, normalize_path};
 
-// Generate a unique file path by adding _copy suffix if file exists
+/// 低内存模式：开启后降低各后台任务的并行度与内存占用，
+/// 避免 8GB 级别的机器在大图库上被索引任务挤爆内存
+static LOW_MEMORY_MODE: AtomicBool = AtomicBool::new(false);
+
+/// 查询低内存模式是否生效（供各 worker 模块调整并行度/缓存上限）
+pub fn is_low_memory() -> bool {
+    LOW_MEMORY_MODE.load(Ordering::SeqCst)
+}
+
+/// 根据系统内存自动判断是否应启用低内存模式：
+/// 总内存不足 9GB，或当前可用内存不足 2GB
+fn detect_low_memory() -> bool {
+    let mut sys = sysinfo::System::new();
+    sys.refresh_memory();
+    let total = sys.total_memory();
+    let available = sys.available_memory();
+    total < 9 * 1024 * 1024 * 1024 || available < 2 * 1024 * 1024 * 1024
+}
+
+/// 设置低内存模式。enabled 为 None 时按系统内存自动检测，返回生效后的值
+#[tauri::command]
+fn set_low_memory_mode(enabled: Option<bool>) -> bool {
+    let effective = enabled.unwrap_or_else(detect_low_memory);
+    LOW_MEMORY_MODE.store(effective, Ordering::SeqCst);
+    log::info!("Low-memory mode set to {} (explicit: {})", effective, enabled.is_some());
+    effective
+}
+
+/// 查询低内存模式当前状态
+#[tauri::command]
+fn get_low_memory_mode() -> bool {
+    is_low_memory()
+}
+
+/// 冲突改名后缀风格: "copy" -> _copy/_copy2, "paren" -> " (1)", "dash" -> "-001"
+static CONFLICT_SUFFIX_STYLE: once_cell::sync::Lazy<std::sync::RwLock<String>> =
+    once_cell::sync::Lazy::new(|| std::sync::RwLock::new("copy".to_string()));
+
+/// 按当前后缀风格生成第 counter 个候选文件名
+fn conflict_suffix_name(file_stem: &str, extension: &str, counter: u32, style: &str) -> String {
+    match style {
+        "paren" => format!("{} ({}){}", file_stem, counter, extension),
+        "dash" => format!("{}-{:03}{}", file_stem, counter, extension),
+        _ => {
+            if counter == 1 {
+                format!("{}_copy{}", file_stem, extension)
+            } else {
+                format!("{}_copy{}{}", file_stem, counter, extension)
+            }
+        }
+    }
+}
+
+// Generate a unique file path by adding a suffix (style configurable) if file exists
 fn generate_unique_file_path(dest_path: &str) -> String {
     let path = Path::new(dest_path);
     if !path.exists() {
         return dest_path.to_string();
     }
-    
+
     // Get parent directory and file stem/extension
     let parent = path.parent().unwrap_or_else(|| Path::new(""));
     let file_stem = path.file_stem()
@@ -343,46 +838,280 @@ fn generate_unique_file_path(dest_path: &str) -> String {
         .and_then(|e| e.to_str())
         .map(|e| format!(".{}", e))
         .unwrap_or_default();
-    
-    // Try _copy, _copy2, _copy3, etc.
+
+    let style = CONFLICT_SUFFIX_STYLE.read().unwrap().clone();
+
+    // Try suffixed names until one is free
     for counter in 1.. {
-        let new_name = if counter == 1 {
-            format!("{}_copy{}", file_stem, extension)
-        } else {
-            format!("{}_copy{}{}", file_stem, counter, extension)
-        };
+        let new_name = conflict_suffix_name(file_stem, &extension, counter, &style);
         let new_path = parent.join(&new_name);
         if !new_path.exists() {
             return new_path.to_str().unwrap_or(dest_path).to_string();
         }
     }
-    
+
     // Fallback (should never reach here)
     dest_path.to_string()
 }
 
+/// 批量解决目标目录下的文件名冲突，复制/移动/导入共用同一套策略。
+/// policy: "rename" 按后缀风格自动改名 | "overwrite" 覆盖 | "skip" 跳过 (返回 None)
+/// 返回与 paths 一一对应的最终目标路径
+fn resolve_conflicts(paths: &[String], dest_dir: &str, policy: &str) -> Vec<Option<String>> {
+    let dir = normalize_path(dest_dir);
+    let dir = dir.trim_end_matches('/');
+    // 同一批内已分配的目标，避免批内互相撞名
+    let mut taken: std::collections::HashSet<String> = std::collections::HashSet::new();
+    let style = CONFLICT_SUFFIX_STYLE.read().unwrap().clone();
+
+    paths.iter().map(|src| {
+        let name = Path::new(src)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("");
+        let dest = format!("{}/{}", dir, name);
+        let conflict = Path::new(&dest).exists() || taken.contains(&dest);
+
+        let target = if !conflict {
+            Some(dest)
+        } else {
+            match policy {
+                "overwrite" => Some(dest),
+                "skip" => None,
+                _ => {
+                    // rename: 按后缀风格找到一个既不在磁盘上也不在本批内的名字
+                    let path = Path::new(&dest);
+                    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+                    let ext = path.extension()
+                        .and_then(|e| e.to_str())
+                        .map(|e| format!(".{}", e))
+                        .unwrap_or_default();
+                    (1..)
+                        .map(|counter| format!("{}/{}", dir, conflict_suffix_name(stem, &ext, counter, &style)))
+                        .find(|candidate| !Path::new(candidate).exists() && !taken.contains(candidate))
+                }
+            }
+        };
+
+        if let Some(t) = &target {
+            taken.insert(t.clone());
+        }
+        target
+    }).collect()
+}
+
+/// 设置冲突改名后缀风格 (copy/paren/dash)
+#[tauri::command]
+fn set_conflict_suffix_style(style: String) -> Result<(), String> {
+    match style.as_str() {
+        "copy" | "paren" | "dash" => {
+            *CONFLICT_SUFFIX_STYLE.write().unwrap() = style;
+            Ok(())
+        }
+        other => Err(format!("未知的后缀风格: {}", other)),
+    }
+}
+
+#[tauri::command]
+fn get_conflict_suffix_style() -> String {
+    CONFLICT_SUFFIX_STYLE.read().unwrap().clone()
+}
+
+/// 供前端导入流程预演冲突解决结果 (与复制/移动使用同一套逻辑)
+#[tauri::command]
+async fn resolve_file_conflicts(paths: Vec<String>, dest_dir: String, policy: String) -> Result<Vec<Option<String>>, String> {
+    Ok(resolve_conflicts(&paths, &dest_dir, &policy))
+}
+
 // Check if file extension is supported
 fn is_supported_image(extension: &str) -> bool {
     SUPPORTED_EXTENSIONS.contains(&extension.to_lowercase().as_str())
 }
 
+/// 伴生文件扩展名：RAW 底片、caption 文本、XMP 边车。
+/// 移动/删除图片时同主名的伴生文件跟随处理
+const COMPANION_EXTENSIONS: &[&str] = &[
+    "arw", "cr2", "cr3", "nef", "dng", "raf", "orf", "rw2", "pef", "srw", "txt", "xmp",
+];
+
+/// 返回与 file_path 同目录、同主名（大小写不敏感）的伴生文件
+fn companion_files(file_path: &str) -> Vec<String> {
+    let path = Path::new(file_path);
+    let (Some(parent), Some(stem)) = (
+        path.parent(),
+        path.file_stem().and_then(|s| s.to_str()),
+    ) else {
+        return Vec::new();
+    };
+    let stem_lower = stem.to_lowercase();
+
+    let Ok(entries) = fs::read_dir(parent) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let p = e.path();
+            if p == path || !p.is_file() {
+                return None;
+            }
+            let ext = p.extension()?.to_str()?.to_lowercase();
+            if !COMPANION_EXTENSIONS.contains(&ext.as_str()) {
+                return None;
+            }
+            if p.file_stem()?.to_str()?.to_lowercase() != stem_lower {
+                return None;
+            }
+            Some(normalize_path(p.to_str()?))
+        })
+        .collect()
+}
+
+/// 查询某个图片的伴生文件（RAW/txt/xmp），供前端显示角标
+#[tauri::command]
+fn get_companion_files(file_path: String) -> Vec<String> {
+    companion_files(&file_path)
+}
+
+/// 查看器解码缓存：file_id + 编辑版本号 -> 解码后的 data URL。
+/// 按字节数做 LRU 淘汰，前后翻页浏览相邻图片时避免重复解码
+struct ViewerCache {
+    entries: HashMap<String, String>,
+    order: std::collections::VecDeque<String>,
+    total_bytes: usize,
+    max_bytes: usize,
+}
+
+impl ViewerCache {
+    fn new(max_bytes: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: std::collections::VecDeque::new(),
+            total_bytes: 0,
+            max_bytes,
+        }
+    }
+
+    fn get(&mut self, key: &str) -> Option<String> {
+        let value = self.entries.get(key)?.clone();
+        // 命中后移到队尾（最近使用）
+        if let Some(pos) = self.order.iter().position(|k| k == key) {
+            if let Some(k) = self.order.remove(pos) {
+                self.order.push_back(k);
+            }
+        }
+        Some(value)
+    }
+
+    fn insert(&mut self, key: String, value: String) {
+        // 单个条目超过总上限时直接不缓存
+        if value.len() > self.max_bytes {
+            return;
+        }
+        if let Some(old) = self.entries.remove(&key) {
+            self.total_bytes -= old.len();
+            if let Some(pos) = self.order.iter().position(|k| *k == key) {
+                self.order.remove(pos);
+            }
+        }
+        self.total_bytes += value.len();
+        self.entries.insert(key.clone(), value);
+        self.order.push_back(key);
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        while self.total_bytes > self.max_bytes {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            if let Some(value) = self.entries.remove(&oldest) {
+                self.total_bytes -= value.len();
+            }
+        }
+    }
+
+    fn set_max_bytes(&mut self, max_bytes: usize) {
+        self.max_bytes = max_bytes;
+        self.evict();
+    }
+
+    /// 移除某个文件的全部缓存版本（文件内容被覆盖后调用）
+    fn remove_file(&mut self, file_id: &str) {
+        let prefix = format!("{}@", file_id);
+        let stale: Vec<String> = self
+            .entries
+            .keys()
+            .filter(|k| k.starts_with(&prefix))
+            .cloned()
+            .collect();
+        for key in stale {
+            if let Some(value) = self.entries.remove(&key) {
+                self.total_bytes -= value.len();
+            }
+            if let Some(pos) = self.order.iter().position(|k| *k == key) {
+                self.order.remove(pos);
+            }
+        }
+    }
+}
+
+static VIEWER_CACHE: Lazy<Mutex<ViewerCache>> =
+    Lazy::new(|| Mutex::new(ViewerCache::new(256 * 1024 * 1024)));
+
+/// 组合缓存键：同一文件编辑后版本号递增，旧版本自然失效
+fn viewer_cache_key(file_id: &str, revision: u64) -> String {
+    format!("{}@{}", file_id, revision)
+}
+
+/// 设置查看器解码缓存的上限（MB），0 表示禁用并清空
+#[tauri::command]
+fn set_viewer_cache_size(mb: u64) {
+    VIEWER_CACHE
+        .lock()
+        .unwrap()
+        .set_max_bytes(mb as usize * 1024 * 1024);
+}
+
+/// 查询查看器解码缓存的占用情况
+#[tauri::command]
+fn get_viewer_cache_stats() -> serde_json::Value {
+    let cache = VIEWER_CACHE.lock().unwrap();
+    serde_json::json!({
+        "entries": cache.entries.len(),
+        "bytes": cache.total_bytes,
+        "maxBytes": cache.max_bytes,
+    })
+}
+
 #[tauri::command]
-async fn get_avif_preview(path: String) -> Result<String, String> {
+async fn get_avif_preview(path: String, file_id: Option<String>, revision: Option<u64>) -> Result<String, String> {
     use base64::{Engine as _, engine::general_purpose};
     use std::fs;
 
+    let cache_key = file_id.map(|id| viewer_cache_key(&id, revision.unwrap_or(0)));
+    if let Some(key) = &cache_key {
+        if let Some(hit) = VIEWER_CACHE.lock().unwrap().get(key) {
+            return Ok(hit);
+        }
+    }
+
     // Direct read and base64 encode to leverage WebView2 native AVIF support.
     // This avoids backend decoding dependencies entirely.
     let result = tokio::task::spawn_blocking(move || {
         let content = fs::read(&path).map_err(|e| format!("Failed to read file: {}", e))?;
         Ok(format!("data:image/avif;base64,{}", general_purpose::STANDARD.encode(content)))
     }).await.map_err(|e| e.to_string())?;
-    
+
+    if let (Some(key), Ok(data_url)) = (cache_key, &result) {
+        VIEWER_CACHE.lock().unwrap().insert(key, data_url.clone());
+    }
+
     result
 }
 
 #[tauri::command]
-async fn get_jxl_preview(path: String) -> Result<String, String> {
+async fn get_jxl_preview(path: String, file_id: Option<String>, revision: Option<u64>) -> Result<String, String> {
     use jxl_oxide::JxlImage;
     use image::DynamicImage;
     use std::io::Cursor;
@@ -390,6 +1119,13 @@ async fn get_jxl_preview(path: String) -> Result<String, String> {
     use fast_image_resize as fr;
     use std::num::NonZeroU32;
 
+    let cache_key = file_id.map(|id| viewer_cache_key(&id, revision.unwrap_or(0)));
+    if let Some(key) = &cache_key {
+        if let Some(hit) = VIEWER_CACHE.lock().unwrap().get(key) {
+            return Ok(hit);
+        }
+    }
+
     // Concurrency limit for heavy decodes
     while ACTIVE_HEAVY_DECODES.load(Ordering::Relaxed) >= MAX_CONCURRENT_HEAVY_DECODES {
         tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
@@ -459,14 +1195,106 @@ async fn get_jxl_preview(path: String) -> Result<String, String> {
     }).await;
 
     ACTIVE_HEAVY_DECODES.fetch_sub(1, Ordering::SeqCst);
+
+    if let (Some(key), Ok(data_url)) = (cache_key, &result) {
+        VIEWER_CACHE.lock().unwrap().insert(key, data_url.clone());
+    }
+
     result
 }
 
 
 #[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
 struct ScanProgress {
     processed: usize,
     total: usize,
+    /// 当前阶段：counting / walking / indexing / dimension-backfill
+    #[serde(skip_serializing_if = "Option::is_none")]
+    phase: Option<&'static str>,
+    /// 当前正在处理的文件路径
+    #[serde(skip_serializing_if = "Option::is_none")]
+    current_path: Option<String>,
+    /// 本阶段吞吐量（文件/秒）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files_per_sec: Option<f64>,
+    /// 预计剩余秒数（吞吐量稳定后才有值）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    eta_seconds: Option<u64>,
+}
+
+impl ScanProgress {
+    /// 只带进度数字的简单事件（用于批量编辑等不分阶段的任务）
+    fn simple(processed: usize, total: usize) -> Self {
+        Self {
+            processed,
+            total,
+            phase: None,
+            current_path: None,
+            files_per_sec: None,
+            eta_seconds: None,
+        }
+    }
+}
+
+/// 扫描进度跟踪器：按阶段计算吞吐量和 ETA，并限制事件发送频率，
+/// 避免长时间 HDD 扫描时高频事件拖慢前端
+struct ScanProgressTracker {
+    app: tauri::AppHandle,
+    phase: &'static str,
+    phase_started: std::time::Instant,
+    last_emit: std::time::Instant,
+}
+
+impl ScanProgressTracker {
+    /// 两次事件之间的最小间隔
+    const MIN_EMIT_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
+
+    fn new(app: tauri::AppHandle, phase: &'static str) -> Self {
+        let now = std::time::Instant::now();
+        Self {
+            app,
+            phase,
+            phase_started: now,
+            // 保证进入新阶段后第一次 report 立即发出
+            last_emit: now - Self::MIN_EMIT_INTERVAL,
+        }
+    }
+
+    /// 切换阶段，吞吐量统计重新开始
+    fn set_phase(&mut self, phase: &'static str) {
+        self.phase = phase;
+        self.phase_started = std::time::Instant::now();
+        self.last_emit = self.phase_started - Self::MIN_EMIT_INTERVAL;
+    }
+
+    /// 上报进度。`force` 为 true 时跳过频率限制（用于阶段起止事件）
+    fn report(&mut self, processed: usize, total: usize, current_path: Option<&str>, force: bool) {
+        if !force && self.last_emit.elapsed() < Self::MIN_EMIT_INTERVAL {
+            return;
+        }
+        self.last_emit = std::time::Instant::now();
+
+        // 吞吐量至少统计半秒，避免启动瞬间的 ETA 大幅抖动
+        let elapsed = self.phase_started.elapsed().as_secs_f64();
+        let files_per_sec = if elapsed >= 0.5 && processed > 0 {
+            Some(processed as f64 / elapsed)
+        } else {
+            None
+        };
+        let eta_seconds = files_per_sec
+            .filter(|fps| *fps > 0.0 && total >= processed)
+            .map(|fps| ((total - processed) as f64 / fps).round() as u64);
+
+        let _ = self.app.emit("scan-progress", ScanProgress {
+            processed,
+            total,
+            phase: Some(self.phase),
+            current_path: current_path.map(|p| p.to_string()),
+            files_per_sec,
+            eta_seconds,
+        });
+    }
 }
 
 #[tauri::command]
@@ -544,7 +1372,7 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
                 if let Ok(md) = e.metadata() {
                     if md.is_dir() { return true; }
                     let ext = e.path().extension().and_then(|s| s.to_str()).map(|s| s.to_lowercase()).unwrap_or_default();
-                    return is_supported_image(&ext);
+                    return is_supported_image(&ext) || model::is_supported_model(&ext);
                 }
                 false
             }).count()
@@ -591,12 +1419,12 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
                     id: entry.file_id.clone(),
                     parent_id: entry.parent_id.clone(),
                     name: entry.name.clone(),
-                    r#type: if entry.file_type == "Image" { FileType::Image } else { FileType::Folder },
+                    r#type: if entry.file_type == "Image" { FileType::Image } else if entry.file_type == "Model" { FileType::Model } else { FileType::Folder },
                     path: f_path.clone(),
                     size: Some(entry.size),
                     children: if entry.file_type == "Folder" { Some(Vec::new()) } else { None },
                     tags: Vec::new(),
-                    url: None, meta: None, description: None, source_url: None, category: None, ai_data: None,
+                    url: None, meta: None, description: None, source_url: None, category: None, ai_data: None, availability: None,
                     created_at: chrono::DateTime::from_timestamp(entry.created_at, 0).map(|dt| dt.to_rfc3339()),
                     updated_at: chrono::DateTime::from_timestamp(entry.modified_at, 0).map(|dt| dt.to_rfc3339()),
                 };
@@ -621,6 +1449,10 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
                         created: chrono::DateTime::from_timestamp(entry.created_at, 0).map(|dt| dt.to_rfc3339()).unwrap_or_default(),
                         modified: chrono::DateTime::from_timestamp(entry.modified_at, 0).map(|dt| dt.to_rfc3339()).unwrap_or_default(),
                         format: entry.format.clone().unwrap_or_else(|| "unknown".to_string()),
+                        bit_depth: entry.bit_depth,
+                        color_space: entry.color_space.clone(),
+                        has_alpha: entry.has_alpha,
+                        is_animated: entry.is_animated,
                     });
                 }
 
@@ -634,7 +1466,7 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
                  let mut root_node = FileNode {
                     id: root_id.clone(), parent_id: None, name: root_path_os.file_name().and_then(|n| n.to_str()).unwrap_or("Root").to_string(),
                     r#type: FileType::Folder, path: normalized_root_path.clone(), size: None, children: Some(Vec::new()), tags: Vec::new(),
-                    url: None, meta: None, description: None, source_url: None, category: None, ai_data: None,
+                    url: None, meta: None, description: None, source_url: None, category: None, ai_data: None, availability: None,
                     created_at: root_metadata.as_ref().and_then(|m| m.created().ok()).and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).and_then(|d| chrono::DateTime::from_timestamp(d.as_secs() as i64, 0)).map(|dt| dt.to_rfc3339()),
                     updated_at: root_metadata.as_ref().and_then(|m| m.modified().ok()).and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).and_then(|d| chrono::DateTime::from_timestamp(d.as_secs() as i64, 0)).map(|dt| dt.to_rfc3339()),
                 };
@@ -694,8 +1526,9 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
             sort_children(&mut all_files);
 
             // 发送 100% 进度
-            let _ = app.emit("scan-progress", ScanProgress { processed: all_files.len(), total: all_files.len() });
-            
+            let mut tracker = ScanProgressTracker::new(app.clone(), "indexing");
+            tracker.report(all_files.len(), all_files.len(), None, true);
+
             return Ok(all_files);
         } else {
              println!("Detected new files in root directory (DB: {}, FS: {}). Creating incremental update...", db_root_children_count, fs_root_count);
@@ -708,7 +1541,7 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
     let mut root_node = FileNode {
         id: root_id.clone(), parent_id: None, name: root_path_os.file_name().and_then(|n| n.to_str()).unwrap_or("Root").to_string(),
         r#type: FileType::Folder, path: normalized_root_path.clone(), size: None, children: Some(Vec::new()), tags: Vec::new(),
-        url: None, meta: None, description: None, source_url: None, category: None, ai_data: None,
+        url: None, meta: None, description: None, source_url: None, category: None, ai_data: None, availability: None,
         created_at: root_metadata.created().ok().and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).and_then(|d| chrono::DateTime::from_timestamp(d.as_secs() as i64, 0)).map(|dt| dt.to_rfc3339()),
         updated_at: root_metadata.modified().ok().and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).and_then(|d| chrono::DateTime::from_timestamp(d.as_secs() as i64, 0)).map(|dt| dt.to_rfc3339()),
     };
@@ -734,7 +1567,11 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
         jwalk::Parallelism::RayonNewPool(16)
     };
 
+    let mut tracker = ScanProgressTracker::new(app.clone(), "counting");
+
     let total_images = if force {
+        // 预先数总量；期间没有增量数字，先发一个 counting 阶段事件让 UI 有反馈
+        tracker.report(0, 0, None, true);
         jwalk::WalkDir::new(&path)
             .parallelism(count_parallelism)
             .process_read_dir(|_, _, _, dir_entry_results| {
@@ -751,7 +1588,7 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
                 let entry = e.ok()?;
                 if entry.file_type().is_file() {
                     let ext = entry.path().extension()?.to_str()?.to_lowercase();
-                    if is_supported_image(&ext) { return Some(1); }
+                    if is_supported_image(&ext) || model::is_supported_model(&ext) { return Some(1); }
                 }
                 None
             })
@@ -762,11 +1599,16 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
     };
 
     let (tx, rx) = crossbeam_channel::unbounded::<(String, FileNode, String)>();
-    let _ = app.emit("scan-progress", ScanProgress { processed: 0, total: total_images });
+    tracker.set_phase("walking");
+    tracker.report(0, total_images, None, true);
 
     let producer_path = path.clone();
     let cached_index_arc = Arc::new(cached_index_map);
 
+    // 收集被跳过的路径（无权限 / 路径过长 / 目录循环），扫描结束后汇报
+    let skip_log: Arc<Mutex<Vec<ScanSkip>>> = Arc::new(Mutex::new(Vec::new()));
+    let skip_log_producer = skip_log.clone();
+
     // HDD优化：检测是否为HDD并调整并行度
     // 在HDD上，高并行度会导致磁头竞争，降低性能
     let scan_parallelism = if is_likely_hdd(&producer_path) {
@@ -777,6 +1619,8 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
     };
 
     std::thread::spawn(move || {
+        // 扫描属于后台 I/O，提示系统给前台让路
+        io_throttle::lower_io_priority();
         let normalized_root = normalize_path(&producer_path);
         let root_p_local = Path::new(&producer_path);
 
@@ -786,19 +1630,41 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
                 dir_entry_results.retain(|result| {
                     result.as_ref().map(|entry| {
                         let name = entry.file_name().to_str().unwrap_or("");
-                        // 彻底不进入 .Aurora_Cache 和其他隐藏文件夹的子目录
-                        name != ".Aurora_Cache" && !(name.starts_with('.') && name != ".pixcall")
+                        // 彻底不进入 .Aurora_Cache 和其他隐藏文件夹的子目录；
+                        // 隐藏判定包含 Windows 的 HIDDEN / SYSTEM 属性，受设置开关控制
+                        if name == ".Aurora_Cache" { return false; }
+                        if name == ".pixcall" { return true; }
+                        !fs_attrs::should_hide(name, &entry.path())
                     }).unwrap_or(true)
                 });
             })
             .into_iter()
             .filter_map(|entry_result| {
-                let entry = entry_result.ok()?;
+                // 应用正在退出：快速清空剩余条目，让扫描线程尽早结束
+                if shutdown::is_requested() { return None; }
+                let entry = match entry_result {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        record_scan_skip(&skip_log_producer, classify_walk_error(&e));
+                        return None;
+                    }
+                };
                 let entry_path = entry.path();
                 if entry_path == root_p_local { return None; }
 
                 let full_path = normalize_path(entry_path.to_str()?);
-                let metadata = entry.metadata().ok()?;
+                io_throttle::throttle_op();
+                let metadata = match entry.metadata() {
+                    Ok(metadata) => metadata,
+                    Err(e) => {
+                        let mut skip = classify_walk_error(&e);
+                        if skip.path.is_empty() {
+                            skip.path = full_path.clone();
+                        }
+                        record_scan_skip(&skip_log_producer, skip);
+                        return None;
+                    }
+                };
                 let p_path = entry_path.parent().map(|p| normalize_path(p.to_str().unwrap_or(""))).unwrap_or(normalized_root.clone());
                 
                 let is_directory = metadata.is_dir();
@@ -813,6 +1679,7 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
                 let mut width = 0;
                 let mut height = 0;
                 let mut has_cached_dims = false;
+                let mut header = ImageHeaderInfo::default();
 
                 if let Some(c) = cached {
                     if c.modified_at == mtime && c.size == metadata.len() {
@@ -823,6 +1690,13 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
                                 has_cached_dims = true;
                             }
                         }
+                        // 文件没变，文件头信息也直接复用
+                        header = ImageHeaderInfo {
+                            bit_depth: c.bit_depth,
+                            color_space: c.color_space.clone(),
+                            has_alpha: c.has_alpha,
+                            is_animated: c.is_animated,
+                        };
                     }
                 }
 
@@ -831,7 +1705,7 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
                 if is_directory {
                     let folder_node = FileNode {
                         id: file_id.clone(), parent_id: None, name: file_name, r#type: FileType::Folder, path: full_path.clone(),
-                        size: None, children: Some(Vec::new()), tags: Vec::new(), url: None, meta: None, description: None, source_url: None, category: None, ai_data: None,
+                        size: None, children: Some(Vec::new()), tags: Vec::new(), url: None, meta: None, description: None, source_url: None, category: None, ai_data: None, availability: None,
                         created_at: metadata.created().ok().and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).and_then(|d| chrono::DateTime::from_timestamp(d.as_secs() as i64, 0)).map(|dt| dt.to_rfc3339()),
                         updated_at: chrono::DateTime::from_timestamp(mtime, 0).map(|dt| dt.to_rfc3339()),
                     };
@@ -839,19 +1713,27 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
                 } else if is_supported_image(&extension) {
                     // 如果没有缓存可复用维度，且处于强制扫描模式（通常是欢迎界面或手动刷新），
                     // 我们直接在这里同步读取维度，这样最终写入数据库的就是完整信息。
-                    if !has_cached_dims && force {
+                    // 云盘占位文件读内容会触发下载，只记录元数据不读文件头
+                    if !has_cached_dims && force && !fs_attrs::is_offline_placeholder(&entry_path) {
+                         // 只读文件头，按 64KB 记账
+                         io_throttle::throttle(64 * 1024);
                          let dims = get_image_dimensions(&entry_path.to_string_lossy());
                          width = dims.0;
                          height = dims.1;
+                         header = probe_image_header(&entry_path.to_string_lossy());
                     }
 
                     let image_node = FileNode {
                         id: file_id.clone(), parent_id: None, name: file_name.to_string(), r#type: FileType::Image, path: full_path.clone(),
-                        size: Some(metadata.len()), children: None, tags: Vec::new(), url: None, description: None, source_url: None, category: None, ai_data: None,
+                        size: Some(metadata.len()), children: None, tags: Vec::new(), url: None, description: None, source_url: None, category: None, ai_data: None, availability: fs_attrs::availability_of(&entry_path),
                         created_at: metadata.created().ok().and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).and_then(|d| chrono::DateTime::from_timestamp(d.as_secs() as i64, 0)).map(|dt| dt.to_rfc3339()),
                         updated_at: chrono::DateTime::from_timestamp(mtime, 0).map(|dt| dt.to_rfc3339()),
                         meta: Some(ImageMeta {
                             width, height, size_kb: (metadata.len() / 1024) as u32, format: extension,
+                            bit_depth: header.bit_depth,
+                            color_space: header.color_space.clone(),
+                            has_alpha: header.has_alpha,
+                            is_animated: header.is_animated,
                             created: metadata.created().ok()
                                 .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
                                 .map(|d| d.as_secs() as i64)
@@ -862,6 +1744,15 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
                         }),
                     };
                     Some((file_id, image_node, p_path))
+                } else if model::is_supported_model(&extension) {
+                    // 模型文件只记基础信息，静帧由前端按需触发生成
+                    let model_node = FileNode {
+                        id: file_id.clone(), parent_id: None, name: file_name.to_string(), r#type: FileType::Model, path: full_path.clone(),
+                        size: Some(metadata.len()), children: None, tags: Vec::new(), url: None, meta: None, description: None, source_url: None, category: None, ai_data: None, availability: fs_attrs::availability_of(&entry_path),
+                        created_at: metadata.created().ok().and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).and_then(|d| chrono::DateTime::from_timestamp(d.as_secs() as i64, 0)).map(|dt| dt.to_rfc3339()),
+                        updated_at: chrono::DateTime::from_timestamp(mtime, 0).map(|dt| dt.to_rfc3339()),
+                    };
+                    Some((file_id, model_node, p_path))
                 } else { None }
             })
             .for_each(|item| {
@@ -915,8 +1806,9 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
         if matches!(node.r#type, FileType::Image) {
             processed_count += 1;
             if !force && processed_count > current_total { current_total = processed_count; }
-            if force && processed_count % 500 == 0 {
-                let _ = app.emit("scan-progress", ScanProgress { processed: processed_count, total: current_total });
+            if force {
+                // 发送频率由 tracker 限制，带当前路径/吞吐量/ETA
+                tracker.report(processed_count, current_total, Some(&node.path), false);
             }
         }
 
@@ -930,8 +1822,12 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
             parent_id: None, // 稍后修正
             path: node.path.clone(),
             name: node.name.clone(),
-            file_type: match node.r#type { FileType::Image => "Image".to_string(), FileType::Folder => "Folder".to_string(), _ => "Unknown".to_string() },
+            file_type: match node.r#type { FileType::Image => "Image".to_string(), FileType::Folder => "Folder".to_string(), FileType::Model => "Model".to_string(), _ => "Unknown".to_string() },
             size: node.size.unwrap_or(0), width: w, height: h, format: fmt,
+            bit_depth: node.meta.as_ref().and_then(|m| m.bit_depth),
+            color_space: node.meta.as_ref().and_then(|m| m.color_space.clone()),
+            has_alpha: node.meta.as_ref().and_then(|m| m.has_alpha),
+            is_animated: node.meta.as_ref().and_then(|m| m.is_animated),
             created_at: c_at, modified_at: m_at, 
         });
 
@@ -967,10 +1863,7 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
     sort_children(&mut all_files);
 
     // 扫描完成后，发送最终进度（确保显示实际数量）
-    let _ = app.emit("scan-progress", ScanProgress {
-        processed: processed_count,
-        total: current_total,
-    });
+    tracker.report(processed_count, current_total, None, true);
 
     // 扫描完成后的日志
     eprintln!("[Scan Complete] Total received: {}, Total files in map: {}, Expected: {}",
@@ -982,6 +1875,23 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
         eprintln!("[Scan Warning] Consider checking disk health or using SSD for better performance.");
     }
 
+    // 汇报并持久化本次扫描跳过的路径（生产者线程已结束，skip_log 不再变化）
+    {
+        let skipped = std::mem::take(&mut *skip_log.lock().unwrap());
+        if !skipped.is_empty() {
+            log::warn!("[Scan] {} 个路径被跳过（无权限 / 路径过长 / 目录循环）", skipped.len());
+        }
+        let report = ScanReport {
+            root: normalized_root_path.clone(),
+            finished_at: chrono::Utc::now().timestamp(),
+            skipped,
+        };
+        if let Ok(content) = serde_json::to_string(&report) {
+            let _ = fs::write(scan_report_path(&app), content);
+        }
+        let _ = app.emit("scan-report", report);
+    }
+
     // 6. 后台增量补全逻辑
     let mut to_process: Vec<String> = Vec::new();
     if std::env::var("AURORA_DISABLE_BACKGROUND_INDEX").as_deref().ok() != Some("1") {
@@ -999,22 +1909,85 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
     
     tokio::task::spawn_blocking(move || {
         let mut conn = app_db_inner.get_connection();
-        let _ = db::file_index::batch_upsert(&mut conn, &entries_to_save);
+        if is_low_memory() {
+            // 低内存模式下分块写入，避免一次性事务占用过大内存
+            for chunk in entries_to_save.chunks(2000) {
+                let _ = db::file_index::batch_upsert(&mut conn, chunk);
+            }
+        } else {
+            let _ = db::file_index::batch_upsert(&mut conn, &entries_to_save);
+        }
         let _ = db::file_index::delete_orphaned_entries(&mut conn, &root_to_clean, &scanned_paths);
+        let _ = db::activity_log::record(
+            &conn,
+            "scan",
+            &root_to_clean,
+            Some(&format!("索引 {} 项", entries_to_save.len())),
+            "background",
+        );
+        // 顺带控制日志表大小
+        let _ = db::activity_log::prune(&conn, 10000);
     });
 
-    // 8. 处理后台补充逻辑
+    // 8. 处理后台补充逻辑：先把待补全路径持久化到队列，再启动可恢复任务。
+    // 应用中途退出时，剩余路径仍在队列里，下次启动会继续处理
     if !to_process.is_empty() {
         let pool = app.state::<AppDbPool>().inner().clone();
-        let app_handle = app.clone();
-        tokio::spawn(async move {
-            let batch_size: usize = std::env::var("AURORA_INDEX_BATCH_SIZE").ok().and_then(|s| s.parse().ok()).unwrap_or(200);
-            let batch_delay_ms: u64 = std::env::var("AURORA_INDEX_BATCH_DELAY_MS").ok().and_then(|s| s.parse().ok()).unwrap_or(50);
+        let _ = tokio::task::spawn_blocking(move || {
+            let mut conn = pool.get_connection();
+            db::file_index::enqueue_backfill(&mut conn, &to_process)
+        }).await;
+        spawn_dimension_backfill(app.clone());
+    }
+
+    Ok(all_files)
+}
+
+/// 尺寸补全任务是否正在运行（避免重复启动）
+static BACKFILL_RUNNING: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// 启动（或恢复）尺寸补全任务。
+/// 待处理路径保存在 dimension_backfill_queue 表中，每处理完一批就出队，
+/// 因此应用中途退出后下次调用本函数即可从断点继续。
+fn spawn_dimension_backfill(app: tauri::AppHandle) {
+    if BACKFILL_RUNNING.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+        // 已有任务在运行，新入队的路径会被它的下一轮循环取到
+        return;
+    }
 
-            for chunk in to_process.chunks(batch_size) {
+    let pool = app.state::<AppDbPool>().inner().clone();
+    tokio::spawn(async move {
+        let batch_size: usize = std::env::var("AURORA_INDEX_BATCH_SIZE").ok().and_then(|s| s.parse().ok()).unwrap_or(200);
+        let batch_delay_ms: u64 = std::env::var("AURORA_INDEX_BATCH_DELAY_MS").ok().and_then(|s| s.parse().ok()).unwrap_or(50);
+
+        loop {
+            // 应用正在退出：队列留在表里，下次启动从断点继续
+            if shutdown::is_requested() {
+                break;
+            }
+            // 每轮从队列重新读取，处理期间新入队的路径下一轮也会被取到
+            let pool_q = pool.clone();
+            let pending = tokio::task::spawn_blocking(move || {
+                let conn = pool_q.get_connection();
+                db::file_index::get_backfill_paths(&conn).unwrap_or_default()
+            }).await.unwrap_or_default();
+
+            if pending.is_empty() {
+                break;
+            }
+
+            let backfill_total = pending.len();
+            let mut backfill_done = 0usize;
+            let mut tracker = ScanProgressTracker::new(app.clone(), "dimension-backfill");
+
+            for chunk in pending.chunks(batch_size) {
+                // 批次之间响应关停请求，已出队的批次不受影响
+                if shutdown::is_requested() {
+                    break;
+                }
                 let chunk_vec: Vec<String> = chunk.to_vec();
                 let pool_clone = pool.clone();
-                let app_handle_clone = app_handle.clone();
+                let app_handle_clone = app.clone();
                 let _ = tokio::task::spawn_blocking(move || {
                     let mut conn = pool_clone.get_connection();
                     let mut entries = Vec::new();
@@ -1026,13 +1999,16 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
                                     let id = generate_id(path);
                                     let name = std::path::Path::new(path).file_name().and_then(|n| n.to_str()).unwrap_or("").to_string();
                                     let fmt = std::path::Path::new(path).extension().and_then(|e| e.to_str()).map(|s| s.to_string());
-                                    
+
                                     let c_at = md.created().ok().and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).map(|d| d.as_secs() as i64).unwrap_or(0);
                                     let m_at = md.modified().ok().and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).map(|d| d.as_secs() as i64).unwrap_or(0);
 
+                                    let header = probe_image_header(path);
                                     entries.push(db::file_index::FileIndexEntry {
                                         file_id: id, parent_id: None, path: path.clone(), name, file_type: "Image".to_string(),
                                         size: md.len(), width: Some(w), height: Some(h), format: fmt, created_at: c_at, modified_at: m_at,
+                                        bit_depth: header.bit_depth, color_space: header.color_space,
+                                        has_alpha: header.has_alpha, is_animated: header.is_animated,
                                     });
                                 }
                             }
@@ -1043,13 +2019,102 @@ async fn scan_directory(path: String, force_rescan: Option<bool>, app: tauri::Ap
                         // 通知前端这些文件的元数据已更新
                         let _ = app_handle_clone.emit("metadata-updated", &entries);
                     }
+                    // 无论成功与否都出队：文件已不存在或无法解码时不再反复重试
+                    let _ = db::file_index::dequeue_backfill(&mut conn, &chunk_vec);
                 }).await.ok();
+
+                backfill_done += chunk.len();
+                tracker.report(backfill_done, backfill_total, chunk.last().map(|s| s.as_str()), backfill_done == backfill_total);
+
                 tokio::time::sleep(std::time::Duration::from_millis(batch_delay_ms)).await;
             }
-        });
-    }
+        }
 
-    Ok(all_files)
+        BACKFILL_RUNNING.store(false, Ordering::SeqCst);
+    });
+}
+
+/// 汇总各后台任务的状态（队列深度、吞吐量、最近错误、预计剩余时间），
+/// 供设置面板展示"索引健康度"仪表盘
+#[tauri::command]
+async fn get_background_status(
+    pool: tauri::State<'_, AppDbPool>,
+    color_db_pool: tauri::State<'_, Arc<color_db::ColorDbPool>>,
+) -> Result<serde_json::Value, String> {
+    let pool = pool.inner().clone();
+    let color_pool = color_db_pool.inner().clone();
+
+    // 数据库侧的队列深度统计
+    let (color_pending, color_errors, backfill_queued, image_total) =
+        tokio::task::spawn_blocking(move || {
+            let (pending, errors) = {
+                let mut cconn = color_pool.get_connection();
+                (
+                    color_db::get_pending_files_count(&mut cconn).unwrap_or(0),
+                    color_db::get_error_files_count(&mut cconn).unwrap_or(0),
+                )
+            };
+            let conn = pool.get_connection();
+            let backfill: i64 = conn
+                .query_row("SELECT COUNT(*) FROM dimension_backfill_queue", [], |row| row.get(0))
+                .unwrap_or(0);
+            let images: i64 = conn
+                .query_row("SELECT COUNT(*) FROM file_index WHERE file_type = 'Image'", [], |row| row.get(0))
+                .unwrap_or(0);
+            (pending, errors, backfill, images)
+        })
+        .await
+        .map_err(|e| format!("Failed to collect background status: {}", e))?;
+
+    let (color_processed, color_rate, color_last_error) = color_worker::worker_stats();
+    let color_eta_seconds = if color_rate > 0.0 {
+        (color_pending as f64 / color_rate).ceil() as u64
+    } else {
+        0
+    };
+
+    // 嵌入数量（CLIP 管理器可能尚未初始化，用 -1 表示未知）
+    let embedded_count = match clip::get_clip_manager().await {
+        Some(manager) => {
+            let guard = manager.read().await;
+            guard
+                .embedding_store()
+                .and_then(|store| store.get_embedding_count().ok())
+                .unwrap_or(-1)
+        }
+        None => -1,
+    };
+    let embeddings_pending = if embedded_count >= 0 {
+        (image_total - embedded_count).max(0)
+    } else {
+        -1
+    };
+
+    Ok(serde_json::json!({
+        "color": {
+            "pending": color_pending,
+            "errors": color_errors,
+            "paused": color_worker::is_paused(),
+            "buildingIndex": color_worker::is_color_index_building(),
+            "processedSinceStart": color_processed,
+            "filesPerSec": color_rate,
+            "etaSeconds": color_eta_seconds,
+            "lastError": color_last_error,
+        },
+        "embeddings": {
+            "totalImages": image_total,
+            "embedded": embedded_count,
+            "pending": embeddings_pending,
+            "paused": PAUSE_GENERATION.load(Ordering::SeqCst),
+        },
+        "thumbnails": {
+            "pregenRunning": thumbnail::is_pregenerating(),
+        },
+        "backfill": {
+            "queued": backfill_queued,
+            "running": BACKFILL_RUNNING.load(Ordering::SeqCst),
+        },
+    }))
 }
 
 fn sort_children(all_files: &mut HashMap<String, FileNode>) {
@@ -1063,10 +2128,10 @@ fn sort_children(all_files: &mut HashMap<String, FileNode>) {
                 match (a_node, b_node) {
                     (Some(a_n), Some(b_n)) => {
                         match (&a_n.r#type, &b_n.r#type) {
-                            (FileType::Folder, FileType::Folder) => a_n.name.cmp(&b_n.name),
+                            (FileType::Folder, FileType::Folder) => natural_sort::natural_cmp(&a_n.name, &b_n.name),
                             (FileType::Folder, _) => std::cmp::Ordering::Less,
                             (_, FileType::Folder) => std::cmp::Ordering::Greater,
-                            _ => a_n.name.cmp(&b_n.name),
+                            _ => natural_sort::natural_cmp(&a_n.name, &b_n.name),
                         }
                     }
                     _ => std::cmp::Ordering::Equal,
@@ -1077,6 +2142,12 @@ fn sort_children(all_files: &mut HashMap<String, FileNode>) {
     }
 }
 
+/// 切换中文是否按拼音排序（影响 sort_children 和 SQL 的 NATURAL collation）
+#[tauri::command]
+fn set_natural_sort_pinyin(enabled: bool) {
+    natural_sort::set_pinyin_enabled(enabled);
+}
+
 #[tauri::command]
 async fn force_rescan(path: String, app: tauri::AppHandle) -> Result<HashMap<String, FileNode>, String> {
     // Wrapper that forces a full rescan by forwarding to scan_directory with force_rescan = true
@@ -1112,7 +2183,8 @@ async fn scan_file(file_path: String, parent_id: Option<String>, app: tauri::App
     
     let is_directory = path.is_dir();
     let is_image = is_supported_image(&extension);
-    
+    let is_model = model::is_supported_model(&extension);
+
     let mut result_node = if is_directory {
         // Create folder node
         FileNode {
@@ -1146,11 +2218,13 @@ async fn scan_file(file_path: String, parent_id: Option<String>, app: tauri::App
             source_url: None,
             category: None,
             ai_data: None,
+            availability: None,
         }
     } else if is_image {
         // Create image file node
         let file_size = metadata.len();
         let (width, height) = get_image_dimensions(&path.to_string_lossy());
+        let header = probe_image_header(&path.to_string_lossy());
         
         // Create image file node
         let image_node = FileNode {
@@ -1183,6 +2257,10 @@ async fn scan_file(file_path: String, parent_id: Option<String>, app: tauri::App
                 width,
                 height,
                 size_kb: (file_size / 1024) as u32,
+                bit_depth: header.bit_depth,
+                color_space: header.color_space,
+                has_alpha: header.has_alpha,
+                is_animated: header.is_animated,
                 created: metadata
                     .created()
                     .ok()
@@ -1207,6 +2285,7 @@ async fn scan_file(file_path: String, parent_id: Option<String>, app: tauri::App
             source_url: None,
             category: None,
             ai_data: None,
+            availability: fs_attrs::availability_of(path),
         };
         
         // Add image to color database
@@ -1227,14 +2306,14 @@ async fn scan_file(file_path: String, parent_id: Option<String>, app: tauri::App
         
         image_node
     } else {
-        // Create unknown file node
+        // Create unknown file node (模型文件同结构，只是类型标成 Model)
         let file_size = metadata.len();
-        
+
         FileNode {
             id: file_id,
             parent_id,
             name: file_name,
-            r#type: FileType::Unknown,
+            r#type: if is_model { FileType::Model } else { FileType::Unknown },
             path: normalize_path(&file_path),
             size: Some(file_size),
             children: None,
@@ -1261,6 +2340,7 @@ async fn scan_file(file_path: String, parent_id: Option<String>, app: tauri::App
             source_url: None,
             category: None,
             ai_data: None,
+            availability: None,
         }
     };
 
@@ -1297,9 +2377,13 @@ async fn scan_file(file_path: String, parent_id: Option<String>, app: tauri::App
                 parent_id: node_clone.parent_id,
                 path: node_clone.path,
                 name: node_clone.name,
-                file_type: match node_clone.r#type { FileType::Image => "Image".to_string(), FileType::Folder => "Folder".to_string(), _ => "Unknown".to_string() },
+                file_type: match node_clone.r#type { FileType::Image => "Image".to_string(), FileType::Folder => "Folder".to_string(), FileType::Model => "Model".to_string(), _ => "Unknown".to_string() },
                 size: node_clone.size.unwrap_or(0),
                 width: w, height: h, format: fmt,
+                bit_depth: node_clone.meta.as_ref().and_then(|m| m.bit_depth),
+                color_space: node_clone.meta.as_ref().and_then(|m| m.color_space.clone()),
+                has_alpha: node_clone.meta.as_ref().and_then(|m| m.has_alpha),
+                is_animated: node_clone.meta.as_ref().and_then(|m| m.is_animated),
                 created_at: c_at, modified_at: m_at, 
             };
             let _ = db::file_index::batch_upsert(&mut conn, &[entry]);
@@ -1365,12 +2449,9 @@ async fn get_dominant_colors(
             {
                 let mut conn = pool.get_connection();
                 // 先检查是否存在记录
-                match color_db::get_colors_by_file_path(&mut conn, &file_path_for_save) {
-                    Ok(None) => {
-                        // 不存在记录，插入待处理状态
-                        let _ = color_db::add_pending_files(&mut conn, &[file_path_for_save.clone()]);
-                    },
-                    _ => {}
+                if let Ok(None) = color_db::get_colors_by_file_path(&mut conn, &file_path_for_save) {
+                    // 不存在记录，插入待处理状态
+                    let _ = color_db::add_pending_files(&mut conn, std::slice::from_ref(&file_path_for_save));
                 }
             } // Drop lock
             
@@ -1395,7 +2476,7 @@ async fn add_pending_files_to_db(
         let mut conn = pool.get_connection();
         
         for chunk in file_paths.chunks(batch_size) {
-            let chunk_vec: Vec<String> = chunk.iter().cloned().collect();
+            let chunk_vec: Vec<String> = chunk.to_vec();
             
             match color_db::add_pending_files(&mut conn, &chunk_vec) {
                 Ok(count) => total += count,
@@ -1453,12 +2534,14 @@ async fn create_folder(path: String, app: tauri::AppHandle) -> Result<(), String
         file_type: "Folder".to_string(),
         size: 0,
         width: None, height: None, format: None,
+        bit_depth: None, color_space: None, has_alpha: None, is_animated: None,
         created_at: md.as_ref().and_then(|m| m.created().ok()).and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).map(|d| d.as_secs() as i64).unwrap_or(0),
         modified_at: md.as_ref().and_then(|m| m.modified().ok()).and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).map(|d| d.as_secs() as i64).unwrap_or(0),
     };
     
     let _ = db::file_index::batch_upsert(&mut conn, &[entry]);
-    
+    let _ = db::activity_log::record(&conn, "add", &normalize_path(&path), None, "user");
+
     Ok(())
 }
 
@@ -1535,6 +2618,13 @@ async fn rename_file(old_path: String, new_path: String, app: tauri::AppHandle)
         }
     });
 
+    // 记录到操作日志，支持撤销
+    undo::record(undo::Operation::Rename {
+        old_path: old_path.clone(),
+        new_path: new_path.clone(),
+    });
+    log_activity(&app, "rename", &old_path, Some(&new_path), "user");
+
     Ok(())
 }
 
@@ -1554,85 +2644,477 @@ async fn db_copy_file_metadata(src_path: String, dest_path: String, app: tauri::
         let _ = db::file_metadata::copy_metadata(&conn, &old_id, &new_id, &dest_path);
     }
 
-    // 2. 同步颜色数据库
-    let color_db = app.state::<Arc<color_db::ColorDbPool>>().inner();
-    let _ = color_db.copy_colors(&src_path, &dest_path);
+    // 2. 同步颜色数据库
+    let color_db = app.state::<Arc<color_db::ColorDbPool>>().inner();
+    let _ = color_db.copy_colors(&src_path, &dest_path);
+
+    // [New] 3. 同步索引数据库 (file_index) - 这是解决启动降级的关键
+    // 获取源文件的索引信息作为模板
+    let src_normalized = normalize_path(&src_path);
+    let dest_normalized = normalize_path(&dest_path);
+    
+    let mut conn_mut = app_db.get_connection(); // 需要可变连接用于 upsert
+    
+    if is_dir {
+        // 如果是文件夹复制，逻辑较复杂，建议直接让下一次扫描处理，或者递归更新索引
+        // 这里简单处理：清理掉目标路径的旧索引，迫使下次扫描该路径
+        let _ = db::file_index::delete_entries_by_path(&conn_mut, &dest_normalized);
+    } else {
+        // 如果是单文件复制，我们直接插入一条新索引
+        if let Ok(md) = fs::metadata(dest_p) {
+            let new_id = generate_id(&dest_normalized);
+            let file_name = dest_p.file_name().and_then(|n| n.to_str()).unwrap_or("").to_string();
+            let ext = dest_p.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).unwrap_or_default();
+            
+            // 尝试获取尺寸（如果之前有的话）
+            let mut width = None;
+            let mut height = None;
+            let mut format = None;
+            let mut header = ImageHeaderInfo::default();
+
+            let all_entries = db::file_index::get_entries_under_path(&conn_mut, &src_normalized).unwrap_or_default();
+            if let Some(src_entry) = all_entries.iter().find(|e| e.path == src_normalized) {
+                width = src_entry.width;
+                height = src_entry.height;
+                format = src_entry.format.clone();
+                header = ImageHeaderInfo {
+                    bit_depth: src_entry.bit_depth,
+                    color_space: src_entry.color_space.clone(),
+                    has_alpha: src_entry.has_alpha,
+                    is_animated: src_entry.is_animated,
+                };
+            }
+
+            let new_entry = db::file_index::FileIndexEntry {
+                file_id: new_id,
+                parent_id: dest_p.parent().map(|p| generate_id(&normalize_path(p.to_str().unwrap_or("")))),
+                path: dest_normalized,
+                name: file_name,
+                file_type: "Image".to_string(),
+                size: md.len(),
+                width, height, format: format.or(Some(ext)),
+                bit_depth: header.bit_depth, color_space: header.color_space,
+                has_alpha: header.has_alpha, is_animated: header.is_animated,
+                created_at: md.created().ok().and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).map(|d| d.as_secs() as i64).unwrap_or(0),
+                modified_at: md.modified().ok().and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).map(|d| d.as_secs() as i64).unwrap_or(0),
+            };
+            
+            let _ = db::file_index::batch_upsert(&mut conn_mut, &[new_entry]);
+        }
+    }
+
+    Ok(())
+}
+
+// Command to delete a file or folder
+#[tauri::command]
+async fn delete_file(path: String, app: tauri::AppHandle) -> Result<(), String> {
+    let file_path = Path::new(&path);
+
+    // 伴生文件（RAW/txt/xmp）跟随图片一起删除
+    let companions = if file_path.is_file()
+        && is_supported_image(file_path.extension().and_then(|e| e.to_str()).unwrap_or(""))
+    {
+        companion_files(&path)
+    } else {
+        Vec::new()
+    };
+
+    if file_path.is_dir() {
+        // Delete directory recursively
+        fs::remove_dir_all(file_path)
+            .map_err(|e| format!("Failed to delete directory: {}{}", e, file_lock::lock_hint(&path)))?;
+    } else {
+        // Delete file
+        fs::remove_file(file_path)
+            .map_err(|e| format!("Failed to delete file: {}{}", e, file_lock::lock_hint(&path)))?;
+    }
+
+    for companion in &companions {
+        let _ = fs::remove_file(companion);
+    }
+
+    // 同步清理数据库记录
+    let app_db = app.state::<AppDbPool>();
+    let conn = app_db.get_connection();
+    for p in std::iter::once(&path).chain(companions.iter()) {
+        let _ = db::file_index::delete_entries_by_path(&conn, p);
+        let _ = db::file_metadata::delete_metadata_by_path(&conn, p);
+        let _ = db::decode_errors::delete_errors_by_path(&conn, p);
+    }
+
+    let _ = db::activity_log::record(&conn, "delete", &normalize_path(&path), None, "user");
+
+    let color_db = app.state::<Arc<color_db::ColorDbPool>>().inner();
+    let _ = color_db.delete_colors_by_path(&path);
+
+    Ok(())
+}
+
+/// 清理候选：空文件夹、零字节文件、索引中存在但磁盘缺失的条目
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CleanupPlan {
+    /// 空文件夹（含只剩空子文件夹的），子文件夹排在父文件夹前面
+    empty_folders: Vec<String>,
+    zero_byte_files: Vec<String>,
+    /// 索引里有记录但磁盘上已不存在的路径
+    missing_entries: Vec<String>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CleanupReport {
+    removed_folders: usize,
+    removed_files: usize,
+    removed_entries: usize,
+}
+
+/// 递归收集空文件夹和零字节文件。
+/// 返回该目录是否"有效为空"（只包含有效为空的子文件夹）；
+/// 空文件夹按后序压入，子文件夹排在父文件夹前面，删除时按序即可
+fn collect_cleanup_candidates(dir: &Path, plan: &mut CleanupPlan) -> bool {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+    let mut effectively_empty = true;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let Some(path_str) = path.to_str() else {
+            effectively_empty = false;
+            continue;
+        };
+        if path_str.contains(".Aurora_Cache") {
+            effectively_empty = false;
+            continue;
+        }
+        if path.is_dir() {
+            let child_empty = collect_cleanup_candidates(&path, plan);
+            if child_empty {
+                plan.empty_folders.push(normalize_path(path_str));
+            } else {
+                effectively_empty = false;
+            }
+        } else {
+            if entry.metadata().map(|m| m.len() == 0).unwrap_or(false) {
+                plan.zero_byte_files.push(normalize_path(path_str));
+            }
+            effectively_empty = false;
+        }
+    }
+    effectively_empty
+}
+
+/// 扫描 root 下的清理候选：空文件夹、零字节文件、索引中的孤儿记录。
+/// 只做收集不做删除，结果交给 apply_cleanup 执行
+#[tauri::command]
+async fn find_cleanup_candidates(
+    root: String,
+    pool: tauri::State<'_, AppDbPool>,
+) -> Result<CleanupPlan, String> {
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let mut plan = CleanupPlan {
+            empty_folders: Vec::new(),
+            zero_byte_files: Vec::new(),
+            missing_entries: Vec::new(),
+        };
+        let root_path = Path::new(&root);
+        if !root_path.is_dir() {
+            return Err(format!("不是文件夹: {}", root));
+        }
+        collect_cleanup_candidates(root_path, &mut plan);
+
+        // 索引里有记录但磁盘上已不存在的路径
+        let conn = pool.get_connection();
+        let entries = db::file_index::get_entries_under_path(&conn, &root)
+            .map_err(|e| e.to_string())?;
+        for entry in entries {
+            if !Path::new(&entry.path).exists() {
+                plan.missing_entries.push(entry.path);
+            }
+        }
+        Ok(plan)
+    })
+    .await
+    .map_err(|e| format!("Cleanup scan task failed: {}", e))?
+}
+
+/// 执行清理计划。dry_run 为 true 时只重新校验并返回将删除的数量。
+/// 每一项执行前都重新校验（空/零字节/缺失），目录状态变化过的条目跳过
+#[tauri::command]
+async fn apply_cleanup(
+    plan: CleanupPlan,
+    dry_run: Option<bool>,
+    pool: tauri::State<'_, AppDbPool>,
+    app: tauri::AppHandle,
+) -> Result<CleanupReport, String> {
+    let dry_run = dry_run.unwrap_or(false);
+    let mut report = CleanupReport {
+        removed_folders: 0,
+        removed_files: 0,
+        removed_entries: 0,
+    };
 
-    // [New] 3. 同步索引数据库 (file_index) - 这是解决启动降级的关键
-    // 获取源文件的索引信息作为模板
-    let src_normalized = normalize_path(&src_path);
-    let dest_normalized = normalize_path(&dest_path);
-    
-    let mut conn_mut = app_db.get_connection(); // 需要可变连接用于 upsert
-    
-    if is_dir {
-        // 如果是文件夹复制，逻辑较复杂，建议直接让下一次扫描处理，或者递归更新索引
-        // 这里简单处理：清理掉目标路径的旧索引，迫使下次扫描该路径
-        let _ = db::file_index::delete_entries_by_path(&conn_mut, &dest_normalized);
-    } else {
-        // 如果是单文件复制，我们直接插入一条新索引
-        if let Ok(md) = fs::metadata(dest_p) {
-            let new_id = generate_id(&dest_normalized);
-            let file_name = dest_p.file_name().and_then(|n| n.to_str()).unwrap_or("").to_string();
-            let ext = dest_p.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).unwrap_or_default();
-            
-            // 尝试获取尺寸（如果之前有的话）
-            let mut width = None;
-            let mut height = None;
-            let mut format = None;
+    // 零字节文件：再次校验后走回收目录（可撤销）
+    for file in &plan.zero_byte_files {
+        let p = Path::new(file);
+        if !p.is_file() || p.metadata().map(|m| m.len() != 0).unwrap_or(true) {
+            continue;
+        }
+        if !dry_run {
+            delete_file_to_trash(file.clone(), app.clone()).await?;
+        }
+        report.removed_files += 1;
+    }
 
-            let all_entries = db::file_index::get_entries_under_path(&conn_mut, &src_normalized).unwrap_or_default();
-            if let Some(src_entry) = all_entries.iter().find(|e| e.path == src_normalized) {
-                width = src_entry.width;
-                height = src_entry.height;
-                format = src_entry.format.clone();
-            }
+    // 空文件夹：列表已保证子在前父在后，按序删除
+    for folder in &plan.empty_folders {
+        let p = Path::new(folder);
+        if !p.is_dir() || fs::read_dir(p).map(|mut d| d.next().is_some()).unwrap_or(true) {
+            continue;
+        }
+        if !dry_run {
+            fs::remove_dir(p).map_err(|e| format!("删除空文件夹失败 {}: {}", folder, e))?;
+            let conn = pool.get_connection();
+            let _ = db::file_index::delete_entries_by_path(&conn, folder);
+            let _ = db::file_metadata::delete_metadata_by_path(&conn, folder);
+        }
+        report.removed_folders += 1;
+    }
 
-            let new_entry = db::file_index::FileIndexEntry {
-                file_id: new_id,
-                parent_id: dest_p.parent().map(|p| generate_id(&normalize_path(p.to_str().unwrap_or("")))),
-                path: dest_normalized,
-                name: file_name,
-                file_type: "Image".to_string(),
-                size: md.len(),
-                width, height, format: format.or(Some(ext)),
-                created_at: md.created().ok().and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).map(|d| d.as_secs() as i64).unwrap_or(0),
-                modified_at: md.modified().ok().and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).map(|d| d.as_secs() as i64).unwrap_or(0),
-            };
-            
-            let _ = db::file_index::batch_upsert(&mut conn_mut, &[new_entry]);
+    // 索引孤儿：磁盘上确实不存在才清
+    for entry in &plan.missing_entries {
+        if Path::new(entry).exists() {
+            continue;
+        }
+        if !dry_run {
+            let conn = pool.get_connection();
+            let _ = db::file_index::delete_entries_by_path(&conn, entry);
+            let _ = db::file_metadata::delete_metadata_by_path(&conn, entry);
+            let _ = db::decode_errors::delete_errors_by_path(&conn, entry);
+            drop(conn);
+            let color_db = app.state::<Arc<color_db::ColorDbPool>>().inner();
+            let _ = color_db.delete_colors_by_path(entry);
         }
+        report.removed_entries += 1;
     }
 
+    Ok(report)
+}
+
+/// 移动文件/目录，跨卷时回退为复制+删除
+fn move_path_with_fallback(src: &Path, dest: &Path) -> Result<(), String> {
+    if fs::rename(src, dest).is_ok() {
+        return Ok(());
+    }
+    // 跨卷移动：复制后删除源
+    if src.is_dir() {
+        copy_dir_all(src, dest).map_err(|e| format!("复制目录失败: {}", e))?;
+        fs::remove_dir_all(src).map_err(|e| format!("删除源目录失败: {}", e))?;
+    } else {
+        fs::copy(src, dest).map_err(|e| format!("复制文件失败: {}", e))?;
+        fs::remove_file(src).map_err(|e| format!("删除源文件失败: {}", e))?;
+    }
     Ok(())
 }
 
-// Command to delete a file or folder
+/// 回收目录（删除的文件先移入这里，以便撤销恢复）
+fn trash_root() -> std::path::PathBuf {
+    std::env::temp_dir().join("aurora_trash")
+}
+
+// 把文件或文件夹移入回收目录（可撤销的删除）
 #[tauri::command]
-async fn delete_file(path: String, app: tauri::AppHandle) -> Result<(), String> {
+async fn delete_file_to_trash(path: String, app: tauri::AppHandle) -> Result<(), String> {
     let file_path = Path::new(&path);
-    if file_path.is_dir() {
-        // Delete directory recursively
-        fs::remove_dir_all(file_path)
-            .map_err(|e| format!("Failed to delete directory: {}", e))?;
-    } else {
-        // Delete file
-        fs::remove_file(file_path)
-            .map_err(|e| format!("Failed to delete file: {}", e))?;
+    if !file_path.exists() {
+        return Err(format!("文件不存在: {}", path));
     }
 
-    // 同步清理数据库记录
+    let trash_dir = trash_root();
+    fs::create_dir_all(&trash_dir).map_err(|e| format!("创建回收目录失败: {}", e))?;
+
+    // 回收路径带时间戳前缀避免重名
+    let file_name = file_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("无效的文件名: {}", path))?;
+    let trash_path = trash_dir.join(format!("{}_{}", chrono::Utc::now().timestamp_millis(), file_name));
+
+    // 伴生文件（RAW/txt/xmp）跟随图片一起进回收目录
+    let companions = if !file_path.is_dir()
+        && is_supported_image(file_path.extension().and_then(|e| e.to_str()).unwrap_or(""))
+    {
+        companion_files(&path)
+    } else {
+        Vec::new()
+    };
+
+    move_path_with_fallback(file_path, &trash_path)?;
+
+    // 同步清理数据库记录（与 delete_file 相同）
     let app_db = app.state::<AppDbPool>();
     let conn = app_db.get_connection();
     let _ = db::file_index::delete_entries_by_path(&conn, &path);
     let _ = db::file_metadata::delete_metadata_by_path(&conn, &path);
-    
+    let _ = db::decode_errors::delete_errors_by_path(&conn, &path);
+
     let color_db = app.state::<Arc<color_db::ColorDbPool>>().inner();
     let _ = color_db.delete_colors_by_path(&path);
+    drop(conn);
+
+    for companion in companions {
+        let Some(name) = Path::new(&companion).file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let companion_trash = trash_dir.join(format!("{}_{}", chrono::Utc::now().timestamp_millis(), name));
+        if move_path_with_fallback(Path::new(&companion), &companion_trash).is_ok() {
+            let conn = app_db.get_connection();
+            let _ = db::file_index::delete_entries_by_path(&conn, &companion);
+            let _ = db::file_metadata::delete_metadata_by_path(&conn, &companion);
+            drop(conn);
+            undo::record(undo::Operation::DeleteToTrash {
+                original_path: companion,
+                trash_path: companion_trash.to_string_lossy().replace('\\', "/"),
+            });
+        }
+    }
+
+    // 记录到操作日志，支持撤销（主文件最后记，单次撤销先恢复主文件）
+    undo::record(undo::Operation::DeleteToTrash {
+        original_path: path.clone(),
+        trash_path: trash_path.to_string_lossy().replace('\\', "/"),
+    });
+    log_activity(&app, "delete", &normalize_path(&path), Some("trash"), "user");
 
     Ok(())
 }
 
+/// 反向执行一条操作（撤销用）。执行期间抑制记录，避免把反向操作又记入日志
+async fn apply_inverse_operation(op: &undo::Operation, app: tauri::AppHandle) -> Result<(), String> {
+    match op {
+        undo::Operation::Rename { old_path, new_path } => {
+            rename_file(new_path.clone(), old_path.clone(), app).await
+        }
+        undo::Operation::Move { src_path, dest_path } => {
+            move_file(dest_path.clone(), src_path.clone(), app).await
+        }
+        undo::Operation::TagChange { file_id, old_tags, .. } => {
+            let app_db = app.state::<AppDbPool>();
+            let conn = app_db.get_connection();
+            conn.execute(
+                "UPDATE file_metadata SET tags = ?1 WHERE file_id = ?2",
+                params![old_tags, file_id],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(())
+        }
+        undo::Operation::RatingChange { file_id, old_rating, .. } => {
+            let app_db = app.state::<AppDbPool>();
+            let conn = app_db.get_connection();
+            conn.execute(
+                "UPDATE file_metadata SET rating = ?1 WHERE file_id = ?2",
+                params![old_rating, file_id],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(())
+        }
+        undo::Operation::DeleteToTrash { original_path, trash_path } => {
+            // 从回收目录移回原位置；下一次扫描会重建索引
+            move_path_with_fallback(Path::new(trash_path), Path::new(original_path))
+        }
+    }
+}
+
+/// 正向执行一条操作（重做用）
+async fn apply_forward_operation(op: &undo::Operation, app: tauri::AppHandle) -> Result<(), String> {
+    match op {
+        undo::Operation::Rename { old_path, new_path } => {
+            rename_file(old_path.clone(), new_path.clone(), app).await
+        }
+        undo::Operation::Move { src_path, dest_path } => {
+            move_file(src_path.clone(), dest_path.clone(), app).await
+        }
+        undo::Operation::TagChange { file_id, new_tags, .. } => {
+            let app_db = app.state::<AppDbPool>();
+            let conn = app_db.get_connection();
+            conn.execute(
+                "UPDATE file_metadata SET tags = ?1 WHERE file_id = ?2",
+                params![new_tags, file_id],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(())
+        }
+        undo::Operation::RatingChange { file_id, new_rating, .. } => {
+            let app_db = app.state::<AppDbPool>();
+            let conn = app_db.get_connection();
+            conn.execute(
+                "UPDATE file_metadata SET rating = ?1 WHERE file_id = ?2",
+                params![new_rating, file_id],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(())
+        }
+        undo::Operation::DeleteToTrash { original_path, trash_path } => {
+            move_path_with_fallback(Path::new(original_path), Path::new(trash_path))
+        }
+    }
+}
+
+/// 撤销最近一次操作，返回被撤销的操作（没有可撤销的操作时返回 None）
+#[tauri::command]
+async fn undo_last_operation(app: tauri::AppHandle) -> Result<Option<undo::Operation>, String> {
+    let Some(op) = undo::take_undo() else {
+        return Ok(None);
+    };
+
+    undo::set_suppressed(true);
+    let result = apply_inverse_operation(&op, app).await;
+    undo::set_suppressed(false);
+
+    match result {
+        Ok(()) => {
+            undo::push_redo(op.clone());
+            Ok(Some(op))
+        }
+        Err(e) => {
+            // 撤销失败，放回日志保持一致
+            undo::restore_undo(op);
+            Err(format!("撤销失败: {}", e))
+        }
+    }
+}
+
+/// 重做最近一次被撤销的操作
+#[tauri::command]
+async fn redo(app: tauri::AppHandle) -> Result<Option<undo::Operation>, String> {
+    let Some(op) = undo::take_redo() else {
+        return Ok(None);
+    };
+
+    undo::set_suppressed(true);
+    let result = apply_forward_operation(&op, app).await;
+    undo::set_suppressed(false);
+
+    match result {
+        Ok(()) => {
+            undo::push_undo(op.clone());
+            Ok(Some(op))
+        }
+        Err(e) => {
+            undo::restore_redo(op);
+            Err(format!("重做失败: {}", e))
+        }
+    }
+}
+
+/// 当前 (可撤销, 可重做) 的数量，供前端置灰按钮
+#[tauri::command]
+fn get_undo_redo_counts() -> (usize, usize) {
+    undo::history_counts()
+}
+
 
 #[tauri::command]
 async fn copy_image_colors(
@@ -1693,6 +3175,86 @@ async fn copy_image_to_clipboard(file_path: String) -> Result<(), String> {
     Ok(())
 }
 
+/// 按样式格式化单个路径（copy_paths_to_clipboard 用）
+fn format_path_for_clipboard(path: &str, style: &str) -> String {
+    match style {
+        "quoted" => format!("\"{}\"", path.replace('"', "\\\"")),
+        "file-uri" => {
+            // RFC 8089：保留字母数字与 / - _ . ~ :，其余百分号编码
+            let mut encoded = String::with_capacity(path.len());
+            for b in path.bytes() {
+                match b {
+                    b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9'
+                    | b'/' | b'-' | b'_' | b'.' | b'~' | b':' => encoded.push(b as char),
+                    _ => encoded.push_str(&format!("%{:02X}", b)),
+                }
+            }
+            // 带盘符的 Windows 路径要有第三个斜杠（file:///C:/...）
+            if encoded.len() >= 2 && encoded.as_bytes()[1] == b':' {
+                format!("file:///{}", encoded)
+            } else {
+                format!("file://{}", encoded)
+            }
+        }
+        "unc" => {
+            let backslashed = path.replace('/', "\\");
+            if backslashed.starts_with("\\\\") {
+                backslashed // 已是网络共享路径
+            } else {
+                format!("\\\\?\\{}", backslashed)
+            }
+        }
+        "name" => Path::new(path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(path)
+            .to_string(),
+        _ => path.to_string(),
+    }
+}
+
+/// 把选中文件的路径按指定样式写入剪贴板，每行一个。
+/// style："plain" | "quoted" | "file-uri" | "unc" | "name"
+#[tauri::command]
+fn copy_paths_to_clipboard(
+    file_ids: Vec<String>,
+    style: String,
+    pool: tauri::State<AppDbPool>,
+) -> Result<usize, String> {
+    let conn = pool.get_connection();
+    let mut lines = Vec::with_capacity(file_ids.len());
+    for id in &file_ids {
+        if let Some(entry) = db::file_index::get_entry_by_id(&conn, id).map_err(|e| e.to_string())? {
+            lines.push(format_path_for_clipboard(&entry.path, &style));
+        }
+    }
+    if lines.is_empty() {
+        return Err("选中的文件都不在索引中".to_string());
+    }
+    let count = lines.len();
+    let mut clipboard = arboard::Clipboard::new()
+        .map_err(|e| format!("Failed to access clipboard: {}", e))?;
+    clipboard
+        .set_text(lines.join("\n"))
+        .map_err(|e| format!("写入剪贴板失败: {}", e))?;
+    Ok(count)
+}
+
+/// 递归复制整个目录（std::fs 没有现成实现）
+fn copy_dir_all(src: &Path, dest: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dest)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let dest_path = dest.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &dest_path)?;
+        } else {
+            fs::copy(entry.path(), &dest_path)?;
+        }
+    }
+    Ok(())
+}
+
 #[tauri::command]
 async fn copy_file(src_path: String, dest_path: String) -> Result<String, String> {
     let src = Path::new(&src_path);
@@ -1833,8 +3395,8 @@ async fn copy_file(src_path: String, dest_path: String) -> Result<String, String
         
         for attempt in 0..max_retries {
             if is_dir {
-                // Use fs::copy_dir_all for directory copying
-                match fs::copy_dir_all(src, dest) {
+                // Use copy_dir_all for directory copying
+                match copy_dir_all(src, dest) {
                     Ok(_) => {
                         let norm = normalize_path(&final_dest_path);
                         println!("Returning normalized path: {}", norm);
@@ -1854,8 +3416,8 @@ async fn copy_file(src_path: String, dest_path: String) -> Result<String, String
                         return Ok(norm);
                     },
                     Err(e) => {
-                        last_error = Some(e);
                         println!("fs::copy attempt {} failed: {:?}", attempt + 1, e);
+                        last_error = Some(e);
                     }
                 }
             }
@@ -1888,6 +3450,14 @@ async fn move_file(src_path: String, dest_path: String, app: tauri::AppHandle) -
     
     let is_dir = src.is_dir();
 
+    // 目标已存在时按设置的后缀风格自动改名，与复制/导入保持一致的行为
+    let dest_path = if !is_dir && dest.exists() && normalize_path(&src_path) != normalize_path(&dest_path) {
+        generate_unique_file_path(&dest_path)
+    } else {
+        dest_path
+    };
+    let dest = Path::new(&dest_path);
+
     // Create dest directory if it doesn't exist
     if let Some(parent) = dest.parent() {
         if !parent.exists() {
@@ -1915,18 +3485,22 @@ async fn move_file(src_path: String, dest_path: String, app: tauri::AppHandle) -
     }
     
     // 如果 rename 失败且不是目录，尝试 copy + delete 兜底
-    if !success && !is_dir {
-        if let Ok(_) = fs::copy(src, dest) {
-            if let Ok(_) = fs::remove_file(src) {
+    if !success && !is_dir
+        && fs::copy(src, dest).is_ok() {
+            if fs::remove_file(src).is_ok() {
                 success = true;
             } else {
                 let _ = fs::remove_file(dest); // 清理副本
             }
         }
-    }
     
     if !success {
-        return Err(format!("无法移动文件/文件夹 (可能被锁定或跨卷): {:?}", last_error));
+        // 查询占用者进程，让 UI 可以提示 "文件正被 XXX 占用"
+        return Err(format!(
+            "无法移动文件/文件夹 (可能被锁定或跨卷): {:?}{}",
+            last_error,
+            file_lock::lock_hint(&src_path)
+        ));
     }
 
     // 物理移动成功后，同步迁移元数据 (避免竞态条件)
@@ -1952,11 +3526,243 @@ async fn move_file(src_path: String, dest_path: String, app: tauri::AppHandle) -
         
         tx.commit().map_err(|e| format!("提交事务失败: {}", e))?;
     }
-    
-    let color_db = app.state::<Arc<color_db::ColorDbPool>>().inner();
-    let _ = color_db.move_colors(&src_path, &dest_path);
-    
-    Ok(())
+    
+    let color_db = app.state::<Arc<color_db::ColorDbPool>>().inner();
+    let _ = color_db.move_colors(&src_path, &dest_path);
+
+    // 伴生文件（RAW/txt/xmp）跟随图片一起移动，各自记一条撤销日志
+    if !is_dir {
+        let ext = Path::new(&src_path).extension().and_then(|e| e.to_str()).unwrap_or("");
+        if is_supported_image(ext) {
+            if let Some(dest_parent) = Path::new(&dest_path).parent() {
+                for companion in companion_files(&src_path) {
+                    let Some(name) = Path::new(&companion).file_name().and_then(|n| n.to_str()) else {
+                        continue;
+                    };
+                    let companion_dest = normalize_path(&dest_parent.join(name).to_string_lossy());
+                    if Path::new(&companion_dest).exists() {
+                        continue;
+                    }
+                    if move_path_with_fallback(Path::new(&companion), Path::new(&companion_dest)).is_ok() {
+                        let app_db = app.state::<AppDbPool>();
+                        let conn = app_db.get_connection();
+                        let _ = db::file_index::migrate_index_dir(&conn, &companion, &companion_dest);
+                        let _ = db::file_metadata::migrate_metadata(
+                            &conn,
+                            &generate_id(&companion),
+                            &generate_id(&companion_dest),
+                            &companion_dest,
+                        );
+                        undo::record(undo::Operation::Move {
+                            src_path: companion,
+                            dest_path: companion_dest,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    // 记录到操作日志，支持撤销（主文件最后记，单次撤销先恢复主文件）
+    undo::record(undo::Operation::Move {
+        src_path: src_path.clone(),
+        dest_path: dest_path.clone(),
+    });
+    log_activity(&app, "move", &src_path, Some(&dest_path), "user");
+
+    Ok(())
+}
+
+/// 尽力迁移 CLIP 嵌入：file_id 随路径变化，移动文件后把嵌入搬到新 ID 下
+async fn migrate_embedding(old_id: &str, new_id: &str) {
+    if let Some(manager) = clip::get_clip_manager().await {
+        let guard = manager.read().await;
+        if let Some(store) = guard.embedding_store() {
+            if let Ok(Some(mut emb)) = store.get_embedding(old_id) {
+                emb.file_id = new_id.to_string();
+                let _ = store.save_embedding(&emb);
+                let _ = store.delete_embedding(old_id);
+            }
+        }
+    }
+}
+
+/// 批量版嵌入迁移：一次事务改写所有键（文件夹合并、命名空间迁移）
+async fn migrate_embeddings_batch(pairs: Vec<(String, String)>) {
+    if pairs.is_empty() {
+        return;
+    }
+    if let Some(manager) = clip::get_clip_manager().await {
+        let guard = manager.read().await;
+        if let Some(store) = guard.embedding_store() {
+            let store = store.clone();
+            let _ = tokio::task::spawn_blocking(move || store.migrate_embeddings_batch(&pairs)).await;
+        }
+    }
+}
+
+/// 命名空间迁移改写了 metadata 库里的键，把共享嵌入库里的键跟着改过去。
+/// CLIP 还没加载时先留着改写对，等下次切库 / 改设置时再套用
+async fn apply_pending_id_migrations() {
+    if clip::get_clip_manager().await.is_none() {
+        return;
+    }
+    migrate_embeddings_batch(db::take_pending_id_migrations()).await;
+}
+
+/// 文件内容被覆盖后使各级派生缓存失效并重新排队提取：
+/// 查看器解码缓存、主色调、CLIP 嵌入。
+/// 缩略图（及模糊变体）的缓存键包含文件大小/mtime/头部内容，覆盖后自动失效，无需处理
+async fn invalidate_file_caches(app: &tauri::AppHandle, path: &str) {
+    let normalized = normalize_path(path);
+    let file_id = generate_id(&normalized);
+
+    // 查看器缓存：清掉该文件的所有版本
+    VIEWER_CACHE.lock().unwrap().remove_file(&file_id);
+
+    // 主色调：删除旧结果并重新排队，后台 worker 会自动提取
+    let color_db = app.state::<Arc<color_db::ColorDbPool>>().inner().clone();
+    let _ = color_db.delete_colors_by_path(&normalized);
+    {
+        let mut conn = color_db.get_connection();
+        let _ = color_db::add_pending_files(&mut conn, std::slice::from_ref(&normalized));
+    }
+
+    // CLIP 嵌入：删除旧向量，下次语义索引时重新生成
+    if let Some(manager) = clip::get_clip_manager().await {
+        let guard = manager.read().await;
+        if let Some(store) = guard.embedding_store() {
+            let _ = store.delete_embedding(&file_id);
+        }
+    }
+}
+
+/// 前端监视器发现文件被外部程序修改时调用，使派生缓存失效
+#[tauri::command]
+async fn notify_file_modified(file_path: String, app: tauri::AppHandle) -> Result<(), String> {
+    invalidate_file_caches(&app, &file_path).await;
+    Ok(())
+}
+
+/// 往当前库的变更日志追加一条记录（尽力而为，失败不影响主流程）
+fn log_activity(app: &tauri::AppHandle, action: &str, path: &str, detail: Option<&str>, origin: &str) {
+    let pool = app.state::<AppDbPool>();
+    let conn = pool.get_connection();
+    let _ = db::activity_log::record(&conn, action, &normalize_path(path), detail, origin);
+}
+
+/// 读取库变更日志（since 为 Unix 秒时间戳，None 表示不限），按时间倒序
+#[tauri::command]
+async fn get_activity_feed(
+    since: Option<i64>,
+    limit: Option<usize>,
+    pool: tauri::State<'_, AppDbPool>,
+) -> Result<Vec<db::activity_log::ActivityEntry>, String> {
+    let limit = limit.unwrap_or(200).clamp(1, 2000);
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get_connection();
+        db::activity_log::get_feed(&conn, since, limit).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("读取变更日志任务失败: {}", e))?
+}
+
+/// 把多个源文件夹的内容合并到目标文件夹（导入后整理的常见操作）。
+/// conflict_policy: "rename"(默认，按后缀风格改名) | "overwrite" | "skip"；
+/// delete_empty_sources 为 true 时删除清空的源文件夹。
+/// 元数据/颜色/嵌入随移动一起迁移，返回实际移动的条目数
+#[tauri::command]
+async fn merge_folders(
+    src_ids: Vec<String>,
+    dest_id: String,
+    conflict_policy: Option<String>,
+    delete_empty_sources: Option<bool>,
+    pool: tauri::State<'_, AppDbPool>,
+    app: tauri::AppHandle,
+) -> Result<usize, String> {
+    let policy = conflict_policy.unwrap_or_else(|| "rename".to_string());
+    if !matches!(policy.as_str(), "rename" | "overwrite" | "skip") {
+        return Err(format!("未知的冲突策略: {}", policy));
+    }
+
+    // ID → 路径，并校验都是文件夹
+    let (src_paths, dest_path) = {
+        let conn = pool.get_connection();
+        let dest = db::file_index::get_entry_by_id(&conn, &dest_id)
+            .map_err(|e| e.to_string())?
+            .ok_or("目标文件夹不在索引中")?;
+        if dest.file_type != "Folder" {
+            return Err(format!("目标不是文件夹: {}", dest.path));
+        }
+        let mut src_paths = Vec::new();
+        for id in &src_ids {
+            let entry = db::file_index::get_entry_by_id(&conn, id)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("源文件夹不在索引中: {}", id))?;
+            if entry.file_type != "Folder" {
+                return Err(format!("源不是文件夹: {}", entry.path));
+            }
+            src_paths.push(entry.path);
+        }
+        (src_paths, dest.path)
+    };
+
+    let mut moved = 0usize;
+    // 嵌入键的改写攒起来，最后一个事务批量套用
+    let mut embedding_moves: Vec<(String, String)> = Vec::new();
+    for src_path in &src_paths {
+        if *src_path == dest_path {
+            continue;
+        }
+        // 目标在源的子树里时跳过，避免把文件夹搬进自己
+        if dest_path.starts_with(&format!("{}/", src_path)) {
+            return Err(format!("目标文件夹在源文件夹内部: {}", src_path));
+        }
+
+        let children: Vec<String> = fs::read_dir(src_path)
+            .map_err(|e| format!("读取源文件夹失败: {}", e))?
+            .filter_map(|e| e.ok())
+            .filter_map(|e| e.path().to_str().map(normalize_path))
+            .collect();
+
+        for child in children {
+            let Some(name) = Path::new(&child).file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let mut target = format!("{}/{}", dest_path, name);
+            if Path::new(&target).exists() {
+                match policy.as_str() {
+                    "skip" => continue,
+                    "overwrite" if Path::new(&target).is_file() => {
+                        versions::snapshot_before_overwrite(&target);
+                        delete_file(target.clone(), app.clone()).await?;
+                    }
+                    // 文件夹撞名（以及 rename 策略）按后缀风格自动改名
+                    _ => target = generate_unique_file_path(&target),
+                }
+            }
+
+            let old_id = generate_id(&child);
+            move_file(child.clone(), target.clone(), app.clone()).await?;
+            embedding_moves.push((old_id, generate_id(&target)));
+            moved += 1;
+        }
+
+        // 源文件夹清空后删除（并清理索引记录）
+        if delete_empty_sources.unwrap_or(false)
+            && fs::read_dir(src_path).map(|mut d| d.next().is_none()).unwrap_or(false)
+        {
+            let _ = fs::remove_dir(src_path);
+            let conn = pool.get_connection();
+            let _ = db::file_index::delete_entries_by_path(&conn, src_path);
+            let _ = db::file_metadata::delete_metadata_by_path(&conn, src_path);
+        }
+    }
+
+    migrate_embeddings_batch(embedding_moves).await;
+
+    Ok(moved)
 }
 
 #[tauri::command]
@@ -1983,29 +3789,42 @@ async fn write_file_from_bytes(file_path: String, bytes: Vec<u8>, app: tauri::Ap
             Ok(mut file) => {
                 match file.write_all(&bytes) {
                     Ok(_) => {
-                        // 同步更新索引数据库
-                        let app_db = app.state::<AppDbPool>();
-                        let mut conn = app_db.get_connection();
-                        let normalized_path = normalize_path(&file_path);
-                        let id = generate_id(&normalized_path);
-                        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("").to_string();
                         let ext = path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).unwrap_or_default();
-                        let md = fs::metadata(path).ok();
-
-                        if is_supported_image(&ext) {
-                            let entry = db::file_index::FileIndexEntry {
-                                file_id: id,
-                                parent_id: path.parent().map(|p| generate_id(&normalize_path(p.to_str().unwrap_or("")))),
-                                path: normalized_path,
-                                name,
-                                file_type: "Image".to_string(),
-                                size: md.as_ref().map(|m| m.len()).unwrap_or(0),
-                                width: None, height: None, format: Some(ext),
-                                created_at: md.as_ref().and_then(|m| m.created().ok()).and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).map(|d| d.as_secs() as i64).unwrap_or(0),
-                                modified_at: md.as_ref().and_then(|m| m.modified().ok()).and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).map(|d| d.as_secs() as i64).unwrap_or(0),
-                            };
-                            let _ = db::file_index::batch_upsert(&mut conn, &[entry]);
+                        let is_image = is_supported_image(&ext);
+
+                        // 同步更新索引数据库
+                        {
+                            let app_db = app.state::<AppDbPool>();
+                            let mut conn = app_db.get_connection();
+                            let normalized_path = normalize_path(&file_path);
+                            let id = generate_id(&normalized_path);
+                            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("").to_string();
+                            let md = fs::metadata(path).ok();
+
+                            if is_image {
+                                let header = probe_image_header(&normalized_path);
+                                let entry = db::file_index::FileIndexEntry {
+                                    file_id: id,
+                                    parent_id: path.parent().map(|p| generate_id(&normalize_path(p.to_str().unwrap_or("")))),
+                                    path: normalized_path,
+                                    name,
+                                    file_type: "Image".to_string(),
+                                    size: md.as_ref().map(|m| m.len()).unwrap_or(0),
+                                    width: None, height: None, format: Some(ext.clone()),
+                                    bit_depth: header.bit_depth, color_space: header.color_space,
+                                    has_alpha: header.has_alpha, is_animated: header.is_animated,
+                                    created_at: md.as_ref().and_then(|m| m.created().ok()).and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).map(|d| d.as_secs() as i64).unwrap_or(0),
+                                    modified_at: md.as_ref().and_then(|m| m.modified().ok()).and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).map(|d| d.as_secs() as i64).unwrap_or(0),
+                                };
+                                let _ = db::file_index::batch_upsert(&mut conn, &[entry]);
+                            }
+                        }
+
+                        // 覆盖写入后使派生缓存失效并重新排队提取
+                        if is_image {
+                            invalidate_file_caches(&app, &file_path).await;
                         }
+                        log_activity(&app, "add", &file_path, None, "user");
                         return Ok(());
                     },
                     Err(e) => {
@@ -2048,9 +3867,8 @@ async fn get_default_paths() -> Result<HashMap<String, String>, String> {
     // Default resource root (Pictures folder)
     let resource_root = if cfg!(windows) {
         format!("{}\\Pictures\\AuroraGallery", home)
-    } else if cfg!(target_os = "macos") {
-        format!("{}/Pictures/AuroraGallery", home)
     } else {
+        // macOS 和 Linux 都使用 ~/Pictures
         format!("{}/Pictures/AuroraGallery", home)
     };
     
@@ -2105,14 +3923,13 @@ async fn open_path(path: String, is_file: Option<bool>) -> Result<(), String> {
     // 使用绝对路径创建Path对象，确保所有后续操作都基于绝对路径
     let abs_path_obj = Path::new(&absolute_path);
     
-    // 是否是从右键菜单（上下文菜单）调用的
-    // is_file 为 Some 时表示从文件列表/文件夹树的右键菜单调用
-    // is_file 为 None 时表示从设置面板等地方直接打开文件夹
-    let is_context_menu = is_file.is_some();
-    
-    println!("open_path: path={}, is_file={:?}, is_context_menu={}", 
-             path, is_file, is_context_menu);
-    
+    // is_file 语义：Some(true) = 在文件管理器中定位并选中该文件；
+    // Some(false) = 打开该文件夹本身（不再错开到它的父目录）；
+    // None = 直接打开该路径（设置面板等场景）
+    let select = is_file == Some(true);
+
+    println!("open_path: path={}, is_file={:?}, select={}", path, is_file, select);
+
     // 直接使用系统命令打开文件管理器，但不等待命令完成，避免阻塞和闪退问题
     let result = if cfg!(windows) {
         #[cfg(target_os = "windows")]
@@ -2120,19 +3937,18 @@ async fn open_path(path: String, is_file: Option<bool>) -> Result<(), String> {
             // Windows: 使用 explorer.exe
             // 将正斜杠转换为反斜杠，确保 Windows 能够正确识别路径
             let win_path = absolute_path.replace("/", "\\");
-            
-            if is_context_menu {
-                // 如果是右键菜单调用，使用 /select 选项在文件管理器中选中该文件/文件夹
-                // 对路径进行安全处理：去除尾部的反斜杠（如果是文件夹）
+
+            if select {
+                // /select 选项在文件管理器中定位并高亮该文件
+                // 对路径进行安全处理：去除尾部的反斜杠
                 let clean_path = win_path.trim_end_matches('\\');
-                
+
                 // 使用 raw_arg 手动构建参数，确保 /select 格式正确
-                // 我们在 /select, 后面加一个空格，并用引号包裹路径，这是最兼容的格式
                 // 格式：/select, "C:\Path\To\File"
                 let raw_arg = format!("/select, \"{}\"", clean_path);
-                
+
                 println!("Windows command: explorer.exe [raw_arg] {}", raw_arg);
-                
+
                 Command::new("explorer.exe")
                     .raw_arg(raw_arg)
                     .stdout(std::process::Stdio::null())
@@ -2141,7 +3957,7 @@ async fn open_path(path: String, is_file: Option<bool>) -> Result<(), String> {
                     .spawn()
                     .map(|_| ())
             } else {
-                // 否则直接打开该路径
+                // 文件夹与直接打开：都打开路径本身
                 println!("Windows command: explorer.exe \"{}\"", win_path);
                 Command::new("explorer.exe")
                     .arg(win_path)
@@ -2158,9 +3974,8 @@ async fn open_path(path: String, is_file: Option<bool>) -> Result<(), String> {
              Ok(())
         }
     } else if cfg!(target_os = "macos") {
-        // macOS: 使用 open 命令
-        if is_context_menu {
-            // 使用 -R 参数在 Finder 中显示并选中
+        // macOS: 使用 open 命令，-R 在 Finder 中显示并选中
+        if select {
             println!("macOS command: open -R \"{}\"", absolute_path);
             Command::new("open")
                 .arg("-R")
@@ -2175,24 +3990,41 @@ async fn open_path(path: String, is_file: Option<bool>) -> Result<(), String> {
                 .map(|_| ())
         }
     } else {
-        // Linux: 使用 xdg-open 命令
-        // Linux 下 xdg-open 不支持选中文件，如果是文件则打开其父目录
-        let target_path = if is_context_menu {
-            match abs_path_obj.parent() {
-                Some(parent) => parent.to_string_lossy().to_string(),
-                None => absolute_path.clone(),
-            }
+        // Linux: 选中文件走 DBus 的 org.freedesktop.FileManager1.ShowItems
+        // （Nautilus / Dolphin / Nemo 都实现了），失败再退回 xdg-open 父目录
+        if select {
+            let uri = format!("file://{}", absolute_path);
+            println!("Linux command: dbus-send ShowItems {}", uri);
+            let dbus = Command::new("dbus-send")
+                .args([
+                    "--session",
+                    "--dest=org.freedesktop.FileManager1",
+                    "--type=method_call",
+                    "/org/freedesktop/FileManager1",
+                    "org.freedesktop.FileManager1.ShowItems",
+                    &format!("array:string:{}", uri),
+                    "string:",
+                ])
+                .spawn()
+                .map(|_| ());
+            dbus.or_else(|_| {
+                // 没有 DBus 会话时退回打开父目录（无法高亮）
+                let parent = abs_path_obj
+                    .parent()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_else(|| absolute_path.clone());
+                println!("Linux fallback: xdg-open \"{}\"", parent);
+                Command::new("xdg-open").arg(parent).spawn().map(|_| ())
+            })
         } else {
-            absolute_path.clone()
-        };
-        
-        println!("Linux command: xdg-open \"{}\"", target_path);
-        Command::new("xdg-open")
-            .arg(target_path)
-            .spawn()
-            .map(|_| ())
+            println!("Linux command: xdg-open \"{}\"", absolute_path);
+            Command::new("xdg-open")
+                .arg(&absolute_path)
+                .spawn()
+                .map(|_| ())
+        }
     };
-    
+
     match result {
         Ok(_) => {
             println!("Successfully started file manager for path: {}", absolute_path);
@@ -2269,6 +4101,8 @@ async fn set_window_min_size(app_handle: tauri::AppHandle, width: f64, height: f
 #[tauri::command]
 async fn exit_app(app_handle: tauri::AppHandle) -> Result<(), String> {
     save_window_state(&app_handle);
+    // 先让后台任务收尾（带超时），避免扫描 / 向量生成写到一半被杀
+    shutdown::coordinate(&app_handle).await;
     app_handle.exit(0);
     Ok(())
 }
@@ -2348,44 +4182,570 @@ fn db_delete_person(pool: tauri::State<AppDbPool>, id: String) -> Result<(), Str
 }
 
 #[tauri::command]
-fn db_update_person_avatar(
-    pool: tauri::State<AppDbPool>,
-    person_id: String,
-    cover_file_id: String,
-    face_box: Option<db::persons::FaceBox>
-) -> Result<(), String> {
-    let conn = pool.get_connection();
-    db::persons::update_person_avatar(&conn, &person_id, &cover_file_id, face_box.as_ref()).map_err(|e| e.to_string())
+fn db_update_person_avatar(
+    pool: tauri::State<AppDbPool>,
+    person_id: String,
+    cover_file_id: String,
+    face_box: Option<db::persons::FaceBox>
+) -> Result<(), String> {
+    let conn = pool.get_connection();
+    db::persons::update_person_avatar(&conn, &person_id, &cover_file_id, face_box.as_ref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn db_get_all_topics(pool: tauri::State<AppDbPool>) -> Result<Vec<db::topics::Topic>, String> {
+    let conn = pool.get_connection();
+    db::topics::get_all_topics(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn db_upsert_topic(pool: tauri::State<AppDbPool>, topic: db::topics::Topic) -> Result<(), String> {
+    let conn = pool.get_connection();
+    db::topics::upsert_topic(&conn, &topic).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn db_delete_topic(pool: tauri::State<AppDbPool>, id: String) -> Result<(), String> {
+    let conn = pool.get_connection();
+    db::topics::delete_topic(&conn, &id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn db_get_all_categories(pool: tauri::State<AppDbPool>) -> Result<Vec<db::categories::Category>, String> {
+    let conn = pool.get_connection();
+    db::categories::get_all_categories(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn db_upsert_category(pool: tauri::State<AppDbPool>, category: db::categories::Category) -> Result<(), String> {
+    let conn = pool.get_connection();
+    db::categories::upsert_category(&conn, &category).map_err(|e| e.to_string())
+}
+
+/// 重命名分类，同步更新引用它的文件元数据
+#[tauri::command]
+fn db_rename_category(pool: tauri::State<AppDbPool>, id: String, new_name: String) -> Result<(), String> {
+    let conn = pool.get_connection();
+    db::categories::rename_category(&conn, &id, &new_name).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn db_delete_category(pool: tauri::State<AppDbPool>, id: String) -> Result<(), String> {
+    let conn = pool.get_connection();
+    db::categories::delete_category(&conn, &id).map_err(|e| e.to_string())
+}
+
+/// 按传入顺序重排侧边栏分类
+#[tauri::command]
+fn db_reorder_categories(pool: tauri::State<AppDbPool>, ids: Vec<String>) -> Result<(), String> {
+    let conn = pool.get_connection();
+    db::categories::reorder_categories(&conn, &ids).map_err(|e| e.to_string())
+}
+
+/// 各分类下的文件数，供侧边栏徽标展示
+#[tauri::command]
+fn get_category_counts(pool: tauri::State<AppDbPool>) -> Result<Vec<db::categories::CategoryCount>, String> {
+    let conn = pool.get_connection();
+    db::categories::get_category_counts(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn db_upsert_file_metadata(
+    pool: tauri::State<'_, AppDbPool>,
+    writer: tauri::State<'_, db::writer::DbWriter>,
+    mut metadata: db::file_metadata::FileMetadata
+) -> Result<(), String> {
+    // Ensure path is normalized before saving, so that get_metadata_under_path (which uses LIKE 'path/%') works correctly
+    metadata.path = normalize_path(&metadata.path);
+
+    // 标签/评分变化记入操作日志（其余字段不参与撤销）
+    {
+        let conn = pool.get_connection();
+        if let Ok(Some(old)) = db::file_metadata::get_metadata_by_id(&conn, &metadata.file_id) {
+            if old.tags != metadata.tags {
+                undo::record(undo::Operation::TagChange {
+                    file_id: metadata.file_id.clone(),
+                    old_tags: old.tags.clone(),
+                    new_tags: metadata.tags.clone(),
+                });
+            }
+            if old.rating != metadata.rating {
+                undo::record(undo::Operation::RatingChange {
+                    file_id: metadata.file_id.clone(),
+                    old_rating: old.rating,
+                    new_rating: metadata.rating,
+                });
+            }
+        }
+        let _ = db::activity_log::record(&conn, "metadata", &metadata.path, None, "user");
+    }
+
+    let writeback = (
+        metadata.path.clone(),
+        metadata.rating,
+        metadata.tags.clone(),
+        metadata.description.clone(),
+    );
+
+    // 写操作交给单写入者队列，与其它并发写合并提交，避免 "database is locked"
+    writer
+        .submit(move |conn| {
+            db::file_metadata::upsert_file_metadata(conn, &metadata).map_err(|e| e.to_string())
+        })
+        .await?;
+
+    // 库级开关打开时，把评分/标签/描述以 XMP 形式写回文件本身
+    let (path, rating, tags, description) = writeback;
+    let enabled = {
+        let conn = pool.get_connection();
+        db::get_library_setting(&conn, "metadata_writeback")
+            .ok()
+            .flatten()
+            .as_deref()
+            == Some("1")
+    };
+    let ext = Path::new(&path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    if enabled && metadata_writeback::supports_writeback(&ext) {
+        let tag_list: Vec<String> = tags
+            .as_ref()
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().filter_map(|t| t.as_str().map(String::from)).collect())
+            .unwrap_or_default();
+        let _ = tokio::task::spawn_blocking(move || {
+            // 写回会改动原图文件，先留一份版本快照
+            versions::snapshot_before_overwrite(&path);
+            if let Err(e) =
+                metadata_writeback::write_back(&path, rating, &tag_list, description.as_deref())
+            {
+                eprintln!("Metadata write-back failed for {}: {}", path, e);
+            }
+        })
+        .await;
+    }
+
+    Ok(())
+}
+
+/// 设置当前库的元数据写回开关（评分/标签/描述嵌入 XMP）
+#[tauri::command]
+fn set_metadata_writeback(enabled: bool, pool: tauri::State<AppDbPool>) -> Result<(), String> {
+    let conn = pool.get_connection();
+    db::set_library_setting(&conn, "metadata_writeback", if enabled { "1" } else { "0" })
+        .map_err(|e| e.to_string())
+}
+
+/// 查询当前库的元数据写回开关
+#[tauri::command]
+fn get_metadata_writeback(pool: tauri::State<AppDbPool>) -> Result<bool, String> {
+    let conn = pool.get_connection();
+    Ok(db::get_library_setting(&conn, "metadata_writeback")
+        .map_err(|e| e.to_string())?
+        .as_deref()
+        == Some("1"))
+}
+
+/// 手动把单个文件的元数据写回文件本身（不受库级开关限制）
+#[tauri::command]
+async fn writeback_file_metadata(
+    file_id: String,
+    pool: tauri::State<'_, AppDbPool>,
+) -> Result<(), String> {
+    let meta = {
+        let conn = pool.get_connection();
+        db::file_metadata::get_metadata_by_id(&conn, &file_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "文件没有元数据记录".to_string())?
+    };
+    let tag_list: Vec<String> = meta
+        .tags
+        .as_ref()
+        .and_then(|v| v.as_array())
+        .map(|a| a.iter().filter_map(|t| t.as_str().map(String::from)).collect())
+        .unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        metadata_writeback::write_back(
+            &meta.path,
+            meta.rating,
+            &tag_list,
+            meta.description.as_deref(),
+        )
+    })
+    .await
+    .map_err(|e| format!("写回任务失败: {}", e))?
+}
+
+/// 设置单个文件的笔记 (Markdown)，传 None 表示清空
+#[tauri::command]
+async fn set_note(
+    file_id: String,
+    notes: Option<String>,
+    writer: tauri::State<'_, db::writer::DbWriter>,
+) -> Result<(), String> {
+    writer
+        .submit(move |conn| {
+            db::file_metadata::set_note(conn, &file_id, notes.as_deref()).map_err(|e| e.to_string())
+        })
+        .await
+}
+
+#[tauri::command]
+fn get_note(file_id: String, pool: tauri::State<AppDbPool>) -> Result<Option<String>, String> {
+    let conn = pool.get_connection();
+    db::file_metadata::get_note(&conn, &file_id).map_err(|e| e.to_string())
+}
+
+/// 全文搜索笔记，返回命中文件及高亮片段
+#[tauri::command]
+fn search_notes(
+    query: String,
+    limit: Option<i64>,
+    pool: tauri::State<AppDbPool>,
+) -> Result<Vec<db::file_metadata::NoteSearchResult>, String> {
+    let conn = pool.get_connection();
+    db::file_metadata::search_notes(&conn, &query, limit.unwrap_or(100)).map_err(|e| e.to_string())
+}
+
+/// 批量编辑元数据：在单个事务中对一批文件应用标签增删、分类、描述模板和评分，
+/// 过程中发送 bulk-metadata-progress 事件，返回实际更新的文件数
+#[tauri::command]
+async fn bulk_update_metadata(
+    file_ids: Vec<String>,
+    patch: db::file_metadata::MetadataPatch,
+    pool: tauri::State<'_, AppDbPool>,
+    app: tauri::AppHandle,
+) -> Result<usize, String> {
+    let pool = pool.inner().clone();
+
+    let updated = tokio::task::spawn_blocking(move || {
+        let mut conn = pool.get_connection();
+        db::file_metadata::bulk_update_metadata(&mut conn, &file_ids, &patch, |processed, total| {
+            let _ = app.emit("bulk-metadata-progress", ScanProgress::simple(processed, total));
+        })
+        .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Bulk update task failed: {}", e))??;
+
+    Ok(updated)
+}
+
+/// 把同主名 .txt caption 的内容填入还没有描述的图片元数据。
+/// `scope` 为目录路径时只处理该目录下的图片，为 None 时处理整个索引。
+/// 返回实际填充的文件数
+#[tauri::command]
+async fn apply_companion_captions(
+    scope: Option<String>,
+    pool: tauri::State<'_, AppDbPool>,
+) -> Result<usize, String> {
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let paths: Vec<String> = {
+            let conn = pool.get_connection();
+            let entries = match &scope {
+                Some(dir) => db::file_index::get_entries_under_path(&conn, dir),
+                None => db::file_index::get_all_image_files(&conn),
+            }
+            .map_err(|e| e.to_string())?;
+            entries
+                .into_iter()
+                .filter(|e| e.file_type == "Image")
+                .map(|e| e.path)
+                .collect()
+        };
+
+        let mut filled = 0usize;
+        for path in paths {
+            let caption_path = Path::new(&path).with_extension("txt");
+            if !caption_path.is_file() {
+                continue;
+            }
+            let Ok(caption) = fs::read_to_string(&caption_path) else {
+                continue;
+            };
+            let caption = caption.trim();
+            // 跳过空文件和明显不是 caption 的大文本
+            if caption.is_empty() || caption.len() > 4096 {
+                continue;
+            }
+
+            let conn = pool.get_connection();
+            let file_id = generate_id(&path);
+            if db::file_metadata::fill_description_if_empty(&conn, &file_id, &path, caption)
+                .map_err(|e| e.to_string())?
+            {
+                filled += 1;
+            }
+        }
+        Ok(filled)
+    })
+    .await
+    .map_err(|e| format!("Caption import task failed: {}", e))?
+}
+
+/// 返回所有记录在案的损坏文件（解码失败的图片），供前端列表展示和批量删除
+#[tauri::command]
+fn get_corrupt_files(pool: tauri::State<AppDbPool>) -> Result<Vec<db::decode_errors::DecodeErrorEntry>, String> {
+    let conn = pool.get_connection();
+    db::decode_errors::get_corrupt_files(&conn).map_err(|e| e.to_string())
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct RenamePreview {
+    file_id: String,
+    old_path: String,
+    new_path: String,
+    /// 与磁盘现有文件或批次内其它新名字冲突
+    conflict: bool,
+}
+
+/// 渲染批量重命名模板。
+/// 支持 {seq}（三位序号）、{date}（文件修改日期）、{original}（原文件名不含扩展名）、
+/// {width}/{height}（图片尺寸，可组合成 {width}x{height}）
+fn render_rename_template(template: &str, seq: usize, entry: &db::file_index::FileIndexEntry) -> String {
+    let original_stem = Path::new(&entry.name)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(&entry.name);
+    let date = chrono::DateTime::from_timestamp(entry.modified_at, 0)
+        .map(|dt| dt.format("%Y-%m-%d").to_string())
+        .unwrap_or_default();
+    template
+        .replace("{seq}", &format!("{:03}", seq))
+        .replace("{date}", &date)
+        .replace("{original}", original_stem)
+        .replace("{width}", &entry.width.unwrap_or(0).to_string())
+        .replace("{height}", &entry.height.unwrap_or(0).to_string())
+}
+
+/// 按模板批量重命名文件。dry_run 时只返回预览（含冲突标记），
+/// 实际执行走 rename_file 现有通路，元数据迁移和撤销记录保持一致
+#[tauri::command]
+async fn batch_rename(
+    file_ids: Vec<String>,
+    template: String,
+    dry_run: Option<bool>,
+    pool: tauri::State<'_, AppDbPool>,
+    app: tauri::AppHandle,
+) -> Result<Vec<RenamePreview>, String> {
+    if template.trim().is_empty() {
+        return Err("重命名模板不能为空".to_string());
+    }
+    let dry_run = dry_run.unwrap_or(false);
+
+    // 读取索引条目（文件夹不参与批量重命名）
+    let entries: Vec<db::file_index::FileIndexEntry> = {
+        let conn = pool.get_connection();
+        let mut list = Vec::new();
+        for id in &file_ids {
+            if let Ok(Some(entry)) = db::file_index::get_entry_by_id(&conn, id) {
+                if entry.file_type != "Folder" {
+                    list.push(entry);
+                }
+            }
+        }
+        list
+    };
+
+    let mut previews = Vec::new();
+    let mut seen_names: std::collections::HashSet<String> = std::collections::HashSet::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let parent = Path::new(&entry.path)
+            .parent()
+            .map(|p| normalize_path(p.to_str().unwrap_or("")))
+            .unwrap_or_default();
+        let ext = Path::new(&entry.name)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| format!(".{}", e))
+            .unwrap_or_default();
+        let stem = render_rename_template(&template, i + 1, entry);
+        let new_path = format!("{}/{}{}", parent, stem, ext);
+
+        // 冲突：批次内新名字重复，或磁盘上已有同名文件（自身除外）
+        let conflict = !seen_names.insert(new_path.to_lowercase())
+            || (new_path != entry.path && Path::new(&new_path).exists());
+
+        previews.push(RenamePreview {
+            file_id: entry.file_id.clone(),
+            old_path: entry.path.clone(),
+            new_path,
+            conflict,
+        });
+    }
+
+    if dry_run {
+        return Ok(previews);
+    }
+
+    // 实际执行：跳过冲突项和名字没有变化的项
+    for preview in &previews {
+        if preview.conflict || preview.new_path == preview.old_path {
+            continue;
+        }
+        rename_file(preview.old_path.clone(), preview.new_path.clone(), app.clone()).await?;
+    }
+
+    Ok(previews)
+}
+
+/// 获取文件夹的递归大小（字节）。
+/// 结果缓存在 file_index.recursive_size 中，文件操作后自动失效
+#[tauri::command]
+async fn get_recursive_size(folder_id: String, pool: tauri::State<'_, AppDbPool>) -> Result<u64, String> {
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get_connection();
+        db::file_index::get_recursive_size(&conn, &folder_id).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Failed to compute folder size: {}", e))?
+}
+
+/// 随机抽取 scope 下的图片路径（灵感视图 / 随机浏览 / 幻灯片）。
+/// 采样在 SQL 层完成，避免把整个索引发给前端再洗牌。
+/// SQLite 的 RANDOM() 不可播种，这里用 rowid 乘法散列做确定性伪随机排序：
+/// 相同的 seed 得到相同的顺序，便于分页取同一轮洗牌的后续批次
+#[tauri::command]
+async fn get_random_files(
+    scope: Option<String>,
+    count: Option<usize>,
+    seed: Option<u64>,
+    pool: tauri::State<'_, AppDbPool>,
+) -> Result<Vec<String>, String> {
+    let pool = pool.inner().clone();
+    let count = count.unwrap_or(50).clamp(1, 5000);
+    let seed = seed.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos() as u64)
+            .unwrap_or(1)
+    }) % 1_000_000_007;
+
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get_connection();
+        let scope_clause = if scope.is_some() { " AND path LIKE ?2" } else { "" };
+        let sql = format!(
+            "SELECT path FROM file_index WHERE file_type = 'Image'{}
+             ORDER BY (rowid * 2654435761 + ?1) % 4294967296
+             LIMIT {}",
+            scope_clause, count
+        );
+        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+        match scope {
+            Some(dir) => {
+                let pattern = format!("{}%", normalize_path(&dir));
+                stmt.query_map(params![seed as i64, pattern], |row| row.get(0))
+                    .map_err(|e| e.to_string())?
+                    .collect::<Result<Vec<String>, _>>()
+                    .map_err(|e| e.to_string())
+            }
+            None => stmt
+                .query_map(params![seed as i64], |row| row.get(0))
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<String>, _>>()
+                .map_err(|e| e.to_string()),
+        }
+    })
+    .await
+    .map_err(|e| format!("随机抽样任务失败: {}", e))?
+}
+
+/// 磁盘占用分析：返回以 root_id 为根的层级大小树、最大文件和格式分布。
+/// depth 默认展开 2 层
+#[tauri::command]
+async fn get_storage_treemap(
+    root_id: String,
+    depth: Option<u32>,
+    pool: tauri::State<'_, AppDbPool>,
+) -> Result<db::file_index::StorageTreemap, String> {
+    let pool = pool.inner().clone();
+    let depth = depth.unwrap_or(2);
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get_connection();
+        db::file_index::get_storage_treemap(&conn, &root_id, depth).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Failed to build storage treemap: {}", e))?
+}
+
+/// 便携库支持：库根路径变化（盘符 / 挂载点改变）时，把索引、元数据和
+/// 颜色库里的绝对路径整体重映射到新根。
+/// 迁移沿用 rename_file 的稳定 ID 机制，标签 / 评分 / CLIP 嵌入全部保留。
+/// 重映射只在库设置 portable_mode 打开时执行；library_root 每次都更新，
+/// 这样之后再开启便携模式也能从正确的基准出发
+fn remap_library_root(pool: &AppDbPool, color_db: &color_db::ColorDbPool, new_root: &str) {
+    let new_root = normalize_path(new_root);
+    let moved_from = {
+        let conn = pool.get_connection();
+        let recorded = db::get_library_setting(&conn, "library_root").ok().flatten();
+        let portable = db::get_library_setting(&conn, "portable_mode")
+            .ok()
+            .flatten()
+            .as_deref()
+            == Some("1");
+        let moved_from = match recorded {
+            Some(old_root) if portable && old_root != new_root => {
+                let _ = db::file_index::migrate_index_dir(&conn, &old_root, &new_root);
+                let _ = db::file_metadata::migrate_metadata_dir(&conn, &old_root, &new_root);
+                let _ = db::activity_log::record(&conn, "move", &new_root, Some(&old_root), "background");
+                Some(old_root)
+            }
+            _ => None,
+        };
+        let _ = db::set_library_setting(&conn, "library_root", &new_root);
+        moved_from
+    };
+    if let Some(old_root) = moved_from {
+        let _ = color_db.move_colors(&old_root, &new_root);
+    }
 }
 
+/// 设置当前库的便携模式。打开后库根路径变化（换盘符 / 换机器挂载）时，
+/// 下次打开会自动把数据库里的路径整体迁移到新根
 #[tauri::command]
-fn db_get_all_topics(pool: tauri::State<AppDbPool>) -> Result<Vec<db::topics::Topic>, String> {
+fn set_portable_mode(enabled: bool, pool: tauri::State<AppDbPool>) -> Result<(), String> {
     let conn = pool.get_connection();
-    db::topics::get_all_topics(&conn).map_err(|e| e.to_string())
+    db::set_library_setting(&conn, "portable_mode", if enabled { "1" } else { "0" })
+        .map_err(|e| e.to_string())
 }
 
+/// 查询当前库的便携模式开关
 #[tauri::command]
-fn db_upsert_topic(pool: tauri::State<AppDbPool>, topic: db::topics::Topic) -> Result<(), String> {
+fn get_portable_mode(pool: tauri::State<AppDbPool>) -> Result<bool, String> {
     let conn = pool.get_connection();
-    db::topics::upsert_topic(&conn, &topic).map_err(|e| e.to_string())
+    Ok(db::get_library_setting(&conn, "portable_mode")
+        .map_err(|e| e.to_string())?
+        .as_deref()
+        == Some("1"))
 }
 
+/// 当前库的 ID 命名空间前缀（空串表示未启用）
 #[tauri::command]
-fn db_delete_topic(pool: tauri::State<AppDbPool>, id: String) -> Result<(), String> {
-    let conn = pool.get_connection();
-    db::topics::delete_topic(&conn, &id).map_err(|e| e.to_string())
+fn get_id_namespace() -> String {
+    db::current_id_namespace()
 }
 
+/// 修改当前库的 ID 命名空间并整体改写既有键，返回改写的键数。
+/// 传空串回退到无前缀的旧格式
 #[tauri::command]
-async fn db_upsert_file_metadata(
-    pool: tauri::State<'_, AppDbPool>, 
-    mut metadata: db::file_metadata::FileMetadata
-) -> Result<(), String> {
-    // Ensure path is normalized before saving, so that get_metadata_under_path (which uses LIKE 'path/%') works correctly
-    metadata.path = normalize_path(&metadata.path);
-    
-    let conn = pool.get_connection();
-    db::file_metadata::upsert_file_metadata(&conn, &metadata).map_err(|e| e.to_string())
+async fn set_id_namespace(
+    namespace: String,
+    pool: tauri::State<'_, AppDbPool>,
+) -> Result<usize, String> {
+    let pool = pool.inner().clone();
+    let rewritten = tokio::task::spawn_blocking(move || {
+        let mut conn = pool.get_connection();
+        db::apply_id_namespace(&mut conn, &namespace)
+    })
+    .await
+    .map_err(|e| format!("迁移任务失败: {}", e))??;
+    apply_pending_id_migrations().await;
+    Ok(rewritten)
 }
 
 #[tauri::command]
@@ -2398,8 +4758,14 @@ async fn switch_root_database(
     
     // 我们将数据库存储在根目录下的 .aurora 文件夹中
     let aurora_dir = root.join(".aurora");
-    
-    let metadata_db_path = aurora_dir.join("metadata.db");
+
+    // 先抢新库的占用锁，被别的实例占用时直接报错、不切换
+    library_lock::acquire(&aurora_dir)?;
+
+    // 恢复该库记录的当前 profile（见 CURRENT_PROFILE 一节）
+    let profile = read_saved_profile(&aurora_dir);
+    let metadata_db_path = profile_db_path(&aurora_dir, &profile);
+    *CURRENT_PROFILE.write().unwrap() = profile;
     let colors_db_path = aurora_dir.join("colors.db");
     
     // 切换元数据数据库
@@ -2410,10 +4776,237 @@ async fn switch_root_database(
     
     // 重新启动缓存预热（可选，因为 switch 已经标记为未初始化）
     let _ = color_db_pool.ensure_cache_initialized_async();
-    
+
+    // 便携库：根路径变化时把数据库里的绝对路径重映射到新根
+    remap_library_root(app_db_pool.inner(), color_db_pool.inner(), &new_root_path);
+
+    // 命名空间迁移改写过键的话，同步到共享嵌入库
+    apply_pending_id_migrations().await;
+
+    Ok(())
+}
+
+/// 当前库的 profile 基准目录：库根下的 .aurora，没有配置库根时退回应用数据目录
+fn profile_base_dir(pool: &AppDbPool, app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    let root = {
+        let conn = pool.get_connection();
+        db::get_library_setting(&conn, "library_root").ok().flatten()
+    };
+    match root {
+        Some(root) => Ok(Path::new(&root).join(".aurora")),
+        None => app.path().app_data_dir().map_err(|e| e.to_string()),
+    }
+}
+
+/// 切换到指定 profile（不存在时创建，并从当前库复制 file_index 以免全量重扫）。
+/// 收藏 / 评分 / 分类随 metadata 数据库各自独立，颜色库与图片文件夹共享
+#[tauri::command]
+async fn switch_profile(
+    name: String,
+    pool: tauri::State<'_, AppDbPool>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    if !is_valid_profile_name(&name) {
+        return Err("profile 名称只能包含字母、数字、下划线和连字符".to_string());
+    }
+    if *CURRENT_PROFILE.read().unwrap() == name {
+        return Ok(());
+    }
+
+    let base_dir = profile_base_dir(&pool, &app)?;
+    let target = profile_db_path(&base_dir, &name);
+
+    if !target.exists() {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        // 先建好目标库的表结构，再把当前库的 file_index 与库设置复制过去
+        AppDbPool::new(&target)?;
+        let conn = pool.get_connection();
+        let escaped = target.to_string_lossy().replace('\\', "/").replace('\'', "''");
+        conn.execute(&format!("ATTACH DATABASE '{}' AS dst", escaped), [])
+            .map_err(|e| format!("附加 profile 数据库失败: {}", e))?;
+        let copy = conn
+            .execute("INSERT OR IGNORE INTO dst.file_index SELECT * FROM file_index", [])
+            .and_then(|_| {
+                conn.execute(
+                    "INSERT OR IGNORE INTO dst.library_settings SELECT * FROM library_settings",
+                    [],
+                )
+            })
+            .and_then(|_| {
+                // ID 命名空间必须跟当前库一致（复制过去的 file_index 带着
+                // 当前前缀），覆盖掉建表时随机分配的那个
+                conn.execute(
+                    "INSERT OR REPLACE INTO dst.library_settings
+                     SELECT key, value FROM library_settings WHERE key = 'id_namespace'",
+                    [],
+                )
+            });
+        let _ = conn.execute("DETACH DATABASE dst", []);
+        copy.map_err(|e| format!("复制索引到新 profile 失败: {}", e))?;
+    }
+
+    pool.switch(&target)?;
+    apply_pending_id_migrations().await;
+    let _ = fs::write(base_dir.join("current_profile.txt"), &name);
+    *CURRENT_PROFILE.write().unwrap() = name;
     Ok(())
 }
 
+/// 当前激活的 profile 名
+#[tauri::command]
+fn get_current_profile() -> String {
+    CURRENT_PROFILE.read().unwrap().clone()
+}
+
+/// 列出当前库的所有 profile（default 永远在首位）
+#[tauri::command]
+async fn list_profiles(
+    pool: tauri::State<'_, AppDbPool>,
+    app: tauri::AppHandle,
+) -> Result<Vec<String>, String> {
+    let base_dir = profile_base_dir(&pool, &app)?;
+    let mut profiles = vec!["default".to_string()];
+    if let Ok(entries) = fs::read_dir(base_dir.join("profiles")) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("db") {
+                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                    if is_valid_profile_name(stem) {
+                        profiles.push(stem.to_string());
+                    }
+                }
+            }
+        }
+    }
+    profiles[1..].sort();
+    Ok(profiles)
+}
+
+/// "洞察" 页的库统计：总量、逐月增长、热门标签、格式分布、
+/// AI 模型分布、分辨率直方图和存储占用，全部用 SQL 聚合一次算完
+#[tauri::command]
+async fn get_library_statistics(
+    pool: tauri::State<'_, AppDbPool>,
+) -> Result<serde_json::Value, String> {
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get_connection();
+
+        let (image_count, folder_count, total_bytes): (i64, i64, i64) = conn
+            .query_row(
+                "SELECT
+                    COUNT(CASE WHEN file_type = 'Image' THEN 1 END),
+                    COUNT(CASE WHEN file_type = 'Folder' THEN 1 END),
+                    COALESCE(SUM(CASE WHEN file_type = 'Image' THEN size END), 0)
+                 FROM file_index",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .map_err(|e| e.to_string())?;
+
+        // 逐月新增（按文件创建时间，最近 24 个月）
+        let mut stmt = conn
+            .prepare(
+                "SELECT strftime('%Y-%m', created_at, 'unixepoch') AS month, COUNT(*)
+                 FROM file_index
+                 WHERE file_type = 'Image' AND created_at > 0
+                 GROUP BY month ORDER BY month DESC LIMIT 24",
+            )
+            .map_err(|e| e.to_string())?;
+        let mut growth: Vec<(String, i64)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        growth.reverse();
+
+        // 格式分布
+        let mut stmt = conn
+            .prepare(
+                "SELECT lower(COALESCE(format, 'unknown')), COUNT(*)
+                 FROM file_index WHERE file_type = 'Image'
+                 GROUP BY 1 ORDER BY 2 DESC",
+            )
+            .map_err(|e| e.to_string())?;
+        let formats: Vec<(String, i64)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        // 分辨率直方图（按百万像素分桶）
+        let mut stmt = conn
+            .prepare(
+                "SELECT
+                    CASE
+                        WHEN width * height < 1000000 THEN '<1MP'
+                        WHEN width * height < 4000000 THEN '1-4MP'
+                        WHEN width * height < 12000000 THEN '4-12MP'
+                        WHEN width * height < 24000000 THEN '12-24MP'
+                        ELSE '>24MP'
+                    END AS bucket, COUNT(*)
+                 FROM file_index
+                 WHERE file_type = 'Image' AND width > 0 AND height > 0
+                 GROUP BY bucket",
+            )
+            .map_err(|e| e.to_string())?;
+        let resolutions: std::collections::HashMap<String, i64> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        // 热门标签与 AI 模型分布：tags/ai_data 是 JSON 文本，在 Rust 侧解析聚合
+        let mut tag_counts: HashMap<String, i64> = HashMap::new();
+        let mut model_counts: HashMap<String, i64> = HashMap::new();
+        let mut stmt = conn
+            .prepare("SELECT tags, ai_data FROM file_metadata WHERE tags IS NOT NULL OR ai_data IS NOT NULL")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, Option<String>>(0)?, row.get::<_, Option<String>>(1)?))
+            })
+            .map_err(|e| e.to_string())?;
+        for row in rows.filter_map(|r| r.ok()) {
+            if let Some(tags) = row.0.and_then(|t| serde_json::from_str::<serde_json::Value>(&t).ok()) {
+                if let Some(arr) = tags.as_array() {
+                    for tag in arr.iter().filter_map(|t| t.as_str()) {
+                        *tag_counts.entry(tag.to_string()).or_insert(0) += 1;
+                    }
+                }
+            }
+            if let Some(ai) = row.1.and_then(|t| serde_json::from_str::<serde_json::Value>(&t).ok()) {
+                if let Some(model) = ai.get("model").and_then(|m| m.as_str()) {
+                    if !model.is_empty() {
+                        *model_counts.entry(model.to_string()).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+        let mut top_tags: Vec<(String, i64)> = tag_counts.into_iter().collect();
+        top_tags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top_tags.truncate(50);
+        let mut top_models: Vec<(String, i64)> = model_counts.into_iter().collect();
+        top_models.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top_models.truncate(20);
+
+        Ok(serde_json::json!({
+            "imageCount": image_count,
+            "folderCount": folder_count,
+            "totalBytes": total_bytes,
+            "growthByMonth": growth.iter().map(|(m, c)| serde_json::json!({"month": m, "count": c})).collect::<Vec<_>>(),
+            "topTags": top_tags.iter().map(|(t, c)| serde_json::json!({"tag": t, "count": c})).collect::<Vec<_>>(),
+            "formats": formats.iter().map(|(f, c)| serde_json::json!({"format": f, "count": c})).collect::<Vec<_>>(),
+            "aiModels": top_models.iter().map(|(m, c)| serde_json::json!({"model": m, "count": c})).collect::<Vec<_>>(),
+            "resolutionHistogram": resolutions,
+        }))
+    })
+    .await
+    .map_err(|e| format!("统计任务失败: {}", e))?
+}
+
 // 获取主色调数据库统计信息
 #[tauri::command]
 async fn get_color_db_stats(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
@@ -2492,7 +5085,7 @@ async fn retry_color_extraction(
         reset_count
     }).await.map_err(|e| format!("Failed to retry color extraction: {}", e))?;
     
-    result.map_err(|e| e)
+    result
 }
 
 // 从数据库中删除错误文件记录
@@ -2510,7 +5103,7 @@ async fn delete_color_db_error_files(
         color_db::delete_error_files(&mut conn, &file_paths)
     }).await.map_err(|e| format!("Failed to delete color db error files: {}", e))?;
     
-    result.map_err(|e| e)
+    result
 }
 
 /// 检查应用更新
@@ -2611,6 +5204,67 @@ fn open_update_download_folder() -> Result<(), String> {
     downloader.open_download_folder()
 }
 
+/// 根据来源链接选择公开 oEmbed 接口，不认识的站点返回 None
+fn oembed_endpoint(source_url: &str) -> Option<String> {
+    let encoded = urlencoding::encode(source_url);
+    if source_url.contains("twitter.com") || source_url.contains("//x.com") || source_url.contains("www.x.com") {
+        Some(format!("https://publish.twitter.com/oembed?url={}", encoded))
+    } else if source_url.contains("pixiv.net") {
+        Some(format!("https://embed.pixiv.net/oembed.php?url={}", encoded))
+    } else {
+        None
+    }
+}
+
+/// 通过来源站点的公开 oEmbed 接口抓取标题/作者并写入元数据，
+/// 返回 { title, author, authorUrl, provider }
+#[tauri::command]
+async fn fetch_source_metadata(
+    file_id: String,
+    pool: tauri::State<'_, AppDbPool>,
+) -> Result<serde_json::Value, String> {
+    let source_url = {
+        let conn = pool.get_connection();
+        db::file_metadata::get_metadata_by_id(&conn, &file_id)
+            .map_err(|e| e.to_string())?
+            .and_then(|m| m.source_url)
+            .ok_or("该文件没有来源链接")?
+    };
+
+    let endpoint = oembed_endpoint(&source_url).ok_or("不支持的来源站点")?;
+
+    let client = reqwest::Client::new();
+    let response = client
+        .get(&endpoint)
+        .header("User-Agent", "Aurora-Gallery")
+        .send()
+        .await
+        .map_err(|e| format!("请求来源站点失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("来源站点返回 HTTP {}", response.status()));
+    }
+    let data: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("解析 oEmbed 响应失败: {}", e))?;
+
+    let title = data.get("title").and_then(|v| v.as_str()).map(|s| s.to_string());
+    let author = data.get("author_name").and_then(|v| v.as_str()).map(|s| s.to_string());
+
+    {
+        let conn = pool.get_connection();
+        db::file_metadata::set_source_info(&conn, &file_id, title.as_deref(), author.as_deref())
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(serde_json::json!({
+        "title": title,
+        "author": author,
+        "authorUrl": data.get("author_url"),
+        "provider": data.get("provider_name"),
+    }))
+}
+
 /// 代理 HTTP 请求（用于绕过 CORS）
 #[tauri::command]
 async fn proxy_http_request(
@@ -2699,13 +5353,157 @@ async fn clip_search_by_text(
     let searcher = clip::search::SimilaritySearcher::new(embedding_store.clone());
     let options = SearchOptions {
         top_k: top_k.unwrap_or(50),
-        min_score: min_score.unwrap_or(0.0),
+        // 未显式指定时使用当前相似度预设的默认值
+        min_score: min_score.unwrap_or_else(|| crate::color_search::current_similarity_params().clip_min_score),
         include_score: true,
     };
     
     searcher.search(&text_embedding, &options)
 }
 
+/// 文本搜索 + 调色板重排序：CLIP 为主路，目标调色板的颜色相似度
+/// 作为加权信号混入。候选池取 top_k 的 4 倍，重排序后截回 top_k，
+/// 让颜色契合但语义分稍低的图有机会浮上来。权重默认 0.7 / 0.3
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn clip_search_by_text_with_palette(
+    text: String,
+    palette: Vec<String>,
+    top_k: Option<usize>,
+    min_score: Option<f32>,
+    clip_weight: Option<f32>,
+    color_weight: Option<f32>,
+    pool: tauri::State<'_, AppDbPool>,
+    color_db_pool: tauri::State<'_, Arc<color_db::ColorDbPool>>,
+) -> Result<Vec<SearchResult>, String> {
+    let top_k = top_k.unwrap_or(50);
+    // 候选池放大 4 倍再重排序，否则颜色信号只能在头部结果里洗牌
+    let candidates = clip_search_by_text(text, Some(top_k * 4), min_score).await?;
+    if candidates.is_empty() {
+        return Ok(candidates);
+    }
+
+    // 颜色库按路径存储，先把 file_id 映射回当前路径
+    let id_paths: Vec<(String, String)> = {
+        let conn = pool.get_connection();
+        candidates
+            .iter()
+            .filter_map(|r| {
+                db::file_index::get_entry_by_id(&conn, &r.file_id)
+                    .ok()
+                    .flatten()
+                    .map(|entry| (r.file_id.clone(), entry.path))
+            })
+            .collect()
+    };
+    let paths: Vec<String> = id_paths.iter().map(|(_, p)| p.clone()).collect();
+    let path_scores = color_search::palette_similarity_scores(color_db_pool.inner(), &paths, &palette);
+    let id_scores: std::collections::HashMap<String, f32> = id_paths
+        .into_iter()
+        .filter_map(|(id, path)| path_scores.get(&path).map(|s| (id, *s)))
+        .collect();
+
+    Ok(clip::search::Reranker::new(clip_weight.unwrap_or(0.7))
+        .with_signal(color_weight.unwrap_or(0.3), id_scores)
+        .apply(candidates, top_k))
+}
+
+/// 调色板搜索 + 文本重排序：上一条的反向，颜色为主路，CLIP 文本
+/// 相似度作为加权信号。调色板搜索只返回有序路径不带分数，主路分数
+/// 按排名位置折算；返回值仍是路径列表，前端沿用调色板搜索的展示逻辑
+#[tauri::command]
+async fn search_by_palette_with_text(
+    target_palette: Vec<String>,
+    text: String,
+    limit: Option<usize>,
+    color_weight: Option<f32>,
+    clip_weight: Option<f32>,
+    pool: tauri::State<'_, AppDbPool>,
+    color_db_pool: tauri::State<'_, Arc<color_db::ColorDbPool>>,
+) -> Result<Vec<String>, String> {
+    let limit = limit.unwrap_or(200);
+    let candidates = color_search::search_by_palette(
+        color_db_pool.clone(),
+        target_palette,
+        None,
+        Some(limit * 4),
+    )
+    .await?;
+    if candidates.is_empty() {
+        return Ok(candidates);
+    }
+
+    let manager = clip::get_clip_manager().await
+        .ok_or("CLIP manager not initialized")?;
+
+    // 检查并加载模型
+    {
+        let guard = manager.read().await;
+        if !guard.is_model_loaded() {
+            drop(guard);
+
+            let mut guard = manager.write().await;
+            if !guard.is_model_loaded() {
+                log::info!("CLIP model not loaded, loading now...");
+                guard.load_model().await.map_err(|e| format!("Failed to load model: {}", e))?;
+            }
+        }
+    }
+
+    let (text_embedding, embedding_store) = {
+        let mut guard = manager.write().await;
+        let model = guard.model_mut().ok_or("CLIP model not available")?;
+        let text_embedding = model.encode_text(&text)?;
+        let store = guard.embedding_store()
+            .ok_or("Embedding store not available")?
+            .clone();
+        (text_embedding, store)
+    };
+
+    // 路径查回 file_id（ID 在移动后保持不变，不能按当前路径重新散列）
+    let path_ids: Vec<(String, String)> = {
+        let conn = pool.get_connection();
+        let mut stmt = conn
+            .prepare("SELECT file_id FROM file_index WHERE path = ?1")
+            .map_err(|e| e.to_string())?;
+        candidates
+            .iter()
+            .filter_map(|path| {
+                stmt.query_row([path.as_str()], |row| row.get::<_, String>(0))
+                    .ok()
+                    .map(|id| (path.clone(), id))
+            })
+            .collect()
+    };
+
+    let mut clip_scores = std::collections::HashMap::new();
+    for (path, file_id) in &path_ids {
+        if let Ok(Some(emb)) = embedding_store.get_embedding(file_id) {
+            let score = clip::model::cosine_similarity(&text_embedding, &emb.embedding);
+            clip_scores.insert(path.clone(), score.max(0.0));
+        }
+    }
+
+    // 主路没有分数，用排名位置折算成 1.0 递减到接近 0 的线性分
+    let total = candidates.len();
+    let base: Vec<SearchResult> = candidates
+        .into_iter()
+        .enumerate()
+        .map(|(i, path)| SearchResult {
+            file_id: path,
+            score: (total - i) as f32 / total as f32,
+            rank: i + 1,
+        })
+        .collect();
+
+    Ok(clip::search::Reranker::new(color_weight.unwrap_or(0.7))
+        .with_signal(clip_weight.unwrap_or(0.3), clip_scores)
+        .apply(base, limit)
+        .into_iter()
+        .map(|r| r.file_id)
+        .collect())
+}
+
 /// 使用图片搜索相似图片（以图搜图）
 #[tauri::command]
 async fn clip_search_by_image(
@@ -2746,13 +5544,63 @@ async fn clip_search_by_image(
     let searcher = clip::search::SimilaritySearcher::new(embedding_store.clone());
     let options = SearchOptions {
         top_k: top_k.unwrap_or(50),
-        min_score: min_score.unwrap_or(0.0),
+        // 未显式指定时使用当前相似度预设的默认值
+        min_score: min_score.unwrap_or_else(|| crate::color_search::current_similarity_params().clip_min_score),
         include_score: true,
     };
     
     searcher.search(&image_embedding, &options)
 }
 
+/// 使用内存中的图片数据搜索相似图片（拖入外部图片作为查询）
+/// 与 clip_search_by_image 相同，但不要求文件存在于图库中
+#[tauri::command]
+async fn clip_search_by_image_data(
+    image_data: Vec<u8>,
+    top_k: Option<usize>,
+    min_score: Option<f32>,
+) -> Result<Vec<SearchResult>, String> {
+    let manager = clip::get_clip_manager().await
+        .ok_or("CLIP manager not initialized")?;
+
+    // 检查并加载模型
+    {
+        let guard = manager.read().await;
+        if !guard.is_model_loaded() {
+            drop(guard);
+
+            let mut guard = manager.write().await;
+            if !guard.is_model_loaded() {
+                log::info!("CLIP model not loaded, loading now...");
+                guard.load_model().await.map_err(|e| format!("Failed to load model: {}", e))?;
+            }
+        }
+    }
+
+    let mut guard = manager.write().await;
+
+    let model = guard.model_mut()
+        .ok_or("CLIP model not available")?;
+
+    // 直接编码图片字节
+    let image_embedding = model.encode_image_bytes(&image_data)?;
+
+    // 获取嵌入存储
+    let embedding_store = guard.embedding_store()
+        .ok_or("Embedding store not available")?;
+
+    // 执行搜索
+    let searcher = clip::search::SimilaritySearcher::new(embedding_store.clone());
+    let options = SearchOptions {
+        top_k: top_k.unwrap_or(50),
+        // 未显式指定时使用当前相似度预设的默认值
+        min_score: min_score.unwrap_or_else(|| crate::color_search::current_similarity_params().clip_min_score),
+        include_score: true,
+    };
+
+    searcher.search(&image_embedding, &options)
+}
+
 /// 为指定图片生成嵌入向量
 #[tauri::command]
 async fn clip_generate_embedding(
@@ -2815,6 +5663,21 @@ use once_cell::sync::Lazy;
 static CANCEL_GENERATION: Lazy<AtomicBool> = Lazy::new(|| AtomicBool::new(false));
 /// 暂停标志，用于暂停嵌入生成
 static PAUSE_GENERATION: Lazy<AtomicBool> = Lazy::new(|| AtomicBool::new(false));
+/// 嵌入生成是否在跑（优雅退出时据此记录断点）
+static EMBEDDING_RUNNING: Lazy<AtomicBool> = Lazy::new(|| AtomicBool::new(false));
+
+fn is_embedding_generation_running() -> bool {
+    EMBEDDING_RUNNING.load(Ordering::SeqCst)
+}
+
+/// RAII 守卫：批量生成结束（含任何一条提前返回路径）时自动清掉运行标志
+struct EmbeddingRunGuard;
+
+impl Drop for EmbeddingRunGuard {
+    fn drop(&mut self) {
+        EMBEDDING_RUNNING.store(false, Ordering::SeqCst);
+    }
+}
 
 /// 取消嵌入生成
 #[tauri::command]
@@ -2865,7 +5728,9 @@ async fn clip_generate_embeddings_batch(
 ) -> Result<serde_json::Value, String> {
     // 重置取消标志
     reset_cancel_flag();
-    
+    EMBEDDING_RUNNING.store(true, Ordering::SeqCst);
+    let _run_guard = EmbeddingRunGuard;
+
     let manager = clip::get_clip_manager().await
         .ok_or("CLIP manager not initialized")?;
     
@@ -2888,7 +5753,14 @@ async fn clip_generate_embeddings_batch(
         let guard = manager.read().await;
         let model = guard.model().ok_or("CLIP model not available")?;
         let using_gpu = model.is_using_gpu();
-        let batch_size = if using_gpu { 32 } else { 8 };
+        // 低内存模式下禁用批量推理，逐张处理以控制峰值内存
+        let batch_size = if is_low_memory() {
+            1
+        } else if using_gpu {
+            32
+        } else {
+            8
+        };
         let model_name = guard.config().model_name.clone();
         (using_gpu, batch_size, model_name)
     };
@@ -2946,9 +5818,14 @@ async fn clip_generate_embeddings_batch(
                     }
                 },
                 Ok(false) => {
-                    files_to_process.push((file_path.clone(), file_id.clone()));
-                    if index < 5 {
-                        log::debug!("File {} needs processing: file_id={}", index, file_id);
+                    // 云盘占位文件编码会触发整文件下载，跳过（materialize_file 之后再补）
+                    if fs_attrs::is_offline_placeholder(Path::new(file_path)) {
+                        skipped_count += 1;
+                    } else {
+                        files_to_process.push((file_path.clone(), file_id.clone()));
+                        if index < 5 {
+                            log::debug!("File {} needs processing: file_id={}", index, file_id);
+                        }
                     }
                 },
                 Err(e) => {
@@ -3243,8 +6120,7 @@ async fn clip_get_embedding_count() -> Result<i64, String> {
 #[tauri::command]
 async fn clip_get_model_status(model_name: String) -> Result<serde_json::Value, String> {
     use crate::clip::model::ModelInfo;
-    use std::path::Path;
-    
+
     let model_info = match model_name.as_str() {
         "ViT-B-32" => ModelInfo::vit_b_32(),
         "ViT-L-14" => ModelInfo::vit_l_14(),
@@ -3258,9 +6134,9 @@ async fn clip_get_model_status(model_name: String) -> Result<serde_json::Value,
     let cache_dir = &guard.config().cache_dir;
     
     // 检查模型文件是否存在
-    let image_model_file = model_info.image_model_url.split('/').last().unwrap_or("image_encoder.onnx");
-    let text_model_file = model_info.text_model_url.split('/').last().unwrap_or("text_encoder.onnx");
-    let tokenizer_file = model_info.tokenizer_url.split('/').last().unwrap_or("tokenizer.json");
+    let image_model_file = model_info.image_model_url.split('/').next_back().unwrap_or("image_encoder.onnx");
+    let text_model_file = model_info.text_model_url.split('/').next_back().unwrap_or("text_encoder.onnx");
+    let tokenizer_file = model_info.tokenizer_url.split('/').next_back().unwrap_or("tokenizer.json");
     
     let image_path = cache_dir.join(image_model_file);
     let text_path = cache_dir.join(text_model_file);
@@ -3323,9 +6199,9 @@ async fn clip_delete_model(model_name: String) -> Result<(), String> {
     let cache_dir = &guard.config().cache_dir;
     
     // 删除模型文件
-    let image_model_file = model_info.image_model_url.split('/').last().unwrap_or("image_encoder.onnx");
-    let text_model_file = model_info.text_model_url.split('/').last().unwrap_or("text_encoder.onnx");
-    let tokenizer_file = model_info.tokenizer_url.split('/').last().unwrap_or("tokenizer.json");
+    let image_model_file = model_info.image_model_url.split('/').next_back().unwrap_or("image_encoder.onnx");
+    let text_model_file = model_info.text_model_url.split('/').next_back().unwrap_or("text_encoder.onnx");
+    let tokenizer_file = model_info.tokenizer_url.split('/').next_back().unwrap_or("tokenizer.json");
     
     let image_path = cache_dir.join(image_model_file);
     let text_path = cache_dir.join(text_model_file);
@@ -3418,7 +6294,9 @@ async fn get_all_image_files(app: tauri::AppHandle) -> Result<Vec<serde_json::Va
 }
 
 fn main() {
-    
+    // 启动时按系统内存自动判断是否进入低内存模式（前端设置可随后覆盖）
+    LOW_MEMORY_MODE.store(detect_low_memory(), Ordering::SeqCst);
+
     tauri::Builder::default()
         // 清理调试阶段的 setup 注入，恢复默认构建
         .plugin(tauri_plugin_dialog::init())
@@ -3426,22 +6304,43 @@ fn main() {
         .plugin(tauri_plugin_shell::init())
         .plugin(
             tauri_plugin_log::Builder::default()
-                .level(log::LevelFilter::Info)
+                // 底层放行到 Trace，实际级别由 app_log 的运行期过滤器决定
+                .level(log::LevelFilter::Trace)
+                .filter(app_log::should_log)
+                .targets([
+                    tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::Stdout),
+                    tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::LogDir {
+                        file_name: Some(app_log::LOG_FILE_NAME.into()),
+                    }),
+                ])
+                .max_file_size(app_log::MAX_LOG_FILE_SIZE)
+                .rotation_strategy(tauri_plugin_log::RotationStrategy::KeepSome(3))
                 .build()
         )
         .plugin(tauri_plugin_drag::init())
+        .register_uri_scheme_protocol("quickpreview", quick_preview::handle_protocol)
+        .register_uri_scheme_protocol("deepzoom", deep_zoom::handle_protocol)
         .invoke_handler(tauri::generate_handler![
             save_user_data,
             load_user_data,
             search_by_palette,
+            search_by_palette_stream,
+            search_by_palette_detailed,
+            set_similarity_preset,
+            get_similarity_params,
             search_by_color,
             scan_directory,
             db_copy_file_metadata,
             force_rescan,
+            set_natural_sort_pinyin,
             add_pending_files_to_db,
             get_default_paths,
             get_thumbnail,
             get_thumbnails_batch,
+            get_thumbnail_settings,
+            set_thumbnail_settings,
+            regenerate_thumbnails,
+            pregenerate_thumbnails,
             save_remote_thumbnail,
             get_avif_preview,
             get_jxl_preview,
@@ -3452,11 +6351,134 @@ fn main() {
             open_path,
             create_folder,
             rename_file,
+            batch_rename,
             delete_file,
+            delete_file_to_trash,
+            get_corrupt_files,
+            get_recursive_size,
+            get_storage_treemap,
+            get_random_files,
+            undo_last_operation,
+            redo,
+            get_undo_redo_counts,
             copy_file,
             copy_image_colors,
             move_file,
+            merge_folders,
+            resolve_file_conflicts,
+            set_conflict_suffix_style,
+            get_conflict_suffix_style,
+            get_companion_files,
+            apply_companion_captions,
+            find_cleanup_candidates,
+            apply_cleanup,
             write_file_from_bytes,
+            notify_file_modified,
+            get_activity_feed,
+            set_portable_mode,
+            get_portable_mode,
+            get_id_namespace,
+            set_id_namespace,
+            folder_sync::sync_folder,
+            export_backend::save_export_target,
+            export_backend::list_export_targets,
+            export_backend::delete_export_target,
+            export_backend::export_to_target,
+            api_server::start_api_server,
+            api_server::stop_api_server,
+            api_server::get_api_server_status,
+            ingest::set_ingest_inbox,
+            ingest::get_ingest_inbox,
+            automation::run_automation,
+            plugins::load_plugins,
+            plugins::list_plugins,
+            plugins::reload_plugins,
+            plugins::run_plugin_processor,
+            plugins::run_plugin_metadata,
+            plugins::run_plugin_export,
+            relink::suggest_relink_targets,
+            relink::relink_missing,
+            library_lock::is_library_read_only,
+            library_lock::get_library_lock_info,
+            db_health::get_db_health,
+            db_health::run_db_health_check,
+            app_log::set_log_level,
+            app_log::get_log_levels,
+            app_log::get_recent_logs,
+            benchmark::run_benchmark,
+            volume_info::get_volume_info,
+            io_throttle::set_io_throttle_settings,
+            io_throttle::get_io_throttle_settings,
+            get_last_scan_report,
+            fs_attrs::set_show_hidden_files,
+            fs_attrs::get_show_hidden_files,
+            fs_attrs::materialize_file,
+            open_with::get_open_with_apps,
+            open_with::open_with,
+            open_with::set_external_editor,
+            open_with::get_external_editor,
+            open_with::open_in_editor,
+            wallpaper::set_as_wallpaper,
+            wallpaper::start_wallpaper_rotation,
+            wallpaper::stop_wallpaper_rotation,
+            wallpaper::get_wallpaper_rotation_status,
+            quick_preview::show_quick_preview,
+            quick_preview::toggle_quick_preview,
+            quick_preview::close_quick_preview,
+            compare::compare_images,
+            histogram::get_image_histogram,
+            deep_zoom::prepare_deep_zoom,
+            deep_zoom::clear_deep_zoom_cache,
+            face_export::export_face_crops,
+            face_redact::export_redacted,
+            face_redact::share_redacted,
+            dataset_export::export_training_dataset,
+            watch_folders::save_watch_rule,
+            watch_folders::list_watch_rules,
+            watch_folders::delete_watch_rule,
+            watch_folders::test_rule,
+            watch_folders::start_watch_rules,
+            watch_folders::stop_watch_rules,
+            watch_folders::get_watch_rules_status,
+            autostart::set_launch_at_login,
+            autostart::get_launch_at_login,
+            shutdown::take_resume_state,
+            tray_status::set_tray_status,
+            notifications::notify_job,
+            notifications::set_notifications_enabled,
+            notifications::get_notifications_enabled,
+            i18n::set_locale,
+            i18n::get_locale_strings,
+            audio::get_audio_info,
+            audio::generate_waveform_thumbnail,
+            model::generate_model_thumbnail,
+            workbench::workbench_add,
+            workbench::workbench_remove,
+            workbench::workbench_list,
+            workbench::workbench_clear,
+            list_export::export_file_list,
+            drag_out::start_file_drag,
+            share::share_files,
+            zip_package::package_as_zip,
+            cull::start_cull_session,
+            cull::cull_decide,
+            cull::finish_cull_session,
+            cull::cancel_cull_session,
+            annotate::save_annotations,
+            annotate::load_annotations,
+            annotate::export_annotated_copy,
+            versions::list_versions,
+            versions::restore_version,
+            virtual_folder::create_virtual_folder,
+            virtual_folder::list_virtual_folders,
+            virtual_folder::get_virtual_folder_ids,
+            virtual_folder::get_virtual_folder_page,
+            virtual_folder::delete_virtual_folder,
+            related::precompute_related,
+            related::get_related,
+            grounding::ground_text_in_image,
+            embedding_io::clip_export_embeddings,
+            embedding_io::clip_import_embeddings,
             scan_file,
             hide_window,
             show_window,
@@ -3465,6 +6487,16 @@ fn main() {
             get_dominant_colors,
             color_worker::pause_color_extraction,
             color_worker::resume_color_extraction,
+            color_worker::build_color_index,
+            color_worker::is_color_index_building,
+            color_worker::reextract_colors,
+            color_extractor::set_color_extraction_settings,
+            color_extractor::get_color_extraction_settings,
+            get_background_status,
+            set_low_memory_mode,
+            get_low_memory_mode,
+            set_viewer_cache_size,
+            get_viewer_cache_stats,
             force_wal_checkpoint,
             get_wal_info,
             db_get_all_people,
@@ -3474,11 +6506,40 @@ fn main() {
             db_get_all_topics,
             db_upsert_topic,
             db_delete_topic,
+            db_get_all_categories,
+            db_upsert_category,
+            db_rename_category,
+            db_delete_category,
+            db_reorder_categories,
+            get_category_counts,
+            classifier::run_auto_classification,
+            classifier::is_classification_running,
+            classifier::run_nsfw_scan,
+            classifier::clip_zero_shot_classify,
+            sd_metadata::extract_sd_metadata,
+            sd_metadata::scan_sd_metadata,
+            metadata_writeback::scan_embedded_keywords,
+            filter_query::query_files,
+            sd_metadata::get_files_by_sd_model,
+            sd_metadata::search_prompts,
+            sd_metadata::search_by_prompt_text,
             db_upsert_file_metadata,
+            set_metadata_writeback,
+            get_metadata_writeback,
+            writeback_file_metadata,
+            bulk_update_metadata,
+            set_note,
+            get_note,
+            search_notes,
             db_copy_file_metadata,
             switch_root_database,
+            switch_profile,
+            get_current_profile,
+            list_profiles,
             copy_image_to_clipboard,
+            copy_paths_to_clipboard,
             get_color_db_stats,
+            get_library_statistics,
             get_color_db_error_files,
             retry_color_extraction,
             delete_color_db_error_files,
@@ -3492,9 +6553,13 @@ fn main() {
             install_update,
             open_update_download_folder,
             proxy_http_request,
+            fetch_source_metadata,
             // CLIP 相关命令
             clip_search_by_text,
+            clip_search_by_text_with_palette,
+            search_by_palette_with_text,
             clip_search_by_image,
+            clip_search_by_image_data,
             clip_generate_embedding,
             clip_get_embedding_status,
             clip_load_model,
@@ -3512,8 +6577,10 @@ fn main() {
         ])
         .setup(|app| {
             // 创建托盘菜单
-            let show_item = MenuItem::with_id(app, "show", "显示窗口", true, None::<&str>)?;
-            let quit_item = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
+            // 托盘菜单文字走本地化词条表（set_locale 时会重建菜单）
+            i18n::load_locale(app.handle());
+            let show_item = MenuItem::with_id(app, "show", i18n::t("tray.show"), true, None::<&str>)?;
+            let quit_item = MenuItem::with_id(app, "quit", i18n::t("tray.quit"), true, None::<&str>)?;
             let menu = Menu::with_items(app, &[&show_item, &quit_item])?;
             
             // 获取应用句柄用于事件处理
@@ -3547,7 +6614,13 @@ fn main() {
                             }
                         }
                         "quit" => {
-                            app.exit(0);
+                            // 与 exit_app 相同的收尾流程，托盘退出也不跳过
+                            let app = app.clone();
+                            tauri::async_runtime::spawn(async move {
+                                save_window_state(&app);
+                                shutdown::coordinate(&app).await;
+                                app.exit(0);
+                            });
                         }
                         _ => {}
                     }
@@ -3574,6 +6647,14 @@ fn main() {
             
             // 获取数据库路径（如果有保存的根目录，则使用其下的 .aurora 文件夹）
             let (db_path, app_db_path) = get_initial_db_paths(app.handle());
+
+            // 抢库占用锁；被别的实例占用时降级为只读（前端据此禁用写入口）
+            if let Some(lock_dir) = db_path.parent() {
+                if let Err(e) = library_lock::acquire(lock_dir) {
+                    eprintln!("[library_lock] {}，本实例以只读方式打开", e);
+                    library_lock::mark_read_only();
+                }
+            }
             
             // 初始化颜色数据库
             let pool = match color_db::ColorDbPool::new(&db_path) {
@@ -3628,8 +6709,33 @@ fn main() {
                     panic!("Failed to create app database pool: {}", e);
                 }
             };
+            // 便携库：数据库随库放在 root/.aurora 下时，启动即检查根路径是否变化
+            if app_db_path.parent().and_then(|d| d.file_name()) == Some(std::ffi::OsStr::new(".aurora")) {
+                if let Some(root) = app_db_path.parent().and_then(|d| d.parent()).and_then(|r| r.to_str()) {
+                    remap_library_root(&app_db_pool, &pool_arc, root);
+                }
+            }
+            // 启动时如果发生了命名空间迁移，把嵌入库的键跟着改写
+            tauri::async_runtime::spawn(apply_pending_id_migrations());
+
+            // 启动单写入者队列，所有元数据写操作经由它串行化提交
+            app.manage(db::writer::DbWriter::start(app_db_pool.clone()));
             app.manage(app_db_pool);
-            
+
+            // 后台数据库健康检查（损坏时自动修复并广播结果）
+            let health_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                db_health::run_health_check(health_handle).await;
+            });
+
+            // 恢复上次未完成的尺寸补全任务（队列为空时立即退出）
+            if std::env::var("AURORA_DISABLE_BACKGROUND_INDEX").as_deref().ok() != Some("1") {
+                let backfill_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    spawn_dimension_backfill(backfill_handle);
+                });
+            }
+
             // 启动后台颜色提取任务
             // 持续处理待处理文件，每批最多处理50个文件
             let batch_size = 50;
@@ -3679,20 +6785,50 @@ fn main() {
                 ).await;
             });
             
+            // --background 启动：托盘与后台任务都已就位，主窗口保持隐藏
             // 恢复窗口位置和大小
-            if let Some(window) = app.get_webview_window("main") {
+            if autostart::is_background_launch() {
+                log::info!("以后台模式启动，主窗口保持隐藏");
+            } else if let Some(window) = app.get_webview_window("main") {
                 let app_handle_for_state = app.handle();
                 let path = get_window_state_path(app_handle_for_state);
                 let mut state_restored = false;
                 if path.exists() {
                     if let Ok(json) = fs::read_to_string(&path) {
                         if let Ok(state) = serde_json::from_str::<SavedWindowState>(&json) {
+                            // 校验保存的位置仍然可见：显示器可能已断开或换了排布
+                            let monitors: Vec<(f64, f64, f64, f64)> = window
+                                .available_monitors()
+                                .map(|ms| {
+                                    ms.iter()
+                                        .map(|m| {
+                                            let pos = m.position().to_logical::<f64>(m.scale_factor());
+                                            let size = m.size().to_logical::<f64>(m.scale_factor());
+                                            (pos.x, pos.y, size.width, size.height)
+                                        })
+                                        .collect()
+                                })
+                                .unwrap_or_default();
+                            let monitor_connected = state.monitor.as_ref().is_none_or(|name| {
+                                window
+                                    .available_monitors()
+                                    .map(|ms| ms.iter().any(|m| m.name() == Some(name)))
+                                    .unwrap_or(true)
+                            });
                             let _ = window.set_size(tauri::Size::Logical(tauri::LogicalSize { width: state.width, height: state.height }));
-                            let _ = window.set_position(tauri::Position::Logical(tauri::LogicalPosition { x: state.x, y: state.y }));
+                            if monitor_connected
+                                && window_bounds_visible(&monitors, state.x, state.y, state.width)
+                            {
+                                let _ = window.set_position(tauri::Position::Logical(tauri::LogicalPosition { x: state.x, y: state.y }));
+                                state_restored = true;
+                            } else {
+                                // 位置失效：尺寸照旧，位置交给下面的居中逻辑
+                                log::info!("上次的窗口位置不在任何已连接显示器上，恢复为居中");
+                            }
                             if state.maximized {
                                 let _ = window.maximize();
+                                state_restored = true;
                             }
-                            state_restored = true;
                         }
                     }
                 }
@@ -3709,6 +6845,8 @@ fn main() {
             if let tauri::WindowEvent::CloseRequested { .. } = event {
                 // 保存窗口状态
                 save_window_state(window.app_handle());
+                // 释放库占用锁，别的实例可以立即接管
+                library_lock::release();
             }
         })
         .run(tauri::generate_context!())
@@ -0,0 +1,738 @@
+//! 元数据写回：把评分 / 标签 / 描述以 XMP 包的形式嵌入图片文件本身，
+//! 让元数据跟随文件迁移到其它软件（Lightroom、digiKam、Bridge 等均可读取）。
+//!
+//! - JPEG：写入 APP1 XMP 段（替换已有的 XMP 段）
+//! - PNG：写入 iTXt 块（关键字 XML:com.adobe.xmp）
+//! - TIFF：在文件末尾追加 XMP 数据并重建 IFD0（标签 700），不移动原有数据
+//!
+//! 所有写入都通过同目录临时文件 + rename 原子替换，中途失败不会损坏原文件
+
+use std::fs;
+use std::path::Path;
+
+/// XML 文本转义
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// 生成标准 XMP 包（xmp:Rating + dc:subject + dc:description）
+pub fn build_xmp_packet(
+    rating: Option<i64>,
+    tags: &[String],
+    description: Option<&str>,
+) -> String {
+    let mut body = String::new();
+    if let Some(r) = rating {
+        body.push_str(&format!("   <xmp:Rating>{}</xmp:Rating>\n", r.clamp(0, 5)));
+    }
+    if !tags.is_empty() {
+        body.push_str("   <dc:subject>\n    <rdf:Bag>\n");
+        for tag in tags {
+            body.push_str(&format!("     <rdf:li>{}</rdf:li>\n", xml_escape(tag)));
+        }
+        body.push_str("    </rdf:Bag>\n   </dc:subject>\n");
+    }
+    if let Some(desc) = description {
+        if !desc.is_empty() {
+            body.push_str(&format!(
+                "   <dc:description>\n    <rdf:Alt>\n     <rdf:li xml:lang=\"x-default\">{}</rdf:li>\n    </rdf:Alt>\n   </dc:description>\n",
+                xml_escape(desc)
+            ));
+        }
+    }
+
+    format!(
+        "<?xpacket begin=\"\u{FEFF}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n\
+         <x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n \
+         <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n  \
+         <rdf:Description rdf:about=\"\"\n    \
+         xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\"\n    \
+         xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n{}  \
+         </rdf:Description>\n \
+         </rdf:RDF>\n\
+         </x:xmpmeta>\n\
+         <?xpacket end=\"w\"?>",
+        body
+    )
+}
+
+/// JPEG APP1 XMP 段的命名空间头
+const XMP_JPEG_HEADER: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+
+/// 在 JPEG 字节流中插入 / 替换 APP1 XMP 段。
+/// 新段插在开头的 APP0/APP1(Exif) 段之后，已有的 XMP 段被丢弃
+fn embed_xmp_jpeg(data: &[u8], xmp: &str) -> Result<Vec<u8>, String> {
+    if data.len() < 4 || data[0] != 0xFF || data[1] != 0xD8 {
+        return Err("不是有效的 JPEG 文件".to_string());
+    }
+    let payload_len = XMP_JPEG_HEADER.len() + xmp.len() + 2;
+    if payload_len > 0xFFFF {
+        return Err("XMP 包过大，无法放入单个 APP1 段".to_string());
+    }
+
+    let mut out = Vec::with_capacity(data.len() + payload_len + 2);
+    out.extend_from_slice(&data[..2]);
+
+    let mut pos = 2usize;
+    let mut inserted = false;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            break;
+        }
+        let marker = data[pos + 1];
+        // SOS 之后是压缩数据，不再有可插入的位置
+        if marker == 0xDA {
+            break;
+        }
+        let seg_len = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        let seg_end = pos + 2 + seg_len;
+        if seg_len < 2 || seg_end > data.len() {
+            return Err("JPEG 段长度损坏".to_string());
+        }
+
+        let is_xmp = marker == 0xE1
+            && data[pos + 4..seg_end].starts_with(XMP_JPEG_HEADER);
+        let is_leading_app = marker == 0xE0 || marker == 0xE1;
+
+        // 在开头的 APP0/APP1 段结束后插入新 XMP 段
+        if !inserted && !is_leading_app {
+            out.extend_from_slice(&[0xFF, 0xE1]);
+            out.extend_from_slice(&(payload_len as u16).to_be_bytes());
+            out.extend_from_slice(XMP_JPEG_HEADER);
+            out.extend_from_slice(xmp.as_bytes());
+            inserted = true;
+        }
+
+        // 旧的 XMP 段直接丢弃
+        if !is_xmp {
+            out.extend_from_slice(&data[pos..seg_end]);
+        }
+        pos = seg_end;
+    }
+
+    if !inserted {
+        out.extend_from_slice(&[0xFF, 0xE1]);
+        out.extend_from_slice(&(payload_len as u16).to_be_bytes());
+        out.extend_from_slice(XMP_JPEG_HEADER);
+        out.extend_from_slice(xmp.as_bytes());
+    }
+    out.extend_from_slice(&data[pos..]);
+    Ok(out)
+}
+
+/// PNG 块 CRC32（IEEE 802.3 多项式，与 PNG 规范一致）
+fn png_crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            if crc & 1 != 0 {
+                crc = (crc >> 1) ^ 0xEDB8_8320;
+            } else {
+                crc >>= 1;
+            }
+        }
+    }
+    !crc
+}
+
+/// PNG XMP iTXt 块的关键字
+const XMP_PNG_KEYWORD: &[u8] = b"XML:com.adobe.xmp";
+
+/// 在 PNG 字节流中插入 / 替换 XMP iTXt 块（插在 IEND 之前）
+fn embed_xmp_png(data: &[u8], xmp: &str) -> Result<Vec<u8>, String> {
+    const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    if data.len() < 8 || data[..8] != SIGNATURE {
+        return Err("不是有效的 PNG 文件".to_string());
+    }
+
+    // iTXt 数据：keyword\0 压缩标志 压缩方法 语言\0 翻译关键字\0 文本
+    let mut chunk_data = Vec::with_capacity(XMP_PNG_KEYWORD.len() + 5 + xmp.len());
+    chunk_data.extend_from_slice(XMP_PNG_KEYWORD);
+    chunk_data.extend_from_slice(&[0, 0, 0, 0, 0]);
+    chunk_data.extend_from_slice(xmp.as_bytes());
+
+    let mut xmp_chunk = Vec::with_capacity(chunk_data.len() + 12);
+    xmp_chunk.extend_from_slice(&(chunk_data.len() as u32).to_be_bytes());
+    xmp_chunk.extend_from_slice(b"iTXt");
+    xmp_chunk.extend_from_slice(&chunk_data);
+    let mut crc_input = Vec::with_capacity(4 + chunk_data.len());
+    crc_input.extend_from_slice(b"iTXt");
+    crc_input.extend_from_slice(&chunk_data);
+    xmp_chunk.extend_from_slice(&png_crc32(&crc_input).to_be_bytes());
+
+    let mut out = Vec::with_capacity(data.len() + xmp_chunk.len());
+    out.extend_from_slice(&data[..8]);
+
+    let mut pos = 8usize;
+    while pos + 12 <= data.len() {
+        let len = u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize;
+        let chunk_type = &data[pos + 4..pos + 8];
+        let chunk_end = pos + 12 + len;
+        if chunk_end > data.len() {
+            return Err("PNG 块长度损坏".to_string());
+        }
+
+        if chunk_type == b"IEND" {
+            out.extend_from_slice(&xmp_chunk);
+            out.extend_from_slice(&data[pos..]);
+            return Ok(out);
+        }
+
+        // 旧的 XMP iTXt 块直接丢弃
+        let is_old_xmp = chunk_type == b"iTXt"
+            && data[pos + 8..chunk_end - 4].starts_with(XMP_PNG_KEYWORD);
+        if !is_old_xmp {
+            out.extend_from_slice(&data[pos..chunk_end]);
+        }
+        pos = chunk_end;
+    }
+
+    Err("PNG 文件缺少 IEND 块".to_string())
+}
+
+/// 在 TIFF 字节流中写入 XMP（标签 700）。
+/// 不移动已有数据：XMP 数据与重建后的 IFD0 追加在文件末尾，仅改写文件头的 IFD0 偏移
+fn embed_xmp_tiff(data: &[u8], xmp: &str) -> Result<Vec<u8>, String> {
+    if data.len() < 8 {
+        return Err("不是有效的 TIFF 文件".to_string());
+    }
+    let little_endian = match &data[..2] {
+        b"II" => true,
+        b"MM" => false,
+        _ => return Err("不是有效的 TIFF 文件".to_string()),
+    };
+    let read_u16 = |bytes: &[u8]| -> u16 {
+        let arr = [bytes[0], bytes[1]];
+        if little_endian { u16::from_le_bytes(arr) } else { u16::from_be_bytes(arr) }
+    };
+    let read_u32 = |bytes: &[u8]| -> u32 {
+        let arr = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if little_endian { u32::from_le_bytes(arr) } else { u32::from_be_bytes(arr) }
+    };
+    let write_u16 = |v: u16| -> [u8; 2] {
+        if little_endian { v.to_le_bytes() } else { v.to_be_bytes() }
+    };
+    let write_u32 = |v: u32| -> [u8; 4] {
+        if little_endian { v.to_le_bytes() } else { v.to_be_bytes() }
+    };
+
+    if read_u16(&data[2..4]) != 42 {
+        return Err("不支持的 TIFF 变体（BigTIFF）".to_string());
+    }
+
+    let ifd0_offset = read_u32(&data[4..8]) as usize;
+    if ifd0_offset + 2 > data.len() {
+        return Err("TIFF IFD0 偏移损坏".to_string());
+    }
+    let entry_count = read_u16(&data[ifd0_offset..ifd0_offset + 2]) as usize;
+    let entries_end = ifd0_offset + 2 + entry_count * 12;
+    if entries_end + 4 > data.len() {
+        return Err("TIFF IFD0 结构损坏".to_string());
+    }
+    let next_ifd = read_u32(&data[entries_end..entries_end + 4]);
+
+    // 收集 IFD0 条目，丢弃已有的 XMP 条目（标签 700）
+    const TAG_XMP: u16 = 700;
+    let mut entries: Vec<[u8; 12]> = Vec::with_capacity(entry_count + 1);
+    for i in 0..entry_count {
+        let start = ifd0_offset + 2 + i * 12;
+        let tag = read_u16(&data[start..start + 2]);
+        if tag == TAG_XMP {
+            continue;
+        }
+        let mut entry = [0u8; 12];
+        entry.copy_from_slice(&data[start..start + 12]);
+        entries.push(entry);
+    }
+
+    let mut out = data.to_vec();
+    // 偏移按惯例对齐到偶数
+    if !out.len().is_multiple_of(2) {
+        out.push(0);
+    }
+    let xmp_offset = out.len() as u32;
+    out.extend_from_slice(xmp.as_bytes());
+    if !out.len().is_multiple_of(2) {
+        out.push(0);
+    }
+
+    // 新的 XMP 条目：类型 1 (BYTE)，数据在追加区
+    let mut xmp_entry = [0u8; 12];
+    xmp_entry[..2].copy_from_slice(&write_u16(TAG_XMP));
+    xmp_entry[2..4].copy_from_slice(&write_u16(1));
+    xmp_entry[4..8].copy_from_slice(&write_u32(xmp.len() as u32));
+    xmp_entry[8..12].copy_from_slice(&write_u32(xmp_offset));
+    entries.push(xmp_entry);
+    // IFD 条目要求按标签升序排列
+    entries.sort_by_key(|e| read_u16(&e[..2]));
+
+    // 在文件末尾重建 IFD0，并把文件头指向它
+    let new_ifd_offset = out.len() as u32;
+    out.extend_from_slice(&write_u16(entries.len() as u16));
+    for entry in &entries {
+        out.extend_from_slice(entry);
+    }
+    out.extend_from_slice(&write_u32(next_ifd));
+    out[4..8].copy_from_slice(&write_u32(new_ifd_offset));
+
+    Ok(out)
+}
+
+/// 判断扩展名是否支持元数据写回
+pub fn supports_writeback(ext: &str) -> bool {
+    matches!(ext, "jpg" | "jpeg" | "png" | "tif" | "tiff")
+}
+
+/// 把评分 / 标签 / 描述写回图片文件。
+/// 先在同目录生成临时文件，成功后 rename 原子替换原文件
+pub fn write_back(
+    file_path: &str,
+    rating: Option<i64>,
+    tags: &[String],
+    description: Option<&str>,
+) -> Result<(), String> {
+    let path = Path::new(file_path);
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    if !supports_writeback(&ext) {
+        return Err(format!("不支持写回的格式: {}", ext));
+    }
+
+    let data = fs::read(path).map_err(|e| format!("读取文件失败: {}", e))?;
+    let xmp = build_xmp_packet(rating, tags, description);
+
+    let new_data = match ext.as_str() {
+        "jpg" | "jpeg" => embed_xmp_jpeg(&data, &xmp)?,
+        "png" => embed_xmp_png(&data, &xmp)?,
+        _ => embed_xmp_tiff(&data, &xmp)?,
+    };
+
+    let tmp_path = path.with_extension(format!("{}.aurora-tmp", ext));
+    fs::write(&tmp_path, &new_data).map_err(|e| format!("写入临时文件失败: {}", e))?;
+    if let Err(e) = fs::rename(&tmp_path, path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(format!("替换原文件失败: {}", e));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jpeg_roundtrip_keeps_structure() {
+        // 最小 JPEG：SOI + APP0 + 伪 SOS 数据 + EOI
+        let mut jpeg = vec![0xFF, 0xD8];
+        jpeg.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x04, 0x01, 0x02]);
+        jpeg.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02]);
+        jpeg.extend_from_slice(&[0x11, 0x22, 0xFF, 0xD9]);
+
+        let out = embed_xmp_jpeg(&jpeg, "<xmp/>").unwrap();
+        assert_eq!(&out[..2], &[0xFF, 0xD8]);
+        // 原 APP0 保留在新 XMP 段之前
+        assert_eq!(&out[2..4], &[0xFF, 0xE0]);
+        assert_eq!(&out[8..10], &[0xFF, 0xE1]);
+        // 再写一次不会累积出第二个 XMP 段
+        let twice = embed_xmp_jpeg(&out, "<xmp2/>").unwrap();
+        let count = twice.windows(XMP_JPEG_HEADER.len()).filter(|w| *w == XMP_JPEG_HEADER).count();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_png_chunk_inserted_before_iend() {
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        // 空 IHDR（内容不合法但结构合法）+ IEND
+        png.extend_from_slice(&[0, 0, 0, 0]);
+        png.extend_from_slice(b"IHDR");
+        png.extend_from_slice(&png_crc32(b"IHDR").to_be_bytes());
+        png.extend_from_slice(&[0, 0, 0, 0]);
+        png.extend_from_slice(b"IEND");
+        png.extend_from_slice(&png_crc32(b"IEND").to_be_bytes());
+
+        let out = embed_xmp_png(&png, "<xmp/>").unwrap();
+        let itxt_pos = out.windows(4).position(|w| w == b"iTXt").unwrap();
+        let iend_pos = out.windows(4).position(|w| w == b"IEND").unwrap();
+        assert!(itxt_pos < iend_pos);
+    }
+
+    #[test]
+    fn test_xmp_packet_escapes_xml() {
+        let packet = build_xmp_packet(Some(4), &["a<b".to_string()], Some("x & y"));
+        assert!(packet.contains("<xmp:Rating>4</xmp:Rating>"));
+        assert!(packet.contains("a&lt;b"));
+        assert!(packet.contains("x &amp; y"));
+    }
+}
+
+// ---------------------------------------------------------------------------
+// 读取方向：扫描文件中已有的 IPTC/XMP 关键字与人物标签，
+// 让在 Lightroom 等软件里整理过的库导入后自带标签
+// ---------------------------------------------------------------------------
+
+/// 从 XMP 包中提取某个元素（如 dc:subject / Iptc4xmpExt:PersonInImage）
+/// 内的所有 <rdf:li> 文本
+fn xmp_list_items(xmp: &str, element: &str) -> Vec<String> {
+    let open = format!("<{}", element);
+    let close = format!("</{}>", element);
+    let Some(start) = xmp.find(&open) else {
+        return Vec::new();
+    };
+    let block = match xmp[start..].find(&close) {
+        Some(end) => &xmp[start..start + end],
+        None => return Vec::new(),
+    };
+
+    let mut items = Vec::new();
+    let mut rest = block;
+    while let Some(li_start) = rest.find("<rdf:li") {
+        let after_tag = &rest[li_start..];
+        let Some(gt) = after_tag.find('>') else { break };
+        let content_start = gt + 1;
+        let Some(li_end) = after_tag[content_start..].find("</rdf:li>") else {
+            break;
+        };
+        let text = after_tag[content_start..content_start + li_end].trim();
+        if !text.is_empty() {
+            items.push(xml_unescape(text));
+        }
+        rest = &after_tag[content_start + li_end..];
+    }
+    items
+}
+
+/// XML 文本反转义（与 xml_escape 对应）
+fn xml_unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// 从 XMP 包中收集关键字与人物标签：
+/// dc:subject（通用关键字）、Iptc4xmpExt:PersonInImage（IPTC 扩展人物）、
+/// mwg-rs 人脸区域名（Lightroom/Picasa 的人物标注）
+fn keywords_from_xmp(xmp: &str) -> Vec<String> {
+    let mut keywords = xmp_list_items(xmp, "dc:subject");
+    keywords.extend(xmp_list_items(xmp, "Iptc4xmpExt:PersonInImage"));
+
+    // mwg-rs 区域名以属性形式出现：mwg-rs:Name="某人"
+    let mut rest = xmp;
+    while let Some(pos) = rest.find("mwg-rs:Name=\"") {
+        let value_start = pos + "mwg-rs:Name=\"".len();
+        let Some(end) = rest[value_start..].find('"') else {
+            break;
+        };
+        let name = rest[value_start..value_start + end].trim();
+        if !name.is_empty() {
+            keywords.push(xml_unescape(name));
+        }
+        rest = &rest[value_start + end..];
+    }
+    keywords
+}
+
+/// 从 IPTC-IIM 数据块中提取关键字（记录 2 数据集 25）
+fn keywords_from_iptc(data: &[u8]) -> Vec<String> {
+    let mut keywords = Vec::new();
+    let mut pos = 0usize;
+    while pos + 5 <= data.len() {
+        if data[pos] != 0x1C {
+            pos += 1;
+            continue;
+        }
+        let record = data[pos + 1];
+        let dataset = data[pos + 2];
+        let len = u16::from_be_bytes([data[pos + 3], data[pos + 4]]) as usize;
+        let value_end = pos + 5 + len;
+        if value_end > data.len() {
+            break;
+        }
+        if record == 2 && dataset == 25 {
+            if let Ok(kw) = std::str::from_utf8(&data[pos + 5..value_end]) {
+                let kw = kw.trim();
+                if !kw.is_empty() {
+                    keywords.push(kw.to_string());
+                }
+            }
+        }
+        pos = value_end;
+    }
+    keywords
+}
+
+/// 从 JPEG APP13 (Photoshop IRB) 段中取出 IPTC 数据块（资源 ID 0x0404）
+fn iptc_from_app13(segment: &[u8]) -> Option<Vec<u8>> {
+    const PS_HEADER: &[u8] = b"Photoshop 3.0\0";
+    let body = segment.strip_prefix(PS_HEADER)?;
+    let mut pos = 0usize;
+    while pos + 12 <= body.len() {
+        if &body[pos..pos + 4] != b"8BIM" {
+            break;
+        }
+        let resource_id = u16::from_be_bytes([body[pos + 4], body[pos + 5]]);
+        // Pascal 字符串名称，补齐到偶数长度
+        let name_len = body[pos + 6] as usize;
+        let mut name_total = 1 + name_len;
+        if !name_total.is_multiple_of(2) {
+            name_total += 1;
+        }
+        let size_pos = pos + 6 + name_total;
+        if size_pos + 4 > body.len() {
+            break;
+        }
+        let size = u32::from_be_bytes([
+            body[size_pos],
+            body[size_pos + 1],
+            body[size_pos + 2],
+            body[size_pos + 3],
+        ]) as usize;
+        let data_start = size_pos + 4;
+        if data_start + size > body.len() {
+            break;
+        }
+        if resource_id == 0x0404 {
+            return Some(body[data_start..data_start + size].to_vec());
+        }
+        let mut advance = size;
+        if !advance.is_multiple_of(2) {
+            advance += 1;
+        }
+        pos = data_start + advance;
+    }
+    None
+}
+
+/// 读取文件中内嵌的关键字与人物标签（去重后返回，无法解析时返回空）
+pub fn read_embedded_keywords(file_path: &str) -> Vec<String> {
+    let path = Path::new(file_path);
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    let Ok(data) = fs::read(path) else {
+        return Vec::new();
+    };
+
+    let mut keywords = Vec::new();
+    match ext.as_str() {
+        "jpg" | "jpeg" => {
+            let mut pos = 2usize;
+            if data.len() < 4 || data[0] != 0xFF || data[1] != 0xD8 {
+                return Vec::new();
+            }
+            while pos + 4 <= data.len() {
+                if data[pos] != 0xFF {
+                    break;
+                }
+                let marker = data[pos + 1];
+                if marker == 0xDA {
+                    break;
+                }
+                let seg_len = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+                let seg_end = pos + 2 + seg_len;
+                if seg_len < 2 || seg_end > data.len() {
+                    break;
+                }
+                let payload = &data[pos + 4..seg_end];
+                if marker == 0xE1 {
+                    if let Some(xmp) = payload.strip_prefix(XMP_JPEG_HEADER) {
+                        if let Ok(xmp) = std::str::from_utf8(xmp) {
+                            keywords.extend(keywords_from_xmp(xmp));
+                        }
+                    }
+                } else if marker == 0xED {
+                    if let Some(iptc) = iptc_from_app13(payload) {
+                        keywords.extend(keywords_from_iptc(&iptc));
+                    }
+                }
+                pos = seg_end;
+            }
+        }
+        "png" => {
+            const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+            if data.len() < 8 || data[..8] != SIGNATURE {
+                return Vec::new();
+            }
+            let mut pos = 8usize;
+            while pos + 12 <= data.len() {
+                let len = u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize;
+                let chunk_type = &data[pos + 4..pos + 8];
+                let chunk_end = pos + 12 + len;
+                if chunk_end > data.len() {
+                    break;
+                }
+                if chunk_type == b"iTXt" {
+                    let chunk = &data[pos + 8..chunk_end - 4];
+                    if let Some(rest) = chunk.strip_prefix(XMP_PNG_KEYWORD) {
+                        // 跳过 \0 压缩标志 压缩方法 语言\0 翻译关键字\0
+                        let mut skipped = rest;
+                        for _ in 0..2 {
+                            if let Some(nul) = skipped.iter().position(|&b| b == 0) {
+                                skipped = &skipped[nul + 1..];
+                            }
+                        }
+                        // 第一次跳过的是 keyword 结尾 \0 + 两个标志字节
+                        if skipped.len() > 2 {
+                            if let Ok(xmp) = std::str::from_utf8(&skipped[2..]) {
+                                keywords.extend(keywords_from_xmp(xmp));
+                            }
+                        }
+                    }
+                }
+                pos = chunk_end;
+            }
+        }
+        "tif" | "tiff" => {
+            // 只在 XMP（标签 700）里找；TIFF 的 IPTC（标签 33723）在 Lightroom
+            // 导出中一般与 XMP 内容重复
+            if let Some(xmp) = tiff_xmp_payload(&data) {
+                if let Ok(xmp) = std::str::from_utf8(&xmp) {
+                    keywords.extend(keywords_from_xmp(xmp));
+                }
+            }
+        }
+        _ => {}
+    }
+
+    // 去重（保持首次出现顺序）
+    let mut seen = std::collections::HashSet::new();
+    keywords.retain(|k| seen.insert(k.to_lowercase()));
+    keywords
+}
+
+/// 取出 TIFF IFD0 中 XMP 条目（标签 700）指向的数据
+fn tiff_xmp_payload(data: &[u8]) -> Option<Vec<u8>> {
+    if data.len() < 8 {
+        return None;
+    }
+    let little_endian = match &data[..2] {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let read_u16 = |bytes: &[u8]| -> u16 {
+        let arr = [bytes[0], bytes[1]];
+        if little_endian { u16::from_le_bytes(arr) } else { u16::from_be_bytes(arr) }
+    };
+    let read_u32 = |bytes: &[u8]| -> u32 {
+        let arr = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if little_endian { u32::from_le_bytes(arr) } else { u32::from_be_bytes(arr) }
+    };
+    if read_u16(&data[2..4]) != 42 {
+        return None;
+    }
+    let ifd0 = read_u32(&data[4..8]) as usize;
+    if ifd0 + 2 > data.len() {
+        return None;
+    }
+    let count = read_u16(&data[ifd0..ifd0 + 2]) as usize;
+    for i in 0..count {
+        let start = ifd0 + 2 + i * 12;
+        if start + 12 > data.len() {
+            return None;
+        }
+        if read_u16(&data[start..start + 2]) == 700 {
+            let len = read_u32(&data[start + 4..start + 8]) as usize;
+            let offset = read_u32(&data[start + 8..start + 12]) as usize;
+            if len <= 4 {
+                // 数据内联在偏移字段里
+                return Some(data[start + 8..start + 8 + len].to_vec());
+            }
+            if offset + len <= data.len() {
+                return Some(data[offset..offset + len].to_vec());
+            }
+            return None;
+        }
+    }
+    None
+}
+
+/// 内嵌关键字扫描的运行标志（避免重复启动）
+static KEYWORD_SCAN_RUNNING: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct KeywordScanProgress {
+    processed: usize,
+    total: usize,
+    /// 合并到新标签的文件数
+    tagged: usize,
+}
+
+/// 后台扫描索引中的图片，把内嵌的 IPTC/XMP 关键字合并进标签。
+/// `scope` 为目录路径时只扫该目录，为 None 时扫整个索引；返回新增了标签的文件数
+#[tauri::command]
+pub async fn scan_embedded_keywords(
+    scope: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<usize, String> {
+    use std::sync::atomic::Ordering;
+    use tauri::{Emitter, Manager};
+
+    if KEYWORD_SCAN_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("关键字扫描已在运行".to_string());
+    }
+
+    let pool = app.state::<crate::db::AppDbPool>().inner().clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let paths: Vec<String> = {
+            let conn = pool.get_connection();
+            let entries = match &scope {
+                Some(dir) => crate::db::file_index::get_entries_under_path(&conn, dir),
+                None => crate::db::file_index::get_all_image_files(&conn),
+            }
+            .map_err(|e| e.to_string())?;
+            entries
+                .into_iter()
+                .filter(|e| e.file_type == "Image")
+                .map(|e| e.path)
+                .collect()
+        };
+
+        let total = paths.len();
+        let mut tagged = 0usize;
+
+        for (i, path) in paths.iter().enumerate() {
+            let keywords = read_embedded_keywords(path);
+            if !keywords.is_empty() {
+                let file_id = crate::db::generate_id(path);
+                let conn = pool.get_connection();
+                if crate::db::file_metadata::merge_imported_tags(&conn, &file_id, path, &keywords)
+                    .map_err(|e| e.to_string())?
+                {
+                    tagged += 1;
+                }
+            }
+
+            if (i + 1).is_multiple_of(100) || i + 1 == total {
+                let _ = app.emit("keyword-scan-progress", KeywordScanProgress {
+                    processed: i + 1,
+                    total,
+                    tagged,
+                });
+            }
+        }
+
+        Ok(tagged)
+    })
+    .await
+    .map_err(|e| format!("关键字扫描任务失败: {}", e));
+
+    KEYWORD_SCAN_RUNNING.store(false, Ordering::SeqCst);
+    result?
+}
@@ -0,0 +1,399 @@
+//! 3D 模型素材的预览：OBJ / glTF（含 GLB）解析成三角网格后，
+//! 用内置的软件光栅器渲染一张固定转台角度的静帧，游戏美术的
+//! 参考库里模型文件就不再是白板图标。
+//!
+//! 没有可用的 GPU 离屏渲染依赖，光栅器是手写的平直着色 + 深度
+//! 缓冲，512px 静帧在几十万面的模型上也就几百毫秒。FBX 是闭源
+//! 二进制格式，这里只作为 Model 类型入索引、不做渲染。
+//! 静帧 PNG 落在缩略图缓存目录的 models/ 子目录，缓存键带文件
+//! 大小和修改时间，源文件改动后自动失效。
+
+use std::path::{Path, PathBuf};
+
+/// 按 Model 类型入索引的扩展名（fbx 仅索引，不支持渲染）
+const SUPPORTED_MODEL_EXTENSIONS: &[&str] = &["obj", "gltf", "glb", "fbx"];
+
+/// 静帧边长与转台角度（绕 Y 轴偏航 + 略俯视）
+const RENDER_SIZE: u32 = 512;
+const YAW_DEG: f32 = 35.0;
+const PITCH_DEG: f32 = 20.0;
+
+pub fn is_supported_model(extension: &str) -> bool {
+    SUPPORTED_MODEL_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+}
+
+/// 解析出的三角网格（只取顶点位置，材质贴图一概忽略）
+struct Mesh {
+    positions: Vec<[f32; 3]>,
+    triangles: Vec<[u32; 3]>,
+}
+
+/// OBJ：只认 v 与 f 行，f 的多边形做扇形三角化，支持负索引
+fn parse_obj(text: &str) -> Result<Mesh, String> {
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut triangles: Vec<[u32; 3]> = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("v ") {
+            let mut it = rest.split_whitespace();
+            let x: f32 = it.next().and_then(|s| s.parse().ok()).ok_or("OBJ 顶点行格式错误")?;
+            let y: f32 = it.next().and_then(|s| s.parse().ok()).ok_or("OBJ 顶点行格式错误")?;
+            let z: f32 = it.next().and_then(|s| s.parse().ok()).ok_or("OBJ 顶点行格式错误")?;
+            positions.push([x, y, z]);
+        } else if let Some(rest) = line.strip_prefix("f ") {
+            // 顶点引用形如 "v"、"v/vt"、"v//vn"，只取第一段
+            let idx: Vec<u32> = rest
+                .split_whitespace()
+                .filter_map(|part| {
+                    let first = part.split('/').next()?;
+                    let i: i64 = first.parse().ok()?;
+                    let resolved = if i < 0 { positions.len() as i64 + i } else { i - 1 };
+                    if resolved >= 0 && (resolved as usize) < positions.len() {
+                        Some(resolved as u32)
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            for k in 2..idx.len() {
+                triangles.push([idx[0], idx[k - 1], idx[k]]);
+            }
+        }
+    }
+    if triangles.is_empty() {
+        return Err("OBJ 中没有可渲染的三角面".to_string());
+    }
+    Ok(Mesh { positions, triangles })
+}
+
+/// 从 accessor 里读标量索引（u8 / u16 / u32）
+fn read_indices(data: &[u8], component_type: u64, count: usize) -> Option<Vec<u32>> {
+    match component_type {
+        5121 => Some(data.get(..count)?.iter().map(|&b| b as u32).collect()),
+        5123 => Some(
+            data.get(..count * 2)?
+                .chunks_exact(2)
+                .map(|b| u16::from_le_bytes([b[0], b[1]]) as u32)
+                .collect(),
+        ),
+        5125 => Some(
+            data.get(..count * 4)?
+                .chunks_exact(4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// glTF / GLB：遍历所有 mesh primitive，取 POSITION 与 indices。
+/// 只支持 float VEC3 位置与三角形拓扑；buffer 来自 GLB 的 BIN 块
+/// 或 .gltf 旁边的外部 .bin 文件（data: URI 不支持）
+fn parse_gltf(path: &Path) -> Result<Mesh, String> {
+    let raw = std::fs::read(path).map_err(|e| format!("读取文件失败: {}", e))?;
+
+    let (json, bin): (serde_json::Value, Option<Vec<u8>>) = if raw.starts_with(b"glTF") {
+        // GLB 容器：12 字节头 + 若干 (长度, 类型, 数据) 块
+        let mut json_chunk: Option<&[u8]> = None;
+        let mut bin_chunk: Option<&[u8]> = None;
+        let mut pos = 12usize;
+        while pos + 8 <= raw.len() {
+            let len = u32::from_le_bytes([raw[pos], raw[pos + 1], raw[pos + 2], raw[pos + 3]]) as usize;
+            let kind = &raw[pos + 4..pos + 8];
+            let body = raw.get(pos + 8..pos + 8 + len).ok_or("GLB 块长度越界")?;
+            match kind {
+                b"JSON" => json_chunk = Some(body),
+                b"BIN\0" => bin_chunk = Some(body),
+                _ => {}
+            }
+            pos += 8 + len;
+            if !len.is_multiple_of(4) {
+                pos += 4 - len % 4;
+            }
+        }
+        let json_chunk = json_chunk.ok_or("GLB 缺少 JSON 块")?;
+        (
+            serde_json::from_slice(json_chunk).map_err(|e| format!("glTF JSON 解析失败: {}", e))?,
+            bin_chunk.map(|b| b.to_vec()),
+        )
+    } else {
+        (
+            serde_json::from_slice(&raw).map_err(|e| format!("glTF JSON 解析失败: {}", e))?,
+            None,
+        )
+    };
+
+    // 外部 buffer 按 uri 相对 .gltf 所在目录加载
+    let mut buffers: Vec<Vec<u8>> = Vec::new();
+    for buf in json["buffers"].as_array().map(|a| a.as_slice()).unwrap_or(&[]) {
+        match buf["uri"].as_str() {
+            None => buffers.push(bin.clone().ok_or("glTF buffer 缺少 uri 且无 BIN 块")?),
+            Some(uri) if uri.starts_with("data:") => {
+                return Err("暂不支持内嵌 data: URI 的 glTF buffer".to_string());
+            }
+            Some(uri) => {
+                let bin_path = path.parent().unwrap_or(Path::new(".")).join(uri);
+                buffers.push(
+                    std::fs::read(&bin_path)
+                        .map_err(|e| format!("读取 glTF buffer {} 失败: {}", uri, e))?,
+                );
+            }
+        }
+    }
+
+    // accessor → 所在 bufferView 的原始字节切片（考虑各自的 byteOffset）
+    let accessor_data = |idx: u64| -> Option<(&serde_json::Value, &[u8])> {
+        let accessor = json["accessors"].get(idx as usize)?;
+        let view = json["bufferViews"].get(accessor["bufferView"].as_u64()? as usize)?;
+        let buffer = buffers.get(view["buffer"].as_u64()? as usize)?;
+        let offset = (view["byteOffset"].as_u64().unwrap_or(0)
+            + accessor["byteOffset"].as_u64().unwrap_or(0)) as usize;
+        Some((accessor, buffer.get(offset..)?))
+    };
+
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut triangles: Vec<[u32; 3]> = Vec::new();
+    for mesh in json["meshes"].as_array().map(|a| a.as_slice()).unwrap_or(&[]) {
+        for prim in mesh["primitives"].as_array().map(|a| a.as_slice()).unwrap_or(&[]) {
+            // mode 缺省即 4（三角形），其他拓扑跳过
+            if prim["mode"].as_u64().unwrap_or(4) != 4 {
+                continue;
+            }
+            let Some(pos_idx) = prim["attributes"]["POSITION"].as_u64() else { continue };
+            let Some((accessor, data)) = accessor_data(pos_idx) else { continue };
+            if accessor["componentType"].as_u64() != Some(5126)
+                || accessor["type"].as_str() != Some("VEC3")
+            {
+                continue;
+            }
+            let count = accessor["count"].as_u64().unwrap_or(0) as usize;
+            let Some(bytes) = data.get(..count * 12) else { continue };
+            let base = positions.len() as u32;
+            positions.extend(bytes.chunks_exact(12).map(|c| {
+                [
+                    f32::from_le_bytes([c[0], c[1], c[2], c[3]]),
+                    f32::from_le_bytes([c[4], c[5], c[6], c[7]]),
+                    f32::from_le_bytes([c[8], c[9], c[10], c[11]]),
+                ]
+            }));
+
+            let indices: Vec<u32> = match prim["indices"].as_u64() {
+                Some(i) => {
+                    let Some((acc, data)) = accessor_data(i) else { continue };
+                    let n = acc["count"].as_u64().unwrap_or(0) as usize;
+                    match read_indices(data, acc["componentType"].as_u64().unwrap_or(0), n) {
+                        Some(v) => v,
+                        None => continue,
+                    }
+                }
+                None => (0..count as u32).collect(),
+            };
+            for tri in indices.chunks_exact(3) {
+                triangles.push([base + tri[0], base + tri[1], base + tri[2]]);
+            }
+        }
+    }
+    if triangles.is_empty() {
+        return Err("glTF 中没有可渲染的三角网格".to_string());
+    }
+    Ok(Mesh { positions, triangles })
+}
+
+/// 把网格归一化到单位包围球（质心平移到原点）
+fn normalize_mesh(mesh: &mut Mesh) {
+    if mesh.positions.is_empty() {
+        return;
+    }
+    let n = mesh.positions.len() as f32;
+    let mut center = [0.0f32; 3];
+    for p in &mesh.positions {
+        for k in 0..3 {
+            center[k] += p[k] / n;
+        }
+    }
+    let mut radius = 0.0f32;
+    for p in &mesh.positions {
+        let d = (0..3).map(|k| (p[k] - center[k]).powi(2)).sum::<f32>().sqrt();
+        radius = radius.max(d);
+    }
+    let scale = if radius > 0.0 { 1.0 / radius } else { 1.0 };
+    for p in &mut mesh.positions {
+        for k in 0..3 {
+            p[k] = (p[k] - center[k]) * scale;
+        }
+    }
+}
+
+/// 平直着色 + 深度缓冲的转台静帧，透明背景
+fn render_turntable(mesh: &Mesh) -> image::RgbaImage {
+    let size = RENDER_SIZE as i32;
+    let mut img = image::RgbaImage::new(RENDER_SIZE, RENDER_SIZE);
+    let mut zbuf = vec![f32::INFINITY; (size * size) as usize];
+
+    let (yaw, pitch) = (YAW_DEG.to_radians(), PITCH_DEG.to_radians());
+    let (sy, cy) = yaw.sin_cos();
+    let (sp, cp) = pitch.sin_cos();
+    // 偏航 + 俯仰后的相机空间坐标；z 越小离相机越近
+    let transform = |p: &[f32; 3]| -> [f32; 3] {
+        let (x, z) = (p[0] * cy + p[2] * sy, -p[0] * sy + p[2] * cy);
+        let (y, z) = (p[1] * cp - z * sp, p[1] * sp + z * cp);
+        [x, y, z]
+    };
+    // 简单透视：相机在 z = -2.5 处朝向原点
+    let cam_dist = 2.5f32;
+    let project = |p: &[f32; 3]| -> (f32, f32, f32) {
+        let z = p[2] + cam_dist;
+        let f = 0.42 * size as f32 / z.max(0.01);
+        (
+            size as f32 / 2.0 + p[0] * f,
+            size as f32 / 2.0 - p[1] * f,
+            z,
+        )
+    };
+    // 主光源方向（左上前方）
+    let light = {
+        let v = [-0.4f32, 0.7, -0.6];
+        let len = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+        [v[0] / len, v[1] / len, v[2] / len]
+    };
+
+    for tri in &mesh.triangles {
+        let [a, b, c] = [
+            transform(&mesh.positions[tri[0] as usize]),
+            transform(&mesh.positions[tri[1] as usize]),
+            transform(&mesh.positions[tri[2] as usize]),
+        ];
+        // 面法线与亮度（双面渲染，法线朝向取绝对值）
+        let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+        let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+        let n = [
+            u[1] * v[2] - u[2] * v[1],
+            u[2] * v[0] - u[0] * v[2],
+            u[0] * v[1] - u[1] * v[0],
+        ];
+        let nl = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+        if nl == 0.0 {
+            continue;
+        }
+        let diffuse = ((n[0] * light[0] + n[1] * light[1] + n[2] * light[2]) / nl).abs();
+        let shade = (40.0 + diffuse * 200.0) as u8;
+        let color = image::Rgba([shade, shade, shade.saturating_add(10), 0xFF]);
+
+        let (ax, ay, az) = project(&a);
+        let (bx, by, bz) = project(&b);
+        let (cx, cy2, cz) = project(&c);
+        let min_x = (ax.min(bx).min(cx).floor() as i32).max(0);
+        let max_x = (ax.max(bx).max(cx).ceil() as i32).min(size - 1);
+        let min_y = (ay.min(by).min(cy2).floor() as i32).max(0);
+        let max_y = (ay.max(by).max(cy2).ceil() as i32).min(size - 1);
+        let area = (bx - ax) * (cy2 - ay) - (by - ay) * (cx - ax);
+        if area.abs() < 1e-6 {
+            continue;
+        }
+        for py in min_y..=max_y {
+            for px in min_x..=max_x {
+                let (fx, fy) = (px as f32 + 0.5, py as f32 + 0.5);
+                let w0 = ((bx - ax) * (fy - ay) - (by - ay) * (fx - ax)) / area;
+                let w1 = ((cx - bx) * (fy - by) - (cy2 - by) * (fx - bx)) / area;
+                let w2 = 1.0 - w0 - w1;
+                if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                    continue;
+                }
+                // 重心插值深度（w0 对应 c，w1 对应 a，w2 对应 b）
+                let depth = w1 * az + w2 * bz + w0 * cz;
+                let zi = (py * size + px) as usize;
+                if depth < zbuf[zi] {
+                    zbuf[zi] = depth;
+                    img.put_pixel(px as u32, py as u32, color);
+                }
+            }
+        }
+    }
+    img
+}
+
+/// 缓存键：路径 + 大小 + 修改时间的 md5（与缩略图缓存同一套思路）
+fn cache_key(path: &str) -> Result<String, String> {
+    let metadata = std::fs::metadata(path).map_err(|e| format!("读取文件信息失败: {}", e))?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Ok(format!(
+        "{:x}",
+        md5::compute(format!("{}-{}-{}", path, metadata.len(), modified).as_bytes())
+    ))
+}
+
+/// 生成（或命中缓存的）模型转台静帧，返回 PNG 路径
+#[tauri::command]
+pub async fn generate_model_thumbnail(
+    file_path: String,
+    cache_root: String,
+) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || {
+        let path = PathBuf::from(&file_path);
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        let mut mesh = match ext.as_str() {
+            "obj" => {
+                let text = std::fs::read_to_string(&path).map_err(|e| format!("读取文件失败: {}", e))?;
+                parse_obj(&text)?
+            }
+            "gltf" | "glb" => parse_gltf(&path)?,
+            "fbx" => return Err("FBX 为闭源二进制格式，暂不支持预览渲染".to_string()),
+            _ => return Err(format!("不支持的模型格式: {}", ext)),
+        };
+
+        let dir = PathBuf::from(&cache_root).join("models");
+        std::fs::create_dir_all(&dir).map_err(|e| format!("创建缓存目录失败: {}", e))?;
+        let out = dir.join(format!("{}.png", cache_key(&file_path)?));
+        if out.exists() {
+            return Ok(out.to_string_lossy().to_string());
+        }
+
+        normalize_mesh(&mut mesh);
+        render_turntable(&mesh)
+            .save(&out)
+            .map_err(|e| format!("保存静帧失败: {}", e))?;
+        Ok(out.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("渲染任务失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CUBE_OBJ: &str = "\
+v -1 -1 -1\nv 1 -1 -1\nv 1 1 -1\nv -1 1 -1\nv -1 -1 1\nv 1 -1 1\nv 1 1 1\nv -1 1 1\n\
+f 1 2 3 4\nf 5 6 7 8\nf 1 2 6 5\nf 2 3 7 6\nf 3 4 8 7\nf 4 1 5 8\n";
+
+    #[test]
+    fn test_parse_obj_and_render() {
+        let mut mesh = parse_obj(CUBE_OBJ).unwrap();
+        assert_eq!(mesh.positions.len(), 8);
+        // 6 个四边形面扇形三角化成 12 个三角形
+        assert_eq!(mesh.triangles.len(), 12);
+
+        normalize_mesh(&mut mesh);
+        // 归一化后所有顶点都在单位球内
+        for p in &mesh.positions {
+            let d = (p[0] * p[0] + p[1] * p[1] + p[2] * p[2]).sqrt();
+            assert!(d <= 1.0 + 1e-5);
+        }
+
+        let img = render_turntable(&mesh);
+        // 画面中心应被立方体覆盖（非透明）
+        let c = img.get_pixel(RENDER_SIZE / 2, RENDER_SIZE / 2);
+        assert_eq!(c[3], 0xFF);
+        // 角落应保持透明背景
+        assert_eq!(img.get_pixel(0, 0)[3], 0);
+    }
+}
@@ -0,0 +1,90 @@
+//! 自然排序比较器
+//!
+//! 目录子项之前用普通 `cmp` 排序，导致 "img10" 排在 "img2" 前面，
+//! 中文也只能按码点排序。这里实现一个自然排序比较器：
+//! - 连续数字按数值比较（img2 < img10），位数相同再比较前导零
+//! - ASCII 字母不区分大小写
+//! - 中文字符可选按拼音比较（默认开启），使中英文混排更符合直觉
+//!
+//! 同名比较器还注册为 SQLite 的 `NATURAL` collation
+//! （见 `AppDbPool::new`/`switch`），SQL 端可用 `ORDER BY name COLLATE NATURAL`。
+
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::str::Chars;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+
+use pinyin::ToPinyin;
+
+/// 中文字符是否按拼音参与排序（前端可通过命令切换）
+static PINYIN_ENABLED: AtomicBool = AtomicBool::new(true);
+
+pub fn set_pinyin_enabled(enabled: bool) {
+    PINYIN_ENABLED.store(enabled, AtomicOrdering::SeqCst);
+}
+
+fn pinyin_enabled() -> bool {
+    PINYIN_ENABLED.load(AtomicOrdering::SeqCst)
+}
+
+/// 消耗一段连续的 ASCII 数字，返回 (数值, 位数)。
+/// 数值用饱和累加避免超长数字串溢出；位数用于在数值相同时比较前导零。
+fn take_number(iter: &mut Peekable<Chars>) -> (u128, usize) {
+    let mut value: u128 = 0;
+    let mut len = 0usize;
+    while let Some(&c) = iter.peek() {
+        if let Some(d) = c.to_digit(10) {
+            value = value.saturating_mul(10).saturating_add(d as u128);
+            len += 1;
+            iter.next();
+        } else {
+            break;
+        }
+    }
+    (value, len)
+}
+
+/// 单个字符的排序键：中文取拼音（可选），其它字符取小写形式
+fn char_key(c: char) -> String {
+    if pinyin_enabled() {
+        if let Some(p) = c.to_pinyin() {
+            return p.plain().to_string();
+        }
+    }
+    c.to_lowercase().collect()
+}
+
+/// 自然排序比较：数字按数值、字母不区分大小写、中文可按拼音
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut ia = a.chars().peekable();
+    let mut ib = b.chars().peekable();
+
+    loop {
+        match (ia.peek().copied(), ib.peek().copied()) {
+            // 两边都比完且等价时退回原始字节比较，保证排序结果确定
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ca), Some(cb)) => {
+                if ca.is_ascii_digit() && cb.is_ascii_digit() {
+                    let (va, la) = take_number(&mut ia);
+                    let (vb, lb) = take_number(&mut ib);
+                    match va.cmp(&vb) {
+                        Ordering::Equal => match la.cmp(&lb) {
+                            Ordering::Equal => continue,
+                            ord => return ord,
+                        },
+                        ord => return ord,
+                    }
+                } else {
+                    ia.next();
+                    ib.next();
+                    match char_key(ca).cmp(&char_key(cb)) {
+                        Ordering::Equal => continue,
+                        ord => return ord,
+                    }
+                }
+            }
+        }
+    }
+}
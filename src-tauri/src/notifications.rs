@@ -0,0 +1,136 @@
+//! 长任务的系统通知：导入完成、向量生成结束、同步失败时弹原生 toast。
+//!
+//! 走各平台自带的通知通道（Windows 用 PowerShell 的 WinRT Toast、
+//! macOS 用 osascript、Linux 用 notify-send），不引入插件依赖。
+//! 主窗口可见时不打扰用户，只发 "job-toast" 事件让前端弹应用内提示；
+//! 窗口藏在托盘时才发系统通知。事件里带着 view 字段（如 "activity" /
+//! "settings/sync"），前端收到点击后跳到对应视图。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+
+/// 系统通知总开关（默认开）
+static NOTIFICATIONS_ENABLED: AtomicBool = AtomicBool::new(true);
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct JobToast {
+    kind: String,
+    title: String,
+    body: String,
+    /// 点击后应打开的视图
+    view: Option<String>,
+}
+
+/// 按任务类型给出默认标题与目标视图
+fn defaults_for(kind: &str) -> (&'static str, Option<&'static str>) {
+    match kind {
+        "import-complete" => ("导入完成", Some("activity")),
+        "embedding-complete" => ("向量生成完成", Some("settings/ai")),
+        "sync-failed" => ("文件夹同步失败", Some("settings/sync")),
+        "export-complete" => ("导出完成", Some("activity")),
+        _ => ("后台任务完成", None),
+    }
+}
+
+#[cfg(windows)]
+fn show_os_toast(title: &str, body: &str) -> Result<(), String> {
+    // WinRT Toast：借 PowerShell 的 AUMID 发，点击会带起焦点
+    let script = format!(
+        "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] | Out-Null;\
+         $t = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02);\
+         $x = $t.GetElementsByTagName('text'); $x.Item(0).AppendChild($t.CreateTextNode('{}')) | Out-Null; $x.Item(1).AppendChild($t.CreateTextNode('{}')) | Out-Null;\
+         [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('Aurora Gallery').Show([Windows.UI.Notifications.ToastNotification]::new($t));",
+        title.replace('\'', "''"),
+        body.replace('\'', "''")
+    );
+    let status = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .status()
+        .map_err(|e| format!("调用 PowerShell 失败: {}", e))?;
+    if !status.success() {
+        return Err("发送系统通知失败".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn show_os_toast(title: &str, body: &str) -> Result<(), String> {
+    let script = format!(
+        "display notification \"{}\" with title \"{}\"",
+        body.replace('"', "\\\""),
+        title.replace('"', "\\\"")
+    );
+    let status = std::process::Command::new("osascript")
+        .args(["-e", &script])
+        .status()
+        .map_err(|e| format!("调用 osascript 失败: {}", e))?;
+    if !status.success() {
+        return Err("发送系统通知失败".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn show_os_toast(title: &str, body: &str) -> Result<(), String> {
+    let status = std::process::Command::new("notify-send")
+        .args(["--app-name=Aurora Gallery", title, body])
+        .status()
+        .map_err(|e| format!("调用 notify-send 失败: {}", e))?;
+    if !status.success() {
+        return Err("发送系统通知失败".to_string());
+    }
+    Ok(())
+}
+
+/// 任务管理方的通知入口：kind 决定默认标题与跳转视图，
+/// body 为具体内容（"已导入 42 个文件" / 失败原因等）
+#[tauri::command]
+pub async fn notify_job(
+    kind: String,
+    body: String,
+    title: Option<String>,
+    view: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let (default_title, default_view) = defaults_for(&kind);
+    let toast = JobToast {
+        kind: kind.clone(),
+        title: title.unwrap_or_else(|| default_title.to_string()),
+        body,
+        view: view.or_else(|| default_view.map(|v| v.to_string())),
+    };
+
+    // 应用内提示始终发，前端据窗口状态决定是否展示
+    let _ = app.emit("job-toast", toast.clone());
+
+    if !NOTIFICATIONS_ENABLED.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+    // 窗口前台可见时不用系统通知打扰
+    let window_visible = app
+        .get_webview_window("main")
+        .map(|w| w.is_visible().unwrap_or(false) && !w.is_minimized().unwrap_or(false))
+        .unwrap_or(false);
+    if window_visible {
+        return Ok(());
+    }
+
+    tokio::task::spawn_blocking(move || show_os_toast(&toast.title, &toast.body))
+        .await
+        .map_err(|e| format!("通知任务失败: {}", e))?
+}
+
+/// 设置系统通知开关
+#[tauri::command]
+pub fn set_notifications_enabled(enabled: bool) {
+    NOTIFICATIONS_ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+/// 查询系统通知开关
+#[tauri::command]
+pub fn get_notifications_enabled() -> bool {
+    NOTIFICATIONS_ENABLED.load(Ordering::SeqCst)
+}
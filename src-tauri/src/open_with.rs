@@ -0,0 +1,307 @@
+//! "打开方式"与外部编辑器集成。
+//!
+//! [`get_open_with_apps`] 按文件类型向操作系统查询可用的应用列表
+//! （Windows 读注册表的 OpenWithList，Linux 按 MIME 匹配 .desktop 文件，
+//! macOS 走系统默认），[`open_with`] 用选中的应用打开文件。
+//!
+//! 另外支持用户配置一个常用外部编辑器（Photoshop / Krita 等）：
+//! [`open_in_editor`] 把文件交给编辑器后在后台盯住文件修改时间，
+//! 编辑器保存时自动让缩略图等派生缓存失效，前端无需手动刷新。
+
+use std::path::Path;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+/// 一个可用于"打开方式"的应用
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenWithApp {
+    /// 传回 open_with 的标识（Windows 为 exe 名，Linux 为 .desktop 文件名）
+    pub id: String,
+    pub name: String,
+}
+
+/// 用户配置的外部编辑器可执行文件路径
+static EXTERNAL_EDITOR: Lazy<RwLock<Option<String>>> = Lazy::new(|| RwLock::new(None));
+
+/// 编辑会话里盯文件变化的最长时长与轮询间隔
+const EDIT_WATCH_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const EDIT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+fn default_entry() -> OpenWithApp {
+    OpenWithApp {
+        id: "default".to_string(),
+        name: "系统默认应用".to_string(),
+    }
+}
+
+#[cfg(windows)]
+fn reg_query(key: &str) -> Option<String> {
+    let output = std::process::Command::new("reg")
+        .args(["query", key])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Windows：HKCU FileExts 下的 OpenWithList 是资源管理器记录的
+/// "打开方式"历史，数据列就是 exe 名
+#[cfg(windows)]
+fn list_apps(path: &str) -> Vec<OpenWithApp> {
+    let mut apps = vec![default_entry()];
+    let Some(ext) = Path::new(path).extension().and_then(|e| e.to_str()) else {
+        return apps;
+    };
+    let key = format!(
+        "HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\FileExts\\.{}\\OpenWithList",
+        ext.to_lowercase()
+    );
+    let Some(output) = reg_query(&key) else {
+        return apps;
+    };
+    for line in output.lines() {
+        // 形如 "    a    REG_SZ    krita.exe"，跳过 MRUList 排序值
+        let mut cols = line.split_whitespace();
+        let (Some(value_name), Some(kind), Some(data)) = (cols.next(), cols.next(), cols.next())
+        else {
+            continue;
+        };
+        if kind != "REG_SZ" || value_name == "MRUList" || !data.to_lowercase().ends_with(".exe") {
+            continue;
+        }
+        let name = Path::new(data)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or(data)
+            .to_string();
+        if !apps.iter().any(|a| a.id.eq_ignore_ascii_case(data)) {
+            apps.push(OpenWithApp {
+                id: data.to_string(),
+                name,
+            });
+        }
+    }
+    apps
+}
+
+/// Windows：exe 名经 App Paths 解析成完整路径后启动；解析不到就按名字交给系统
+#[cfg(windows)]
+fn launch_with(path: &str, app_id: &str) -> Result<(), String> {
+    if app_id == "default" {
+        return std::process::Command::new("cmd")
+            .args(["/C", "start", "", path])
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| format!("打开文件失败: {}", e));
+    }
+    let resolved = reg_query(&format!(
+        "HKLM\\SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\App Paths\\{}",
+        app_id
+    ))
+    .and_then(|output| {
+        output.lines().find_map(|line| {
+            let mut cols = line.split_whitespace();
+            if cols.next() == Some("(Default)") && cols.next() == Some("REG_SZ") {
+                Some(line.splitn(3, "REG_SZ").nth(1)?.trim().to_string())
+            } else {
+                None
+            }
+        })
+    })
+    .unwrap_or_else(|| app_id.to_string());
+    std::process::Command::new(resolved)
+        .arg(path)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("启动 {} 失败: {}", app_id, e))
+}
+
+/// Linux：按 MIME 类型在应用目录里找声明支持的 .desktop 文件
+#[cfg(target_os = "linux")]
+fn list_apps(path: &str) -> Vec<OpenWithApp> {
+    let mut apps = vec![default_entry()];
+    let Some(mime) = std::process::Command::new("xdg-mime")
+        .args(["query", "filetype", path])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+    else {
+        return apps;
+    };
+    let mut dirs = vec!["/usr/share/applications".to_string()];
+    if let Ok(home) = std::env::var("HOME") {
+        dirs.push(format!("{}/.local/share/applications", home));
+    }
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if !file_name.ends_with(".desktop") {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            let supports = content
+                .lines()
+                .find(|l| l.starts_with("MimeType="))
+                .map(|l| l.split(';').any(|m| m.trim_start_matches("MimeType=") == mime))
+                .unwrap_or(false);
+            if !supports {
+                continue;
+            }
+            let name = content
+                .lines()
+                .find(|l| l.starts_with("Name="))
+                .map(|l| l["Name=".len()..].to_string())
+                .unwrap_or_else(|| file_name.clone());
+            if !apps.iter().any(|a| a.id == file_name) {
+                apps.push(OpenWithApp {
+                    id: file_name.clone(),
+                    name,
+                });
+            }
+        }
+    }
+    apps
+}
+
+#[cfg(target_os = "linux")]
+fn launch_with(path: &str, app_id: &str) -> Result<(), String> {
+    if app_id == "default" {
+        return std::process::Command::new("xdg-open")
+            .arg(path)
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| format!("打开文件失败: {}", e));
+    }
+    // gtk-launch 接受 .desktop 名（可不带扩展名）
+    std::process::Command::new("gtk-launch")
+        .args([app_id.trim_end_matches(".desktop"), path])
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("启动 {} 失败: {}", app_id, e))
+}
+
+/// macOS：没有公开的枚举接口（需要 LaunchServices），只提供系统默认；
+/// open_with 里 app_id 也可直接传应用名交给 `open -a`
+#[cfg(target_os = "macos")]
+fn list_apps(_path: &str) -> Vec<OpenWithApp> {
+    vec![default_entry()]
+}
+
+#[cfg(target_os = "macos")]
+fn launch_with(path: &str, app_id: &str) -> Result<(), String> {
+    let mut cmd = std::process::Command::new("open");
+    if app_id != "default" {
+        cmd.args(["-a", app_id]);
+    }
+    cmd.arg(path)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("打开文件失败: {}", e))
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn list_apps(_path: &str) -> Vec<OpenWithApp> {
+    vec![default_entry()]
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn launch_with(_path: &str, _app_id: &str) -> Result<(), String> {
+    Err("当前平台不支持".to_string())
+}
+
+/// 查询能打开该文件的应用列表（首项始终是系统默认）
+#[tauri::command]
+pub async fn get_open_with_apps(file_path: String) -> Result<Vec<OpenWithApp>, String> {
+    if !Path::new(&file_path).exists() {
+        return Err("文件不存在".to_string());
+    }
+    tokio::task::spawn_blocking(move || Ok(list_apps(&file_path)))
+        .await
+        .map_err(|e| format!("查询应用列表失败: {}", e))?
+}
+
+/// 用指定应用打开文件（app_id 来自 get_open_with_apps）
+#[tauri::command]
+pub fn open_with(file_path: String, app_id: String) -> Result<(), String> {
+    if !Path::new(&file_path).exists() {
+        return Err("文件不存在".to_string());
+    }
+    launch_with(&file_path, &app_id)
+}
+
+/// 配置外部编辑器（可执行文件完整路径，传空清除）
+#[tauri::command]
+pub fn set_external_editor(editor_path: String) -> Result<(), String> {
+    let trimmed = editor_path.trim();
+    if trimmed.is_empty() {
+        *EXTERNAL_EDITOR.write().unwrap() = None;
+        return Ok(());
+    }
+    if !Path::new(trimmed).is_file() {
+        return Err(format!("编辑器不存在: {}", trimmed));
+    }
+    *EXTERNAL_EDITOR.write().unwrap() = Some(trimmed.to_string());
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_external_editor() -> Option<String> {
+    EXTERNAL_EDITOR.read().unwrap().clone()
+}
+
+/// 把文件交给配置好的外部编辑器，并在后台盯住保存动作。
+/// 编辑器每次保存都会让缩略图等派生缓存失效并广播
+/// "file-edited-externally"，最长盯 30 分钟
+#[tauri::command]
+pub async fn open_in_editor(file_path: String, app: tauri::AppHandle) -> Result<(), String> {
+    let editor = EXTERNAL_EDITOR
+        .read()
+        .unwrap()
+        .clone()
+        .ok_or("尚未配置外部编辑器")?;
+    if !Path::new(&file_path).exists() {
+        return Err("文件不存在".to_string());
+    }
+    std::process::Command::new(&editor)
+        .arg(&file_path)
+        .spawn()
+        .map_err(|e| format!("启动编辑器失败: {}", e))?;
+
+    // 轮询修改时间；mtime 变化说明编辑器保存了一版
+    tauri::async_runtime::spawn(async move {
+        use tauri::Emitter;
+        let mtime_of = |p: &str| {
+            std::fs::metadata(p)
+                .and_then(|m| m.modified())
+                .ok()
+        };
+        let mut last_mtime = mtime_of(&file_path);
+        let deadline = std::time::Instant::now() + EDIT_WATCH_TIMEOUT;
+        while std::time::Instant::now() < deadline {
+            tokio::time::sleep(EDIT_POLL_INTERVAL).await;
+            let current = mtime_of(&file_path);
+            if current.is_none() {
+                // 文件被编辑器删除 / 改名，停止监视
+                break;
+            }
+            if current != last_mtime {
+                last_mtime = current;
+                crate::invalidate_file_caches(&app, &file_path).await;
+                let _ = app.emit("file-edited-externally", file_path.clone());
+            }
+        }
+    });
+    Ok(())
+}
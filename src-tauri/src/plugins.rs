@@ -0,0 +1,335 @@
+//! 插件系统：从插件目录加载动态库，扩展格式支持而无需改动主程序。
+//!
+//! 插件是一个动态库（Windows .dll / Linux .so / macOS .dylib），放在
+//! 应用数据目录的 plugins/ 下，导出一个 `aurora_plugin_init` 函数返回
+//! [`PluginDescriptor`]。三种插件类型：
+//! - 图像处理器（kind=1）：接收 RGBA8 像素缓冲，返回处理后的缓冲
+//! - 元数据提供者（kind=2）：接收文件路径，返回 JSON 字符串
+//! - 导出目标（kind=3）：接收本地路径与远端相对路径，执行上传
+//!
+//! 文件访问按 API 收敛：处理器拿到的是像素缓冲而不是文件句柄，
+//! 元数据提供者只会收到库索引内的路径；插件分配的内存由插件自带的
+//! free 函数回收。ABI 版本不匹配的插件拒绝加载。
+//! 生命周期：启动后显式 load_plugins 扫描加载，reload_plugins 全量重载
+//! （卸载旧库后重新扫描），应用退出时随进程释放。
+
+use std::ffi::{c_char, CStr, CString};
+use std::path::Path;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::db::AppDbPool;
+
+/// 当前插件 ABI 版本，描述符里的 abi_version 必须与之相等
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// 插件导出的描述符（C ABI）。不支持的能力置空即可
+#[repr(C)]
+pub struct PluginDescriptor {
+    pub abi_version: u32,
+    pub name: *const c_char,
+    pub version: *const c_char,
+    /// 1 = 图像处理器，2 = 元数据提供者，3 = 导出目标
+    pub kind: u32,
+    /// 处理 RGBA8 缓冲，返回同尺寸的新缓冲（长度写入 out_len），失败返回空指针
+    pub process_image: Option<
+        unsafe extern "C" fn(
+            data: *const u8,
+            len: usize,
+            width: u32,
+            height: u32,
+            out_len: *mut usize,
+        ) -> *mut u8,
+    >,
+    /// 回收 process_image 返回的缓冲
+    pub free_buffer: Option<unsafe extern "C" fn(ptr: *mut u8, len: usize)>,
+    /// 读取文件元数据，返回 JSON 字符串（UTF-8，空指针表示失败）
+    pub read_metadata: Option<unsafe extern "C" fn(path: *const c_char) -> *mut c_char>,
+    /// 回收 read_metadata 返回的字符串
+    pub free_string: Option<unsafe extern "C" fn(ptr: *mut c_char)>,
+    /// 上传一个文件，返回 0 表示成功
+    pub export_file:
+        Option<unsafe extern "C" fn(local_path: *const c_char, remote_rel: *const c_char) -> i32>,
+}
+
+type InitFn = unsafe extern "C" fn() -> *const PluginDescriptor;
+
+struct LoadedPlugin {
+    name: String,
+    version: String,
+    kind: u32,
+    file_path: String,
+    desc: *const PluginDescriptor,
+    /// 库句柄必须与 desc 同寿命，desc 指向库内的静态数据
+    _lib: libloading::Library,
+}
+
+// desc 指向已加载库里的只读静态数据，库句柄随 LoadedPlugin 一起持有
+unsafe impl Send for LoadedPlugin {}
+
+static PLUGINS: Lazy<Mutex<Vec<LoadedPlugin>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// 对前端展示的插件信息
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginInfo {
+    pub name: String,
+    pub version: String,
+    pub kind: String,
+    pub path: String,
+}
+
+fn kind_label(kind: u32) -> &'static str {
+    match kind {
+        1 => "imageProcessor",
+        2 => "metadataProvider",
+        3 => "exportTarget",
+        _ => "unknown",
+    }
+}
+
+fn plugins_dir(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("plugins");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+fn is_dylib(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("dll") | Some("so") | Some("dylib")
+    )
+}
+
+/// 加载单个动态库：取 aurora_plugin_init，校验 ABI 版本并拷出基本信息
+fn load_one(path: &Path) -> Result<LoadedPlugin, String> {
+    let lib = unsafe { libloading::Library::new(path) }
+        .map_err(|e| format!("加载动态库失败: {}", e))?;
+    let init: libloading::Symbol<InitFn> = unsafe { lib.get(b"aurora_plugin_init") }
+        .map_err(|_| "缺少 aurora_plugin_init 导出".to_string())?;
+    let desc = unsafe { init() };
+    if desc.is_null() {
+        return Err("aurora_plugin_init 返回空描述符".to_string());
+    }
+    let (abi, name, version, kind) = unsafe {
+        let d = &*desc;
+        let name = if d.name.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(d.name).to_string_lossy().into_owned()
+        };
+        let version = if d.version.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(d.version).to_string_lossy().into_owned()
+        };
+        (d.abi_version, name, version, d.kind)
+    };
+    if abi != PLUGIN_ABI_VERSION {
+        return Err(format!(
+            "ABI 版本不匹配: 插件 {} / 宿主 {}",
+            abi, PLUGIN_ABI_VERSION
+        ));
+    }
+    if name.is_empty() {
+        return Err("插件没有名称".to_string());
+    }
+    Ok(LoadedPlugin {
+        name,
+        version,
+        kind,
+        file_path: path.to_string_lossy().replace('\\', "/"),
+        desc,
+        _lib: lib,
+    })
+}
+
+fn snapshot() -> Vec<PluginInfo> {
+    PLUGINS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|p| PluginInfo {
+            name: p.name.clone(),
+            version: p.version.clone(),
+            kind: kind_label(p.kind).to_string(),
+            path: p.file_path.clone(),
+        })
+        .collect()
+}
+
+/// 扫描插件目录并加载所有动态库（已加载的同名插件跳过）。
+/// 返回加载成功的插件列表；单个插件加载失败只打日志不中断
+#[tauri::command]
+pub async fn load_plugins(app: tauri::AppHandle) -> Result<Vec<PluginInfo>, String> {
+    let dir = plugins_dir(&app)?;
+    let entries = std::fs::read_dir(&dir).map_err(|e| e.to_string())?;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if !path.is_file() || !is_dylib(&path) {
+            continue;
+        }
+        let path_str = path.to_string_lossy().replace('\\', "/");
+        {
+            let guard = PLUGINS.lock().unwrap();
+            if guard.iter().any(|p| p.file_path == path_str) {
+                continue;
+            }
+        }
+        match load_one(&path) {
+            Ok(plugin) => {
+                let mut guard = PLUGINS.lock().unwrap();
+                // 同名插件保留先加载的
+                if !guard.iter().any(|p| p.name == plugin.name) {
+                    guard.push(plugin);
+                }
+            }
+            Err(e) => eprintln!("[plugins] 加载 {} 失败: {}", path.display(), e),
+        }
+    }
+    Ok(snapshot())
+}
+
+/// 列出已加载的插件
+#[tauri::command]
+pub async fn list_plugins() -> Result<Vec<PluginInfo>, String> {
+    Ok(snapshot())
+}
+
+/// 卸载全部插件后重新扫描加载
+#[tauri::command]
+pub async fn reload_plugins(app: tauri::AppHandle) -> Result<Vec<PluginInfo>, String> {
+    PLUGINS.lock().unwrap().clear();
+    load_plugins(app).await
+}
+
+/// 用图像处理器插件处理一张图片，结果存为 PNG。
+/// output_path 省略时在原文件名后加 `_<插件名>` 后缀
+#[tauri::command]
+pub async fn run_plugin_processor(
+    name: String,
+    file_path: String,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let output = output_path.unwrap_or_else(|| {
+        let p = Path::new(&file_path);
+        let stem = p.file_stem().and_then(|s| s.to_str()).unwrap_or("output");
+        let parent = p.parent().map(|d| d.to_string_lossy().replace('\\', "/")).unwrap_or_default();
+        format!("{}/{}_{}.png", parent, stem, name)
+    });
+
+    tokio::task::spawn_blocking(move || {
+        let img = image::open(&file_path)
+            .map_err(|e| format!("解码失败: {}", e))?
+            .to_rgba8();
+        let (width, height) = img.dimensions();
+        let input = img.into_raw();
+
+        let processed = {
+            let guard = PLUGINS.lock().unwrap();
+            let plugin = guard
+                .iter()
+                .find(|p| p.name == name && p.kind == 1)
+                .ok_or_else(|| format!("图像处理器插件不存在: {}", name))?;
+            let d = unsafe { &*plugin.desc };
+            let process = d.process_image.ok_or("插件未实现 process_image")?;
+            let free = d.free_buffer.ok_or("插件未实现 free_buffer")?;
+
+            let mut out_len: usize = 0;
+            let out_ptr = unsafe {
+                process(input.as_ptr(), input.len(), width, height, &mut out_len)
+            };
+            if out_ptr.is_null() {
+                return Err("插件处理失败".to_string());
+            }
+            let out = unsafe { std::slice::from_raw_parts(out_ptr, out_len) }.to_vec();
+            unsafe { free(out_ptr, out_len) };
+            out
+        };
+
+        let buffer = image::RgbaImage::from_raw(width, height, processed)
+            .ok_or("插件返回的缓冲尺寸不符")?;
+        buffer
+            .save_with_format(&output, image::ImageFormat::Png)
+            .map_err(|e| format!("保存失败: {}", e))?;
+        Ok(output)
+    })
+    .await
+    .map_err(|e| format!("插件处理任务失败: {}", e))?
+}
+
+/// 用元数据提供者插件读取文件元数据（仅允许库索引内的路径）
+#[tauri::command]
+pub async fn run_plugin_metadata(
+    name: String,
+    file_path: String,
+    pool: tauri::State<'_, AppDbPool>,
+) -> Result<serde_json::Value, String> {
+    let normalized = crate::db::normalize_path(&file_path);
+    {
+        let conn = pool.get_connection();
+        let indexed = crate::db::file_index::get_entry_by_id(&conn, &crate::db::generate_id(&normalized))
+            .map_err(|e| e.to_string())?
+            .is_some();
+        if !indexed {
+            return Err("文件不在库索引中".to_string());
+        }
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let guard = PLUGINS.lock().unwrap();
+        let plugin = guard
+            .iter()
+            .find(|p| p.name == name && p.kind == 2)
+            .ok_or_else(|| format!("元数据提供者插件不存在: {}", name))?;
+        let d = unsafe { &*plugin.desc };
+        let read = d.read_metadata.ok_or("插件未实现 read_metadata")?;
+        let free = d.free_string.ok_or("插件未实现 free_string")?;
+
+        let c_path = CString::new(normalized).map_err(|e| e.to_string())?;
+        let ptr = unsafe { read(c_path.as_ptr()) };
+        if ptr.is_null() {
+            return Err("插件读取元数据失败".to_string());
+        }
+        let json = unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned();
+        unsafe { free(ptr) };
+        serde_json::from_str(&json).map_err(|e| format!("插件返回的不是合法 JSON: {}", e))
+    })
+    .await
+    .map_err(|e| format!("插件任务失败: {}", e))?
+}
+
+/// 用导出目标插件上传一个文件
+#[tauri::command]
+pub async fn run_plugin_export(
+    name: String,
+    local_path: String,
+    remote_rel: String,
+) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let guard = PLUGINS.lock().unwrap();
+        let plugin = guard
+            .iter()
+            .find(|p| p.name == name && p.kind == 3)
+            .ok_or_else(|| format!("导出目标插件不存在: {}", name))?;
+        let d = unsafe { &*plugin.desc };
+        let export = d.export_file.ok_or("插件未实现 export_file")?;
+
+        let c_local = CString::new(local_path).map_err(|e| e.to_string())?;
+        let c_remote = CString::new(remote_rel).map_err(|e| e.to_string())?;
+        let code = unsafe { export(c_local.as_ptr(), c_remote.as_ptr()) };
+        if code != 0 {
+            return Err(format!("插件上传失败，返回码 {}", code));
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("插件任务失败: {}", e))?
+}
@@ -0,0 +1,155 @@
+//! 快速预览浮窗（Quick Look 风格）：无边框、置顶的小窗直接看图，
+//! 不用切回主窗口。内容经自定义协议 quickpreview:// 渲染：
+//! `/view?id=` 返回一页全屏看图的极简 HTML，`/raw?id=` 返回图片字节，
+//! 两者都只认索引里存在的 file_id，不暴露任意文件读取。
+//!
+//! 热键开关由前端绑定（keydown 里调 [`toggle_quick_preview`]），
+//! 再按一次或按 Esc 关闭。
+
+use std::borrow::Cow;
+use std::path::Path;
+
+use tauri::{Manager, WebviewUrl};
+
+use crate::db::AppDbPool;
+
+/// 预览窗口的标签（同一时间只有一个预览窗）
+const WINDOW_LABEL: &str = "quick-preview";
+
+/// file_id 对应的预览页地址
+fn view_url(file_id: &str) -> String {
+    format!("quickpreview://localhost/view?id={}", file_id)
+}
+
+fn content_type_for(path: &str) -> &'static str {
+    match Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default()
+        .as_str()
+    {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "avif" => "image/avif",
+        "svg" => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+fn http_response(status: u16, content_type: &str, body: Vec<u8>) -> tauri::http::Response<Cow<'static, [u8]>> {
+    tauri::http::Response::builder()
+        .status(status)
+        .header("Content-Type", content_type)
+        .body(Cow::Owned(body))
+        .unwrap_or_else(|_| tauri::http::Response::new(Cow::Borrowed(&[][..])))
+}
+
+/// 按 file_id 从索引取路径（只允许预览已索引的文件）
+fn indexed_path(pool: &AppDbPool, file_id: &str) -> Option<String> {
+    let conn = pool.get_connection();
+    crate::db::file_index::get_entry_by_id(&conn, file_id)
+        .ok()
+        .flatten()
+        .map(|e| e.path)
+}
+
+/// quickpreview:// 协议处理器，挂在 Builder 的 register_uri_scheme_protocol 上
+pub fn handle_protocol<R: tauri::Runtime>(
+    ctx: tauri::UriSchemeContext<'_, R>,
+    request: tauri::http::Request<Vec<u8>>,
+) -> tauri::http::Response<Cow<'static, [u8]>> {
+    let uri = request.uri();
+    let query = crate::api_server::parse_query(uri.query().unwrap_or(""));
+    let Some(file_id) = query.get("id") else {
+        return http_response(400, "text/plain; charset=utf-8", b"missing id".to_vec());
+    };
+    let pool = ctx.app_handle().state::<AppDbPool>().inner().clone();
+    let Some(path) = indexed_path(&pool, file_id) else {
+        return http_response(404, "text/plain; charset=utf-8", b"not indexed".to_vec());
+    };
+
+    match uri.path() {
+        "/view" => {
+            let name = Path::new(&path)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("")
+                .replace('<', "&lt;");
+            let html = format!(
+                "<!doctype html><html><head><meta charset=\"utf-8\"><title>{}</title><style>\
+                 html,body{{margin:0;height:100%;background:#111;overflow:hidden}}\
+                 img{{width:100%;height:100%;object-fit:contain;-webkit-user-drag:none}}\
+                 </style></head><body><img src=\"/raw?id={}\" alt=\"\">\
+                 <script>addEventListener('keydown',e=>{{if(e.key==='Escape')window.close()}})</script>\
+                 </body></html>",
+                name, file_id
+            );
+            http_response(200, "text/html; charset=utf-8", html.into_bytes())
+        }
+        "/raw" => match std::fs::read(&path) {
+            Ok(bytes) => http_response(200, content_type_for(&path), bytes),
+            Err(_) => http_response(404, "text/plain; charset=utf-8", b"read failed".to_vec()),
+        },
+        _ => http_response(404, "text/plain; charset=utf-8", b"not found".to_vec()),
+    }
+}
+
+/// 打开（或复用）预览浮窗显示指定文件
+#[tauri::command]
+pub async fn show_quick_preview(file_id: String, app: tauri::AppHandle) -> Result<(), String> {
+    let pool = app.state::<AppDbPool>().inner().clone();
+    if indexed_path(&pool, &file_id).is_none() {
+        return Err("文件不在索引中".to_string());
+    }
+    let url: tauri::Url = view_url(&file_id)
+        .parse()
+        .map_err(|e| format!("预览地址无效: {}", e))?;
+
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        window.navigate(url).map_err(|e| e.to_string())?;
+        window.show().map_err(|e| e.to_string())?;
+        window.set_focus().map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+
+    tauri::WebviewWindowBuilder::new(&app, WINDOW_LABEL, WebviewUrl::CustomProtocol(url))
+        .title("快速预览")
+        .inner_size(760.0, 760.0)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .center()
+        .build()
+        .map_err(|e| format!("创建预览窗口失败: {}", e))?;
+    Ok(())
+}
+
+/// 热键开关：预览窗可见就关掉，否则打开 file_id 对应的预览。
+/// 关闭时不需要 file_id
+#[tauri::command]
+pub async fn toggle_quick_preview(
+    file_id: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        if window.is_visible().unwrap_or(false) {
+            return window.close().map_err(|e| e.to_string());
+        }
+    }
+    match file_id {
+        Some(file_id) => show_quick_preview(file_id, app).await,
+        None => Ok(()),
+    }
+}
+
+/// 关闭预览浮窗（不存在时静默）
+#[tauri::command]
+pub fn close_quick_preview(app: tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        let _ = window.close();
+    }
+}
@@ -0,0 +1,159 @@
+//! “更多相似图片”预计算：空闲时把每张图的 CLIP 最近邻 top-20 先算好，
+//! 存进 embeddings.db 的 related_images 表，查看器里的相似条带即点即出，
+//! 不用每次打开都做一遍全库余弦比较。
+//!
+//! 余弦相似度是全量两两比较（O(n²)），所以只对还没算过、或嵌入比上次
+//! 计算更新的图重算，其余直接跳过；脏图的比较本身用 rayon 并行。
+//! 嵌入随文件增删持续变化，预计算由前端在空闲时触发，单飞互斥。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::clip;
+
+/// 每张图保留的最近邻数量
+const TOP_K: usize = 20;
+/// 每攒这么多行落一次库
+const SAVE_BATCH: usize = 200;
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RelatedImage {
+    pub file_id: String,
+    /// 余弦相似度（嵌入已归一化，等于点积）
+    pub score: f32,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct RelatedProgress {
+    computed: usize,
+    total: usize,
+}
+
+fn normalize(mut v: Vec<f32>) -> Vec<f32> {
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for x in &mut v {
+            *x /= norm;
+        }
+    }
+    v
+}
+
+/// 空闲时预计算最近邻，返回本次重算的图数。已在跑时直接报错（单飞）
+#[tauri::command]
+pub async fn precompute_related(app: tauri::AppHandle) -> Result<usize, String> {
+    if RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("相似图预计算已在进行中".to_string());
+    }
+
+    let result = precompute_inner(app).await;
+    RUNNING.store(false, Ordering::SeqCst);
+    result
+}
+
+async fn precompute_inner(app: tauri::AppHandle) -> Result<usize, String> {
+    let manager = clip::get_clip_manager()
+        .await
+        .ok_or("CLIP 模型未加载，无法计算相似图")?;
+    let store = {
+        let guard = manager.read().await;
+        guard
+            .embedding_store()
+            .ok_or("嵌入存储未初始化")?
+            .clone()
+    };
+
+    tokio::task::spawn_blocking(move || -> Result<usize, String> {
+        use rayon::prelude::*;
+
+        let _ = store.prune_related();
+
+        let embeddings = store.get_all_embeddings()?;
+        if embeddings.is_empty() {
+            return Ok(0);
+        }
+        // 已算过且嵌入没更新过的跳过
+        let fresh: std::collections::HashMap<String, i64> =
+            store.get_related_timestamps()?.into_iter().collect();
+        let normalized: Vec<(String, Vec<f32>, i64)> = embeddings
+            .into_iter()
+            .map(|e| (e.file_id, normalize(e.embedding), e.created_at))
+            .collect();
+        let stale: Vec<usize> = normalized
+            .iter()
+            .enumerate()
+            .filter(|(_, (id, _, created_at))| {
+                fresh.get(id).is_none_or(|computed_at| *computed_at < *created_at)
+            })
+            .map(|(i, _)| i)
+            .collect();
+        let total = stale.len();
+        if total == 0 {
+            return Ok(0);
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let mut computed = 0usize;
+        for chunk in stale.chunks(SAVE_BATCH) {
+            let rows: Vec<(String, String, i64)> = chunk
+                .par_iter()
+                .map(|&i| {
+                    let (id, emb, _) = &normalized[i];
+                    let mut scored: Vec<RelatedImage> = normalized
+                        .iter()
+                        .filter(|(other_id, _, _)| other_id != id)
+                        .map(|(other_id, other_emb, _)| RelatedImage {
+                            file_id: other_id.clone(),
+                            score: emb.iter().zip(other_emb).map(|(a, b)| a * b).sum(),
+                        })
+                        .collect();
+                    scored.sort_unstable_by(|a, b| {
+                        b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal)
+                    });
+                    scored.truncate(TOP_K);
+                    let neighbors = serde_json::to_string(&scored).unwrap_or_else(|_| "[]".into());
+                    (id.clone(), neighbors, now)
+                })
+                .collect();
+            store.save_related_batch(&rows)?;
+            computed += rows.len();
+            let _ = app.emit("related-progress", RelatedProgress { computed, total });
+        }
+        Ok(computed)
+    })
+    .await
+    .map_err(|e| format!("相似图预计算任务失败: {}", e))?
+}
+
+/// 读取某张图的预计算最近邻（还没算到时返回空列表，前端可先触发预计算）
+#[tauri::command]
+pub async fn get_related(file_id: String) -> Result<Vec<RelatedImage>, String> {
+    let Some(manager) = clip::get_clip_manager().await else {
+        return Ok(Vec::new());
+    };
+    let store = {
+        let guard = manager.read().await;
+        match guard.embedding_store() {
+            Some(store) => store.clone(),
+            None => return Ok(Vec::new()),
+        }
+    };
+    let row = tokio::task::spawn_blocking(move || store.get_related(&file_id))
+        .await
+        .map_err(|e| format!("查询相似图失败: {}", e))??;
+    match row {
+        Some((neighbors, _)) => {
+            serde_json::from_str(&neighbors).map_err(|e| format!("解析相似图缓存失败: {}", e))
+        }
+        None => Ok(Vec::new()),
+    }
+}
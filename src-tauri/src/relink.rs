@@ -0,0 +1,228 @@
+//! 失联文件重连：盘符变化、文件夹在应用外被改名后，索引里会留下
+//! 大量磁盘上找不到的条目。这里提供两步修复：
+//!
+//! 1. [`suggest_relink_targets`]：在给定目录下按"文件名 + 大小"给失联条目
+//!    找候选新位置，按旧父目录 → 新父目录聚合投票，给出前缀映射建议；
+//! 2. [`relink_missing`]：按前缀映射逐条把失联条目指向新路径。
+//!
+//! 重连只改 path 列，file_id 保持不变（与 rename_file 的稳定 ID 机制一致），
+//! 标签 / 评分 / CLIP 嵌入自然保留；主色调按路径存储，整体随前缀迁移。
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::db::{self, AppDbPool};
+
+/// 一条前缀映射建议
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RelinkSuggestion {
+    pub old_prefix: String,
+    pub new_prefix: String,
+    /// 支持该映射的"文件名 + 大小"匹配数
+    pub matches: usize,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RelinkReport {
+    pub dry_run: bool,
+    pub relinked: usize,
+    /// 按映射推算后磁盘上仍然找不到的条目数
+    pub still_missing: usize,
+}
+
+/// 在 scan_root 下为失联条目找新位置，返回按匹配数降序的前缀映射建议。
+/// 同名同大小出现在多个位置时该文件不参与投票，避免误导
+#[tauri::command]
+pub async fn suggest_relink_targets(
+    scan_root: String,
+    pool: tauri::State<'_, AppDbPool>,
+) -> Result<Vec<RelinkSuggestion>, String> {
+    let scan_root = db::normalize_path(&scan_root);
+    if !Path::new(&scan_root).is_dir() {
+        return Err(format!("目录不存在: {}", scan_root));
+    }
+    let pool = pool.inner().clone();
+
+    tokio::task::spawn_blocking(move || {
+        // 1. 收集失联条目
+        let missing: Vec<db::file_index::FileIndexEntry> = {
+            let conn = pool.get_connection();
+            db::file_index::get_all_image_files(&conn)
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .filter(|e| !Path::new(&e.path).exists())
+                .collect()
+        };
+        if missing.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // 2. 扫描候选目录，按（文件名，大小）建表；撞键的置空表示不唯一
+        let mut candidates: HashMap<(String, u64), Option<String>> = HashMap::new();
+        for entry in jwalk::WalkDir::new(&scan_root)
+            .process_read_dir(|_, _, _, dir_entry_results| {
+                dir_entry_results.retain(|result| {
+                    result
+                        .as_ref()
+                        .map(|entry| {
+                            let name = entry.file_name().to_str().unwrap_or("");
+                            name != ".Aurora_Cache" && !name.starts_with('.')
+                        })
+                        .unwrap_or(true)
+                });
+            })
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let Some(path) = entry.path().to_str().map(db::normalize_path) else {
+                continue;
+            };
+            let Some(name) = Path::new(&path).file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let Ok(md) = entry.metadata() else {
+                continue;
+            };
+            candidates
+                .entry((name.to_string(), md.len()))
+                .and_modify(|slot| *slot = None)
+                .or_insert(Some(path));
+        }
+
+        // 3. 逐条匹配并按（旧父目录，新父目录）投票
+        let mut votes: HashMap<(String, String), usize> = HashMap::new();
+        for entry in &missing {
+            let key = (entry.name.clone(), entry.size);
+            let Some(Some(found)) = candidates.get(&key) else {
+                continue;
+            };
+            let old_parent = Path::new(&entry.path)
+                .parent()
+                .and_then(|p| p.to_str())
+                .unwrap_or("")
+                .to_string();
+            let new_parent = Path::new(found)
+                .parent()
+                .and_then(|p| p.to_str())
+                .unwrap_or("")
+                .to_string();
+            if old_parent.is_empty() || new_parent.is_empty() || old_parent == new_parent {
+                continue;
+            }
+            *votes.entry((old_parent, new_parent)).or_insert(0) += 1;
+        }
+
+        let mut suggestions: Vec<RelinkSuggestion> = votes
+            .into_iter()
+            .map(|((old_prefix, new_prefix), matches)| RelinkSuggestion {
+                old_prefix,
+                new_prefix,
+                matches,
+            })
+            .collect();
+        suggestions.sort_by(|a, b| {
+            b.matches
+                .cmp(&a.matches)
+                .then_with(|| a.old_prefix.cmp(&b.old_prefix))
+        });
+        suggestions.truncate(20);
+        Ok(suggestions)
+    })
+    .await
+    .map_err(|e| format!("重连建议任务失败: {}", e))?
+}
+
+/// 把 old_prefix 下的失联条目重连到 new_prefix 下的同相对路径。
+/// 只处理磁盘上旧路径缺失且新路径存在的条目；file_id 不变
+#[tauri::command]
+pub async fn relink_missing(
+    old_prefix: String,
+    new_prefix: String,
+    dry_run: Option<bool>,
+    app: tauri::AppHandle,
+) -> Result<RelinkReport, String> {
+    let old_prefix = db::normalize_path(&old_prefix);
+    let new_prefix = db::normalize_path(&new_prefix);
+    if old_prefix == new_prefix {
+        return Err("新旧前缀相同".to_string());
+    }
+    let dry_run = dry_run.unwrap_or(false);
+    let pool = app.state::<AppDbPool>().inner().clone();
+    let color_db = app.state::<Arc<crate::color_db::ColorDbPool>>().inner().clone();
+
+    tokio::task::spawn_blocking(move || {
+        let mut report = RelinkReport {
+            dry_run,
+            relinked: 0,
+            still_missing: 0,
+        };
+        let conn = pool.get_connection();
+        let entries =
+            db::file_index::get_entries_under_path(&conn, &old_prefix).map_err(|e| e.to_string())?;
+        let old_dir_prefix = format!("{}/", old_prefix);
+
+        for entry in &entries {
+            if Path::new(&entry.path).exists() {
+                continue;
+            }
+            let new_path = if entry.path == old_prefix {
+                new_prefix.clone()
+            } else if let Some(rel) = entry.path.strip_prefix(&old_dir_prefix) {
+                format!("{}/{}", new_prefix, rel)
+            } else {
+                continue;
+            };
+            let target_exists = if entry.file_type == "Folder" {
+                Path::new(&new_path).is_dir()
+            } else {
+                Path::new(&new_path).is_file()
+            };
+            if !target_exists {
+                if entry.file_type != "Folder" {
+                    report.still_missing += 1;
+                }
+                continue;
+            }
+            if dry_run {
+                report.relinked += 1;
+                continue;
+            }
+            // 稳定 ID：只改 path，元数据/嵌入随 file_id 保留
+            let _ = conn.execute(
+                "UPDATE file_index SET path = ?1 WHERE file_id = ?2",
+                rusqlite::params![new_path, entry.file_id],
+            );
+            let _ = conn.execute(
+                "UPDATE file_metadata SET path = ?1 WHERE file_id = ?2",
+                rusqlite::params![new_path, entry.file_id],
+            );
+            report.relinked += 1;
+        }
+
+        if !dry_run && report.relinked > 0 {
+            // 主色调按路径存储，整体随前缀迁移；祖先目录的缓存大小已过期
+            let _ = color_db.move_colors(&old_prefix, &new_prefix);
+            let _ = db::file_index::invalidate_size_cache_for(&conn, &old_prefix);
+            let _ = db::file_index::invalidate_size_cache_for(&conn, &new_prefix);
+            let _ = db::activity_log::record(
+                &conn,
+                "move",
+                &old_prefix,
+                Some(&format!("重连到 {}（{} 项）", new_prefix, report.relinked)),
+                "user",
+            );
+        }
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("重连任务失败: {}", e))?
+}
@@ -0,0 +1,376 @@
+//! AI 生成图片元数据解析。
+//!
+//! 识别 Stable Diffusion WebUI (A1111) 和 NovelAI 在 PNG tEXt/iTXt 块
+//! 以及 JPEG/WebP EXIF UserComment 中嵌入的生成参数，
+//! 解析出 prompt/seed/model 等字段写入 file_metadata.ai_data，
+//! prompt 另外进入 prompt_fts 全文索引供搜索。
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::db::{self, AppDbPool};
+
+/// 读取 PNG 的 tEXt/iTXt 文本块，返回 (keyword, text) 列表
+fn read_png_text_chunks(path: &Path) -> Option<Vec<(String, String)>> {
+    let mut file = BufReader::new(File::open(path).ok()?);
+    let mut signature = [0u8; 8];
+    file.read_exact(&mut signature).ok()?;
+    if signature != [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A] {
+        return None;
+    }
+
+    let mut chunks = Vec::new();
+    loop {
+        let mut header = [0u8; 8];
+        if file.read_exact(&mut header).is_err() {
+            break;
+        }
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let chunk_type = &header[4..8];
+
+        // 文本块都在图像数据之前，碰到 IDAT/IEND 就可以停了
+        if chunk_type == b"IDAT" || chunk_type == b"IEND" {
+            break;
+        }
+
+        if chunk_type == b"tEXt" || chunk_type == b"iTXt" {
+            // 防御超大块
+            if length > 4 * 1024 * 1024 {
+                return None;
+            }
+            let mut data = vec![0u8; length];
+            file.read_exact(&mut data).ok()?;
+            if let Some(null_pos) = data.iter().position(|&b| b == 0) {
+                let keyword = String::from_utf8_lossy(&data[..null_pos]).to_string();
+                let text = if chunk_type == b"iTXt" {
+                    // iTXt: keyword\0 compression_flag compression_method language\0 translated\0 text
+                    let rest = &data[null_pos + 1..];
+                    if rest.len() < 2 || rest[0] != 0 {
+                        // 压缩的 iTXt 不处理
+                        String::new()
+                    } else {
+                        let mut parts = rest[2..].splitn(3, |&b| b == 0);
+                        let _language = parts.next();
+                        let _translated = parts.next();
+                        parts
+                            .next()
+                            .map(|t| String::from_utf8_lossy(t).to_string())
+                            .unwrap_or_default()
+                    }
+                } else {
+                    String::from_utf8_lossy(&data[null_pos + 1..]).to_string()
+                };
+                if !text.is_empty() {
+                    chunks.push((keyword, text));
+                }
+            }
+        } else {
+            // 跳过块数据
+            std::io::copy(
+                &mut file.by_ref().take(length as u64),
+                &mut std::io::sink(),
+            )
+            .ok()?;
+        }
+        // 跳过 CRC
+        let mut crc = [0u8; 4];
+        file.read_exact(&mut crc).ok()?;
+    }
+    Some(chunks)
+}
+
+/// 解析 A1111 的 "parameters" 文本：
+/// 第一段是 prompt，"Negative prompt:" 开头的是负面提示，
+/// 最后一行是 "Steps: 20, Sampler: ..., Seed: ..., Model: ..." 键值对
+fn parse_a1111_parameters(text: &str) -> Value {
+    let mut prompt_lines: Vec<&str> = Vec::new();
+    let mut negative = String::new();
+    let mut settings_line = "";
+
+    let mut in_negative = false;
+    for line in text.lines() {
+        if let Some(rest) = line.strip_prefix("Negative prompt:") {
+            in_negative = true;
+            negative = rest.trim_start().to_string();
+        } else if line.contains("Steps:") && line.contains(',') {
+            settings_line = line;
+            in_negative = false;
+        } else if in_negative {
+            negative.push('\n');
+            negative.push_str(line);
+        } else {
+            prompt_lines.push(line);
+        }
+    }
+
+    let mut result = json!({
+        "source": "a1111",
+        "prompt": prompt_lines.join("\n").trim(),
+        "raw": text,
+    });
+    if !negative.is_empty() {
+        result["negativePrompt"] = json!(negative.trim());
+    }
+
+    // 解析设置行里的键值对（值不会包含逗号的简单格式）
+    for pair in settings_line.split(", ") {
+        if let Some((key, value)) = pair.split_once(": ") {
+            let value = value.trim();
+            match key.trim() {
+                "Steps" => result["steps"] = parse_number(value),
+                "Sampler" => result["sampler"] = json!(value),
+                "CFG scale" => result["cfgScale"] = parse_number(value),
+                "Seed" => result["seed"] = parse_number(value),
+                "Model" => result["model"] = json!(value),
+                "Model hash" => result["modelHash"] = json!(value),
+                "Size" => result["size"] = json!(value),
+                _ => {}
+            }
+        }
+    }
+    result
+}
+
+fn parse_number(s: &str) -> Value {
+    if let Ok(i) = s.parse::<i64>() {
+        json!(i)
+    } else if let Ok(f) = s.parse::<f64>() {
+        json!(f)
+    } else {
+        json!(s)
+    }
+}
+
+/// 解析 NovelAI 的 PNG 元数据（Software=NovelAI，Comment 是 JSON）
+fn parse_novelai(chunks: &[(String, String)]) -> Option<Value> {
+    let software = chunks.iter().find(|(k, _)| k == "Software")?;
+    if !software.1.contains("NovelAI") {
+        return None;
+    }
+
+    let mut result = json!({ "source": "novelai" });
+    if let Some((_, desc)) = chunks.iter().find(|(k, _)| k == "Description") {
+        result["prompt"] = json!(desc.trim());
+    }
+    if let Some((_, comment)) = chunks.iter().find(|(k, _)| k == "Comment") {
+        result["raw"] = json!(comment);
+        if let Ok(data) = serde_json::from_str::<Value>(comment) {
+            if let Some(uc) = data.get("uc") {
+                result["negativePrompt"] = uc.clone();
+            }
+            for (src, dst) in [("seed", "seed"), ("steps", "steps"), ("sampler", "sampler"), ("scale", "cfgScale")] {
+                if let Some(v) = data.get(src) {
+                    result[dst] = v.clone();
+                }
+            }
+        }
+    }
+    Some(result)
+}
+
+/// 从 JPEG/WebP 的 EXIF UserComment 里取 A1111 参数文本
+fn read_exif_user_comment(path: &Path) -> Option<String> {
+    let file = File::open(path).ok()?;
+    let mut reader = BufReader::new(file);
+    let exif = exif::Reader::new().read_from_container(&mut reader).ok()?;
+    let field = exif.get_field(exif::Tag::UserComment, exif::In::PRIMARY)?;
+    if let exif::Value::Undefined(ref bytes, _) = field.value {
+        // UserComment 前 8 字节是编码标识 (ASCII\0\0\0 / UNICODE\0)
+        if bytes.len() > 8 {
+            let (charset, payload) = bytes.split_at(8);
+            if charset.starts_with(b"UNICODE") {
+                let utf16: Vec<u16> = payload
+                    .chunks_exact(2)
+                    .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                    .collect();
+                return Some(String::from_utf16_lossy(&utf16));
+            }
+            return Some(String::from_utf8_lossy(payload).trim_matches('\0').to_string());
+        }
+    }
+    None
+}
+
+/// 解析单个文件的 AI 生成元数据，不是 AI 生成图时返回 None
+pub fn parse_sd_metadata(file_path: &str) -> Option<Value> {
+    let path = Path::new(file_path);
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+
+    match ext.as_str() {
+        "png" => {
+            let chunks = read_png_text_chunks(path)?;
+            if let Some(novelai) = parse_novelai(&chunks) {
+                return Some(novelai);
+            }
+            let (_, params) = chunks.iter().find(|(k, _)| k == "parameters")?;
+            Some(parse_a1111_parameters(params))
+        }
+        "jpg" | "jpeg" | "webp" => {
+            let comment = read_exif_user_comment(path)?;
+            if comment.contains("Steps:") {
+                Some(parse_a1111_parameters(&comment))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// 解析单个文件的 AI 生成元数据并写入 file_metadata.ai_data。
+/// 返回解析结果，不是 AI 生成图时返回 None 且不改动元数据
+#[tauri::command]
+pub async fn extract_sd_metadata(
+    file_path: String,
+    pool: tauri::State<'_, AppDbPool>,
+) -> Result<Option<Value>, String> {
+    let pool = pool.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let parsed = parse_sd_metadata(&file_path);
+        if let Some(data) = &parsed {
+            let file_id = db::generate_id(&file_path);
+            let conn = pool.get_connection();
+            db::file_metadata::set_ai_data(&conn, &file_id, data).map_err(|e| e.to_string())?;
+        }
+        Ok(parsed)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// SD 元数据扫描单飞标志
+static SD_SCAN_RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SdScanProgress {
+    processed: usize,
+    total: usize,
+    /// 识别出 AI 元数据的文件数
+    detected: usize,
+}
+
+/// 后台扫描索引中的图片，提取 AI 生成元数据。
+/// `scope` 为目录路径时只扫该目录，为 None 时扫整个索引；
+/// 已有 ai_data 的文件跳过。返回新识别的文件数
+#[tauri::command]
+pub async fn scan_sd_metadata(scope: Option<String>, app: AppHandle) -> Result<usize, String> {
+    if SD_SCAN_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("SD 元数据扫描已在运行".to_string());
+    }
+
+    let pool = app.state::<AppDbPool>().inner().clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        // 收集范围内的图片路径，跳过已有 ai_data 的文件
+        let (paths, already_parsed) = {
+            let conn = pool.get_connection();
+            let entries = match &scope {
+                Some(dir) => db::file_index::get_entries_under_path(&conn, dir),
+                None => db::file_index::get_all_image_files(&conn),
+            }
+            .map_err(|e| e.to_string())?;
+            let paths: Vec<String> = entries
+                .into_iter()
+                .filter(|e| e.file_type == "Image")
+                .map(|e| e.path)
+                .collect();
+
+            let mut stmt = conn
+                .prepare("SELECT file_id FROM file_metadata WHERE ai_data IS NOT NULL")
+                .map_err(|e| e.to_string())?;
+            let already: std::collections::HashSet<String> = stmt
+                .query_map([], |row| row.get(0))
+                .map_err(|e| e.to_string())?
+                .filter_map(|r| r.ok())
+                .collect();
+            (paths, already)
+        };
+
+        let total = paths.len();
+        let mut detected = 0usize;
+
+        for (i, path) in paths.iter().enumerate() {
+            let file_id = db::generate_id(path);
+            if !already_parsed.contains(&file_id) {
+                if let Some(data) = parse_sd_metadata(path) {
+                    let conn = pool.get_connection();
+                    db::file_metadata::set_ai_data(&conn, &file_id, &data)
+                        .map_err(|e| e.to_string())?;
+                    detected += 1;
+                }
+            }
+
+            if (i + 1).is_multiple_of(100) || i + 1 == total {
+                let _ = app.emit("sd-scan-progress", SdScanProgress {
+                    processed: i + 1,
+                    total,
+                    detected,
+                });
+            }
+        }
+
+        Ok(detected)
+    })
+    .await
+    .map_err(|e| format!("SD metadata scan task failed: {}", e))
+    .and_then(|r| r);
+
+    SD_SCAN_RUNNING.store(false, Ordering::SeqCst);
+    result
+}
+
+/// 按生成模型名查询文件
+#[tauri::command]
+pub fn get_files_by_sd_model(
+    model: String,
+    pool: tauri::State<AppDbPool>,
+) -> Result<Vec<db::file_metadata::FileMetadata>, String> {
+    let conn = pool.get_connection();
+    db::file_metadata::get_files_by_sd_model(&conn, &model).map_err(|e| e.to_string())
+}
+
+/// 按生成方式搜索：提示词（含负面）全文匹配，可叠加采样器/步数/CFG 过滤
+#[tauri::command]
+pub fn search_by_prompt_text(
+    query: String,
+    sampler: Option<String>,
+    steps: Option<i64>,
+    cfg_scale: Option<f64>,
+    limit: Option<i64>,
+    pool: tauri::State<AppDbPool>,
+) -> Result<Vec<db::file_metadata::PromptSearchResult>, String> {
+    let conn = pool.get_connection();
+    db::file_metadata::search_by_prompt_text(
+        &conn,
+        &query,
+        sampler.as_deref(),
+        steps,
+        cfg_scale,
+        limit.unwrap_or(100),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// 全文搜索 AI 提示词，返回命中文件及高亮片段
+#[tauri::command]
+pub fn search_prompts(
+    query: String,
+    limit: Option<i64>,
+    pool: tauri::State<AppDbPool>,
+) -> Result<Vec<db::file_metadata::NoteSearchResult>, String> {
+    let conn = pool.get_connection();
+    db::file_metadata::search_prompts(&conn, &query, limit.unwrap_or(100)).map_err(|e| e.to_string())
+}
@@ -0,0 +1,132 @@
+//! 把选中文件交给系统分享通道：Windows 走共享面板（WinRT
+//! DataTransferManager，经 PowerShell 的 interop 垫片唤起），
+//! macOS 没有可脚本化的共享面板，退而用 AppleScript 建一封带
+//! 附件的邮件草稿（AirDrop 只能从 Finder 发起），Linux 用
+//! xdg-email 附件。与系统通知一样不引入平台绑定依赖，全部走
+//! 子进程，机器上缺对应工具时报人话错误。
+
+use tauri::Manager;
+
+use crate::db::{self, AppDbPool};
+
+#[cfg(windows)]
+pub(crate) fn share_with_os(paths: &[String]) -> Result<(), String> {
+    // WinRT 共享面板需要窗口句柄，借 C# 垫片拿前台窗口再 ShowShareUIForWindow
+    let file_list = paths
+        .iter()
+        .map(|p| format!("'{}'", p.replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(",");
+    let script = format!(
+        "Add-Type -TypeDefinition @'\n\
+using System;\n\
+using System.Runtime.InteropServices;\n\
+public static class ShareInterop {{\n\
+    [DllImport(\"user32.dll\")] public static extern IntPtr GetForegroundWindow();\n\
+    [ComImport, Guid(\"3A3DCD6C-3EAB-43DC-BCDE-45671CE800C8\"), InterfaceType(ComInterfaceType.InterfaceIsIUnknown)]\n\
+    public interface IDataTransferManagerInterop {{\n\
+        IntPtr GetForWindow([In] IntPtr appWindow, [In] ref Guid riid);\n\
+        void ShowShareUIForWindow(IntPtr appWindow);\n\
+    }}\n\
+}}\n\
+'@;\n\
+        [Windows.ApplicationModel.DataTransfer.DataTransferManager, Windows.ApplicationModel.DataTransfer, ContentType = WindowsRuntime] | Out-Null;\n\
+        [Windows.Storage.StorageFile, Windows.Storage, ContentType = WindowsRuntime] | Out-Null;\n\
+        $files = @({file_list}) | ForEach-Object {{ [Windows.Storage.StorageFile]::GetFileFromPathAsync($_).GetAwaiter().GetResult() }};\n\
+        $hwnd = [ShareInterop]::GetForegroundWindow();\n\
+        $interop = [Windows.ApplicationModel.DataTransfer.DataTransferManager]::As([ShareInterop+IDataTransferManagerInterop]);\n\
+        $riid = [Guid]'A5CAEE9B-8708-49D1-8D36-67D25A8DA00C';\n\
+        $dtmPtr = $interop.GetForWindow($hwnd, [ref]$riid);\n\
+        $dtm = [System.Runtime.InteropServices.Marshal]::GetObjectForIUnknown($dtmPtr);\n\
+        Register-ObjectEvent -InputObject $dtm -EventName DataRequested -Action {{\n\
+            $request = $EventArgs.Request;\n\
+            $request.Data.Properties.Title = 'Aurora Gallery';\n\
+            $request.Data.SetStorageItems($Event.MessageData);\n\
+        }} -MessageData $files | Out-Null;\n\
+        $interop.ShowShareUIForWindow($hwnd);\n\
+        Start-Sleep -Seconds 30;"
+    );
+    let status = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .status()
+        .map_err(|e| format!("调用 PowerShell 失败: {}", e))?;
+    if !status.success() {
+        return Err("唤起系统共享面板失败".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn share_with_os(paths: &[String]) -> Result<(), String> {
+    // macOS 的共享面板（NSSharingServicePicker）没有脚本接口，
+    // 这里用 Mail 建草稿作为通用分享；AirDrop 用户可在 Finder 里继续
+    let attach_lines: String = paths
+        .iter()
+        .map(|p| {
+            format!(
+                "make new attachment with properties {{file name:POSIX file \"{}\"}} at after the last paragraph\n",
+                p.replace('"', "\\\"")
+            )
+        })
+        .collect();
+    let script = format!(
+        "tell application \"Mail\"\n\
+         set msg to make new outgoing message with properties {{visible:true}}\n\
+         tell msg to tell content\n{}\
+         end tell\n\
+         activate\n\
+         end tell",
+        attach_lines
+    );
+    let status = std::process::Command::new("osascript")
+        .args(["-e", &script])
+        .status()
+        .map_err(|e| format!("调用 osascript 失败: {}", e))?;
+    if !status.success() {
+        return Err("创建分享邮件失败".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+pub(crate) fn share_with_os(paths: &[String]) -> Result<(), String> {
+    let mut cmd = std::process::Command::new("xdg-email");
+    for p in paths {
+        cmd.arg("--attach").arg(p);
+    }
+    let status = cmd
+        .status()
+        .map_err(|_| "分享需要系统安装 xdg-email（xdg-utils）".to_string())?;
+    if !status.success() {
+        return Err("唤起邮件分享失败".to_string());
+    }
+    Ok(())
+}
+
+/// 把选中文件交给系统分享（共享面板 / 邮件附件）
+#[tauri::command]
+pub async fn share_files(file_ids: Vec<String>, app: tauri::AppHandle) -> Result<(), String> {
+    let pool = app.state::<AppDbPool>().inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let paths: Vec<String> = {
+            let conn = pool.get_connection();
+            let mut resolved = Vec::with_capacity(file_ids.len());
+            for id in &file_ids {
+                if let Some(entry) =
+                    db::file_index::get_entry_by_id(&conn, id).map_err(|e| e.to_string())?
+                {
+                    if entry.file_type != "Folder" {
+                        resolved.push(entry.path);
+                    }
+                }
+            }
+            resolved
+        };
+        if paths.is_empty() {
+            return Err("选中的文件都不在索引中".to_string());
+        }
+        share_with_os(&paths)
+    })
+    .await
+    .map_err(|e| format!("分享任务失败: {}", e))?
+}
@@ -0,0 +1,134 @@
+//! 优雅退出协调器：退出前先让后台任务收尾，而不是写到一半被杀。
+//!
+//! 流程：置全局关停标志（扫描 / 缩略图预热 / 维度回填的循环会尽快退出），
+//! 通知主色调与 CLIP 任务取消，把当时还在跑的任务记到
+//! resume_state.json，然后等待（带超时）各 RUNNING 标志落下、
+//! 冲刷写入队列并对两个数据库做 WAL 检查点，最后才真正退出。
+//! 下次启动时前端用 [`take_resume_state`] 取走记录，重新拉起被打断的任务。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+/// 全局关停标志，后台循环在每个工作项之间检查它
+static SHUTDOWN_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// 等待后台任务收尾的上限
+const SHUTDOWN_TIMEOUT_MS: u64 = 8_000;
+/// 轮询间隔
+const POLL_MS: u64 = 100;
+
+/// 是否已请求关停（供扫描 / 预热 / 回填等循环查询）
+pub fn is_requested() -> bool {
+    SHUTDOWN_REQUESTED.load(Ordering::SeqCst)
+}
+
+/// 退出时被打断的任务清单（resume_state.json 的内容）
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumeState {
+    pub exited_at: i64,
+    /// 退出时缩略图预热还在跑
+    pub thumbnail_pregen: bool,
+    /// 退出时维度回填还在跑
+    pub dimension_backfill: bool,
+    /// 退出时 CLIP 向量生成被取消（有未完成的批次）
+    pub clip_embedding: bool,
+    /// 退出时主色调提取还有待处理项
+    pub color_extraction: bool,
+}
+
+fn resume_state_path(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join("resume_state.json"))
+}
+
+/// 还有哪些后台任务没停干净
+fn busy_jobs() -> Vec<&'static str> {
+    let mut busy = Vec::new();
+    if crate::thumbnail::is_pregenerating() {
+        busy.push("thumbnail-pregen");
+    }
+    if crate::BACKFILL_RUNNING.load(Ordering::SeqCst) {
+        busy.push("dimension-backfill");
+    }
+    busy
+}
+
+/// 协调关停：通知各后台任务、记录断点、等待收尾、冲刷数据库。
+/// 超时后不再等待（剩余任务的进度以各自的批次提交为准）
+pub async fn coordinate(app: &tauri::AppHandle) {
+    SHUTDOWN_REQUESTED.store(true, Ordering::SeqCst);
+
+    // 先记下退出瞬间还在跑什么，下次启动据此恢复
+    let state = ResumeState {
+        exited_at: chrono::Utc::now().timestamp(),
+        thumbnail_pregen: crate::thumbnail::is_pregenerating(),
+        dimension_backfill: crate::BACKFILL_RUNNING.load(Ordering::SeqCst),
+        clip_embedding: crate::is_embedding_generation_running(),
+        color_extraction: false,
+    };
+
+    // 通知各任务停下：CLIP 走取消标志，主色调走关闭标志（会先保存缓冲区）
+    crate::CANCEL_GENERATION.store(true, Ordering::SeqCst);
+    crate::color_worker::shutdown_color_extraction().await;
+
+    if state.thumbnail_pregen || state.dimension_backfill || state.clip_embedding {
+        if let Ok(path) = resume_state_path(app) {
+            if let Ok(json) = serde_json::to_string_pretty(&state) {
+                let _ = std::fs::write(&path, json);
+            }
+        }
+    }
+
+    // 等待 RUNNING 标志落下，最多 SHUTDOWN_TIMEOUT_MS
+    let deadline = std::time::Instant::now()
+        + std::time::Duration::from_millis(SHUTDOWN_TIMEOUT_MS);
+    loop {
+        let busy = busy_jobs();
+        if busy.is_empty() {
+            break;
+        }
+        if std::time::Instant::now() >= deadline {
+            log::warn!("退出等待超时，仍在运行的任务: {}", busy.join(", "));
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(POLL_MS)).await;
+    }
+
+    // 冲刷写入队列：提交一个空操作并等它完成，排在它前面的批次必然已提交
+    if let Some(writer) = app.try_state::<crate::db::writer::DbWriter>() {
+        let _ = writer.submit(|_conn| Ok(())).await;
+    }
+
+    // 两个库都做一次 WAL 检查点，把日志并回主文件
+    if let Some(pool) = app.try_state::<crate::db::AppDbPool>() {
+        let pool = pool.inner().clone();
+        let _ = tokio::task::spawn_blocking(move || {
+            let conn = pool.get_connection();
+            conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")
+        })
+        .await;
+    }
+    if let Some(pool) = app.try_state::<std::sync::Arc<crate::color_db::ColorDbPool>>() {
+        let pool = pool.inner().clone();
+        let _ = tokio::task::spawn_blocking(move || pool.force_wal_checkpoint()).await;
+    }
+
+    log::info!("后台任务已收尾，应用退出");
+}
+
+/// 取走上次退出的断点记录（读后即删；没有记录时返回 None）。
+/// 前端启动时调用，据此重新拉起被打断的预热 / 回填 / 向量生成
+#[tauri::command]
+pub fn take_resume_state(app: tauri::AppHandle) -> Result<Option<ResumeState>, String> {
+    let path = resume_state_path(&app)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let _ = std::fs::remove_file(&path);
+    Ok(serde_json::from_str(&text).ok())
+}
@@ -1,15 +1,14 @@
 use tauri::Manager;
+use tauri::Emitter;
 use serde::Serialize;
 use std::path::Path;
 use std::fs;
 use std::io::{Read, BufReader};
 use std::num::NonZeroU32;
-use tauri;
 use jxl_oxide::JxlImage;
 use fast_image_resize as fr;
 use image::codecs::jpeg::{JpegEncoder, JpegDecoder};
 use image::ImageFormat;
-use image;
 use rayon::prelude::*;
 use crate::color_extractor;
 
@@ -19,9 +18,110 @@ pub struct BatchResult {
     pub url: Option<String>,
 }
 
+/// 缩略图生成设置（前端在启动和修改设置时下发，只保存在内存中）
+#[derive(Clone, Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThumbnailSettings {
+    /// JPEG 压缩质量 1-100
+    pub jpeg_quality: u8,
+    /// 首选输出格式：auto（按透明度自动选择）/ jpeg / webp
+    pub format: String,
+    /// 短边目标尺寸（像素）
+    pub target_size: u32,
+    /// 透明图片的背景处理：none（保留透明）/ checkerboard（棋盘格）/ "#RRGGBB"（纯色）。
+    /// 生成时直接合成背景，避免前端暗色模式下透明主体看不清
+    #[serde(default = "default_transparent_background")]
+    pub transparent_background: String,
+}
+
+fn default_transparent_background() -> String {
+    "none".to_string()
+}
+
+impl Default for ThumbnailSettings {
+    fn default() -> Self {
+        Self {
+            jpeg_quality: 80,
+            format: "auto".to_string(),
+            target_size: 256,
+            transparent_background: default_transparent_background(),
+        }
+    }
+}
+
+static THUMBNAIL_SETTINGS: once_cell::sync::Lazy<std::sync::RwLock<ThumbnailSettings>> =
+    once_cell::sync::Lazy::new(|| std::sync::RwLock::new(ThumbnailSettings::default()));
+
+pub(crate) fn current_settings() -> ThumbnailSettings {
+    THUMBNAIL_SETTINGS.read().unwrap().clone()
+}
+
+/// 更新缩略图设置。缓存键包含设置，修改后旧缓存自动失效
+#[tauri::command]
+pub fn set_thumbnail_settings(settings: ThumbnailSettings) -> Result<(), String> {
+    if settings.jpeg_quality == 0 || settings.jpeg_quality > 100 {
+        return Err("JPEG 质量必须在 1-100 之间".to_string());
+    }
+    // AVIF 编码暂不支持（image crate 未启用 avif 编码特性）
+    if !matches!(settings.format.as_str(), "auto" | "jpeg" | "webp") {
+        return Err(format!("不支持的缩略图格式: {}", settings.format));
+    }
+    if !(64..=1024).contains(&settings.target_size) {
+        return Err("缩略图尺寸必须在 64-1024 之间".to_string());
+    }
+    if !matches!(settings.transparent_background.as_str(), "none" | "checkerboard")
+        && parse_hex_color(&settings.transparent_background).is_none()
+    {
+        return Err(format!("无效的透明背景设置: {}", settings.transparent_background));
+    }
+    *THUMBNAIL_SETTINGS.write().unwrap() = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_thumbnail_settings() -> ThumbnailSettings {
+    current_settings()
+}
+
+/// 解析 "#RRGGBB" 形式的颜色
+fn parse_hex_color(s: &str) -> Option<[u8; 3]> {
+    let hex = s.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let v = u32::from_str_radix(hex, 16).ok()?;
+    Some([(v >> 16) as u8, (v >> 8) as u8, v as u8])
+}
+
+/// 把 RGBA 像素按 alpha 合成到背景上，返回 RGB 缓冲。
+/// 背景为 "checkerboard" 时使用 8px 灰白棋盘格，否则使用指定的纯色
+fn composite_transparent_pixels(pixels: &[u8], width: u32, background: &str) -> Vec<u8> {
+    let solid = parse_hex_color(background);
+    let mut out = Vec::with_capacity(pixels.len() / 4 * 3);
+    for (i, p) in pixels.chunks_exact(4).enumerate() {
+        let bg = match solid {
+            Some(c) => c,
+            None => {
+                let x = (i as u32) % width;
+                let y = (i as u32) / width;
+                if ((x / 8) + (y / 8)).is_multiple_of(2) {
+                    [0xCC, 0xCC, 0xCC]
+                } else {
+                    [0xFF, 0xFF, 0xFF]
+                }
+            }
+        };
+        let a = p[3] as u16;
+        for ch in 0..3 {
+            out.push(((p[ch] as u16 * a + bg[ch] as u16 * (255 - a)) / 255) as u8);
+        }
+    }
+    out
+}
+
 fn is_jxl(buffer: &[u8]) -> bool {
     if buffer.starts_with(&[0xFF, 0x0A]) { return true; }
-    if buffer.len() >= 12 && &buffer[0..12] == &[0, 0, 0, 0x0C, 0x4A, 0x58, 0x4C, 0x20, 0x0D, 0x0A, 0x87, 0x0A] { return true; }
+    if buffer.len() >= 12 && buffer[0..12] == [0, 0, 0, 0x0C, 0x4A, 0x58, 0x4C, 0x20, 0x0D, 0x0A, 0x87, 0x0A] { return true; }
     false
 }
 
@@ -33,16 +133,14 @@ fn is_avif(buffer: &[u8]) -> bool {
     false
 }
 
-// Core thumbnail generation (kept synchronous; invoked from spawn_blocking)
-pub(crate) fn process_single_thumbnail(file_path: &str, cache_root: &Path) -> Option<String> {
-    use std::io::BufWriter;
-
+/// 计算某个文件在当前设置下的缩略图缓存路径 (jpg, webp)。
+/// 缓存键包含尺寸/质量/格式设置，修改设置后旧缓存自动失效
+pub(crate) fn thumbnail_cache_paths(
+    file_path: &str,
+    cache_root: &Path,
+    settings: &ThumbnailSettings,
+) -> Option<(std::path::PathBuf, std::path::PathBuf)> {
     let image_path = Path::new(file_path);
-    if !image_path.exists() || file_path.contains(".Aurora_Cache") {
-        return None;
-    }
-
-    // Quick hash
     let metadata = fs::metadata(image_path).ok()?;
     let size = metadata.len();
     let modified = metadata.modified()
@@ -53,12 +151,151 @@ pub(crate) fn process_single_thumbnail(file_path: &str, cache_root: &Path) -> Op
     let mut buffer = [0u8; 4096];
     let bytes_read = file.read(&mut buffer).unwrap_or(0);
 
-    let cache_key = format!("{}-{}-{:?}", size, modified, &buffer[..bytes_read]);
+    let cache_key = format!(
+        "{}-{}-{:?}-q{}-s{}-{}-{}",
+        size, modified, &buffer[..bytes_read],
+        settings.jpeg_quality, settings.target_size, settings.format,
+        settings.transparent_background,
+    );
     let hash_str = format!("{:x}", md5::compute(cache_key.as_bytes()));
     let cache_filename = if hash_str.len() >= 24 { hash_str[..24].to_string() } else { format!("{:0>24}", hash_str) };
 
-    let jpg_cache_file_path = cache_root.join(format!("{}.jpg", cache_filename));
-    let webp_cache_file_path = cache_root.join(format!("{}.webp", cache_filename));
+    Some((
+        cache_root.join(format!("{}.jpg", cache_filename)),
+        cache_root.join(format!("{}.webp", cache_filename)),
+    ))
+}
+
+/// 在小尺寸灰度图上用梯度能量重心估算视觉主体位置，
+/// 返回主体中心在原图坐标系中的位置。
+/// 纯色/低对比图像退化为几何中心
+fn estimate_subject_center(img: &image::DynamicImage) -> (u32, u32) {
+    let small = img.thumbnail(64, 64);
+    let gray = small.to_luma8();
+    let (w, h) = gray.dimensions();
+    if w < 3 || h < 3 {
+        return (img.width() / 2, img.height() / 2);
+    }
+
+    let mut total = 0.0f64;
+    let mut sum_x = 0.0f64;
+    let mut sum_y = 0.0f64;
+    for y in 1..h - 1 {
+        for x in 1..w - 1 {
+            let px = |dx: i32, dy: i32| {
+                gray.get_pixel((x as i32 + dx) as u32, (y as i32 + dy) as u32).0[0] as f64
+            };
+            // Sobel 风格的梯度能量：细节丰富（高熵）的区域能量高
+            let gx = px(1, 0) - px(-1, 0);
+            let gy = px(0, 1) - px(0, -1);
+            let energy = gx.abs() + gy.abs();
+            total += energy;
+            sum_x += x as f64 * energy;
+            sum_y += y as f64 * energy;
+        }
+    }
+
+    if total < f64::EPSILON {
+        return (img.width() / 2, img.height() / 2);
+    }
+
+    let cx = (sum_x / total / w as f64 * img.width() as f64) as u32;
+    let cy = (sum_y / total / h as f64 * img.height() as f64) as u32;
+    (cx.min(img.width()), cy.min(img.height()))
+}
+
+/// 以主体为中心裁剪为正方形（用于方格布局的智能裁剪模式）
+fn smart_crop_square(img: image::DynamicImage) -> image::DynamicImage {
+    let (w, h) = (img.width(), img.height());
+    if w == h || w == 0 || h == 0 {
+        return img;
+    }
+    let side = w.min(h);
+    let (cx, cy) = estimate_subject_center(&img);
+    let x0 = cx.saturating_sub(side / 2).min(w - side);
+    let y0 = cy.saturating_sub(side / 2).min(h - side);
+    img.crop_imm(x0, y0, side, side)
+}
+
+/// HDR（EXR）线性浮点数据转 8 bit：Reinhard 色调映射压缩动态范围，
+/// 再套 sRGB 近似 gamma（1/2.2）。alpha 通道只做线性截断不参与映射。
+/// 非浮点格式原样返回
+fn tonemap_hdr(img: image::DynamicImage) -> image::DynamicImage {
+    fn map(v: f32) -> u8 {
+        let v = v.max(0.0);
+        ((v / (1.0 + v)).powf(1.0 / 2.2) * 255.0).round().clamp(0.0, 255.0) as u8
+    }
+    match img {
+        image::DynamicImage::ImageRgb32F(src) => {
+            let (w, h) = src.dimensions();
+            let pixels: Vec<u8> = src.into_raw().iter().map(|&v| map(v)).collect();
+            image::RgbImage::from_raw(w, h, pixels)
+                .map(image::DynamicImage::ImageRgb8)
+                .unwrap_or(image::DynamicImage::new_rgb8(0, 0))
+        }
+        image::DynamicImage::ImageRgba32F(src) => {
+            let (w, h) = src.dimensions();
+            let pixels: Vec<u8> = src
+                .into_raw()
+                .chunks_exact(4)
+                .flat_map(|px| {
+                    [
+                        map(px[0]),
+                        map(px[1]),
+                        map(px[2]),
+                        (px[3].clamp(0.0, 1.0) * 255.0).round() as u8,
+                    ]
+                })
+                .collect();
+            image::RgbaImage::from_raw(w, h, pixels)
+                .map(image::DynamicImage::ImageRgba8)
+                .unwrap_or(image::DynamicImage::new_rgba8(0, 0))
+        }
+        other => other,
+    }
+}
+
+/// 给缓存文件名加后缀（用于同一文件的智能裁剪变体）
+fn cache_path_with_suffix(path: &Path, suffix: &str) -> std::path::PathBuf {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+    let ext = path.extension().and_then(|s| s.to_str()).unwrap_or_default();
+    path.with_file_name(format!("{}{}.{}", stem, suffix, ext))
+}
+
+// Core thumbnail generation (kept synchronous; invoked from spawn_blocking)
+pub(crate) fn process_single_thumbnail(file_path: &str, cache_root: &Path) -> Option<String> {
+    process_single_thumbnail_opts(file_path, cache_root, false)
+}
+
+/// `smart_crop` 为 true 时先以主体为中心裁剪成正方形再缩放，
+/// 裁剪变体使用独立的缓存文件（_sq 后缀），与普通缩略图互不影响
+pub(crate) fn process_single_thumbnail_opts(file_path: &str, cache_root: &Path, smart_crop: bool) -> Option<String> {
+    use std::io::BufWriter;
+
+    let image_path = Path::new(file_path);
+    if !image_path.exists() || file_path.contains(".Aurora_Cache") {
+        return None;
+    }
+    // 云盘占位文件解码会触发整文件下载，等用户 materialize_file 之后再生成
+    if crate::fs_attrs::is_offline_placeholder(image_path) {
+        return None;
+    }
+
+    let settings = current_settings();
+    let (jpg_cache_file_path, webp_cache_file_path) =
+        thumbnail_cache_paths(file_path, cache_root, &settings)?;
+    let (jpg_cache_file_path, webp_cache_file_path) = if smart_crop {
+        (
+            cache_path_with_suffix(&jpg_cache_file_path, "_sq"),
+            cache_path_with_suffix(&webp_cache_file_path, "_sq"),
+        )
+    } else {
+        (jpg_cache_file_path, webp_cache_file_path)
+    };
+
+    let mut file = fs::File::open(image_path).ok()?;
+    let mut buffer = [0u8; 4096];
+    let bytes_read = file.read(&mut buffer).unwrap_or(0);
 
     if jpg_cache_file_path.exists() {
         return Some(jpg_cache_file_path.to_str().unwrap_or_default().to_string());
@@ -67,13 +304,17 @@ pub(crate) fn process_single_thumbnail(file_path: &str, cache_root: &Path) -> Op
         return Some(webp_cache_file_path.to_str().unwrap_or_default().to_string());
     }
 
-    let format = image::guess_format(&buffer[..bytes_read]).ok();
-    
+    let mut format = image::guess_format(&buffer[..bytes_read]).ok();
+
     // Fallback for AVIF/JXL if guess_format failed
-    if format.is_none() {
-        if is_avif(&buffer[..bytes_read]) {
+    if format.is_none()
+        && is_avif(&buffer[..bytes_read]) {
             // 后端暂不支持 AVIF 解码
         }
+
+    // TGA 没有魔数可猜，只能按扩展名指定格式
+    if format.is_none() && file_path.to_lowercase().ends_with(".tga") {
+        format = Some(ImageFormat::Tga);
     }
 
     let is_jxl_file = file_path.to_lowercase().ends_with(".jxl") || is_jxl(&buffer[..bytes_read]);
@@ -93,7 +334,8 @@ pub(crate) fn process_single_thumbnail(file_path: &str, cache_root: &Path) -> Op
             let file = fs::File::open(image_path).ok()?;
             let reader = BufReader::new(file);
             let mut decoder = JpegDecoder::new(reader).ok()?;
-            decoder.scale(256, 256).ok()?;
+            let scale_target = settings.target_size.min(u16::MAX as u32) as u16;
+            decoder.scale(scale_target, scale_target).ok()?;
             image::DynamicImage::from_decoder(decoder).ok()?
         } else if is_jxl_file {
             // Special handling for JXL using jxl-oxide
@@ -126,18 +368,27 @@ pub(crate) fn process_single_thumbnail(file_path: &str, cache_root: &Path) -> Op
                 image_reader = image_reader.with_guessed_format().ok()?;
             }
             image_reader.no_limits();
-            image_reader.decode().ok()?
+            let decoded = image_reader.decode().ok()?;
+            // EXR 是线性 HDR，直接截到 8 bit 会大面积过曝，先做色调映射
+            if format == Some(ImageFormat::OpenExr) {
+                tonemap_hdr(decoded)
+            } else {
+                decoded
+            }
         };
 
+        // 智能裁剪：以视觉主体为中心裁剪为正方形
+        let img = if smart_crop { smart_crop_square(img) } else { img };
+
         let width = img.width();
         let height = img.height();
-        const TARGET_MIN_SIZE: u32 = 256;
+        let target_min_size: u32 = settings.target_size;
         let (dst_width, dst_height) = if width < height {
             let ratio = height as f32 / width as f32;
-            (TARGET_MIN_SIZE, (TARGET_MIN_SIZE as f32 * ratio) as u32)
+            (target_min_size, (target_min_size as f32 * ratio) as u32)
         } else {
             let ratio = width as f32 / height as f32;
-            ((TARGET_MIN_SIZE as f32 * ratio) as u32, TARGET_MIN_SIZE)
+            ((target_min_size as f32 * ratio) as u32, target_min_size)
         };
 
         let src_width = NonZeroU32::new(width)?;
@@ -165,7 +416,32 @@ pub(crate) fn process_single_thumbnail(file_path: &str, cache_root: &Path) -> Op
 
             if !cache_root.exists() { let _ = fs::create_dir_all(cache_root); }
 
-            if has_actual_transparency {
+            // 透明像素合成到配置的背景（棋盘格或纯色）上，输出不透明缩略图
+            if has_actual_transparency && settings.transparent_background != "none" {
+                let rgb_buffer = composite_transparent_pixels(pixels, dst_width, &settings.transparent_background);
+                return if settings.format == "webp" {
+                    let cache_file = fs::File::create(&webp_cache_file_path).ok()?;
+                    let mut writer = BufWriter::new(cache_file);
+                    let resized_img = image::DynamicImage::ImageRgb8(image::ImageBuffer::from_raw(dst_width, dst_height, rgb_buffer)?);
+                    resized_img.write_to(&mut writer, ImageFormat::WebP).ok()?;
+                    Some(webp_cache_file_path.to_str().unwrap_or_default().to_string())
+                } else {
+                    let cache_file = fs::File::create(&jpg_cache_file_path).ok()?;
+                    let mut writer = BufWriter::new(cache_file);
+                    let mut encoder = JpegEncoder::new_with_quality(&mut writer, settings.jpeg_quality);
+                    encoder.encode(&rgb_buffer, dst_width, dst_height, image::ColorType::Rgb8).ok()?;
+                    Some(jpg_cache_file_path.to_str().unwrap_or_default().to_string())
+                };
+            }
+
+            // 格式偏好：auto 按实际透明度选择，否则按设置强制
+            let use_webp = match settings.format.as_str() {
+                "webp" => true,
+                "jpeg" => false,
+                _ => has_actual_transparency,
+            };
+
+            if use_webp {
                 let cache_file = fs::File::create(&webp_cache_file_path).ok()?;
                 let mut writer = BufWriter::new(cache_file);
                 let resized_img = image::DynamicImage::ImageRgba8(image::ImageBuffer::from_raw(dst_width, dst_height, dst_image.buffer().to_vec())?);
@@ -175,11 +451,11 @@ pub(crate) fn process_single_thumbnail(file_path: &str, cache_root: &Path) -> Op
                 // If no transparency was actually found, save as JPEG to save space
                 let cache_file = fs::File::create(&jpg_cache_file_path).ok()?;
                 let mut writer = BufWriter::new(cache_file);
-                let mut encoder = JpegEncoder::new_with_quality(&mut writer, 80);
-                
+                let mut encoder = JpegEncoder::new_with_quality(&mut writer, settings.jpeg_quality);
+
                 // Convert RGBA to RGB for JPEG
                 let rgb_buffer: Vec<u8> = pixels.chunks_exact(4).flat_map(|p| [p[0], p[1], p[2]]).collect();
-                encoder.encode(&rgb_buffer, dst_width, dst_height, image::ColorType::Rgb8.into()).ok()?;
+                encoder.encode(&rgb_buffer, dst_width, dst_height, image::ColorType::Rgb8).ok()?;
                 Some(jpg_cache_file_path.to_str().unwrap_or_default().to_string())
             }
         } else {
@@ -195,11 +471,19 @@ pub(crate) fn process_single_thumbnail(file_path: &str, cache_root: &Path) -> Op
             resizer.resize(&src_image.view(), &mut dst_image.view_mut()).ok()?;
 
             if !cache_root.exists() { let _ = fs::create_dir_all(cache_root); }
-            let cache_file = fs::File::create(&jpg_cache_file_path).ok()?;
-            let mut writer = BufWriter::new(cache_file);
-            let mut encoder = JpegEncoder::new_with_quality(&mut writer, 80);
-            encoder.encode(dst_image.buffer(), dst_width, dst_height, image::ColorType::Rgb8.into()).ok()?;
-            Some(jpg_cache_file_path.to_str().unwrap_or_default().to_string())
+            if settings.format == "webp" {
+                let cache_file = fs::File::create(&webp_cache_file_path).ok()?;
+                let mut writer = BufWriter::new(cache_file);
+                let resized_img = image::DynamicImage::ImageRgb8(image::ImageBuffer::from_raw(dst_width, dst_height, dst_image.buffer().to_vec())?);
+                resized_img.write_to(&mut writer, ImageFormat::WebP).ok()?;
+                Some(webp_cache_file_path.to_str().unwrap_or_default().to_string())
+            } else {
+                let cache_file = fs::File::create(&jpg_cache_file_path).ok()?;
+                let mut writer = BufWriter::new(cache_file);
+                let mut encoder = JpegEncoder::new_with_quality(&mut writer, settings.jpeg_quality);
+                encoder.encode(dst_image.buffer(), dst_width, dst_height, image::ColorType::Rgb8).ok()?;
+                Some(jpg_cache_file_path.to_str().unwrap_or_default().to_string())
+            }
         }
     })();
 
@@ -218,14 +502,69 @@ pub struct ThumbnailBatchResult {
     pub url: Option<String>,
     pub colors: Option<Vec<color_extractor::ColorResult>>,
     pub from_cache: bool,
+    /// 文件存在但解码失败（损坏图片），前端据此显示破损角标
+    pub decode_failed: bool,
+}
+
+/// 生成缩略图的模糊变体（敏感内容遮罩）。
+/// 先缩到极小再放大，等效强高斯模糊且开销极低；结果缓存为 _blur.jpg
+fn blurred_variant(thumb_path: &str) -> Option<String> {
+    let src = Path::new(thumb_path);
+    let stem = src.file_stem()?.to_str()?;
+    let blur_path = src.with_file_name(format!("{}_blur.jpg", stem));
+    if blur_path.exists() {
+        return Some(blur_path.to_str()?.to_string());
+    }
+    let img = image::open(src).ok()?;
+    let (w, h) = (img.width().max(1), img.height().max(1));
+    let blurred = img
+        .thumbnail(16, 16)
+        .resize_exact(w, h, image::imageops::FilterType::Triangle);
+    blurred.to_rgb8().save(&blur_path).ok()?;
+    Some(blur_path.to_str()?.to_string())
 }
 
 #[tauri::command]
-pub async fn get_thumbnail(file_path: String, cache_root: String) -> Result<Option<String>, String> {
+pub async fn get_thumbnail(
+    file_path: String,
+    cache_root: String,
+    smart_crop: Option<bool>,
+    reveal: Option<bool>,
+    pool: tauri::State<'_, crate::db::AppDbPool>,
+) -> Result<Option<String>, String> {
+    let pool = pool.inner().clone();
+    let smart_crop = smart_crop.unwrap_or(false);
+    let reveal = reveal.unwrap_or(false);
     let result = tauri::async_runtime::spawn_blocking(move || {
         let root = Path::new(&cache_root);
         if !root.exists() { let _ = fs::create_dir_all(root); }
-        process_single_thumbnail(&file_path, root)
+        let url = process_single_thumbnail_opts(&file_path, root, smart_crop);
+
+        // 文件存在但解码失败 → 记入损坏文件表；解码成功则清除旧记录（文件可能被修复）
+        if Path::new(&file_path).is_file() && !file_path.contains(".Aurora_Cache") {
+            let conn = pool.get_connection();
+            if url.is_none() {
+                let _ = crate::db::decode_errors::record_decode_error(&conn, &file_path, "缩略图解码失败");
+            } else {
+                let _ = crate::db::decode_errors::clear_decode_error(&conn, &file_path);
+            }
+        }
+
+        // 敏感内容默认返回模糊变体，调用方显式传 reveal=true 才给清晰缩略图
+        match url {
+            Some(url) if !reveal => {
+                let sensitive = {
+                    let conn = pool.get_connection();
+                    crate::db::file_metadata::is_sensitive(&conn, &file_path).unwrap_or(false)
+                };
+                if sensitive {
+                    Some(blurred_variant(&url).unwrap_or(url))
+                } else {
+                    Some(url)
+                }
+            }
+            other => other,
+        }
     }).await;
 
     match result { Ok(val) => Ok(val), Err(e) => Err(e.to_string()) }
@@ -236,51 +575,46 @@ pub async fn get_thumbnails_batch(
     file_paths: Vec<String>,
     cache_root: String,
     on_event: tauri::ipc::Channel<ThumbnailBatchResult>,
-    _app: tauri::AppHandle
+    app: tauri::AppHandle
 ) -> Result<(), String> {
     let file_paths_clone2 = file_paths;
+    let pool = app.state::<crate::db::AppDbPool>().inner().clone();
     let result = tauri::async_runtime::spawn_blocking(move || {
         let root = Path::new(&cache_root);
         if !root.exists() { let _ = fs::create_dir_all(root); }
 
+        let settings = current_settings();
         file_paths_clone2.par_iter().for_each(|path| {
-            use std::fs;
-            use std::io::Read;
-
             let image_path = Path::new(path);
             if !image_path.exists() || path.contains(".Aurora_Cache") {
-                let _ = on_event.send(ThumbnailBatchResult { path: path.clone(), url: None, colors: None, from_cache: false });
+                let _ = on_event.send(ThumbnailBatchResult { path: path.clone(), url: None, colors: None, from_cache: false, decode_failed: false });
                 return;
             }
 
-            let metadata = match fs::metadata(image_path) { Ok(m) => m, Err(_) => { let _ = on_event.send(ThumbnailBatchResult { path: path.clone(), url: None, colors: None, from_cache: false }); return; } };
-            let size = metadata.len();
-            let modified = metadata.modified().map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs()).unwrap_or(0);
-
-            let mut file = match fs::File::open(image_path) { Ok(f) => f, Err(_) => { let _ = on_event.send(ThumbnailBatchResult { path: path.clone(), url: None, colors: None, from_cache: false }); return; } };
-            let mut buffer = [0u8; 4096];
-            let bytes_read = file.read(&mut buffer).unwrap_or(0);
-
-            let cache_key = format!("{}-{}-{:?}", size, modified, &buffer[..bytes_read]);
-            let hash_str = format!("{:x}", md5::compute(cache_key.as_bytes()));
-            let cache_filename = if hash_str.len() >= 24 { hash_str[..24].to_string() } else { format!("{:0>24}", hash_str) };
-
-            let jpg_cache_file_path = root.join(format!("{}.jpg", cache_filename));
-            let webp_cache_file_path = root.join(format!("{}.webp", cache_filename));
+            let (jpg_cache_file_path, webp_cache_file_path) = match thumbnail_cache_paths(path, root, &settings) {
+                Some(paths) => paths,
+                None => { let _ = on_event.send(ThumbnailBatchResult { path: path.clone(), url: None, colors: None, from_cache: false, decode_failed: false }); return; }
+            };
 
             if jpg_cache_file_path.exists() {
                 let url = Some(jpg_cache_file_path.to_str().unwrap_or_default().to_string());
-                let _ = on_event.send(ThumbnailBatchResult { path: path.clone(), url, colors: None, from_cache: true });
+                let _ = on_event.send(ThumbnailBatchResult { path: path.clone(), url, colors: None, from_cache: true, decode_failed: false });
                 return;
             }
             if webp_cache_file_path.exists() {
                 let url = Some(webp_cache_file_path.to_str().unwrap_or_default().to_string());
-                let _ = on_event.send(ThumbnailBatchResult { path: path.clone(), url, colors: None, from_cache: true });
+                let _ = on_event.send(ThumbnailBatchResult { path: path.clone(), url, colors: None, from_cache: true, decode_failed: false });
                 return;
             }
 
             let url = process_single_thumbnail(path, root);
-            let _ = on_event.send(ThumbnailBatchResult { path: path.clone(), url, colors: None, from_cache: false });
+            // 文件存在却没有生成缩略图 → 解码失败，记入损坏文件表
+            let decode_failed = url.is_none();
+            if decode_failed {
+                let conn = pool.get_connection();
+                let _ = crate::db::decode_errors::record_decode_error(&conn, path, "缩略图解码失败");
+            }
+            let _ = on_event.send(ThumbnailBatchResult { path: path.clone(), url, colors: None, from_cache: false, decode_failed });
         });
         Ok(())
     }).await;
@@ -288,6 +622,170 @@ pub async fn get_thumbnails_batch(
     match result { Ok(val) => val, Err(e) => Err(e.to_string()) }
 }
 
+#[derive(Clone, Serialize)]
+struct RegenerateProgress {
+    processed: usize,
+    total: usize,
+}
+
+/// 预生成单飞标志
+static PREGEN_RUNNING: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// 缩略图预生成是否正在运行（供后台状态面板查询）
+pub fn is_pregenerating() -> bool {
+    PREGEN_RUNNING.load(std::sync::atomic::Ordering::SeqCst)
+}
+
+/// 预热缩略图缓存：为索引中还没有缓存的图片提前生成缩略图。
+/// `recursive` 为 false 时只处理文件夹的直接子项。
+/// 用半数 CPU 的独立线程池跑，避免影响前台浏览；
+/// 过程中发送 thumbnail-pregen-progress 事件，返回新生成的数量
+#[tauri::command]
+pub async fn pregenerate_thumbnails(
+    folder_id: String,
+    recursive: Option<bool>,
+    cache_root: String,
+    app: tauri::AppHandle,
+) -> Result<usize, String> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    if PREGEN_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("缩略图预生成已在运行".to_string());
+    }
+
+    let pool = app.state::<crate::db::AppDbPool>().inner().clone();
+    let recursive = recursive.unwrap_or(true);
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let root = Path::new(&cache_root);
+        if !root.exists() { let _ = fs::create_dir_all(root); }
+
+        // 从索引收集范围内的图片路径
+        let paths: Vec<String> = {
+            let conn = pool.get_connection();
+            let folder = crate::db::file_index::get_entry_by_id(&conn, &folder_id)
+                .map_err(|e| e.to_string())?
+                .ok_or("文件夹不在索引中")?;
+            let folder_path = folder.path.clone();
+            let entries = crate::db::file_index::get_entries_under_path(&conn, &folder.path)
+                .map_err(|e| e.to_string())?;
+            entries.into_iter()
+                .filter(|e| e.file_type == "Image")
+                .filter(|e| recursive || e.path.rfind('/').map(|i| &e.path[..i]) == Some(folder_path.as_str()))
+                .map(|e| e.path)
+                .collect()
+        };
+
+        let settings = current_settings();
+        let total = paths.len();
+        let done = AtomicUsize::new(0);
+        let generated = AtomicUsize::new(0);
+
+        // 半数 CPU 的独立线程池：预热是后台任务，给前台留出余量
+        // 低内存模式下进一步收紧到 1-2 个线程
+        let threads = if crate::is_low_memory() {
+            (num_cpus::get() / 4).clamp(1, 2)
+        } else {
+            (num_cpus::get() / 2).max(1)
+        };
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .start_handler(|_| crate::io_throttle::lower_io_priority())
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        pool.install(|| {
+            paths.par_iter().for_each(|path| {
+                // 应用正在退出：剩余文件下次启动再补
+                if crate::shutdown::is_requested() {
+                    return;
+                }
+                // 已有缓存的跳过，只补缺
+                let cached = thumbnail_cache_paths(path, root, &settings)
+                    .map(|(jpg, webp)| jpg.exists() || webp.exists())
+                    .unwrap_or(false);
+                if !cached {
+                    // 整张解码，按原文件大小扣限速配额
+                    let size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+                    crate::io_throttle::throttle(size);
+                }
+                if !cached && process_single_thumbnail(path, root).is_some() {
+                    generated.fetch_add(1, Ordering::SeqCst);
+                }
+
+                let processed = done.fetch_add(1, Ordering::SeqCst) + 1;
+                if processed.is_multiple_of(50) || processed == total {
+                    let _ = app.emit("thumbnail-pregen-progress", RegenerateProgress { processed, total });
+                }
+            });
+        });
+
+        Ok(generated.load(Ordering::SeqCst))
+    }).await;
+
+    PREGEN_RUNNING.store(false, std::sync::atomic::Ordering::SeqCst);
+    match result { Ok(val) => val, Err(e) => Err(e.to_string()) }
+}
+
+/// 按当前设置重新生成缩略图缓存。
+/// `scope` 为目录路径时只处理该目录下的图片，为 None 时处理整个索引。
+/// 过程中发送 thumbnail-regenerate-progress 事件，返回处理的文件数
+#[tauri::command]
+pub async fn regenerate_thumbnails(
+    scope: Option<String>,
+    cache_root: String,
+    app: tauri::AppHandle,
+) -> Result<usize, String> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let pool = app.state::<crate::db::AppDbPool>().inner().clone();
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let root = Path::new(&cache_root);
+        if !root.exists() { let _ = fs::create_dir_all(root); }
+
+        // 从索引收集范围内的图片路径
+        let paths: Vec<String> = {
+            let conn = pool.get_connection();
+            let entries = match &scope {
+                Some(dir) => crate::db::file_index::get_entries_under_path(&conn, dir),
+                None => crate::db::file_index::get_all_image_files(&conn),
+            }.map_err(|e| e.to_string())?;
+            entries.into_iter()
+                .filter(|e| e.file_type == "Image")
+                .map(|e| e.path)
+                .collect()
+        };
+
+        let settings = current_settings();
+        let total = paths.len();
+        let done = AtomicUsize::new(0);
+
+        paths.par_iter().for_each(|path| {
+            let size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+            crate::io_throttle::throttle(size);
+            // 删除当前设置下的旧缓存后重新生成（设置变化时旧键文件自然失效）
+            if let Some((jpg, webp)) = thumbnail_cache_paths(path, root, &settings) {
+                let _ = fs::remove_file(jpg);
+                let _ = fs::remove_file(webp);
+            }
+            let _ = process_single_thumbnail(path, root);
+
+            let processed = done.fetch_add(1, Ordering::SeqCst) + 1;
+            if processed.is_multiple_of(50) || processed == total {
+                let _ = app.emit("thumbnail-regenerate-progress", RegenerateProgress { processed, total });
+            }
+        });
+
+        Ok(total)
+    }).await;
+
+    match result { Ok(val) => val, Err(e) => Err(e.to_string()) }
+}
+
 #[tauri::command]
 pub async fn save_remote_thumbnail(
     file_path: String,
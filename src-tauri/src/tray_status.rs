@@ -0,0 +1,143 @@
+//! 托盘状态指示：后台索引 / 向量生成跑起来时，在托盘图标上画一条
+//! 底部进度条；有未读的"新文件已入库"时画一个右上角红点。
+//! 任务管理方（前端或后台任务）通过 [`set_tray_status`] 推送状态，
+//! 悬停提示同步更新成人能读的文字。
+//!
+//! 图标是在默认窗口图标的 RGBA 上现画的，不需要额外资源文件。
+
+use serde::Deserialize;
+use tauri::Manager;
+
+/// 托盘状态。state："idle"（还原默认图标）| "busy"（显示进度条）。
+/// unread 为未读的新入库文件数，大于 0 时画红点
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TrayStatus {
+    pub state: String,
+    /// 0 - 100，busy 时底部进度条的长度
+    #[serde(default)]
+    pub progress: Option<u8>,
+    #[serde(default)]
+    pub unread: Option<u32>,
+    /// 悬停提示里显示的任务名（如 "索引中" / "生成向量"）
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// 进度条颜色（主题蓝）与未读红点颜色
+const BAR_COLOR: [u8; 4] = [0x3B, 0x82, 0xF6, 0xFF];
+const DOT_COLOR: [u8; 4] = [0xEF, 0x44, 0x44, 0xFF];
+
+/// 在图标 RGBA 上叠加进度条与未读红点（原地修改）
+fn compose_overlay(rgba: &mut [u8], width: u32, height: u32, progress: Option<u8>, unread: bool) {
+    let set_px = |rgba: &mut [u8], x: u32, y: u32, color: [u8; 4]| {
+        let i = ((y * width + x) * 4) as usize;
+        if i + 3 < rgba.len() {
+            rgba[i..i + 4].copy_from_slice(&color);
+        }
+    };
+
+    if let Some(progress) = progress {
+        // 底部 1/6 高度的进度条，背景半透明暗色，前景主题蓝
+        let bar_h = (height / 6).max(2);
+        let filled = width * progress.min(100) as u32 / 100;
+        for y in height.saturating_sub(bar_h)..height {
+            for x in 0..width {
+                let color = if x < filled {
+                    BAR_COLOR
+                } else {
+                    [0x33, 0x33, 0x33, 0xC0]
+                };
+                set_px(rgba, x, y, color);
+            }
+        }
+    }
+
+    if unread {
+        // 右上角红点，直径约 1/3 边长
+        let r = (width.min(height) / 6).max(2) as i32;
+        let (cx, cy) = (width as i32 - r - 1, r + 1);
+        for dy in -r..=r {
+            for dx in -r..=r {
+                if dx * dx + dy * dy <= r * r {
+                    let (x, y) = (cx + dx, cy + dy);
+                    if x >= 0 && y >= 0 && (x as u32) < width && (y as u32) < height {
+                        set_px(rgba, x as u32, y as u32, DOT_COLOR);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 更新托盘图标与悬停提示。
+/// state 为 "idle" 时还原默认图标（unread 红点仍然生效）
+#[tauri::command]
+pub fn set_tray_status(status: TrayStatus, app: tauri::AppHandle) -> Result<(), String> {
+    let tray = app.state::<Option<tauri::tray::TrayIcon>>();
+    let Some(tray) = tray.inner() else {
+        return Err("托盘图标不可用".to_string());
+    };
+    let base = app
+        .default_window_icon()
+        .ok_or("没有默认窗口图标")?
+        .clone();
+
+    let unread = status.unread.unwrap_or(0);
+    let busy = status.state == "busy";
+
+    // 悬停提示：应用名 + 任务与进度 + 未读数
+    let mut tooltip = "Aurora Gallery".to_string();
+    if busy {
+        let label = status.label.as_deref().unwrap_or("后台任务");
+        match status.progress {
+            Some(p) => tooltip.push_str(&format!(" — {} {}%", label, p.min(100))),
+            None => tooltip.push_str(&format!(" — {}", label)),
+        }
+    }
+    if unread > 0 {
+        tooltip.push_str(&format!("（{} 个新文件）", unread));
+    }
+    tray.set_tooltip(Some(&tooltip)).map_err(|e| e.to_string())?;
+
+    if !busy && unread == 0 {
+        tray.set_icon(Some(base)).map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+
+    let (width, height) = (base.width(), base.height());
+    let mut rgba = base.rgba().to_vec();
+    compose_overlay(
+        &mut rgba,
+        width,
+        height,
+        if busy { Some(status.progress.unwrap_or(0)) } else { None },
+        unread > 0,
+    );
+    tray.set_icon(Some(tauri::image::Image::new_owned(rgba, width, height)))
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compose_overlay_draws_bar_and_dot() {
+        let (w, h) = (32u32, 32u32);
+        let mut rgba = vec![0u8; (w * h * 4) as usize];
+        compose_overlay(&mut rgba, w, h, Some(50), true);
+
+        // 进度条左半段是主题蓝，右半段是暗色背景
+        let last_row = ((h - 1) * w * 4) as usize;
+        assert_eq!(&rgba[last_row..last_row + 4], &BAR_COLOR);
+        let right = last_row + ((w - 1) * 4) as usize;
+        assert_eq!(rgba[right + 3], 0xC0);
+
+        // 右上角红点
+        let r = (w / 6) as usize;
+        let dot = (((r + 1) * w as usize) + (w as usize - r - 1)) * 4;
+        assert_eq!(&rgba[dot..dot + 4], &DOT_COLOR);
+    }
+}
@@ -0,0 +1,120 @@
+//! 操作日志：记录可逆操作（重命名、移动、标签/评分修改、删除到回收目录），
+//! 支持撤销/重做。日志只保存在内存中，应用重启后清空。
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+/// 历史记录上限，超出后丢弃最旧的记录
+const MAX_HISTORY: usize = 100;
+
+/// 一条可逆操作的完整信息（正向和反向执行所需的数据都在其中）
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Operation {
+    #[serde(rename_all = "camelCase")]
+    Rename {
+        old_path: String,
+        new_path: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Move {
+        src_path: String,
+        dest_path: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    TagChange {
+        file_id: String,
+        old_tags: Option<serde_json::Value>,
+        new_tags: Option<serde_json::Value>,
+    },
+    #[serde(rename_all = "camelCase")]
+    RatingChange {
+        file_id: String,
+        old_rating: Option<i64>,
+        new_rating: Option<i64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    DeleteToTrash {
+        original_path: String,
+        trash_path: String,
+    },
+}
+
+struct Journal {
+    undo_stack: VecDeque<Operation>,
+    redo_stack: VecDeque<Operation>,
+}
+
+static JOURNAL: Lazy<Mutex<Journal>> = Lazy::new(|| {
+    Mutex::new(Journal {
+        undo_stack: VecDeque::new(),
+        redo_stack: VecDeque::new(),
+    })
+});
+
+/// 撤销/重做执行期间置位，避免反向操作又被记录一遍
+static SUPPRESS_RECORDING: AtomicBool = AtomicBool::new(false);
+
+pub fn set_suppressed(suppressed: bool) {
+    SUPPRESS_RECORDING.store(suppressed, Ordering::SeqCst);
+}
+
+fn is_suppressed() -> bool {
+    SUPPRESS_RECORDING.load(Ordering::SeqCst)
+}
+
+/// 记录一条新操作。新操作会清空重做栈（与常规编辑器行为一致）
+pub fn record(op: Operation) {
+    if is_suppressed() {
+        return;
+    }
+    let mut journal = JOURNAL.lock().unwrap();
+    journal.redo_stack.clear();
+    journal.undo_stack.push_back(op);
+    while journal.undo_stack.len() > MAX_HISTORY {
+        journal.undo_stack.pop_front();
+    }
+}
+
+/// 取出最近一条可撤销的操作（调用方执行反向操作后应调用 push_redo）
+pub fn take_undo() -> Option<Operation> {
+    JOURNAL.lock().unwrap().undo_stack.pop_back()
+}
+
+/// 取出最近一条可重做的操作（调用方执行正向操作后应调用 push_undo）
+pub fn take_redo() -> Option<Operation> {
+    JOURNAL.lock().unwrap().redo_stack.pop_back()
+}
+
+/// 撤销完成后，把操作放入重做栈
+pub fn push_redo(op: Operation) {
+    JOURNAL.lock().unwrap().redo_stack.push_back(op);
+}
+
+/// 重做完成后，把操作放回撤销栈（不清空重做栈）
+pub fn push_undo(op: Operation) {
+    let mut journal = JOURNAL.lock().unwrap();
+    journal.undo_stack.push_back(op);
+    while journal.undo_stack.len() > MAX_HISTORY {
+        journal.undo_stack.pop_front();
+    }
+}
+
+/// 撤销失败时把操作放回撤销栈，保持日志一致
+pub fn restore_undo(op: Operation) {
+    JOURNAL.lock().unwrap().undo_stack.push_back(op);
+}
+
+/// 重做失败时把操作放回重做栈
+pub fn restore_redo(op: Operation) {
+    JOURNAL.lock().unwrap().redo_stack.push_back(op);
+}
+
+/// 当前 (可撤销, 可重做) 的数量，供前端置灰按钮
+pub fn history_counts() -> (usize, usize) {
+    let journal = JOURNAL.lock().unwrap();
+    (journal.undo_stack.len(), journal.redo_stack.len())
+}
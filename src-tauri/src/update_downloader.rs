@@ -400,6 +400,7 @@ impl UpdateDownloader {
     }
 
     /// 下载任务
+    #[allow(clippy::too_many_arguments)]
     async fn download_task(
         url: &str,
         file_path: &PathBuf,
@@ -0,0 +1,172 @@
+//! 文件版本历史：文件被覆盖（合并文件夹的 overwrite 策略、元数据
+//! 写回改动原图）前，把旧内容搬进同目录的 .aurora_versions/ 托管
+//! 存储，可列出与恢复。点号开头的目录天然被扫描跳过，不会混进索引。
+//!
+//! 存储按文件限额：最多保留 [`MAX_VERSIONS_PER_FILE`] 个版本、
+//! 总量不超过 [`MAX_BYTES_PER_FILE`]，超限时从最旧的开始删。
+//! 恢复前会先把当前内容存成新版本，恢复操作本身也可以反悔。
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::db::{self, AppDbPool};
+
+const VERSIONS_DIR: &str = ".aurora_versions";
+/// 每个文件最多保留的版本数
+const MAX_VERSIONS_PER_FILE: usize = 10;
+/// 每个文件的版本总量上限
+const MAX_BYTES_PER_FILE: u64 = 128 * 1024 * 1024;
+/// 超过这个大小的文件不做版本快照（避免占满磁盘）
+const MAX_SNAPSHOT_BYTES: u64 = 512 * 1024 * 1024;
+
+/// 某个文件的版本目录：{文件所在目录}/.aurora_versions/{file_id}/
+fn versions_dir_for(path: &str) -> Option<PathBuf> {
+    let file_id = db::generate_id(&db::normalize_path(path));
+    Path::new(path).parent().map(|p| p.join(VERSIONS_DIR).join(file_id))
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionInfo {
+    /// 版本标识（存储文件名，restore_version 时原样传回）
+    pub version: String,
+    pub size: u64,
+    /// 快照时间（Unix 秒）
+    pub created_at: i64,
+}
+
+/// 列出某目录下的版本，按时间倒序
+fn read_versions(dir: &Path) -> Vec<VersionInfo> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut versions: Vec<VersionInfo> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let meta = e.metadata().ok()?;
+            if !meta.is_file() {
+                return None;
+            }
+            // 文件名形如 "{unix_ts}_{原名}"
+            let created_at: i64 = name.split('_').next()?.parse().ok()?;
+            Some(VersionInfo { version: name, size: meta.len(), created_at })
+        })
+        .collect();
+    versions.sort_by_key(|v| std::cmp::Reverse(v.created_at));
+    versions
+}
+
+/// 把超限的旧版本删掉
+fn trim_versions(dir: &Path) {
+    let versions = read_versions(dir);
+    let mut total: u64 = versions.iter().map(|v| v.size).sum();
+    for (i, v) in versions.iter().enumerate() {
+        if (i >= MAX_VERSIONS_PER_FILE || (i > 0 && total > MAX_BYTES_PER_FILE))
+            && std::fs::remove_file(dir.join(&v.version)).is_ok()
+        {
+            total = total.saturating_sub(v.size);
+        }
+    }
+}
+
+/// 在文件即将被覆盖前快照当前内容。文件不存在 / 过大时静默跳过，
+/// 快照失败只记日志不阻断调用方的覆盖流程
+pub fn snapshot_before_overwrite(path: &str) {
+    let src = Path::new(path);
+    let Ok(meta) = src.metadata() else { return };
+    if !meta.is_file() || meta.len() > MAX_SNAPSHOT_BYTES {
+        return;
+    }
+    let Some(dir) = versions_dir_for(path) else { return };
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        log::warn!("创建版本目录失败 {}: {}", dir.display(), e);
+        return;
+    }
+    let name = src.file_name().and_then(|n| n.to_str()).unwrap_or("file");
+    let dest = dir.join(format!("{}_{}", chrono::Utc::now().timestamp(), name));
+    match std::fs::copy(src, &dest) {
+        Ok(_) => trim_versions(&dir),
+        Err(e) => log::warn!("版本快照失败 {}: {}", path, e),
+    }
+}
+
+/// 列出某文件的历史版本（新的在前）
+#[tauri::command]
+pub fn list_versions(file_id: String, pool: tauri::State<AppDbPool>) -> Result<Vec<VersionInfo>, String> {
+    let conn = pool.get_connection();
+    let entry = db::file_index::get_entry_by_id(&conn, &file_id)
+        .map_err(|e| e.to_string())?
+        .ok_or("文件不在索引中")?;
+    Ok(versions_dir_for(&entry.path).map(|d| read_versions(&d)).unwrap_or_default())
+}
+
+/// 恢复到指定版本。当前内容先存成新版本再覆盖，恢复本身可反悔
+#[tauri::command]
+pub async fn restore_version(
+    file_id: String,
+    version: String,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    // 版本标识就是存储文件名，拒绝任何带路径分隔符的输入
+    if version.contains('/') || version.contains('\\') || version.contains("..") {
+        return Err("无效的版本标识".to_string());
+    }
+    let pool = app.state::<AppDbPool>().inner().clone();
+    let path = tokio::task::spawn_blocking(move || -> Result<String, String> {
+        let conn = pool.get_connection();
+        let entry = db::file_index::get_entry_by_id(&conn, &file_id)
+            .map_err(|e| e.to_string())?
+            .ok_or("文件不在索引中")?;
+        let dir = versions_dir_for(&entry.path).ok_or("无法定位版本目录")?;
+        let stored = dir.join(&version);
+        if !stored.is_file() {
+            return Err(format!("版本不存在: {}", version));
+        }
+        snapshot_before_overwrite(&entry.path);
+        std::fs::copy(&stored, &entry.path).map_err(|e| format!("恢复版本失败: {}", e))?;
+        let _ = db::activity_log::record(
+            &conn,
+            "restore",
+            &entry.path,
+            Some(&format!("version:{}", version)),
+            "versions",
+        );
+        Ok(entry.path)
+    })
+    .await
+    .map_err(|e| format!("恢复任务失败: {}", e))??;
+
+    crate::invalidate_file_caches(&app, &path).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_list_and_trim() {
+        let base = std::env::temp_dir().join("aurora_versions_test");
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(&base).unwrap();
+        let file = base.join("photo.png");
+
+        // 连拍多个快照（超过保留上限）
+        for i in 0..(MAX_VERSIONS_PER_FILE + 3) {
+            std::fs::write(&file, format!("content-{}", i)).unwrap();
+            snapshot_before_overwrite(&file.to_string_lossy());
+        }
+        let dir = versions_dir_for(&file.to_string_lossy()).unwrap();
+        let versions = read_versions(&dir);
+        assert!(!versions.is_empty());
+        assert!(versions.len() <= MAX_VERSIONS_PER_FILE);
+        // 按时间倒序
+        for w in versions.windows(2) {
+            assert!(w[0].created_at >= w[1].created_at);
+        }
+        let _ = std::fs::remove_dir_all(&base);
+    }
+}
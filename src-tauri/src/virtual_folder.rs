@@ -0,0 +1,166 @@
+//! 搜索结果虚拟文件夹：把一次搜索（CLIP / 颜色 / 查询 DSL）的命中列表
+//! 物化成一个临时文件夹 id，让分页、缩略图预取、批量操作这些吃文件夹
+//! id 的下游功能统一作用在搜索结果上。
+//!
+//! 只存内存里的 file_id 列表（重启即失效，不落库）；id 以 "vf-" 开头，
+//! 与路径哈希出来的真实 id 区分，消费方用 [`is_virtual_id`] 分流。
+//! 分页读取时按 id 去索引反查，已经被删除 / 移走的条目自动跳过。
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+use crate::db::{self, AppDbPool};
+use crate::{FileNode, FileType, ImageMeta};
+
+/// 虚拟文件夹 id 的前缀（真实 id 是路径 MD5，不会以它开头）
+pub const VIRTUAL_ID_PREFIX: &str = "vf-";
+
+struct VirtualFolder {
+    name: String,
+    file_ids: Vec<String>,
+    created_at: i64,
+}
+
+static FOLDERS: Lazy<Mutex<HashMap<String, VirtualFolder>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_SEQ: AtomicU64 = AtomicU64::new(1);
+
+pub fn is_virtual_id(id: &str) -> bool {
+    id.starts_with(VIRTUAL_ID_PREFIX)
+}
+
+/// 取某个虚拟文件夹当前的 file_id 列表（不存在时为 None）
+pub fn resolve_ids(id: &str) -> Option<Vec<String>> {
+    FOLDERS.lock().unwrap().get(id).map(|f| f.file_ids.clone())
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VirtualFolderInfo {
+    pub id: String,
+    pub name: String,
+    pub count: usize,
+    pub created_at: i64,
+}
+
+/// 把一组搜索命中物化成虚拟文件夹，返回其 id（去重、保持传入顺序）
+#[tauri::command]
+pub fn create_virtual_folder(name: String, file_ids: Vec<String>) -> String {
+    let mut seen = HashSet::new();
+    let file_ids: Vec<String> = file_ids
+        .into_iter()
+        .filter(|id| seen.insert(id.clone()))
+        .collect();
+    let id = format!("{}{}", VIRTUAL_ID_PREFIX, NEXT_SEQ.fetch_add(1, Ordering::SeqCst));
+    FOLDERS.lock().unwrap().insert(
+        id.clone(),
+        VirtualFolder {
+            name,
+            file_ids,
+            created_at: chrono::Utc::now().timestamp(),
+        },
+    );
+    id
+}
+
+/// 列出当前会话里的所有虚拟文件夹
+#[tauri::command]
+pub fn list_virtual_folders() -> Vec<VirtualFolderInfo> {
+    let folders = FOLDERS.lock().unwrap();
+    let mut infos: Vec<VirtualFolderInfo> = folders
+        .iter()
+        .map(|(id, f)| VirtualFolderInfo {
+            id: id.clone(),
+            name: f.name.clone(),
+            count: f.file_ids.len(),
+            created_at: f.created_at,
+        })
+        .collect();
+    infos.sort_by_key(|i| i.created_at);
+    infos
+}
+
+/// 虚拟文件夹的原始 file_id 列表，批量操作（工作台 / 导出 / 打包等）
+/// 直接拿它展开后走各自的批量接口
+#[tauri::command]
+pub fn get_virtual_folder_ids(folder_id: String) -> Result<Vec<String>, String> {
+    resolve_ids(&folder_id).ok_or_else(|| "虚拟文件夹不存在或已失效".to_string())
+}
+
+/// 分页读取虚拟文件夹的内容，返回与 scan_directory 相同形状的节点，
+/// 缩略图预取等下游逻辑无需区分真实/虚拟来源
+#[tauri::command]
+pub fn get_virtual_folder_page(
+    folder_id: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    pool: tauri::State<AppDbPool>,
+) -> Result<Vec<FileNode>, String> {
+    let ids = resolve_ids(&folder_id).ok_or("虚拟文件夹不存在或已失效")?;
+    let offset = offset.unwrap_or(0).min(ids.len());
+    let end = limit.map_or(ids.len(), |l| (offset + l).min(ids.len()));
+
+    let conn = pool.get_connection();
+    let mut nodes = Vec::with_capacity(end - offset);
+    for id in &ids[offset..end] {
+        let Some(entry) = db::file_index::get_entry_by_id(&conn, id).map_err(|e| e.to_string())?
+        else {
+            // 条目已被删除或移走，静默跳过
+            continue;
+        };
+        let meta = match (entry.width, entry.height) {
+            (Some(w), Some(h)) => Some(ImageMeta {
+                width: w,
+                height: h,
+                size_kb: (entry.size / 1024) as u32,
+                created: chrono::DateTime::from_timestamp(entry.created_at, 0)
+                    .map(|dt| dt.to_rfc3339())
+                    .unwrap_or_default(),
+                modified: chrono::DateTime::from_timestamp(entry.modified_at, 0)
+                    .map(|dt| dt.to_rfc3339())
+                    .unwrap_or_default(),
+                format: entry.format.clone().unwrap_or_default(),
+                bit_depth: entry.bit_depth,
+                color_space: entry.color_space.clone(),
+                has_alpha: entry.has_alpha,
+                is_animated: entry.is_animated,
+            }),
+            _ => None,
+        };
+        nodes.push(FileNode {
+            id: entry.file_id.clone(),
+            parent_id: entry.parent_id.clone(),
+            name: entry.name.clone(),
+            r#type: match entry.file_type.as_str() {
+                "Image" => FileType::Image,
+                "Folder" => FileType::Folder,
+                "Model" => FileType::Model,
+                _ => FileType::Unknown,
+            },
+            path: entry.path.clone(),
+            size: Some(entry.size),
+            children: if entry.file_type == "Folder" { Some(Vec::new()) } else { None },
+            tags: Vec::new(),
+            created_at: chrono::DateTime::from_timestamp(entry.created_at, 0).map(|dt| dt.to_rfc3339()),
+            updated_at: chrono::DateTime::from_timestamp(entry.modified_at, 0).map(|dt| dt.to_rfc3339()),
+            url: None,
+            meta,
+            description: None,
+            source_url: None,
+            category: None,
+            ai_data: None,
+            availability: None,
+        });
+    }
+    Ok(nodes)
+}
+
+/// 删除一个虚拟文件夹（只移除列表，不动文件）
+#[tauri::command]
+pub fn delete_virtual_folder(folder_id: String) -> bool {
+    FOLDERS.lock().unwrap().remove(&folder_id).is_some()
+}
@@ -0,0 +1,313 @@
+//! 卷类型检测：判断路径所在的盘是 SSD 还是机械硬盘（HDD），扫描并行度
+//! 据此取值。早期版本靠小文件读取延迟猜测，SSD 一忙就误判成 HDD；
+//! 这里改为直接问操作系统：
+//!
+//! - Windows：`IOCTL_STORAGE_QUERY_PROPERTY` 查寻道惩罚（seek penalty），
+//!   拿不到再查 TRIM 支持兜底；
+//! - Linux：`/sys/block/<设备>/queue/rotational` 旋转标志；
+//! - macOS：`diskutil info` 的 Solid State 字段（IOKit 的命令行出口）。
+//!
+//! 结果按卷缓存（设备类型不会变，进程内缓存不过期），并通过
+//! [`get_volume_info`] 暴露给前端设置页展示。
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+/// 一个卷的检测结果
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VolumeInfo {
+    /// 卷标识（Windows 盘符如 "C:"，Unix 为挂载点）
+    pub volume: String,
+    /// "ssd" | "hdd" | "unknown"
+    pub kind: String,
+    /// 设备是否旋转介质（查不到为 None）
+    pub rotational: Option<bool>,
+    /// 检测依据："seek-penalty" | "trim" | "rotational-flag" | "diskutil" | "unknown"
+    pub source: String,
+}
+
+/// 按卷缓存检测结果（设备类型不随时间变化，无需过期）
+static VOLUME_CACHE: Lazy<Mutex<HashMap<String, VolumeInfo>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn unknown(volume: &str) -> VolumeInfo {
+    VolumeInfo {
+        volume: volume.to_string(),
+        kind: "unknown".to_string(),
+        rotational: None,
+        source: "unknown".to_string(),
+    }
+}
+
+fn from_rotational(volume: &str, rotational: bool, source: &str) -> VolumeInfo {
+    VolumeInfo {
+        volume: volume.to_string(),
+        kind: if rotational { "hdd" } else { "ssd" }.to_string(),
+        rotational: Some(rotational),
+        source: source.to_string(),
+    }
+}
+
+// ---------- Windows：seek penalty / TRIM ----------
+
+/// 路径所属的盘符（"C:"）。UNC / 相对路径返回 None
+#[cfg(windows)]
+fn volume_key(path: &str) -> Option<String> {
+    let bytes = path.as_bytes();
+    if bytes.len() >= 2 && bytes[1] == b':' && bytes[0].is_ascii_alphabetic() {
+        Some(format!("{}:", (bytes[0] as char).to_ascii_uppercase()))
+    } else {
+        None
+    }
+}
+
+#[cfg(windows)]
+fn detect_volume(volume: &str) -> VolumeInfo {
+    use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::Storage::FileSystem::{
+        CreateFileW, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
+    };
+    use windows_sys::Win32::System::Ioctl::{
+        DEVICE_SEEK_PENALTY_DESCRIPTOR, DEVICE_TRIM_DESCRIPTOR, IOCTL_STORAGE_QUERY_PROPERTY,
+        PropertyStandardQuery, STORAGE_PROPERTY_QUERY, StorageDeviceSeekPenaltyProperty,
+        StorageDeviceTrimProperty,
+    };
+    use windows_sys::Win32::System::IO::DeviceIoControl;
+
+    // 打开 \\.\C: 设备句柄（0 访问权限即可发存储查询 IOCTL）
+    let device: Vec<u16> = format!("\\\\.\\{}", volume)
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    let handle = unsafe {
+        CreateFileW(
+            device.as_ptr(),
+            0,
+            FILE_SHARE_READ | FILE_SHARE_WRITE,
+            std::ptr::null(),
+            OPEN_EXISTING,
+            0,
+            std::ptr::null_mut(),
+        )
+    };
+    if handle == INVALID_HANDLE_VALUE {
+        return unknown(volume);
+    }
+
+    let query_property = |property_id: i32, out: *mut std::ffi::c_void, out_len: u32| -> bool {
+        let query = STORAGE_PROPERTY_QUERY {
+            PropertyId: property_id,
+            QueryType: PropertyStandardQuery,
+            AdditionalParameters: [0],
+        };
+        let mut returned = 0u32;
+        unsafe {
+            DeviceIoControl(
+                handle,
+                IOCTL_STORAGE_QUERY_PROPERTY,
+                &query as *const _ as *const std::ffi::c_void,
+                std::mem::size_of::<STORAGE_PROPERTY_QUERY>() as u32,
+                out,
+                out_len,
+                &mut returned,
+                std::ptr::null_mut(),
+            ) != 0
+        }
+    };
+
+    // 首选寻道惩罚：有惩罚 = 机械盘
+    let mut seek: DEVICE_SEEK_PENALTY_DESCRIPTOR = unsafe { std::mem::zeroed() };
+    if query_property(
+        StorageDeviceSeekPenaltyProperty,
+        &mut seek as *mut _ as *mut std::ffi::c_void,
+        std::mem::size_of::<DEVICE_SEEK_PENALTY_DESCRIPTOR>() as u32,
+    ) {
+        unsafe { CloseHandle(handle) };
+        return from_rotational(volume, seek.IncursSeekPenalty != 0, "seek-penalty");
+    }
+
+    // 兜底 TRIM：支持 TRIM 的基本都是 SSD
+    let mut trim: DEVICE_TRIM_DESCRIPTOR = unsafe { std::mem::zeroed() };
+    if query_property(
+        StorageDeviceTrimProperty,
+        &mut trim as *mut _ as *mut std::ffi::c_void,
+        std::mem::size_of::<DEVICE_TRIM_DESCRIPTOR>() as u32,
+    ) {
+        unsafe { CloseHandle(handle) };
+        return from_rotational(volume, trim.TrimEnabled == 0, "trim");
+    }
+
+    unsafe { CloseHandle(handle) };
+    unknown(volume)
+}
+
+// ---------- Linux：rotational 标志 ----------
+
+/// 路径所在挂载点（/proc/mounts 里最长前缀匹配），连同设备路径
+#[cfg(target_os = "linux")]
+fn mount_of(path: &str) -> Option<(String, String)> {
+    let canonical = std::fs::canonicalize(path).ok()?;
+    let canonical = canonical.to_str()?;
+    let mounts = std::fs::read_to_string("/proc/mounts").ok()?;
+    let mut best: Option<(String, String)> = None;
+    for line in mounts.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(device), Some(mount_point)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        let matched = canonical == mount_point
+            || mount_point == "/"
+            || canonical.starts_with(&format!("{}/", mount_point));
+        if matched
+            && best
+                .as_ref()
+                .is_none_or(|(mp, _)| mount_point.len() > mp.len())
+        {
+            best = Some((mount_point.to_string(), device.to_string()));
+        }
+    }
+    best
+}
+
+#[cfg(target_os = "linux")]
+fn volume_key(path: &str) -> Option<String> {
+    mount_of(path).map(|(mount_point, _)| mount_point)
+}
+
+/// 分区设备名 → 所属块设备名（sda2 → sda，nvme0n1p1 → nvme0n1）
+#[cfg(target_os = "linux")]
+fn base_block_device(device: &str) -> Option<String> {
+    let name = device.strip_prefix("/dev/")?;
+    if name.contains('/') {
+        // /dev/mapper/... 等逻辑卷，对不上 /sys/block，放弃
+        return None;
+    }
+    let base = if name.starts_with("nvme") || name.starts_with("mmcblk") {
+        match name.rfind('p') {
+            Some(i) if name[i + 1..].chars().all(|c| c.is_ascii_digit()) && i + 1 < name.len() => {
+                &name[..i]
+            }
+            _ => name,
+        }
+    } else {
+        name.trim_end_matches(|c: char| c.is_ascii_digit())
+    };
+    if base.is_empty() {
+        None
+    } else {
+        Some(base.to_string())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn detect_volume(volume: &str) -> VolumeInfo {
+    let Some((_, device)) = mount_of(volume) else {
+        return unknown(volume);
+    };
+    let Some(base) = base_block_device(&device) else {
+        return unknown(volume);
+    };
+    let flag_path = format!("/sys/block/{}/queue/rotational", base);
+    match std::fs::read_to_string(&flag_path) {
+        Ok(content) => from_rotational(volume, content.trim() == "1", "rotational-flag"),
+        Err(_) => unknown(volume),
+    }
+}
+
+// ---------- macOS：diskutil（IOKit 的命令行出口） ----------
+
+#[cfg(target_os = "macos")]
+fn volume_key(path: &str) -> Option<String> {
+    // df -P 的最后一列是挂载点
+    let output = std::process::Command::new("df")
+        .args(["-P", path])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let line = text.lines().nth(1)?;
+    let mount = line.split_whitespace().last()?;
+    Some(mount.to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn detect_volume(volume: &str) -> VolumeInfo {
+    let Ok(output) = std::process::Command::new("diskutil")
+        .args(["info", volume])
+        .output()
+    else {
+        return unknown(volume);
+    };
+    let text = String::from_utf8_lossy(&output.stdout);
+    for line in text.lines() {
+        if let Some((key, value)) = line.split_once(':') {
+            if key.trim() == "Solid State" {
+                return from_rotational(volume, value.trim() != "Yes", "diskutil");
+            }
+        }
+    }
+    unknown(volume)
+}
+
+// ---------- 其他平台 ----------
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn volume_key(_path: &str) -> Option<String> {
+    None
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn detect_volume(volume: &str) -> VolumeInfo {
+    unknown(volume)
+}
+
+/// 查询路径所在卷的信息（带进程内缓存）
+pub fn volume_info(path: &str) -> VolumeInfo {
+    let Some(volume) = volume_key(path) else {
+        return unknown(path);
+    };
+    if let Some(cached) = VOLUME_CACHE.lock().unwrap().get(&volume) {
+        return cached.clone();
+    }
+    let info = detect_volume(&volume);
+    VOLUME_CACHE
+        .lock()
+        .unwrap()
+        .insert(volume, info.clone());
+    info
+}
+
+/// 路径是否位于机械硬盘上。查不出来按 SSD 处理（维持并行扫描），
+/// 不再因为盘忙而误降速
+pub fn is_hdd(path: &str) -> bool {
+    volume_info(path).kind == "hdd"
+}
+
+/// 查询路径所在卷的类型与检测依据（设置页 / 调试面板展示用）
+#[tauri::command]
+pub fn get_volume_info(path: String) -> VolumeInfo {
+    volume_info(&path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_base_block_device() {
+        assert_eq!(base_block_device("/dev/sda2").as_deref(), Some("sda"));
+        assert_eq!(
+            base_block_device("/dev/nvme0n1p1").as_deref(),
+            Some("nvme0n1")
+        );
+        assert_eq!(
+            base_block_device("/dev/mmcblk0p3").as_deref(),
+            Some("mmcblk0")
+        );
+        assert_eq!(base_block_device("/dev/mapper/vg-root"), None);
+    }
+}
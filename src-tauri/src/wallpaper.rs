@@ -0,0 +1,274 @@
+//! 壁纸设置：把图片设为桌面壁纸（fill / fit / tile 三种铺放方式），
+//! 以及可选的轮换壁纸调度器——用一条筛选查询（集合 / 智能专辑）喂图，
+//! 按设定间隔轮着换。
+//!
+//! 各平台走桌面原生接口：Windows 写注册表铺放方式后
+//! SystemParametersInfo 广播；macOS 用 System Events 脚本接口；
+//! Linux 走 gsettings（GNOME 系）。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::db::{self, AppDbPool};
+
+/// 轮换任务句柄（同一时间只有一个轮换在跑）
+static ROTATION: Lazy<Mutex<Option<tauri::async_runtime::JoinHandle<()>>>> =
+    Lazy::new(|| Mutex::new(None));
+/// 轮换循环的停止标志
+static ROTATION_STOP: Lazy<std::sync::Arc<AtomicBool>> =
+    Lazy::new(|| std::sync::Arc::new(AtomicBool::new(false)));
+
+/// 轮换状态（前端展示用）
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RotationStatus {
+    pub running: bool,
+    pub query: String,
+    pub interval_minutes: u64,
+    pub current: Option<String>,
+}
+
+static ROTATION_STATUS: Lazy<Mutex<RotationStatus>> =
+    Lazy::new(|| Mutex::new(RotationStatus::default()));
+
+fn validate_mode(mode: &str) -> Result<(), String> {
+    match mode {
+        "fill" | "fit" | "tile" => Ok(()),
+        other => Err(format!("不支持的铺放方式: {}（可选 fill / fit / tile）", other)),
+    }
+}
+
+#[cfg(windows)]
+fn apply_wallpaper(path: &str, mode: &str) -> Result<(), String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        SystemParametersInfoW, SPIF_SENDCHANGE, SPIF_UPDATEINIFILE, SPI_SETDESKWALLPAPER,
+    };
+
+    // 铺放方式写注册表：WallpaperStyle（fill=10 / fit=6 / tile=0），
+    // 平铺还要把 TileWallpaper 置 1
+    let (style, tile) = match mode {
+        "fill" => ("10", "0"),
+        "fit" => ("6", "0"),
+        _ => ("0", "1"),
+    };
+    for (value, data) in [("WallpaperStyle", style), ("TileWallpaper", tile)] {
+        let status = std::process::Command::new("reg")
+            .args([
+                "add",
+                "HKCU\\Control Panel\\Desktop",
+                "/v",
+                value,
+                "/t",
+                "REG_SZ",
+                "/d",
+                data,
+                "/f",
+            ])
+            .status()
+            .map_err(|e| format!("写注册表失败: {}", e))?;
+        if !status.success() {
+            return Err(format!("写注册表失败: {}", value));
+        }
+    }
+
+    let win_path = path.replace('/', "\\");
+    let wide: Vec<u16> = std::ffi::OsStr::new(&win_path)
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let ok = unsafe {
+        SystemParametersInfoW(
+            SPI_SETDESKWALLPAPER,
+            0,
+            wide.as_ptr() as *mut std::ffi::c_void,
+            SPIF_UPDATEINIFILE | SPIF_SENDCHANGE,
+        )
+    };
+    if ok == 0 {
+        return Err("SystemParametersInfo 设置壁纸失败".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn apply_wallpaper(path: &str, _mode: &str) -> Result<(), String> {
+    // System Events 的脚本接口不暴露铺放方式，mode 在 macOS 上尽力而为
+    let script = format!(
+        "tell application \"System Events\" to set picture of every desktop to \"{}\"",
+        path.replace('"', "\\\"")
+    );
+    let status = std::process::Command::new("osascript")
+        .args(["-e", &script])
+        .status()
+        .map_err(|e| format!("调用 osascript 失败: {}", e))?;
+    if !status.success() {
+        return Err("设置壁纸失败（osascript 返回错误）".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn apply_wallpaper(path: &str, mode: &str) -> Result<(), String> {
+    // GNOME 系桌面走 gsettings；fill→zoom / fit→scaled / tile→wallpaper
+    let options = match mode {
+        "fill" => "zoom",
+        "fit" => "scaled",
+        _ => "wallpaper",
+    };
+    let uri = format!("file://{}", path);
+    for (key, value) in [
+        ("picture-uri", uri.as_str()),
+        ("picture-uri-dark", uri.as_str()),
+        ("picture-options", options),
+    ] {
+        let status = std::process::Command::new("gsettings")
+            .args(["set", "org.gnome.desktop.background", key, value])
+            .status()
+            .map_err(|e| format!("调用 gsettings 失败: {}", e))?;
+        // picture-uri-dark 在旧版 GNOME 上不存在，失败不致命
+        if !status.success() && key != "picture-uri-dark" {
+            return Err(format!("设置 {} 失败", key));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+fn apply_wallpaper(_path: &str, _mode: &str) -> Result<(), String> {
+    Err("当前平台不支持设置壁纸".to_string())
+}
+
+/// 把图片设为桌面壁纸。mode 为 "fill"（充满）/ "fit"（适应）/ "tile"（平铺）
+#[tauri::command]
+pub async fn set_as_wallpaper(file_path: String, mode: Option<String>) -> Result<(), String> {
+    let mode = mode.unwrap_or_else(|| "fill".to_string());
+    validate_mode(&mode)?;
+    let path = db::normalize_path(&file_path);
+    if !std::path::Path::new(&path).is_file() {
+        return Err(format!("文件不存在: {}", path));
+    }
+    tokio::task::spawn_blocking(move || apply_wallpaper(&path, &mode))
+        .await
+        .map_err(|e| format!("设置壁纸任务失败: {}", e))?
+}
+
+/// 按筛选查询取轮换候选（与智能专辑同一套查询语法）
+fn rotation_candidates(
+    pool: &AppDbPool,
+    query: &str,
+    scope: Option<&str>,
+) -> Result<Vec<String>, String> {
+    let (mut where_clause, mut params) = crate::filter_query::compile(query)?;
+    if let Some(dir) = scope {
+        where_clause.push_str(" AND fi.path LIKE ?");
+        params.push(rusqlite::types::Value::Text(format!(
+            "{}%",
+            db::normalize_path(dir)
+        )));
+    }
+    let conn = pool.get_connection();
+    let sql = format!(
+        "SELECT fi.path FROM file_index fi
+         LEFT JOIN file_metadata fm ON fm.file_id = fi.file_id
+         WHERE {}
+         ORDER BY fi.path",
+        where_clause
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params_from_iter(params), |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+/// 启动轮换壁纸：每 interval_minutes 分钟从查询结果里换下一张。
+/// query 用与智能专辑相同的筛选语法（如 `tag:风景 rating:>=4`）
+#[tauri::command]
+pub async fn start_wallpaper_rotation(
+    query: String,
+    scope: Option<String>,
+    interval_minutes: Option<u64>,
+    mode: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let interval = interval_minutes.unwrap_or(30).clamp(1, 24 * 60);
+    let mode = mode.unwrap_or_else(|| "fill".to_string());
+    validate_mode(&mode)?;
+    // 先验证查询能编译、当前至少有一张候选
+    let pool = app.state::<AppDbPool>().inner().clone();
+    let initial = rotation_candidates(&pool, &query, scope.as_deref())?;
+    if initial.is_empty() {
+        return Err("查询没有匹配到任何图片".to_string());
+    }
+
+    stop_rotation_inner();
+    ROTATION_STOP.store(false, Ordering::SeqCst);
+    *ROTATION_STATUS.lock().unwrap() = RotationStatus {
+        running: true,
+        query: query.clone(),
+        interval_minutes: interval,
+        current: None,
+    };
+
+    let stop = ROTATION_STOP.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        let mut index = 0usize;
+        loop {
+            // 每轮重新查询，新入库的图自然进入轮换
+            let candidates = rotation_candidates(&pool, &query, scope.as_deref())
+                .unwrap_or_default();
+            if !candidates.is_empty() {
+                let path = candidates[index % candidates.len()].clone();
+                index = index.wrapping_add(1);
+                let apply_path = path.clone();
+                let apply_mode = mode.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    apply_wallpaper(&apply_path, &apply_mode)
+                })
+                .await;
+                match result {
+                    Ok(Ok(())) => {
+                        ROTATION_STATUS.lock().unwrap().current = Some(path);
+                    }
+                    Ok(Err(e)) => log::warn!("轮换壁纸设置失败: {}", e),
+                    Err(e) => log::warn!("轮换壁纸任务失败: {}", e),
+                }
+            }
+            // 分段睡眠，停止请求最多延迟 1 秒生效
+            for _ in 0..(interval * 60) {
+                if stop.load(Ordering::SeqCst) {
+                    return;
+                }
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+            }
+        }
+    });
+    *ROTATION.lock().unwrap() = Some(handle);
+    Ok(())
+}
+
+fn stop_rotation_inner() {
+    ROTATION_STOP.store(true, Ordering::SeqCst);
+    if let Some(handle) = ROTATION.lock().unwrap().take() {
+        handle.abort();
+    }
+    let mut status = ROTATION_STATUS.lock().unwrap();
+    status.running = false;
+}
+
+/// 停止轮换壁纸（当前壁纸保持不变）
+#[tauri::command]
+pub fn stop_wallpaper_rotation() {
+    stop_rotation_inner();
+}
+
+/// 查询轮换状态
+#[tauri::command]
+pub fn get_wallpaper_rotation_status() -> RotationStatus {
+    ROTATION_STATUS.lock().unwrap().clone()
+}
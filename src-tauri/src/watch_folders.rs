@@ -0,0 +1,396 @@
+//! 监视文件夹自动入库：按规则盯住外部目录（典型是 Downloads），
+//! 新出现的文件命中通配模式后移动 / 复制到库内目录并附加标签。
+//!
+//! 规则存 metadata.db 的 watch_rules 表（随库走），执行器是个轮询
+//! 循环：每 5 秒列一遍被监视目录，文件大小在相邻两轮之间不再变化
+//! 才算"落定"（避免把还在下载的半截文件搬走）。命中后走与网页采集
+//! 相同的入索引流程，并发 "watch-rule-applied" 事件让前端刷新。
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+
+use crate::db::{self, watch_rules::WatchRule, AppDbPool};
+
+/// 轮询间隔（秒）
+const POLL_INTERVAL_SECS: u64 = 5;
+
+/// 监视循环句柄与停止标志（与壁纸轮换同一套管理方式）
+static WATCHER: Lazy<Mutex<Option<tauri::async_runtime::JoinHandle<()>>>> =
+    Lazy::new(|| Mutex::new(None));
+static WATCHER_STOP: Lazy<std::sync::Arc<AtomicBool>> =
+    Lazy::new(|| std::sync::Arc::new(AtomicBool::new(false)));
+/// 启动以来命中的文件数（状态展示用）
+static APPLIED_COUNT: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WatcherStatus {
+    pub running: bool,
+    pub applied_count: u64,
+}
+
+/// 规则试跑的结果：是否命中 + 原因说明
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleTestResult {
+    pub matches: bool,
+    pub reason: String,
+}
+
+/// 文件名通配匹配：支持 * 与 ?，大小写不敏感
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pat: Vec<char> = pattern.to_lowercase().chars().collect();
+    let txt: Vec<char> = name.to_lowercase().chars().collect();
+    // 经典两指针 + 星号回溯
+    let (mut p, mut t) = (0usize, 0usize);
+    let (mut star, mut star_t) = (None::<usize>, 0usize);
+    while t < txt.len() {
+        if p < pat.len() && (pat[p] == '?' || pat[p] == txt[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pat.len() && pat[p] == '*' {
+            star = Some(p);
+            star_t = t;
+            p += 1;
+        } else if let Some(s) = star {
+            p = s + 1;
+            star_t += 1;
+            t = star_t;
+        } else {
+            return false;
+        }
+    }
+    while p < pat.len() && pat[p] == '*' {
+        p += 1;
+    }
+    p == pat.len()
+}
+
+/// 校验规则字段（保存与试跑共用）
+fn validate_rule(rule: &WatchRule) -> Result<(), String> {
+    if rule.pattern.trim().is_empty() {
+        return Err("通配模式不能为空".to_string());
+    }
+    if !matches!(rule.action.as_str(), "move" | "copy") {
+        return Err(format!("未知的动作: {}（可选 move / copy）", rule.action));
+    }
+    if rule.watch_dir.trim().is_empty() || rule.dest_dir.trim().is_empty() {
+        return Err("监视目录与目标目录不能为空".to_string());
+    }
+    Ok(())
+}
+
+/// 保存（新增或更新）一条监视规则
+#[tauri::command]
+pub fn save_watch_rule(
+    mut rule: WatchRule,
+    pool: tauri::State<AppDbPool>,
+) -> Result<WatchRule, String> {
+    validate_rule(&rule)?;
+    if rule.id.trim().is_empty() {
+        rule.id = format!("{:x}", md5::compute(format!(
+            "{}-{}-{}",
+            rule.watch_dir,
+            rule.pattern,
+            chrono::Utc::now().timestamp_millis()
+        )));
+    }
+    rule.updated_at = Some(chrono::Utc::now().timestamp());
+    let conn = pool.get_connection();
+    db::watch_rules::upsert_rule(&conn, &rule).map_err(|e| e.to_string())?;
+    Ok(rule)
+}
+
+/// 列出当前库的所有监视规则
+#[tauri::command]
+pub fn list_watch_rules(pool: tauri::State<AppDbPool>) -> Result<Vec<WatchRule>, String> {
+    let conn = pool.get_connection();
+    db::watch_rules::get_all_rules(&conn).map_err(|e| e.to_string())
+}
+
+/// 删除一条监视规则
+#[tauri::command]
+pub fn delete_watch_rule(id: String, pool: tauri::State<AppDbPool>) -> Result<(), String> {
+    let conn = pool.get_connection();
+    db::watch_rules::delete_rule(&conn, &id).map_err(|e| e.to_string())
+}
+
+/// 规则试跑：给一个示例路径，看这条规则会不会命中、为什么
+#[tauri::command]
+pub fn test_rule(rule: WatchRule, sample_path: String) -> Result<RuleTestResult, String> {
+    validate_rule(&rule)?;
+    let normalized = db::normalize_path(&sample_path);
+    let watch_dir = db::normalize_path(&rule.watch_dir);
+    let Some(name) = Path::new(&normalized).file_name().and_then(|n| n.to_str()) else {
+        return Err("示例路径没有文件名".to_string());
+    };
+
+    let parent = Path::new(&normalized)
+        .parent()
+        .map(|p| db::normalize_path(&p.to_string_lossy()))
+        .unwrap_or_default();
+    if parent != watch_dir {
+        return Ok(RuleTestResult {
+            matches: false,
+            reason: format!("文件不在监视目录 {} 下", watch_dir),
+        });
+    }
+    if !glob_match(&rule.pattern, name) {
+        return Ok(RuleTestResult {
+            matches: false,
+            reason: format!("文件名 {} 不匹配模式 {}", name, rule.pattern),
+        });
+    }
+    Ok(RuleTestResult {
+        matches: true,
+        reason: format!(
+            "命中：{} 到 {}{}",
+            if rule.action == "move" { "移动" } else { "复制" },
+            rule.dest_dir,
+            if rule.tags.is_empty() {
+                String::new()
+            } else {
+                format!("，标签 {}", rule.tags.join(", "))
+            }
+        ),
+    })
+}
+
+/// 把命中的文件搬进库并入索引（与网页采集的入库流程一致）
+fn apply_rule(rule: &WatchRule, src: &Path, app: &tauri::AppHandle) -> Result<String, String> {
+    let name = src
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("文件名无效")?;
+    std::fs::create_dir_all(&rule.dest_dir).map_err(|e| format!("创建目标目录失败: {}", e))?;
+    let dest = crate::generate_unique_file_path(&format!(
+        "{}/{}",
+        rule.dest_dir.trim_end_matches('/'),
+        name
+    ));
+
+    if rule.action == "move" {
+        // 跨卷 rename 会失败，退回复制 + 删除
+        if std::fs::rename(src, &dest).is_err() {
+            std::fs::copy(src, &dest).map_err(|e| format!("复制失败: {}", e))?;
+            std::fs::remove_file(src).map_err(|e| format!("删除源文件失败: {}", e))?;
+        }
+    } else {
+        std::fs::copy(src, &dest).map_err(|e| format!("复制失败: {}", e))?;
+    }
+
+    let normalized = db::normalize_path(&dest);
+    let ext = Path::new(&normalized)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    let file_id = db::generate_id(&normalized);
+    {
+        let pool = app.state::<AppDbPool>();
+        let mut conn = pool.get_connection();
+        let md = std::fs::metadata(&dest).ok();
+        let header = crate::probe_image_header(&normalized);
+        let entry = db::file_index::FileIndexEntry {
+            file_id: file_id.clone(),
+            parent_id: Some(db::generate_id(&db::normalize_path(&rule.dest_dir))),
+            path: normalized.clone(),
+            name: name.to_string(),
+            file_type: if crate::is_supported_image(&ext) {
+                "Image".to_string()
+            } else {
+                "Unknown".to_string()
+            },
+            size: md.as_ref().map(|m| m.len()).unwrap_or(0),
+            width: None,
+            height: None,
+            format: Some(ext),
+            bit_depth: header.bit_depth,
+            color_space: header.color_space,
+            has_alpha: header.has_alpha,
+            is_animated: header.is_animated,
+            created_at: chrono::Utc::now().timestamp(),
+            modified_at: md
+                .as_ref()
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0),
+        };
+        let _ = db::file_index::batch_upsert(&mut conn, &[entry]);
+
+        if !rule.tags.is_empty() {
+            let metadata = db::file_metadata::FileMetadata {
+                file_id: file_id.clone(),
+                path: normalized.clone(),
+                tags: Some(serde_json::json!(rule.tags)),
+                description: None,
+                source_url: None,
+                source_title: None,
+                source_author: None,
+                ai_data: None,
+                category: None,
+                rating: None,
+                notes: None,
+                sensitive: None,
+                updated_at: Some(chrono::Utc::now().timestamp()),
+            };
+            let _ = db::file_metadata::upsert_file_metadata(&conn, &metadata);
+        }
+
+        let _ = db::activity_log::record(
+            &conn,
+            "add",
+            &normalized,
+            Some(&format!("watch-rule:{}", rule.name)),
+            "background",
+        );
+    }
+
+    Ok(normalized)
+}
+
+/// 一轮轮询：对每条启用的规则列目录，落定的新文件套用规则。
+/// pending 记录上一轮看到的文件大小，settled 记录已处理过的路径
+fn poll_once(
+    rules: &[WatchRule],
+    pending: &mut HashMap<String, u64>,
+    settled: &mut std::collections::HashSet<String>,
+    app: &tauri::AppHandle,
+) {
+    for rule in rules.iter().filter(|r| r.enabled) {
+        let Ok(entries) = std::fs::read_dir(&rule.watch_dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if name.starts_with('.') || !glob_match(&rule.pattern, name) {
+                continue;
+            }
+            let key = db::normalize_path(&path.to_string_lossy());
+            if settled.contains(&key) {
+                continue;
+            }
+            let Ok(md) = entry.metadata() else { continue };
+            match pending.get(&key) {
+                // 相邻两轮大小一致 → 落定，执行规则
+                Some(&size) if size == md.len() => {
+                    pending.remove(&key);
+                    settled.insert(key.clone());
+                    match apply_rule(rule, &path, app) {
+                        Ok(dest) => {
+                            APPLIED_COUNT.fetch_add(1, Ordering::Relaxed);
+                            let _ = app.emit(
+                                "watch-rule-applied",
+                                serde_json::json!({
+                                    "rule": rule.name,
+                                    "source": key,
+                                    "dest": dest,
+                                }),
+                            );
+                        }
+                        Err(e) => log::warn!("监视规则 {} 处理 {} 失败: {}", rule.name, key, e),
+                    }
+                }
+                _ => {
+                    pending.insert(key, md.len());
+                }
+            }
+        }
+    }
+}
+
+/// 启动监视循环（重复调用会先停掉旧循环）
+#[tauri::command]
+pub async fn start_watch_rules(app: tauri::AppHandle) -> Result<(), String> {
+    stop_watcher_inner();
+    WATCHER_STOP.store(false, Ordering::SeqCst);
+
+    let stop = WATCHER_STOP.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        let mut pending = HashMap::new();
+        let mut settled = std::collections::HashSet::new();
+        loop {
+            if stop.load(Ordering::SeqCst) {
+                return;
+            }
+            // 每轮重读规则，规则改动即时生效
+            let rules = {
+                let pool = app.state::<AppDbPool>();
+                let conn = pool.get_connection();
+                db::watch_rules::get_all_rules(&conn).unwrap_or_default()
+            };
+            let app_clone = app.clone();
+            let mut p = std::mem::take(&mut pending);
+            let mut s = std::mem::take(&mut settled);
+            let result = tokio::task::spawn_blocking(move || {
+                poll_once(&rules, &mut p, &mut s, &app_clone);
+                (p, s)
+            })
+            .await;
+            if let Ok((p, s)) = result {
+                pending = p;
+                settled = s;
+            }
+            for _ in 0..POLL_INTERVAL_SECS {
+                if stop.load(Ordering::SeqCst) {
+                    return;
+                }
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+            }
+        }
+    });
+    *WATCHER.lock().unwrap() = Some(handle);
+    Ok(())
+}
+
+fn stop_watcher_inner() {
+    WATCHER_STOP.store(true, Ordering::SeqCst);
+    if let Some(handle) = WATCHER.lock().unwrap().take() {
+        handle.abort();
+    }
+}
+
+/// 停止监视循环
+#[tauri::command]
+pub fn stop_watch_rules() {
+    stop_watcher_inner();
+}
+
+/// 查询监视器状态
+#[tauri::command]
+pub fn get_watch_rules_status() -> WatcherStatus {
+    WatcherStatus {
+        running: WATCHER.lock().unwrap().is_some() && !WATCHER_STOP.load(Ordering::SeqCst),
+        applied_count: APPLIED_COUNT.load(Ordering::Relaxed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.png", "shot.png"));
+        assert!(glob_match("*.PNG", "shot.png"));
+        assert!(glob_match("IMG_????.jpg", "IMG_1234.jpg"));
+        assert!(!glob_match("IMG_????.jpg", "IMG_12345.jpg"));
+        assert!(glob_match("*", "anything.webp"));
+        assert!(!glob_match("*.png", "shot.jpeg"));
+        assert!(glob_match("a*b*c", "aXXbYYc"));
+        assert!(!glob_match("a*b*c", "aXXbYY"));
+    }
+}
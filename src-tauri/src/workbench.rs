@@ -0,0 +1,74 @@
+//! 工作台：跨多次搜索临时收集文件的后端选集。用户在不同搜索结果里
+//! 陆续"放入工作台"，攒齐后一次性做批量操作（移动 / 打标 / 导出）。
+//!
+//! 选集以 JSON 数组存在库设置表的 workbench 键下，随 metadata.db
+//! 持久化，重启后仍在；切换图库后各库互不影响。追加时去重并保持
+//! 放入顺序，列出时顺带过滤掉已不在索引中的条目。
+
+use crate::db::{self, AppDbPool};
+
+const SETTING_KEY: &str = "workbench";
+
+fn load_ids(conn: &rusqlite::Connection) -> Result<Vec<String>, String> {
+    let raw = db::get_library_setting(conn, SETTING_KEY).map_err(|e| e.to_string())?;
+    match raw {
+        Some(json) => serde_json::from_str(&json).map_err(|e| format!("工作台数据损坏: {}", e)),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn save_ids(conn: &rusqlite::Connection, ids: &[String]) -> Result<(), String> {
+    let json = serde_json::to_string(ids).map_err(|e| e.to_string())?;
+    db::set_library_setting(conn, SETTING_KEY, &json).map_err(|e| e.to_string())
+}
+
+/// 追加文件到工作台（已在选集中的跳过），返回追加后的选集大小
+#[tauri::command]
+pub fn workbench_add(file_ids: Vec<String>, pool: tauri::State<AppDbPool>) -> Result<usize, String> {
+    let conn = pool.get_connection();
+    let mut ids = load_ids(&conn)?;
+    for id in file_ids {
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    save_ids(&conn, &ids)?;
+    Ok(ids.len())
+}
+
+/// 从工作台移除指定文件，返回移除后的选集大小
+#[tauri::command]
+pub fn workbench_remove(file_ids: Vec<String>, pool: tauri::State<AppDbPool>) -> Result<usize, String> {
+    let conn = pool.get_connection();
+    let mut ids = load_ids(&conn)?;
+    ids.retain(|id| !file_ids.contains(id));
+    save_ids(&conn, &ids)?;
+    Ok(ids.len())
+}
+
+/// 列出工作台内容（按放入顺序）。已从索引中消失的文件
+/// （被删除 / 移出库）在这里顺带清掉，不会一直留着死条目
+#[tauri::command]
+pub fn workbench_list(pool: tauri::State<AppDbPool>) -> Result<Vec<String>, String> {
+    let conn = pool.get_connection();
+    let ids = load_ids(&conn)?;
+    let mut alive = Vec::with_capacity(ids.len());
+    for id in &ids {
+        match db::file_index::get_entry_by_id(&conn, id) {
+            Ok(Some(_)) => alive.push(id.clone()),
+            Ok(None) => {}
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+    if alive.len() != ids.len() {
+        save_ids(&conn, &alive)?;
+    }
+    Ok(alive)
+}
+
+/// 清空工作台
+#[tauri::command]
+pub fn workbench_clear(pool: tauri::State<AppDbPool>) -> Result<(), String> {
+    let conn = pool.get_connection();
+    save_ids(&conn, &[])
+}
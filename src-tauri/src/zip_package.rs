@@ -0,0 +1,307 @@
+//! 选集打包：把一组文件压成一个 ZIP（可选压缩前缩放 / 转格式，
+//! 可选附 manifest.json 清单），返回归档路径，邮件 / 网盘交付用。
+//!
+//! 没有现成的 zip 依赖，这里手写了一个最小的 ZIP 写入器：
+//! deflate 压缩（flate2）+ CRC-32（crc32fast），local header +
+//! central directory + EOCD 的标准布局。不做 zip64，单文件与
+//! 归档总量都按 4GB 以内处理——图片交付场景足够。
+
+use std::io::{Seek, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Deserialize;
+use tauri::{Emitter, Manager};
+
+use crate::db::{self, AppDbPool};
+
+/// 打包任务的单飞标志
+static ZIP_RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ZipOptions {
+    /// 压缩前把长边缩到该值（仅图片；None 不缩放）
+    #[serde(default)]
+    pub max_edge: Option<u32>,
+    /// 压缩前转格式："jpeg" | "png" | "webp"（仅图片；None 不转换）
+    #[serde(default)]
+    pub convert_format: Option<String>,
+    /// 是否在包里附 manifest.json 清单（默认附）
+    #[serde(default = "default_manifest")]
+    pub manifest: bool,
+}
+
+fn default_manifest() -> bool {
+    true
+}
+
+/// 一个 central directory 条目需要回填的信息
+struct ZipEntry {
+    name: String,
+    crc: u32,
+    compressed: u32,
+    uncompressed: u32,
+    offset: u32,
+    dos_time: u16,
+    dos_date: u16,
+}
+
+pub(crate) struct ZipWriter<W: Write + Seek> {
+    out: W,
+    entries: Vec<ZipEntry>,
+}
+
+impl<W: Write + Seek> ZipWriter<W> {
+    pub(crate) fn new(out: W) -> Self {
+        Self { out, entries: Vec::new() }
+    }
+
+    /// 追加一个 deflate 压缩的文件条目
+    pub(crate) fn add_file(&mut self, name: &str, data: &[u8], mtime: Option<chrono::NaiveDateTime>) -> Result<(), String> {
+        let offset = self.out.stream_position().map_err(|e| e.to_string())? as u32;
+        let crc = crc32fast::hash(data);
+        let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).map_err(|e| e.to_string())?;
+        let compressed = encoder.finish().map_err(|e| e.to_string())?;
+
+        // MS-DOS 时间戳（2 秒精度）
+        let (dos_time, dos_date) = mtime
+            .map(|t| {
+                use chrono::{Datelike, Timelike};
+                let date = (((t.year().clamp(1980, 2107) - 1980) as u16) << 9)
+                    | ((t.month() as u16) << 5)
+                    | t.day() as u16;
+                let time = ((t.hour() as u16) << 11)
+                    | ((t.minute() as u16) << 5)
+                    | (t.second() as u16 / 2);
+                (time, date)
+            })
+            .unwrap_or((0, 0x21));
+
+        let name_bytes = name.as_bytes();
+        let mut header = Vec::with_capacity(30 + name_bytes.len());
+        header.extend_from_slice(&0x04034b50u32.to_le_bytes()); // local header 签名
+        header.extend_from_slice(&20u16.to_le_bytes()); // 需要版本 2.0
+        header.extend_from_slice(&(1u16 << 11).to_le_bytes()); // UTF-8 文件名
+        header.extend_from_slice(&8u16.to_le_bytes()); // deflate
+        header.extend_from_slice(&dos_time.to_le_bytes());
+        header.extend_from_slice(&dos_date.to_le_bytes());
+        header.extend_from_slice(&crc.to_le_bytes());
+        header.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+        header.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        header.extend_from_slice(&(name_bytes.len() as u16).to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes()); // 无扩展字段
+        header.extend_from_slice(name_bytes);
+        self.out.write_all(&header).map_err(|e| e.to_string())?;
+        self.out.write_all(&compressed).map_err(|e| e.to_string())?;
+
+        self.entries.push(ZipEntry {
+            name: name.to_string(),
+            crc,
+            compressed: compressed.len() as u32,
+            uncompressed: data.len() as u32,
+            offset,
+            dos_time,
+            dos_date,
+        });
+        Ok(())
+    }
+
+    /// 写 central directory 与 EOCD 并结束
+    pub(crate) fn finish(mut self) -> Result<(), String> {
+        let cd_start = self.out.stream_position().map_err(|e| e.to_string())? as u32;
+        for e in &self.entries {
+            let name_bytes = e.name.as_bytes();
+            let mut rec = Vec::with_capacity(46 + name_bytes.len());
+            rec.extend_from_slice(&0x02014b50u32.to_le_bytes());
+            rec.extend_from_slice(&20u16.to_le_bytes()); // 写入版本
+            rec.extend_from_slice(&20u16.to_le_bytes()); // 需要版本
+            rec.extend_from_slice(&(1u16 << 11).to_le_bytes());
+            rec.extend_from_slice(&8u16.to_le_bytes());
+            rec.extend_from_slice(&e.dos_time.to_le_bytes());
+            rec.extend_from_slice(&e.dos_date.to_le_bytes());
+            rec.extend_from_slice(&e.crc.to_le_bytes());
+            rec.extend_from_slice(&e.compressed.to_le_bytes());
+            rec.extend_from_slice(&e.uncompressed.to_le_bytes());
+            rec.extend_from_slice(&(name_bytes.len() as u16).to_le_bytes());
+            rec.extend_from_slice(&[0u8; 12]); // 扩展/注释长度、磁盘号、内外部属性
+            rec.extend_from_slice(&e.offset.to_le_bytes());
+            rec.extend_from_slice(name_bytes);
+            self.out.write_all(&rec).map_err(|e2| e2.to_string())?;
+        }
+        let cd_end = self.out.stream_position().map_err(|e| e.to_string())? as u32;
+        let count = self.entries.len() as u16;
+        let mut eocd = Vec::with_capacity(22);
+        eocd.extend_from_slice(&0x06054b50u32.to_le_bytes());
+        eocd.extend_from_slice(&[0u8; 4]); // 磁盘号
+        eocd.extend_from_slice(&count.to_le_bytes());
+        eocd.extend_from_slice(&count.to_le_bytes());
+        eocd.extend_from_slice(&(cd_end - cd_start).to_le_bytes());
+        eocd.extend_from_slice(&cd_start.to_le_bytes());
+        eocd.extend_from_slice(&0u16.to_le_bytes()); // 无注释
+        self.out.write_all(&eocd).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+/// 需要时对图片做缩放 / 转格式，返回（最终文件名, 字节）
+fn prepare_entry(path: &str, name: &str, options: &ZipOptions) -> Result<(String, Vec<u8>), String> {
+    let wants_convert = options.convert_format.is_some() || options.max_edge.is_some();
+    let ext = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    if !wants_convert || !crate::is_supported_image(&ext) {
+        return Ok((
+            name.to_string(),
+            std::fs::read(path).map_err(|e| format!("读取 {} 失败: {}", path, e))?,
+        ));
+    }
+
+    let mut img = image::open(path).map_err(|e| format!("解码 {} 失败: {}", path, e))?;
+    if let Some(edge) = options.max_edge {
+        let edge = edge.clamp(16, 16384);
+        if img.width().max(img.height()) > edge {
+            img = img.thumbnail(edge, edge);
+        }
+    }
+    let (out_ext, fmt) = match options.convert_format.as_deref() {
+        Some("jpeg") | Some("jpg") => ("jpg", image::ImageFormat::Jpeg),
+        Some("png") => ("png", image::ImageFormat::Png),
+        Some("webp") => ("webp", image::ImageFormat::WebP),
+        Some(other) => return Err(format!("不支持的转换格式: {}", other)),
+        None => (ext.as_str(), image::ImageFormat::from_extension(&ext).unwrap_or(image::ImageFormat::Png)),
+    };
+    // JPEG 不支持 alpha
+    if fmt == image::ImageFormat::Jpeg && img.color().has_alpha() {
+        img = image::DynamicImage::ImageRgb8(img.to_rgb8());
+    }
+    let mut bytes = Vec::new();
+    img.write_to(&mut std::io::Cursor::new(&mut bytes), fmt)
+        .map_err(|e| format!("编码 {} 失败: {}", path, e))?;
+    let stem = Path::new(name).file_stem().and_then(|s| s.to_str()).unwrap_or("file");
+    Ok((format!("{}.{}", stem, out_ext), bytes))
+}
+
+/// 把选集打包成 ZIP，返回归档路径。dest 可以是目录（自动命名）
+/// 或以 .zip 结尾的完整路径。打包过程发 "zip-progress" 事件
+#[tauri::command]
+pub async fn package_as_zip(
+    file_ids: Vec<String>,
+    dest: String,
+    options: Option<ZipOptions>,
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    if ZIP_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("已有打包任务在运行".to_string());
+    }
+    let pool = app.state::<AppDbPool>().inner().clone();
+    let options = options.unwrap_or_default();
+
+    let result = tokio::task::spawn_blocking(move || {
+        let entries: Vec<(String, String, u64)> = {
+            let conn = pool.get_connection();
+            let mut list = Vec::with_capacity(file_ids.len());
+            for id in &file_ids {
+                if let Some(entry) = db::file_index::get_entry_by_id(&conn, id).map_err(|e| e.to_string())? {
+                    if entry.file_type != "Folder" {
+                        list.push((entry.path, entry.name, entry.size));
+                    }
+                }
+            }
+            list
+        };
+        if entries.is_empty() {
+            return Err("选中的文件都不在索引中".to_string());
+        }
+
+        let dest_path = Path::new(&dest);
+        let out_path = if dest.to_lowercase().ends_with(".zip") {
+            dest_path.to_path_buf()
+        } else {
+            dest_path.join(format!(
+                "aurora_package_{}.zip",
+                chrono::Local::now().format("%Y%m%d_%H%M%S")
+            ))
+        };
+        if let Some(parent) = out_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("创建目标目录失败: {}", e))?;
+        }
+        let file = std::fs::File::create(&out_path).map_err(|e| format!("创建归档失败: {}", e))?;
+        let mut zip = ZipWriter::new(std::io::BufWriter::new(file));
+
+        let total = entries.len();
+        let mut manifest = Vec::with_capacity(total);
+        let mut used_names = std::collections::HashSet::new();
+        for (i, (path, name, size)) in entries.iter().enumerate() {
+            let (mut entry_name, bytes) = prepare_entry(path, name, &options)?;
+            // 同名条目加序号避让
+            let mut n = 1;
+            while !used_names.insert(entry_name.clone()) {
+                let p = Path::new(&entry_name);
+                let stem = p.file_stem().and_then(|s| s.to_str()).unwrap_or("file");
+                let ext = p.extension().and_then(|s| s.to_str()).unwrap_or("");
+                entry_name = format!("{}_{}.{}", stem, n, ext);
+                n += 1;
+            }
+            let mtime = std::fs::metadata(path)
+                .ok()
+                .and_then(|m| m.modified().ok())
+                .map(|t| chrono::DateTime::<chrono::Local>::from(t).naive_local());
+            zip.add_file(&entry_name, &bytes, mtime)?;
+            manifest.push(serde_json::json!({
+                "name": entry_name,
+                "sourcePath": path,
+                "sourceSize": size,
+                "packagedSize": bytes.len(),
+            }));
+            if (i + 1).is_multiple_of(5) || i + 1 == total {
+                let _ = app.emit("zip-progress", serde_json::json!({ "done": i + 1, "total": total }));
+            }
+        }
+
+        if options.manifest {
+            let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+            zip.add_file("manifest.json", json.as_bytes(), Some(chrono::Local::now().naive_local()))?;
+        }
+        zip.finish()?;
+        Ok(out_path.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("打包任务失败: {}", e))
+    .and_then(|r| r);
+
+    ZIP_RUNNING.store(false, Ordering::SeqCst);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zip_writer_layout() {
+        let mut buf = std::io::Cursor::new(Vec::new());
+        {
+            let mut zip = ZipWriter::new(&mut buf);
+            zip.add_file("a.txt", b"hello zip", None).unwrap();
+            zip.add_file("b.txt", b"second entry", None).unwrap();
+            zip.finish().unwrap();
+        }
+        let bytes = buf.into_inner();
+        // local header 签名开头，EOCD 在结尾 22 字节处
+        assert_eq!(&bytes[0..4], &0x04034b50u32.to_le_bytes());
+        let eocd = &bytes[bytes.len() - 22..];
+        assert_eq!(&eocd[0..4], &0x06054b50u32.to_le_bytes());
+        // EOCD 里的条目数为 2
+        assert_eq!(u16::from_le_bytes([eocd[10], eocd[11]]), 2);
+        // central directory 偏移处应是 CD 签名
+        let cd_start = u32::from_le_bytes([eocd[16], eocd[17], eocd[18], eocd[19]]) as usize;
+        assert_eq!(&bytes[cd_start..cd_start + 4], &0x02014b50u32.to_le_bytes());
+    }
+}